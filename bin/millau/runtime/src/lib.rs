@@ -35,7 +35,7 @@ pub mod rialto_messages;
 use crate::rialto_messages::{ToRialtoMessagePayload, WithRialtoMessageBridge};
 
 use bridge_runtime_common::messages::{source::estimate_message_dispatch_and_delivery_fee, MessageBridge};
-use codec::Decode;
+use pallet_bridge_messages::WeightInfoExt as MessagesWeightInfoExt;
 use pallet_grandpa::{fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use pallet_transaction_payment::{FeeDetails, RuntimeDispatchInfo};
 use sp_api::impl_runtime_apis;
@@ -62,9 +62,11 @@ pub use frame_support::{
 
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_balances::Event as BalancesEvent;
 pub use pallet_bridge_grandpa::Call as BridgeGrandpaRialtoCall;
 pub use pallet_bridge_grandpa::Call as BridgeGrandpaWestendCall;
 pub use pallet_bridge_messages::Call as MessagesCall;
+pub use pallet_grandpa::Call as GrandpaCall;
 pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
 
@@ -211,13 +213,18 @@ impl pallet_aura::Config for Runtime {
 impl pallet_bridge_dispatch::Config for Runtime {
 	type Event = Event;
 	type MessageId = (bp_messages::LaneId, bp_messages::MessageNonce);
+	type LaneId = bp_messages::LaneId;
+	type MessageIdToLaneId = pallet_bridge_dispatch::LaneIdOfMessageId;
 	type Call = Call;
 	type CallFilter = ();
 	type EncodedCall = crate::rialto_messages::FromRialtoEncodedCall;
+	type SpecVersionGraceWindow = ();
+	type OutdatedCallDecoder = ();
 	type SourceChainAccountId = bp_rialto::AccountId;
 	type TargetChainAccountPublic = MultiSigner;
 	type TargetChainSignature = MultiSignature;
 	type AccountIdConverter = bp_millau::AccountIdConverter;
+	type WeightInfo = pallet_bridge_dispatch::weights::RialtoWeight<Runtime>;
 }
 
 impl pallet_grandpa::Config for Runtime {
@@ -317,6 +324,9 @@ parameter_types! {
 	// Assuming the worst case of every header being finalized, we will keep headers for at least a
 	// week.
 	pub const HeadersToKeep: u32 = 7 * bp_millau::DAYS as u32;
+	// Headers that may be used to confirm delivery of messages are kept for at most the same
+	// period as regular headers.
+	pub const MaxGuardedHeaders: u32 = 7 * bp_millau::DAYS as u32;
 }
 
 pub type RialtoGrandpaInstance = ();
@@ -324,6 +334,12 @@ impl pallet_bridge_grandpa::Config for Runtime {
 	type BridgedChain = bp_rialto::Rialto;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
+	type HeadersPruningGuard = bridge_runtime_common::messages::source::UnconfirmedMessagesHeadersPruningGuard<
+		Runtime,
+		WithRialtoMessagesInstance,
+	>;
+	type MaxGuardedHeaders = MaxGuardedHeaders;
+	type OwnerOrigin = frame_system::EnsureRoot<AccountId>;
 
 	// TODO [#391]: Use weights generated for the Millau runtime instead of Rialto ones.
 	type WeightInfo = pallet_bridge_grandpa::weights::RialtoWeight<Runtime>;
@@ -334,6 +350,9 @@ impl pallet_bridge_grandpa::Config<WestendGrandpaInstance> for Runtime {
 	type BridgedChain = bp_westend::Westend;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
+	type HeadersPruningGuard = ();
+	type MaxGuardedHeaders = MaxGuardedHeaders;
+	type OwnerOrigin = frame_system::EnsureRoot<AccountId>;
 
 	// TODO [#391]: Use weights generated for the Millau runtime instead of Rialto ones.
 	type WeightInfo = pallet_bridge_grandpa::weights::RialtoWeight<Runtime>;
@@ -347,10 +366,25 @@ parameter_types! {
 		bp_millau::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE;
 	pub const MaxUnconfirmedMessagesAtInboundLane: bp_messages::MessageNonce =
 		bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE;
+	pub const MaxLaneRelayers: u32 = 16;
+	pub const MaxQueuedMessagesAtInboundLane: bp_messages::MessageNonce =
+		bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE;
 	// `IdentityFee` is used by Millau => we may use weight directly
 	pub const GetDeliveryConfirmationTransactionFee: Balance =
 		bp_millau::MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT as _;
 	pub const RootAccountForPayments: Option<AccountId> = None;
+	// in addition to `GetDeliveryConfirmationTransactionFee`, that only covers confirmation transaction
+	// cost, confirmation relayer receives part of messages fees
+	pub const ConfirmationRelayerRewardShare: Perbill = Perbill::from_percent(10);
+	// relayers bids are converted into lanes fees once per session
+	pub const FeeAuctionPeriod: BlockNumber = bp_millau::SESSION_LENGTH;
+	// there's no treasury => fees of pruned messages, that can't be refunded to submitters, stay at the relayers fund
+	pub const UndeliveredMessagesFeeRecipient: Option<AccountId> = None;
+	// wallets are expected to retry transactions within hours, not days
+	pub const IdempotencyKeyLifetime: BlockNumber = bp_millau::HOURS;
+	// senders should back off when half of the bridged chain inbound lane capacity is occupied
+	pub const LaneCongestionThreshold: bp_messages::MessageNonce =
+		bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE / 2;
 }
 
 /// Instance of the messages pallet used to relay messages to/from Rialto chain.
@@ -361,9 +395,13 @@ impl pallet_bridge_messages::Config<WithRialtoMessagesInstance> for Runtime {
 	// TODO: https://github.com/paritytech/parity-bridges-common/issues/390
 	type WeightInfo = pallet_bridge_messages::weights::RialtoWeight<Runtime>;
 	type Parameter = rialto_messages::MillauToRialtoMessagesParameter;
+	// there are no collectives in this runtime => only root and pallet owner may manage the pallet
+	type OwnerOrigin = frame_system::EnsureRoot<AccountId>;
 	type MaxMessagesToPruneAtOnce = MaxMessagesToPruneAtOnce;
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type MaxLaneRelayers = MaxLaneRelayers;
+	type MaxQueuedMessagesAtInboundLane = MaxQueuedMessagesAtInboundLane;
 
 	type OutboundPayload = crate::rialto_messages::ToRialtoMessagePayload;
 	type OutboundMessageFee = Balance;
@@ -382,11 +420,53 @@ impl pallet_bridge_messages::Config<WithRialtoMessagesInstance> for Runtime {
 		GetDeliveryConfirmationTransactionFee,
 		RootAccountForPayments,
 	>;
+	type OnDeliveryConfirmed = ();
+	type ConfirmationRelayerRewardShare = ConfirmationRelayerRewardShare;
+	type FeeAuctionPeriod = FeeAuctionPeriod;
+	type UndeliveredMessagesFeeRecipient = UndeliveredMessagesFeeRecipient;
+	type IdempotencyKeyLifetime = IdempotencyKeyLifetime;
+	type LaneCongestionThreshold = LaneCongestionThreshold;
+	type BridgedHeaderNumber = bp_rialto::BlockNumber;
+	type BestFinalizedBridgedHeaderNumber =
+		bridge_runtime_common::messages::source::BestFinalizedBridgedHeaderNumber<Runtime, RialtoGrandpaInstance>;
 
 	type SourceHeaderChain = crate::rialto_messages::Rialto;
 	type MessageDispatch = crate::rialto_messages::FromRialtoMessageDispatch;
 }
 
+parameter_types! {
+	pub const MaxGeneratedMessagesPerBlock: u32 = 64;
+	pub const MaxGeneratedMessagePayloadSize: u32 = 16 * 1024;
+	pub SendGeneratedMessageWeight: Weight =
+		<pallet_bridge_messages::weights::RialtoWeight<Runtime> as MessagesWeightInfoExt>::send_message_weight(
+			&bp_runtime::PreComputedSize(MaxGeneratedMessagePayloadSize::get() as usize),
+		);
+}
+
+impl pallet_bridge_message_generator::Config for Runtime {
+	type Event = Event;
+	type OutboundPayload = crate::rialto_messages::ToRialtoMessagePayload;
+	type OutboundMessageFee = Balance;
+	type MessagesBridge = pallet_bridge_messages::Pallet<Runtime, WithRialtoMessagesInstance>;
+	type PayloadBuilder = crate::rialto_messages::ToRialtoGeneratedMessageBuilder;
+	type MaxMessagesPerBlock = MaxGeneratedMessagesPerBlock;
+	type MaxPayloadSize = MaxGeneratedMessagePayloadSize;
+	type SendMessageWeight = SendGeneratedMessageWeight;
+}
+
+parameter_types! {
+	pub const MaxBridgeNameLength: u32 = 64;
+	pub const MaxPalletsPerBridge: u32 = 8;
+	pub const MaxLanesPerBridge: u32 = 16;
+}
+
+impl pallet_bridge_registry::Config for Runtime {
+	type Event = Event;
+	type MaxNameLength = MaxBridgeNameLength;
+	type MaxPalletsPerBridge = MaxPalletsPerBridge;
+	type MaxLanesPerBridge = MaxLanesPerBridge;
+}
+
 construct_runtime!(
 	pub enum Runtime where
 		Block = Block,
@@ -394,7 +474,7 @@ construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic
 	{
 		BridgeRialtoMessages: pallet_bridge_messages::{Pallet, Call, Storage, Event<T>},
-		BridgeDispatch: pallet_bridge_dispatch::{Pallet, Event<T>},
+		BridgeDispatch: pallet_bridge_dispatch::{Pallet, Call, Event<T>},
 		BridgeRialtoGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage},
 		BridgeWestendGrandpa: pallet_bridge_grandpa::<Instance1>::{Pallet, Call, Config<T>, Storage},
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
@@ -407,6 +487,8 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
 		ShiftSessionManager: pallet_shift_session_manager::{Pallet},
+		MessageGenerator: pallet_bridge_message_generator::{Pallet, Call, Storage, Event},
+		BridgeRegistry: pallet_bridge_registry::{Pallet, Call, Storage, Event},
 	}
 );
 
@@ -420,6 +502,12 @@ pub type Block = generic::Block<Header, UncheckedExtrinsic>;
 pub type SignedBlock = generic::SignedBlock<Block>;
 /// BlockId type as expected by this runtime.
 pub type BlockId = generic::BlockId<Block>;
+/// Signed extension that allows relayers to be reimbursed for messages delivery confirmation transactions.
+pub type RefundConfirmationTransactionFee =
+	bridge_runtime_common::messages_extension::RefundConfirmationTransactionFee<Runtime, WithRialtoMessagesInstance>;
+/// Signed extension that refunds messages delivery transactions fees from the relayers pot.
+pub type RefundDeliveryTransactionFee =
+	bridge_runtime_common::messages_extension::RefundDeliveryTransactionFee<Runtime, WithRialtoMessagesInstance>;
 /// The SignedExtension to the basic transaction logic.
 pub type SignedExtra = (
 	frame_system::CheckSpecVersion<Runtime>,
@@ -429,6 +517,8 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	RefundConfirmationTransactionFee,
+	RefundDeliveryTransactionFee,
 );
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
@@ -569,13 +659,36 @@ impl_runtime_apis! {
 	}
 
 	impl bp_rialto::RialtoFinalityApi<Block> for Runtime {
-		fn best_finalized() -> (bp_rialto::BlockNumber, bp_rialto::Hash) {
-			let header = BridgeRialtoGrandpa::best_finalized();
-			(header.number, header.hash())
+		fn best_finalized(
+			instance: bp_runtime::ChainId,
+		) -> Result<(bp_rialto::BlockNumber, bp_rialto::Hash), bp_runtime::UnknownBridgeInstance> {
+			let header = match instance {
+				bp_runtime::RIALTO_CHAIN_ID => BridgeRialtoGrandpa::best_finalized(),
+				_ => return bp_runtime::unknown_bridge_instance(instance),
+			};
+			Ok((header.number, header.hash()))
 		}
 
-		fn is_known_header(hash: bp_rialto::Hash) -> bool {
-			BridgeRialtoGrandpa::is_known_header(hash)
+		fn is_known_header(
+			instance: bp_runtime::ChainId,
+			hash: bp_rialto::Hash,
+		) -> Result<bool, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoGrandpa::is_known_header(hash)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+
+		fn finality_pallet_state(
+			instance: bp_runtime::ChainId,
+		) -> Result<
+			Option<bp_header_chain::FinalityPalletState<bp_rialto::BlockNumber, bp_rialto::Hash>>,
+			bp_runtime::UnknownBridgeInstance,
+		> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoGrandpa::finality_pallet_state()),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 	}
 
@@ -588,60 +701,178 @@ impl_runtime_apis! {
 		fn is_known_header(hash: bp_westend::Hash) -> bool {
 			BridgeWestendGrandpa::is_known_header(hash)
 		}
+
+		fn finality_pallet_state(
+		) -> Option<bp_header_chain::FinalityPalletState<bp_westend::BlockNumber, bp_westend::Hash>> {
+			BridgeWestendGrandpa::finality_pallet_state()
+		}
 	}
 
 	impl bp_rialto::ToRialtoOutboundLaneApi<Block, Balance, ToRialtoMessagePayload> for Runtime {
 		fn estimate_message_delivery_and_dispatch_fee(
-			_lane_id: bp_messages::LaneId,
+			instance: bp_runtime::ChainId,
+			lane_id: bp_messages::LaneId,
 			payload: ToRialtoMessagePayload,
-		) -> Option<Balance> {
-			estimate_message_dispatch_and_delivery_fee::<WithRialtoMessageBridge>(
-				&payload,
-				WithRialtoMessageBridge::RELAYER_FEE_PERCENT,
-			).ok()
+		) -> Result<Option<Balance>, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(estimate_message_dispatch_and_delivery_fee::<WithRialtoMessageBridge>(
+					&lane_id,
+					&payload,
+					WithRialtoMessageBridge::RELAYER_FEE_PERCENT,
+				).ok()),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 
 		fn message_details(
+			instance: bp_runtime::ChainId,
 			lane: bp_messages::LaneId,
 			begin: bp_messages::MessageNonce,
 			end: bp_messages::MessageNonce,
-		) -> Vec<bp_messages::MessageDetails<Balance>> {
-			(begin..=end).filter_map(|nonce| {
+		) -> Result<Vec<bp_messages::MessageDetails<Balance>>, bp_runtime::UnknownBridgeInstance> {
+			if instance != bp_runtime::RIALTO_CHAIN_ID {
+				return bp_runtime::unknown_bridge_instance(instance);
+			}
+
+			Ok((begin..=end).filter_map(|nonce| {
 				let message_data = BridgeRialtoMessages::outbound_message_data(lane, nonce)?;
-				let decoded_payload = rialto_messages::ToRialtoMessagePayload::decode(
-					&mut &message_data.payload[..]
-				).ok()?;
+				// payload of the cancelled message is replaced with empty (noop) payload
+				let (dispatch_weight, dispatch_fee_payment) = if message_data.payload.is_empty() {
+					(0, bp_runtime::DispatchFeePayment::AtSourceChain)
+				} else {
+					let payload = bp_messages::VersionedMessagePayload::decode_payload::<
+						rialto_messages::ToRialtoMessagePayload,
+					>(&message_data.payload)
+					.ok()?;
+					(payload.weight, payload.dispatch_fee_payment)
+				};
 				Some(bp_messages::MessageDetails {
 					nonce,
-					dispatch_weight: decoded_payload.weight,
+					dispatch_weight,
 					size: message_data.payload.len() as _,
 					delivery_and_dispatch_fee: message_data.fee,
-					// TODO: include dispatch fee type (https://github.com/paritytech/parity-bridges-common/pull/911)
+					dispatch_fee_payment,
 				})
 			})
-			.collect()
+			.collect())
+		}
+
+		fn latest_received_nonce(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::MessageNonce, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::outbound_latest_received_nonce(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+
+		fn latest_generated_nonce(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::MessageNonce, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::outbound_latest_generated_nonce(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+	}
+
+	impl bp_messages::OutboundMessagesApi<Block> for Runtime {
+		fn outbound_messages(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+			begin: bp_messages::MessageNonce,
+			end: bp_messages::MessageNonce,
+			include_outbound_lane_state: bool,
+		) -> Result<bp_messages::OutboundMessagesWithStorageKeys, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::outbound_messages_with_storage_keys(
+					lane,
+					begin,
+					end,
+					include_outbound_lane_state,
+				)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+	}
+
+	impl bp_messages::InboundLaneApi<Block, bp_rialto::AccountId> for Runtime {
+		fn inbound_lane_data(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::InboundLaneData<bp_rialto::AccountId>, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::inbound_lane_data(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
+	}
 
-		fn latest_received_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
-			BridgeRialtoMessages::outbound_latest_received_nonce(lane)
+	impl bp_messages::LaneStatisticsApi<Block, Balance> for Runtime {
+		fn lane_statistics(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::LaneStatistics<Balance>, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::lane_statistics(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
+	}
 
-		fn latest_generated_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
-			BridgeRialtoMessages::outbound_latest_generated_nonce(lane)
+	impl bp_messages::LaneCongestionApi<Block> for Runtime {
+		fn lane_congestion(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::LaneCongestion, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::lane_congestion(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 	}
 
 	impl bp_rialto::FromRialtoInboundLaneApi<Block> for Runtime {
-		fn latest_received_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
-			BridgeRialtoMessages::inbound_latest_received_nonce(lane)
+		fn latest_received_nonce(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::MessageNonce, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::inbound_latest_received_nonce(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 
-		fn latest_confirmed_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
-			BridgeRialtoMessages::inbound_latest_confirmed_nonce(lane)
+		fn latest_confirmed_nonce(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::MessageNonce, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::inbound_latest_confirmed_nonce(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+
+		fn unrewarded_relayers_state(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::UnrewardedRelayersState, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::RIALTO_CHAIN_ID => Ok(BridgeRialtoMessages::inbound_unrewarded_relayers_state(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+	}
+
+	impl bp_bridge_registry::BridgeRegistryApi<Block, BlockNumber> for Runtime {
+		fn bridges() -> Vec<(bp_runtime::ChainId, bp_bridge_registry::BridgeInfo<BlockNumber>)> {
+			BridgeRegistry::bridges()
 		}
 
-		fn unrewarded_relayers_state(lane: bp_messages::LaneId) -> bp_messages::UnrewardedRelayersState {
-			BridgeRialtoMessages::inbound_unrewarded_relayers_state(lane)
+		fn bridge(bridge_id: bp_runtime::ChainId) -> Option<bp_bridge_registry::BridgeInfo<BlockNumber>> {
+			BridgeRegistry::bridge(bridge_id)
 		}
 	}
 }
@@ -699,6 +930,7 @@ mod tests {
 		let max_incoming_inbound_lane_data_proof_size = bp_messages::InboundLaneData::<()>::encoded_size_hint(
 			bp_millau::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
 			bp_rialto::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE as _,
+			bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE as _,
 		)
 		.unwrap_or(u32::MAX);
 		pallet_bridge_messages::ensure_able_to_receive_confirmation::<Weights>(
@@ -21,10 +21,13 @@ use crate::Runtime;
 use bp_messages::{
 	source_chain::TargetHeaderChain,
 	target_chain::{ProvedMessages, SourceHeaderChain},
-	InboundLaneData, LaneId, Message, MessageNonce, Parameter as MessagesParameter,
+	InboundLaneData, LaneId, Message, MessageNonce, Parameter as MessagesParameter, ProofRejectionReason,
+};
+use bp_runtime::{ChainId, DispatchFeePayment, MILLAU_CHAIN_ID, RIALTO_CHAIN_ID};
+use bridge_runtime_common::{
+	conversion_rate::{ConversionRateSource, ParameterConversionRate},
+	messages::{self, MessageBridge, MessageTransaction},
 };
-use bp_runtime::{ChainId, MILLAU_CHAIN_ID, RIALTO_CHAIN_ID};
-use bridge_runtime_common::messages::{self, MessageBridge, MessageTransaction};
 use codec::{Decode, Encode};
 use frame_support::{
 	parameter_types,
@@ -32,7 +35,7 @@ use frame_support::{
 	RuntimeDebug,
 };
 use sp_runtime::{traits::Zero, FixedPointNumber, FixedU128};
-use sp_std::{convert::TryFrom, ops::RangeInclusive};
+use sp_std::{convert::TryFrom, ops::RangeInclusive, prelude::*};
 
 /// Initial value of `RialtoToMillauConversionRate` parameter.
 pub const INITIAL_RIALTO_TO_MILLAU_CONVERSION_RATE: FixedU128 = FixedU128::from_inner(FixedU128::DIV);
@@ -42,6 +45,12 @@ parameter_types! {
 	pub storage RialtoToMillauConversionRate: FixedU128 = INITIAL_RIALTO_TO_MILLAU_CONVERSION_RATE;
 }
 
+/// Source of the Rialto to Millau conversion rate, used by the message fee calculation.
+///
+/// This runtime has no liquidity pools, so the rate is fed by the pallet owner. Runtimes with DEX
+/// pallet may use `bridge_runtime_common::conversion_rate::PoolConversionRate` instead.
+pub type RialtoToMillauConversionRateSource = ParameterConversionRate<RialtoToMillauConversionRate>;
+
 /// Message payload for Millau -> Rialto messages.
 pub type ToRialtoMessagePayload = messages::source::FromThisChainMessagePayload<WithRialtoMessageBridge>;
 
@@ -60,10 +69,14 @@ type FromRialtoMessagesProof = messages::target::FromBridgedChainMessagesProof<b
 /// Messages delivery proof for Millau -> Rialto messages.
 type ToRialtoMessagesDeliveryProof = messages::source::FromBridgedChainMessagesDeliveryProof<bp_rialto::Hash>;
 
+/// Messages delivery proof of several lanes for Millau -> Rialto messages.
+type ToRialtoMessagesDeliveryBatchProof = messages::source::FromBridgedChainMessagesDeliveryBatchProof<bp_rialto::Hash>;
+
 /// Call-dispatch based message dispatch for Rialto -> Millau messages.
 pub type FromRialtoMessageDispatch = messages::target::FromBridgedChainMessageDispatch<
 	WithRialtoMessageBridge,
 	crate::Runtime,
+	pallet_balances::Pallet<Runtime>,
 	pallet_bridge_dispatch::DefaultInstance,
 >;
 
@@ -78,7 +91,8 @@ impl MessageBridge for WithRialtoMessageBridge {
 	type BridgedChain = Rialto;
 
 	fn bridged_balance_to_this_balance(bridged_balance: bp_rialto::Balance) -> bp_millau::Balance {
-		bp_millau::Balance::try_from(RialtoToMillauConversionRate::get().saturating_mul_int(bridged_balance))
+		let conversion_rate = RialtoToMillauConversionRateSource::conversion_rate();
+		bp_millau::Balance::try_from(conversion_rate.saturating_mul_int(bridged_balance))
 			.unwrap_or(bp_millau::Balance::MAX)
 	}
 }
@@ -104,7 +118,9 @@ impl messages::ThisChainWithMessages for Millau {
 	type Call = crate::Call;
 
 	fn is_outbound_lane_enabled(lane: &LaneId) -> bool {
-		*lane == [0, 0, 0, 0] || *lane == [0, 0, 0, 1]
+		*lane == [0, 0, 0, 0]
+			|| *lane == [0, 0, 0, 1]
+			|| pallet_bridge_messages::Pallet::<Runtime, crate::WithRialtoMessagesInstance>::is_lane_registered(lane)
 	}
 
 	fn maximal_pending_messages_at_outbound_lane() -> MessageNonce {
@@ -112,9 +128,12 @@ impl messages::ThisChainWithMessages for Millau {
 	}
 
 	fn estimate_delivery_confirmation_transaction() -> MessageTransaction<Weight> {
-		let inbound_data_size =
-			InboundLaneData::<bp_millau::AccountId>::encoded_size_hint(bp_millau::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE, 1)
-				.unwrap_or(u32::MAX);
+		let inbound_data_size = InboundLaneData::<bp_millau::AccountId>::encoded_size_hint(
+			bp_millau::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
+			1,
+			0,
+		)
+		.unwrap_or(u32::MAX);
 
 		MessageTransaction {
 			dispatch_weight: bp_millau::MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT,
@@ -134,6 +153,11 @@ impl messages::ThisChainWithMessages for Millau {
 			transaction,
 		)
 	}
+
+	fn apply_lane_fee_multiplier(lane: &LaneId, fee: bp_millau::Balance) -> Option<bp_millau::Balance> {
+		pallet_bridge_messages::Pallet::<Runtime, crate::WithRialtoMessagesInstance>::lane_fee_multiplier(lane)
+			.checked_mul_int(fee)
+	}
 }
 
 /// Rialto chain from message lane point of view.
@@ -202,15 +226,20 @@ impl messages::BridgedChainWithMessages for Rialto {
 }
 
 impl TargetHeaderChain<ToRialtoMessagePayload, bp_rialto::AccountId> for Rialto {
-	type Error = &'static str;
+	type Error = ProofRejectionReason;
 	// The proof is:
 	// - hash of the header this proof has been created with;
 	// - the storage proof or one or several keys;
 	// - id of the lane we prove state of.
 	type MessagesDeliveryProof = ToRialtoMessagesDeliveryProof;
+	// The proof is:
+	// - hash of the header this proof has been created with;
+	// - the storage proof of several keys;
+	// - ids of lanes we prove states of.
+	type MessagesDeliveryBatchProof = ToRialtoMessagesDeliveryBatchProof;
 
 	fn verify_message(payload: &ToRialtoMessagePayload) -> Result<(), Self::Error> {
-		messages::source::verify_chain_message::<WithRialtoMessageBridge>(payload)
+		messages::source::verify_chain_message::<WithRialtoMessageBridge>(payload).map_err(Into::into)
 	}
 
 	fn verify_messages_delivery_proof(
@@ -220,10 +249,31 @@ impl TargetHeaderChain<ToRialtoMessagePayload, bp_rialto::AccountId> for Rialto
 			proof,
 		)
 	}
+
+	fn verify_messages_delivery_proof_with_traversed_nodes(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<((LaneId, InboundLaneData<bp_millau::AccountId>), Option<u32>), Self::Error> {
+		messages::source::verify_messages_delivery_proof_with_traversed_nodes::<
+			WithRialtoMessageBridge,
+			Runtime,
+			crate::RialtoGrandpaInstance,
+		>(proof)
+		.map(|(lane, traversed_nodes)| (lane, Some(traversed_nodes)))
+	}
+
+	fn verify_messages_delivery_batch_proof(
+		proof: Self::MessagesDeliveryBatchProof,
+	) -> Result<Vec<(LaneId, InboundLaneData<bp_millau::AccountId>)>, Self::Error> {
+		messages::source::verify_messages_delivery_batch_proof::<
+			WithRialtoMessageBridge,
+			Runtime,
+			crate::RialtoGrandpaInstance,
+		>(proof)
+	}
 }
 
 impl SourceHeaderChain<bp_rialto::Balance> for Rialto {
-	type Error = &'static str;
+	type Error = ProofRejectionReason;
 	// The proof is:
 	// - hash of the header this proof has been created with;
 	// - the storage proof or one or several keys;
@@ -240,6 +290,41 @@ impl SourceHeaderChain<bp_rialto::Balance> for Rialto {
 			messages_count,
 		)
 	}
+
+	fn verify_messages_proof_with_traversed_nodes(
+		proof: Self::MessagesProof,
+		messages_count: u32,
+	) -> Result<(ProvedMessages<Message<bp_rialto::Balance>>, Option<u32>), Self::Error> {
+		messages::target::verify_messages_proof_with_traversed_nodes::<
+			WithRialtoMessageBridge,
+			Runtime,
+			crate::RialtoGrandpaInstance,
+		>(proof, messages_count)
+		.map(|(messages, traversed_nodes)| (messages, Some(traversed_nodes)))
+	}
+}
+
+/// Builder of Millau -> Rialto messages that are sent by the message generator pallet.
+///
+/// Generated messages are only used to load the bridge. Their call is filled with zero bytes, so
+/// it can't be decoded (and dispatched) at Rialto. Since spec version of Rialto runtime is unknown
+/// here, it is set to zero.
+pub struct ToRialtoGeneratedMessageBuilder;
+
+impl pallet_bridge_message_generator::MessagePayloadBuilder<ToRialtoMessagePayload>
+	for ToRialtoGeneratedMessageBuilder
+{
+	fn build_payload(payload_size: u32, dispatch_weight: Weight) -> ToRialtoMessagePayload {
+		let fee_pot =
+			pallet_bridge_messages::Pallet::<Runtime, crate::WithRialtoMessagesInstance>::pallets_fee_pot_account_id();
+		ToRialtoMessagePayload {
+			spec_version: 0,
+			weight: dispatch_weight,
+			origin: bp_message_dispatch::CallOrigin::SourceAccount(fee_pot),
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			call: sp_std::vec![0; payload_size as usize],
+		}
+	}
 }
 
 /// Millau -> Rialto message lane pallet parameters.
@@ -214,6 +214,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let rpc_extensions_builder = {
 		use sc_finality_grandpa::FinalityProofProvider as GrandpaFinalityProofProvider;
 
+		use pallet_bridge_messages_rpc::{Messages, MessagesApi};
 		use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 		use sc_finality_grandpa_rpc::{GrandpaApi, GrandpaRpcHandler};
 		use sc_rpc::DenyUnsafe;
@@ -240,6 +241,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 			io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
 				client.clone(),
 			)));
+			io.extend_with(MessagesApi::to_delegate(Messages::new(client.clone())));
 			io.extend_with(GrandpaApi::to_delegate(GrandpaRpcHandler::new(
 				shared_authority_set.clone(),
 				shared_voter_state.clone(),
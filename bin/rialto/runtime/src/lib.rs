@@ -41,7 +41,7 @@ pub mod rialto_poa;
 use crate::millau_messages::{ToMillauMessagePayload, WithMillauMessageBridge};
 
 use bridge_runtime_common::messages::{source::estimate_message_dispatch_and_delivery_fee, MessageBridge};
-use codec::Decode;
+use pallet_bridge_messages::WeightInfoExt as MessagesWeightInfoExt;
 use pallet_grandpa::{fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use pallet_transaction_payment::{FeeDetails, RuntimeDispatchInfo};
 use sp_api::impl_runtime_apis;
@@ -68,10 +68,12 @@ pub use frame_support::{
 
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_balances::Event as BalancesEvent;
 pub use pallet_bridge_currency_exchange::Call as BridgeCurrencyExchangeCall;
 pub use pallet_bridge_eth_poa::Call as BridgeEthPoACall;
 pub use pallet_bridge_grandpa::Call as BridgeGrandpaMillauCall;
 pub use pallet_bridge_messages::Call as MessagesCall;
+pub use pallet_grandpa::Call as GrandpaCall;
 pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
 
@@ -218,6 +220,7 @@ impl pallet_aura::Config for Runtime {
 
 type RialtoPoA = pallet_bridge_eth_poa::Instance1;
 impl pallet_bridge_eth_poa::Config<RialtoPoA> for Runtime {
+	type EngineConfiguration = rialto_poa::BridgeEngineConfiguration;
 	type AuraConfiguration = rialto_poa::BridgeAuraConfiguration;
 	type FinalityVotesCachingInterval = rialto_poa::FinalityVotesCachingInterval;
 	type ValidatorsConfiguration = rialto_poa::BridgeValidatorsConfiguration;
@@ -228,6 +231,7 @@ impl pallet_bridge_eth_poa::Config<RialtoPoA> for Runtime {
 
 type Kovan = pallet_bridge_eth_poa::Instance2;
 impl pallet_bridge_eth_poa::Config<Kovan> for Runtime {
+	type EngineConfiguration = kovan::BridgeEngineConfiguration;
 	type AuraConfiguration = kovan::BridgeAuraConfiguration;
 	type FinalityVotesCachingInterval = kovan::FinalityVotesCachingInterval;
 	type ValidatorsConfiguration = kovan::BridgeValidatorsConfiguration;
@@ -261,13 +265,18 @@ impl pallet_bridge_currency_exchange::Config<KovanCurrencyExchange> for Runtime
 impl pallet_bridge_dispatch::Config for Runtime {
 	type Event = Event;
 	type MessageId = (bp_messages::LaneId, bp_messages::MessageNonce);
+	type LaneId = bp_messages::LaneId;
+	type MessageIdToLaneId = pallet_bridge_dispatch::LaneIdOfMessageId;
 	type Call = Call;
 	type CallFilter = ();
 	type EncodedCall = crate::millau_messages::FromMillauEncodedCall;
+	type SpecVersionGraceWindow = ();
+	type OutdatedCallDecoder = ();
 	type SourceChainAccountId = bp_millau::AccountId;
 	type TargetChainAccountPublic = MultiSigner;
 	type TargetChainSignature = MultiSignature;
 	type AccountIdConverter = bp_rialto::AccountIdConverter;
+	type WeightInfo = pallet_bridge_dispatch::weights::RialtoWeight<Runtime>;
 }
 
 pub struct DepositInto;
@@ -439,11 +448,23 @@ parameter_types! {
 	pub const HeadersToKeep: u32 = 7 * bp_rialto::DAYS as u32;
 }
 
+parameter_types! {
+	/// Headers that may be used to confirm delivery of messages are kept for at most the same
+	/// period as regular headers.
+	pub MaxGuardedHeaders: u32 = HeadersToKeep::get();
+}
+
 pub type MillauGrandpaInstance = ();
 impl pallet_bridge_grandpa::Config for Runtime {
 	type BridgedChain = bp_millau::Millau;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
+	type HeadersPruningGuard = bridge_runtime_common::messages::source::UnconfirmedMessagesHeadersPruningGuard<
+		Runtime,
+		WithMillauMessagesInstance,
+	>;
+	type MaxGuardedHeaders = MaxGuardedHeaders;
+	type OwnerOrigin = frame_system::EnsureRoot<AccountId>;
 	type WeightInfo = pallet_bridge_grandpa::weights::RialtoWeight<Runtime>;
 }
 
@@ -455,10 +476,25 @@ parameter_types! {
 		bp_rialto::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE;
 	pub const MaxUnconfirmedMessagesAtInboundLane: bp_messages::MessageNonce =
 		bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE;
+	pub const MaxLaneRelayers: u32 = 16;
+	pub const MaxQueuedMessagesAtInboundLane: bp_messages::MessageNonce =
+		bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE;
 	// `IdentityFee` is used by Rialto => we may use weight directly
 	pub const GetDeliveryConfirmationTransactionFee: Balance =
 		bp_rialto::MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT as _;
 	pub const RootAccountForPayments: Option<AccountId> = None;
+	// in addition to `GetDeliveryConfirmationTransactionFee`, that only covers confirmation transaction
+	// cost, confirmation relayer receives part of messages fees
+	pub const ConfirmationRelayerRewardShare: Perbill = Perbill::from_percent(10);
+	// relayers bids are converted into lanes fees once per session
+	pub const FeeAuctionPeriod: BlockNumber = bp_rialto::SESSION_LENGTH;
+	// there's no treasury => fees of pruned messages, that can't be refunded to submitters, stay at the relayers fund
+	pub const UndeliveredMessagesFeeRecipient: Option<AccountId> = None;
+	// wallets are expected to retry transactions within hours, not days
+	pub const IdempotencyKeyLifetime: BlockNumber = bp_rialto::HOURS;
+	// senders should back off when half of the bridged chain inbound lane capacity is occupied
+	pub const LaneCongestionThreshold: bp_messages::MessageNonce =
+		bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE / 2;
 }
 
 /// Instance of the messages pallet used to relay messages to/from Millau chain.
@@ -468,9 +504,13 @@ impl pallet_bridge_messages::Config<WithMillauMessagesInstance> for Runtime {
 	type Event = Event;
 	type WeightInfo = pallet_bridge_messages::weights::RialtoWeight<Runtime>;
 	type Parameter = millau_messages::RialtoToMillauMessagesParameter;
+	// there are no collectives in this runtime => only root and pallet owner may manage the pallet
+	type OwnerOrigin = frame_system::EnsureRoot<AccountId>;
 	type MaxMessagesToPruneAtOnce = MaxMessagesToPruneAtOnce;
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type MaxLaneRelayers = MaxLaneRelayers;
+	type MaxQueuedMessagesAtInboundLane = MaxQueuedMessagesAtInboundLane;
 
 	type OutboundPayload = crate::millau_messages::ToMillauMessagePayload;
 	type OutboundMessageFee = Balance;
@@ -489,11 +529,53 @@ impl pallet_bridge_messages::Config<WithMillauMessagesInstance> for Runtime {
 		GetDeliveryConfirmationTransactionFee,
 		RootAccountForPayments,
 	>;
+	type OnDeliveryConfirmed = ();
+	type ConfirmationRelayerRewardShare = ConfirmationRelayerRewardShare;
+	type FeeAuctionPeriod = FeeAuctionPeriod;
+	type UndeliveredMessagesFeeRecipient = UndeliveredMessagesFeeRecipient;
+	type IdempotencyKeyLifetime = IdempotencyKeyLifetime;
+	type LaneCongestionThreshold = LaneCongestionThreshold;
+	type BridgedHeaderNumber = bp_millau::BlockNumber;
+	type BestFinalizedBridgedHeaderNumber =
+		bridge_runtime_common::messages::source::BestFinalizedBridgedHeaderNumber<Runtime, MillauGrandpaInstance>;
 
 	type SourceHeaderChain = crate::millau_messages::Millau;
 	type MessageDispatch = crate::millau_messages::FromMillauMessageDispatch;
 }
 
+parameter_types! {
+	pub const MaxGeneratedMessagesPerBlock: u32 = 64;
+	pub const MaxGeneratedMessagePayloadSize: u32 = 16 * 1024;
+	pub SendGeneratedMessageWeight: Weight =
+		<pallet_bridge_messages::weights::RialtoWeight<Runtime> as MessagesWeightInfoExt>::send_message_weight(
+			&bp_runtime::PreComputedSize(MaxGeneratedMessagePayloadSize::get() as usize),
+		);
+}
+
+impl pallet_bridge_message_generator::Config for Runtime {
+	type Event = Event;
+	type OutboundPayload = crate::millau_messages::ToMillauMessagePayload;
+	type OutboundMessageFee = Balance;
+	type MessagesBridge = pallet_bridge_messages::Pallet<Runtime, WithMillauMessagesInstance>;
+	type PayloadBuilder = crate::millau_messages::ToMillauGeneratedMessageBuilder;
+	type MaxMessagesPerBlock = MaxGeneratedMessagesPerBlock;
+	type MaxPayloadSize = MaxGeneratedMessagePayloadSize;
+	type SendMessageWeight = SendGeneratedMessageWeight;
+}
+
+parameter_types! {
+	pub const MaxBridgeNameLength: u32 = 64;
+	pub const MaxPalletsPerBridge: u32 = 8;
+	pub const MaxLanesPerBridge: u32 = 16;
+}
+
+impl pallet_bridge_registry::Config for Runtime {
+	type Event = Event;
+	type MaxNameLength = MaxBridgeNameLength;
+	type MaxPalletsPerBridge = MaxPalletsPerBridge;
+	type MaxLanesPerBridge = MaxLanesPerBridge;
+}
+
 construct_runtime!(
 	pub enum Runtime where
 		Block = Block,
@@ -505,7 +587,7 @@ construct_runtime!(
 		BridgeRialtoCurrencyExchange: pallet_bridge_currency_exchange::<Instance1>::{Pallet, Call},
 		BridgeKovanCurrencyExchange: pallet_bridge_currency_exchange::<Instance2>::{Pallet, Call},
 		BridgeMillauGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage},
-		BridgeDispatch: pallet_bridge_dispatch::{Pallet, Event<T>},
+		BridgeDispatch: pallet_bridge_dispatch::{Pallet, Call, Event<T>},
 		BridgeMillauMessages: pallet_bridge_messages::{Pallet, Call, Storage, Event<T>},
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Call, Storage},
@@ -517,6 +599,8 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
 		ShiftSessionManager: pallet_shift_session_manager::{Pallet},
+		MessageGenerator: pallet_bridge_message_generator::{Pallet, Call, Storage, Event},
+		BridgeRegistry: pallet_bridge_registry::{Pallet, Call, Storage, Event},
 	}
 );
 
@@ -530,6 +614,12 @@ pub type Block = generic::Block<Header, UncheckedExtrinsic>;
 pub type SignedBlock = generic::SignedBlock<Block>;
 /// BlockId type as expected by this runtime.
 pub type BlockId = generic::BlockId<Block>;
+/// Signed extension that allows relayers to be reimbursed for messages delivery confirmation transactions.
+pub type RefundConfirmationTransactionFee =
+	bridge_runtime_common::messages_extension::RefundConfirmationTransactionFee<Runtime, WithMillauMessagesInstance>;
+/// Signed extension that refunds messages delivery transactions fees from the relayers pot.
+pub type RefundDeliveryTransactionFee =
+	bridge_runtime_common::messages_extension::RefundDeliveryTransactionFee<Runtime, WithMillauMessagesInstance>;
 /// The SignedExtension to the basic transaction logic.
 pub type SignedExtra = (
 	frame_system::CheckSpecVersion<Runtime>,
@@ -539,6 +629,8 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	RefundConfirmationTransactionFee,
+	RefundDeliveryTransactionFee,
 );
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
@@ -639,13 +731,36 @@ impl_runtime_apis! {
 	}
 
 	impl bp_millau::MillauFinalityApi<Block> for Runtime {
-		fn best_finalized() -> (bp_millau::BlockNumber, bp_millau::Hash) {
-			let header = BridgeMillauGrandpa::best_finalized();
-			(header.number, header.hash())
+		fn best_finalized(
+			instance: bp_runtime::ChainId,
+		) -> Result<(bp_millau::BlockNumber, bp_millau::Hash), bp_runtime::UnknownBridgeInstance> {
+			let header = match instance {
+				bp_runtime::MILLAU_CHAIN_ID => BridgeMillauGrandpa::best_finalized(),
+				_ => return bp_runtime::unknown_bridge_instance(instance),
+			};
+			Ok((header.number, header.hash()))
+		}
+
+		fn is_known_header(
+			instance: bp_runtime::ChainId,
+			hash: bp_millau::Hash,
+		) -> Result<bool, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauGrandpa::is_known_header(hash)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 
-		fn is_known_header(hash: bp_millau::Hash) -> bool {
-			BridgeMillauGrandpa::is_known_header(hash)
+		fn finality_pallet_state(
+			instance: bp_runtime::ChainId,
+		) -> Result<
+			Option<bp_header_chain::FinalityPalletState<bp_millau::BlockNumber, bp_millau::Hash>>,
+			bp_runtime::UnknownBridgeInstance,
+		> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauGrandpa::finality_pallet_state()),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 	}
 
@@ -743,56 +858,169 @@ impl_runtime_apis! {
 
 	impl bp_millau::ToMillauOutboundLaneApi<Block, Balance, ToMillauMessagePayload> for Runtime {
 		fn estimate_message_delivery_and_dispatch_fee(
-			_lane_id: bp_messages::LaneId,
+			instance: bp_runtime::ChainId,
+			lane_id: bp_messages::LaneId,
 			payload: ToMillauMessagePayload,
-		) -> Option<Balance> {
-			estimate_message_dispatch_and_delivery_fee::<WithMillauMessageBridge>(
-				&payload,
-				WithMillauMessageBridge::RELAYER_FEE_PERCENT,
-			).ok()
+		) -> Result<Option<Balance>, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(estimate_message_dispatch_and_delivery_fee::<WithMillauMessageBridge>(
+					&lane_id,
+					&payload,
+					WithMillauMessageBridge::RELAYER_FEE_PERCENT,
+				).ok()),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 
 		fn message_details(
+			instance: bp_runtime::ChainId,
 			lane: bp_messages::LaneId,
 			begin: bp_messages::MessageNonce,
 			end: bp_messages::MessageNonce,
-		) -> Vec<bp_messages::MessageDetails<Balance>> {
-			(begin..=end).filter_map(|nonce| {
+		) -> Result<Vec<bp_messages::MessageDetails<Balance>>, bp_runtime::UnknownBridgeInstance> {
+			if instance != bp_runtime::MILLAU_CHAIN_ID {
+				return bp_runtime::unknown_bridge_instance(instance);
+			}
+
+			Ok((begin..=end).filter_map(|nonce| {
 				let message_data = BridgeMillauMessages::outbound_message_data(lane, nonce)?;
-				let decoded_payload = millau_messages::ToMillauMessagePayload::decode(
-					&mut &message_data.payload[..]
-				).ok()?;
+				// payload of the cancelled message is replaced with empty (noop) payload
+				let (dispatch_weight, dispatch_fee_payment) = if message_data.payload.is_empty() {
+					(0, bp_runtime::DispatchFeePayment::AtSourceChain)
+				} else {
+					let payload = bp_messages::VersionedMessagePayload::decode_payload::<
+						millau_messages::ToMillauMessagePayload,
+					>(&message_data.payload)
+					.ok()?;
+					(payload.weight, payload.dispatch_fee_payment)
+				};
 				Some(bp_messages::MessageDetails {
 					nonce,
-					dispatch_weight: decoded_payload.weight,
+					dispatch_weight,
 					size: message_data.payload.len() as _,
 					delivery_and_dispatch_fee: message_data.fee,
-					// TODO: include dispatch fee type (https://github.com/paritytech/parity-bridges-common/pull/911)
+					dispatch_fee_payment,
 				})
 			})
-			.collect()
+			.collect())
+		}
+
+		fn latest_received_nonce(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::MessageNonce, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::outbound_latest_received_nonce(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 
-		fn latest_received_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
-			BridgeMillauMessages::outbound_latest_received_nonce(lane)
+		fn latest_generated_nonce(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::MessageNonce, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::outbound_latest_generated_nonce(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
+	}
 
-		fn latest_generated_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
-			BridgeMillauMessages::outbound_latest_generated_nonce(lane)
+	impl bp_messages::OutboundMessagesApi<Block> for Runtime {
+		fn outbound_messages(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+			begin: bp_messages::MessageNonce,
+			end: bp_messages::MessageNonce,
+			include_outbound_lane_state: bool,
+		) -> Result<bp_messages::OutboundMessagesWithStorageKeys, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::outbound_messages_with_storage_keys(
+					lane,
+					begin,
+					end,
+					include_outbound_lane_state,
+				)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+	}
+
+	impl bp_messages::InboundLaneApi<Block, bp_millau::AccountId> for Runtime {
+		fn inbound_lane_data(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::InboundLaneData<bp_millau::AccountId>, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::inbound_lane_data(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+	}
+
+	impl bp_messages::LaneStatisticsApi<Block, Balance> for Runtime {
+		fn lane_statistics(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::LaneStatistics<Balance>, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::lane_statistics(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+	}
+
+	impl bp_messages::LaneCongestionApi<Block> for Runtime {
+		fn lane_congestion(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::LaneCongestion, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::lane_congestion(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
 	}
 
 	impl bp_millau::FromMillauInboundLaneApi<Block> for Runtime {
-		fn latest_received_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
-			BridgeMillauMessages::inbound_latest_received_nonce(lane)
+		fn latest_received_nonce(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::MessageNonce, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::inbound_latest_received_nonce(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+
+		fn latest_confirmed_nonce(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::MessageNonce, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::inbound_latest_confirmed_nonce(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
+		}
+
+		fn unrewarded_relayers_state(
+			instance: bp_runtime::ChainId,
+			lane: bp_messages::LaneId,
+		) -> Result<bp_messages::UnrewardedRelayersState, bp_runtime::UnknownBridgeInstance> {
+			match instance {
+				bp_runtime::MILLAU_CHAIN_ID => Ok(BridgeMillauMessages::inbound_unrewarded_relayers_state(lane)),
+				_ => bp_runtime::unknown_bridge_instance(instance),
+			}
 		}
+	}
 
-		fn latest_confirmed_nonce(lane: bp_messages::LaneId) -> bp_messages::MessageNonce {
-			BridgeMillauMessages::inbound_latest_confirmed_nonce(lane)
+	impl bp_bridge_registry::BridgeRegistryApi<Block, BlockNumber> for Runtime {
+		fn bridges() -> Vec<(bp_runtime::ChainId, bp_bridge_registry::BridgeInfo<BlockNumber>)> {
+			BridgeRegistry::bridges()
 		}
 
-		fn unrewarded_relayers_state(lane: bp_messages::LaneId) -> bp_messages::UnrewardedRelayersState {
-			BridgeMillauMessages::inbound_unrewarded_relayers_state(lane)
+		fn bridge(bridge_id: bp_runtime::ChainId) -> Option<bp_bridge_registry::BridgeInfo<BlockNumber>> {
+			BridgeRegistry::bridge(bridge_id)
 		}
 	}
 
@@ -902,6 +1130,7 @@ impl_runtime_apis! {
 						spec_version: 0,
 						weight: params.size as _,
 						origin: dispatch_origin,
+						dispatch_fee_payment: bp_runtime::DispatchFeePayment::AtSourceChain,
 						call: message_payload,
 					};
 					(message, pallet_bridge_messages::benchmarking::MESSAGE_FEE.into())
@@ -976,6 +1205,7 @@ impl_runtime_apis! {
 								rialto_public,
 								rialto_signature,
 							),
+							dispatch_fee_payment: bp_runtime::DispatchFeePayment::AtSourceChain,
 							call: call.encode(),
 						}.encode(),
 					)
@@ -1023,6 +1253,7 @@ impl_runtime_apis! {
 				MessagesBench::<Runtime, WithMillauMessagesInstance>
 			);
 			add_benchmark!(params, batches, pallet_bridge_grandpa, BridgeMillauGrandpa);
+			add_benchmark!(params, batches, pallet_bridge_dispatch, BridgeDispatch);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
@@ -1118,6 +1349,7 @@ mod tests {
 		let max_incoming_inbound_lane_data_proof_size = bp_messages::InboundLaneData::<()>::encoded_size_hint(
 			bp_rialto::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
 			bp_millau::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE as _,
+			bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE as _,
 		)
 		.unwrap_or(u32::MAX);
 		pallet_bridge_messages::ensure_able_to_receive_confirmation::<Weights>(
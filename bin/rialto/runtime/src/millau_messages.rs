@@ -21,10 +21,13 @@ use crate::Runtime;
 use bp_messages::{
 	source_chain::TargetHeaderChain,
 	target_chain::{ProvedMessages, SourceHeaderChain},
-	InboundLaneData, LaneId, Message, MessageNonce, Parameter as MessagesParameter,
+	InboundLaneData, LaneId, Message, MessageNonce, Parameter as MessagesParameter, ProofRejectionReason,
+};
+use bp_runtime::{ChainId, DispatchFeePayment, MILLAU_CHAIN_ID, RIALTO_CHAIN_ID};
+use bridge_runtime_common::{
+	conversion_rate::{ConversionRateSource, ParameterConversionRate},
+	messages::{self, MessageBridge, MessageTransaction},
 };
-use bp_runtime::{ChainId, MILLAU_CHAIN_ID, RIALTO_CHAIN_ID};
-use bridge_runtime_common::messages::{self, MessageBridge, MessageTransaction};
 use codec::{Decode, Encode};
 use frame_support::{
 	parameter_types,
@@ -32,7 +35,7 @@ use frame_support::{
 	RuntimeDebug,
 };
 use sp_runtime::{traits::Zero, FixedPointNumber, FixedU128};
-use sp_std::{convert::TryFrom, ops::RangeInclusive};
+use sp_std::{convert::TryFrom, ops::RangeInclusive, prelude::*};
 
 /// Initial value of `MillauToRialtoConversionRate` parameter.
 pub const INITIAL_MILLAU_TO_RIALTO_CONVERSION_RATE: FixedU128 = FixedU128::from_inner(FixedU128::DIV);
@@ -42,6 +45,12 @@ parameter_types! {
 	pub storage MillauToRialtoConversionRate: FixedU128 = INITIAL_MILLAU_TO_RIALTO_CONVERSION_RATE;
 }
 
+/// Source of the Millau to Rialto conversion rate, used by the message fee calculation.
+///
+/// This runtime has no liquidity pools, so the rate is fed by the pallet owner. Runtimes with DEX
+/// pallet may use `bridge_runtime_common::conversion_rate::PoolConversionRate` instead.
+pub type MillauToRialtoConversionRateSource = ParameterConversionRate<MillauToRialtoConversionRate>;
+
 /// Message payload for Rialto -> Millau messages.
 pub type ToMillauMessagePayload = messages::source::FromThisChainMessagePayload<WithMillauMessageBridge>;
 
@@ -58,6 +67,7 @@ pub type FromMillauEncodedCall = messages::target::FromBridgedChainEncodedMessag
 pub type FromMillauMessageDispatch = messages::target::FromBridgedChainMessageDispatch<
 	WithMillauMessageBridge,
 	crate::Runtime,
+	pallet_balances::Pallet<Runtime>,
 	pallet_bridge_dispatch::DefaultInstance,
 >;
 
@@ -67,6 +77,10 @@ pub type FromMillauMessagesProof = messages::target::FromBridgedChainMessagesPro
 /// Messages delivery proof for Rialto -> Millau messages.
 pub type ToMillauMessagesDeliveryProof = messages::source::FromBridgedChainMessagesDeliveryProof<bp_millau::Hash>;
 
+/// Messages delivery proof of several lanes for Rialto -> Millau messages.
+pub type ToMillauMessagesDeliveryBatchProof =
+	messages::source::FromBridgedChainMessagesDeliveryBatchProof<bp_millau::Hash>;
+
 /// Millau <-> Rialto message bridge.
 #[derive(RuntimeDebug, Clone, Copy)]
 pub struct WithMillauMessageBridge;
@@ -78,7 +92,8 @@ impl MessageBridge for WithMillauMessageBridge {
 	type BridgedChain = Millau;
 
 	fn bridged_balance_to_this_balance(bridged_balance: bp_millau::Balance) -> bp_rialto::Balance {
-		bp_rialto::Balance::try_from(MillauToRialtoConversionRate::get().saturating_mul_int(bridged_balance))
+		let conversion_rate = MillauToRialtoConversionRateSource::conversion_rate();
+		bp_rialto::Balance::try_from(conversion_rate.saturating_mul_int(bridged_balance))
 			.unwrap_or(bp_rialto::Balance::MAX)
 	}
 }
@@ -104,7 +119,9 @@ impl messages::ThisChainWithMessages for Rialto {
 	type Call = crate::Call;
 
 	fn is_outbound_lane_enabled(lane: &LaneId) -> bool {
-		*lane == [0, 0, 0, 0] || *lane == [0, 0, 0, 1]
+		*lane == [0, 0, 0, 0]
+			|| *lane == [0, 0, 0, 1]
+			|| pallet_bridge_messages::Pallet::<Runtime, crate::WithMillauMessagesInstance>::is_lane_registered(lane)
 	}
 
 	fn maximal_pending_messages_at_outbound_lane() -> MessageNonce {
@@ -112,9 +129,12 @@ impl messages::ThisChainWithMessages for Rialto {
 	}
 
 	fn estimate_delivery_confirmation_transaction() -> MessageTransaction<Weight> {
-		let inbound_data_size =
-			InboundLaneData::<bp_rialto::AccountId>::encoded_size_hint(bp_rialto::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE, 1)
-				.unwrap_or(u32::MAX);
+		let inbound_data_size = InboundLaneData::<bp_rialto::AccountId>::encoded_size_hint(
+			bp_rialto::MAXIMAL_ENCODED_ACCOUNT_ID_SIZE,
+			1,
+			0,
+		)
+		.unwrap_or(u32::MAX);
 
 		MessageTransaction {
 			dispatch_weight: bp_rialto::MAX_SINGLE_MESSAGE_DELIVERY_CONFIRMATION_TX_WEIGHT,
@@ -134,6 +154,11 @@ impl messages::ThisChainWithMessages for Rialto {
 			transaction,
 		)
 	}
+
+	fn apply_lane_fee_multiplier(lane: &LaneId, fee: bp_rialto::Balance) -> Option<bp_rialto::Balance> {
+		pallet_bridge_messages::Pallet::<Runtime, crate::WithMillauMessagesInstance>::lane_fee_multiplier(lane)
+			.checked_mul_int(fee)
+	}
 }
 
 /// Millau chain from message lane point of view.
@@ -202,15 +227,20 @@ impl messages::BridgedChainWithMessages for Millau {
 }
 
 impl TargetHeaderChain<ToMillauMessagePayload, bp_millau::AccountId> for Millau {
-	type Error = &'static str;
+	type Error = ProofRejectionReason;
 	// The proof is:
 	// - hash of the header this proof has been created with;
 	// - the storage proof of one or several keys;
 	// - id of the lane we prove state of.
 	type MessagesDeliveryProof = ToMillauMessagesDeliveryProof;
+	// The proof is:
+	// - hash of the header this proof has been created with;
+	// - the storage proof of several keys;
+	// - ids of lanes we prove states of.
+	type MessagesDeliveryBatchProof = ToMillauMessagesDeliveryBatchProof;
 
 	fn verify_message(payload: &ToMillauMessagePayload) -> Result<(), Self::Error> {
-		messages::source::verify_chain_message::<WithMillauMessageBridge>(payload)
+		messages::source::verify_chain_message::<WithMillauMessageBridge>(payload).map_err(Into::into)
 	}
 
 	fn verify_messages_delivery_proof(
@@ -220,10 +250,31 @@ impl TargetHeaderChain<ToMillauMessagePayload, bp_millau::AccountId> for Millau
 			proof,
 		)
 	}
+
+	fn verify_messages_delivery_proof_with_traversed_nodes(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<((LaneId, InboundLaneData<bp_rialto::AccountId>), Option<u32>), Self::Error> {
+		messages::source::verify_messages_delivery_proof_with_traversed_nodes::<
+			WithMillauMessageBridge,
+			Runtime,
+			crate::MillauGrandpaInstance,
+		>(proof)
+		.map(|(lane, traversed_nodes)| (lane, Some(traversed_nodes)))
+	}
+
+	fn verify_messages_delivery_batch_proof(
+		proof: Self::MessagesDeliveryBatchProof,
+	) -> Result<Vec<(LaneId, InboundLaneData<bp_rialto::AccountId>)>, Self::Error> {
+		messages::source::verify_messages_delivery_batch_proof::<
+			WithMillauMessageBridge,
+			Runtime,
+			crate::MillauGrandpaInstance,
+		>(proof)
+	}
 }
 
 impl SourceHeaderChain<bp_millau::Balance> for Millau {
-	type Error = &'static str;
+	type Error = ProofRejectionReason;
 	// The proof is:
 	// - hash of the header this proof has been created with;
 	// - the storage proof of one or several keys;
@@ -240,6 +291,41 @@ impl SourceHeaderChain<bp_millau::Balance> for Millau {
 			messages_count,
 		)
 	}
+
+	fn verify_messages_proof_with_traversed_nodes(
+		proof: Self::MessagesProof,
+		messages_count: u32,
+	) -> Result<(ProvedMessages<Message<bp_millau::Balance>>, Option<u32>), Self::Error> {
+		messages::target::verify_messages_proof_with_traversed_nodes::<
+			WithMillauMessageBridge,
+			Runtime,
+			crate::MillauGrandpaInstance,
+		>(proof, messages_count)
+		.map(|(messages, traversed_nodes)| (messages, Some(traversed_nodes)))
+	}
+}
+
+/// Builder of Rialto -> Millau messages that are sent by the message generator pallet.
+///
+/// Generated messages are only used to load the bridge. Their call is filled with zero bytes, so
+/// it can't be decoded (and dispatched) at Millau. Since spec version of Millau runtime is unknown
+/// here, it is set to zero.
+pub struct ToMillauGeneratedMessageBuilder;
+
+impl pallet_bridge_message_generator::MessagePayloadBuilder<ToMillauMessagePayload>
+	for ToMillauGeneratedMessageBuilder
+{
+	fn build_payload(payload_size: u32, dispatch_weight: Weight) -> ToMillauMessagePayload {
+		let fee_pot =
+			pallet_bridge_messages::Pallet::<Runtime, crate::WithMillauMessagesInstance>::pallets_fee_pot_account_id();
+		ToMillauMessagePayload {
+			spec_version: 0,
+			weight: dispatch_weight,
+			origin: bp_message_dispatch::CallOrigin::SourceAccount(fee_pot),
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			call: sp_std::vec![0; payload_size as usize],
+		}
+	}
 }
 
 /// Rialto -> Millau message lane pallet parameters.
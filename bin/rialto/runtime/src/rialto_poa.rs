@@ -23,13 +23,14 @@ use bp_header_chain::InclusionProofVerifier;
 use frame_support::RuntimeDebug;
 use hex_literal::hex;
 use pallet_bridge_eth_poa::{
-	AuraConfiguration, ChainTime as TChainTime, PruningStrategy as TPruningStrategy, ValidatorsConfiguration,
-	ValidatorsSource,
+	AuraConfiguration, ChainTime as TChainTime, EngineConfiguration, PruningStrategy as TPruningStrategy,
+	ValidatorsConfiguration, ValidatorsSource,
 };
 use sp_std::prelude::*;
 
 frame_support::parameter_types! {
 	pub const FinalityVotesCachingInterval: Option<u64> = Some(8);
+	pub const BridgeEngineConfiguration: EngineConfiguration = EngineConfiguration::Aura;
 	pub BridgeAuraConfiguration: AuraConfiguration =
 		aura_configuration();
 	pub BridgeValidatorsConfiguration: ValidatorsConfiguration =
@@ -0,0 +1,245 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sources of the bridged chain token to this chain token conversion rate, that is used by the
+//! message fee calculation.
+//!
+//! The conversion rate may either be an operator-fed runtime parameter, or it may be computed from
+//! reserves of the on-chain liquidity pool (e.g. DEX pallet pool) of bridged and this chain tokens.
+//! The pool rate is only used if pool has been updated recently and it is deep enough - otherwise
+//! the fallback rate is used.
+
+use frame_support::{traits::Get, RuntimeDebug};
+use sp_runtime::{
+	traits::{BlockNumberProvider, Saturating},
+	FixedPointNumber, FixedU128,
+};
+use sp_std::marker::PhantomData;
+
+/// Source of the conversion rate.
+///
+/// The rate is used as: `ThisChainTokens = BridgedChainTokens * conversion_rate`.
+pub trait ConversionRateSource {
+	/// Returns current conversion rate.
+	fn conversion_rate() -> FixedU128;
+}
+
+/// Conversion rate that is stored in the runtime parameter.
+pub struct ParameterConversionRate<Rate>(PhantomData<Rate>);
+
+impl<Rate: Get<FixedU128>> ConversionRateSource for ParameterConversionRate<Rate> {
+	fn conversion_rate() -> FixedU128 {
+		Rate::get()
+	}
+}
+
+/// State of the liquidity pool of bridged and this chain tokens.
+#[derive(RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolState<BlockNumber> {
+	/// Amount of bridged chain tokens in the pool.
+	pub bridged_reserve: u128,
+	/// Amount of this chain tokens in the pool.
+	pub this_reserve: u128,
+	/// Number of block where the pool has been updated last time (e.g. where the last swap has
+	/// happened).
+	pub updated_at: BlockNumber,
+}
+
+/// On-chain liquidity pool of bridged and this chain tokens.
+pub trait LiquidityPool {
+	/// Block number type.
+	type BlockNumber;
+
+	/// Returns current state of the pool or `None` if pool doesn't exist.
+	fn state() -> Option<PoolState<Self::BlockNumber>>;
+}
+
+/// Configuration of the pool-based conversion rate.
+pub trait PoolConversionRateConfig {
+	/// Liquidity pool of bridged and this chain tokens.
+	type Pool: LiquidityPool;
+	/// Provider of the current block number.
+	type BlockNumberProvider: BlockNumberProvider<BlockNumber = <Self::Pool as LiquidityPool>::BlockNumber>;
+	/// Maximal number of blocks since last pool update. If pool has been updated earlier, its
+	/// rate is considered stale.
+	type MaxStaleness: Get<<Self::Pool as LiquidityPool>::BlockNumber>;
+	/// Amount of bridged chain tokens that is used to estimate pool slippage.
+	type ReferenceAmount: Get<u128>;
+	/// Maximal slippage (as a fraction of pool rate), when `ReferenceAmount` of bridged chain tokens
+	/// is exchanged using the pool. If slippage is larger, then the pool is considered too shallow
+	/// (and too easy to manipulate) to be used as a price source.
+	type MaxSlippage: Get<FixedU128>;
+	/// Conversion rate that is used when pool rate can't be used.
+	type Fallback: ConversionRateSource;
+}
+
+/// Reason why the pool rate can't be used.
+#[derive(RuntimeDebug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolRateError {
+	/// The pool doesn't exist.
+	MissingPool,
+	/// The pool has no liquidity.
+	EmptyPool,
+	/// The pool hasn't been updated for too long.
+	StalePool,
+	/// The pool is too shallow.
+	TooLargeSlippage,
+}
+
+/// Conversion rate, computed from reserves of the on-chain liquidity pool.
+pub struct PoolConversionRate<Config>(PhantomData<Config>);
+
+impl<Config: PoolConversionRateConfig> PoolConversionRate<Config>
+where
+	<Config::Pool as LiquidityPool>::BlockNumber: Saturating + PartialOrd,
+{
+	/// Returns conversion rate, computed from the pool reserves.
+	pub fn pool_rate() -> Result<FixedU128, PoolRateError> {
+		let pool = Config::Pool::state().ok_or(PoolRateError::MissingPool)?;
+		if pool.bridged_reserve == 0 || pool.this_reserve == 0 {
+			return Err(PoolRateError::EmptyPool);
+		}
+
+		let current_block = Config::BlockNumberProvider::current_block_number();
+		if current_block.saturating_sub(pool.updated_at) > Config::MaxStaleness::get() {
+			return Err(PoolRateError::StalePool);
+		}
+
+		// with constant product pool, exchanging `a` bridged tokens gives us
+		// `this_reserve * a / (bridged_reserve + a)` of this tokens, so the slippage is
+		// `a / (bridged_reserve + a)`
+		let reference_amount = Config::ReferenceAmount::get();
+		let slippage =
+			FixedU128::checked_from_rational(reference_amount, pool.bridged_reserve.saturating_add(reference_amount))
+				.ok_or(PoolRateError::EmptyPool)?;
+		if slippage > Config::MaxSlippage::get() {
+			return Err(PoolRateError::TooLargeSlippage);
+		}
+
+		FixedU128::checked_from_rational(pool.this_reserve, pool.bridged_reserve).ok_or(PoolRateError::EmptyPool)
+	}
+}
+
+impl<Config: PoolConversionRateConfig> ConversionRateSource for PoolConversionRate<Config>
+where
+	<Config::Pool as LiquidityPool>::BlockNumber: Saturating + PartialOrd,
+{
+	fn conversion_rate() -> FixedU128 {
+		Self::pool_rate().unwrap_or_else(|_| Config::Fallback::conversion_rate())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::parameter_types;
+	use std::cell::RefCell;
+
+	thread_local! {
+		static POOL_STATE: RefCell<Option<PoolState<u64>>> = RefCell::new(None);
+	}
+
+	const CURRENT_BLOCK: u64 = 100;
+
+	struct TestPool;
+
+	impl LiquidityPool for TestPool {
+		type BlockNumber = u64;
+
+		fn state() -> Option<PoolState<u64>> {
+			POOL_STATE.with(|state| *state.borrow())
+		}
+	}
+
+	struct TestBlockNumberProvider;
+
+	impl BlockNumberProvider for TestBlockNumberProvider {
+		type BlockNumber = u64;
+
+		fn current_block_number() -> u64 {
+			CURRENT_BLOCK
+		}
+	}
+
+	parameter_types! {
+		const MaxStaleness: u64 = 10;
+		const ReferenceAmount: u128 = 1_000;
+		const MaxSlippage: FixedU128 = FixedU128::from_inner(FixedU128::DIV / 100);
+		const FallbackRate: FixedU128 = FixedU128::from_inner(FixedU128::DIV * 7);
+	}
+
+	struct TestConfig;
+
+	impl PoolConversionRateConfig for TestConfig {
+		type Pool = TestPool;
+		type BlockNumberProvider = TestBlockNumberProvider;
+		type MaxStaleness = MaxStaleness;
+		type ReferenceAmount = ReferenceAmount;
+		type MaxSlippage = MaxSlippage;
+		type Fallback = ParameterConversionRate<FallbackRate>;
+	}
+
+	type TestConversionRate = PoolConversionRate<TestConfig>;
+
+	fn set_pool_state(bridged_reserve: u128, this_reserve: u128, updated_at: u64) {
+		POOL_STATE.with(|state| {
+			*state.borrow_mut() = Some(PoolState {
+				bridged_reserve,
+				this_reserve,
+				updated_at,
+			})
+		});
+	}
+
+	#[test]
+	fn pool_rate_is_used_when_pool_is_fresh_and_deep() {
+		set_pool_state(1_000_000, 2_000_000, CURRENT_BLOCK - 10);
+		assert_eq!(
+			TestConversionRate::pool_rate(),
+			Ok(FixedU128::saturating_from_integer(2))
+		);
+		assert_eq!(
+			TestConversionRate::conversion_rate(),
+			FixedU128::saturating_from_integer(2)
+		);
+	}
+
+	#[test]
+	fn fallback_rate_is_used_when_pool_is_missing_or_empty() {
+		POOL_STATE.with(|state| *state.borrow_mut() = None);
+		assert_eq!(TestConversionRate::pool_rate(), Err(PoolRateError::MissingPool));
+		assert_eq!(TestConversionRate::conversion_rate(), FallbackRate::get());
+
+		set_pool_state(0, 2_000_000, CURRENT_BLOCK);
+		assert_eq!(TestConversionRate::pool_rate(), Err(PoolRateError::EmptyPool));
+		assert_eq!(TestConversionRate::conversion_rate(), FallbackRate::get());
+	}
+
+	#[test]
+	fn fallback_rate_is_used_when_pool_is_stale() {
+		set_pool_state(1_000_000, 2_000_000, CURRENT_BLOCK - 11);
+		assert_eq!(TestConversionRate::pool_rate(), Err(PoolRateError::StalePool));
+		assert_eq!(TestConversionRate::conversion_rate(), FallbackRate::get());
+	}
+
+	#[test]
+	fn fallback_rate_is_used_when_pool_is_shallow() {
+		// exchanging 1_000 tokens in the pool with 10_000 tokens gives ~9% slippage
+		set_pool_state(10_000, 20_000, CURRENT_BLOCK);
+		assert_eq!(TestConversionRate::pool_rate(), Err(PoolRateError::TooLargeSlippage));
+		assert_eq!(TestConversionRate::conversion_rate(), FallbackRate::get());
+	}
+}
@@ -18,5 +18,7 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod conversion_rate;
 pub mod messages;
 pub mod messages_benchmarking;
+pub mod messages_extension;
@@ -20,19 +20,24 @@
 //! pallet is used to dispatch incoming messages. Message identified by a tuple
 //! of to elements - message lane id and message nonce.
 
+use bp_header_chain::HeadersPruningGuard;
 use bp_message_dispatch::MessageDispatch as _;
 use bp_messages::{
-	source_chain::{LaneMessageVerifier, Sender},
+	source_chain::{ChainMessageVerifier, LaneMessageVerifier, MessagesDeliveryProofVerifier, Sender},
 	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages},
-	InboundLaneData, LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData,
+	InboundLaneData, LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData, ProofRejectionReason,
+};
+use bp_runtime::{ChainId, DispatchFeePayment, Size, StorageProofChecker};
+use codec::{Decode, DecodeAll, Encode};
+use frame_support::{
+	traits::{Currency, ExistenceRequirement, Get, Instance},
+	weights::{Weight, WeightToFeePolynomial},
+	Parameter, RuntimeDebug,
 };
-use bp_runtime::{ChainId, Size, StorageProofChecker};
-use codec::{Decode, Encode};
-use frame_support::{traits::Instance, weights::Weight, RuntimeDebug};
 use hash_db::Hasher;
 use sp_runtime::{
-	traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedDiv, CheckedMul},
-	FixedPointNumber, FixedPointOperand, FixedU128,
+	traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Header as HeaderT, Zero},
+	DispatchError, FixedPointNumber, FixedPointOperand, FixedU128,
 };
 use sp_std::{cmp::PartialOrd, convert::TryFrom, fmt::Debug, marker::PhantomData, ops::RangeInclusive, vec::Vec};
 use sp_trie::StorageProof;
@@ -70,7 +75,7 @@ pub trait ChainWithMessages {
 	/// different weights.
 	type Weight: From<frame_support::weights::Weight> + PartialOrd;
 	/// Type of balances that is used on the chain.
-	type Balance: Encode + Decode + CheckedAdd + CheckedDiv + CheckedMul + PartialOrd + From<u32> + Copy;
+	type Balance: Encode + Decode + CheckedAdd + CheckedSub + CheckedDiv + CheckedMul + PartialOrd + From<u32> + Copy;
 
 	/// Instance of the `pallet-bridge-messages` pallet.
 	type MessagesInstance: Instance;
@@ -103,6 +108,13 @@ pub trait ThisChainWithMessages: ChainWithMessages {
 
 	/// Returns minimal transaction fee that must be paid for given transaction at This chain.
 	fn transaction_payment(transaction: MessageTransaction<WeightOf<Self>>) -> BalanceOf<Self>;
+
+	/// Apply lane-specific multiplier to the minimal fee of message, sent over given lane.
+	///
+	/// The multiplier is supposed to be adjustable by governance, so that it could react to fee
+	/// volatility at the Bridged chain without changing the global conversion rate. Returns `None`
+	/// if overflow has happened.
+	fn apply_lane_fee_multiplier(lane: &LaneId, fee: BalanceOf<Self>) -> Option<BalanceOf<Self>>;
 }
 
 /// Bridged chain that has `pallet-bridge-messages` and `dispatch` modules.
@@ -213,6 +225,32 @@ pub mod source {
 		}
 	}
 
+	/// Messages delivery proof of several lanes from bridged chain:
+	///
+	/// - hash of finalized header;
+	/// - storage proof of states of all inbound lanes;
+	/// - ids of all lanes.
+	#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug)]
+	pub struct FromBridgedChainMessagesDeliveryBatchProof<BridgedHeaderHash> {
+		/// Hash of the bridge header the proof is for.
+		pub bridged_header_hash: BridgedHeaderHash,
+		/// Storage trie proof generated for [`Self::bridged_header_hash`].
+		pub storage_proof: RawStorageProof,
+		/// Ids of lanes of which messages were delivered and the proof is for.
+		pub lanes: Vec<LaneId>,
+	}
+
+	impl<BridgedHeaderHash> Size for FromBridgedChainMessagesDeliveryBatchProof<BridgedHeaderHash> {
+		fn size_hint(&self) -> u32 {
+			u32::try_from(
+				self.storage_proof
+					.iter()
+					.fold(0usize, |sum, node| sum.saturating_add(node.len())),
+			)
+			.unwrap_or(u32::MAX)
+		}
+	}
+
 	/// 'Parsed' message delivery proof - inbound lane id and its state.
 	pub type ParsedMessagesDeliveryProofFromBridgedChain<B> = (LaneId, InboundLaneData<AccountIdOf<ThisChain<B>>>);
 
@@ -228,19 +266,71 @@ pub mod source {
 	/// - message is rejected if its lane is currently blocked;
 	/// - message is rejected if there are too many pending (undelivered) messages at the outbound lane;
 	/// - check that the sender has rights to dispatch the call on target chain using provided dispatch origin;
-	/// - check that the sender has paid enough funds for both message delivery and dispatch.
+	/// - check that the sender has paid enough funds for both message delivery and dispatch. The minimal
+	///   fee is computed by the `F` fee calculation.
+	#[derive(RuntimeDebug)]
+	pub struct FromThisChainMessageVerifier<B, F = DefaultMessageFeeCalculation>(PhantomData<(B, F)>);
+
+	/// Components of the message delivery and dispatch fee (in This chain tokens).
+	#[derive(RuntimeDebug, PartialEq)]
+	pub struct MessageFee<Balance> {
+		/// Cost of the message delivery transaction at the Bridged chain (excluding the message
+		/// dispatch) plus the cost of delivery confirmation transaction at This chain.
+		pub delivery_cost: Balance,
+		/// Cost of the message dispatch at the Bridged chain.
+		pub dispatch_cost: Balance,
+		/// Reward that is paid to relayers in addition to delivery and dispatch costs.
+		pub relayer_reward: Balance,
+	}
+
+	impl<Balance: CheckedAdd> MessageFee<Balance> {
+		/// Returns total message fee or `None` if overflow has happened.
+		pub fn total(&self) -> Option<Balance> {
+			self.delivery_cost
+				.checked_add(&self.dispatch_cost)
+				.and_then(|fee| fee.checked_add(&self.relayer_reward))
+		}
+	}
+
+	/// Message fee calculation.
+	///
+	/// It is used by the `FromThisChainMessageVerifier` to compute minimal fee that the message
+	/// sender must pay for delivering and dispatching message at the Bridged chain.
+	pub trait MessageFeeCalculation<B: MessageBridge> {
+		/// Compute components of the fee that must be paid for given message.
+		fn calculate_message_fee(
+			payload: &FromThisChainMessagePayload<B>,
+		) -> Result<MessageFee<BalanceOf<ThisChain<B>>>, &'static str>;
+	}
+
+	/// Default message fee calculation.
+	///
+	/// Costs are computed using `BridgedChainWithMessages` and `ThisChainWithMessages` transactions
+	/// estimations. The relayer reward is `MessageBridge::RELAYER_FEE_PERCENT` of delivery and dispatch
+	/// costs.
 	#[derive(RuntimeDebug)]
-	pub struct FromThisChainMessageVerifier<B>(PhantomData<B>);
+	pub struct DefaultMessageFeeCalculation;
+
+	impl<B: MessageBridge> MessageFeeCalculation<B> for DefaultMessageFeeCalculation {
+		fn calculate_message_fee(
+			payload: &FromThisChainMessagePayload<B>,
+		) -> Result<MessageFee<BalanceOf<ThisChain<B>>>, &'static str> {
+			estimate_message_fee_components::<B>(payload, B::RELAYER_FEE_PERCENT)
+		}
+	}
 
 	pub(crate) const OUTBOUND_LANE_DISABLED: &str = "The outbound message lane is disabled.";
 	pub(crate) const TOO_MANY_PENDING_MESSAGES: &str = "Too many pending messages at the lane.";
 	pub(crate) const BAD_ORIGIN: &str = "Unable to match the source origin to expected target origin.";
 	pub(crate) const TOO_LOW_FEE: &str = "Provided fee is below minimal threshold required by the lane.";
+	pub(crate) const MESSAGE_FEE_OVERFLOW: &str =
+		"Overflow when computing minimal required message delivery and dispatch fee";
 
-	impl<B> LaneMessageVerifier<AccountIdOf<ThisChain<B>>, FromThisChainMessagePayload<B>, BalanceOf<ThisChain<B>>>
-		for FromThisChainMessageVerifier<B>
+	impl<B, F> LaneMessageVerifier<AccountIdOf<ThisChain<B>>, FromThisChainMessagePayload<B>, BalanceOf<ThisChain<B>>>
+		for FromThisChainMessageVerifier<B, F>
 	where
 		B: MessageBridge,
+		F: MessageFeeCalculation<B>,
 		AccountIdOf<ThisChain<B>>: PartialEq + Clone,
 	{
 		type Error = &'static str;
@@ -270,8 +360,10 @@ pub mod source {
 			// `Dispatch`, so we verify the message accordingly.
 			pallet_bridge_dispatch::verify_message_origin(submitter, payload).map_err(|_| BAD_ORIGIN)?;
 
-			let minimal_fee_in_this_tokens =
-				estimate_message_dispatch_and_delivery_fee::<B>(payload, B::RELAYER_FEE_PERCENT)?;
+			let minimal_fee_in_this_tokens = F::calculate_message_fee(payload)?
+				.total()
+				.and_then(|fee| ThisChain::<B>::apply_lane_fee_multiplier(lane, fee))
+				.ok_or(MESSAGE_FEE_OVERFLOW)?;
 
 			// compare with actual fee paid
 			if *delivery_and_dispatch_fee < minimal_fee_in_this_tokens {
@@ -320,41 +412,153 @@ pub mod source {
 	/// Estimate delivery and dispatch fee that must be paid for delivering a message to the Bridged chain.
 	///
 	/// The fee is paid in This chain Balance, but we use Bridged chain balance to avoid additional conversions.
+	/// The fee multiplier of the lane is applied to the estimated fee.
 	/// Returns `None` if overflow has happened.
 	pub fn estimate_message_dispatch_and_delivery_fee<B: MessageBridge>(
+		lane: &LaneId,
 		payload: &FromThisChainMessagePayload<B>,
 		relayer_fee_percent: u32,
 	) -> Result<BalanceOf<ThisChain<B>>, &'static str> {
-		// the fee (in Bridged tokens) of all transactions that are made on the Bridged chain
+		estimate_message_fee_components::<B>(payload, relayer_fee_percent)?
+			.total()
+			.and_then(|fee| ThisChain::<B>::apply_lane_fee_multiplier(lane, fee))
+			.ok_or(MESSAGE_FEE_OVERFLOW)
+	}
+
+	/// Estimate components of the fee that must be paid for delivering a message to the Bridged chain.
+	///
+	/// All components are computed in This chain Balance.
+	pub fn estimate_message_fee_components<B: MessageBridge>(
+		payload: &FromThisChainMessagePayload<B>,
+		relayer_fee_percent: u32,
+	) -> Result<MessageFee<BalanceOf<ThisChain<B>>>, &'static str> {
+		// the fee (in Bridged tokens) of the delivery transaction that is made on the Bridged chain, both
+		// with and without message dispatch
 		let delivery_transaction =
 			BridgedChain::<B>::estimate_delivery_transaction(&payload.call, payload.weight.into());
 		let delivery_transaction_fee = BridgedChain::<B>::transaction_payment(delivery_transaction);
+		let delivery_transaction_without_dispatch =
+			BridgedChain::<B>::estimate_delivery_transaction(&payload.call, WeightOf::<BridgedChain<B>>::from(0));
+		let delivery_transaction_without_dispatch_fee =
+			BridgedChain::<B>::transaction_payment(delivery_transaction_without_dispatch);
 
 		// the fee (in This tokens) of all transactions that are made on This chain
 		let confirmation_transaction = ThisChain::<B>::estimate_delivery_confirmation_transaction();
 		let confirmation_transaction_fee = ThisChain::<B>::transaction_payment(confirmation_transaction);
 
-		// minimal fee (in This tokens) is a sum of all required fees
-		let minimal_fee =
-			B::bridged_balance_to_this_balance(delivery_transaction_fee).checked_add(&confirmation_transaction_fee);
+		// delivery cost (in This tokens) is a sum of delivery (without dispatch) and confirmation fees
+		let delivery_transaction_without_dispatch_fee =
+			B::bridged_balance_to_this_balance(delivery_transaction_without_dispatch_fee);
+		let delivery_cost = delivery_transaction_without_dispatch_fee
+			.checked_add(&confirmation_transaction_fee)
+			.ok_or(MESSAGE_FEE_OVERFLOW)?;
+
+		// dispatch cost (in This tokens) is the difference between delivery transaction fees. If the
+		// dispatch fee is paid at the Bridged chain, the message submitter doesn't pay it here
+		let dispatch_cost = match payload.dispatch_fee_payment {
+			DispatchFeePayment::AtSourceChain => B::bridged_balance_to_this_balance(delivery_transaction_fee)
+				.checked_sub(&delivery_transaction_without_dispatch_fee)
+				.unwrap_or_else(|| 0u32.into()),
+			DispatchFeePayment::AtTargetChain => 0u32.into(),
+		};
 
-		// before returning, add extra fee that is paid to the relayer (relayer interest)
-		minimal_fee
+		// extra fee that is paid to the relayer (relayer interest)
+		let relayer_reward = delivery_cost
+			.checked_add(&dispatch_cost)
 			.and_then(|fee|
 			// having message with fee that is near the `Balance::MAX_VALUE` of the chain is
 			// unlikely and should be treated as an error
 			// => let's do multiplication first
 			fee
 				.checked_mul(&relayer_fee_percent.into())
-				.and_then(|interest| interest.checked_div(&100u32.into()))
-				.and_then(|interest| fee.checked_add(&interest)))
-			.ok_or("Overflow when computing minimal required message delivery and dispatch fee")
+				.and_then(|interest| interest.checked_div(&100u32.into())))
+			.ok_or(MESSAGE_FEE_OVERFLOW)?;
+
+		Ok(MessageFee {
+			delivery_cost,
+			dispatch_cost,
+			relayer_reward,
+		})
+	}
+
+	/// Chain message verifier that is doing basic Bridged-chain specific checks (see
+	/// `verify_chain_message`).
+	///
+	/// It may be used together with any `MessagesDeliveryProofVerifier` in the
+	/// `bp_messages::source_chain::TargetHeaderChainAdapter`.
+	pub struct FromThisChainMessagePayloadVerifier<B>(PhantomData<B>);
+
+	impl<B: MessageBridge> ChainMessageVerifier<FromThisChainMessagePayload<B>> for FromThisChainMessagePayloadVerifier<B> {
+		fn verify_message(payload: &FromThisChainMessagePayload<B>) -> Result<(), &'static str> {
+			verify_chain_message::<B>(payload)
+		}
+	}
+
+	/// Messages delivery proof verifier for Substrate-based Bridged chains, where delivery is proved
+	/// by the storage proof of inbound lane(s) state(s) at the finalized Bridged chain header.
+	pub struct FromBridgedChainStorageProofVerifier<B, ThisRuntime, GrandpaInstance>(
+		PhantomData<(B, ThisRuntime, GrandpaInstance)>,
+	);
+
+	impl<B, ThisRuntime, GrandpaInstance> MessagesDeliveryProofVerifier<AccountIdOf<ThisChain<B>>>
+		for FromBridgedChainStorageProofVerifier<B, ThisRuntime, GrandpaInstance>
+	where
+		B: MessageBridge,
+		ThisRuntime: pallet_bridge_grandpa::Config<GrandpaInstance>,
+		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
+		GrandpaInstance: 'static,
+		HashOf<BridgedChain<B>>:
+			Into<bp_runtime::HashOf<<ThisRuntime as pallet_bridge_grandpa::Config<GrandpaInstance>>::BridgedChain>>,
+		HashOf<BridgedChain<B>>: Parameter,
+	{
+		type Error = ProofRejectionReason;
+
+		type MessagesDeliveryProof = FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<B>>>;
+		type MessagesDeliveryBatchProof = FromBridgedChainMessagesDeliveryBatchProof<HashOf<BridgedChain<B>>>;
+
+		fn verify_messages_delivery_proof(
+			proof: Self::MessagesDeliveryProof,
+		) -> Result<ParsedMessagesDeliveryProofFromBridgedChain<B>, Self::Error> {
+			verify_messages_delivery_proof::<B, ThisRuntime, GrandpaInstance>(proof)
+		}
+
+		fn verify_messages_delivery_proof_with_traversed_nodes(
+			proof: Self::MessagesDeliveryProof,
+		) -> Result<(ParsedMessagesDeliveryProofFromBridgedChain<B>, Option<u32>), Self::Error> {
+			verify_messages_delivery_proof_with_traversed_nodes::<B, ThisRuntime, GrandpaInstance>(proof)
+				.map(|(lane, traversed_nodes)| (lane, Some(traversed_nodes)))
+		}
+
+		fn verify_messages_delivery_batch_proof(
+			proof: Self::MessagesDeliveryBatchProof,
+		) -> Result<Vec<ParsedMessagesDeliveryProofFromBridgedChain<B>>, Self::Error> {
+			verify_messages_delivery_batch_proof::<B, ThisRuntime, GrandpaInstance>(proof)
+		}
 	}
 
 	/// Verify proof of This -> Bridged chain messages delivery.
 	pub fn verify_messages_delivery_proof<B: MessageBridge, ThisRuntime, GrandpaInstance: 'static>(
 		proof: FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<B>>>,
-	) -> Result<ParsedMessagesDeliveryProofFromBridgedChain<B>, &'static str>
+	) -> Result<ParsedMessagesDeliveryProofFromBridgedChain<B>, ProofRejectionReason>
+	where
+		ThisRuntime: pallet_bridge_grandpa::Config<GrandpaInstance>,
+		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
+		HashOf<BridgedChain<B>>:
+			Into<bp_runtime::HashOf<<ThisRuntime as pallet_bridge_grandpa::Config<GrandpaInstance>>::BridgedChain>>,
+	{
+		verify_messages_delivery_proof_with_traversed_nodes::<B, ThisRuntime, GrandpaInstance>(proof)
+			.map(|(lane, _)| lane)
+	}
+
+	/// Verify proof of This -> Bridged chain messages delivery and return the number of trie nodes
+	/// that have been traversed during verification.
+	pub fn verify_messages_delivery_proof_with_traversed_nodes<
+		B: MessageBridge,
+		ThisRuntime,
+		GrandpaInstance: 'static,
+	>(
+		proof: FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<B>>>,
+	) -> Result<(ParsedMessagesDeliveryProofFromBridgedChain<B>, u32), ProofRejectionReason>
 	where
 		ThisRuntime: pallet_bridge_grandpa::Config<GrandpaInstance>,
 		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
@@ -378,15 +582,148 @@ pub mod source {
 				>(&lane);
 				let raw_inbound_lane_data = storage
 					.read_value(storage_inbound_lane_data_key.0.as_ref())
-					.map_err(|_| "Failed to read inbound lane state from storage proof")?
-					.ok_or("Inbound lane state is missing from the messages proof")?;
-				let inbound_lane_data = InboundLaneData::decode(&mut &raw_inbound_lane_data[..])
-					.map_err(|_| "Failed to decode inbound lane state from the proof")?;
+					.map_err(|_| ProofRejectionReason::MissingLaneState)?
+					.ok_or(ProofRejectionReason::MissingLaneState)?;
+				let inbound_lane_data = InboundLaneData::decode_all(&raw_inbound_lane_data[..])
+					.map_err(|_| ProofRejectionReason::FailedToDecodeLaneState)?;
+
+				// reject proofs that are padded with unrelated nodes
+				let traversed_nodes = storage.traversed_nodes_count() as u32;
+				storage
+					.ensure_no_unused_nodes()
+					.map_err(|_| ProofRejectionReason::ExtraProofNodes)?;
+
+				Ok(((lane, inbound_lane_data), traversed_nodes))
+			},
+		)
+		.map_err(storage_proof_rejection_reason::<ThisRuntime, GrandpaInstance>)?
+	}
+
+	/// Verify proof of This -> Bridged chain messages delivery at several lanes.
+	pub fn verify_messages_delivery_batch_proof<B: MessageBridge, ThisRuntime, GrandpaInstance: 'static>(
+		proof: FromBridgedChainMessagesDeliveryBatchProof<HashOf<BridgedChain<B>>>,
+	) -> Result<Vec<ParsedMessagesDeliveryProofFromBridgedChain<B>>, ProofRejectionReason>
+	where
+		ThisRuntime: pallet_bridge_grandpa::Config<GrandpaInstance>,
+		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
+		HashOf<BridgedChain<B>>:
+			Into<bp_runtime::HashOf<<ThisRuntime as pallet_bridge_grandpa::Config<GrandpaInstance>>::BridgedChain>>,
+	{
+		let FromBridgedChainMessagesDeliveryBatchProof {
+			bridged_header_hash,
+			storage_proof,
+			lanes,
+		} = proof;
+		if lanes.is_empty() {
+			return Err(ProofRejectionReason::Empty);
+		}
+
+		pallet_bridge_grandpa::Pallet::<ThisRuntime, GrandpaInstance>::parse_finalized_storage_proof(
+			bridged_header_hash.into(),
+			StorageProof::new(storage_proof),
+			|storage| {
+				// Every lane state must be in the proof => any error is fatal.
+				let mut result = Vec::with_capacity(lanes.len());
+				for lane in lanes {
+					let storage_inbound_lane_data_key = pallet_bridge_messages::storage_keys::inbound_lane_data_key::<
+						ThisRuntime,
+						MessagesInstanceOf<BridgedChain<B>>,
+					>(&lane);
+					let raw_inbound_lane_data = storage
+						.read_value(storage_inbound_lane_data_key.0.as_ref())
+						.map_err(|_| ProofRejectionReason::MissingLaneState)?
+						.ok_or(ProofRejectionReason::MissingLaneState)?;
+					let inbound_lane_data = InboundLaneData::decode_all(&raw_inbound_lane_data[..])
+						.map_err(|_| ProofRejectionReason::FailedToDecodeLaneState)?;
+
+					result.push((lane, inbound_lane_data));
+				}
 
-				Ok((lane, inbound_lane_data))
+				// reject proofs that are padded with unrelated nodes
+				storage
+					.ensure_no_unused_nodes()
+					.map_err(|_| ProofRejectionReason::ExtraProofNodes)?;
+
+				Ok(result)
 			},
 		)
-		.map_err(<&'static str>::from)?
+		.map_err(storage_proof_rejection_reason::<ThisRuntime, GrandpaInstance>)?
+	}
+
+	/// Number of the best finalized Bridged chain header, known to the bridge GRANDPA pallet.
+	///
+	/// It may be used as `pallet_bridge_messages::Config::BestFinalizedBridgedHeaderNumber`.
+	pub struct BestFinalizedBridgedHeaderNumber<ThisRuntime, GrandpaInstance>(
+		PhantomData<(ThisRuntime, GrandpaInstance)>,
+	);
+
+	impl<ThisRuntime, GrandpaInstance> Get<pallet_bridge_grandpa::BridgedBlockNumber<ThisRuntime, GrandpaInstance>>
+		for BestFinalizedBridgedHeaderNumber<ThisRuntime, GrandpaInstance>
+	where
+		ThisRuntime: pallet_bridge_grandpa::Config<GrandpaInstance>,
+		GrandpaInstance: 'static,
+	{
+		fn get() -> pallet_bridge_grandpa::BridgedBlockNumber<ThisRuntime, GrandpaInstance> {
+			*pallet_bridge_grandpa::Pallet::<ThisRuntime, GrandpaInstance>::best_finalized().number()
+		}
+	}
+
+	/// Headers pruning guard of the bridge GRANDPA pallet, that keeps Bridged chain headers which may
+	/// be used to prove delivery of sent, but not yet confirmed messages.
+	pub struct UnconfirmedMessagesHeadersPruningGuard<ThisRuntime, MessagesInstance>(
+		PhantomData<(ThisRuntime, MessagesInstance)>,
+	);
+
+	impl<ThisRuntime, MessagesInstance>
+		HeadersPruningGuard<<ThisRuntime as pallet_bridge_messages::Config<MessagesInstance>>::BridgedHeaderNumber>
+		for UnconfirmedMessagesHeadersPruningGuard<ThisRuntime, MessagesInstance>
+	where
+		ThisRuntime: pallet_bridge_messages::Config<MessagesInstance>,
+		MessagesInstance: Instance,
+	{
+		fn oldest_required_header(
+		) -> Option<<ThisRuntime as pallet_bridge_messages::Config<MessagesInstance>>::BridgedHeaderNumber> {
+			pallet_bridge_messages::Pallet::<ThisRuntime, MessagesInstance>::oldest_required_bridged_header()
+		}
+	}
+}
+
+/// Convert error of `pallet_bridge_grandpa::Pallet::parse_finalized_storage_proof` into proof rejection reason.
+fn storage_proof_rejection_reason<ThisRuntime, GrandpaInstance: 'static>(error: DispatchError) -> ProofRejectionReason
+where
+	ThisRuntime: pallet_bridge_grandpa::Config<GrandpaInstance>,
+{
+	type GrandpaError<ThisRuntime, GrandpaInstance> = pallet_bridge_grandpa::Error<ThisRuntime, GrandpaInstance>;
+
+	if error == GrandpaError::<ThisRuntime, GrandpaInstance>::UnknownHeader.into() {
+		ProofRejectionReason::UnknownHeader
+	} else if error == GrandpaError::<ThisRuntime, GrandpaInstance>::StorageRootMismatch.into() {
+		ProofRejectionReason::StorageRootMismatch
+	} else if error == GrandpaError::<ThisRuntime, GrandpaInstance>::DuplicateNodesInStorageProof.into() {
+		ProofRejectionReason::ExtraProofNodes
+	} else {
+		ProofRejectionReason::Other(error.into())
+	}
+}
+
+/// Convert error of `pallet_bridge_beefy::Pallet::parse_finalized_storage_proof` into proof rejection reason.
+fn mmr_storage_proof_rejection_reason<ThisRuntime, BeefyInstance: 'static>(error: DispatchError) -> ProofRejectionReason
+where
+	ThisRuntime: pallet_bridge_beefy::Config<BeefyInstance>,
+{
+	type BeefyError<ThisRuntime, BeefyInstance> = pallet_bridge_beefy::Error<ThisRuntime, BeefyInstance>;
+
+	if error == BeefyError::<ThisRuntime, BeefyInstance>::UnknownCommitment.into()
+		|| error == BeefyError::<ThisRuntime, BeefyInstance>::InvalidMmrLeafHeader.into()
+		|| error == BeefyError::<ThisRuntime, BeefyInstance>::InvalidMmrProof.into()
+	{
+		ProofRejectionReason::UnknownHeader
+	} else if error == BeefyError::<ThisRuntime, BeefyInstance>::StorageRootMismatch.into() {
+		ProofRejectionReason::StorageRootMismatch
+	} else if error == BeefyError::<ThisRuntime, BeefyInstance>::DuplicateNodesInStorageProof.into() {
+		ProofRejectionReason::ExtraProofNodes
+	} else {
+		ProofRejectionReason::Other(error.into())
 	}
 }
 
@@ -439,6 +776,50 @@ pub mod target {
 		}
 	}
 
+	/// Messages proof from bridged chain, anchored in the BEEFY-signed MMR root:
+	///
+	/// - number of the bridged block, at which the MMR root has been imported by the BEEFY pallet;
+	/// - MMR leaf and proof of its membership in the MMR;
+	/// - bridged header, referenced by the MMR leaf;
+	/// - storage proof of messages and (optionally) outbound lane state;
+	/// - lane id;
+	/// - nonces (inclusive range) of messages which are included in this proof.
+	///
+	/// Unlike `FromBridgedChainMessagesProof`, the header doesn't need to be imported by the
+	/// bridged chain light client. So messages may be proved against any historical bridged
+	/// block, given a single imported commitment.
+	#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug)]
+	pub struct FromBridgedChainMessagesMmrProof<BridgedBlockNumber, BridgedHeaderHash, BridgedHeader> {
+		/// Number of the bridged block with imported MMR root.
+		pub commitment_block_number: BridgedBlockNumber,
+		/// MMR leaf, that references the `bridged_header`.
+		pub mmr_leaf: bp_beefy::BeefyMmrLeaf<BridgedBlockNumber, BridgedHeaderHash>,
+		/// Proof of the `mmr_leaf` membership in the MMR.
+		pub mmr_proof: bp_beefy::MmrLeafProof,
+		/// The bridged header the storage proof is for.
+		pub bridged_header: BridgedHeader,
+		/// A storage trie proof of messages being delivered.
+		pub storage_proof: RawStorageProof,
+		pub lane: LaneId,
+		/// Nonce of the first message being delivered.
+		pub nonces_start: MessageNonce,
+		/// Nonce of the last message being delivered.
+		pub nonces_end: MessageNonce,
+	}
+
+	impl<BridgedBlockNumber, BridgedHeaderHash, BridgedHeader> Size
+		for FromBridgedChainMessagesMmrProof<BridgedBlockNumber, BridgedHeaderHash, BridgedHeader>
+	{
+		fn size_hint(&self) -> u32 {
+			u32::try_from(
+				self.storage_proof
+					.iter()
+					.fold(0usize, |sum, node| sum.saturating_add(node.len())),
+			)
+			.unwrap_or(u32::MAX)
+		}
+	}
+
 	/// Encoded Call of This chain as it is transferred over bridge.
 	///
 	/// Our Call is opaque (`Vec<u8>`) for Bridged chain. So it is encoded, prefixed with
@@ -456,21 +837,31 @@ pub mod target {
 	}
 
 	/// Dispatching Bridged -> This chain messages.
+	///
+	/// If the message dispatch fee is paid at This chain, it is transferred (using `ThisCurrency`) from
+	/// the dispatch origin account to the account of the relayer that has delivered the message. The fee
+	/// is computed the same way as the adjusted weight fee of the regular This chain transaction.
 	#[derive(RuntimeDebug, Clone, Copy)]
-	pub struct FromBridgedChainMessageDispatch<B, ThisRuntime, ThisDispatchInstance> {
-		_marker: PhantomData<(B, ThisRuntime, ThisDispatchInstance)>,
+	pub struct FromBridgedChainMessageDispatch<B, ThisRuntime, ThisCurrency, ThisDispatchInstance> {
+		_marker: PhantomData<(B, ThisRuntime, ThisCurrency, ThisDispatchInstance)>,
 	}
 
-	impl<B: MessageBridge, ThisRuntime, ThisDispatchInstance>
-		MessageDispatch<<BridgedChain<B> as ChainWithMessages>::Balance>
-		for FromBridgedChainMessageDispatch<B, ThisRuntime, ThisDispatchInstance>
+	impl<B: MessageBridge, ThisRuntime, ThisCurrency, ThisDispatchInstance>
+		MessageDispatch<AccountIdOf<ThisChain<B>>, BalanceOf<BridgedChain<B>>>
+		for FromBridgedChainMessageDispatch<B, ThisRuntime, ThisCurrency, ThisDispatchInstance>
 	where
+		BalanceOf<ThisChain<B>>: FixedPointOperand,
 		ThisDispatchInstance: frame_support::traits::Instance,
-		ThisRuntime: pallet_bridge_dispatch::Config<ThisDispatchInstance, MessageId = (LaneId, MessageNonce)>,
-		<ThisRuntime as pallet_bridge_dispatch::Config<ThisDispatchInstance>>::Event:
-			From<pallet_bridge_dispatch::RawEvent<(LaneId, MessageNonce), ThisDispatchInstance>>,
-		pallet_bridge_dispatch::Pallet<ThisRuntime, ThisDispatchInstance>:
-			bp_message_dispatch::MessageDispatch<(LaneId, MessageNonce), Message = FromBridgedChainMessagePayload<B>>,
+		ThisRuntime: pallet_bridge_dispatch::Config<ThisDispatchInstance, MessageId = (LaneId, MessageNonce)>
+			+ pallet_transaction_payment::Config,
+		<ThisRuntime as pallet_transaction_payment::Config>::OnChargeTransaction:
+			pallet_transaction_payment::OnChargeTransaction<ThisRuntime, Balance = BalanceOf<ThisChain<B>>>,
+		ThisCurrency: Currency<AccountIdOf<ThisChain<B>>, Balance = BalanceOf<ThisChain<B>>>,
+		pallet_bridge_dispatch::Pallet<ThisRuntime, ThisDispatchInstance>: bp_message_dispatch::MessageDispatch<
+			AccountIdOf<ThisChain<B>>,
+			(LaneId, MessageNonce),
+			Message = FromBridgedChainMessagePayload<B>,
+		>,
 	{
 		type DispatchPayload = FromBridgedChainMessagePayload<B>;
 
@@ -480,14 +871,34 @@ pub mod target {
 			message.data.payload.as_ref().map(|payload| payload.weight).unwrap_or(0)
 		}
 
-		fn dispatch(message: DispatchMessage<Self::DispatchPayload, BalanceOf<BridgedChain<B>>>) {
+		fn dispatch(
+			relayer_account: &AccountIdOf<ThisChain<B>>,
+			message: DispatchMessage<Self::DispatchPayload, BalanceOf<BridgedChain<B>>>,
+		) -> Weight {
 			let message_id = (message.key.lane_id, message.key.nonce);
 			pallet_bridge_dispatch::Pallet::<ThisRuntime, ThisDispatchInstance>::dispatch(
 				B::BridgedChain::ID,
 				B::ThisChain::ID,
 				message_id,
-				message.data.payload.map_err(drop),
-			);
+				message.data.payload,
+				|dispatch_origin, dispatch_weight| {
+					let unadjusted_weight_fee =
+						<ThisRuntime as pallet_transaction_payment::Config>::WeightToFee::calc(&dispatch_weight);
+					let fee_multiplier = pallet_transaction_payment::Pallet::<ThisRuntime>::next_fee_multiplier();
+					let adjusted_weight_fee = fee_multiplier.saturating_mul_int(unadjusted_weight_fee);
+					if !adjusted_weight_fee.is_zero() {
+						ThisCurrency::transfer(
+							dispatch_origin,
+							relayer_account,
+							adjusted_weight_fee,
+							ExistenceRequirement::AllowDeath,
+						)
+						.map_err(drop)
+					} else {
+						Ok(())
+					}
+				},
+			)
 		}
 	}
 
@@ -509,14 +920,30 @@ pub mod target {
 	pub fn verify_messages_proof<B: MessageBridge, ThisRuntime, GrandpaInstance: 'static>(
 		proof: FromBridgedChainMessagesProof<HashOf<BridgedChain<B>>>,
 		messages_count: u32,
-	) -> Result<ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, &'static str>
+	) -> Result<ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, ProofRejectionReason>
 	where
 		ThisRuntime: pallet_bridge_grandpa::Config<GrandpaInstance>,
 		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
 		HashOf<BridgedChain<B>>:
 			Into<bp_runtime::HashOf<<ThisRuntime as pallet_bridge_grandpa::Config<GrandpaInstance>>::BridgedChain>>,
 	{
-		verify_messages_proof_with_parser::<B, _, _>(
+		verify_messages_proof_with_traversed_nodes::<B, ThisRuntime, GrandpaInstance>(proof, messages_count)
+			.map(|(messages, _)| messages)
+	}
+
+	/// Verify proof of Bridged -> This chain messages and return the number of trie nodes that
+	/// have been traversed during verification.
+	pub fn verify_messages_proof_with_traversed_nodes<B: MessageBridge, ThisRuntime, GrandpaInstance: 'static>(
+		proof: FromBridgedChainMessagesProof<HashOf<BridgedChain<B>>>,
+		messages_count: u32,
+	) -> Result<(ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, u32), ProofRejectionReason>
+	where
+		ThisRuntime: pallet_bridge_grandpa::Config<GrandpaInstance>,
+		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
+		HashOf<BridgedChain<B>>:
+			Into<bp_runtime::HashOf<<ThisRuntime as pallet_bridge_grandpa::Config<GrandpaInstance>>::BridgedChain>>,
+	{
+		verify_messages_proof_with_parser_and_traversed_nodes::<B, _, _>(
 			proof,
 			messages_count,
 			|bridged_header_hash, bridged_storage_proof| {
@@ -529,40 +956,72 @@ pub mod target {
 					storage,
 					_dummy: Default::default(),
 				})
-				.map_err(|err| MessageProofError::Custom(err.into()))
+				.map_err(storage_proof_rejection_reason::<ThisRuntime, GrandpaInstance>)
+			},
+		)
+	}
+
+	/// Verify MMR-anchored proof of Bridged -> This chain messages.
+	///
+	/// The `messages_count` argument verification (sane limits) is supposed to be made
+	/// outside of this function. This function only verifies that the proof declares exactly
+	/// `messages_count` messages.
+	pub fn verify_messages_mmr_proof<B: MessageBridge, ThisRuntime, BeefyInstance: 'static>(
+		proof: FromBridgedChainMessagesMmrProof<
+			pallet_bridge_beefy::BridgedBlockNumber<ThisRuntime, BeefyInstance>,
+			pallet_bridge_beefy::BridgedBlockHash<ThisRuntime, BeefyInstance>,
+			pallet_bridge_beefy::BridgedHeader<ThisRuntime, BeefyInstance>,
+		>,
+		messages_count: u32,
+	) -> Result<ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, ProofRejectionReason>
+	where
+		ThisRuntime: pallet_bridge_beefy::Config<BeefyInstance>,
+		ThisRuntime: pallet_bridge_messages::Config<MessagesInstanceOf<BridgedChain<B>>>,
+		pallet_bridge_beefy::BridgedBlockHash<ThisRuntime, BeefyInstance>: Into<HashOf<BridgedChain<B>>>,
+	{
+		let FromBridgedChainMessagesMmrProof {
+			commitment_block_number,
+			mmr_leaf,
+			mmr_proof,
+			bridged_header,
+			storage_proof,
+			lane,
+			nonces_start,
+			nonces_end,
+		} = proof;
+
+		verify_messages_proof_with_parser::<B, _, _>(
+			FromBridgedChainMessagesProof {
+				bridged_header_hash: bridged_header.hash().into(),
+				storage_proof,
+				lane,
+				nonces_start,
+				nonces_end,
+			},
+			messages_count,
+			|_, bridged_storage_proof| {
+				pallet_bridge_beefy::Pallet::<ThisRuntime, BeefyInstance>::parse_finalized_storage_proof(
+					commitment_block_number,
+					mmr_leaf,
+					mmr_proof,
+					bridged_header,
+					StorageProof::new(bridged_storage_proof),
+					|storage_adapter| storage_adapter,
+				)
+				.map(|storage| StorageProofCheckerAdapter::<_, B, ThisRuntime> {
+					storage,
+					_dummy: Default::default(),
+				})
+				.map_err(mmr_storage_proof_rejection_reason::<ThisRuntime, BeefyInstance>)
 			},
 		)
-		.map_err(Into::into)
-	}
-
-	#[derive(Debug, PartialEq)]
-	pub(crate) enum MessageProofError {
-		Empty,
-		MessagesCountMismatch,
-		MissingRequiredMessage,
-		FailedToDecodeMessage,
-		FailedToDecodeOutboundLaneState,
-		Custom(&'static str),
-	}
-
-	impl From<MessageProofError> for &'static str {
-		fn from(err: MessageProofError) -> &'static str {
-			match err {
-				MessageProofError::Empty => "Messages proof is empty",
-				MessageProofError::MessagesCountMismatch => "Declared messages count doesn't match actual value",
-				MessageProofError::MissingRequiredMessage => "Message is missing from the proof",
-				MessageProofError::FailedToDecodeMessage => "Failed to decode message from the proof",
-				MessageProofError::FailedToDecodeOutboundLaneState => {
-					"Failed to decode outbound lane data from the proof"
-				}
-				MessageProofError::Custom(err) => err,
-			}
-		}
 	}
 
 	pub(crate) trait MessageProofParser {
 		fn read_raw_outbound_lane_data(&self, lane_id: &LaneId) -> Option<Vec<u8>>;
 		fn read_raw_message(&self, message_key: &MessageKey) -> Option<Vec<u8>>;
+		fn traversed_nodes_count(&self) -> u32;
+		fn ensure_no_unused_nodes(self) -> Result<(), ProofRejectionReason>;
 	}
 
 	struct StorageProofCheckerAdapter<H: Hasher, B, ThisRuntime> {
@@ -592,6 +1051,16 @@ pub mod target {
 			>(&message_key.lane_id, message_key.nonce);
 			self.storage.read_value(storage_message_key.0.as_ref()).ok()?
 		}
+
+		fn traversed_nodes_count(&self) -> u32 {
+			self.storage.traversed_nodes_count() as u32
+		}
+
+		fn ensure_no_unused_nodes(self) -> Result<(), ProofRejectionReason> {
+			self.storage
+				.ensure_no_unused_nodes()
+				.map_err(|_| ProofRejectionReason::ExtraProofNodes)
+		}
 	}
 
 	/// Verify proof of Bridged -> This chain messages using given message proof parser.
@@ -599,9 +1068,24 @@ pub mod target {
 		proof: FromBridgedChainMessagesProof<HashOf<BridgedChain<B>>>,
 		messages_count: u32,
 		build_parser: BuildParser,
-	) -> Result<ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, MessageProofError>
+	) -> Result<ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, ProofRejectionReason>
+	where
+		BuildParser: FnOnce(HashOf<BridgedChain<B>>, RawStorageProof) -> Result<Parser, ProofRejectionReason>,
+		Parser: MessageProofParser,
+	{
+		verify_messages_proof_with_parser_and_traversed_nodes::<B, _, _>(proof, messages_count, build_parser)
+			.map(|(messages, _)| messages)
+	}
+
+	/// Verify proof of Bridged -> This chain messages using given message proof parser and return
+	/// the number of trie nodes that have been traversed during verification.
+	pub(crate) fn verify_messages_proof_with_parser_and_traversed_nodes<B: MessageBridge, BuildParser, Parser>(
+		proof: FromBridgedChainMessagesProof<HashOf<BridgedChain<B>>>,
+		messages_count: u32,
+		build_parser: BuildParser,
+	) -> Result<(ProvedMessages<Message<BalanceOf<BridgedChain<B>>>>, u32), ProofRejectionReason>
 	where
-		BuildParser: FnOnce(HashOf<BridgedChain<B>>, RawStorageProof) -> Result<Parser, MessageProofError>,
+		BuildParser: FnOnce(HashOf<BridgedChain<B>>, RawStorageProof) -> Result<Parser, ProofRejectionReason>,
 		Parser: MessageProofParser,
 	{
 		let FromBridgedChainMessagesProof {
@@ -618,7 +1102,7 @@ pub mod target {
 			// (this bounds maximal capacity of messages vec below)
 			let messages_in_the_proof = nonces_difference.saturating_add(1);
 			if messages_in_the_proof != MessageNonce::from(messages_count) {
-				return Err(MessageProofError::MessagesCountMismatch);
+				return Err(ProofRejectionReason::MessagesCountMismatch);
 			}
 
 			messages_in_the_proof
@@ -637,9 +1121,9 @@ pub mod target {
 			let message_key = MessageKey { lane_id: lane, nonce };
 			let raw_message_data = parser
 				.read_raw_message(&message_key)
-				.ok_or(MessageProofError::MissingRequiredMessage)?;
-			let message_data = MessageData::<BalanceOf<BridgedChain<B>>>::decode(&mut &raw_message_data[..])
-				.map_err(|_| MessageProofError::FailedToDecodeMessage)?;
+				.ok_or(ProofRejectionReason::MissingMessage)?;
+			let message_data = MessageData::<BalanceOf<BridgedChain<B>>>::decode_all(&raw_message_data[..])
+				.map_err(|_| ProofRejectionReason::FailedToDecodeMessage)?;
 			messages.push(Message {
 				key: message_key,
 				data: message_data,
@@ -655,21 +1139,25 @@ pub mod target {
 		let raw_outbound_lane_data = parser.read_raw_outbound_lane_data(&lane);
 		if let Some(raw_outbound_lane_data) = raw_outbound_lane_data {
 			proved_lane_messages.lane_state = Some(
-				OutboundLaneData::decode(&mut &raw_outbound_lane_data[..])
-					.map_err(|_| MessageProofError::FailedToDecodeOutboundLaneState)?,
+				OutboundLaneData::decode_all(&raw_outbound_lane_data[..])
+					.map_err(|_| ProofRejectionReason::FailedToDecodeLaneState)?,
 			);
 		}
 
 		// Now we may actually check if the proof is empty or not.
 		if proved_lane_messages.lane_state.is_none() && proved_lane_messages.messages.is_empty() {
-			return Err(MessageProofError::Empty);
+			return Err(ProofRejectionReason::Empty);
 		}
 
+		// All required values are read => there must be no unused nodes in the proof.
+		let traversed_nodes = parser.traversed_nodes_count();
+		parser.ensure_no_unused_nodes()?;
+
 		// We only support single lane messages in this schema
 		let mut proved_messages = ProvedMessages::new();
 		proved_messages.insert(lane, proved_lane_messages);
 
-		Ok(proved_messages)
+		Ok((proved_messages, traversed_nodes))
 	}
 }
 
@@ -788,6 +1276,12 @@ mod tests {
 				}
 			}
 
+			impl CheckedSub for $name {
+				fn checked_sub(&self, other: &Self) -> Option<Self> {
+					self.0.checked_sub(other.0).map(Self)
+				}
+			}
+
 			impl CheckedDiv for $name {
 				fn checked_div(&self, other: &Self) -> Option<Self> {
 					self.0.checked_div(other.0).map(Self)
@@ -824,7 +1318,7 @@ mod tests {
 		type Call = ThisChainCall;
 
 		fn is_outbound_lane_enabled(lane: &LaneId) -> bool {
-			lane == TEST_LANE_ID
+			lane == TEST_LANE_ID || lane == EXPENSIVE_TEST_LANE_ID
 		}
 
 		fn maximal_pending_messages_at_outbound_lane() -> MessageNonce {
@@ -841,6 +1335,14 @@ mod tests {
 		fn transaction_payment(transaction: MessageTransaction<WeightOf<Self>>) -> BalanceOf<Self> {
 			ThisChainBalance(transaction.dispatch_weight as u32 * THIS_CHAIN_WEIGHT_TO_BALANCE_RATE as u32)
 		}
+
+		fn apply_lane_fee_multiplier(lane: &LaneId, fee: BalanceOf<Self>) -> Option<BalanceOf<Self>> {
+			if lane == EXPENSIVE_TEST_LANE_ID {
+				fee.checked_mul(&ThisChainBalance(2))
+			} else {
+				Some(fee)
+			}
+		}
 	}
 
 	impl BridgedChainWithMessages for ThisChain {
@@ -897,6 +1399,10 @@ mod tests {
 		fn transaction_payment(_transaction: MessageTransaction<WeightOf<Self>>) -> BalanceOf<Self> {
 			unreachable!()
 		}
+
+		fn apply_lane_fee_multiplier(_lane: &LaneId, _fee: BalanceOf<Self>) -> Option<BalanceOf<Self>> {
+			unreachable!()
+		}
 	}
 
 	impl BridgedChainWithMessages for BridgedChain {
@@ -935,6 +1441,7 @@ mod tests {
 			spec_version: 1,
 			weight: 100,
 			origin: bp_message_dispatch::CallOrigin::SourceRoot,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			call: ThisChainCall::Transfer.encode(),
 		}
 		.encode();
@@ -949,6 +1456,7 @@ mod tests {
 				spec_version: 1,
 				weight: 100,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				call: target::FromBridgedChainEncodedMessageCall::<OnThisChainBridge> {
 					encoded_call: ThisChainCall::Transfer.encode(),
 					_marker: PhantomData::default(),
@@ -959,6 +1467,7 @@ mod tests {
 	}
 
 	const TEST_LANE_ID: &LaneId = b"test";
+	const EXPENSIVE_TEST_LANE_ID: &LaneId = b"exps";
 	const MAXIMAL_PENDING_MESSAGES_AT_TEST_LANE: MessageNonce = 32;
 
 	fn regular_outbound_message_payload() -> source::FromThisChainMessagePayload<OnThisChainBridge> {
@@ -966,10 +1475,118 @@ mod tests {
 			spec_version: 1,
 			weight: 100,
 			origin: bp_message_dispatch::CallOrigin::SourceRoot,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			call: vec![42],
 		}
 	}
 
+	#[test]
+	fn message_fee_components_are_estimated() {
+		// payload of the This -> Bridged chain message
+		let payload = regular_outbound_message_payload();
+
+		assert_eq!(
+			source::estimate_message_fee_components::<OnThisChainBridge>(
+				&payload,
+				OnThisChainBridge::RELAYER_FEE_PERCENT,
+			),
+			Ok(source::MessageFee {
+				delivery_cost: ThisChainBalance(2600),
+				dispatch_cost: ThisChainBalance(2400),
+				relayer_reward: ThisChainBalance(500),
+			}),
+		);
+	}
+
+	#[test]
+	fn message_fee_components_exclude_dispatch_cost_if_dispatch_fee_is_paid_at_target_chain() {
+		// payload of the This -> Bridged chain message, dispatch of which is paid at the Bridged chain
+		let payload = source::FromThisChainMessagePayload::<OnThisChainBridge> {
+			dispatch_fee_payment: DispatchFeePayment::AtTargetChain,
+			..regular_outbound_message_payload()
+		};
+
+		assert_eq!(
+			source::estimate_message_fee_components::<OnThisChainBridge>(
+				&payload,
+				OnThisChainBridge::RELAYER_FEE_PERCENT,
+			),
+			Ok(source::MessageFee {
+				delivery_cost: ThisChainBalance(2600),
+				dispatch_cost: ThisChainBalance(0),
+				relayer_reward: ThisChainBalance(260),
+			}),
+		);
+	}
+
+	#[test]
+	fn custom_message_fee_calculation_is_used_by_verifier() {
+		struct ZeroFeeCalculation;
+
+		impl source::MessageFeeCalculation<OnThisChainBridge> for ZeroFeeCalculation {
+			fn calculate_message_fee(
+				_payload: &source::FromThisChainMessagePayload<OnThisChainBridge>,
+			) -> Result<source::MessageFee<ThisChainBalance>, &'static str> {
+				Ok(source::MessageFee {
+					delivery_cost: ThisChainBalance(0),
+					dispatch_cost: ThisChainBalance(0),
+					relayer_reward: ThisChainBalance(0),
+				})
+			}
+		}
+
+		assert!(
+			source::FromThisChainMessageVerifier::<OnThisChainBridge, ZeroFeeCalculation>::verify_message(
+				&Sender::Root,
+				&ThisChainBalance(0),
+				TEST_LANE_ID,
+				&test_lane_outbound_data(),
+				&regular_outbound_message_payload(),
+			)
+			.is_ok()
+		);
+	}
+
+	#[test]
+	fn lane_fee_multiplier_is_applied_to_message_fee() {
+		const EXPECTED_MINIMAL_FEE: u32 = 11000;
+
+		// payload of the This -> Bridged chain message
+		let payload = regular_outbound_message_payload();
+
+		// fee at the expensive lane is multiplied by 2
+		assert_eq!(
+			source::estimate_message_dispatch_and_delivery_fee::<OnThisChainBridge>(
+				EXPENSIVE_TEST_LANE_ID,
+				&payload,
+				OnThisChainBridge::RELAYER_FEE_PERCENT,
+			),
+			Ok(ThisChainBalance(EXPECTED_MINIMAL_FEE)),
+		);
+
+		// and the verifier checks the multiplied fee
+		assert_eq!(
+			source::FromThisChainMessageVerifier::<OnThisChainBridge>::verify_message(
+				&Sender::Root,
+				&ThisChainBalance(EXPECTED_MINIMAL_FEE - 1),
+				EXPENSIVE_TEST_LANE_ID,
+				&test_lane_outbound_data(),
+				&payload,
+			),
+			Err(source::TOO_LOW_FEE)
+		);
+		assert!(
+			source::FromThisChainMessageVerifier::<OnThisChainBridge>::verify_message(
+				&Sender::Root,
+				&ThisChainBalance(EXPECTED_MINIMAL_FEE),
+				EXPENSIVE_TEST_LANE_ID,
+				&test_lane_outbound_data(),
+				&payload,
+			)
+			.is_ok(),
+		);
+	}
+
 	#[test]
 	fn message_fee_is_checked_by_verifier() {
 		const EXPECTED_MINIMAL_FEE: u32 = 5500;
@@ -980,6 +1597,7 @@ mod tests {
 		// let's check if estimation matching hardcoded value
 		assert_eq!(
 			source::estimate_message_dispatch_and_delivery_fee::<OnThisChainBridge>(
+				TEST_LANE_ID,
 				&payload,
 				OnThisChainBridge::RELAYER_FEE_PERCENT,
 			),
@@ -1016,6 +1634,7 @@ mod tests {
 			spec_version: 1,
 			weight: 100,
 			origin: bp_message_dispatch::CallOrigin::SourceRoot,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			call: vec![42],
 		};
 
@@ -1059,6 +1678,7 @@ mod tests {
 			spec_version: 1,
 			weight: 100,
 			origin: bp_message_dispatch::CallOrigin::SourceAccount(ThisChainAccountId(1)),
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			call: vec![42],
 		};
 
@@ -1126,6 +1746,7 @@ mod tests {
 				spec_version: 1,
 				weight: 5,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				call: vec![1, 2, 3, 4, 5, 6],
 			},)
 			.is_err()
@@ -1141,6 +1762,7 @@ mod tests {
 				spec_version: 1,
 				weight: BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT + 1,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				call: vec![1, 2, 3, 4, 5, 6],
 			},)
 			.is_err()
@@ -1156,6 +1778,7 @@ mod tests {
 				spec_version: 1,
 				weight: BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				call: vec![0; source::maximal_message_size::<OnThisChainBridge>() as usize + 1],
 			},)
 			.is_err()
@@ -1171,12 +1794,84 @@ mod tests {
 				spec_version: 1,
 				weight: BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT,
 				origin: bp_message_dispatch::CallOrigin::SourceRoot,
+				dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 				call: vec![0; source::maximal_message_size::<OnThisChainBridge>() as _],
 			},),
 			Ok(()),
 		);
 	}
 
+	/// Proof of messages delivery that is not a storage proof (e.g. it is an EVM event proof).
+	#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq)]
+	struct TestEventProof {
+		lane: LaneId,
+		last_delivered_nonce: MessageNonce,
+	}
+
+	impl Size for TestEventProof {
+		fn size_hint(&self) -> u32 {
+			0
+		}
+	}
+
+	struct TestEventProofVerifier;
+
+	impl MessagesDeliveryProofVerifier<ThisChainAccountId> for TestEventProofVerifier {
+		type Error = ProofRejectionReason;
+		type MessagesDeliveryProof = TestEventProof;
+		type MessagesDeliveryBatchProof = TestEventProof;
+
+		fn verify_messages_delivery_proof(
+			proof: TestEventProof,
+		) -> Result<(LaneId, InboundLaneData<ThisChainAccountId>), Self::Error> {
+			Ok((
+				proof.lane,
+				InboundLaneData {
+					relayers: vec![(1, proof.last_delivered_nonce, ThisChainAccountId(1))]
+						.into_iter()
+						.collect(),
+					..Default::default()
+				},
+			))
+		}
+
+		fn verify_messages_delivery_batch_proof(
+			proof: TestEventProof,
+		) -> Result<Vec<(LaneId, InboundLaneData<ThisChainAccountId>)>, Self::Error> {
+			Ok(vec![Self::verify_messages_delivery_proof(proof)?])
+		}
+	}
+
+	type TestTargetHeaderChain = bp_messages::source_chain::TargetHeaderChainAdapter<
+		source::FromThisChainMessagePayloadVerifier<OnThisChainBridge>,
+		TestEventProofVerifier,
+	>;
+
+	#[test]
+	fn target_header_chain_adapter_works_with_non_storage_proofs() {
+		use bp_messages::source_chain::TargetHeaderChain;
+
+		let message = source::FromThisChainMessagePayload::<OnThisChainBridge> {
+			spec_version: 1,
+			weight: BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT,
+			origin: bp_message_dispatch::CallOrigin::SourceRoot,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
+			call: vec![0; source::maximal_message_size::<OnThisChainBridge>() as usize + 1],
+		};
+		assert!(<TestTargetHeaderChain as TargetHeaderChain<_, ThisChainAccountId>>::verify_message(&message).is_err());
+
+		let (lane, inbound_lane_data) = <TestTargetHeaderChain as TargetHeaderChain<
+			source::FromThisChainMessagePayload<OnThisChainBridge>,
+			ThisChainAccountId,
+		>>::verify_messages_delivery_proof(TestEventProof {
+			lane: *b"test",
+			last_delivered_nonce: 10,
+		})
+		.unwrap();
+		assert_eq!(lane, *b"test");
+		assert_eq!(inbound_lane_data.last_delivered_nonce(), 10);
+	}
+
 	#[derive(Debug)]
 	struct TestMessageProofParser {
 		failing: bool,
@@ -1208,6 +1903,63 @@ mod tests {
 				None
 			}
 		}
+
+		fn traversed_nodes_count(&self) -> u32 {
+			// every value is assumed to be stored in a single leaf node, below the shared root node
+			1 + self.messages.clone().count() as u32 + self.outbound_lane_data.is_some() as u32
+		}
+
+		fn ensure_no_unused_nodes(self) -> Result<(), ProofRejectionReason> {
+			Ok(())
+		}
+	}
+
+	/// Parser that has some unused nodes in the proof.
+	struct PaddedMessageProofParser(TestMessageProofParser);
+
+	impl target::MessageProofParser for PaddedMessageProofParser {
+		fn read_raw_outbound_lane_data(&self, lane_id: &LaneId) -> Option<Vec<u8>> {
+			self.0.read_raw_outbound_lane_data(lane_id)
+		}
+
+		fn read_raw_message(&self, message_key: &MessageKey) -> Option<Vec<u8>> {
+			self.0.read_raw_message(message_key)
+		}
+
+		fn traversed_nodes_count(&self) -> u32 {
+			self.0.traversed_nodes_count()
+		}
+
+		fn ensure_no_unused_nodes(self) -> Result<(), ProofRejectionReason> {
+			Err(ProofRejectionReason::ExtraProofNodes)
+		}
+	}
+
+	/// Parser that appends unknown field to every value of the wrapped parser.
+	struct ExtendedFormatMessageProofParser(TestMessageProofParser);
+
+	impl target::MessageProofParser for ExtendedFormatMessageProofParser {
+		fn read_raw_outbound_lane_data(&self, lane_id: &LaneId) -> Option<Vec<u8>> {
+			self.0.read_raw_outbound_lane_data(lane_id).map(|mut data| {
+				data.push(0);
+				data
+			})
+		}
+
+		fn read_raw_message(&self, message_key: &MessageKey) -> Option<Vec<u8>> {
+			self.0.read_raw_message(message_key).map(|mut data| {
+				data.push(0);
+				data
+			})
+		}
+
+		fn traversed_nodes_count(&self) -> u32 {
+			self.0.traversed_nodes_count()
+		}
+
+		fn ensure_no_unused_nodes(self) -> Result<(), ProofRejectionReason> {
+			self.0.ensure_no_unused_nodes()
+		}
 	}
 
 	#[allow(clippy::reversed_empty_ranges)]
@@ -1233,7 +1985,7 @@ mod tests {
 				5,
 				|_, _| unreachable!(),
 			),
-			Err(target::MessageProofError::MessagesCountMismatch),
+			Err(ProofRejectionReason::MessagesCountMismatch),
 		);
 	}
 
@@ -1245,7 +1997,7 @@ mod tests {
 				15,
 				|_, _| unreachable!(),
 			),
-			Err(target::MessageProofError::MessagesCountMismatch),
+			Err(ProofRejectionReason::MessagesCountMismatch),
 		);
 	}
 
@@ -1255,9 +2007,9 @@ mod tests {
 			target::verify_messages_proof_with_parser::<OnThisChainBridge, _, TestMessageProofParser>(
 				messages_proof(10),
 				10,
-				|_, _| Err(target::MessageProofError::Custom("test")),
+				|_, _| Err(ProofRejectionReason::Other("test")),
 			),
-			Err(target::MessageProofError::Custom("test")),
+			Err(ProofRejectionReason::Other("test")),
 		);
 	}
 
@@ -1271,7 +2023,7 @@ mod tests {
 					outbound_lane_data: None,
 				}
 			),),
-			Err(target::MessageProofError::MissingRequiredMessage),
+			Err(ProofRejectionReason::MissingMessage),
 		);
 	}
 
@@ -1285,7 +2037,38 @@ mod tests {
 					outbound_lane_data: None,
 				}
 			),),
-			Err(target::MessageProofError::FailedToDecodeMessage),
+			Err(ProofRejectionReason::FailedToDecodeMessage),
+		);
+	}
+
+	#[test]
+	fn message_proof_is_rejected_if_it_has_unused_nodes() {
+		assert_eq!(
+			target::verify_messages_proof_with_parser::<OnThisChainBridge, _, _>(messages_proof(10), 10, |_, _| Ok(
+				PaddedMessageProofParser(TestMessageProofParser {
+					failing: false,
+					messages: 1..=10,
+					outbound_lane_data: None,
+				})
+			),),
+			Err(ProofRejectionReason::ExtraProofNodes),
+		);
+	}
+
+	#[test]
+	fn message_proof_returns_number_of_traversed_nodes() {
+		assert_eq!(
+			target::verify_messages_proof_with_parser_and_traversed_nodes::<OnThisChainBridge, _, _>(
+				messages_proof(10),
+				10,
+				|_, _| Ok(TestMessageProofParser {
+					failing: false,
+					messages: 1..=10,
+					outbound_lane_data: None,
+				}),
+			)
+			.map(|(_, traversed_nodes)| traversed_nodes),
+			Ok(11),
 		);
 	}
 
@@ -1300,10 +2083,39 @@ mod tests {
 						oldest_unpruned_nonce: 1,
 						latest_received_nonce: 1,
 						latest_generated_nonce: 1,
+						..Default::default()
 					}),
 				}
 			),),
-			Err(target::MessageProofError::FailedToDecodeOutboundLaneState),
+			Err(ProofRejectionReason::FailedToDecodeLaneState),
+		);
+	}
+
+	#[test]
+	fn message_proof_is_rejected_if_message_has_unknown_fields() {
+		assert_eq!(
+			target::verify_messages_proof_with_parser::<OnThisChainBridge, _, _>(messages_proof(10), 10, |_, _| Ok(
+				ExtendedFormatMessageProofParser(TestMessageProofParser {
+					failing: false,
+					messages: 1..=10,
+					outbound_lane_data: None,
+				})
+			),),
+			Err(ProofRejectionReason::FailedToDecodeMessage),
+		);
+	}
+
+	#[test]
+	fn message_proof_is_rejected_if_outbound_lane_state_has_unknown_fields() {
+		assert_eq!(
+			target::verify_messages_proof_with_parser::<OnThisChainBridge, _, _>(messages_proof(0), 0, |_, _| Ok(
+				ExtendedFormatMessageProofParser(TestMessageProofParser {
+					failing: false,
+					messages: no_messages_range(),
+					outbound_lane_data: Some(Default::default()),
+				})
+			),),
+			Err(ProofRejectionReason::FailedToDecodeLaneState),
 		);
 	}
 
@@ -1317,7 +2129,7 @@ mod tests {
 					outbound_lane_data: None,
 				}
 			),),
-			Err(target::MessageProofError::Empty),
+			Err(ProofRejectionReason::Empty),
 		);
 	}
 
@@ -1332,6 +2144,7 @@ mod tests {
 						oldest_unpruned_nonce: 1,
 						latest_received_nonce: 1,
 						latest_generated_nonce: 1,
+						..Default::default()
 					}),
 				}
 			),),
@@ -1342,6 +2155,7 @@ mod tests {
 						oldest_unpruned_nonce: 1,
 						latest_received_nonce: 1,
 						latest_generated_nonce: 1,
+						..Default::default()
 					}),
 					messages: Vec::new(),
 				},
@@ -1362,6 +2176,7 @@ mod tests {
 						oldest_unpruned_nonce: 1,
 						latest_received_nonce: 1,
 						latest_generated_nonce: 1,
+						..Default::default()
 					}),
 				}
 			),),
@@ -1372,6 +2187,7 @@ mod tests {
 						oldest_unpruned_nonce: 1,
 						latest_received_nonce: 1,
 						latest_generated_nonce: 1,
+						..Default::default()
 					}),
 					messages: vec![Message {
 						key: MessageKey {
@@ -1403,10 +2219,11 @@ mod tests {
 						oldest_unpruned_nonce: 1,
 						latest_received_nonce: 1,
 						latest_generated_nonce: 1,
+						..Default::default()
 					}),
 				}),
 			),
-			Err(target::MessageProofError::MessagesCountMismatch),
+			Err(ProofRejectionReason::MessagesCountMismatch),
 		);
 	}
 
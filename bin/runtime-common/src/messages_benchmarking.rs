@@ -24,15 +24,15 @@ use crate::messages::{
 	BridgedChain, HashOf, MessageBridge, ThisChain,
 };
 
-use bp_messages::{LaneId, MessageData, MessageKey, MessagePayload};
+use bp_messages::{LaneId, MessageData, MessageKey, MessagePayload, VersionedMessagePayload};
 use codec::Encode;
 use ed25519_dalek::{PublicKey, SecretKey, Signer, KEYPAIR_LENGTH, SECRET_KEY_LENGTH};
 use frame_support::weights::Weight;
 use pallet_bridge_messages::benchmarking::{MessageDeliveryProofParams, MessageProofParams, ProofSize};
 use sp_core::Hasher;
 use sp_runtime::traits::Header;
-use sp_std::prelude::*;
-use sp_trie::{record_all_keys, trie_types::TrieDBMut, Layout, MemoryDB, Recorder, TrieMut};
+use sp_std::{collections::btree_set::BTreeSet, prelude::*};
+use sp_trie::{read_trie_value_with, record_all_keys, trie_types::TrieDBMut, Layout, MemoryDB, Recorder, TrieMut};
 
 /// Generate ed25519 signature to be used in `pallet_brdige_call_dispatch::CallOrigin::TargetAccount`.
 ///
@@ -100,7 +100,7 @@ where
 			};
 			let message_data = MessageData {
 				fee: BalanceOf::<BridgedChain<B>>::from(0),
-				payload: message_payload.clone(),
+				payload: VersionedMessagePayload::new(message_payload.clone()).encode(),
 			};
 			let storage_key = make_bridged_message_storage_key(message_key);
 			trie.insert(&storage_key, &message_data.encode())
@@ -109,23 +109,19 @@ where
 			storage_keys.push(storage_key);
 		}
 
-		// insert outbound lane state
+		// insert outbound lane state (the key is always read by the runtime, even if the value is missing)
+		let storage_key = make_bridged_outbound_lane_data_key(params.lane);
 		if let Some(outbound_lane_data) = params.outbound_lane_data {
-			let storage_key = make_bridged_outbound_lane_data_key(params.lane);
 			trie.insert(&storage_key, &outbound_lane_data.encode())
 				.map_err(|_| "TrieMut::insert has failed")
 				.expect("TrieMut::insert should not fail in benchmarks");
-			storage_keys.push(storage_key);
 		}
+		storage_keys.push(storage_key);
 	}
 	root = grow_trie(root, &mut mdb, params.size);
 
 	// generate storage proof to be delivered to This chain
-	let mut proof_recorder = Recorder::<H::Out>::new();
-	record_all_keys::<Layout<H>, _>(&mdb, &root, &mut proof_recorder)
-		.map_err(|_| "record_all_keys has failed")
-		.expect("record_all_keys should not fail in benchmarks");
-	let storage_proof = proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect();
+	let storage_proof = record_proof(&mdb, &root, &storage_keys);
 
 	// prepare Bridged chain header and insert it into the Substrate pallet
 	let bridged_header = make_bridged_header(root);
@@ -174,11 +170,7 @@ where
 	root = grow_trie(root, &mut mdb, params.size);
 
 	// generate storage proof to be delivered to This chain
-	let mut proof_recorder = Recorder::<H::Out>::new();
-	record_all_keys::<Layout<H>, _>(&mdb, &root, &mut proof_recorder)
-		.map_err(|_| "record_all_keys has failed")
-		.expect("record_all_keys should not fail in benchmarks");
-	let storage_proof = proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect();
+	let storage_proof = record_proof(&mdb, &root, &[storage_key]);
 
 	// prepare Bridged chain header and insert it into the Substrate pallet
 	let bridged_header = make_bridged_header(root);
@@ -192,6 +184,28 @@ where
 	}
 }
 
+/// Record proof of given storage keys.
+///
+/// Storage proofs with unused nodes are rejected by the runtime, so only nodes that are traversed
+/// while reading given keys are included in the proof.
+fn record_proof<H: Hasher>(mdb: &MemoryDB<H>, root: &H::Out, storage_keys: &[Vec<u8>]) -> Vec<Vec<u8>> {
+	let mut proof_recorder = Recorder::<H::Out>::new();
+	for storage_key in storage_keys {
+		read_trie_value_with::<Layout<H>, _, _>(mdb, root, storage_key, &mut proof_recorder)
+			.map_err(|_| "read_trie_value_with has failed")
+			.expect("read_trie_value_with should not fail in benchmarks");
+	}
+
+	// the same node may be traversed several times, but duplicate nodes are rejected by the runtime
+	proof_recorder
+		.drain()
+		.into_iter()
+		.map(|n| n.data.to_vec())
+		.collect::<BTreeSet<_>>()
+		.into_iter()
+		.collect()
+}
+
 /// Populate trie with dummy keys+values until trie has at least given size.
 fn grow_trie<H: Hasher>(mut root: H::Out, mdb: &mut MemoryDB<H>, trie_size: ProofSize) -> H::Out {
 	let (iterations, leaf_size, minimal_trie_size) = match trie_size {
@@ -0,0 +1,209 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signed extensions that allow relayers to be reimbursed for messages delivery and delivery
+//! confirmation transactions.
+//!
+//! The fee, that is paid by the confirmation transaction submitter, is stored in the messages
+//! pallet storage before the transaction is dispatched. The messages pallet then reimburses it
+//! from the fees of messages, confirmed by this transaction (see `ConfirmationTransactionFee`
+//! storage item of the pallet).
+//!
+//! The fee, that is paid by the delivery transaction submitter, is refunded from the relayers pot
+//! (see `Pallet::relayers_pot_account_id`) right after the transaction is dispatched, if it has
+//! delivered at least one new message. If the pot has no funds, the fee is not refunded.
+
+use bp_messages::source_chain::MessageDeliveryAndDispatchPayment;
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::{CallableCallFor, DispatchInfo, PostDispatchInfo},
+	traits::IsSubType,
+	RuntimeDebug,
+};
+use pallet_bridge_messages::{
+	Call as MessagesCall, ConfirmationTransactionFee, DeliveredMessagesInTransaction, Instance,
+};
+use pallet_transaction_payment::BalanceOf;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension, Zero},
+	transaction_validity::TransactionValidityError,
+	DispatchResult,
+};
+use sp_std::{fmt::Debug, marker::PhantomData};
+
+/// Signed extension that stores fee of messages delivery confirmation transaction, so that the
+/// messages pallet may reimburse it to the confirmation relayer.
+///
+/// The tip is not reimbursed - it is the relayer choice to pay it. Fees of all other transactions
+/// are ignored.
+#[derive(Decode, Encode, Clone, Eq, PartialEq, RuntimeDebug)]
+pub struct RefundConfirmationTransactionFee<Runtime, I>(PhantomData<(Runtime, I)>);
+
+impl<Runtime, I> RefundConfirmationTransactionFee<Runtime, I> {
+	/// Create new signed extension.
+	pub fn new() -> Self {
+		RefundConfirmationTransactionFee(PhantomData)
+	}
+}
+
+impl<Runtime, I> Default for RefundConfirmationTransactionFee<Runtime, I> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<Runtime, I> SignedExtension for RefundConfirmationTransactionFee<Runtime, I>
+where
+	Runtime: pallet_bridge_messages::Config<I> + pallet_transaction_payment::Config + Send + Sync,
+	Runtime::Call: Dispatchable<Info = DispatchInfo>
+		+ IsSubType<CallableCallFor<pallet_bridge_messages::Pallet<Runtime, I>, Runtime>>,
+	BalanceOf<Runtime>: Into<Runtime::OutboundMessageFee>,
+	I: Instance + Clone + Eq + Debug + Send + Sync,
+{
+	const IDENTIFIER: &'static str = "RefundConfirmationTransactionFee";
+	type AccountId = Runtime::AccountId;
+	type Call = Runtime::Call;
+	type AdditionalSigned = ();
+	type Pre = bool;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		match call.is_sub_type() {
+			Some(MessagesCall::receive_messages_delivery_proof(..)) => {
+				// `receive_messages_delivery_proof` never refunds weight, so the fee computed here
+				// is the fee that is actually paid (minus tip)
+				let fee = pallet_transaction_payment::Pallet::<Runtime>::compute_fee(len as u32, info, Zero::zero());
+				ConfirmationTransactionFee::<Runtime, I>::put(fee.into());
+				Ok(true)
+			}
+			_ => Ok(false),
+		}
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		_info: &DispatchInfoOf<Self::Call>,
+		_post_info: &PostDispatchInfoOf<Self::Call>,
+		_len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		if pre {
+			ConfirmationTransactionFee::<Runtime, I>::kill();
+		}
+		Ok(())
+	}
+}
+
+/// Signed extension that refunds fee of messages delivery transaction from the relayers pot.
+///
+/// The tip is not refunded - it is the relayer choice to pay it. Fees of transactions that are
+/// not delivering any new messages (e.g. if other relayer has delivered the same messages before)
+/// are not refunded. Fees of all other transactions are ignored.
+#[derive(Decode, Encode, Clone, Eq, PartialEq, RuntimeDebug)]
+pub struct RefundDeliveryTransactionFee<Runtime, I>(PhantomData<(Runtime, I)>);
+
+impl<Runtime, I> RefundDeliveryTransactionFee<Runtime, I> {
+	/// Create new signed extension.
+	pub fn new() -> Self {
+		RefundDeliveryTransactionFee(PhantomData)
+	}
+}
+
+impl<Runtime, I> Default for RefundDeliveryTransactionFee<Runtime, I> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<Runtime, I> SignedExtension for RefundDeliveryTransactionFee<Runtime, I>
+where
+	Runtime: pallet_bridge_messages::Config<I> + pallet_transaction_payment::Config + Send + Sync,
+	Runtime::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>
+		+ IsSubType<CallableCallFor<pallet_bridge_messages::Pallet<Runtime, I>, Runtime>>,
+	BalanceOf<Runtime>: Into<Runtime::OutboundMessageFee>,
+	I: Instance + Clone + Eq + Debug + Send + Sync,
+{
+	const IDENTIFIER: &'static str = "RefundDeliveryTransactionFee";
+	type AccountId = Runtime::AccountId;
+	type Call = Runtime::Call;
+	type AdditionalSigned = ();
+	type Pre = Option<Runtime::AccountId>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		match call.is_sub_type() {
+			Some(MessagesCall::receive_messages_proof(..)) => {
+				DeliveredMessagesInTransaction::<I>::put(0);
+				Ok(Some(who.clone()))
+			}
+			_ => Ok(None),
+		}
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let relayer = match pre {
+			Some(relayer) => relayer,
+			None => return Ok(()),
+		};
+
+		let delivered_messages = DeliveredMessagesInTransaction::<I>::take().unwrap_or(0);
+		if result.is_err() || delivered_messages == 0 {
+			return Ok(());
+		}
+
+		// `receive_messages_proof` refunds unspent weight, so we are refunding the fee that has
+		// actually been paid (minus tip)
+		let fee = pallet_transaction_payment::Pallet::<Runtime>::compute_actual_fee(
+			len as u32,
+			info,
+			post_info,
+			Zero::zero(),
+		);
+		// if the pot has no funds, the relayer is simply not refunded - the transaction itself
+		// must not fail because of that
+		let _ = Runtime::MessageDeliveryAndDispatchPayment::refund_delivery_and_dispatch_fee(
+			&relayer,
+			&fee.into(),
+			&pallet_bridge_messages::Pallet::<Runtime, I>::relayers_pot_account_id(),
+		);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,192 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! GRANDPA justification verifier fuzzer.
+
+#![warn(missing_docs)]
+
+use bp_header_chain::justification::{
+	optimize_justification, verify_justification_with_mode, GrandpaJustification, VerificationMode,
+};
+use bp_test_utils::{accounts, make_malformed_justification, JustificationGeneratorParams, JustificationMalformation};
+use codec::Decode;
+use finality_grandpa::voter_set::VoterSet;
+use honggfuzz::fuzz;
+use sp_finality_grandpa::{AuthorityId, SetId};
+use sp_runtime::traits::Header as HeaderT;
+
+type TestHeader = sp_runtime::testing::Header;
+type TestNumber = <TestHeader as HeaderT>::Number;
+type TestHash = <TestHeader as HeaderT>::Hash;
+
+/// Maximal number of authorities in the generated justification.
+const MAX_AUTHORITIES: u8 = 8;
+/// Maximal number of votes ancestries in the generated justification.
+const MAX_ANCESTORS: u8 = 32;
+
+/// Fuzzer input reader. When the input is exhausted, zeroes are returned.
+struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+	fn read_u8(&mut self) -> u8 {
+		match self.0.split_first() {
+			Some((byte, rest)) => {
+				self.0 = rest;
+				*byte
+			}
+			None => 0,
+		}
+	}
+
+	fn read_u32(&mut self) -> u32 {
+		u32::from_le_bytes([self.read_u8(), self.read_u8(), self.read_u8(), self.read_u8()])
+	}
+}
+
+/// Read justification generator parameters and (optional) malformation from the fuzzer input.
+fn read_justification_params(
+	input: &mut Input,
+) -> (
+	JustificationGeneratorParams<TestHeader>,
+	Option<JustificationMalformation>,
+) {
+	let authorities_count = 1 + input.read_u8() % MAX_AUTHORITIES;
+	let authorities = accounts(authorities_count as u16)
+		.into_iter()
+		.map(|account| (account, 1 + input.read_u8() as u64 % 4))
+		.collect();
+	let header = bp_test_utils::test_header::<TestHeader>(1 + input.read_u8() as TestNumber);
+	let round = input.read_u8() as u64;
+	let set_id = input.read_u8() as SetId;
+	let forks = 1 + (input.read_u8() % authorities_count) as u32;
+	let mut ancestors = (input.read_u8() % MAX_ANCESTORS) as u32;
+
+	let malformation = match input.read_u8() % 8 {
+		0 => Some(JustificationMalformation::WrongRound),
+		1 => Some(JustificationMalformation::ForgedSignature(input.read_u32())),
+		2 => Some(JustificationMalformation::AncestorPrecommit(input.read_u32())),
+		3 => Some(JustificationMalformation::NonCanonicalAncestry(input.read_u32())),
+		4 => {
+			ancestors = ancestors.max(1);
+			Some(JustificationMalformation::MissingAncestry(input.read_u32()))
+		}
+		5 => Some(JustificationMalformation::ExtraAncestry),
+		6 => Some(JustificationMalformation::NotEnoughWeight),
+		_ => None,
+	};
+
+	(
+		JustificationGeneratorParams {
+			header,
+			round,
+			set_id,
+			authorities,
+			ancestors,
+			forks,
+		},
+		malformation,
+	)
+}
+
+/// Verify justification in all modes and check that the optimized justification (if any) is
+/// accepted in strict mode.
+///
+/// Returns results of verification in default and strict modes.
+fn verify_justification(
+	finalized_target: (TestHash, TestNumber),
+	set_id: SetId,
+	voter_set: &VoterSet<AuthorityId>,
+	justification: &GrandpaJustification<TestHeader>,
+) -> (bool, bool) {
+	let verify =
+		|mode| verify_justification_with_mode(finalized_target, set_id, voter_set, justification, mode).is_ok();
+	let is_accepted_in_default_mode = verify(VerificationMode::Default);
+	let is_accepted_in_strict_mode = verify(VerificationMode::Strict);
+	let _ = verify(VerificationMode::FilterInvalidVotes);
+
+	if let Ok(optimized_justification) =
+		optimize_justification(finalized_target, set_id, voter_set, justification.clone())
+	{
+		assert!(
+			verify_justification_with_mode(
+				finalized_target,
+				set_id,
+				voter_set,
+				&optimized_justification,
+				VerificationMode::Strict,
+			)
+			.is_ok(),
+			"Optimized justification is rejected: {:?}",
+			optimized_justification,
+		);
+	}
+
+	(is_accepted_in_default_mode, is_accepted_in_strict_mode)
+}
+
+fn run_fuzzer() {
+	fuzz!(|data: &[u8]| {
+		let mut input = Input(data);
+		let (params, malformation) = read_justification_params(&mut input);
+		log::info!(
+			"Generating justification for header {} with {} authorities, {} ancestors, {} forks and {:?} malformation",
+			params.header.number,
+			params.authorities.len(),
+			params.ancestors,
+			params.forks,
+			malformation,
+		);
+
+		let finalized_target = (params.header.hash(), params.header.number);
+		let set_id = params.set_id;
+		let voter_set = VoterSet::new(
+			params
+				.authorities
+				.iter()
+				.map(|(account, weight)| (AuthorityId::from(*account), *weight)),
+		)
+		.expect("Generated authorities set is never empty and its weight never overflows; qed");
+		let justification = match malformation {
+			Some(malformation) => make_malformed_justification(params, malformation),
+			None => bp_test_utils::make_justification_for_header(params),
+		};
+
+		let (is_accepted_in_default_mode, is_accepted_in_strict_mode) =
+			verify_justification(finalized_target, set_id, &voter_set, &justification);
+		let is_expected_to_be_accepted = malformation.is_none();
+		assert_eq!(
+			(is_accepted_in_default_mode, is_accepted_in_strict_mode),
+			(is_expected_to_be_accepted, is_expected_to_be_accepted),
+			"Unexpected verification result of justification with {:?} malformation: {:?}",
+			malformation,
+			justification,
+		);
+
+		// rest of the input is treated as raw justification, which verification must not panic
+		if let Ok(justification) = GrandpaJustification::<TestHeader>::decode(&mut &*input.0) {
+			let finalized_target = (justification.commit.target_hash, justification.commit.target_number);
+			verify_justification(finalized_target, set_id, &voter_set, &justification);
+		}
+	})
+}
+
+fn main() {
+	env_logger::init();
+
+	loop {
+		run_fuzzer();
+	}
+}
@@ -72,6 +72,7 @@ fn run_fuzzer() {
 				Ok(Some(key_value_pair.1.clone()))
 			);
 		}
+		assert_eq!(checker.ensure_no_unused_nodes(), Ok(()));
 	})
 }
 
@@ -0,0 +1,991 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BEEFY Bridge Pallet
+//!
+//! This pallet is an on-chain BEEFY light client for Substrate based chains. It is an alternative
+//! to the GRANDPA light client (`pallet-bridge-grandpa`) for bridging with chains, where full
+//! GRANDPA justifications verification is too expensive.
+//!
+//! BEEFY validators are signing commitments to the bridged chain MMR root. Once the commitment is
+//! verified, the MMR root is stored in the pallet. It may be used later to verify that some leaf
+//! (and every leaf commits to the parent block hash) is a member of the bridged chain MMR.
+//!
+//! The pallet is responsible for tracking BEEFY validator set hand-offs. Every MMR leaf has a
+//! short representation (id, length and merkle root of addresses) of the next validator set. So
+//! when the next set starts signing commitments, the relayer provides the full list of its
+//! validators, which is then verified against this representation.
+//!
+//! Since every MMR leaf commits to the parent block hash, the pallet may be used to verify storage
+//! proofs of any (even historical) bridged chain block, given the MMR root, that has been imported
+//! at some later block.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Runtime-generated enums
+#![allow(clippy::large_enum_variant)]
+
+use crate::weights::WeightInfo;
+
+use bp_beefy::{
+	BeefyAuthoritySet, BeefyMmrLeaf, BeefyValidatorAddress, Commitment, InitializationData, MmrLeafProof, MmrRootHash,
+	SignedCommitment,
+};
+use bp_runtime::{BlockNumberOf, Chain, HashOf, HasherOf, HeaderOf};
+use frame_support::{ensure, fail};
+use frame_system::{ensure_signed, RawOrigin};
+use sp_core::H256;
+use sp_runtime::traits::{BadOrigin, CheckedAdd, Header as HeaderT, One};
+use sp_std::{convert::TryInto, prelude::*};
+
+#[cfg(test)]
+mod mock;
+
+/// Pallet containing weights for this pallet.
+pub mod weights;
+
+// Re-export in crate namespace for `construct_runtime!`
+pub use pallet::*;
+
+/// Block number of the bridged chain.
+pub type BridgedBlockNumber<T, I> = BlockNumberOf<<T as Config<I>>::BridgedChain>;
+/// Block hash of the bridged chain.
+pub type BridgedBlockHash<T, I> = HashOf<<T as Config<I>>::BridgedChain>;
+/// Hasher of the bridged chain.
+pub type BridgedBlockHasher<T, I> = HasherOf<<T as Config<I>>::BridgedChain>;
+/// Header of the bridged chain.
+pub type BridgedHeader<T, I> = HeaderOf<<T as Config<I>>::BridgedChain>;
+/// MMR leaf of the bridged chain.
+pub type BridgedMmrLeaf<T, I> = BeefyMmrLeaf<BridgedBlockNumber<T, I>, BridgedBlockHash<T, I>>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config<I: 'static = ()>: frame_system::Config {
+		/// The chain we are bridging to here.
+		type BridgedChain: Chain;
+
+		/// The upper bound on the number of requests allowed by the pallet.
+		///
+		/// A request refers to an action which writes a commitment to storage.
+		///
+		/// Once this bound is reached the pallet will not allow any dispatchables to be called
+		/// until the request count has decreased.
+		#[pallet::constant]
+		type MaxRequests: Get<u32>;
+
+		/// Maximal number of imported commitments (MMR roots) to keep in the storage.
+		///
+		/// The setting is there to prevent growing the on-chain state indefinitely. Note
+		/// the setting does not relate to block numbers - we will simply keep as much items
+		/// in the storage, so it doesn't guarantee any fixed timeframe for imported commitments.
+		#[pallet::constant]
+		type CommitmentsToKeep: Get<u32>;
+
+		/// Origin that has the same rights as the pallet owner.
+		///
+		/// It may be used to let multisig or collective manage the pallet. Root and the `PalletOwner`
+		/// account are always allowed to manage the pallet.
+		type OwnerOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Weights gathered through benchmarking.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
+
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+		fn on_initialize(_n: T::BlockNumber) -> frame_support::weights::Weight {
+			<RequestCount<T, I>>::mutate(|count| *count = count.saturating_sub(1));
+
+			(0_u64)
+				.saturating_add(T::DbWeight::get().reads(1))
+				.saturating_add(T::DbWeight::get().writes(1))
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
+		/// Verify BEEFY commitment and import the bridged chain MMR root.
+		///
+		/// The commitment must be signed by either the current, or the next validator set. In the
+		/// latter case, the `validator_set` must contain addresses of all validators of the next set.
+		/// Once the commitment of the next set is imported, it becomes the current set.
+		///
+		/// The `mmr_leaf` is the latest leaf of the MMR at the commitment block. It is used to learn
+		/// the next validator set.
+		#[pallet::weight(T::WeightInfo::submit_commitment(
+			commitment.signatures.len().try_into().unwrap_or(u32::MAX),
+			mmr_proof.items.len().try_into().unwrap_or(u32::MAX),
+		))]
+		pub fn submit_commitment(
+			origin: OriginFor<T>,
+			commitment: SignedCommitment<BridgedBlockNumber<T, I>>,
+			validator_set: Option<Vec<BeefyValidatorAddress>>,
+			mmr_leaf: BridgedMmrLeaf<T, I>,
+			mmr_proof: MmrLeafProof,
+		) -> DispatchResultWithPostInfo {
+			ensure_operational::<T, I>()?;
+			let _ = ensure_signed(origin)?;
+
+			ensure!(
+				Self::request_count() < T::MaxRequests::get(),
+				<Error<T, I>>::TooManyRequests
+			);
+
+			let best_block_number = match <BestBlockNumber<T, I>>::get() {
+				Some(best_block_number) => best_block_number,
+				None => {
+					log::error!(
+						target: "runtime::bridge-beefy",
+						"Cannot import commitment {:?} because pallet is not yet initialized",
+						commitment.commitment,
+					);
+					fail!(<Error<T, I>>::NotInitialized);
+				}
+			};
+
+			let SignedCommitment { commitment, signatures } = commitment;
+			log::trace!(target: "runtime::bridge-beefy", "Going to try and import commitment {:?}", commitment);
+
+			// We do a quick check here to ensure that our chain is making progress and isn't
+			// "travelling back in time" (which could be indicative of something bad, e.g a hard-fork).
+			ensure!(
+				commitment.block_number > best_block_number,
+				<Error<T, I>>::OldCommitment
+			);
+
+			// select the validator set that has signed the commitment
+			let current_authority_set = <CurrentAuthoritySet<T, I>>::get();
+			let current_authority_set_id = current_authority_set.id;
+			let next_authority_set = <NextAuthoritySet<T, I>>::get();
+			let (signers, is_handoff) = if commitment.validator_set_id == current_authority_set_id {
+				(current_authority_set, false)
+			} else if commitment.validator_set_id == next_authority_set.id {
+				let validators = validator_set.ok_or(<Error<T, I>>::InvalidValidatorSet)?;
+				let signers = BeefyAuthoritySet::new(validators, next_authority_set.id);
+				ensure!(
+					signers.to_next_authority_set() == next_authority_set,
+					<Error<T, I>>::InvalidValidatorSet
+				);
+				(signers, true)
+			} else {
+				fail!(<Error<T, I>>::InvalidValidatorSetId);
+			};
+
+			verify_commitment_signatures::<T, I>(&commitment, &signatures, &signers)?;
+			verify_mmr_leaf::<T, I>(&commitment, &mmr_leaf, mmr_proof)?;
+
+			if is_handoff {
+				log::info!(
+					target: "runtime::bridge-beefy",
+					"Transitioned from validator set {} to {}! New validators are: {:?}",
+					current_authority_set_id,
+					signers.id,
+					signers.validators,
+				);
+				<CurrentAuthoritySet<T, I>>::put(&signers);
+			}
+			// the leaf announces the next validator set
+			let leaf_next_authority_set = mmr_leaf.beefy_next_authority_set;
+			if leaf_next_authority_set.id > signers.id {
+				<NextAuthoritySet<T, I>>::put(leaf_next_authority_set);
+			}
+
+			<RequestCount<T, I>>::mutate(|count| *count += 1);
+			insert_commitment::<T, I>(commitment.block_number, commitment.payload);
+			log::info!(
+				target: "runtime::bridge-beefy",
+				"Succesfully imported commitment for block {:?}!",
+				commitment.block_number,
+			);
+
+			Ok(().into())
+		}
+
+		/// Bootstrap the bridge pallet with an initial block number and validator sets from which to
+		/// sync.
+		///
+		/// This function is only allowed to be called from a trusted origin and writes to storage
+		/// with practically no checks in terms of the validity of the data. It is important that
+		/// you ensure that valid data is being passed in.
+		#[pallet::weight((T::DbWeight::get().reads_writes(2, 5), DispatchClass::Operational))]
+		pub fn initialize(
+			origin: OriginFor<T>,
+			init_data: InitializationData<BridgedBlockNumber<T, I>>,
+		) -> DispatchResultWithPostInfo {
+			ensure_owner_or_root::<T, I>(origin)?;
+
+			let init_allowed = !<BestBlockNumber<T, I>>::exists();
+			ensure!(init_allowed, <Error<T, I>>::AlreadyInitialized);
+			initialize_bridge::<T, I>(init_data.clone());
+
+			log::info!(
+				target: "runtime::bridge-beefy",
+				"Pallet has been initialized with the following parameters: {:?}",
+				init_data
+			);
+
+			Ok(().into())
+		}
+
+		/// Change `PalletOwner`.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational))]
+		pub fn set_owner(origin: OriginFor<T>, new_owner: Option<T::AccountId>) -> DispatchResultWithPostInfo {
+			ensure_owner_or_root::<T, I>(origin)?;
+			match new_owner {
+				Some(new_owner) => {
+					PalletOwner::<T, I>::put(&new_owner);
+					log::info!(target: "runtime::bridge-beefy", "Setting pallet Owner to: {:?}", new_owner);
+				}
+				None => {
+					PalletOwner::<T, I>::kill();
+					log::info!(target: "runtime::bridge-beefy", "Removed Owner of pallet.");
+				}
+			}
+
+			Ok(().into())
+		}
+
+		/// Halt or resume all pallet operations.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational))]
+		pub fn set_operational(origin: OriginFor<T>, operational: bool) -> DispatchResultWithPostInfo {
+			ensure_owner_or_root::<T, I>(origin)?;
+			<IsHalted<T, I>>::put(!operational);
+
+			if operational {
+				log::info!(target: "runtime::bridge-beefy", "Resuming pallet operations.");
+			} else {
+				log::warn!(target: "runtime::bridge-beefy", "Stopping pallet operations.");
+			}
+
+			Ok(().into())
+		}
+	}
+
+	/// The current number of requests which have written to storage.
+	///
+	/// If the `RequestCount` hits `MaxRequests`, no more calls will be allowed to the pallet until
+	/// the request capacity is increased.
+	///
+	/// The `RequestCount` is decreased by one at the beginning of every block. This is to ensure
+	/// that the pallet can always make progress.
+	#[pallet::storage]
+	#[pallet::getter(fn request_count)]
+	pub(super) type RequestCount<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
+
+	/// Number of the best block with imported commitment. It is `None` until the pallet is initialized.
+	#[pallet::storage]
+	pub(super) type BestBlockNumber<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, BridgedBlockNumber<T, I>, OptionQuery>;
+
+	/// A ring buffer of block numbers with imported commitments. Ordered by the insertion time.
+	#[pallet::storage]
+	pub(super) type ImportedBlockNumbers<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, u32, BridgedBlockNumber<T, I>>;
+
+	/// Current ring buffer position.
+	#[pallet::storage]
+	pub(super) type ImportedBlockNumbersPointer<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
+
+	/// MMR roots from commitments which have been imported into the pallet.
+	#[pallet::storage]
+	pub(super) type ImportedMmrRoots<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, BridgedBlockNumber<T, I>, MmrRootHash>;
+
+	/// The current BEEFY validator set.
+	#[pallet::storage]
+	pub(super) type CurrentAuthoritySet<T: Config<I>, I: 'static = ()> = StorageValue<_, BeefyAuthoritySet, ValueQuery>;
+
+	/// Short representation of the next BEEFY validator set.
+	#[pallet::storage]
+	pub(super) type NextAuthoritySet<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, bp_beefy::BeefyNextAuthoritySet, ValueQuery>;
+
+	/// Optional pallet owner.
+	///
+	/// Pallet owner has a right to halt all pallet operations and then resume it. If it is
+	/// `None`, then there are no direct ways to halt/resume pallet operations, but other
+	/// runtime methods may still be used to do that (i.e. democracy::referendum to update halt
+	/// flag directly or call the `halt_operations`).
+	#[pallet::storage]
+	pub(super) type PalletOwner<T: Config<I>, I: 'static = ()> = StorageValue<_, T::AccountId, OptionQuery>;
+
+	/// If true, all pallet transactions are failed immediately.
+	#[pallet::storage]
+	pub(super) type IsHalted<T: Config<I>, I: 'static = ()> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
+		/// Optional module owner account.
+		pub owner: Option<T::AccountId>,
+		/// Optional module initialization data.
+		pub init_data: Option<InitializationData<BridgedBlockNumber<T, I>>>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config<I>, I: 'static> Default for GenesisConfig<T, I> {
+		fn default() -> Self {
+			Self {
+				owner: None,
+				init_data: None,
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config<I>, I: 'static> GenesisBuild<T, I> for GenesisConfig<T, I> {
+		fn build(&self) {
+			if let Some(ref owner) = self.owner {
+				<PalletOwner<T, I>>::put(owner);
+			}
+
+			if let Some(init_data) = self.init_data.clone() {
+				initialize_bridge::<T, I>(init_data);
+			} else {
+				// Since the bridge hasn't been initialized we shouldn't allow anyone to perform
+				// transactions.
+				<IsHalted<T, I>>::put(true);
+			}
+		}
+	}
+
+	#[pallet::error]
+	pub enum Error<T, I = ()> {
+		/// The commitment is signed by unknown validator set.
+		InvalidValidatorSetId,
+		/// The validator set is empty or doesn't match the next validator set, known to the pallet.
+		InvalidValidatorSet,
+		/// The number of signatures doesn't match the number of validators or some signature is
+		/// invalid.
+		InvalidCommitmentSignatures,
+		/// The commitment is signed by less than 2/3 of validators.
+		NotEnoughSignatures,
+		/// The MMR leaf is not the leaf of the commitment block.
+		InvalidMmrLeaf,
+		/// The MMR proof is invalid for the MMR root from the commitment.
+		InvalidMmrProof,
+		/// There are no imported commitment for given block.
+		UnknownCommitment,
+		/// The header doesn't match the parent block, referenced by the MMR leaf.
+		InvalidMmrLeafHeader,
+		/// The storage proof doesn't contains storage root. So it is invalid for given header.
+		StorageRootMismatch,
+		/// The storage proof contains duplicate nodes.
+		DuplicateNodesInStorageProof,
+		/// There are too many requests for the current window to handle.
+		TooManyRequests,
+		/// The commitment being imported is older than the best commitment known to the pallet.
+		OldCommitment,
+		/// The pallet is not yet initialized.
+		NotInitialized,
+		/// The pallet has already been initialized.
+		AlreadyInitialized,
+		/// All pallet operations are halted.
+		Halted,
+	}
+
+	/// Verify signatures of the commitment.
+	///
+	/// Every signature must be produced by the validator at the same position in the set and more
+	/// than 2/3 of validators must sign the commitment.
+	pub(crate) fn verify_commitment_signatures<T: Config<I>, I: 'static>(
+		commitment: &Commitment<BridgedBlockNumber<T, I>>,
+		signatures: &[Option<bp_beefy::BeefyValidatorSignature>],
+		authority_set: &BeefyAuthoritySet,
+	) -> Result<(), sp_runtime::DispatchError> {
+		ensure!(!authority_set.validators.is_empty(), <Error<T, I>>::InvalidValidatorSet);
+		ensure!(
+			signatures.len() == authority_set.validators.len(),
+			<Error<T, I>>::InvalidCommitmentSignatures
+		);
+
+		let commitment_hash = commitment.hash();
+		let mut valid_signatures = 0;
+		for (signature, validator) in signatures.iter().zip(authority_set.validators.iter()) {
+			if let Some(signature) = signature {
+				let signer = bp_beefy::recover_validator_address(signature, &commitment_hash);
+				if signer.as_ref() != Some(validator) {
+					log::error!(
+						target: "runtime::bridge-beefy",
+						"Received invalid signature of validator {:?} for commitment {:?}",
+						validator,
+						commitment,
+					);
+					fail!(<Error<T, I>>::InvalidCommitmentSignatures);
+				}
+
+				valid_signatures += 1;
+			}
+		}
+
+		ensure!(
+			valid_signatures >= authority_set.signatures_required(),
+			<Error<T, I>>::NotEnoughSignatures
+		);
+
+		Ok(())
+	}
+
+	/// Verify that the MMR leaf is the latest leaf of the MMR, committed by the commitment.
+	pub(crate) fn verify_mmr_leaf<T: Config<I>, I: 'static>(
+		commitment: &Commitment<BridgedBlockNumber<T, I>>,
+		mmr_leaf: &BridgedMmrLeaf<T, I>,
+		mmr_proof: MmrLeafProof,
+	) -> Result<(), sp_runtime::DispatchError> {
+		// the leaf is added by the commitment block and it references its parent
+		ensure!(
+			mmr_leaf.parent_number_and_hash.0.checked_add(&One::one()) == Some(commitment.block_number),
+			<Error<T, I>>::InvalidMmrLeaf
+		);
+		ensure!(
+			mmr_proof.leaf_index.checked_add(1) == Some(mmr_proof.leaf_count),
+			<Error<T, I>>::InvalidMmrLeaf
+		);
+		ensure!(
+			bp_beefy::verify_mmr_leaf_proof(commitment.payload, mmr_leaf.hash(), mmr_proof),
+			<Error<T, I>>::InvalidMmrProof
+		);
+
+		Ok(())
+	}
+
+	/// Import a previously verified MMR root to the storage.
+	///
+	/// Note this function solely takes care of updating the storage and pruning old entries,
+	/// but does not verify the validaty of such import.
+	pub(crate) fn insert_commitment<T: Config<I>, I: 'static>(
+		block_number: BridgedBlockNumber<T, I>,
+		mmr_root: MmrRootHash,
+	) {
+		let index = <ImportedBlockNumbersPointer<T, I>>::get();
+		let pruning = <ImportedBlockNumbers<T, I>>::try_get(index);
+		<BestBlockNumber<T, I>>::put(block_number);
+		<ImportedMmrRoots<T, I>>::insert(block_number, mmr_root);
+		<ImportedBlockNumbers<T, I>>::insert(index, block_number);
+
+		// Update ring buffer pointer and remove old MMR root.
+		<ImportedBlockNumbersPointer<T, I>>::put((index + 1) % T::CommitmentsToKeep::get());
+		if let Ok(block_number) = pruning {
+			log::debug!(target: "runtime::bridge-beefy", "Pruning old MMR root: {:?}.", block_number);
+			<ImportedMmrRoots<T, I>>::remove(block_number);
+		}
+	}
+
+	/// Since this writes to storage with no real checks this should only be used in functions that
+	/// were called by a trusted origin.
+	pub(crate) fn initialize_bridge<T: Config<I>, I: 'static>(
+		init_params: InitializationData<BridgedBlockNumber<T, I>>,
+	) {
+		let InitializationData {
+			best_block_number,
+			authority_set,
+			next_authority_set,
+			is_halted,
+		} = init_params;
+
+		<BestBlockNumber<T, I>>::put(best_block_number);
+		<ImportedBlockNumbersPointer<T, I>>::put(0);
+		<CurrentAuthoritySet<T, I>>::put(authority_set);
+		<NextAuthoritySet<T, I>>::put(next_authority_set);
+
+		<IsHalted<T, I>>::put(is_halted);
+	}
+
+	/// Ensure that the origin is either root, or `PalletOwner`.
+	fn ensure_owner_or_root<T: Config<I>, I: 'static>(origin: T::Origin) -> Result<(), BadOrigin> {
+		let origin = match T::OwnerOrigin::try_origin(origin) {
+			Ok(_) => return Ok(()),
+			Err(origin) => origin,
+		};
+
+		match origin.into() {
+			Ok(RawOrigin::Root) => Ok(()),
+			Ok(RawOrigin::Signed(ref signer)) if Some(signer) == <PalletOwner<T, I>>::get().as_ref() => Ok(()),
+			_ => Err(BadOrigin),
+		}
+	}
+
+	/// Ensure that the pallet is in operational mode (not halted).
+	fn ensure_operational<T: Config<I>, I: 'static>() -> Result<(), Error<T, I>> {
+		if <IsHalted<T, I>>::get() {
+			Err(<Error<T, I>>::Halted)
+		} else {
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Get number of the best bridged chain block with imported commitment.
+	///
+	/// Returns `None` if the pallet has not been initialized yet.
+	pub fn best_block_number() -> Option<BridgedBlockNumber<T, I>> {
+		<BestBlockNumber<T, I>>::get()
+	}
+
+	/// Get the bridged chain MMR root, that has been imported at given block.
+	pub fn mmr_root(block_number: BridgedBlockNumber<T, I>) -> Option<MmrRootHash> {
+		<ImportedMmrRoots<T, I>>::get(block_number)
+	}
+
+	/// Verify that the leaf with given hash is a member of the bridged chain MMR, which root has
+	/// been imported at given block.
+	pub fn verify_mmr_leaf_proof(
+		block_number: BridgedBlockNumber<T, I>,
+		leaf_hash: H256,
+		proof: MmrLeafProof,
+	) -> Result<(), sp_runtime::DispatchError> {
+		let mmr_root = Self::mmr_root(block_number).ok_or(Error::<T, I>::UnknownCommitment)?;
+		ensure!(
+			bp_beefy::verify_mmr_leaf_proof(mmr_root, leaf_hash, proof),
+			Error::<T, I>::InvalidMmrProof
+		);
+		Ok(())
+	}
+
+	/// Verify that the passed storage proof is valid, given it is crafted using the bridged chain
+	/// header, which is referenced by the MMR leaf. The leaf must be a member of the bridged chain
+	/// MMR, which root has been imported at given block. If the proof is valid, then the `parse`
+	/// callback is called and the function returns its result. After reading all required values,
+	/// the `parse` callback should ensure that the proof has no unused nodes.
+	pub fn parse_finalized_storage_proof<R>(
+		block_number: BridgedBlockNumber<T, I>,
+		mmr_leaf: BridgedMmrLeaf<T, I>,
+		mmr_proof: MmrLeafProof,
+		header: BridgedHeader<T, I>,
+		storage_proof: sp_trie::StorageProof,
+		parse: impl FnOnce(bp_runtime::StorageProofChecker<BridgedBlockHasher<T, I>>) -> R,
+	) -> Result<R, sp_runtime::DispatchError> {
+		// the leaf is added by the child of the header
+		ensure!(
+			mmr_leaf.parent_number_and_hash == (*header.number(), header.hash()),
+			Error::<T, I>::InvalidMmrLeafHeader
+		);
+		Self::verify_mmr_leaf_proof(block_number, mmr_leaf.hash(), mmr_proof)?;
+
+		let storage_proof_checker =
+			bp_runtime::StorageProofChecker::new(*header.state_root(), storage_proof).map_err(|err| match err {
+				bp_runtime::StorageProofError::DuplicateNodesInProof => Error::<T, I>::DuplicateNodesInStorageProof,
+				_ => Error::<T, I>::StorageRootMismatch,
+			})?;
+
+		Ok(parse(storage_proof_checker))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{
+		authority_set, mmr_leaf, next_authority_set, run_test, signed_commitment, single_leaf_mmr_proof,
+		validator_keys, Origin, TestMmrLeaf, TestNumber, TestRuntime, TEST_OWNER_ORIGIN_ACCOUNT,
+	};
+	use bp_beefy::{BeefyMmrLeaf, MmrLeafProof};
+	use frame_support::weights::PostDispatchInfo;
+	use frame_support::{assert_noop, assert_ok};
+	use sp_runtime::DispatchError;
+
+	const VALIDATORS: u8 = 4;
+
+	fn init_with_origin(
+		origin: Origin,
+	) -> Result<InitializationData<TestNumber>, sp_runtime::DispatchErrorWithPostInfo<PostDispatchInfo>> {
+		let init_data = InitializationData {
+			best_block_number: 0,
+			authority_set: authority_set(1, VALIDATORS),
+			next_authority_set: next_authority_set(2, VALIDATORS),
+			is_halted: false,
+		};
+
+		Pallet::<TestRuntime>::initialize(origin, init_data.clone()).map(|_| init_data)
+	}
+
+	fn initialize_bridge() {
+		assert_ok!(init_with_origin(Origin::root()));
+	}
+
+	fn submit_commitment_with(
+		block_number: TestNumber,
+		set_id: bp_beefy::ValidatorSetId,
+		signers: usize,
+		validator_set: Option<Vec<BeefyValidatorAddress>>,
+		leaf: TestMmrLeaf,
+		proof: MmrLeafProof,
+	) -> frame_support::dispatch::DispatchResultWithPostInfo {
+		let commitment = signed_commitment(
+			block_number,
+			leaf.hash(),
+			set_id,
+			&validator_keys(set_id, VALIDATORS),
+			signers,
+		);
+
+		Pallet::<TestRuntime>::submit_commitment(Origin::signed(1), commitment, validator_set, leaf, proof)
+	}
+
+	fn submit_commitment(block_number: TestNumber) -> frame_support::dispatch::DispatchResultWithPostInfo {
+		submit_commitment_with(
+			block_number,
+			1,
+			VALIDATORS as _,
+			None,
+			mmr_leaf(block_number, next_authority_set(2, VALIDATORS)),
+			single_leaf_mmr_proof(),
+		)
+	}
+
+	#[test]
+	fn init_root_or_owner_origin_can_initialize_pallet() {
+		run_test(|| {
+			assert_noop!(init_with_origin(Origin::signed(1)), DispatchError::BadOrigin);
+			assert_ok!(init_with_origin(Origin::root()));
+
+			// Reset storage so we can initialize the pallet again
+			BestBlockNumber::<TestRuntime>::kill();
+			PalletOwner::<TestRuntime>::put(2);
+			assert_ok!(init_with_origin(Origin::signed(2)));
+
+			BestBlockNumber::<TestRuntime>::kill();
+			assert_ok!(init_with_origin(Origin::signed(TEST_OWNER_ORIGIN_ACCOUNT)));
+		})
+	}
+
+	#[test]
+	fn init_can_only_initialize_pallet_once() {
+		run_test(|| {
+			initialize_bridge();
+			assert_noop!(
+				init_with_origin(Origin::root()),
+				<Error<TestRuntime>>::AlreadyInitialized
+			);
+		})
+	}
+
+	#[test]
+	fn pallet_rejects_transactions_if_halted() {
+		run_test(|| {
+			initialize_bridge();
+
+			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::root(), false));
+			assert_noop!(submit_commitment(1), Error::<TestRuntime>::Halted);
+
+			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::root(), true));
+			assert_ok!(submit_commitment(1));
+		})
+	}
+
+	#[test]
+	fn pallet_rejects_commitments_if_not_initialized() {
+		run_test(|| {
+			assert_noop!(submit_commitment(1), Error::<TestRuntime>::NotInitialized);
+		})
+	}
+
+	#[test]
+	fn succesfully_imports_commitment_signed_by_current_set() {
+		run_test(|| {
+			initialize_bridge();
+
+			assert_ok!(submit_commitment(1));
+
+			let leaf = mmr_leaf(1, next_authority_set(2, VALIDATORS));
+			assert_eq!(Pallet::<TestRuntime>::best_block_number(), Some(1));
+			assert_eq!(Pallet::<TestRuntime>::mmr_root(1), Some(leaf.hash()));
+			assert_ok!(Pallet::<TestRuntime>::verify_mmr_leaf_proof(
+				1,
+				leaf.hash(),
+				single_leaf_mmr_proof()
+			));
+		})
+	}
+
+	#[test]
+	fn rejects_old_commitment() {
+		run_test(|| {
+			initialize_bridge();
+
+			assert_ok!(submit_commitment(2));
+			assert_noop!(submit_commitment(2), Error::<TestRuntime>::OldCommitment);
+			assert_noop!(submit_commitment(1), Error::<TestRuntime>::OldCommitment);
+		})
+	}
+
+	#[test]
+	fn rejects_commitment_with_not_enough_signatures() {
+		run_test(|| {
+			initialize_bridge();
+
+			assert_noop!(
+				submit_commitment_with(
+					1,
+					1,
+					2,
+					None,
+					mmr_leaf(1, next_authority_set(2, VALIDATORS)),
+					single_leaf_mmr_proof(),
+				),
+				Error::<TestRuntime>::NotEnoughSignatures,
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_commitment_signed_by_unknown_validators() {
+		run_test(|| {
+			initialize_bridge();
+
+			let leaf = mmr_leaf(1, next_authority_set(2, VALIDATORS));
+			let commitment = signed_commitment(1, leaf.hash(), 1, &validator_keys(5, VALIDATORS), VALIDATORS as _);
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_commitment(
+					Origin::signed(1),
+					commitment,
+					None,
+					leaf,
+					single_leaf_mmr_proof()
+				),
+				Error::<TestRuntime>::InvalidCommitmentSignatures,
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_commitment_signed_by_unknown_validator_set() {
+		run_test(|| {
+			initialize_bridge();
+
+			assert_noop!(
+				submit_commitment_with(
+					1,
+					3,
+					VALIDATORS as _,
+					None,
+					mmr_leaf(1, next_authority_set(2, VALIDATORS)),
+					single_leaf_mmr_proof(),
+				),
+				Error::<TestRuntime>::InvalidValidatorSetId,
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_commitment_with_invalid_mmr_proof() {
+		run_test(|| {
+			initialize_bridge();
+
+			let leaf = mmr_leaf(1, next_authority_set(2, VALIDATORS));
+			let commitment = signed_commitment(
+				1,
+				Default::default(),
+				1,
+				&validator_keys(1, VALIDATORS),
+				VALIDATORS as _,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_commitment(
+					Origin::signed(1),
+					commitment,
+					None,
+					leaf,
+					single_leaf_mmr_proof()
+				),
+				Error::<TestRuntime>::InvalidMmrProof,
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_commitment_with_mmr_leaf_of_other_block() {
+		run_test(|| {
+			initialize_bridge();
+
+			assert_noop!(
+				submit_commitment_with(
+					2,
+					1,
+					VALIDATORS as _,
+					None,
+					mmr_leaf(1, next_authority_set(2, VALIDATORS)),
+					single_leaf_mmr_proof(),
+				),
+				Error::<TestRuntime>::InvalidMmrLeaf,
+			);
+		})
+	}
+
+	#[test]
+	fn enacts_validator_set_handoff() {
+		run_test(|| {
+			initialize_bridge();
+
+			// next set can't sign commitments without providing its validators
+			let leaf = mmr_leaf(1, next_authority_set(3, VALIDATORS));
+			assert_noop!(
+				submit_commitment_with(1, 2, VALIDATORS as _, None, leaf.clone(), single_leaf_mmr_proof()),
+				Error::<TestRuntime>::InvalidValidatorSet,
+			);
+
+			// validators must match the next set, announced by MMR leaves
+			assert_noop!(
+				submit_commitment_with(
+					1,
+					2,
+					VALIDATORS as _,
+					Some(authority_set(3, VALIDATORS).validators),
+					leaf.clone(),
+					single_leaf_mmr_proof(),
+				),
+				Error::<TestRuntime>::InvalidValidatorSet,
+			);
+
+			assert_ok!(submit_commitment_with(
+				1,
+				2,
+				VALIDATORS as _,
+				Some(authority_set(2, VALIDATORS).validators),
+				leaf,
+				single_leaf_mmr_proof(),
+			));
+			assert_eq!(CurrentAuthoritySet::<TestRuntime>::get(), authority_set(2, VALIDATORS));
+			assert_eq!(
+				NextAuthoritySet::<TestRuntime>::get(),
+				next_authority_set(3, VALIDATORS)
+			);
+
+			// previous set is unable to sign commitments anymore
+			assert_noop!(submit_commitment(2), Error::<TestRuntime>::InvalidValidatorSetId);
+		})
+	}
+
+	#[test]
+	fn old_mmr_roots_are_pruned() {
+		run_test(|| {
+			initialize_bridge();
+
+			for block_number in 1..=6 {
+				assert_ok!(submit_commitment(block_number));
+				RequestCount::<TestRuntime>::kill();
+			}
+
+			assert!(Pallet::<TestRuntime>::mmr_root(1).is_none());
+			assert!(Pallet::<TestRuntime>::mmr_root(2).is_some());
+			assert!(Pallet::<TestRuntime>::mmr_root(6).is_some());
+		})
+	}
+
+	#[test]
+	fn parse_finalized_storage_proof_works() {
+		run_test(|| {
+			initialize_bridge();
+
+			let (state_root, storage_proof) = bp_runtime::craft_valid_storage_proof();
+			let header = sp_runtime::testing::Header::new(
+				1,
+				Default::default(),
+				state_root,
+				Default::default(),
+				Default::default(),
+			);
+			let leaf = BeefyMmrLeaf {
+				parent_number_and_hash: (1, header.hash()),
+				..mmr_leaf(2, next_authority_set(2, VALIDATORS))
+			};
+			assert_ok!(submit_commitment_with(
+				2,
+				1,
+				VALIDATORS as _,
+				None,
+				leaf.clone(),
+				single_leaf_mmr_proof(),
+			));
+
+			// proof is accepted if the header is referenced by the MMR leaf with imported MMR root
+			assert_eq!(
+				Pallet::<TestRuntime>::parse_finalized_storage_proof(
+					2,
+					leaf.clone(),
+					single_leaf_mmr_proof(),
+					header.clone(),
+					storage_proof.clone(),
+					|_| (),
+				),
+				Ok(()),
+			);
+
+			// proof is rejected if there's no MMR root imported at given block
+			assert_noop!(
+				Pallet::<TestRuntime>::parse_finalized_storage_proof(
+					3,
+					leaf.clone(),
+					single_leaf_mmr_proof(),
+					header.clone(),
+					storage_proof.clone(),
+					|_| (),
+				),
+				Error::<TestRuntime>::UnknownCommitment,
+			);
+
+			// proof is rejected if the header isn't referenced by the MMR leaf
+			let mut other_header = header.clone();
+			other_header.number = 2;
+			assert_noop!(
+				Pallet::<TestRuntime>::parse_finalized_storage_proof(
+					2,
+					leaf.clone(),
+					single_leaf_mmr_proof(),
+					other_header,
+					storage_proof.clone(),
+					|_| (),
+				),
+				Error::<TestRuntime>::InvalidMmrLeafHeader,
+			);
+
+			// proof is rejected if the storage proof doesn't match header state root
+			assert_noop!(
+				Pallet::<TestRuntime>::parse_finalized_storage_proof(
+					2,
+					leaf,
+					single_leaf_mmr_proof(),
+					header,
+					sp_trie::StorageProof::new(vec![]),
+					|_| (),
+				),
+				Error::<TestRuntime>::StorageRootMismatch,
+			);
+		})
+	}
+
+	#[test]
+	fn rate_limiter_disallows_imports_once_limit_is_hit_in_single_block() {
+		run_test(|| {
+			initialize_bridge();
+
+			assert_ok!(submit_commitment(1));
+			assert_ok!(submit_commitment(2));
+			assert_noop!(submit_commitment(3), <Error<TestRuntime>>::TooManyRequests);
+		})
+	}
+}
@@ -0,0 +1,198 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+// From construct_runtime macro
+#![allow(clippy::from_over_into)]
+
+use bp_beefy::{
+	BeefyAuthoritySet, BeefyMmrLeaf, BeefyNextAuthoritySet, BeefyValidatorAddress, Commitment, MmrLeafProof,
+	SignedCommitment, ValidatorSetId,
+};
+use bp_runtime::Chain;
+use frame_support::{construct_runtime, ord_parameter_types, parameter_types, weights::Weight};
+use sp_core::{ecdsa, Pair, H256};
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	Perbill,
+};
+
+pub type AccountId = u64;
+pub type TestNumber = crate::BridgedBlockNumber<TestRuntime, ()>;
+pub type TestMmrLeaf = crate::BridgedMmrLeaf<TestRuntime, ()>;
+
+type Block = frame_system::mocking::MockBlock<TestRuntime>;
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
+
+use crate as beefy;
+
+construct_runtime! {
+	pub enum TestRuntime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Beefy: beefy::{Pallet},
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+impl frame_system::Config for TestRuntime {
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type DbWeight = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const MaxRequests: u32 = 2;
+	pub const CommitmentsToKeep: u32 = 5;
+}
+
+/// Account that is accepted by the `OwnerOrigin`.
+pub const TEST_OWNER_ORIGIN_ACCOUNT: AccountId = 300;
+
+ord_parameter_types! {
+	pub const OwnerOriginAccount: AccountId = TEST_OWNER_ORIGIN_ACCOUNT;
+}
+
+impl beefy::Config for TestRuntime {
+	type BridgedChain = TestBridgedChain;
+	type MaxRequests = MaxRequests;
+	type CommitmentsToKeep = CommitmentsToKeep;
+	type OwnerOrigin = frame_system::EnsureSignedBy<OwnerOriginAccount, AccountId>;
+	type WeightInfo = ();
+}
+
+#[derive(Debug)]
+pub struct TestBridgedChain;
+
+impl Chain for TestBridgedChain {
+	type BlockNumber = <TestRuntime as frame_system::Config>::BlockNumber;
+	type Hash = <TestRuntime as frame_system::Config>::Hash;
+	type Hasher = <TestRuntime as frame_system::Config>::Hashing;
+	type Header = <TestRuntime as frame_system::Config>::Header;
+}
+
+pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
+	sp_io::TestExternalities::new(Default::default()).execute_with(test)
+}
+
+/// Keys of validators of the set with given id.
+pub fn validator_keys(set_id: ValidatorSetId, validators: u8) -> Vec<ecdsa::Pair> {
+	(0..validators)
+		.map(|index| ecdsa::Pair::from_seed(&[set_id as u8 * 16 + index + 1; 32]))
+		.collect()
+}
+
+/// Ethereum address of the validator.
+pub fn validator_address(validator: &ecdsa::Pair) -> BeefyValidatorAddress {
+	let message = H256::repeat_byte(42);
+	let signature = validator.sign_prehashed(message.as_fixed_bytes());
+	bp_beefy::recover_validator_address(&signature, &message).expect("signature is valid; qed")
+}
+
+/// Validator set with given id.
+pub fn authority_set(set_id: ValidatorSetId, validators: u8) -> BeefyAuthoritySet {
+	BeefyAuthoritySet::new(
+		validator_keys(set_id, validators)
+			.iter()
+			.map(validator_address)
+			.collect(),
+		set_id,
+	)
+}
+
+/// Short representation of the validator set with given id.
+pub fn next_authority_set(set_id: ValidatorSetId, validators: u8) -> BeefyNextAuthoritySet {
+	authority_set(set_id, validators).to_next_authority_set()
+}
+
+/// MMR leaf, added by the block with given number.
+pub fn mmr_leaf(block_number: TestNumber, next_authority_set: BeefyNextAuthoritySet) -> TestMmrLeaf {
+	BeefyMmrLeaf {
+		version: 0,
+		parent_number_and_hash: (block_number - 1, H256::repeat_byte(block_number as u8)),
+		beefy_next_authority_set: next_authority_set,
+		parachain_heads: H256::zero(),
+	}
+}
+
+/// Proof of the leaf of MMR that has single leaf.
+pub fn single_leaf_mmr_proof() -> MmrLeafProof {
+	MmrLeafProof {
+		leaf_index: 0,
+		leaf_count: 1,
+		items: vec![],
+	}
+}
+
+/// Commitment to the MMR root, signed by given validators.
+///
+/// Only `signers` first validators are signing the commitment.
+pub fn signed_commitment(
+	block_number: TestNumber,
+	mmr_root: H256,
+	set_id: ValidatorSetId,
+	validators: &[ecdsa::Pair],
+	signers: usize,
+) -> SignedCommitment<TestNumber> {
+	let commitment = Commitment {
+		payload: mmr_root,
+		block_number,
+		validator_set_id: set_id,
+	};
+	let commitment_hash = commitment.hash();
+	let signatures = validators
+		.iter()
+		.enumerate()
+		.map(|(index, validator)| {
+			if index < signers {
+				Some(validator.sign_prehashed(commitment_hash.as_fixed_bytes()))
+			} else {
+				None
+			}
+		})
+		.collect();
+
+	SignedCommitment { commitment, signatures }
+}
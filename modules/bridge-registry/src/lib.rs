@@ -0,0 +1,415 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bridge Registry Pallet
+//!
+//! The pallet keeps a registry of bridges that are deployed in the runtime. Every bridge is
+//! identified by the `ChainId` (the same identifier that is used to select bridge pallet
+//! instances in bridge runtime APIs) and is described by its name, names of its pallet
+//! instances, lanes it serves and the genesis hash of the bridged chain. The registry is
+//! exposed using `bp_bridge_registry::BridgeRegistryApi`, so relayers and other tools may
+//! discover bridges of the runtime instead of hardcoding this knowledge for every deployment.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use bp_bridge_registry::{BridgeInfo, BridgePallet};
+use bp_messages::LaneId;
+use bp_runtime::ChainId;
+use sp_core::H256;
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+// Re-export in crate namespace for `construct_runtime!`
+pub use pallet::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Maximal length of the bridge name and of the bridge pallet names.
+		#[pallet::constant]
+		type MaxNameLength: Get<u32>;
+		/// Maximal number of pallet instances of the single bridge.
+		#[pallet::constant]
+		type MaxPalletsPerBridge: Get<u32>;
+		/// Maximal number of lanes of the single bridge.
+		#[pallet::constant]
+		type MaxLanesPerBridge: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Register new bridge.
+		///
+		/// May only be called by root.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn register_bridge(
+			origin: OriginFor<T>,
+			bridge_id: ChainId,
+			name: Vec<u8>,
+			pallets: Vec<BridgePallet>,
+			lanes: Vec<LaneId>,
+			bridged_chain_genesis_hash: H256,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(
+				!Bridges::<T>::contains_key(bridge_id),
+				Error::<T>::BridgeAlreadyRegistered
+			);
+			ensure_valid_name::<T>(&name)?;
+			ensure!(
+				pallets.len() <= T::MaxPalletsPerBridge::get() as usize,
+				Error::<T>::TooManyPallets
+			);
+			for pallet in &pallets {
+				ensure_valid_name::<T>(&pallet.name)?;
+			}
+			ensure_valid_lanes::<T>(&lanes)?;
+
+			log::info!(
+				target: "runtime::bridge-registry",
+				"Registering bridge {:?} with {} pallets and {} lanes",
+				bridge_id,
+				pallets.len(),
+				lanes.len(),
+			);
+
+			Bridges::<T>::insert(
+				bridge_id,
+				BridgeInfo {
+					name,
+					pallets,
+					lanes,
+					bridged_chain_genesis_hash,
+					registered_at: frame_system::Pallet::<T>::block_number(),
+				},
+			);
+			Self::deposit_event(Event::BridgeRegistered(bridge_id));
+
+			Ok(().into())
+		}
+
+		/// Replace lanes of the registered bridge.
+		///
+		/// May only be called by root.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_bridge_lanes(
+			origin: OriginFor<T>,
+			bridge_id: ChainId,
+			lanes: Vec<LaneId>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure_valid_lanes::<T>(&lanes)?;
+
+			Bridges::<T>::try_mutate(bridge_id, |bridge| match bridge {
+				Some(bridge) => {
+					bridge.lanes = lanes;
+					Ok(())
+				}
+				None => Err(Error::<T>::UnknownBridge),
+			})?;
+			Self::deposit_event(Event::BridgeLanesUpdated(bridge_id));
+
+			Ok(().into())
+		}
+
+		/// Remove bridge from the registry.
+		///
+		/// Bridge pallets are not affected by this call - it only removes information about the bridge.
+		///
+		/// May only be called by root.
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn unregister_bridge(origin: OriginFor<T>, bridge_id: ChainId) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(Bridges::<T>::contains_key(bridge_id), Error::<T>::UnknownBridge);
+
+			log::info!(target: "runtime::bridge-registry", "Unregistering bridge {:?}", bridge_id);
+
+			Bridges::<T>::remove(bridge_id);
+			Self::deposit_event(Event::BridgeUnregistered(bridge_id));
+
+			Ok(().into())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		/// Bridge has been registered.
+		BridgeRegistered(ChainId),
+		/// Lanes of the registered bridge have been updated.
+		BridgeLanesUpdated(ChainId),
+		/// Bridge has been removed from the registry.
+		BridgeUnregistered(ChainId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Bridge with given id is already registered.
+		BridgeAlreadyRegistered,
+		/// Bridge with given id is not registered.
+		UnknownBridge,
+		/// Bridge or pallet name is empty or exceeds `MaxNameLength`.
+		InvalidName,
+		/// Number of bridge pallets exceeds `MaxPalletsPerBridge`.
+		TooManyPallets,
+		/// Number of bridge lanes exceeds `MaxLanesPerBridge`.
+		TooManyLanes,
+		/// The same lane is listed more than once.
+		DuplicateLane,
+	}
+
+	/// Registered bridges.
+	#[pallet::storage]
+	#[pallet::getter(fn bridge)]
+	pub type Bridges<T: Config> = StorageMap<_, Blake2_128Concat, ChainId, BridgeInfo<T::BlockNumber>, OptionQuery>;
+}
+
+impl<T: Config> Pallet<T> {
+	/// Returns all registered bridges.
+	///
+	/// This function is meant to be used by the `BridgeRegistryApi` implementation.
+	pub fn bridges() -> Vec<(ChainId, BridgeInfo<T::BlockNumber>)> {
+		Bridges::<T>::iter().collect()
+	}
+}
+
+/// Ensure that bridge or pallet name is valid.
+fn ensure_valid_name<T: Config>(name: &[u8]) -> Result<(), Error<T>> {
+	if name.is_empty() || name.len() > T::MaxNameLength::get() as usize {
+		return Err(Error::<T>::InvalidName);
+	}
+
+	Ok(())
+}
+
+/// Ensure that bridge lanes are valid.
+fn ensure_valid_lanes<T: Config>(lanes: &[LaneId]) -> Result<(), Error<T>> {
+	if lanes.len() > T::MaxLanesPerBridge::get() as usize {
+		return Err(Error::<T>::TooManyLanes);
+	}
+	if lanes
+		.iter()
+		.enumerate()
+		.any(|(index, lane)| lanes[..index].contains(lane))
+	{
+		return Err(Error::<T>::DuplicateLane);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{run_test, Origin, TestRuntime};
+	use bp_bridge_registry::BridgePalletKind;
+	use frame_support::{assert_noop, assert_ok, dispatch::DispatchResultWithPostInfo};
+	use sp_runtime::DispatchError;
+
+	const BRIDGE_ID: ChainId = *b"test";
+
+	fn pallets() -> Vec<BridgePallet> {
+		vec![
+			BridgePallet {
+				kind: BridgePalletKind::Finality,
+				name: b"BridgeTestGrandpa".to_vec(),
+			},
+			BridgePallet {
+				kind: BridgePalletKind::Messages,
+				name: b"BridgeTestMessages".to_vec(),
+			},
+		]
+	}
+
+	fn register_bridge(bridge_id: ChainId) -> DispatchResultWithPostInfo {
+		Pallet::<TestRuntime>::register_bridge(
+			Origin::root(),
+			bridge_id,
+			b"Test".to_vec(),
+			pallets(),
+			vec![[0, 0, 0, 0]],
+			[42u8; 32].into(),
+		)
+	}
+
+	#[test]
+	fn calls_are_root_only() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::register_bridge(
+					Origin::signed(1),
+					BRIDGE_ID,
+					b"Test".to_vec(),
+					pallets(),
+					vec![],
+					Default::default(),
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_bridge_lanes(Origin::signed(1), BRIDGE_ID, vec![]),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::unregister_bridge(Origin::signed(1), BRIDGE_ID),
+				DispatchError::BadOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn register_bridge_works() {
+		run_test(|| {
+			frame_system::Pallet::<TestRuntime>::set_block_number(10);
+			assert_ok!(register_bridge(BRIDGE_ID));
+
+			assert_eq!(
+				Pallet::<TestRuntime>::bridge(BRIDGE_ID),
+				Some(BridgeInfo {
+					name: b"Test".to_vec(),
+					pallets: pallets(),
+					lanes: vec![[0, 0, 0, 0]],
+					bridged_chain_genesis_hash: [42u8; 32].into(),
+					registered_at: 10,
+				}),
+			);
+			assert_eq!(Pallet::<TestRuntime>::bridges().len(), 1);
+		});
+	}
+
+	#[test]
+	fn register_bridge_rejects_already_registered_bridge() {
+		run_test(|| {
+			assert_ok!(register_bridge(BRIDGE_ID));
+			assert_noop!(
+				register_bridge(BRIDGE_ID),
+				Error::<TestRuntime>::BridgeAlreadyRegistered
+			);
+		});
+	}
+
+	#[test]
+	fn register_bridge_rejects_invalid_bridge_info() {
+		run_test(|| {
+			let check = |name: Vec<u8>, pallets, lanes, error: Error<TestRuntime>| {
+				assert_noop!(
+					Pallet::<TestRuntime>::register_bridge(
+						Origin::root(),
+						BRIDGE_ID,
+						name,
+						pallets,
+						lanes,
+						Default::default(),
+					),
+					error,
+				);
+			};
+
+			check(vec![], pallets(), vec![], Error::InvalidName);
+			check(vec![b'a'; 100], pallets(), vec![], Error::InvalidName);
+			check(
+				b"Test".to_vec(),
+				vec![BridgePallet {
+					kind: BridgePalletKind::Dispatch,
+					name: vec![],
+				}],
+				vec![],
+				Error::InvalidName,
+			);
+			check(
+				b"Test".to_vec(),
+				[pallets(), pallets()].concat(),
+				vec![],
+				Error::TooManyPallets,
+			);
+			check(
+				b"Test".to_vec(),
+				pallets(),
+				vec![[0, 0, 0, 0], [0, 0, 0, 1], [0, 0, 0, 2], [0, 0, 0, 3], [0, 0, 0, 4]],
+				Error::TooManyLanes,
+			);
+			check(
+				b"Test".to_vec(),
+				pallets(),
+				vec![[0, 0, 0, 0], [0, 0, 0, 0]],
+				Error::DuplicateLane,
+			);
+		});
+	}
+
+	#[test]
+	fn set_bridge_lanes_works() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::set_bridge_lanes(Origin::root(), BRIDGE_ID, vec![[0, 0, 0, 1]]),
+				Error::<TestRuntime>::UnknownBridge,
+			);
+
+			assert_ok!(register_bridge(BRIDGE_ID));
+			assert_noop!(
+				Pallet::<TestRuntime>::set_bridge_lanes(Origin::root(), BRIDGE_ID, vec![[0, 0, 0, 1], [0, 0, 0, 1]]),
+				Error::<TestRuntime>::DuplicateLane,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_bridge_lanes(
+				Origin::root(),
+				BRIDGE_ID,
+				vec![[0, 0, 0, 0], [0, 0, 0, 1]],
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::bridge(BRIDGE_ID).map(|bridge| bridge.lanes),
+				Some(vec![[0, 0, 0, 0], [0, 0, 0, 1]]),
+			);
+		});
+	}
+
+	#[test]
+	fn unregister_bridge_works() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::unregister_bridge(Origin::root(), BRIDGE_ID),
+				Error::<TestRuntime>::UnknownBridge,
+			);
+
+			assert_ok!(register_bridge(BRIDGE_ID));
+			assert_ok!(register_bridge(*b"tst2"));
+			assert_ok!(Pallet::<TestRuntime>::unregister_bridge(Origin::root(), BRIDGE_ID));
+			assert_eq!(Pallet::<TestRuntime>::bridge(BRIDGE_ID), None);
+			assert_eq!(
+				Pallet::<TestRuntime>::bridges()
+					.into_iter()
+					.map(|(id, _)| id)
+					.collect::<Vec<_>>(),
+				vec![*b"tst2"],
+			);
+		});
+	}
+}
@@ -0,0 +1,41 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarks for the bridge dispatch pallet.
+//!
+//! The only dispatchable of the pallet is `dispatch_atomic_batch`. Its weight is the sum of weights
+//! of all batched calls plus the batch overhead (storage transaction and per-call loop), which is
+//! measured here using batches of empty `remark` calls.
+
+use crate::*;
+
+use frame_benchmarking::{benchmarks_instance, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_std::prelude::*;
+
+/// Maximal number of calls in the benchmarked batch.
+const MAX_BATCH_CALLS: u32 = 1_000;
+
+benchmarks_instance! {
+	where_clause { where <T as Config<I>>::Call: From<frame_system::Call<T>> }
+
+	// Benchmark `dispatch_atomic_batch` extrinsic with `c` empty `remark` calls.
+	dispatch_atomic_batch {
+		let c in 0..MAX_BATCH_CALLS;
+		let caller: T::AccountId = whitelisted_caller();
+		let calls = vec![frame_system::Call::<T>::remark(Vec::new()).into(); c as usize];
+	}: dispatch_atomic_batch(RawOrigin::Signed(caller), calls)
+}
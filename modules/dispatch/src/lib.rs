@@ -18,31 +18,47 @@
 //!
 //! The messages are interpreted directly as runtime `Call`. We attempt to decode
 //! them and then dispatch as usual. To prevent compatibility issues, the Calls have
-//! to include a `spec_version`. This will be checked before dispatch. In the case of
-//! a succesful dispatch an event is emitted.
+//! to include a `spec_version`. This will be checked before dispatch. Calls that are
+//! encoded using one of previous spec versions (within configured grace window) are
+//! decoded using the runtime-provided hook. In the case of a succesful dispatch an event
+//! is emitted.
+//!
+//! The message call may also be the `dispatch_atomic_batch` call of this module. Calls of such batch
+//! are dispatched atomically (either all calls succeed, or all changes are reverted) and the
+//! dispatch result of every call is reported using the `MessageBatchDispatched` event.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 // Generated by `decl_event!`
 #![allow(clippy::unused_unit)]
 
+pub use weights::WeightInfo;
+
 use bp_message_dispatch::{CallOrigin, MessageDispatch, MessagePayload, SpecVersion, Weight};
-use bp_runtime::{derive_account_id, ChainId, SourceAccount};
+use bp_messages::{MessagePayloadDecodeError, MessagePayloadVersion, CURRENT_MESSAGE_PAYLOAD_VERSION};
+use bp_runtime::{derive_account_id, ChainId, DispatchFeePayment, SourceAccount};
 use codec::{Decode, Encode};
 use frame_support::{
 	decl_event, decl_module, decl_storage,
-	dispatch::{Dispatchable, Parameter},
+	dispatch::{DispatchErrorWithPostInfo, DispatchResultWithPostInfo, Dispatchable, Parameter},
 	ensure,
-	traits::{Filter, Get},
-	weights::{extract_actual_weight, GetDispatchInfo},
+	storage::with_transaction,
+	traits::{Filter, Get, IsSubType},
+	weights::{extract_actual_weight, DispatchClass, GetDispatchInfo, Pays},
 };
 use frame_system::RawOrigin;
 use sp_runtime::{
-	traits::{BadOrigin, Convert, IdentifyAccount, MaybeDisplay, MaybeSerializeDeserialize, Member, Verify},
-	DispatchResult,
+	traits::{BadOrigin, Convert, Hash, IdentifyAccount, MaybeDisplay, MaybeSerializeDeserialize, Member, Verify},
+	DispatchResult, TransactionOutcome,
 };
 use sp_std::{fmt::Debug, marker::PhantomData, prelude::*};
 
+/// Weights of the pallet calls.
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+
 /// The module configuration trait.
 pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// The overarching event type.
@@ -51,9 +67,17 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// event with this id + dispatch result. Could be e.g. (LaneId, MessageNonce) if
 	/// it comes from the messages module.
 	type MessageId: Parameter;
+	/// Id of the lane (or any other channel) that messages are delivered over. Used as a key of
+	/// per-lane dispatch counters.
+	type LaneId: Parameter;
+	/// Converter from the message id into id of the lane that the message has been delivered over.
+	type MessageIdToLaneId: Convert<Self::MessageId, Self::LaneId>;
 	/// Type of account ID on source chain.
 	type SourceChainAccountId: Parameter + Member + MaybeSerializeDeserialize + Debug + MaybeDisplay + Ord + Default;
 	/// Type of account public key on target chain.
+	///
+	/// Chains that are using 20-byte (Ethereum-style) accounts may use `bp_runtime::AccountId20` here
+	/// and `bp_runtime::EthereumSignature` as `TargetChainSignature`.
 	type TargetChainAccountPublic: Parameter + IdentifyAccount<AccountId = Self::AccountId>;
 	/// Type of signature that may prove that the message has been signed by
 	/// owner of `TargetChainAccountPublic`.
@@ -64,7 +88,7 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 		+ Dispatchable<
 			Origin = <Self as frame_system::Config>::Origin,
 			PostInfo = frame_support::dispatch::PostDispatchInfo,
-		>;
+		> + IsSubType<Call<Self, I>>;
 	/// Pre-dispatch filter for incoming calls.
 	///
 	/// The pallet will filter all incoming calls right before they're dispatched. If this filter
@@ -77,22 +101,42 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// `Call` which has been encoded using previous `spec_version`, then we might end
 	/// up with decoding error, instead of `MessageVersionSpecMismatch`.
 	type EncodedCall: Decode + Encode + Into<Result<<Self as Config<I>>::Call, ()>>;
+	/// Number of previous runtime spec versions that messages may still be encoded with.
+	///
+	/// Messages that are encoded using one of previous spec versions within this window aren't
+	/// rejected immediately. Instead, their calls are decoded using `OutdatedCallDecoder`.
+	type SpecVersionGraceWindow: Get<SpecVersion>;
+	/// Decoder of calls that have been encoded using one of previous runtime spec versions.
+	type OutdatedCallDecoder: DecodeOutdatedCall<Self::EncodedCall, <Self as Config<I>>::Call>;
 	/// A type which can be turned into an AccountId from a 256-bit hash.
 	///
-	/// Used when deriving target chain AccountIds from source chain AccountIds.
+	/// Used when deriving target chain AccountIds from source chain AccountIds. Chains that are
+	/// using 20-byte accounts may use `bp_runtime::AccountId20Converter` here.
 	type AccountIdConverter: sp_runtime::traits::Convert<sp_core::hash::H256, Self::AccountId>;
+	/// Weight information for extrinsics in this pallet.
+	type WeightInfo: WeightInfo;
 }
 
 decl_storage! {
-	trait Store for Pallet<T: Config<I>, I: Instance = DefaultInstance> as Dispatch {}
+	trait Store for Pallet<T: Config<I>, I: Instance = DefaultInstance> as Dispatch {
+		/// Number of messages that have been delivered over given lane of given bridged chain,
+		/// but which call has failed to decode.
+		pub DecodeFailures get(fn decode_failures):
+			double_map hasher(blake2_128_concat) ChainId, hasher(blake2_128_concat) T::LaneId => u64;
+	}
 }
 
 decl_event!(
 	pub enum Event<T, I = DefaultInstance> where
-		<T as Config<I>>::MessageId
+		<T as Config<I>>::MessageId,
+		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::Hash,
 	{
 		/// Message has been rejected before reaching dispatch.
 		MessageRejected(ChainId, MessageId),
+		/// Message has been rejected because it is encoded using unknown payload envelope version.
+		/// Last two arguments are: expected and passed payload version.
+		MessagePayloadVersionMismatch(ChainId, MessageId, MessagePayloadVersion, MessagePayloadVersion),
 		/// Message has been rejected by dispatcher because of spec version mismatch.
 		/// Last two arguments are: expected and passed spec version.
 		MessageVersionSpecMismatch(ChainId, MessageId, SpecVersion, SpecVersion),
@@ -103,10 +147,19 @@ decl_event!(
 		MessageSignatureMismatch(ChainId, MessageId),
 		/// Message has been dispatched with given result.
 		MessageDispatched(ChainId, MessageId, DispatchResult),
-		/// We have failed to decode Call from the message.
-		MessageCallDecodeFailed(ChainId, MessageId),
+		/// Atomic batch of calls from the message has been dispatched. Last argument is the dispatch
+		/// result of every dispatched call. If the last result is an error, then all changes made by
+		/// the batch have been reverted and the rest of calls haven't been dispatched.
+		MessageBatchDispatched(ChainId, MessageId, Vec<DispatchResult>),
+		/// We have failed to decode Call from the message. Last argument is the hash of the encoded
+		/// call from the message payload. It may be used by the source chain application to identify
+		/// the message and to refund its sender.
+		MessageCallDecodeFailed(ChainId, MessageId, Hash),
 		/// The call from the message has been rejected by the call filter.
 		MessageCallRejected(ChainId, MessageId),
+		/// The origin account has failed to pay fee for dispatching the message. Last two arguments are:
+		/// the account that has failed to pay and the message dispatch weight.
+		MessageDispatchPaymentFailed(ChainId, MessageId, AccountId, Weight),
 		/// Phantom member, never used. Needed to handle multiple pallet instances.
 		_Dummy(PhantomData<I>),
 	}
@@ -117,10 +170,28 @@ decl_module! {
 	pub struct Module<T: Config<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
 		/// Deposit one of this module's events by using the default implementation.
 		fn deposit_event() = default;
+
+		/// Dispatch given calls atomically, using the same origin.
+		///
+		/// If any of calls fails, all changes made by previous calls are reverted and the rest
+		/// of calls is not dispatched. The declared weight of the batch is the sum of weights
+		/// of all calls plus the batch overhead. The batch is operational (or free) only if
+		/// all its calls are operational (or free).
+		#[weight = atomic_batch_dispatch_info::<T, I>(calls)]
+		pub fn dispatch_atomic_batch(origin, calls: Vec<<T as Config<I>>::Call>) -> DispatchResultWithPostInfo {
+			let (results, actual_weight) = dispatch_calls_atomically::<T, I>(origin, calls);
+			match results.last() {
+				Some(Err(error)) => Err(DispatchErrorWithPostInfo {
+					post_info: Some(actual_weight).into(),
+					error: *error,
+				}),
+				_ => Ok(Some(actual_weight).into()),
+			}
+		}
 	}
 }
 
-impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
+impl<T: Config<I>, I: Instance> MessageDispatch<T::AccountId, T::MessageId> for Pallet<T, I> {
 	type Message =
 		MessagePayload<T::SourceChainAccountId, T::TargetChainAccountPublic, T::TargetChainSignature, T::EncodedCall>;
 
@@ -128,10 +199,33 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 		message.weight
 	}
 
-	fn dispatch(source_chain: ChainId, target_chain: ChainId, id: T::MessageId, message: Result<Self::Message, ()>) {
+	fn dispatch<P: FnOnce(&T::AccountId, Weight) -> Result<(), ()>>(
+		source_chain: ChainId,
+		target_chain: ChainId,
+		id: T::MessageId,
+		message: Result<Self::Message, MessagePayloadDecodeError>,
+		pay_dispatch_fee: P,
+	) -> Weight {
 		// emit special even if message has been rejected by external component
 		let message = match message {
 			Ok(message) => message,
+			Err(MessagePayloadDecodeError::UnknownVersion(passed_version)) => {
+				log::trace!(
+					target: "runtime::bridge-dispatch",
+					"Message {:?}/{:?}: payload version mismatch. Expected {:?}, got {:?}",
+					source_chain,
+					id,
+					CURRENT_MESSAGE_PAYLOAD_VERSION,
+					passed_version,
+				);
+				Self::deposit_event(RawEvent::MessagePayloadVersionMismatch(
+					source_chain,
+					id,
+					CURRENT_MESSAGE_PAYLOAD_VERSION,
+					passed_version,
+				));
+				return 0;
+			}
 			Err(_) => {
 				log::trace!(
 					target: "runtime::bridge-dispatch",
@@ -140,42 +234,50 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 					id,
 				);
 				Self::deposit_event(RawEvent::MessageRejected(source_chain, id));
-				return;
+				return 0;
 			}
 		};
 
 		// verify spec version
-		// (we want it to be the same, because otherwise we may decode Call improperly)
+		// (we want it to be the same, because otherwise we may decode Call improperly; the only exception
+		// is when message is encoded using one of previous versions within the grace window)
 		let expected_version = <T as frame_system::Config>::Version::get().spec_version;
-		if message.spec_version != expected_version {
-			log::trace!(
-				"Message {:?}/{:?}: spec_version mismatch. Expected {:?}, got {:?}",
-				source_chain,
-				id,
-				expected_version,
-				message.spec_version,
-			);
-			Self::deposit_event(RawEvent::MessageVersionSpecMismatch(
-				source_chain,
-				id,
-				expected_version,
-				message.spec_version,
-			));
-			return;
+		let is_current_version = message.spec_version == expected_version;
+		let is_outdated_version = message.spec_version < expected_version
+			&& expected_version - message.spec_version <= T::SpecVersionGraceWindow::get();
+		if !is_current_version && !is_outdated_version {
+			Self::deposit_spec_version_mismatch(source_chain, id, expected_version, message.spec_version);
+			return message.weight;
 		}
 
 		// now that we have spec version checked, let's decode the call
-		let call = match message.call.into() {
+		let call_hash = T::Hashing::hash_of(&message.call);
+		let call = if is_current_version {
+			message.call.into()
+		} else {
+			T::OutdatedCallDecoder::decode_outdated_call(message.spec_version, message.call)
+		};
+		let call = match call {
 			Ok(call) => call,
+			Err(_) if !is_current_version => {
+				// outdated call decoder has failed to decode the call => report it as spec version mismatch
+				Self::deposit_spec_version_mismatch(source_chain, id, expected_version, message.spec_version);
+				return message.weight;
+			}
 			Err(_) => {
 				log::trace!(
 					target: "runtime::bridge-dispatch",
-					"Failed to decode Call from message {:?}/{:?}",
+					"Failed to decode Call from message {:?}/{:?}. Call hash: {:?}",
 					source_chain,
 					id,
+					call_hash,
 				);
-				Self::deposit_event(RawEvent::MessageCallDecodeFailed(source_chain, id));
-				return;
+				let lane_id = T::MessageIdToLaneId::convert(id.clone());
+				DecodeFailures::<T, I>::mutate(source_chain, lane_id, |failures| {
+					*failures = failures.saturating_add(1)
+				});
+				Self::deposit_event(RawEvent::MessageCallDecodeFailed(source_chain, id, call_hash));
+				return message.weight;
 			}
 		};
 
@@ -207,7 +309,7 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 						target_signature,
 					);
 					Self::deposit_event(RawEvent::MessageSignatureMismatch(source_chain, id));
-					return;
+					return message.weight;
 				}
 
 				log::trace!(target: "runtime::bridge-dispatch", "Target Account: {:?}", &target_account);
@@ -221,8 +323,12 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 			}
 		};
 
-		// filter the call
-		if !T::CallFilter::filter(&call) {
+		// filter the call (and all calls of the atomic batch)
+		let batch_calls = match call.is_sub_type() {
+			Some(Call::<T, I>::dispatch_atomic_batch(ref calls)) => Some(calls.clone()),
+			_ => None,
+		};
+		if !is_call_allowed::<T, I>(&call) {
 			log::trace!(
 				target: "runtime::bridge-dispatch",
 				"Message {:?}/{:?}: the call ({:?}) is rejected by filter",
@@ -231,7 +337,7 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 				call,
 			);
 			Self::deposit_event(RawEvent::MessageCallRejected(source_chain, id));
-			return;
+			return message.weight;
 		}
 
 		// verify weight
@@ -254,12 +360,50 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 				expected_weight,
 				message.weight,
 			));
-			return;
+			return message.weight;
+		}
+
+		// pay dispatch fee right before dispatch
+		let pay_dispatch_fee_at_target_chain = message.dispatch_fee_payment == DispatchFeePayment::AtTargetChain;
+		if pay_dispatch_fee_at_target_chain && pay_dispatch_fee(&origin_account, message.weight).is_err() {
+			log::trace!(
+				target: "runtime::bridge-dispatch",
+				"Failed to pay dispatch fee for dispatching message {:?}/{:?} with weight {}",
+				source_chain,
+				id,
+				message.weight,
+			);
+			Self::deposit_event(RawEvent::MessageDispatchPaymentFailed(
+				source_chain,
+				id,
+				origin_account,
+				message.weight,
+			));
+			return message.weight;
 		}
 
 		// finally dispatch message
 		let origin = RawOrigin::Signed(origin_account).into();
 		log::trace!(target: "runtime::bridge-dispatch", "Message being dispatched is: {:.4096?}", &call);
+
+		// calls of the atomic batch are dispatched one-by-one, so that we're able to report result of every call
+		if let Some(batch_calls) = batch_calls {
+			let (results, actual_batch_weight) = dispatch_calls_atomically::<T, I>(origin, batch_calls);
+
+			log::trace!(
+				target: "runtime::bridge-dispatch",
+				"Message {:?}/{:?} with atomic batch has been dispatched. Weight: {} of {}. Results: {:?}",
+				source_chain,
+				id,
+				actual_batch_weight,
+				message.weight,
+				results,
+			);
+
+			Self::deposit_event(RawEvent::MessageBatchDispatched(source_chain, id, results));
+			return message.weight.saturating_sub(actual_batch_weight);
+		}
+
 		let dispatch_result = call.dispatch(origin);
 		let actual_call_weight = extract_actual_weight(&dispatch_result, &dispatch_info);
 
@@ -278,6 +422,149 @@ impl<T: Config<I>, I: Instance> MessageDispatch<T::MessageId> for Pallet<T, I> {
 			id,
 			dispatch_result.map(drop).map_err(|e| e.error),
 		));
+
+		message.weight.saturating_sub(actual_call_weight)
+	}
+}
+
+/// Message id to lane id converter that works with `(LaneId, MessageNonce)` message ids.
+pub struct LaneIdOfMessageId;
+
+impl<LaneId, MessageNonce> Convert<(LaneId, MessageNonce), LaneId> for LaneIdOfMessageId {
+	fn convert((lane_id, _): (LaneId, MessageNonce)) -> LaneId {
+		lane_id
+	}
+}
+
+/// Getting storage keys for dispatch counters.
+pub mod storage_keys {
+	use super::*;
+	use frame_support::storage::generator::StorageDoubleMap;
+	use sp_core::storage::StorageKey;
+
+	/// Storage key of the number of call decode failures for given bridged chain and lane.
+	pub fn decode_failures_key<T: Config<I>, I: Instance>(source_chain: ChainId, lane_id: &T::LaneId) -> StorageKey {
+		StorageKey(DecodeFailures::<T, I>::storage_double_map_final_key(
+			source_chain,
+			lane_id,
+		))
+	}
+}
+
+/// Returns true if the call is allowed by the filter. If the call is the atomic batch, all its
+/// calls (including calls of nested batches) must also be allowed.
+fn is_call_allowed<T: Config<I>, I: Instance>(call: &<T as Config<I>>::Call) -> bool {
+	if !T::CallFilter::filter(call) {
+		return false;
+	}
+
+	match call.is_sub_type() {
+		Some(Call::<T, I>::dispatch_atomic_batch(ref calls)) => calls.iter().all(is_call_allowed::<T, I>),
+		_ => true,
+	}
+}
+
+/// Returns declared weight, class and fee payment mode of the atomic batch.
+///
+/// The weight is the sum of declared weights of all its calls plus the batch overhead. The batch is
+/// operational only if all its calls are operational and it is free only if all its calls are free.
+fn atomic_batch_dispatch_info<T: Config<I>, I: Instance>(
+	calls: &[<T as Config<I>>::Call],
+) -> (Weight, DispatchClass, Pays) {
+	let calls_info = calls.iter().map(|call| call.get_dispatch_info()).collect::<Vec<_>>();
+	let weight = calls_info.iter().fold(
+		T::WeightInfo::dispatch_atomic_batch(calls.len() as u32),
+		|weight, info| weight.saturating_add(info.weight),
+	);
+	let class = if !calls_info.is_empty() && calls_info.iter().all(|info| info.class == DispatchClass::Operational) {
+		DispatchClass::Operational
+	} else {
+		DispatchClass::Normal
+	};
+	let pays_fee = if !calls_info.is_empty() && calls_info.iter().all(|info| info.pays_fee == Pays::No) {
+		Pays::No
+	} else {
+		Pays::Yes
+	};
+	(weight, class, pays_fee)
+}
+
+/// Dispatch all given calls within single storage transaction.
+///
+/// Returns dispatch results of all dispatched calls and their actual weight (including the batch
+/// overhead). Calls are dispatched until first failure. If some call has failed, the transaction is
+/// rolled back and its error is the last returned result.
+fn dispatch_calls_atomically<T: Config<I>, I: Instance>(
+	origin: T::Origin,
+	calls: Vec<<T as Config<I>>::Call>,
+) -> (Vec<DispatchResult>, Weight) {
+	let (results, calls_weight) = with_transaction(|| {
+		let mut results = Vec::with_capacity(calls.len());
+		let mut actual_weight: Weight = 0;
+		for call in calls {
+			let dispatch_info = call.get_dispatch_info();
+			let dispatch_result = call.dispatch(origin.clone());
+			actual_weight = actual_weight.saturating_add(extract_actual_weight(&dispatch_result, &dispatch_info));
+
+			let is_dispatched = dispatch_result.is_ok();
+			results.push(dispatch_result.map(drop).map_err(|e| e.error));
+			if !is_dispatched {
+				return TransactionOutcome::Rollback((results, actual_weight));
+			}
+		}
+
+		TransactionOutcome::Commit((results, actual_weight))
+	});
+	let batch_overhead = T::WeightInfo::dispatch_atomic_batch(results.len() as u32);
+	(results, calls_weight.saturating_add(batch_overhead))
+}
+
+impl<T: Config<I>, I: Instance> Pallet<T, I> {
+	/// Deposit spec version mismatch event.
+	fn deposit_spec_version_mismatch(
+		source_chain: ChainId,
+		id: T::MessageId,
+		expected_version: SpecVersion,
+		passed_version: SpecVersion,
+	) {
+		log::trace!(
+			target: "runtime::bridge-dispatch",
+			"Message {:?}/{:?}: spec_version mismatch. Expected {:?}, got {:?}",
+			source_chain,
+			id,
+			expected_version,
+			passed_version,
+		);
+		Self::deposit_event(RawEvent::MessageVersionSpecMismatch(
+			source_chain,
+			id,
+			expected_version,
+			passed_version,
+		));
+	}
+}
+
+/// Decoder of calls that have been encoded using one of previous runtime spec versions.
+pub trait DecodeOutdatedCall<EncodedCall, Call> {
+	/// Decode call that has been encoded using given (previous) runtime spec version.
+	///
+	/// Should return error if call can't be decoded or if calls of this spec version aren't supported.
+	fn decode_outdated_call(spec_version: SpecVersion, call: EncodedCall) -> Result<Call, ()>;
+}
+
+impl<EncodedCall, Call> DecodeOutdatedCall<EncodedCall, Call> for () {
+	fn decode_outdated_call(_spec_version: SpecVersion, _call: EncodedCall) -> Result<Call, ()> {
+		Err(())
+	}
+}
+
+/// Outdated call decoder that may be used when encoding of calls hasn't been changed by any
+/// runtime upgrade within the grace window.
+pub struct UnchangedCallEncoding;
+
+impl<EncodedCall: Into<Result<Call, ()>>, Call> DecodeOutdatedCall<EncodedCall, Call> for UnchangedCallEncoding {
+	fn decode_outdated_call(_spec_version: SpecVersion, call: EncodedCall) -> Result<Call, ()> {
+		call.into()
 	}
 }
 
@@ -351,12 +638,14 @@ mod tests {
 	use super::*;
 	use frame_support::{parameter_types, weights::Weight};
 	use frame_system::{EventRecord, Phase};
+	use hex_literal::hex;
 	use sp_core::H256;
 	use sp_runtime::{
 		testing::Header,
 		traits::{BlakeTwo256, IdentityLookup},
 		Perbill,
 	};
+	use sp_version::RuntimeVersion;
 
 	type AccountId = u64;
 	type MessageId = [u8; 4];
@@ -415,6 +704,7 @@ mod tests {
 		pub const MaximumBlockWeight: Weight = 1024;
 		pub const MaximumBlockLength: u32 = 2 * 1024;
 		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const SpecVersionGraceWindow: SpecVersion = 2;
 	}
 
 	impl frame_system::Config for TestRuntime {
@@ -429,7 +719,7 @@ mod tests {
 		type Header = Header;
 		type Event = Event;
 		type BlockHashCount = BlockHashCount;
-		type Version = ();
+		type Version = TestVersion;
 		type PalletInfo = PalletInfo;
 		type AccountData = ();
 		type OnNewAccount = ();
@@ -446,13 +736,42 @@ mod tests {
 	impl Config for TestRuntime {
 		type Event = Event;
 		type MessageId = MessageId;
+		type LaneId = MessageId;
+		type MessageIdToLaneId = sp_runtime::traits::Identity;
 		type SourceChainAccountId = AccountId;
 		type TargetChainAccountPublic = TestAccountPublic;
 		type TargetChainSignature = TestSignature;
 		type Call = Call;
 		type CallFilter = TestCallFilter;
 		type EncodedCall = EncodedCall;
+		type SpecVersionGraceWindow = SpecVersionGraceWindow;
+		type OutdatedCallDecoder = TestOutdatedCallDecoder;
 		type AccountIdConverter = AccountIdConverter;
+		type WeightInfo = ();
+	}
+
+	pub struct TestVersion;
+
+	impl Get<RuntimeVersion> for TestVersion {
+		fn get() -> RuntimeVersion {
+			RuntimeVersion {
+				spec_version: TEST_SPEC_VERSION,
+				..Default::default()
+			}
+		}
+	}
+
+	pub struct TestOutdatedCallDecoder;
+
+	impl DecodeOutdatedCall<EncodedCall, Call> for TestOutdatedCallDecoder {
+		fn decode_outdated_call(spec_version: SpecVersion, call: EncodedCall) -> Result<Call, ()> {
+			// let's say that encoding has been changed by the `TEST_SPEC_VERSION - 1` upgrade
+			if spec_version == TEST_SPEC_VERSION - 1 {
+				call.into()
+			} else {
+				Err(())
+			}
+		}
 	}
 
 	#[derive(Decode, Encode)]
@@ -472,7 +791,7 @@ mod tests {
 		}
 	}
 
-	const TEST_SPEC_VERSION: SpecVersion = 0;
+	const TEST_SPEC_VERSION: SpecVersion = 10;
 	const TEST_WEIGHT: Weight = 1_000_000_000;
 
 	fn new_test_ext() -> sp_io::TestExternalities {
@@ -485,31 +804,32 @@ mod tests {
 	fn prepare_message(
 		origin: CallOrigin<AccountId, TestAccountPublic, TestSignature>,
 		call: Call,
-	) -> <Pallet<TestRuntime> as MessageDispatch<<TestRuntime as Config>::MessageId>>::Message {
+	) -> <Pallet<TestRuntime> as MessageDispatch<AccountId, <TestRuntime as Config>::MessageId>>::Message {
 		MessagePayload {
 			spec_version: TEST_SPEC_VERSION,
 			weight: TEST_WEIGHT,
 			origin,
+			dispatch_fee_payment: DispatchFeePayment::AtSourceChain,
 			call: EncodedCall(call.encode()),
 		}
 	}
 
 	fn prepare_root_message(
 		call: Call,
-	) -> <Pallet<TestRuntime> as MessageDispatch<<TestRuntime as Config>::MessageId>>::Message {
+	) -> <Pallet<TestRuntime> as MessageDispatch<AccountId, <TestRuntime as Config>::MessageId>>::Message {
 		prepare_message(CallOrigin::SourceRoot, call)
 	}
 
 	fn prepare_target_message(
 		call: Call,
-	) -> <Pallet<TestRuntime> as MessageDispatch<<TestRuntime as Config>::MessageId>>::Message {
+	) -> <Pallet<TestRuntime> as MessageDispatch<AccountId, <TestRuntime as Config>::MessageId>>::Message {
 		let origin = CallOrigin::TargetAccount(1, TestAccountPublic(1), TestSignature(1));
 		prepare_message(origin, call)
 	}
 
 	fn prepare_source_message(
 		call: Call,
-	) -> <Pallet<TestRuntime> as MessageDispatch<<TestRuntime as Config>::MessageId>>::Message {
+	) -> <Pallet<TestRuntime> as MessageDispatch<AccountId, <TestRuntime as Config>::MessageId>>::Message {
 		let origin = CallOrigin::SourceAccount(1);
 		prepare_message(origin, call)
 	}
@@ -525,7 +845,7 @@ mod tests {
 			message.spec_version = BAD_SPEC_VERSION;
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -543,6 +863,89 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_dispatch_message_from_spec_version_within_grace_window() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let mut message =
+				prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+			message.spec_version = TEST_SPEC_VERSION - 1;
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
+						SOURCE_CHAIN_ID,
+						id,
+						Ok(())
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn should_fail_on_spec_version_mismatch_if_outdated_call_is_not_decoded() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let mut message =
+				prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+			message.spec_version = TEST_SPEC_VERSION - 2;
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageVersionSpecMismatch(
+						SOURCE_CHAIN_ID,
+						id,
+						TEST_SPEC_VERSION,
+						TEST_SPEC_VERSION - 2,
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn should_fail_on_spec_version_mismatch_if_outside_of_grace_window() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let mut message =
+				prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
+			message.spec_version = TEST_SPEC_VERSION - 3;
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageVersionSpecMismatch(
+						SOURCE_CHAIN_ID,
+						id,
+						TEST_SPEC_VERSION,
+						TEST_SPEC_VERSION - 3,
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
 	#[test]
 	fn should_fail_on_weight_mismatch() {
 		new_test_ext().execute_with(|| {
@@ -552,7 +955,7 @@ mod tests {
 			message.weight = 0;
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -582,7 +985,7 @@ mod tests {
 			);
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -604,7 +1007,13 @@ mod tests {
 			let id = [0; 4];
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Err(()));
+			Dispatch::dispatch(
+				SOURCE_CHAIN_ID,
+				TARGET_CHAIN_ID,
+				id,
+				Err(MessagePayloadDecodeError::InvalidPayload),
+				|_, _| unreachable!(),
+			);
 
 			assert_eq!(
 				System::events(),
@@ -620,6 +1029,38 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_emit_event_for_messages_with_unknown_payload_version() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+			let passed_version = CURRENT_MESSAGE_PAYLOAD_VERSION + 1;
+
+			System::set_block_number(1);
+			let unspent_weight = Dispatch::dispatch(
+				SOURCE_CHAIN_ID,
+				TARGET_CHAIN_ID,
+				id,
+				Err(MessagePayloadDecodeError::UnknownVersion(passed_version)),
+				|_, _| unreachable!(),
+			);
+
+			assert_eq!(unspent_weight, 0);
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessagePayloadVersionMismatch(
+						SOURCE_CHAIN_ID,
+						id,
+						CURRENT_MESSAGE_PAYLOAD_VERSION,
+						passed_version,
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
 	#[test]
 	fn should_fail_on_call_decode() {
 		new_test_ext().execute_with(|| {
@@ -628,9 +1069,10 @@ mod tests {
 			let mut message =
 				prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
 			message.call.0 = vec![];
+			let call_hash = BlakeTwo256::hash_of(&message.call);
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -638,11 +1080,13 @@ mod tests {
 					phase: Phase::Initialization,
 					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageCallDecodeFailed(
 						SOURCE_CHAIN_ID,
-						id
+						id,
+						call_hash,
 					)),
 					topics: vec![],
 				}],
 			);
+			assert_eq!(Dispatch::decode_failures(SOURCE_CHAIN_ID, id), 1);
 		});
 	}
 
@@ -657,7 +1101,7 @@ mod tests {
 			message.weight = weight;
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -680,7 +1124,7 @@ mod tests {
 			let message = prepare_root_message(Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3])));
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -697,6 +1141,42 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_return_unspent_dispatch_weight() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+			let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1, 2, 3]));
+			let call_weight = call.get_dispatch_info().weight;
+
+			// when message is dispatched, the unspent weight is refunded
+			let message = prepare_root_message(call.clone());
+			assert_eq!(
+				Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!()),
+				TEST_WEIGHT - call_weight,
+			);
+
+			// when message isn't dispatched, the whole weight is refunded
+			let call_origin = CallOrigin::TargetAccount(1, TestAccountPublic(1), TestSignature(99));
+			let message = prepare_message(call_origin, call);
+			assert_eq!(
+				Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!()),
+				TEST_WEIGHT,
+			);
+
+			// when message is rejected before dispatch, its weight is unknown
+			assert_eq!(
+				Dispatch::dispatch(
+					SOURCE_CHAIN_ID,
+					TARGET_CHAIN_ID,
+					id,
+					Err(MessagePayloadDecodeError::InvalidPayload),
+					|_, _| unreachable!(),
+				),
+				0
+			);
+		});
+	}
+
 	#[test]
 	fn should_dispatch_bridge_message_from_target_origin() {
 		new_test_ext().execute_with(|| {
@@ -706,7 +1186,7 @@ mod tests {
 			let message = prepare_target_message(call);
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
 
 			assert_eq!(
 				System::events(),
@@ -732,8 +1212,42 @@ mod tests {
 			let message = prepare_source_message(call);
 
 			System::set_block_number(1);
-			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message));
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatched(
+						SOURCE_CHAIN_ID,
+						id,
+						Ok(())
+					)),
+					topics: vec![],
+				}],
+			);
+		})
+	}
+
+	#[test]
+	fn should_pay_dispatch_fee_at_target_chain() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![]));
+			let mut message = prepare_source_message(call);
+			message.dispatch_fee_payment = DispatchFeePayment::AtTargetChain;
+			let expected_account =
+				AccountIdConverter::convert(derive_account_id(SOURCE_CHAIN_ID, SourceAccount::Account(1)));
+
+			System::set_block_number(1);
+			let mut paid_fee = None;
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |account, weight| {
+				paid_fee = Some((*account, weight));
+				Ok(())
+			});
 
+			assert_eq!(paid_fee, Some((expected_account, TEST_WEIGHT)));
 			assert_eq!(
 				System::events(),
 				vec![EventRecord {
@@ -749,6 +1263,201 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn should_not_dispatch_message_if_dispatch_fee_payment_fails() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![]));
+			let mut message = prepare_source_message(call);
+			message.dispatch_fee_payment = DispatchFeePayment::AtTargetChain;
+			let expected_account =
+				AccountIdConverter::convert(derive_account_id(SOURCE_CHAIN_ID, SourceAccount::Account(1)));
+
+			System::set_block_number(1);
+			let unspent_weight = Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| Err(()));
+
+			assert_eq!(unspent_weight, TEST_WEIGHT);
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageDispatchPaymentFailed(
+						SOURCE_CHAIN_ID,
+						id,
+						expected_account,
+						TEST_WEIGHT,
+					)),
+					topics: vec![],
+				}],
+			);
+		})
+	}
+
+	#[test]
+	fn should_dispatch_atomic_batch_from_message() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let call = Call::Dispatch(call_dispatch::Call::<TestRuntime>::dispatch_atomic_batch(vec![
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1])),
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![2])),
+			]));
+			let message = prepare_source_message(call);
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageBatchDispatched(
+						SOURCE_CHAIN_ID,
+						id,
+						vec![Ok(()), Ok(())],
+					)),
+					topics: vec![],
+				}],
+			);
+		})
+	}
+
+	#[test]
+	fn should_stop_atomic_batch_dispatch_on_first_failure() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			// `set_heap_pages` requires root origin, so it fails when dispatched by regular account
+			let call = Call::Dispatch(call_dispatch::Call::<TestRuntime>::dispatch_atomic_batch(vec![
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1])),
+				Call::System(<frame_system::Call<TestRuntime>>::set_heap_pages(1)),
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![3])),
+			]));
+			let message = prepare_source_message(call);
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageBatchDispatched(
+						SOURCE_CHAIN_ID,
+						id,
+						vec![Ok(()), Err(BadOrigin.into())],
+					)),
+					topics: vec![],
+				}],
+			);
+		})
+	}
+
+	#[test]
+	fn should_reject_atomic_batch_with_filtered_call() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let call = Call::Dispatch(call_dispatch::Call::<TestRuntime>::dispatch_atomic_batch(vec![
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1])),
+				Call::System(<frame_system::Call<TestRuntime>>::fill_block(Perbill::from_percent(75))),
+			]));
+			let weight = call.get_dispatch_info().weight;
+			let mut message = prepare_root_message(call);
+			message.weight = weight;
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageCallRejected(
+						SOURCE_CHAIN_ID,
+						id
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn atomic_batch_dispatch_info_includes_overhead_and_depends_on_calls_class() {
+		let normal_call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1]));
+		let operational_call = Call::System(<frame_system::Call<TestRuntime>>::set_heap_pages(1));
+		let operational_call_weight = operational_call.get_dispatch_info().weight;
+
+		assert_eq!(
+			atomic_batch_dispatch_info::<TestRuntime, DefaultInstance>(&[
+				operational_call.clone(),
+				operational_call.clone()
+			]),
+			(
+				<() as WeightInfo>::dispatch_atomic_batch(2) + 2 * operational_call_weight,
+				DispatchClass::Operational,
+				Pays::Yes,
+			),
+		);
+		assert_eq!(
+			atomic_batch_dispatch_info::<TestRuntime, DefaultInstance>(&[operational_call, normal_call]).1,
+			DispatchClass::Normal,
+		);
+		assert_eq!(
+			atomic_batch_dispatch_info::<TestRuntime, DefaultInstance>(&[]),
+			(
+				<() as WeightInfo>::dispatch_atomic_batch(0),
+				DispatchClass::Normal,
+				Pays::Yes
+			),
+		);
+	}
+
+	#[test]
+	fn should_reject_nested_atomic_batch_with_filtered_call() {
+		new_test_ext().execute_with(|| {
+			let id = [0; 4];
+
+			let call = Call::Dispatch(call_dispatch::Call::<TestRuntime>::dispatch_atomic_batch(vec![
+				Call::System(<frame_system::Call<TestRuntime>>::remark(vec![1])),
+				Call::Dispatch(call_dispatch::Call::<TestRuntime>::dispatch_atomic_batch(vec![
+					Call::System(<frame_system::Call<TestRuntime>>::fill_block(Perbill::from_percent(75))),
+				])),
+			]));
+			let weight = call.get_dispatch_info().weight;
+			let mut message = prepare_root_message(call);
+			message.weight = weight;
+
+			System::set_block_number(1);
+			Dispatch::dispatch(SOURCE_CHAIN_ID, TARGET_CHAIN_ID, id, Ok(message), |_, _| unreachable!());
+
+			assert_eq!(
+				System::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: Event::call_dispatch(call_dispatch::Event::<TestRuntime>::MessageCallRejected(
+						SOURCE_CHAIN_ID,
+						id
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn decode_failures_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking
+		// decode failures metrics.
+		let storage_key = storage_keys::decode_failures_key::<TestRuntime, DefaultInstance>(SOURCE_CHAIN_ID, b"test").0;
+		assert_eq!(
+			storage_key,
+			hex!("d6adaadda4f35834d63903b8a1f1d6c73f6eb4e581b4750e5ecd263570313150e8c1c31354d870886a7e63335d3e32417372636544a8995dd50b6657a037a7839304535b74657374").to_vec(),
+		);
+	}
+
 	#[test]
 	fn origin_is_checked_when_verifying_sending_message_using_source_root_account() {
 		let call = Call::System(<frame_system::Call<TestRuntime>>::remark(vec![]));
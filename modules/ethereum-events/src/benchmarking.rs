@@ -0,0 +1,63 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Events module complexity is mostly determined by the verification of the receipt proof
+//! and by dispatched calls, which weight is declared by the caller. So we are giving runtime
+//! opportunity to construct proof of the receipt without dispatchable events.
+
+use super::{Call, Config as EthEventsConfig, Instance, Pallet as EthEventsPallet};
+use bp_eth_poa::receipt_proof::ReceiptInclusionProof;
+use sp_std::prelude::*;
+
+use frame_benchmarking::{account, benchmarks_instance};
+use frame_system::RawOrigin;
+
+const SEED: u32 = 0;
+const MAX_PROOF_SIZE: u32 = 64 * 1024;
+const MAX_LOGS_COUNT: u32 = 256;
+
+/// Pallet we're benchmarking here.
+pub struct Pallet<T: Config<I>, I: Instance>(EthEventsPallet<T, I>);
+
+/// Proof benchmarking parameters.
+pub struct ProofParams {
+	/// Size (in bytes) of the proof should be (if possible) near to this value.
+	pub proof_size: u32,
+	/// Number of logs in the receipt. None of logs should be converted into calls.
+	pub logs_count: u32,
+}
+
+/// Config that must be implemented by runtime.
+pub trait Config<I: Instance>: EthEventsConfig<I> {
+	/// Prepare proof of the finalized receipt.
+	fn make_proof(proof_params: ProofParams) -> ReceiptInclusionProof;
+}
+
+benchmarks_instance! {
+	// Benchmark `dispatch_events` extrinsic with receipt that has no dispatchable events. The weight
+	// of dispatched calls is declared by the caller, so we're only measuring proof verification
+	// and receipt decoding here.
+	dispatch_events {
+		let s in 0..MAX_PROOF_SIZE;
+		let l in 0..MAX_LOGS_COUNT;
+
+		let submitter: T::AccountId = account("submitter", 0, SEED);
+		let proof = T::make_proof(ProofParams {
+			proof_size: s,
+			logs_count: l,
+		});
+	}: dispatch_events(RawOrigin::Signed(submitter), proof, 0)
+}
@@ -0,0 +1,488 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime module that dispatches events of the Ethereum contract as runtime calls.
+//!
+//! The contract emits events (logs) that represent calls that must be dispatched at this chain.
+//! Anyone may submit inclusion proof of the receipt of the transaction that has emitted these
+//! events. The receipt must be included in the finalized block that is known to the Ethereum headers
+//! module. Every event of the receipt that has been emitted by the configured contract is converted
+//! into the call, which is then dispatched. The dispatch result of every call is reported using
+//! the `EventDispatched` event.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Generated by `decl_event!`
+#![allow(clippy::unused_unit)]
+
+use crate::weights::WeightInfo;
+
+use bp_eth_poa::{receipt_proof::ReceiptInclusionProof, Address, LogEntry, RawTransactionReceipt, Receipt, H256};
+use bp_header_chain::InclusionProofVerifier;
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage,
+	dispatch::{DispatchResultWithPostInfo, Dispatchable, Parameter},
+	ensure,
+	traits::Get,
+	weights::{extract_actual_weight, GetDispatchInfo, Weight},
+};
+use frame_system::RawOrigin;
+use sp_runtime::DispatchResult;
+use sp_std::{convert::TryFrom, marker::PhantomData, prelude::*};
+
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+
+/// Minimal size of the RLP-encoded log entry: 21 bytes of address, empty topics list, empty data
+/// and list header.
+const MIN_ENCODED_LOG_SIZE: u32 = 24;
+
+/// Converter of the contract events into runtime calls.
+pub trait EventConverter<AccountId, Call> {
+	/// Convert event that has been emitted by the contract into the call and the account that
+	/// the call must be dispatched from. Returns `None` if the event must be ignored (e.g. if it
+	/// isn't a call event).
+	fn convert(event: &LogEntry) -> Option<(AccountId, Call)>;
+}
+
+/// The module configuration trait.
+pub trait Config<I = DefaultInstance>: frame_system::Config {
+	/// The overarching event type.
+	type Event: From<Event<Self, I>> + Into<<Self as frame_system::Config>::Event>;
+	/// Weights gathered through benchmarking.
+	type WeightInfo: WeightInfo;
+	/// Ethereum blockchain that is the source of events.
+	type PeerBlockchain: InclusionProofVerifier<
+		Transaction = RawTransactionReceipt,
+		TransactionInclusionProof = ReceiptInclusionProof,
+	>;
+	/// Address of the contract, which events are dispatched by the module. Events of all other
+	/// contracts are ignored.
+	type ContractAddress: Get<Address>;
+	/// Converter of the contract events into runtime calls.
+	type EventConverter: EventConverter<Self::AccountId, <Self as Config<I>>::Call>;
+	/// The overarching dispatch call type.
+	type Call: Parameter
+		+ GetDispatchInfo
+		+ Dispatchable<
+			Origin = <Self as frame_system::Config>::Origin,
+			PostInfo = frame_support::dispatch::PostDispatchInfo,
+		>;
+}
+
+decl_storage! {
+	trait Store for Pallet<T: Config<I>, I: Instance = DefaultInstance> as BridgeEthEvents {
+		/// Receipts (identified by block hash and transaction index) which events have already been
+		/// dispatched.
+		DispatchedReceipts: map hasher(blake2_128_concat) (H256, u64) => ();
+	}
+}
+
+decl_event!(
+	pub enum Event<T, I = DefaultInstance> where
+		<T as frame_system::Config>::AccountId,
+	{
+		/// Contract event has been dispatched. Arguments are: hash of the block, index of the
+		/// transaction within the block, index of the event within the transaction receipt, account
+		/// that the call has been dispatched from and the dispatch result.
+		EventDispatched(H256, u64, u32, AccountId, DispatchResult),
+		/// Phantom member, never used. Needed to handle multiple pallet instances.
+		_Dummy(PhantomData<I>),
+	}
+);
+
+decl_error! {
+	pub enum Error for Pallet<T: Config<I>, I: Instance> {
+		/// The receipt is not included in the finalized block, or the transaction has failed.
+		UnfinalizedReceipt,
+		/// The receipt can't be decoded.
+		InvalidReceipt,
+		/// Events of the receipt have already been dispatched.
+		AlreadyDispatched,
+		/// Declared dispatch weight is lower than the weight of calls.
+		InsufficientDispatchWeight,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Config<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+
+		/// Dispatch calls that are represented by the contract events of the transaction receipt.
+		///
+		/// The `dispatch_weight` must cover weight of all calls. It is added to the weight of the
+		/// proof verification. Unspent weight is refunded.
+		#[weight = dispatch_events_weight::<T, I>(proof, None).saturating_add(*dispatch_weight)]
+		pub fn dispatch_events(
+			origin,
+			proof: ReceiptInclusionProof,
+			dispatch_weight: Weight,
+		) -> DispatchResultWithPostInfo {
+			let _ = frame_system::ensure_signed(origin)?;
+
+			let receipt_id = (proof.block, proof.index);
+			ensure!(
+				!DispatchedReceipts::<I>::contains_key(&receipt_id),
+				Error::<T, I>::AlreadyDispatched,
+			);
+
+			// verify and decode receipt
+			let raw_receipt = T::PeerBlockchain::verify_transaction_inclusion_proof(&proof)
+				.ok_or(Error::<T, I>::UnfinalizedReceipt)?;
+			let receipt = Receipt::decode_rlp(&raw_receipt).map_err(|_| Error::<T, I>::InvalidReceipt)?;
+
+			// convert contract events into calls
+			let calls = contract_calls::<T, I>(&receipt);
+			let calls_weight = calls
+				.iter()
+				.map(|(_, _, call)| call.get_dispatch_info().weight)
+				.fold(0, Weight::saturating_add);
+			ensure!(calls_weight <= dispatch_weight, Error::<T, I>::InsufficientDispatchWeight);
+
+			// mark receipt as dispatched before dispatching calls, so that calls can't dispatch it again
+			DispatchedReceipts::<I>::insert(&receipt_id, ());
+
+			let logs_count = u32::try_from(receipt.logs.len()).unwrap_or(u32::MAX);
+			let mut actual_weight = dispatch_events_weight::<T, I>(&proof, Some(logs_count));
+			for (event_index, account, call) in calls {
+				let dispatch_info = call.get_dispatch_info();
+				let dispatch_result = call.dispatch(RawOrigin::Signed(account.clone()).into());
+				actual_weight = actual_weight.saturating_add(extract_actual_weight(&dispatch_result, &dispatch_info));
+
+				log::trace!(
+					target: "runtime::bridge-eth-events",
+					"Event {} of transaction {}/{:?} has been dispatched. Result: {:?}",
+					event_index,
+					proof.index,
+					proof.block,
+					dispatch_result,
+				);
+
+				Self::deposit_event(RawEvent::EventDispatched(
+					proof.block,
+					proof.index,
+					event_index,
+					account,
+					dispatch_result.map(drop).map_err(|e| e.error),
+				));
+			}
+
+			Ok(Some(actual_weight).into())
+		}
+	}
+}
+
+impl<T: Config<I>, I: Instance> Pallet<T, I> {
+	/// Returns true if events of the receipt with given id have already been dispatched.
+	pub fn is_receipt_dispatched(block: H256, index: u64) -> bool {
+		DispatchedReceipts::<I>::contains_key(&(block, index))
+	}
+}
+
+/// Weight of the `dispatch_events` call, excluding weight of dispatched calls. If the number of
+/// logs in the receipt is unknown, the maximal number of logs that may fit into the proof is used.
+fn dispatch_events_weight<T: Config<I>, I: Instance>(proof: &ReceiptInclusionProof, logs_count: Option<u32>) -> Weight {
+	let proof_size = proof
+		.proof
+		.iter()
+		.fold(0usize, |size, node| size.saturating_add(node.len()));
+	let proof_size = u32::try_from(proof_size).unwrap_or(u32::MAX);
+	let logs_count = logs_count.unwrap_or(proof_size / MIN_ENCODED_LOG_SIZE);
+	T::WeightInfo::dispatch_events(proof_size, logs_count)
+}
+
+/// Convert events of the configured contract into calls. Returns index of the event within
+/// the receipt, dispatch origin and the call.
+fn contract_calls<T: Config<I>, I: Instance>(receipt: &Receipt) -> Vec<(u32, T::AccountId, <T as Config<I>>::Call)> {
+	let contract_address = T::ContractAddress::get();
+	receipt
+		.logs
+		.iter()
+		.enumerate()
+		.filter(|(_, event)| event.address == contract_address)
+		.filter_map(|(event_index, event)| {
+			T::EventConverter::convert(event).map(|(account, call)| (event_index as u32, account, call))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	// From construct_runtime macro
+	#![allow(clippy::from_over_into)]
+
+	use super::*;
+	use bp_eth_poa::{TransactionOutcome, U256};
+	use frame_support::{assert_noop, assert_ok, parameter_types};
+	use frame_system::{EventRecord, Phase};
+	use sp_runtime::{
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+		DispatchError,
+	};
+
+	type AccountId = u64;
+
+	const SUBMITTER: AccountId = 1;
+	const DISPATCH_WEIGHT: Weight = 1_000_000_000;
+	const CONTRACT_ADDRESS: [u8; 20] = [1u8; 20];
+	const REMARK_EVENT_TOPIC: [u8; 32] = [2u8; 32];
+	const SET_HEAP_PAGES_EVENT_TOPIC: [u8; 32] = [3u8; 32];
+
+	pub struct DummyBlockchain;
+
+	impl InclusionProofVerifier for DummyBlockchain {
+		type Transaction = RawTransactionReceipt;
+		type TransactionInclusionProof = ReceiptInclusionProof;
+
+		fn verify_transaction_inclusion_proof(proof: &Self::TransactionInclusionProof) -> Option<Self::Transaction> {
+			// proofs of receipts from unknown blocks are invalid
+			if proof.block == H256::zero() {
+				return None;
+			}
+
+			proof.proof.first().cloned()
+		}
+	}
+
+	pub struct DummyEventConverter;
+
+	impl EventConverter<AccountId, Call> for DummyEventConverter {
+		fn convert(event: &LogEntry) -> Option<(AccountId, Call)> {
+			let account = event.topics.get(1)?.to_low_u64_be();
+			match event.topics.first()?.0 {
+				REMARK_EVENT_TOPIC => Some((account, Call::System(frame_system::Call::remark(event.data.clone())))),
+				SET_HEAP_PAGES_EVENT_TOPIC => Some((account, Call::System(frame_system::Call::set_heap_pages(1)))),
+				_ => None,
+			}
+		}
+	}
+
+	type Block = frame_system::mocking::MockBlock<TestRuntime>;
+	type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
+
+	use crate as pallet_bridge_eth_events;
+
+	frame_support::construct_runtime! {
+		pub enum TestRuntime where
+			Block = Block,
+			NodeBlock = Block,
+			UncheckedExtrinsic = UncheckedExtrinsic,
+		{
+			System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+			EthEvents: pallet_bridge_eth_events::{Pallet, Call, Event<T>},
+		}
+	}
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const ContractAddress: Address = Address::repeat_byte(1);
+	}
+
+	impl frame_system::Config for TestRuntime {
+		type Origin = Origin;
+		type Index = u64;
+		type Call = Call;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = Event;
+		type BlockHashCount = BlockHashCount;
+		type Version = ();
+		type PalletInfo = PalletInfo;
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type BaseCallFilter = ();
+		type SystemWeightInfo = ();
+		type BlockWeights = ();
+		type BlockLength = ();
+		type DbWeight = ();
+		type SS58Prefix = ();
+		type OnSetCode = ();
+	}
+
+	impl Config for TestRuntime {
+		type Event = Event;
+		type WeightInfo = ();
+		type PeerBlockchain = DummyBlockchain;
+		type ContractAddress = ContractAddress;
+		type EventConverter = DummyEventConverter;
+		type Call = Call;
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let t = frame_system::GenesisConfig::default()
+			.build_storage::<TestRuntime>()
+			.unwrap();
+		sp_io::TestExternalities::new(t)
+	}
+
+	fn event(contract: [u8; 20], topic: [u8; 32], account: AccountId) -> LogEntry {
+		LogEntry {
+			address: contract.into(),
+			topics: vec![topic.into(), H256::from_low_u64_be(account)],
+			data: vec![42],
+		}
+	}
+
+	fn receipt_proof(events: Vec<LogEntry>) -> ReceiptInclusionProof {
+		let receipt = Receipt {
+			outcome: TransactionOutcome::StatusCode(1),
+			gas_used: U256::zero(),
+			log_bloom: Default::default(),
+			logs: events,
+		};
+		ReceiptInclusionProof {
+			block: H256::repeat_byte(1),
+			index: 0,
+			proof: vec![receipt.rlp()],
+		}
+	}
+
+	fn dispatched_event(event_index: u32, account: AccountId, result: DispatchResult) -> EventRecord<Event, H256> {
+		EventRecord {
+			phase: Phase::Initialization,
+			event: Event::pallet_bridge_eth_events(pallet_bridge_eth_events::Event::<TestRuntime>::EventDispatched(
+				H256::repeat_byte(1),
+				0,
+				event_index,
+				account,
+				result,
+			)),
+			topics: vec![],
+		}
+	}
+
+	#[test]
+	fn unfinalized_receipt_is_rejected() {
+		new_test_ext().execute_with(|| {
+			let mut proof = receipt_proof(vec![event(CONTRACT_ADDRESS, REMARK_EVENT_TOPIC, 1)]);
+			proof.block = H256::zero();
+			assert_noop!(
+				EthEvents::dispatch_events(Origin::signed(SUBMITTER), proof, DISPATCH_WEIGHT),
+				Error::<TestRuntime, DefaultInstance>::UnfinalizedReceipt,
+			);
+		});
+	}
+
+	#[test]
+	fn invalid_receipt_is_rejected() {
+		new_test_ext().execute_with(|| {
+			let mut proof = receipt_proof(vec![event(CONTRACT_ADDRESS, REMARK_EVENT_TOPIC, 1)]);
+			proof.proof = vec![vec![42]];
+			assert_noop!(
+				EthEvents::dispatch_events(Origin::signed(SUBMITTER), proof, DISPATCH_WEIGHT),
+				Error::<TestRuntime, DefaultInstance>::InvalidReceipt,
+			);
+		});
+	}
+
+	#[test]
+	fn receipt_with_insufficient_dispatch_weight_is_rejected() {
+		new_test_ext().execute_with(|| {
+			let proof = receipt_proof(vec![event(CONTRACT_ADDRESS, REMARK_EVENT_TOPIC, 1)]);
+			let call_weight = Call::System(frame_system::Call::remark(vec![42]))
+				.get_dispatch_info()
+				.weight;
+			assert_noop!(
+				EthEvents::dispatch_events(Origin::signed(SUBMITTER), proof, call_weight - 1),
+				Error::<TestRuntime, DefaultInstance>::InsufficientDispatchWeight,
+			);
+		});
+	}
+
+	#[test]
+	fn receipt_events_are_dispatched() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+
+			let proof = receipt_proof(vec![
+				event(CONTRACT_ADDRESS, REMARK_EVENT_TOPIC, 1),
+				// events of other contracts are ignored
+				event([0u8; 20], REMARK_EVENT_TOPIC, 2),
+				// unknown events are ignored
+				event(CONTRACT_ADDRESS, [0u8; 32], 3),
+				// failed calls are reported
+				event(CONTRACT_ADDRESS, SET_HEAP_PAGES_EVENT_TOPIC, 4),
+			]);
+			assert_ok!(EthEvents::dispatch_events(
+				Origin::signed(SUBMITTER),
+				proof,
+				DISPATCH_WEIGHT
+			));
+
+			assert!(EthEvents::is_receipt_dispatched(H256::repeat_byte(1), 0));
+			assert_eq!(
+				System::events(),
+				vec![
+					dispatched_event(0, 1, Ok(())),
+					dispatched_event(3, 4, Err(DispatchError::BadOrigin)),
+				],
+			);
+		});
+	}
+
+	#[test]
+	fn proof_verification_is_charged() {
+		new_test_ext().execute_with(|| {
+			// receipt without dispatchable events
+			let proof = receipt_proof(vec![
+				event([0u8; 20], REMARK_EVENT_TOPIC, 1),
+				event(CONTRACT_ADDRESS, [0u8; 32], 2),
+			]);
+			let proof_size = proof.proof[0].len() as u32;
+
+			// caller pays for verification of the largest receipt that fits into the proof
+			let declared_weight = Call::EthEvents(crate::Call::<TestRuntime>::dispatch_events(proof.clone(), 0))
+				.get_dispatch_info()
+				.weight;
+			assert_eq!(
+				declared_weight,
+				<() as WeightInfo>::dispatch_events(proof_size, proof_size / MIN_ENCODED_LOG_SIZE),
+			);
+
+			// and the weight is refunded if receipt has less logs
+			let post_info = EthEvents::dispatch_events(Origin::signed(SUBMITTER), proof, 0).unwrap();
+			assert_eq!(
+				post_info.actual_weight,
+				Some(<() as WeightInfo>::dispatch_events(proof_size, 2)),
+			);
+			assert!(post_info.actual_weight.unwrap() > 0);
+		});
+	}
+
+	#[test]
+	fn receipt_events_are_dispatched_once() {
+		new_test_ext().execute_with(|| {
+			let proof = receipt_proof(vec![event(CONTRACT_ADDRESS, REMARK_EVENT_TOPIC, 1)]);
+			assert_ok!(EthEvents::dispatch_events(
+				Origin::signed(SUBMITTER),
+				proof.clone(),
+				DISPATCH_WEIGHT
+			));
+			assert_noop!(
+				EthEvents::dispatch_events(Origin::signed(SUBMITTER), proof, DISPATCH_WEIGHT),
+				Error::<TestRuntime, DefaultInstance>::AlreadyDispatched,
+			);
+		});
+	}
+}
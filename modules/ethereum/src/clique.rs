@@ -0,0 +1,722 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique (EIP-225) proof-of-authority verification.
+//!
+//! Clique headers are signed by one of authorized signers. The signature is stored in the last
+//! 65 bytes of the header extra data. Signers may vote to add or remove signers, using the header
+//! author (beneficiary) and nonce fields. Every `epoch` blocks, votes are discarded and the
+//! checkpoint header lists all current signers in its extra data.
+//!
+//! The state of the Clique engine at some header is represented by the `CliqueSnapshot`. Verifying
+//! the header on top of the snapshot produces the snapshot at this header.
+
+use crate::error::Error;
+use crate::ChainTime;
+use bp_eth_poa::{public_to_address, Address, AuraHeader, HeaderId, H256, U256};
+use codec::{Decode, Encode};
+use sp_io::{crypto::secp256k1_ecdsa_recover, hashing::keccak_256};
+use sp_runtime::RuntimeDebug;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+/// Number of extra data prefix bytes reserved for signer vanity.
+const EXTRA_VANITY: usize = 32;
+/// Number of extra data suffix bytes reserved for signer seal.
+const EXTRA_SEAL: usize = 65;
+/// Magic nonce to vote on adding a new signer.
+const NONCE_AUTH_VOTE: [u8; 8] = [0xff; 8];
+/// Magic nonce to vote on removing a signer.
+const NONCE_DROP_VOTE: [u8; 8] = [0x00; 8];
+/// Difficulty of the header, signed by in-turn signer.
+const DIFFICULTY_IN_TURN: u64 = 2;
+/// Difficulty of the header, signed by out-of-turn signer.
+const DIFFICULTY_NO_TURN: u64 = 1;
+
+/// Clique engine configuration parameters.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct CliqueConfiguration {
+	/// Minimal number of seconds between consecutive headers.
+	pub period: u64,
+	/// Number of headers after which votes are discarded and checkpoint header is produced.
+	pub epoch: u64,
+	/// Minimum gas limit.
+	pub min_gas_limit: U256,
+	/// Maximum gas limit.
+	pub max_gas_limit: U256,
+	/// Number of best chain descendants after which the header is treated as finalized.
+	pub finality_confirmations: u64,
+}
+
+/// Single vote of the signer.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct CliqueVote {
+	/// Signer that has cast this vote.
+	pub signer: Address,
+	/// Number of the header where the vote has been cast.
+	pub block_number: u64,
+	/// Address that is voted for.
+	pub address: Address,
+	/// Whether to authorize or to remove the address.
+	pub authorize: bool,
+}
+
+/// Cumulative votes for the single address.
+#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct CliqueTally {
+	/// Whether votes are to authorize or to remove the address.
+	pub authorize: bool,
+	/// Number of votes.
+	pub votes: u64,
+}
+
+/// State of the Clique engine at given header.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct CliqueSnapshot {
+	/// Id of the header this snapshot is computed for.
+	pub id: HeaderId,
+	/// Authorized signers, sorted by address.
+	pub signers: Vec<Address>,
+	/// Numbers of recent headers and their signers.
+	pub recents: BTreeMap<u64, Address>,
+	/// Votes that have been cast since last checkpoint.
+	pub votes: Vec<CliqueVote>,
+	/// Current tally of votes, by voted address.
+	pub tally: BTreeMap<Address, CliqueTally>,
+}
+
+/// Verify Clique header on top of the snapshot at its parent header.
+///
+/// Returns snapshot at the verified header.
+pub fn verify_clique_header<CT: ChainTime>(
+	config: &CliqueConfiguration,
+	snapshot: &CliqueSnapshot,
+	parent: &AuraHeader,
+	header: &AuraHeader,
+	chain_time: &CT,
+) -> Result<CliqueSnapshot, Error> {
+	contextless_checks(config, header, chain_time)?;
+	contextual_checks(config, snapshot, parent, header)?;
+
+	let signer = recover_signer(header)?;
+	let mut snapshot = snapshot.clone();
+	snapshot.apply(config, header, signer)?;
+	Ok(snapshot)
+}
+
+/// Perform basic checks that only require header itself.
+fn contextless_checks<CT: ChainTime>(
+	config: &CliqueConfiguration,
+	header: &AuraHeader,
+	chain_time: &CT,
+) -> Result<(), Error> {
+	if header.seal.len() != 2 {
+		return Err(Error::InvalidSealArity);
+	}
+	if header.number >= u64::max_value() {
+		return Err(Error::RidiculousNumber);
+	}
+	if header.gas_used > header.gas_limit {
+		return Err(Error::TooMuchGasUsed);
+	}
+	if header.gas_limit < config.min_gas_limit || header.gas_limit > config.max_gas_limit {
+		return Err(Error::InvalidGasLimit);
+	}
+	if chain_time.is_timestamp_ahead(header.timestamp) {
+		return Err(Error::HeaderTimestampIsAhead);
+	}
+
+	// checkpoint headers can't contain votes
+	let is_checkpoint = is_checkpoint(config, header.number);
+	let nonce = header.nonce().ok_or(Error::MissingNonce)?;
+	if nonce != NONCE_AUTH_VOTE && nonce != NONCE_DROP_VOTE {
+		return Err(Error::InvalidVote);
+	}
+	if is_checkpoint && (header.author != Address::zero() || nonce != NONCE_DROP_VOTE) {
+		return Err(Error::InvalidVote);
+	}
+
+	// only checkpoint headers may contain signers list
+	if header.extra_data.len() < EXTRA_VANITY {
+		return Err(Error::InvalidExtraData);
+	}
+	if header.extra_data.len() < EXTRA_VANITY + EXTRA_SEAL {
+		return Err(Error::MissingSignature);
+	}
+	let signers_bytes = header.extra_data.len() - EXTRA_VANITY - EXTRA_SEAL;
+	if !is_checkpoint && signers_bytes != 0 {
+		return Err(Error::InvalidExtraData);
+	}
+	if is_checkpoint && signers_bytes % Address::len_bytes() != 0 {
+		return Err(Error::InvalidCheckpointSigners);
+	}
+
+	if header.mix_hash().ok_or(Error::MissingMixHash)? != H256::zero() {
+		return Err(Error::InvalidMixHash);
+	}
+	if header.uncles_hash != empty_uncles_hash() {
+		return Err(Error::InvalidUnclesHash);
+	}
+	if header.number != 0
+		&& header.difficulty != DIFFICULTY_IN_TURN.into()
+		&& header.difficulty != DIFFICULTY_NO_TURN.into()
+	{
+		return Err(Error::InvalidDifficulty);
+	}
+
+	Ok(())
+}
+
+/// Perform checks that require access to parent header and snapshot.
+fn contextual_checks(
+	config: &CliqueConfiguration,
+	snapshot: &CliqueSnapshot,
+	parent: &AuraHeader,
+	header: &AuraHeader,
+) -> Result<(), Error> {
+	let parent_id = parent.compute_id();
+	if snapshot.id != parent_id || header.parent_id() != Some(parent_id) {
+		return Err(Error::MissingParentBlock);
+	}
+	if parent.timestamp.saturating_add(config.period) > header.timestamp {
+		return Err(Error::InvalidTimestamp);
+	}
+
+	// gas limit may only be changed by 1/1024 of the parent gas limit
+	let gas_limit_bound = parent.gas_limit / 1024;
+	let gas_limit_difference = if header.gas_limit > parent.gas_limit {
+		header.gas_limit - parent.gas_limit
+	} else {
+		parent.gas_limit - header.gas_limit
+	};
+	if gas_limit_difference >= gas_limit_bound {
+		return Err(Error::InvalidGasLimit);
+	}
+
+	// checkpoint header must list all current signers
+	if is_checkpoint(config, header.number) && checkpoint_signers(header)? != snapshot.signers {
+		return Err(Error::InvalidCheckpointSigners);
+	}
+
+	Ok(())
+}
+
+impl CliqueSnapshot {
+	/// Create snapshot from the trusted checkpoint header (e.g. genesis header).
+	pub fn from_checkpoint(header: &AuraHeader) -> Result<Self, Error> {
+		let mut signers = checkpoint_signers(header)?;
+		if signers.is_empty() {
+			return Err(Error::InvalidCheckpointSigners);
+		}
+		signers.sort();
+
+		Ok(CliqueSnapshot {
+			id: header.compute_id(),
+			signers,
+			recents: BTreeMap::new(),
+			votes: Vec::new(),
+			tally: BTreeMap::new(),
+		})
+	}
+
+	/// Returns true if given signer is in-turn signer of the header with given number.
+	pub fn is_in_turn(&self, block_number: u64, signer: &Address) -> bool {
+		match self.signers.binary_search(signer) {
+			Ok(signer_index) => block_number % self.signers.len() as u64 == signer_index as u64,
+			Err(_) => false,
+		}
+	}
+
+	/// Apply header, signed by given signer, to the snapshot.
+	fn apply(&mut self, config: &CliqueConfiguration, header: &AuraHeader, signer: Address) -> Result<(), Error> {
+		let number = header.number;
+		if is_checkpoint(config, number) {
+			self.votes.clear();
+			self.tally.clear();
+		}
+
+		// the oldest signer in recents list is allowed to sign again
+		self.prune_recents(number);
+
+		// check that the signer is authorized and hasn't signed recently
+		if self.signers.binary_search(&signer).is_err() {
+			return Err(Error::NotValidator);
+		}
+		if self.recents.values().any(|recent_signer| *recent_signer == signer) {
+			return Err(Error::RecentlySigned);
+		}
+		let expected_difficulty = if self.is_in_turn(number, &signer) {
+			DIFFICULTY_IN_TURN
+		} else {
+			DIFFICULTY_NO_TURN
+		};
+		if header.difficulty != expected_difficulty.into() {
+			return Err(Error::InvalidDifficulty);
+		}
+		self.recents.insert(number, signer);
+
+		// discard previous vote of the signer for the same address
+		let address = header.author;
+		if let Some(vote_index) = self
+			.votes
+			.iter()
+			.position(|vote| vote.signer == signer && vote.address == address)
+		{
+			let vote = self.votes.remove(vote_index);
+			self.uncast(vote.address, vote.authorize);
+		}
+
+		// tally new vote
+		let authorize = match header.nonce().ok_or(Error::MissingNonce)? {
+			NONCE_AUTH_VOTE => true,
+			NONCE_DROP_VOTE => false,
+			_ => return Err(Error::InvalidVote),
+		};
+		if self.cast(address, authorize) {
+			self.votes.push(CliqueVote {
+				signer,
+				block_number: number,
+				address,
+				authorize,
+			});
+		}
+
+		// if the vote has passed, update signers list
+		let tally = self.tally.get(&address).copied();
+		if let Some(tally) = tally.filter(|tally| tally.votes > self.signers.len() as u64 / 2) {
+			match self.signers.binary_search(&address) {
+				Err(signer_index) if tally.authorize => self.signers.insert(signer_index, address),
+				Ok(signer_index) if !tally.authorize => {
+					self.signers.remove(signer_index);
+
+					// signers list has shrunk => remove leftover recents
+					self.prune_recents(number);
+
+					// discard votes of the removed signer
+					let mut vote_index = 0;
+					while vote_index < self.votes.len() {
+						if self.votes[vote_index].signer == address {
+							let vote = self.votes.remove(vote_index);
+							self.uncast(vote.address, vote.authorize);
+						} else {
+							vote_index += 1;
+						}
+					}
+				}
+				_ => (),
+			}
+
+			// discard votes for the address
+			self.votes.retain(|vote| vote.address != address);
+			self.tally.remove(&address);
+		}
+
+		self.id = header.compute_id();
+		Ok(())
+	}
+
+	/// Remove the recent signer that is now allowed to sign again.
+	fn prune_recents(&mut self, block_number: u64) {
+		let limit = self.signers.len() as u64 / 2 + 1;
+		if block_number >= limit {
+			self.recents.remove(&(block_number - limit));
+		}
+	}
+
+	/// Add vote to the tally. Returns false if the vote is meaningless.
+	fn cast(&mut self, address: Address, authorize: bool) -> bool {
+		let is_signer = self.signers.binary_search(&address).is_ok();
+		if is_signer == authorize {
+			return false;
+		}
+
+		self.tally
+			.entry(address)
+			.and_modify(|tally| tally.votes += 1)
+			.or_insert(CliqueTally { authorize, votes: 1 });
+		true
+	}
+
+	/// Remove vote from the tally.
+	fn uncast(&mut self, address: Address, authorize: bool) {
+		let is_last_vote = match self.tally.get_mut(&address) {
+			Some(tally) if tally.authorize == authorize && tally.votes > 1 => {
+				tally.votes -= 1;
+				false
+			}
+			Some(tally) => tally.authorize == authorize,
+			None => false,
+		};
+		if is_last_vote {
+			self.tally.remove(&address);
+		}
+	}
+}
+
+/// Returns hash of the header that is signed by the signer.
+pub fn seal_hash(header: &AuraHeader) -> H256 {
+	let mut unsealed_header = header.clone();
+	let extra_len = unsealed_header.extra_data.len();
+	unsealed_header
+		.extra_data
+		.truncate(extra_len.saturating_sub(EXTRA_SEAL));
+	unsealed_header.compute_hash()
+}
+
+/// Recover the header signer from the signature in its extra data.
+pub fn recover_signer(header: &AuraHeader) -> Result<Address, Error> {
+	let extra_len = header.extra_data.len();
+	if extra_len < EXTRA_VANITY + EXTRA_SEAL {
+		return Err(Error::MissingSignature);
+	}
+
+	let mut signature = [0u8; EXTRA_SEAL];
+	signature.copy_from_slice(&header.extra_data[extra_len - EXTRA_SEAL..]);
+	secp256k1_ecdsa_recover(&signature, seal_hash(header).as_fixed_bytes())
+		.map(|public| public_to_address(&public))
+		.map_err(|_| Error::NotValidator)
+}
+
+/// Returns signers, listed in the checkpoint header extra data.
+fn checkpoint_signers(header: &AuraHeader) -> Result<Vec<Address>, Error> {
+	let extra_len = header.extra_data.len();
+	if extra_len < EXTRA_VANITY + EXTRA_SEAL {
+		return Err(Error::InvalidCheckpointSigners);
+	}
+
+	let signers_bytes = &header.extra_data[EXTRA_VANITY..extra_len - EXTRA_SEAL];
+	if signers_bytes.len() % Address::len_bytes() != 0 {
+		return Err(Error::InvalidCheckpointSigners);
+	}
+
+	Ok(signers_bytes
+		.chunks(Address::len_bytes())
+		.map(Address::from_slice)
+		.collect())
+}
+
+/// Returns true if header with given number is the checkpoint header.
+fn is_checkpoint(config: &CliqueConfiguration, block_number: u64) -> bool {
+	config.epoch != 0 && block_number % config.epoch == 0
+}
+
+/// Returns hash of the empty uncles list.
+fn empty_uncles_hash() -> H256 {
+	// 0xc0 is RLP of the empty list
+	keccak_256(&[0xc0]).into()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use crate::mock::{validator, ConstChainTime};
+	use bp_eth_poa::{
+		rlp_encode,
+		signatures::{secret_to_address, sign, SecretKey},
+	};
+
+	const TEST_PERIOD: u64 = 15;
+	const TEST_EPOCH: u64 = 30_000;
+	pub(crate) const TOTAL_SIGNERS: usize = 3;
+
+	pub(crate) fn test_config() -> CliqueConfiguration {
+		CliqueConfiguration {
+			period: TEST_PERIOD,
+			epoch: TEST_EPOCH,
+			min_gas_limit: 0x1388.into(),
+			max_gas_limit: U256::max_value(),
+			finality_confirmations: 2,
+		}
+	}
+
+	/// Returns secret keys of test signers, sorted by signer address.
+	pub(crate) fn signers(count: usize) -> Vec<SecretKey> {
+		let mut signers = (0..count).map(validator).collect::<Vec<_>>();
+		signers.sort_by_key(secret_to_address);
+		signers
+	}
+
+	fn seal(nonce: [u8; 8]) -> Vec<Vec<u8>> {
+		vec![rlp_encode(&H256::zero()).to_vec(), rlp_encode(&nonce.to_vec()).to_vec()]
+	}
+
+	pub(crate) fn genesis() -> AuraHeader {
+		let mut extra_data = vec![0u8; EXTRA_VANITY];
+		for signer in signers(TOTAL_SIGNERS) {
+			extra_data.extend_from_slice(secret_to_address(&signer).as_bytes());
+		}
+		extra_data.extend_from_slice(&[0u8; EXTRA_SEAL]);
+
+		AuraHeader {
+			gas_limit: 0x1388.into(),
+			difficulty: DIFFICULTY_NO_TURN.into(),
+			uncles_hash: empty_uncles_hash(),
+			extra_data,
+			seal: seal(NONCE_DROP_VOTE),
+			..Default::default()
+		}
+	}
+
+	pub(crate) fn child(parent: &AuraHeader, signer_index: usize) -> AuraHeader {
+		let number = parent.number + 1;
+		let is_in_turn = number % TOTAL_SIGNERS as u64 == signer_index as u64;
+		AuraHeader {
+			parent_hash: parent.compute_hash(),
+			number,
+			timestamp: parent.timestamp + TEST_PERIOD,
+			gas_limit: parent.gas_limit,
+			difficulty: if is_in_turn {
+				DIFFICULTY_IN_TURN
+			} else {
+				DIFFICULTY_NO_TURN
+			}
+			.into(),
+			uncles_hash: empty_uncles_hash(),
+			extra_data: vec![0u8; EXTRA_VANITY],
+			seal: seal(NONCE_DROP_VOTE),
+			..Default::default()
+		}
+	}
+
+	pub(crate) fn sign_by(mut header: AuraHeader, signer: &SecretKey) -> AuraHeader {
+		header.extra_data.extend_from_slice(&[0u8; EXTRA_SEAL]);
+		let signature = sign(signer, seal_hash(&header));
+		let extra_len = header.extra_data.len();
+		header.extra_data[extra_len - EXTRA_SEAL..].copy_from_slice(signature.as_bytes());
+		header
+	}
+
+	pub(crate) fn signed_child(parent: &AuraHeader, signer_index: usize) -> AuraHeader {
+		sign_by(child(parent, signer_index), &signers(TOTAL_SIGNERS)[signer_index])
+	}
+
+	fn verify(snapshot: &CliqueSnapshot, parent: &AuraHeader, header: &AuraHeader) -> Result<CliqueSnapshot, Error> {
+		verify_clique_header(&test_config(), snapshot, parent, header, &ConstChainTime::default())
+	}
+
+	#[test]
+	fn snapshot_is_created_from_checkpoint() {
+		let snapshot = CliqueSnapshot::from_checkpoint(&genesis()).unwrap();
+		assert_eq!(
+			snapshot.signers,
+			signers(TOTAL_SIGNERS).iter().map(secret_to_address).collect::<Vec<_>>(),
+		);
+		assert_eq!(snapshot.id, genesis().compute_id());
+
+		let mut header = genesis();
+		header.extra_data = vec![0u8; EXTRA_VANITY + EXTRA_SEAL];
+		assert_eq!(
+			CliqueSnapshot::from_checkpoint(&header),
+			Err(Error::InvalidCheckpointSigners)
+		);
+	}
+
+	#[test]
+	fn in_turn_and_out_of_turn_headers_are_accepted() {
+		let genesis = genesis();
+		let snapshot = CliqueSnapshot::from_checkpoint(&genesis).unwrap();
+
+		// header#1 is signed by in-turn signer
+		let header1 = signed_child(&genesis, 1);
+		let snapshot = verify(&snapshot, &genesis, &header1).unwrap();
+		assert_eq!(snapshot.id, header1.compute_id());
+
+		// header#2 is signed by out-of-turn signer
+		let header2 = signed_child(&header1, 0);
+		let snapshot = verify(&snapshot, &header1, &header2).unwrap();
+		assert_eq!(snapshot.id, header2.compute_id());
+	}
+
+	#[test]
+	fn header_with_wrong_difficulty_is_rejected() {
+		let genesis = genesis();
+		let snapshot = CliqueSnapshot::from_checkpoint(&genesis).unwrap();
+
+		let mut header = child(&genesis, 1);
+		header.difficulty = DIFFICULTY_NO_TURN.into();
+		let header = sign_by(header, &signers(TOTAL_SIGNERS)[1]);
+		assert_eq!(verify(&snapshot, &genesis, &header), Err(Error::InvalidDifficulty));
+
+		let mut header = child(&genesis, 1);
+		header.difficulty = 3.into();
+		let header = sign_by(header, &signers(TOTAL_SIGNERS)[1]);
+		assert_eq!(verify(&snapshot, &genesis, &header), Err(Error::InvalidDifficulty));
+	}
+
+	#[test]
+	fn header_signed_by_unauthorized_signer_is_rejected() {
+		let genesis = genesis();
+		let snapshot = CliqueSnapshot::from_checkpoint(&genesis).unwrap();
+
+		let header = sign_by(child(&genesis, TOTAL_SIGNERS), &validator(TOTAL_SIGNERS));
+		assert_eq!(verify(&snapshot, &genesis, &header), Err(Error::NotValidator));
+	}
+
+	#[test]
+	fn header_signed_by_recent_signer_is_rejected() {
+		let genesis = genesis();
+		let snapshot = CliqueSnapshot::from_checkpoint(&genesis).unwrap();
+
+		let header1 = signed_child(&genesis, 1);
+		let snapshot = verify(&snapshot, &genesis, &header1).unwrap();
+
+		// with 3 signers, the signer of header#1 can't sign header#2
+		let header2 = signed_child(&header1, 1);
+		assert_eq!(verify(&snapshot, &header1, &header2), Err(Error::RecentlySigned));
+
+		// ... but may sign header#3
+		let header2 = signed_child(&header1, 2);
+		let snapshot = verify(&snapshot, &header1, &header2).unwrap();
+		let header3 = signed_child(&header2, 1);
+		assert!(verify(&snapshot, &header2, &header3).is_ok());
+	}
+
+	#[test]
+	fn signer_is_added_and_removed_by_votes() {
+		let genesis = genesis();
+		let mut snapshot = CliqueSnapshot::from_checkpoint(&genesis).unwrap();
+		let new_signer = secret_to_address(&validator(TOTAL_SIGNERS));
+
+		// two of three signers vote for the new signer
+		let mut parent = genesis;
+		for signer_index in vec![1, 2] {
+			let mut header = child(&parent, signer_index);
+			header.author = new_signer;
+			header.seal = seal(NONCE_AUTH_VOTE);
+			let header = sign_by(header, &signers(TOTAL_SIGNERS)[signer_index]);
+			snapshot = verify(&snapshot, &parent, &header).unwrap();
+			parent = header;
+		}
+		assert!(snapshot.signers.contains(&new_signer));
+		assert!(snapshot.votes.is_empty());
+		assert!(snapshot.tally.is_empty());
+
+		// three of four signers vote to remove the first signer (the order respects recent signers)
+		let removed_signer = secret_to_address(&signers(TOTAL_SIGNERS)[0]);
+		let voters = sp_std::iter::once(validator(TOTAL_SIGNERS))
+			.chain(signers(TOTAL_SIGNERS).into_iter().skip(1))
+			.collect::<Vec<_>>();
+		for voter in &voters {
+			let number = parent.number + 1;
+			let mut header = child(&parent, 0);
+			header.difficulty = if snapshot.is_in_turn(number, &secret_to_address(voter)) {
+				DIFFICULTY_IN_TURN
+			} else {
+				DIFFICULTY_NO_TURN
+			}
+			.into();
+			header.author = removed_signer;
+			header.seal = seal(NONCE_DROP_VOTE);
+			let header = sign_by(header, voter);
+			snapshot = verify(&snapshot, &parent, &header).unwrap();
+			parent = header;
+		}
+		assert!(!snapshot.signers.contains(&removed_signer));
+		assert_eq!(snapshot.signers.len(), TOTAL_SIGNERS);
+		assert!(snapshot.tally.is_empty());
+	}
+
+	#[test]
+	fn checkpoint_header_is_verified() {
+		let config = CliqueConfiguration {
+			epoch: 2,
+			..test_config()
+		};
+		let genesis = genesis();
+		let snapshot = CliqueSnapshot::from_checkpoint(&genesis).unwrap();
+		let header1 = signed_child(&genesis, 1);
+		let snapshot =
+			verify_clique_header(&config, &snapshot, &genesis, &header1, &ConstChainTime::default()).unwrap();
+		let verify_checkpoint = |header: &AuraHeader| {
+			verify_clique_header(&config, &snapshot, &header1, header, &ConstChainTime::default())
+		};
+
+		// checkpoint header must list all signers
+		let mut header2 = child(&header1, 2);
+		header2.extra_data = genesis.extra_data[..genesis.extra_data.len() - EXTRA_SEAL].to_vec();
+		let valid_header2 = sign_by(header2.clone(), &signers(TOTAL_SIGNERS)[2]);
+		assert!(verify_checkpoint(&valid_header2).is_ok());
+
+		// checkpoint header with missing signers is rejected
+		let mut invalid_header2 = header2.clone();
+		invalid_header2.extra_data.truncate(EXTRA_VANITY + Address::len_bytes());
+		let invalid_header2 = sign_by(invalid_header2, &signers(TOTAL_SIGNERS)[2]);
+		assert_eq!(
+			verify_checkpoint(&invalid_header2),
+			Err(Error::InvalidCheckpointSigners)
+		);
+
+		// checkpoint header can't contain votes
+		let mut invalid_header2 = header2;
+		invalid_header2.author = secret_to_address(&validator(TOTAL_SIGNERS));
+		let invalid_header2 = sign_by(invalid_header2, &signers(TOTAL_SIGNERS)[2]);
+		assert_eq!(verify_checkpoint(&invalid_header2), Err(Error::InvalidVote));
+
+		// non-checkpoint header can't list signers
+		let mut header1 = child(&genesis, 1);
+		header1.extra_data = genesis.extra_data[..genesis.extra_data.len() - EXTRA_SEAL].to_vec();
+		let header1 = sign_by(header1, &signers(TOTAL_SIGNERS)[1]);
+		assert_eq!(
+			verify_clique_header(
+				&config,
+				&CliqueSnapshot::from_checkpoint(&genesis).unwrap(),
+				&genesis,
+				&header1,
+				&ConstChainTime::default()
+			),
+			Err(Error::InvalidExtraData),
+		);
+	}
+
+	#[test]
+	fn header_with_invalid_fields_is_rejected() {
+		let genesis = genesis();
+		let snapshot = CliqueSnapshot::from_checkpoint(&genesis).unwrap();
+		let verify_modified = |modify: fn(&mut AuraHeader)| {
+			let mut header = child(&genesis, 1);
+			modify(&mut header);
+			verify(&snapshot, &genesis, &sign_by(header, &signers(TOTAL_SIGNERS)[1]))
+		};
+
+		assert_eq!(
+			verify_modified(|header| header.timestamp -= 1),
+			Err(Error::InvalidTimestamp)
+		);
+		assert_eq!(
+			verify_modified(|header| header.seal[0] = rlp_encode(&H256::repeat_byte(42)).to_vec()),
+			Err(Error::InvalidMixHash),
+		);
+		assert_eq!(
+			verify_modified(|header| header.seal[1] = rlp_encode(&vec![0x42u8; 8]).to_vec()),
+			Err(Error::InvalidVote),
+		);
+		assert_eq!(
+			verify_modified(|header| header.uncles_hash = H256::repeat_byte(42)),
+			Err(Error::InvalidUnclesHash),
+		);
+		assert_eq!(
+			verify_modified(|header| header.gas_limit = header.gas_limit * 2),
+			Err(Error::InvalidGasLimit),
+		);
+		assert_eq!(
+			verify_modified(|header| header.parent_hash = H256::repeat_byte(42)),
+			Err(Error::MissingParentBlock),
+		);
+
+		let mut header = signed_child(&genesis, 1);
+		header.extra_data.truncate(EXTRA_VANITY - 1);
+		assert_eq!(verify(&snapshot, &genesis, &header), Err(Error::InvalidExtraData));
+	}
+}
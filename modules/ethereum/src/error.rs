@@ -64,6 +64,34 @@ pub enum Error {
 	TryingToFinalizeSibling = 20,
 	/// Header timestamp is ahead of on-chain timestamp
 	HeaderTimestampIsAhead = 21,
+	/// The header Ethash mix hash is missing from the header.
+	MissingMixHash = 22,
+	/// The header Ethash nonce is missing from the header.
+	MissingNonce = 23,
+	/// Header timestamp is not greater than the parent header timestamp.
+	InvalidTimestamp = 24,
+	/// Ethash mix hash of the header doesn't match the computed one.
+	InvalidMixHash = 25,
+	/// Ethash proof-of-work of the header doesn't meet the header difficulty.
+	InsufficientWork = 26,
+	/// Header extra data has invalid format.
+	InvalidExtraData = 27,
+	/// Signers list of the checkpoint header is invalid.
+	InvalidCheckpointSigners = 28,
+	/// Header has non-empty uncles hash.
+	InvalidUnclesHash = 29,
+	/// The header signer has signed one of recent headers.
+	RecentlySigned = 30,
+	/// The header contains invalid vote.
+	InvalidVote = 31,
+	/// Missing Ethash seal proof for the operation.
+	MissingSealProof = 32,
+	/// Redundant Ethash seal proof is provided.
+	RedundantSealProof = 33,
+	/// Ethash seal proof doesn't match the header epoch dataset.
+	InvalidSealProof = 34,
+	/// Ethash dataset of the header epoch is unknown.
+	MissingEthashDataset = 35,
 }
 
 impl Error {
@@ -91,6 +119,20 @@ impl Error {
 			Error::UnsignedTooFarInTheFuture => "The unsigned header is too far in future",
 			Error::TryingToFinalizeSibling => "Trying to finalize sibling of finalized block",
 			Error::HeaderTimestampIsAhead => "Header timestamp is ahead of on-chain timestamp",
+			Error::MissingMixHash => "Header is missing mix hash seal",
+			Error::MissingNonce => "Header is missing nonce seal",
+			Error::InvalidTimestamp => "Header timestamp is not greater than parent timestamp",
+			Error::InvalidMixHash => "Header has invalid mix hash",
+			Error::InsufficientWork => "Header proof-of-work is below its difficulty",
+			Error::InvalidExtraData => "Header has invalid extra data",
+			Error::InvalidCheckpointSigners => "Checkpoint header has invalid signers list",
+			Error::InvalidUnclesHash => "Header has non-empty uncles hash",
+			Error::RecentlySigned => "Header signer has recently signed another header",
+			Error::InvalidVote => "Header has invalid vote",
+			Error::MissingSealProof => "The import operation requires Ethash seal proof",
+			Error::RedundantSealProof => "Redundant Ethash seal proof is provided",
+			Error::InvalidSealProof => "Invalid Ethash seal proof provided",
+			Error::MissingEthashDataset => "Ethash dataset of the header epoch is unknown",
 		}
 	}
 
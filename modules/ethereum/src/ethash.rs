@@ -0,0 +1,1128 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethash proof-of-work verification.
+//!
+//! The Ethash seal consists of two fields: the mix hash and the nonce. The mix hash is computed from
+//! 64 pseudo-randomly selected rows of the epoch dataset. The dataset is larger than 1GB, so it can't
+//! be generated on-chain. Instead, the header is submitted together with the `EthashSealProof`: the
+//! dataset rows that are accessed when computing the mix hash and merkle proofs of their inclusion
+//! into the dataset. Merkle roots of epoch datasets are the part of the `EthashConfiguration`.
+//!
+//! There are two ways to verify the seal:
+//!
+//! 1) light verification only checks that the hash of the header, nonce and mix hash meets the header
+//!    difficulty. It doesn't check that the mix hash has been computed from the dataset, so the
+//!    proof-of-work isn't memory-hard and this mode must only be used in tests;
+//! 2) full verification recomputes the mix hash using dataset rows from the seal proof.
+//!
+//! With the `std` feature, epoch caches may be generated off-chain. The `EpochCache` may be used to
+//! verify seals without proofs, and the `DatasetMerkleTree` is used to compute dataset merkle roots
+//! and to build seal proofs. Generated caches are kept by the `EpochCaches` structure.
+
+use crate::error::Error;
+use crate::ChainTime;
+use bp_eth_poa::{AuraHeader, H256, H512, U256, U512};
+use codec::{Decode, Encode};
+use sp_io::hashing::{keccak_256, keccak_512};
+use sp_runtime::RuntimeDebug;
+use sp_std::{convert::TryFrom, prelude::*};
+
+#[cfg(feature = "std")]
+use sp_std::collections::btree_map::BTreeMap;
+
+/// Number of blocks in single Ethash epoch.
+pub const EPOCH_LENGTH: u64 = 30_000;
+/// Number of dataset rows that are accessed when computing the mix hash.
+pub const ACCESSES: u32 = 64;
+
+/// Size of the dataset at genesis.
+const DATASET_BYTES_INIT: u64 = 1 << 30;
+/// Dataset growth per epoch.
+const DATASET_BYTES_GROWTH: u64 = 1 << 23;
+/// Size of the cache at genesis.
+const CACHE_BYTES_INIT: u64 = 1 << 24;
+/// Cache growth per epoch.
+const CACHE_BYTES_GROWTH: u64 = 1 << 17;
+/// Width of mix.
+const MIX_BYTES: usize = 128;
+/// Hash length in bytes.
+const HASH_BYTES: usize = 64;
+/// Number of 32-bit words in the hash.
+const HASH_WORDS: usize = HASH_BYTES / 4;
+/// Number of 32-bit words in the mix.
+const MIX_WORDS: usize = MIX_BYTES / 4;
+/// Number of hashes in the mix.
+const MIX_HASHES: usize = MIX_BYTES / HASH_BYTES;
+/// Number of parents of each dataset element.
+#[cfg(feature = "std")]
+const DATASET_PARENTS: u32 = 256;
+/// Number of rounds in cache production.
+#[cfg(feature = "std")]
+const CACHE_ROUNDS: usize = 3;
+/// Multiplier of the FNV hash function.
+const FNV_PRIME: u32 = 0x0100_0193;
+/// Maximal value of the (excess) difficulty adjustment factor.
+const MAX_DIFFICULTY_ADJUSTMENT_FACTOR: u64 = 99;
+
+/// Single hash of the Ethash cache or dataset, represented as 32-bit words.
+type Node = [u32; HASH_WORDS];
+
+/// Ethash seal verification mode.
+#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug)]
+pub enum EthashVerificationMode {
+	/// Only check that the header hash, nonce and mix hash meet the header difficulty. The mix hash
+	/// isn't checked against the dataset, so this mode must only be used in tests.
+	Light,
+	/// Recompute the mix hash using dataset rows from the seal proof, that is submitted together with
+	/// the header.
+	Full,
+}
+
+/// Ethash dataset of the single epoch.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct EthashDataset {
+	/// Size of the dataset (in bytes).
+	pub size: u64,
+	/// Merkle root of the dataset rows.
+	pub merkle_root: H256,
+}
+
+/// Ethash engine configuration parameters.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct EthashConfiguration {
+	/// Seal verification mode.
+	pub verification_mode: EthashVerificationMode,
+	/// Epoch of the first dataset in `datasets`.
+	pub first_dataset_epoch: u64,
+	/// Datasets of consecutive epochs, starting from the `first_dataset_epoch`. In the
+	/// `EthashVerificationMode::Full` mode, headers of epochs without known dataset are rejected.
+	pub datasets: Vec<EthashDataset>,
+	/// Minimal difficulty of the header.
+	pub min_difficulty: U256,
+	/// Number of blocks the difficulty bomb is delayed for.
+	pub difficulty_bomb_delay: u64,
+	/// Minimum gas limit.
+	pub min_gas_limit: U256,
+	/// Maximum gas limit.
+	pub max_gas_limit: U256,
+	/// Maximum size of extra data.
+	pub maximum_extra_data_size: u64,
+	/// Number of best chain descendants after which the header is treated as finalized.
+	pub finality_confirmations: u64,
+}
+
+/// Dataset row that is accessed when computing the mix hash, with the merkle proof of its inclusion
+/// into the epoch dataset.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub struct DatasetRowProof {
+	/// Dataset items of the row.
+	pub items: [H512; MIX_HASHES],
+	/// Hashes of sibling nodes on the path from the row to the dataset merkle root, starting from
+	/// the leaves level.
+	pub merkle_proof: Vec<H256>,
+}
+
+/// Dataset rows that are accessed when computing the header mix hash, in the access order.
+pub type EthashSealProof = Vec<DatasetRowProof>;
+
+/// Ethash data of the single epoch.
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, RuntimeDebug)]
+pub struct EpochCache {
+	/// Epoch number.
+	epoch: u64,
+	/// Size of the full dataset (in bytes).
+	full_size: u64,
+	/// Cache that is used to compute dataset items.
+	cache: Vec<Node>,
+}
+
+/// Recently generated epoch caches.
+#[cfg(feature = "std")]
+#[derive(RuntimeDebug)]
+pub struct EpochCaches {
+	/// Maximal number of caches to keep.
+	max_caches: usize,
+	/// Generated caches, ordered by epoch number.
+	caches: BTreeMap<u64, EpochCache>,
+}
+
+/// Merkle tree of the full epoch dataset.
+#[cfg(feature = "std")]
+#[derive(RuntimeDebug)]
+pub struct DatasetMerkleTree {
+	/// Size of the dataset (in bytes).
+	size: u64,
+	/// Dataset rows.
+	rows: Vec<[H512; MIX_HASHES]>,
+	/// Tree levels, starting from the leaves level. The last level only contains the root.
+	levels: Vec<Vec<H256>>,
+}
+
+impl EthashConfiguration {
+	/// Returns dataset of the given epoch.
+	pub fn dataset(&self, epoch: u64) -> Option<&EthashDataset> {
+		epoch
+			.checked_sub(self.first_dataset_epoch)
+			.and_then(|index| usize::try_from(index).ok())
+			.and_then(|index| self.datasets.get(index))
+	}
+}
+
+impl DatasetRowProof {
+	/// Returns true if the row is the `row`-th row of the dataset with given merkle root.
+	fn verify(&self, merkle_root: &H256, depth: usize, row: u32) -> bool {
+		if self.merkle_proof.len() != depth {
+			return false;
+		}
+
+		let mut index = row;
+		let mut hash = row_hash(&self.items);
+		for sibling in &self.merkle_proof {
+			hash = match index & 1 {
+				0 => merkle_node_hash(&hash, sibling),
+				_ => merkle_node_hash(sibling, &hash),
+			};
+			index >>= 1;
+		}
+
+		hash == *merkle_root
+	}
+
+	/// Returns dataset items of the row.
+	fn nodes(&self) -> [Node; MIX_HASHES] {
+		let mut nodes = [[0u32; HASH_WORDS]; MIX_HASHES];
+		for (node, item) in nodes.iter_mut().zip(self.items.iter()) {
+			*node = bytes_to_node(item.as_fixed_bytes());
+		}
+		nodes
+	}
+}
+
+/// Verify Ethash header, given its parent header.
+///
+/// The seal proof must be provided in `EthashVerificationMode::Full` mode and must be `None` in
+/// `EthashVerificationMode::Light` mode.
+pub fn verify_ethash_header<CT: ChainTime>(
+	config: &EthashConfiguration,
+	parent: &AuraHeader,
+	header: &AuraHeader,
+	chain_time: &CT,
+	seal_proof: Option<&EthashSealProof>,
+) -> Result<(), Error> {
+	contextless_checks(config, header, chain_time)?;
+	contextual_checks(config, parent, header)?;
+
+	match (config.verification_mode, seal_proof) {
+		(EthashVerificationMode::Light, None) => verify_seal_light(header),
+		(EthashVerificationMode::Light, Some(_)) => Err(Error::RedundantSealProof),
+		(EthashVerificationMode::Full, Some(seal_proof)) => verify_seal_full(config, header, seal_proof),
+		(EthashVerificationMode::Full, None) => Err(Error::MissingSealProof),
+	}
+}
+
+/// Perform basic checks that only require header itself.
+fn contextless_checks<CT: ChainTime>(
+	config: &EthashConfiguration,
+	header: &AuraHeader,
+	chain_time: &CT,
+) -> Result<(), Error> {
+	if header.seal.len() != 2 {
+		return Err(Error::InvalidSealArity);
+	}
+	if header.number >= u64::max_value() {
+		return Err(Error::RidiculousNumber);
+	}
+	if header.gas_used > header.gas_limit {
+		return Err(Error::TooMuchGasUsed);
+	}
+	if header.gas_limit < config.min_gas_limit || header.gas_limit > config.max_gas_limit {
+		return Err(Error::InvalidGasLimit);
+	}
+	if header.extra_data.len() as u64 > config.maximum_extra_data_size {
+		return Err(Error::ExtraDataOutOfBounds);
+	}
+	if header.difficulty < config.min_difficulty {
+		return Err(Error::InvalidDifficulty);
+	}
+	if chain_time.is_timestamp_ahead(header.timestamp) {
+		return Err(Error::HeaderTimestampIsAhead);
+	}
+
+	Ok(())
+}
+
+/// Perform checks that require access to parent header.
+fn contextual_checks(config: &EthashConfiguration, parent: &AuraHeader, header: &AuraHeader) -> Result<(), Error> {
+	if header.parent_hash != parent.compute_hash() || Some(header.number) != parent.number.checked_add(1) {
+		return Err(Error::MissingParentBlock);
+	}
+	if header.timestamp <= parent.timestamp {
+		return Err(Error::InvalidTimestamp);
+	}
+
+	// gas limit may only be changed by 1/1024 of the parent gas limit
+	let gas_limit_bound = parent.gas_limit / 1024;
+	let gas_limit_difference = if header.gas_limit > parent.gas_limit {
+		header.gas_limit - parent.gas_limit
+	} else {
+		parent.gas_limit - header.gas_limit
+	};
+	if gas_limit_difference >= gas_limit_bound {
+		return Err(Error::InvalidGasLimit);
+	}
+
+	if header.difficulty != calculate_difficulty(config, parent, header) {
+		return Err(Error::InvalidDifficulty);
+	}
+
+	Ok(())
+}
+
+/// Calculate expected header difficulty (EIP-100 rules with delayed difficulty bomb).
+pub fn calculate_difficulty(config: &EthashConfiguration, parent: &AuraHeader, header: &AuraHeader) -> U256 {
+	// 0xc0 is RLP of the empty list
+	let empty_uncles_hash: H256 = keccak_256(&[0xc0]).into();
+	let uncles_factor = if parent.uncles_hash == empty_uncles_hash { 1 } else { 2 };
+	let time_factor = header.timestamp.saturating_sub(parent.timestamp) / 9;
+
+	let adjustment_step = parent.difficulty / 2048;
+	let mut difficulty = if uncles_factor >= time_factor {
+		parent
+			.difficulty
+			.saturating_add(adjustment_step * U256::from(uncles_factor - time_factor))
+	} else {
+		let decrease_factor = sp_std::cmp::min(time_factor - uncles_factor, MAX_DIFFICULTY_ADJUSTMENT_FACTOR);
+		parent
+			.difficulty
+			.saturating_sub(adjustment_step * U256::from(decrease_factor))
+	};
+	difficulty = sp_std::cmp::max(difficulty, config.min_difficulty);
+
+	let bomb_period = header.number.saturating_sub(config.difficulty_bomb_delay) / 100_000;
+	if bomb_period > 1 {
+		let bomb = match bomb_period - 2 {
+			bomb_exponent if bomb_exponent < 256 => U256::one() << bomb_exponent as usize,
+			_ => U256::max_value(),
+		};
+		difficulty = difficulty.saturating_add(bomb);
+	}
+
+	difficulty
+}
+
+/// Check that the proof-of-work, computed using mix hash from the header seal, meets the header
+/// difficulty.
+pub fn verify_seal_light(header: &AuraHeader) -> Result<(), Error> {
+	let mix_hash = header.mix_hash().ok_or(Error::MissingMixHash)?;
+	let nonce = header.nonce().ok_or(Error::MissingNonce)?;
+	let seed = seed_hash_with_nonce(&bare_hash(header), &nonce);
+	let result = keccak_256(&[&seed[..], mix_hash.as_bytes()].concat());
+
+	ensure_enough_work(header, &result)
+}
+
+/// Recompute header mix hash using dataset rows from the seal proof and check that the proof-of-work
+/// meets the header difficulty.
+///
+/// Every dataset row is checked against the merkle root of the header epoch dataset.
+pub fn verify_seal_full(
+	config: &EthashConfiguration,
+	header: &AuraHeader,
+	seal_proof: &EthashSealProof,
+) -> Result<(), Error> {
+	let mix_hash = header.mix_hash().ok_or(Error::MissingMixHash)?;
+	let nonce = header.nonce().ok_or(Error::MissingNonce)?;
+	let dataset = config
+		.dataset(epoch(header.number))
+		.ok_or(Error::MissingEthashDataset)?;
+	let rows = u32::try_from(dataset.size / MIX_BYTES as u64)
+		.ok()
+		.filter(|rows| *rows != 0)
+		.ok_or(Error::MissingEthashDataset)?;
+	if seal_proof.len() != ACCESSES as usize {
+		return Err(Error::InvalidSealProof);
+	}
+
+	let depth = merkle_tree_depth(rows);
+	let (computed_mix_hash, result) = hashimoto(&bare_hash(header), &nonce, rows, |access, row| {
+		let row_proof = &seal_proof[access];
+		if !row_proof.verify(&dataset.merkle_root, depth, row) {
+			return Err(Error::InvalidSealProof);
+		}
+
+		Ok(row_proof.nodes())
+	})?;
+	if computed_mix_hash != mix_hash {
+		return Err(Error::InvalidMixHash);
+	}
+
+	ensure_enough_work(header, &result)
+}
+
+/// Recompute header mix hash using epoch cache and check that the proof-of-work meets the header
+/// difficulty.
+#[cfg(feature = "std")]
+pub fn verify_seal_with_cache(cache: &EpochCache, header: &AuraHeader) -> Result<(), Error> {
+	let mix_hash = header.mix_hash().ok_or(Error::MissingMixHash)?;
+	let nonce = header.nonce().ok_or(Error::MissingNonce)?;
+	if cache.epoch != epoch(header.number) {
+		return Err(Error::InvalidMixHash);
+	}
+
+	let (computed_mix_hash, result) = cache.hashimoto(&bare_hash(header), &nonce);
+	if computed_mix_hash != mix_hash {
+		return Err(Error::InvalidMixHash);
+	}
+
+	ensure_enough_work(header, result.as_fixed_bytes())
+}
+
+#[cfg(feature = "std")]
+impl EpochCache {
+	/// Generate cache for given epoch.
+	///
+	/// This is a heavy operation that is not suitable for on-chain use.
+	pub fn generate(epoch: u64) -> Self {
+		Self::generate_with_sizes(epoch, cache_size(epoch), full_size(epoch))
+	}
+
+	/// Generate cache of given size for given epoch.
+	fn generate_with_sizes(epoch: u64, cache_size: u64, full_size: u64) -> Self {
+		let nodes = (cache_size / HASH_BYTES as u64) as usize;
+		let mut cache = Vec::with_capacity(nodes);
+		let mut node = keccak_512(&seed_hash(epoch));
+		for _ in 0..nodes {
+			cache.push(bytes_to_node(&node));
+			node = keccak_512(&node);
+		}
+
+		for _ in 0..CACHE_ROUNDS {
+			for i in 0..nodes {
+				let previous = &cache[(i + nodes - 1) % nodes];
+				let other = &cache[cache[i][0] as usize % nodes];
+				let mut mixed = [0u32; HASH_WORDS];
+				for (index, word) in mixed.iter_mut().enumerate() {
+					*word = previous[index] ^ other[index];
+				}
+				cache[i] = keccak_512_node(&mixed);
+			}
+		}
+
+		EpochCache {
+			epoch,
+			full_size,
+			cache,
+		}
+	}
+
+	/// Returns epoch of this cache.
+	pub fn epoch(&self) -> u64 {
+		self.epoch
+	}
+
+	/// Compute mix hash and proof-of-work result using the light (cache-only) hashimoto algorithm.
+	pub fn hashimoto(&self, bare_hash: &H256, nonce: &[u8; 8]) -> (H256, H256) {
+		let (mix_hash, result) = hashimoto(bare_hash, nonce, self.rows(), |_, row| Ok(self.dataset_row(row)))
+			.expect("dataset rows are computed from the cache and are never rejected; qed");
+		(mix_hash, result.into())
+	}
+
+	/// Returns number of rows in the full dataset.
+	fn rows(&self) -> u32 {
+		(self.full_size / MIX_BYTES as u64) as u32
+	}
+
+	/// Compute dataset row using the cache.
+	fn dataset_row(&self, row: u32) -> [Node; MIX_HASHES] {
+		let mut nodes = [[0u32; HASH_WORDS]; MIX_HASHES];
+		for (index, node) in nodes.iter_mut().enumerate() {
+			*node = self.dataset_item(row * MIX_HASHES as u32 + index as u32);
+		}
+		nodes
+	}
+
+	/// Compute dataset item using the cache.
+	fn dataset_item(&self, index: u32) -> Node {
+		let nodes = self.cache.len() as u32;
+		let mut mix = self.cache[(index % nodes) as usize];
+		mix[0] ^= index;
+		mix = keccak_512_node(&mix);
+
+		for parent in 0..DATASET_PARENTS {
+			let parent_index = fnv(index ^ parent, mix[parent as usize % HASH_WORDS]) % nodes;
+			let parent_node = &self.cache[parent_index as usize];
+			for (word, parent_word) in mix.iter_mut().zip(parent_node.iter()) {
+				*word = fnv(*word, *parent_word);
+			}
+		}
+
+		keccak_512_node(&mix)
+	}
+}
+
+#[cfg(feature = "std")]
+impl EpochCaches {
+	/// Create new caches container that keeps at most `max_caches` recent epoch caches.
+	pub fn new(max_caches: usize) -> Self {
+		EpochCaches {
+			max_caches,
+			caches: BTreeMap::new(),
+		}
+	}
+
+	/// Returns cache of the epoch that given block belongs to, generating it if required.
+	///
+	/// If there are too many caches, the cache of the oldest epoch is removed.
+	pub fn get(&mut self, block_number: u64) -> &EpochCache {
+		let epoch = epoch(block_number);
+		if !self.caches.contains_key(&epoch) {
+			self.caches.insert(epoch, EpochCache::generate(epoch));
+			while self.caches.len() > sp_std::cmp::max(self.max_caches, 1) {
+				let oldest_epoch = match self.caches.keys().find(|cached_epoch| **cached_epoch != epoch) {
+					Some(oldest_epoch) => *oldest_epoch,
+					None => break,
+				};
+				self.caches.remove(&oldest_epoch);
+			}
+		}
+
+		self.caches
+			.get(&epoch)
+			.expect("we have just inserted cache of this epoch; it is never removed above; qed")
+	}
+}
+
+#[cfg(feature = "std")]
+impl DatasetMerkleTree {
+	/// Compute full dataset of the cache epoch and build its merkle tree.
+	///
+	/// This is a heavy operation that requires memory for the full dataset.
+	pub fn generate(cache: &EpochCache) -> Self {
+		let rows = (0..cache.rows())
+			.map(|row| {
+				let nodes = cache.dataset_row(row);
+				let mut items = [H512::zero(); MIX_HASHES];
+				for (item, node) in items.iter_mut().zip(nodes.iter()) {
+					*item = H512(node_to_bytes(node));
+				}
+				items
+			})
+			.collect::<Vec<_>>();
+
+		let mut leaves = rows.iter().map(row_hash).collect::<Vec<_>>();
+		leaves.resize(1 << merkle_tree_depth(cache.rows()), H256::zero());
+		let mut levels = vec![leaves];
+		while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+			let parents = level
+				.chunks(2)
+				.map(|children| merkle_node_hash(&children[0], &children[1]))
+				.collect::<Vec<_>>();
+			levels.push(parents);
+		}
+
+		DatasetMerkleTree {
+			size: cache.full_size,
+			rows,
+			levels,
+		}
+	}
+
+	/// Returns the dataset, that may be added to the `EthashConfiguration`.
+	pub fn dataset(&self) -> EthashDataset {
+		EthashDataset {
+			size: self.size,
+			merkle_root: self.levels[self.levels.len() - 1][0],
+		}
+	}
+
+	/// Build seal proof for the header of the dataset epoch.
+	pub fn seal_proof(&self, header: &AuraHeader) -> Result<EthashSealProof, Error> {
+		let nonce = header.nonce().ok_or(Error::MissingNonce)?;
+		let mut seal_proof = Vec::with_capacity(ACCESSES as usize);
+		hashimoto(&bare_hash(header), &nonce, self.rows.len() as u32, |_, row| {
+			let row_proof = self.row_proof(row);
+			let nodes = row_proof.nodes();
+			seal_proof.push(row_proof);
+			Ok(nodes)
+		})?;
+
+		Ok(seal_proof)
+	}
+
+	/// Returns given dataset row with its merkle proof.
+	fn row_proof(&self, row: u32) -> DatasetRowProof {
+		let mut index = row as usize;
+		let merkle_proof = self.levels[..self.levels.len() - 1]
+			.iter()
+			.map(|level| {
+				let sibling = level[index ^ 1];
+				index >>= 1;
+				sibling
+			})
+			.collect();
+
+		DatasetRowProof {
+			items: self.rows[row as usize],
+			merkle_proof,
+		}
+	}
+}
+
+/// Returns epoch of the given block.
+pub fn epoch(block_number: u64) -> u64 {
+	block_number / EPOCH_LENGTH
+}
+
+/// Returns seed hash of the given epoch.
+pub fn seed_hash(epoch: u64) -> [u8; 32] {
+	let mut seed = [0u8; 32];
+	for _ in 0..epoch {
+		seed = keccak_256(&seed);
+	}
+	seed
+}
+
+/// Returns size of the epoch cache (in bytes).
+pub fn cache_size(epoch: u64) -> u64 {
+	let mut size = CACHE_BYTES_INIT + CACHE_BYTES_GROWTH * epoch - HASH_BYTES as u64;
+	while !is_prime(size / HASH_BYTES as u64) {
+		size -= 2 * HASH_BYTES as u64;
+	}
+	size
+}
+
+/// Returns size of the epoch dataset (in bytes).
+pub fn full_size(epoch: u64) -> u64 {
+	let mut size = DATASET_BYTES_INIT + DATASET_BYTES_GROWTH * epoch - MIX_BYTES as u64;
+	while !is_prime(size / MIX_BYTES as u64) {
+		size -= 2 * MIX_BYTES as u64;
+	}
+	size
+}
+
+/// Compute mix hash and proof-of-work result of the hashimoto algorithm.
+///
+/// The `dataset_row` is called with the access number and the index of the accessed row of the dataset,
+/// that has `rows` rows.
+fn hashimoto(
+	bare_hash: &H256,
+	nonce: &[u8; 8],
+	rows: u32,
+	mut dataset_row: impl FnMut(usize, u32) -> Result<[Node; MIX_HASHES], Error>,
+) -> Result<(H256, [u8; 32]), Error> {
+	let seed = seed_hash_with_nonce(bare_hash, nonce);
+	let seed_node = bytes_to_node(&seed);
+
+	let mut mix = [0u32; MIX_WORDS];
+	for (index, word) in mix.iter_mut().enumerate() {
+		*word = seed_node[index % HASH_WORDS];
+	}
+
+	for i in 0..ACCESSES {
+		let row = fnv(i ^ seed_node[0], mix[i as usize % MIX_WORDS]) % rows;
+		for (j, item) in dataset_row(i as usize, row)?.iter().enumerate() {
+			for (word, item_word) in item.iter().enumerate() {
+				let index = j * HASH_WORDS + word;
+				mix[index] = fnv(mix[index], *item_word);
+			}
+		}
+	}
+
+	let mut mix_hash = [0u8; 32];
+	for (index, chunk) in mix.chunks(4).enumerate() {
+		let compressed = fnv(fnv(fnv(chunk[0], chunk[1]), chunk[2]), chunk[3]);
+		mix_hash[index * 4..index * 4 + 4].copy_from_slice(&compressed.to_le_bytes());
+	}
+
+	let result = keccak_256(&[&seed[..], &mix_hash[..]].concat());
+	Ok((mix_hash.into(), result))
+}
+
+/// Returns depth of the merkle tree of the dataset with given number of rows.
+fn merkle_tree_depth(rows: u32) -> usize {
+	let mut depth = 0;
+	while (1u64 << depth) < rows as u64 {
+		depth += 1;
+	}
+	depth
+}
+
+/// Returns hash of the dataset row (leaf of the dataset merkle tree).
+fn row_hash(items: &[H512; MIX_HASHES]) -> H256 {
+	keccak_256(&items.iter().map(|item| item.as_bytes()).collect::<Vec<_>>().concat()).into()
+}
+
+/// Returns hash of the inner node of the dataset merkle tree.
+fn merkle_node_hash(left: &H256, right: &H256) -> H256 {
+	keccak_256(&[left.as_bytes(), right.as_bytes()].concat()).into()
+}
+
+/// Returns hash of the header without seal.
+fn bare_hash(header: &AuraHeader) -> H256 {
+	header
+		.seal_hash(false)
+		.expect("seal hash without empty steps is always computed; qed")
+}
+
+/// Returns error if the proof-of-work result doesn't meet the header difficulty.
+fn ensure_enough_work(header: &AuraHeader, result: &[u8; 32]) -> Result<(), Error> {
+	if U256::from_big_endian(result) > difficulty_to_boundary(&header.difficulty) {
+		return Err(Error::InsufficientWork);
+	}
+
+	Ok(())
+}
+
+/// Convert difficulty to the maximal valid proof-of-work result.
+fn difficulty_to_boundary(difficulty: &U256) -> U256 {
+	if *difficulty <= U256::one() {
+		return U256::max_value();
+	}
+
+	let boundary = (U512::one() << 256) / U512::from(*difficulty);
+	let mut bytes = [0u8; 64];
+	boundary.to_big_endian(&mut bytes);
+	U256::from_big_endian(&bytes[32..])
+}
+
+/// Returns Keccak-512 of the header bare hash and little-endian nonce.
+fn seed_hash_with_nonce(bare_hash: &H256, nonce: &[u8; 8]) -> [u8; 64] {
+	let mut nonce = *nonce;
+	nonce.reverse();
+	keccak_512(&[bare_hash.as_bytes(), &nonce[..]].concat())
+}
+
+/// FNV-1 hash function, used by Ethash.
+fn fnv(v1: u32, v2: u32) -> u32 {
+	v1.wrapping_mul(FNV_PRIME) ^ v2
+}
+
+/// Compute Keccak-512 of the node.
+#[cfg(feature = "std")]
+fn keccak_512_node(node: &Node) -> Node {
+	bytes_to_node(&keccak_512(&node_to_bytes(node)))
+}
+
+/// Convert bytes to node (little-endian 32-bit words).
+fn bytes_to_node(bytes: &[u8; HASH_BYTES]) -> Node {
+	let mut node = [0u32; HASH_WORDS];
+	for (word, chunk) in node.iter_mut().zip(bytes.chunks(4)) {
+		*word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+	}
+	node
+}
+
+/// Convert node to bytes (little-endian 32-bit words).
+#[cfg(feature = "std")]
+fn node_to_bytes(node: &Node) -> [u8; HASH_BYTES] {
+	let mut bytes = [0u8; HASH_BYTES];
+	for (chunk, word) in bytes.chunks_mut(4).zip(node.iter()) {
+		chunk.copy_from_slice(&word.to_le_bytes());
+	}
+	bytes
+}
+
+/// Trial division primality test.
+fn is_prime(number: u64) -> bool {
+	if number < 2 {
+		return false;
+	}
+
+	let mut divisor = 2;
+	while divisor * divisor <= number {
+		if number % divisor == 0 {
+			return false;
+		}
+		divisor += 1;
+	}
+
+	true
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use crate::mock::ConstChainTime;
+	use bp_eth_poa::rlp_encode;
+	use hex_literal::hex;
+
+	const TEST_DIFFICULTY: u64 = 256;
+
+	fn test_config() -> EthashConfiguration {
+		EthashConfiguration {
+			verification_mode: EthashVerificationMode::Light,
+			first_dataset_epoch: 0,
+			datasets: Vec::new(),
+			min_difficulty: TEST_DIFFICULTY.into(),
+			difficulty_bomb_delay: u64::max_value(),
+			min_gas_limit: 0x1388.into(),
+			max_gas_limit: U256::max_value(),
+			maximum_extra_data_size: 0x20,
+			finality_confirmations: 2,
+		}
+	}
+
+	pub(crate) fn test_cache() -> EpochCache {
+		// 16 cache nodes and 32 dataset rows
+		EpochCache::generate_with_sizes(0, 16 * HASH_BYTES as u64, 32 * MIX_BYTES as u64)
+	}
+
+	fn full_test_config(tree: &DatasetMerkleTree) -> EthashConfiguration {
+		EthashConfiguration {
+			verification_mode: EthashVerificationMode::Full,
+			datasets: vec![tree.dataset()],
+			..test_config()
+		}
+	}
+
+	fn parent_header() -> AuraHeader {
+		AuraHeader {
+			timestamp: 1_000,
+			gas_limit: 0x1388.into(),
+			difficulty: TEST_DIFFICULTY.into(),
+			uncles_hash: keccak_256(&[0xc0]).into(),
+			..Default::default()
+		}
+	}
+
+	fn child_header(parent: &AuraHeader) -> AuraHeader {
+		AuraHeader {
+			parent_hash: parent.compute_hash(),
+			number: parent.number + 1,
+			timestamp: parent.timestamp + 10,
+			gas_limit: parent.gas_limit,
+			difficulty: parent.difficulty,
+			uncles_hash: parent.uncles_hash,
+			..Default::default()
+		}
+	}
+
+	pub(crate) fn mine(cache: &EpochCache, mut header: AuraHeader) -> AuraHeader {
+		let bare_hash = bare_hash(&header);
+		let boundary = difficulty_to_boundary(&header.difficulty);
+		for nonce in 0u64.. {
+			let nonce = nonce.to_be_bytes();
+			let (mix_hash, result) = cache.hashimoto(&bare_hash, &nonce);
+			if U256::from_big_endian(result.as_bytes()) <= boundary {
+				header.seal = vec![rlp_encode(&mix_hash).to_vec(), rlp_encode(&nonce.to_vec()).to_vec()];
+				return header;
+			}
+		}
+
+		unreachable!("the loop above only ends when the header is mined")
+	}
+
+	fn verify(parent: &AuraHeader, header: &AuraHeader) -> Result<(), Error> {
+		verify_ethash_header(&test_config(), parent, header, &ConstChainTime::default(), None)
+	}
+
+	#[test]
+	fn cache_and_dataset_sizes_are_computed() {
+		assert_eq!(cache_size(0), 16_776_896);
+		assert_eq!(cache_size(1), 16_907_456);
+		assert_eq!(full_size(0), 1_073_739_904);
+		assert_eq!(full_size(1), 1_082_130_304);
+	}
+
+	#[test]
+	fn seed_hash_is_computed() {
+		assert_eq!(seed_hash(0), [0u8; 32]);
+		assert_eq!(
+			seed_hash(1),
+			hex!("290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"),
+		);
+		assert_eq!(seed_hash(epoch(EPOCH_LENGTH * 2 - 1)), seed_hash(1));
+	}
+
+	#[test]
+	fn mined_header_passes_light_cache_and_full_verification() {
+		let cache = test_cache();
+		let tree = DatasetMerkleTree::generate(&cache);
+		let header = mine(&cache, child_header(&parent_header()));
+		let seal_proof = tree.seal_proof(&header).unwrap();
+
+		assert_eq!(seal_proof.len(), ACCESSES as usize);
+		assert_eq!(verify_seal_light(&header), Ok(()));
+		assert_eq!(verify_seal_with_cache(&cache, &header), Ok(()));
+		assert_eq!(verify_seal_full(&full_test_config(&tree), &header, &seal_proof), Ok(()));
+	}
+
+	#[test]
+	fn full_verification_requires_seal_proof() {
+		let cache = test_cache();
+		let tree = DatasetMerkleTree::generate(&cache);
+		let parent = parent_header();
+		let header = mine(&cache, child_header(&parent));
+		let seal_proof = tree.seal_proof(&header).unwrap();
+		let chain_time = ConstChainTime::default();
+
+		assert_eq!(
+			verify_ethash_header(
+				&full_test_config(&tree),
+				&parent,
+				&header,
+				&chain_time,
+				Some(&seal_proof)
+			),
+			Ok(()),
+		);
+		assert_eq!(
+			verify_ethash_header(&full_test_config(&tree), &parent, &header, &chain_time, None),
+			Err(Error::MissingSealProof),
+		);
+		assert_eq!(
+			verify_ethash_header(&test_config(), &parent, &header, &chain_time, Some(&seal_proof)),
+			Err(Error::RedundantSealProof),
+		);
+	}
+
+	#[test]
+	fn full_verification_rejects_forged_dataset_rows() {
+		let cache = test_cache();
+		let tree = DatasetMerkleTree::generate(&cache);
+		let config = full_test_config(&tree);
+		let header = mine(&cache, child_header(&parent_header()));
+		let seal_proof = tree.seal_proof(&header).unwrap();
+
+		// dataset item is modified
+		let mut forged_seal_proof = seal_proof.clone();
+		forged_seal_proof[0].items[0] = H512::repeat_byte(42);
+		assert_eq!(
+			verify_seal_full(&config, &header, &forged_seal_proof),
+			Err(Error::InvalidSealProof),
+		);
+
+		// merkle proof is truncated
+		let mut forged_seal_proof = seal_proof.clone();
+		forged_seal_proof[0].merkle_proof.pop();
+		assert_eq!(
+			verify_seal_full(&config, &header, &forged_seal_proof),
+			Err(Error::InvalidSealProof),
+		);
+
+		// rows are reordered
+		let other_row = seal_proof
+			.iter()
+			.position(|row_proof| *row_proof != seal_proof[0])
+			.unwrap();
+		let mut forged_seal_proof = seal_proof.clone();
+		forged_seal_proof.swap(0, other_row);
+		assert_eq!(
+			verify_seal_full(&config, &header, &forged_seal_proof),
+			Err(Error::InvalidSealProof),
+		);
+
+		// some rows are missing
+		let mut forged_seal_proof = seal_proof;
+		forged_seal_proof.pop();
+		assert_eq!(
+			verify_seal_full(&config, &header, &forged_seal_proof),
+			Err(Error::InvalidSealProof),
+		);
+	}
+
+	#[test]
+	fn full_verification_rejects_header_of_epoch_with_unknown_dataset() {
+		let cache = test_cache();
+		let tree = DatasetMerkleTree::generate(&cache);
+		let header = mine(&cache, child_header(&parent_header()));
+		let seal_proof = tree.seal_proof(&header).unwrap();
+
+		let config = EthashConfiguration {
+			first_dataset_epoch: 1,
+			..full_test_config(&tree)
+		};
+		assert_eq!(
+			verify_seal_full(&config, &header, &seal_proof),
+			Err(Error::MissingEthashDataset),
+		);
+	}
+
+	#[test]
+	fn cache_and_full_verification_rejects_forged_mix_hash() {
+		let cache = test_cache();
+		let tree = DatasetMerkleTree::generate(&cache);
+		let mut header = mine(&cache, child_header(&parent_header()));
+		let seal_proof = tree.seal_proof(&header).unwrap();
+		header.seal[0] = rlp_encode(&H256::repeat_byte(42)).to_vec();
+
+		assert_eq!(verify_seal_with_cache(&cache, &header), Err(Error::InvalidMixHash));
+		assert_eq!(
+			verify_seal_full(&full_test_config(&tree), &header, &seal_proof),
+			Err(Error::InvalidMixHash),
+		);
+	}
+
+	#[test]
+	fn verification_rejects_header_with_insufficient_work() {
+		let cache = test_cache();
+		let header = mine(&cache, child_header(&parent_header()));
+
+		assert_eq!(
+			verify_seal_light(&AuraHeader {
+				difficulty: U256::max_value(),
+				..header.clone()
+			}),
+			Err(Error::InsufficientWork),
+		);
+		assert_eq!(
+			verify_seal_with_cache(
+				&cache,
+				&AuraHeader {
+					difficulty: U256::max_value(),
+					..header
+				}
+			),
+			Err(Error::InvalidMixHash),
+		);
+	}
+
+	#[test]
+	fn verification_rejects_header_with_invalid_seal() {
+		let cache = test_cache();
+		let mut header = mine(&cache, child_header(&parent_header()));
+		header.seal[0] = vec![0x80];
+		assert_eq!(verify_seal_light(&header), Err(Error::MissingMixHash));
+
+		let mut header = mine(&cache, child_header(&parent_header()));
+		header.seal[1] = rlp_encode(&vec![0u8; 4]).to_vec();
+		assert_eq!(verify_seal_light(&header), Err(Error::MissingNonce));
+
+		header.seal.push(Vec::new());
+		assert_eq!(verify(&parent_header(), &header), Err(Error::InvalidSealArity));
+	}
+
+	#[test]
+	fn verification_accepts_valid_header() {
+		let parent = parent_header();
+		let header = mine(&test_cache(), child_header(&parent));
+
+		assert_eq!(verify(&parent, &header), Ok(()));
+	}
+
+	#[test]
+	fn verification_rejects_header_with_invalid_parent() {
+		let parent = parent_header();
+		let mut header = child_header(&parent);
+		header.parent_hash = H256::repeat_byte(42);
+		let header = mine(&test_cache(), header);
+
+		assert_eq!(verify(&parent, &header), Err(Error::MissingParentBlock));
+	}
+
+	#[test]
+	fn verification_rejects_header_with_invalid_timestamp() {
+		let parent = parent_header();
+		let mut header = child_header(&parent);
+		header.timestamp = parent.timestamp;
+		let header = mine(&test_cache(), header);
+
+		assert_eq!(verify(&parent, &header), Err(Error::InvalidTimestamp));
+	}
+
+	#[test]
+	fn verification_rejects_header_with_invalid_gas_limit() {
+		let parent = parent_header();
+		let mut header = child_header(&parent);
+		header.gas_limit = parent.gas_limit * 2;
+		let header = mine(&test_cache(), header);
+
+		assert_eq!(verify(&parent, &header), Err(Error::InvalidGasLimit));
+	}
+
+	#[test]
+	fn verification_rejects_header_with_invalid_difficulty() {
+		let parent = parent_header();
+		let mut header = child_header(&parent);
+		header.difficulty = header.difficulty + 1;
+		let header = mine(&test_cache(), header);
+
+		assert_eq!(verify(&parent, &header), Err(Error::InvalidDifficulty));
+	}
+
+	#[test]
+	fn difficulty_is_adjusted() {
+		let config = EthashConfiguration {
+			min_difficulty: 2048.into(),
+			..test_config()
+		};
+		let parent = AuraHeader {
+			difficulty: 2_048_000.into(),
+			..parent_header()
+		};
+		let header_after = |seconds| AuraHeader {
+			timestamp: parent.timestamp + seconds,
+			..child_header(&parent)
+		};
+
+		// fast block => difficulty is increased
+		assert_eq!(
+			calculate_difficulty(&config, &parent, &header_after(5)),
+			U256::from(2_049_000)
+		);
+		// normal block => difficulty is the same
+		assert_eq!(
+			calculate_difficulty(&config, &parent, &header_after(10)),
+			U256::from(2_048_000)
+		);
+		// slow block => difficulty is decreased
+		assert_eq!(
+			calculate_difficulty(&config, &parent, &header_after(30)),
+			U256::from(2_046_000)
+		);
+		// very slow block => difficulty decrease is limited
+		assert_eq!(
+			calculate_difficulty(&config, &parent, &header_after(10_000)),
+			U256::from(1_949_000)
+		);
+
+		// parent with uncles => difficulty is increased
+		let parent_with_uncles = AuraHeader {
+			uncles_hash: H256::repeat_byte(42),
+			..parent.clone()
+		};
+		assert_eq!(
+			calculate_difficulty(&config, &parent_with_uncles, &header_after(10)),
+			U256::from(2_049_000)
+		);
+
+		// difficulty is never less than minimal difficulty
+		let parent_with_min_difficulty = AuraHeader {
+			difficulty: 2048.into(),
+			..parent.clone()
+		};
+		assert_eq!(
+			calculate_difficulty(&config, &parent_with_min_difficulty, &header_after(10_000)),
+			U256::from(2048)
+		);
+
+		// difficulty bomb
+		let bomb_config = EthashConfiguration {
+			difficulty_bomb_delay: 0,
+			..config
+		};
+		let header_with_bomb = AuraHeader {
+			number: 500_000,
+			..header_after(10)
+		};
+		assert_eq!(
+			calculate_difficulty(&bomb_config, &parent, &header_with_bomb),
+			U256::from(2_048_000 + 8),
+		);
+	}
+}
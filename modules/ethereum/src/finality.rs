@@ -129,6 +129,55 @@ pub fn finalize_blocks<S: Storage>(
 	})
 }
 
+/// Tries to finalize blocks when given best block is imported, treating blocks that have at least
+/// `confirmations` descendants as finalized.
+///
+/// This is used by engines where blocks aren't voted for by validators. Returns numbers and hashes
+/// of finalized blocks in ascending order.
+pub fn finalize_confirmed_blocks<S: Storage>(
+	storage: &S,
+	best_finalized: HeaderId,
+	id: HeaderId,
+	submitter: Option<&S::Submitter>,
+	header: &AuraHeader,
+	confirmations: u64,
+) -> Result<Vec<(HeaderId, Option<S::Submitter>)>, Error> {
+	let finalized_number = match id.number.checked_sub(confirmations) {
+		Some(finalized_number) if finalized_number > best_finalized.number => finalized_number,
+		_ => return Ok(Vec::new()),
+	};
+
+	// walk back to the best finalized block, collecting headers that have enough confirmations
+	let mut finalized_headers = Vec::new();
+	let mut current_id = id;
+	let mut current_submitter = submitter.cloned();
+	let mut parent_hash = header.parent_hash;
+	while current_id.number > best_finalized.number {
+		if current_id.number <= finalized_number {
+			finalized_headers.push((current_id, current_submitter.take()));
+		}
+
+		let parent_id = HeaderId {
+			number: current_id.number - 1,
+			hash: parent_hash,
+		};
+		if parent_id.number > best_finalized.number {
+			let (parent, parent_submitter) = storage.header(&parent_hash).ok_or(Error::MissingParentBlock)?;
+			current_submitter = parent_submitter;
+			parent_hash = parent.parent_hash;
+		}
+		current_id = parent_id;
+	}
+
+	// we can't finalize blocks that aren't descendants of the best finalized block
+	if current_id != best_finalized {
+		return Err(Error::TryingToFinalizeSibling);
+	}
+
+	finalized_headers.reverse();
+	Ok(finalized_headers)
+}
+
 /// Returns true if there are enough votes to treat this header as finalized.
 fn is_finalized(
 	validators: &BTreeSet<&Address>,
@@ -326,57 +375,54 @@ mod tests {
 				scheduled_change: None,
 				finality_votes: Default::default(),
 			};
-			assert_eq!(
-				finalize_blocks(
-					&storage,
-					ctx.genesis.compute_id(),
-					(Default::default(), &ctx.addresses),
-					id1,
-					None,
-					&header_to_import.header,
-					u64::max_value(),
-				)
-				.map(|eff| eff.finalized_headers),
-				Ok(Vec::new()),
-			);
+			let finality_effects = finalize_blocks(
+				&storage,
+				ctx.genesis.compute_id(),
+				(Default::default(), &ctx.addresses),
+				id1,
+				None,
+				&header_to_import.header,
+				u64::max_value(),
+			)
+			.unwrap();
+			assert_eq!(finality_effects.finalized_headers, Vec::new());
+			header_to_import.finality_votes = finality_effects.votes;
 			storage.insert_header(header_to_import.clone());
 
 			// when header#2 is inserted, nothing is finalized (2 votes)
 			header_to_import.header = HeaderBuilder::with_parent_hash(id1.hash).sign_by(&validator(1));
 			header_to_import.id = header_to_import.header.compute_id();
 			let id2 = header_to_import.header.compute_id();
-			assert_eq!(
-				finalize_blocks(
-					&storage,
-					ctx.genesis.compute_id(),
-					(Default::default(), &ctx.addresses),
-					id2,
-					None,
-					&header_to_import.header,
-					u64::max_value(),
-				)
-				.map(|eff| eff.finalized_headers),
-				Ok(Vec::new()),
-			);
+			let finality_effects = finalize_blocks(
+				&storage,
+				ctx.genesis.compute_id(),
+				(Default::default(), &ctx.addresses),
+				id2,
+				None,
+				&header_to_import.header,
+				u64::max_value(),
+			)
+			.unwrap();
+			assert_eq!(finality_effects.finalized_headers, Vec::new());
+			header_to_import.finality_votes = finality_effects.votes;
 			storage.insert_header(header_to_import.clone());
 
 			// when header#3 is inserted, header#1 is finalized (3 votes)
 			header_to_import.header = HeaderBuilder::with_parent_hash(id2.hash).sign_by(&validator(2));
 			header_to_import.id = header_to_import.header.compute_id();
 			let id3 = header_to_import.header.compute_id();
-			assert_eq!(
-				finalize_blocks(
-					&storage,
-					ctx.genesis.compute_id(),
-					(Default::default(), &ctx.addresses),
-					id3,
-					None,
-					&header_to_import.header,
-					u64::max_value(),
-				)
-				.map(|eff| eff.finalized_headers),
-				Ok(vec![(id1, None)]),
-			);
+			let finality_effects = finalize_blocks(
+				&storage,
+				ctx.genesis.compute_id(),
+				(Default::default(), &ctx.addresses),
+				id3,
+				None,
+				&header_to_import.header,
+				u64::max_value(),
+			)
+			.unwrap();
+			assert_eq!(finality_effects.finalized_headers, vec![(id1, None)]);
+			header_to_import.finality_votes = finality_effects.votes;
 			storage.insert_header(header_to_import);
 		});
 	}
@@ -15,17 +15,21 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::error::Error;
-use crate::finality::finalize_blocks;
+use crate::finality::{finalize_blocks, finalize_confirmed_blocks, FinalityEffects};
 use crate::validators::{Validators, ValidatorsConfiguration};
-use crate::verification::{is_importable_header, verify_aura_header};
-use crate::{AuraConfiguration, ChainTime, ChangeToEnact, PruningStrategy, Storage};
+use crate::verification::{is_importable_header, verify_header};
+use crate::{
+	AuraConfiguration, ChainTime, ChangeToEnact, CliqueConfiguration, EngineConfiguration, EthashConfiguration,
+	EthashSealProof, PruningStrategy, Storage,
+};
 use bp_eth_poa::{AuraHeader, HeaderId, Receipt};
 use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
 /// Imports bunch of headers and updates blocks finality.
 ///
 /// Transactions receipts must be provided if `header_import_requires_receipts()`
-/// has returned true.
+/// has returned true. Seal proofs must be provided if Ethash engine is used in the
+/// `EthashVerificationMode::Full` mode.
 /// If successful, returns tuple where first element is the number of useful headers
 /// we have imported and the second element is the number of useless headers (duplicate)
 /// we have NOT imported.
@@ -36,25 +40,28 @@ use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 pub fn import_headers<S: Storage, PS: PruningStrategy, CT: ChainTime>(
 	storage: &mut S,
 	pruning_strategy: &mut PS,
+	engine_config: &EngineConfiguration,
 	aura_config: &AuraConfiguration,
 	validators_config: &ValidatorsConfiguration,
 	submitter: Option<S::Submitter>,
-	headers: Vec<(AuraHeader, Option<Vec<Receipt>>)>,
+	headers: Vec<(AuraHeader, Option<Vec<Receipt>>, Option<EthashSealProof>)>,
 	chain_time: &CT,
 	finalized_headers: &mut BTreeMap<S::Submitter, u64>,
 ) -> Result<(u64, u64), Error> {
 	let mut useful = 0;
 	let mut useless = 0;
-	for (header, receipts) in headers {
+	for (header, receipts, seal_proof) in headers {
 		let import_result = import_header(
 			storage,
 			pruning_strategy,
+			engine_config,
 			aura_config,
 			validators_config,
 			submitter.clone(),
 			header,
 			chain_time,
 			receipts,
+			seal_proof,
 		);
 
 		match import_result {
@@ -80,7 +87,8 @@ pub type FinalizedHeaders<S> = Vec<(HeaderId, Option<<S as Storage>::Submitter>)
 /// Imports given header and updates blocks finality (if required).
 ///
 /// Transactions receipts must be provided if `header_import_requires_receipts()`
-/// has returned true.
+/// has returned true. Seal proof must be provided if Ethash engine is used in the
+/// `EthashVerificationMode::Full` mode.
 ///
 /// Returns imported block id and list of all finalized headers.
 /// TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/415)
@@ -88,48 +96,97 @@ pub type FinalizedHeaders<S> = Vec<(HeaderId, Option<<S as Storage>::Submitter>)
 pub fn import_header<S: Storage, PS: PruningStrategy, CT: ChainTime>(
 	storage: &mut S,
 	pruning_strategy: &mut PS,
+	engine_config: &EngineConfiguration,
 	aura_config: &AuraConfiguration,
 	validators_config: &ValidatorsConfiguration,
 	submitter: Option<S::Submitter>,
 	header: AuraHeader,
 	chain_time: &CT,
 	receipts: Option<Vec<Receipt>>,
+	seal_proof: Option<EthashSealProof>,
 ) -> Result<(HeaderId, FinalizedHeaders<S>), Error> {
 	// first check that we are able to import this header at all
 	let (header_id, finalized_id) = is_importable_header(storage, &header)?;
 
 	// verify header
-	let import_context = verify_aura_header(storage, aura_config, submitter, &header, chain_time)?;
-
-	// check if block schedules new validators
-	let validators = Validators::new(validators_config);
-	let (scheduled_change, enacted_change) = validators.extract_validators_change(&header, receipts)?;
-
-	// check if block finalizes some other blocks and corresponding scheduled validators
-	let validators_set = import_context.validators_set();
-	let finalized_blocks = finalize_blocks(
+	let (import_context, clique_snapshot) = verify_header(
 		storage,
-		finalized_id,
-		(validators_set.enact_block, &validators_set.validators),
-		header_id,
-		import_context.submitter(),
+		engine_config,
+		aura_config,
+		submitter,
 		&header,
-		aura_config.two_thirds_majority_transition,
+		chain_time,
+		seal_proof.as_ref(),
 	)?;
-	let enacted_change = enacted_change
-		.map(|validators| ChangeToEnact {
-			signal_block: None,
-			validators,
+	let (best_id, best_total_difficulty) = storage.best_block();
+	let total_difficulty = import_context.total_difficulty() + header.difficulty;
+	let is_best = total_difficulty > best_total_difficulty;
+
+	let (scheduled_change, enacted_change, finalized_blocks) = match *engine_config {
+		EngineConfiguration::Aura => {
+			// check if block schedules new validators
+			let validators = Validators::new(validators_config);
+			let (scheduled_change, enacted_change) = validators.extract_validators_change(&header, receipts)?;
+
+			// check if block finalizes some other blocks and corresponding scheduled validators
+			let validators_set = import_context.validators_set();
+			let finalized_blocks = finalize_blocks(
+				storage,
+				finalized_id,
+				(validators_set.enact_block, &validators_set.validators),
+				header_id,
+				import_context.submitter(),
+				&header,
+				aura_config.two_thirds_majority_transition,
+			)?;
+			let enacted_change = enacted_change
+				.map(|validators| ChangeToEnact {
+					signal_block: None,
+					validators,
+				})
+				.or_else(|| validators.finalize_validators_change(storage, &finalized_blocks.finalized_headers));
+
+			(scheduled_change, enacted_change, finalized_blocks)
+		}
+		EngineConfiguration::Ethash(EthashConfiguration {
+			finality_confirmations, ..
 		})
-		.or_else(|| validators.finalize_validators_change(storage, &finalized_blocks.finalized_headers));
+		| EngineConfiguration::Clique(CliqueConfiguration {
+			finality_confirmations, ..
+		}) => {
+			// validators set never changes => receipts are never required
+			if receipts.is_some() {
+				return Err(Error::RedundantTransactionsReceipts);
+			}
+
+			// only blocks of the best chain are finalized
+			let finalized_headers = match is_best {
+				true => finalize_confirmed_blocks(
+					storage,
+					finalized_id,
+					header_id,
+					import_context.submitter(),
+					&header,
+					finality_confirmations,
+				)?,
+				false => Vec::new(),
+			};
+
+			(
+				None,
+				None,
+				FinalityEffects {
+					finalized_headers,
+					votes: Default::default(),
+				},
+			)
+		}
+	};
 
 	// NOTE: we can't return Err() from anywhere below this line
 	// (because otherwise we'll have inconsistent storage if transaction will fail)
 
 	// and finally insert the block
-	let (best_id, best_total_difficulty) = storage.best_block();
-	let total_difficulty = import_context.total_difficulty() + header.difficulty;
-	let is_best = total_difficulty > best_total_difficulty;
 	storage.insert_header(import_context.into_import_header(
 		is_best,
 		header_id,
@@ -139,6 +196,9 @@ pub fn import_header<S: Storage, PS: PruningStrategy, CT: ChainTime>(
 		scheduled_change,
 		finalized_blocks.votes,
 	));
+	if let Some(clique_snapshot) = clique_snapshot {
+		storage.insert_clique_snapshot(clique_snapshot);
+	}
 
 	// compute upper border of updated pruning range
 	let new_best_block_id = if is_best { header_id } else { best_id };
@@ -159,9 +219,14 @@ pub fn import_header<S: Storage, PS: PruningStrategy, CT: ChainTime>(
 /// Returns true if transactions receipts are required to import given header.
 pub fn header_import_requires_receipts<S: Storage>(
 	storage: &S,
+	engine_config: &EngineConfiguration,
 	validators_config: &ValidatorsConfiguration,
 	header: &AuraHeader,
 ) -> bool {
+	if *engine_config != EngineConfiguration::Aura {
+		return false;
+	}
+
 	is_importable_header(storage, header)
 		.map(|_| Validators::new(validators_config))
 		.map(|validators| validators.maybe_signals_validators_change(header))
@@ -198,12 +263,14 @@ mod tests {
 				import_header(
 					&mut storage,
 					&mut KeepSomeHeadersBehindBest::default(),
+					&EngineConfiguration::Aura,
 					&test_aura_config(),
 					&test_validators_config(),
 					None,
 					Default::default(),
 					&(),
 					None,
+					None,
 				),
 				Err(Error::AncientHeader),
 			);
@@ -219,12 +286,14 @@ mod tests {
 				import_header(
 					&mut storage,
 					&mut KeepSomeHeadersBehindBest::default(),
+					&EngineConfiguration::Aura,
 					&test_aura_config(),
 					&test_validators_config(),
 					None,
 					header.clone(),
 					&(),
 					None,
+					None,
 				)
 				.map(|_| ()),
 				Ok(()),
@@ -233,12 +302,14 @@ mod tests {
 				import_header(
 					&mut storage,
 					&mut KeepSomeHeadersBehindBest::default(),
+					&EngineConfiguration::Aura,
 					&test_aura_config(),
 					&test_validators_config(),
 					None,
 					header,
 					&(),
 					None,
+					None,
 				)
 				.map(|_| ()),
 				Err(Error::KnownHeader),
@@ -260,12 +331,14 @@ mod tests {
 				import_header(
 					&mut storage,
 					&mut KeepSomeHeadersBehindBest::default(),
+					&EngineConfiguration::Aura,
 					&test_aura_config(),
 					&validators_config,
 					None,
 					header,
 					&(),
-					None
+					None,
+					None,
 				)
 				.map(|_| ()),
 				Ok(()),
@@ -298,12 +371,14 @@ mod tests {
 				let (rolling_last_block_id, finalized_blocks) = import_header(
 					&mut storage,
 					&mut KeepSomeHeadersBehindBest::default(),
+					&EngineConfiguration::Aura,
 					&test_aura_config(),
 					&validators_config,
 					Some(100),
 					header,
 					&(),
 					None,
+					None,
 				)
 				.unwrap();
 				match i {
@@ -328,12 +403,14 @@ mod tests {
 			let (rolling_last_block_id, finalized_blocks) = import_header(
 				&mut storage,
 				&mut KeepSomeHeadersBehindBest::default(),
+				&EngineConfiguration::Aura,
 				&test_aura_config(),
 				&validators_config,
 				Some(101),
 				header11.clone(),
 				&(),
 				Some(vec![validators_change_receipt(latest_block_id.hash)]),
+				None,
 			)
 			.unwrap();
 			assert_eq!(finalized_blocks, vec![(parent_id, Some(100))],);
@@ -354,12 +431,14 @@ mod tests {
 				let (rolling_last_block_id, finalized_blocks) = import_header(
 					&mut storage,
 					&mut KeepSomeHeadersBehindBest::default(),
+					&EngineConfiguration::Aura,
 					&test_aura_config(),
 					&validators_config,
 					Some(102),
 					header,
 					&(),
 					None,
+					None,
 				)
 				.unwrap();
 				assert_eq!(finalized_blocks, vec![],);
@@ -384,12 +463,14 @@ mod tests {
 			let (_, finalized_blocks) = import_header(
 				&mut storage,
 				&mut KeepSomeHeadersBehindBest::default(),
+				&EngineConfiguration::Aura,
 				&test_aura_config(),
 				&validators_config,
 				Some(103),
 				header,
 				&(),
 				None,
+				None,
 			)
 			.unwrap();
 			assert_eq!(finalized_blocks, expected_blocks);
@@ -412,6 +493,7 @@ mod tests {
 		import_header(
 			storage,
 			&mut KeepSomeHeadersBehindBest::default(),
+			&EngineConfiguration::Aura,
 			&test_aura_config(),
 			&ValidatorsConfiguration::Single(ValidatorsSource::Contract(
 				[0; 20].into(),
@@ -421,6 +503,7 @@ mod tests {
 			header,
 			&(),
 			None,
+			None,
 		)
 		.map(|_| id)
 	}
@@ -19,7 +19,10 @@
 #![allow(clippy::large_enum_variant)]
 
 use crate::finality::{CachedFinalityVotes, FinalityVotes};
-use bp_eth_poa::{Address, AuraHeader, HeaderId, RawTransaction, RawTransactionReceipt, Receipt, H256, U256};
+use bp_eth_poa::{
+	receipt_proof::{verify_receipt_proof, ReceiptInclusionProof},
+	Address, AuraHeader, HeaderId, RawTransaction, RawTransactionReceipt, Receipt, TransactionOutcome, H256, U256,
+};
 use codec::{Decode, Encode};
 use frame_support::{decl_module, decl_storage, traits::Get};
 use sp_runtime::{
@@ -31,8 +34,13 @@ use sp_runtime::{
 };
 use sp_std::{cmp::Ord, collections::btree_map::BTreeMap, prelude::*};
 
+pub use clique::{CliqueConfiguration, CliqueSnapshot};
+pub use ethash::{EthashConfiguration, EthashDataset, EthashSealProof, EthashVerificationMode};
 pub use validators::{ValidatorsConfiguration, ValidatorsSource};
 
+pub mod clique;
+pub mod ethash;
+
 mod error;
 mod finality;
 mod import;
@@ -72,6 +80,20 @@ pub struct AuraConfiguration {
 	pub maximum_extra_data_size: u64,
 }
 
+/// Sealing engine of the bridged chain.
+///
+/// Validators set changes and finality votes are only tracked for Aura headers. Headers of other engines
+/// are finalized once they have enough descendants in the best chain.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
+pub enum EngineConfiguration {
+	/// Authority round engine, configured by the `Config::AuraConfiguration`.
+	Aura,
+	/// Ethash proof-of-work engine.
+	Ethash(EthashConfiguration),
+	/// Clique proof-of-authority engine. The initial header must be the checkpoint header.
+	Clique(CliqueConfiguration),
+}
+
 /// Transaction pool configuration.
 ///
 /// This is used to limit number of unsigned headers transactions in
@@ -294,6 +316,10 @@ pub trait Storage {
 	/// Get new validators that are scheduled by given header and hash of the previous
 	/// block that has scheduled change.
 	fn scheduled_change(&self, hash: &H256) -> Option<AuraScheduledChange>;
+	/// Get Clique snapshot at given header.
+	fn clique_snapshot(&self, hash: &H256) -> Option<CliqueSnapshot>;
+	/// Insert Clique snapshot at imported header. The snapshot is removed when the header is pruned.
+	fn insert_clique_snapshot(&mut self, snapshot: CliqueSnapshot);
 	/// Insert imported header.
 	fn insert_header(&mut self, header: HeaderToImport<Self::Submitter>);
 	/// Finalize given block and schedules pruning of all headers
@@ -371,6 +397,8 @@ impl<AccountId> OnHeadersSubmitted<AccountId> for () {
 
 /// The module configuration trait.
 pub trait Config<I = DefaultInstance>: frame_system::Config {
+	/// Sealing engine configuration.
+	type EngineConfiguration: Get<EngineConfiguration>;
 	/// Aura configuration.
 	type AuraConfiguration: Get<AuraConfiguration>;
 	/// Validators configuration.
@@ -382,6 +410,9 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// Ideally, this should either be None (when we are sure that there won't
 	/// be any significant finalization delays), or something that is bit larger
 	/// than average finalization delay.
+	///
+	/// Regardless of this value, finality votes of the best block are always cached,
+	/// because next block is most likely to be imported on top of the best block.
 	type FinalityVotesCachingInterval: Get<Option<u64>>;
 	/// Headers pruning strategy.
 	type PruningStrategy: PruningStrategy;
@@ -394,40 +425,57 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 
 decl_module! {
 	pub struct Module<T: Config<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
-		/// Import single Aura header. Requires transaction to be **UNSIGNED**.
+		/// Import single header. Requires transaction to be **UNSIGNED**.
+		///
+		/// The seal proof must only be provided if Ethash engine is used in the
+		/// `EthashVerificationMode::Full` mode.
 		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
-		pub fn import_unsigned_header(origin, header: AuraHeader, receipts: Option<Vec<Receipt>>) {
+		pub fn import_unsigned_header(
+			origin,
+			header: AuraHeader,
+			receipts: Option<Vec<Receipt>>,
+			seal_proof: Option<EthashSealProof>,
+		) {
 			frame_system::ensure_none(origin)?;
 
 			import::import_header(
 				&mut BridgeStorage::<T, I>::new(),
 				&mut T::PruningStrategy::default(),
+				&T::EngineConfiguration::get(),
 				&T::AuraConfiguration::get(),
 				&T::ValidatorsConfiguration::get(),
 				None,
 				header,
 				&T::ChainTime::default(),
 				receipts,
+				seal_proof,
 			).map_err(|e| e.msg())?;
 		}
 
-		/// Import Aura chain headers in a single **SIGNED** transaction.
+		/// Import chain headers in a single **SIGNED** transaction.
 		/// Ignores non-fatal errors (like when known header is provided), rewards
 		/// for successful headers import and penalizes for fatal errors.
 		///
 		/// This should be used with caution - passing too many headers could lead to
 		/// enormous block production/import time.
+		///
+		/// Every header is accompanied by its transactions receipts and the Ethash seal proof
+		/// (see `import_unsigned_header`).
 		#[weight = 0] // TODO: update me (https://github.com/paritytech/parity-bridges-common/issues/78)
-		pub fn import_signed_headers(origin, headers_with_receipts: Vec<(AuraHeader, Option<Vec<Receipt>>)>) {
+		pub fn import_signed_headers(
+			origin,
+			headers: Vec<(AuraHeader, Option<Vec<Receipt>>, Option<EthashSealProof>)>,
+		) {
 			let submitter = frame_system::ensure_signed(origin)?;
 			let mut finalized_headers = BTreeMap::new();
 			let import_result = import::import_headers(
 				&mut BridgeStorage::<T, I>::new(),
 				&mut T::PruningStrategy::default(),
+				&T::EngineConfiguration::get(),
 				&T::AuraConfiguration::get(),
 				&T::ValidatorsConfiguration::get(),
 				Some(submitter.clone()),
-				headers_with_receipts,
+				headers,
 				&T::ChainTime::default(),
 				&mut finalized_headers,
 			);
@@ -470,6 +518,10 @@ decl_storage! {
 		HeadersByNumber: map hasher(blake2_128_concat) u64 => Option<Vec<H256>>;
 		/// Map of cached finality data by header hash.
 		FinalityCache: map hasher(identity) H256 => Option<FinalityVotes<T::AccountId>>;
+		/// Id of the best block, which finality votes are stored in the `FinalityCache`. Entry is
+		/// replaced when new best block is inserted, unless it is also required by the
+		/// `FinalityVotesCachingInterval`.
+		BestFinalityCacheEntry: Option<HeaderId>;
 		/// The ID of next validator set.
 		NextValidatorsSetId: u64;
 		/// Map of validators sets by their id.
@@ -480,6 +532,8 @@ decl_storage! {
 		ValidatorsSetsRc: map hasher(twox_64_concat) u64 => Option<u64>;
 		/// Map of validators set changes scheduled by given header.
 		ScheduledChanges: map hasher(identity) H256 => Option<AuraScheduledChange>;
+		/// Map of Clique snapshots by header hash. Only used by the Clique engine.
+		CliqueSnapshots: map hasher(identity) H256 => Option<CliqueSnapshot>;
 	}
 	add_extra_genesis {
 		config(initial_header): AuraHeader;
@@ -501,6 +555,12 @@ decl_storage! {
 				config.initial_difficulty,
 				&config.initial_validators,
 			);
+
+			if let EngineConfiguration::Clique(_) = T::EngineConfiguration::get() {
+				let initial_snapshot = CliqueSnapshot::from_checkpoint(&config.initial_header)
+					.expect("Initial header of Clique chain must be the checkpoint header");
+				CliqueSnapshots::<I>::insert(initial_snapshot.id.hash, initial_snapshot);
+			}
 		})
 	}
 }
@@ -522,6 +582,7 @@ impl<T: Config<I>, I: Instance> Pallet<T, I> {
 	pub fn is_import_requires_receipts(header: AuraHeader) -> bool {
 		import::header_import_requires_receipts(
 			&BridgeStorage::<T, I>::new(),
+			&T::EngineConfiguration::get(),
 			&T::ValidatorsConfiguration::get(),
 			&header,
 		)
@@ -540,6 +601,13 @@ impl<T: Config<I>, I: Instance> Pallet<T, I> {
 	) -> bool {
 		crate::verify_transaction_finalized(&BridgeStorage::<T, I>::new(), block, tx_index, proof)
 	}
+
+	/// Verify that receipt of successful transaction is included into given finalized block.
+	///
+	/// Returns raw receipt if the proof is valid.
+	pub fn verify_receipt_finalized(proof: &ReceiptInclusionProof) -> Option<RawTransactionReceipt> {
+		crate::verify_receipt_finalized(&BridgeStorage::<T, I>::new(), proof)
+	}
 }
 
 impl<T: Config<I>, I: Instance> frame_support::unsigned::ValidateUnsigned for Pallet<T, I> {
@@ -547,15 +615,17 @@ impl<T: Config<I>, I: Instance> frame_support::unsigned::ValidateUnsigned for Pa
 
 	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
 		match *call {
-			Self::Call::import_unsigned_header(ref header, ref receipts) => {
-				let accept_result = verification::accept_aura_header_into_pool(
+			Self::Call::import_unsigned_header(ref header, ref receipts, ref seal_proof) => {
+				let accept_result = verification::accept_header_into_pool(
 					&BridgeStorage::<T, I>::new(),
+					&T::EngineConfiguration::get(),
 					&T::AuraConfiguration::get(),
 					&T::ValidatorsConfiguration::get(),
 					&pool_configuration(),
 					header,
 					&T::ChainTime::default(),
 					receipts.as_ref(),
+					seal_proof.as_ref(),
 				);
 
 				match accept_result {
@@ -667,6 +737,7 @@ impl<T: Config<I>, I: Instance> BridgeStorage<T, I> {
 
 			ScheduledChanges::<I>::remove(hash);
 			FinalityCache::<T, I>::remove(hash);
+			CliqueSnapshots::<I>::remove(hash);
 			if let Some(header) = header {
 				ValidatorsSetsRc::<I>::mutate(header.next_validators_set_id, |rc| match *rc {
 					Some(rc) if rc > 1 => Some(rc - 1),
@@ -770,6 +841,14 @@ impl<T: Config<I>, I: Instance> Storage for BridgeStorage<T, I> {
 		ScheduledChanges::<I>::get(hash)
 	}
 
+	fn clique_snapshot(&self, hash: &H256) -> Option<CliqueSnapshot> {
+		CliqueSnapshots::<I>::get(hash)
+	}
+
+	fn insert_clique_snapshot(&mut self, snapshot: CliqueSnapshot) {
+		CliqueSnapshots::<I>::insert(snapshot.id.hash, snapshot);
+	}
+
 	fn insert_header(&mut self, header: HeaderToImport<Self::Submitter>) {
 		if header.is_best {
 			BestBlock::<I>::put((header.id, header.total_difficulty));
@@ -810,13 +889,23 @@ impl<T: Config<I>, I: Instance> Storage for BridgeStorage<T, I> {
 			}
 		};
 
-		let finality_votes_caching_interval = T::FinalityVotesCachingInterval::get();
-		if let Some(finality_votes_caching_interval) = finality_votes_caching_interval {
-			let cache_entry_required = header.id.number != 0 && header.id.number % finality_votes_caching_interval == 0;
-			if cache_entry_required {
-				FinalityCache::<T, I>::insert(header.id.hash, header.finality_votes);
+		// finality votes of the best block are cached, so when next block is imported on top of it, we
+		// won't need to replay votes of all ancestors, starting from the last interval cache entry
+		let is_interval_cache_entry = |number: u64| match T::FinalityVotesCachingInterval::get() {
+			Some(finality_votes_caching_interval) => number != 0 && number % finality_votes_caching_interval == 0,
+			None => false,
+		};
+		if header.is_best {
+			let previous_best_entry = BestFinalityCacheEntry::<I>::mutate(|entry| entry.replace(header.id));
+			if let Some(previous_best_entry) = previous_best_entry {
+				if previous_best_entry != header.id && !is_interval_cache_entry(previous_best_entry.number) {
+					FinalityCache::<T, I>::remove(previous_best_entry.hash);
+				}
 			}
 		}
+		if header.is_best || is_interval_cache_entry(header.id.number) {
+			FinalityCache::<T, I>::insert(header.id.hash, header.finality_votes);
+		}
 
 		log::trace!(
 			target: "runtime",
@@ -927,50 +1016,10 @@ pub fn verify_transaction_finalized<S: Storage>(
 		return false;
 	}
 
-	let header = match storage.header(&block) {
-		Some((header, _)) => header,
-		None => {
-			log::trace!(
-				target: "runtime",
-				"Tx finality check failed: can't find header in the storage: {}",
-				block,
-			);
-
-			return false;
-		}
+	let header = match finalized_header(storage, block) {
+		Some(header) => header,
+		None => return false,
 	};
-	let finalized = storage.finalized_block();
-
-	// if header is not yet finalized => return
-	if header.number > finalized.number {
-		log::trace!(
-			target: "runtime",
-			"Tx finality check failed: header {}/{} is not finalized. Best finalized: {}",
-			header.number,
-			block,
-			finalized.number,
-		);
-
-		return false;
-	}
-
-	// check if header is actually finalized
-	let is_finalized = match header.number < finalized.number {
-		true => ancestry(storage, finalized.hash)
-			.skip_while(|(_, ancestor)| ancestor.number > header.number)
-			.any(|(ancestor_hash, _)| ancestor_hash == block),
-		false => block == finalized.hash,
-	};
-	if !is_finalized {
-		log::trace!(
-			target: "runtime",
-			"Tx finality check failed: header {} is not finalized: no canonical path to best finalized block {}",
-			block,
-			finalized.hash,
-		);
-
-		return false;
-	}
 
 	// verify that transaction is included in the block
 	if let Err(computed_root) = header.check_transactions_root(proof.iter().map(|(tx, _)| tx)) {
@@ -1020,6 +1069,102 @@ pub fn verify_transaction_finalized<S: Storage>(
 	}
 }
 
+/// Verify that receipt of successful transaction is included into given finalized block.
+///
+/// Returns raw receipt if the proof is valid.
+pub fn verify_receipt_finalized<S: Storage>(
+	storage: &S,
+	proof: &ReceiptInclusionProof,
+) -> Option<RawTransactionReceipt> {
+	let header = finalized_header(storage, proof.block)?;
+
+	// verify that receipt is included in the block
+	let raw_receipt = match verify_receipt_proof(&header.receipts_root, proof.index, &proof.proof) {
+		Ok(raw_receipt) => raw_receipt,
+		Err(err) => {
+			log::trace!(
+				target: "runtime",
+				"Receipt finality check failed: invalid receipt proof: {:?}",
+				err,
+			);
+
+			return None;
+		}
+	};
+
+	// check that transaction has completed successfully
+	match Receipt::decode_rlp(&raw_receipt) {
+		Ok(receipt) if receipt.outcome == TransactionOutcome::StatusCode(1) => Some(raw_receipt),
+		Ok(_) => {
+			log::trace!(
+				target: "runtime",
+				"Receipt finality check failed: receipt shows that transaction has failed",
+			);
+
+			None
+		}
+		Err(err) => {
+			log::trace!(
+				target: "runtime",
+				"Receipt finality check failed: receipt decode has failed: {}",
+				err,
+			);
+
+			None
+		}
+	}
+}
+
+/// Returns header if it is known to the storage and is finalized.
+fn finalized_header<S: Storage>(storage: &S, block: H256) -> Option<AuraHeader> {
+	let header = match storage.header(&block) {
+		Some((header, _)) => header,
+		None => {
+			log::trace!(
+				target: "runtime",
+				"Finality check failed: can't find header in the storage: {}",
+				block,
+			);
+
+			return None;
+		}
+	};
+	let finalized = storage.finalized_block();
+
+	// if header is not yet finalized => return
+	if header.number > finalized.number {
+		log::trace!(
+			target: "runtime",
+			"Finality check failed: header {}/{} is not finalized. Best finalized: {}",
+			header.number,
+			block,
+			finalized.number,
+		);
+
+		return None;
+	}
+
+	// check if header is actually finalized
+	let is_finalized = match header.number < finalized.number {
+		true => ancestry(storage, finalized.hash)
+			.skip_while(|(_, ancestor)| ancestor.number > header.number)
+			.any(|(ancestor_hash, _)| ancestor_hash == block),
+		false => block == finalized.hash,
+	};
+	if !is_finalized {
+		log::trace!(
+			target: "runtime",
+			"Finality check failed: header {} is not finalized: no canonical path to best finalized block {}",
+			block,
+			finalized.hash,
+		);
+
+		return None;
+	}
+
+	Some(header)
+}
+
 /// Transaction pool configuration.
 fn pool_configuration() -> PoolConfiguration {
 	PoolConfiguration {
@@ -1046,11 +1191,11 @@ pub(crate) mod tests {
 	use super::*;
 	use crate::finality::FinalityAncestor;
 	use crate::mock::{
-		genesis, insert_header, run_test, run_test_with_genesis, validators_addresses, HeaderBuilder, TestRuntime,
-		GAS_LIMIT,
+		genesis, insert_header, run_test, run_test_with_genesis, validators_addresses, HeaderBuilder, Origin,
+		TestEngineConfiguration, TestRuntime, GAS_LIMIT,
 	};
 	use crate::test_utils::validator_utils::*;
-	use bp_eth_poa::compute_merkle_root;
+	use bp_eth_poa::{compute_merkle_root, receipt_proof::prove_receipt};
 
 	const TOTAL_VALIDATORS: usize = 3;
 
@@ -1076,6 +1221,45 @@ pub(crate) mod tests {
 			.sign_by(&validator(0))
 	}
 
+	fn ethash_config(tree: &ethash::DatasetMerkleTree) -> EthashConfiguration {
+		EthashConfiguration {
+			verification_mode: EthashVerificationMode::Full,
+			first_dataset_epoch: 0,
+			datasets: vec![tree.dataset()],
+			min_difficulty: 256.into(),
+			difficulty_bomb_delay: u64::max_value(),
+			min_gas_limit: 0x1388.into(),
+			max_gas_limit: U256::max_value(),
+			maximum_extra_data_size: 0x20,
+			finality_confirmations: 2,
+		}
+	}
+
+	fn ethash_genesis() -> AuraHeader {
+		AuraHeader {
+			timestamp: 1_000,
+			gas_limit: GAS_LIMIT.into(),
+			difficulty: 256.into(),
+			uncles_hash: sp_io::hashing::keccak_256(&[0xc0]).into(),
+			..Default::default()
+		}
+	}
+
+	fn mine_ethash_child(cache: &ethash::EpochCache, parent: &AuraHeader) -> AuraHeader {
+		ethash::tests::mine(
+			cache,
+			AuraHeader {
+				parent_hash: parent.compute_hash(),
+				number: parent.number + 1,
+				timestamp: parent.timestamp + 10,
+				gas_limit: parent.gas_limit,
+				difficulty: parent.difficulty,
+				uncles_hash: parent.uncles_hash,
+				..Default::default()
+			},
+		)
+	}
+
 	fn example_header() -> AuraHeader {
 		HeaderBuilder::with_parent(&example_header_parent())
 			.transactions_root(compute_merkle_root(vec![example_tx()].into_iter()))
@@ -1090,6 +1274,14 @@ pub(crate) mod tests {
 			.sign_by(&validator(0))
 	}
 
+	fn example_receipt_proof(header: &AuraHeader, success: bool) -> ReceiptInclusionProof {
+		ReceiptInclusionProof {
+			block: header.compute_hash(),
+			index: 0,
+			proof: prove_receipt(&[example_tx_receipt(success)], 0).unwrap(),
+		}
+	}
+
 	fn with_headers_to_prune<T>(f: impl Fn(BridgeStorage<TestRuntime>) -> T) -> T {
 		run_test(TOTAL_VALIDATORS, |ctx| {
 			for i in 1..10 {
@@ -1257,12 +1449,17 @@ pub(crate) mod tests {
 			let mut storage = BridgeStorage::<TestRuntime>::new();
 			let interval = <TestRuntime as Config>::FinalityVotesCachingInterval::get().unwrap();
 
-			// for all headers with number < interval, cache entry is not created
+			// for all headers with number < interval, cache entry is only kept for the best header
+			let mut previous_id: Option<HeaderId> = None;
 			for i in 1..interval {
 				let header = HeaderBuilder::with_parent_number(i - 1).sign_by_set(&ctx.validators);
 				let id = header.compute_id();
 				insert_header(&mut storage, header);
-				assert_eq!(FinalityCache::<TestRuntime>::get(&id.hash), None);
+				assert!(FinalityCache::<TestRuntime>::get(&id.hash).is_some());
+				if let Some(previous_id) = previous_id {
+					assert_eq!(FinalityCache::<TestRuntime>::get(&previous_id.hash), None);
+				}
+				previous_id = Some(id);
 			}
 
 			// for header with number = interval, cache entry is created
@@ -1271,6 +1468,13 @@ pub(crate) mod tests {
 			insert_header(&mut storage, header_with_entry);
 			assert!(FinalityCache::<TestRuntime>::get(&header_with_entry_hash).is_some());
 
+			// and it isn't removed when next best header is inserted
+			let next_header = HeaderBuilder::with_parent_number(interval).sign_by_set(&ctx.validators);
+			let next_header_hash = next_header.compute_hash();
+			insert_header(&mut storage, next_header);
+			assert!(FinalityCache::<TestRuntime>::get(&header_with_entry_hash).is_some());
+			assert!(FinalityCache::<TestRuntime>::get(&next_header_hash).is_some());
+
 			// when we later prune this header, cache entry is removed
 			BlocksToPrune::<DefaultInstance>::put(PruningRange {
 				oldest_unpruned_block: interval - 1,
@@ -1281,6 +1485,59 @@ pub(crate) mod tests {
 		});
 	}
 
+	#[test]
+	fn best_finality_cache_entry_follows_fork_switch() {
+		run_test(TOTAL_VALIDATORS, |ctx| {
+			let mut storage = BridgeStorage::<TestRuntime>::new();
+
+			// insert headers 1..3 of fork A
+			let mut fork_a = Vec::new();
+			for i in 1..4 {
+				let header = HeaderBuilder::with_parent_number(i - 1).sign_by_set(&ctx.validators);
+				fork_a.push(header.compute_id());
+				insert_header(&mut storage, header);
+			}
+			assert_eq!(BestFinalityCacheEntry::<DefaultInstance>::get(), Some(fork_a[2]));
+			assert!(FinalityCache::<TestRuntime>::get(&fork_a[2].hash).is_some());
+
+			// insert headers 2..4 of fork B, which is forked at header 1
+			let mut parent = Headers::<TestRuntime>::get(&fork_a[0].hash).unwrap().header;
+			let mut fork_b = Vec::new();
+			for i in 2..5 {
+				let header = HeaderBuilder::with_parent(&parent)
+					.gas_limit((GAS_LIMIT + i).into())
+					.sign_by_set(&ctx.validators);
+				fork_b.push(header.compute_id());
+				insert_header(&mut storage, header.clone());
+				parent = header;
+			}
+
+			// only votes of the new best header are cached
+			assert_eq!(BestFinalityCacheEntry::<DefaultInstance>::get(), Some(fork_b[2]));
+			assert_eq!(FinalityCache::<TestRuntime>::get(&fork_a[2].hash), None);
+			assert_eq!(FinalityCache::<TestRuntime>::get(&fork_b[0].hash), None);
+			assert_eq!(FinalityCache::<TestRuntime>::get(&fork_b[1].hash), None);
+			assert!(FinalityCache::<TestRuntime>::get(&fork_b[2].hash).is_some());
+
+			// when importing header on top of fork B, cached votes are used
+			let cached_votes = storage.cached_finality_votes(&fork_b[2], &genesis().compute_id(), |_| false);
+			assert!(cached_votes.unaccounted_ancestry.is_empty());
+			assert!(cached_votes.votes.is_some());
+
+			// when importing header on top of fork A, votes of fork B are not used
+			let cached_votes = storage.cached_finality_votes(&fork_a[2], &genesis().compute_id(), |_| false);
+			assert_eq!(
+				cached_votes
+					.unaccounted_ancestry
+					.iter()
+					.map(|(id, _, _)| *id)
+					.collect::<Vec<_>>(),
+				fork_a.iter().rev().cloned().collect::<Vec<_>>(),
+			);
+			assert_eq!(cached_votes.votes, None);
+		});
+	}
+
 	#[test]
 	fn cached_finality_votes_finds_entry() {
 		run_test(TOTAL_VALIDATORS, |ctx| {
@@ -1293,8 +1550,14 @@ pub(crate) mod tests {
 				insert_header(&mut storage, header);
 			}
 
-			// when inserting header#6, entry isn't found
+			// when inserting header#6 on top of the best header#5, cached votes of header#5 are used
 			let id5 = headers.last().unwrap().compute_id();
+			let cached_votes = storage.cached_finality_votes(&id5, &genesis().compute_id(), |_| false);
+			assert!(cached_votes.unaccounted_ancestry.is_empty());
+			assert!(cached_votes.votes.is_some());
+
+			// when best header votes are not cached, entry isn't found
+			FinalityCache::<TestRuntime>::remove(id5.hash);
 			assert_eq!(
 				storage.cached_finality_votes(&id5, &genesis().compute_id(), |_| false),
 				CachedFinalityVotes {
@@ -1530,4 +1793,185 @@ pub(crate) mod tests {
 			));
 		});
 	}
+
+	#[test]
+	fn verify_receipt_finalized_works_for_best_finalized_header() {
+		run_test_with_genesis(example_header(), TOTAL_VALIDATORS, |_| {
+			let storage = BridgeStorage::<TestRuntime>::new();
+			assert_eq!(
+				verify_receipt_finalized(&storage, &example_receipt_proof(&example_header(), true)),
+				Some(example_tx_receipt(true)),
+			);
+		});
+	}
+
+	#[test]
+	fn verify_receipt_finalized_works_for_best_finalized_header_ancestor() {
+		run_test(TOTAL_VALIDATORS, |_| {
+			let mut storage = BridgeStorage::<TestRuntime>::new();
+			insert_header(&mut storage, example_header_parent());
+			insert_header(&mut storage, example_header());
+			storage.finalize_and_prune_headers(Some(example_header().compute_id()), 0);
+			assert_eq!(
+				verify_receipt_finalized(&storage, &example_receipt_proof(&example_header_parent(), true)),
+				Some(example_tx_receipt(true)),
+			);
+		});
+	}
+
+	#[test]
+	fn verify_receipt_finalized_rejects_unfinalized_header() {
+		run_test(TOTAL_VALIDATORS, |_| {
+			let mut storage = BridgeStorage::<TestRuntime>::new();
+			insert_header(&mut storage, example_header_parent());
+			insert_header(&mut storage, example_header());
+			assert_eq!(
+				verify_receipt_finalized(&storage, &example_receipt_proof(&example_header(), true)),
+				None,
+			);
+		});
+	}
+
+	#[test]
+	fn verify_receipt_finalized_rejects_invalid_proof() {
+		run_test_with_genesis(example_header(), TOTAL_VALIDATORS, |_| {
+			let storage = BridgeStorage::<TestRuntime>::new();
+			let mut proof = example_receipt_proof(&example_header(), true);
+			proof.index = 1;
+			assert_eq!(verify_receipt_finalized(&storage, &proof), None);
+
+			let mut proof = example_receipt_proof(&example_header(), true);
+			proof.proof.clear();
+			assert_eq!(verify_receipt_finalized(&storage, &proof), None);
+		});
+	}
+
+	#[test]
+	fn verify_receipt_finalized_rejects_failed_transaction() {
+		run_test_with_genesis(example_header_with_failed_receipt(), TOTAL_VALIDATORS, |_| {
+			let storage = BridgeStorage::<TestRuntime>::new();
+			assert_eq!(
+				verify_receipt_finalized(
+					&storage,
+					&example_receipt_proof(&example_header_with_failed_receipt(), false)
+				),
+				None,
+			);
+		});
+	}
+
+	#[test]
+	fn ethash_headers_are_imported_and_finalized() {
+		let cache = ethash::tests::test_cache();
+		let tree = ethash::DatasetMerkleTree::generate(&cache);
+		let seal_proof = |header: &AuraHeader| Some(tree.seal_proof(header).unwrap());
+
+		run_test_with_genesis(ethash_genesis(), TOTAL_VALIDATORS, |_| {
+			TestEngineConfiguration::set(&EngineConfiguration::Ethash(ethash_config(&tree)));
+
+			// headers with Aura seal are rejected
+			let mut aura_header = mine_ethash_child(&cache, &ethash_genesis());
+			let aura_header_seal_proof = seal_proof(&aura_header);
+			aura_header.seal = HeaderBuilder::with_number(1).sign_by(&validator(0)).seal;
+			assert!(Pallet::<TestRuntime>::import_unsigned_header(
+				Origin::none(),
+				aura_header,
+				None,
+				aura_header_seal_proof
+			)
+			.is_err());
+
+			// mined headers are imported and finalized once they have enough confirmations
+			let mut headers = vec![mine_ethash_child(&cache, &ethash_genesis())];
+			for _ in 1..4 {
+				headers.push(mine_ethash_child(&cache, headers.last().unwrap()));
+			}
+			assert!(Pallet::<TestRuntime>::import_signed_headers(
+				Origin::signed(1),
+				headers
+					.iter()
+					.cloned()
+					.map(|header| {
+						let header_seal_proof = seal_proof(&header);
+						(header, None, header_seal_proof)
+					})
+					.collect(),
+			)
+			.is_ok());
+			assert_eq!(Pallet::<TestRuntime>::best_block(), headers[3].compute_id());
+			assert_eq!(Pallet::<TestRuntime>::finalized_block(), headers[1].compute_id());
+
+			// receipts are never required
+			let header = mine_ethash_child(&cache, &headers[3]);
+			assert!(!Pallet::<TestRuntime>::is_import_requires_receipts(header.clone()));
+			assert!(Pallet::<TestRuntime>::import_unsigned_header(
+				Origin::none(),
+				header.clone(),
+				Some(vec![]),
+				seal_proof(&header),
+			)
+			.is_err());
+
+			// seal proof is always required and must match the header
+			assert!(Pallet::<TestRuntime>::import_unsigned_header(Origin::none(), header.clone(), None, None).is_err());
+			assert!(Pallet::<TestRuntime>::import_unsigned_header(
+				Origin::none(),
+				header.clone(),
+				None,
+				seal_proof(&headers[3]),
+			)
+			.is_err());
+
+			let header_seal_proof = seal_proof(&header);
+			assert!(
+				Pallet::<TestRuntime>::import_unsigned_header(Origin::none(), header, None, header_seal_proof).is_ok()
+			);
+			assert_eq!(Pallet::<TestRuntime>::finalized_block(), headers[2].compute_id());
+		});
+	}
+
+	#[test]
+	fn clique_headers_are_imported_and_finalized() {
+		use crate::clique::tests::{genesis as clique_genesis, sign_by, signed_child, test_config, TOTAL_SIGNERS};
+
+		run_test_with_genesis(clique_genesis(), TOTAL_VALIDATORS, |_| {
+			// genesis config has been built with Aura engine, so let's insert initial snapshot here
+			TestEngineConfiguration::set(&EngineConfiguration::Clique(test_config()));
+			BridgeStorage::<TestRuntime>::new()
+				.insert_clique_snapshot(CliqueSnapshot::from_checkpoint(&clique_genesis()).unwrap());
+
+			// header signed by unauthorized signer is rejected
+			let unauthorized_header = sign_by(
+				crate::clique::tests::child(&clique_genesis(), 1),
+				&validator(TOTAL_SIGNERS + 1),
+			);
+			assert!(
+				Pallet::<TestRuntime>::import_unsigned_header(Origin::none(), unauthorized_header, None, None).is_err()
+			);
+
+			// headers, signed by in-turn signers, are imported and finalized once they have enough confirmations
+			let mut headers = vec![signed_child(&clique_genesis(), 1)];
+			for number in 2..5 {
+				headers.push(signed_child(headers.last().unwrap(), number % TOTAL_SIGNERS));
+			}
+			assert!(Pallet::<TestRuntime>::import_signed_headers(
+				Origin::signed(1),
+				headers.iter().cloned().map(|header| (header, None, None)).collect(),
+			)
+			.is_ok());
+			assert_eq!(Pallet::<TestRuntime>::best_block(), headers[3].compute_id());
+			assert_eq!(Pallet::<TestRuntime>::finalized_block(), headers[1].compute_id());
+			assert_eq!(
+				CliqueSnapshots::<DefaultInstance>::get(headers[3].compute_hash()).map(|snapshot| snapshot.id),
+				Some(headers[3].compute_id()),
+			);
+
+			// header signed by recent signer is rejected
+			let recent_signer_header = signed_child(&headers[3], 1);
+			assert!(
+				Pallet::<TestRuntime>::import_unsigned_header(Origin::none(), recent_signer_header, None, None)
+					.is_err()
+			);
+		});
+	}
 }
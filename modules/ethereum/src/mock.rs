@@ -21,7 +21,9 @@ pub use crate::test_utils::{insert_header, validator_utils::*, validators_change
 pub use bp_eth_poa::signatures::secret_to_address;
 
 use crate::validators::{ValidatorsConfiguration, ValidatorsSource};
-use crate::{AuraConfiguration, ChainTime, Config, GenesisConfig as CrateGenesisConfig, PruningStrategy};
+use crate::{
+	AuraConfiguration, ChainTime, Config, EngineConfiguration, GenesisConfig as CrateGenesisConfig, PruningStrategy,
+};
 use bp_eth_poa::{Address, AuraHeader, H256, U256};
 use frame_support::{parameter_types, weights::Weight};
 use secp256k1::SecretKey;
@@ -84,11 +86,13 @@ impl frame_system::Config for TestRuntime {
 
 parameter_types! {
 	pub const TestFinalityVotesCachingInterval: Option<u64> = Some(16);
+	pub storage TestEngineConfiguration: EngineConfiguration = EngineConfiguration::Aura;
 	pub TestAuraConfiguration: AuraConfiguration = test_aura_config();
 	pub TestValidatorsConfiguration: ValidatorsConfiguration = test_validators_config();
 }
 
 impl Config for TestRuntime {
+	type EngineConfiguration = TestEngineConfiguration;
 	type AuraConfiguration = TestAuraConfiguration;
 	type ValidatorsConfiguration = TestValidatorsConfiguration;
 	type FinalityVotesCachingInterval = TestFinalityVotesCachingInterval;
@@ -14,9 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
+use crate::clique::{verify_clique_header, CliqueSnapshot};
 use crate::error::Error;
+use crate::ethash::{verify_ethash_header, EthashSealProof};
 use crate::validators::{Validators, ValidatorsConfiguration};
-use crate::{AuraConfiguration, AuraScheduledChange, ChainTime, ImportContext, PoolConfiguration, Storage};
+use crate::{
+	AuraConfiguration, AuraScheduledChange, ChainTime, EngineConfiguration, ImportContext, PoolConfiguration, Storage,
+};
 use bp_eth_poa::{
 	public_to_address, step_validator, Address, AuraHeader, HeaderId, Receipt, SealedEmptyStep, H256, H520, U128, U256,
 };
@@ -43,6 +47,61 @@ pub fn is_importable_header<S: Storage>(storage: &S, header: &AuraHeader) -> Res
 	Ok((id, finalized_id))
 }
 
+/// Try accept unsigned header into transaction pool.
+///
+/// Returns required and provided tags.
+#[allow(clippy::too_many_arguments)]
+pub fn accept_header_into_pool<S: Storage, CT: ChainTime>(
+	storage: &S,
+	engine_config: &EngineConfiguration,
+	aura_config: &AuraConfiguration,
+	validators_config: &ValidatorsConfiguration,
+	pool_config: &PoolConfiguration,
+	header: &AuraHeader,
+	chain_time: &CT,
+	receipts: Option<&Vec<Receipt>>,
+	seal_proof: Option<&EthashSealProof>,
+) -> Result<(Vec<TransactionTag>, Vec<TransactionTag>), Error> {
+	match *engine_config {
+		EngineConfiguration::Aura => {
+			if seal_proof.is_some() {
+				return Err(Error::RedundantSealProof);
+			}
+
+			accept_aura_header_into_pool(
+				storage,
+				aura_config,
+				validators_config,
+				pool_config,
+				header,
+				chain_time,
+				receipts,
+			)
+		}
+		EngineConfiguration::Ethash(_) | EngineConfiguration::Clique(_) => {
+			// receipts are never required, because validators set never changes
+			if receipts.is_some() {
+				return Err(Error::RedundantTransactionsReceipts);
+			}
+
+			// unlike Aura headers, we only accept headers with known parent into the pool, so
+			// the header is fully verified here
+			let (header_id, _) = is_importable_header(storage, header)?;
+			verify_header(
+				storage,
+				engine_config,
+				aura_config,
+				None,
+				header,
+				chain_time,
+				seal_proof,
+			)?;
+
+			Ok((vec![], vec![header_id.encode()]))
+		}
+	}
+}
+
 /// Try accept unsigned aura header into transaction pool.
 ///
 /// Returns required and provided tags.
@@ -153,6 +212,52 @@ pub fn accept_aura_header_into_pool<S: Storage, CT: ChainTime>(
 	Ok(tags)
 }
 
+/// Verify header by the rules of configured sealing engine.
+///
+/// The seal proof is only accepted (and may be required) by the Ethash engine.
+///
+/// Returns import context of the header and Clique snapshot at the header (for the Clique engine).
+pub fn verify_header<S: Storage, CT: ChainTime>(
+	storage: &S,
+	engine_config: &EngineConfiguration,
+	aura_config: &AuraConfiguration,
+	submitter: Option<S::Submitter>,
+	header: &AuraHeader,
+	chain_time: &CT,
+	seal_proof: Option<&EthashSealProof>,
+) -> Result<(ImportContext<S::Submitter>, Option<CliqueSnapshot>), Error> {
+	if seal_proof.is_some() && !matches!(*engine_config, EngineConfiguration::Ethash(_)) {
+		return Err(Error::RedundantSealProof);
+	}
+
+	match *engine_config {
+		EngineConfiguration::Aura => {
+			verify_aura_header(storage, aura_config, submitter, header, chain_time).map(|context| (context, None))
+		}
+		EngineConfiguration::Ethash(ref ethash_config) => {
+			let context = parent_import_context(storage, submitter, header)?;
+			verify_ethash_header(ethash_config, context.parent_header(), header, chain_time, seal_proof)?;
+
+			Ok((context, None))
+		}
+		EngineConfiguration::Clique(ref clique_config) => {
+			let context = parent_import_context(storage, submitter, header)?;
+			let parent_snapshot = storage
+				.clique_snapshot(&header.parent_hash)
+				.ok_or(Error::MissingParentBlock)?;
+			let snapshot = verify_clique_header(
+				clique_config,
+				&parent_snapshot,
+				context.parent_header(),
+				header,
+				chain_time,
+			)?;
+
+			Ok((context, Some(snapshot)))
+		}
+	}
+}
+
 /// Verify header by Aura rules.
 pub fn verify_aura_header<S: Storage, CT: ChainTime>(
 	storage: &S,
@@ -165,7 +270,20 @@ pub fn verify_aura_header<S: Storage, CT: ChainTime>(
 	contextless_checks(config, header, chain_time)?;
 
 	// the rest of checks requires access to the parent header
-	let context = storage.import_context(submitter, &header.parent_hash).ok_or_else(|| {
+	let context = parent_import_context(storage, submitter, header)?;
+	let header_step = contextual_checks(config, &context, None, header)?;
+	validator_checks(config, &context.validators_set().validators, header, header_step)?;
+
+	Ok(context)
+}
+
+/// Returns import context of the header parent.
+fn parent_import_context<S: Storage>(
+	storage: &S,
+	submitter: Option<S::Submitter>,
+	header: &AuraHeader,
+) -> Result<ImportContext<S::Submitter>, Error> {
+	storage.import_context(submitter, &header.parent_hash).ok_or_else(|| {
 		log::warn!(
 			target: "runtime",
 			"Missing parent PoA block: ({:?}, {})",
@@ -174,11 +292,7 @@ pub fn verify_aura_header<S: Storage, CT: ChainTime>(
 		);
 
 		Error::MissingParentBlock
-	})?;
-	let header_step = contextual_checks(config, &context, None, header)?;
-	validator_checks(config, &context.validators_set().validators, header, header_step)?;
-
-	Ok(context)
+	})
 }
 
 /// Perform basic checks that only require header itself.
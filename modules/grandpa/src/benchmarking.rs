@@ -61,6 +61,10 @@ const MAX_VOTE_ANCESTRIES: u32 = 1000;
 // number of validators.
 const MAX_VALIDATOR_SET_SIZE: u32 = 1024;
 
+// The maximum number of headers in the ancestry proof. In practice this is limited by the number of
+// headers between two mandatory headers of the bridged chain.
+const MAX_ANCESTRY_PROOF_LENGTH: u32 = 1000;
+
 /// Returns number of first header to be imported.
 ///
 /// Since we boostrap the pallet with `HeadersToKeep` already imported headers,
@@ -83,7 +87,7 @@ fn prepare_benchmark_data<T: Config<I>, I: 'static>(
 		header: bp_test_utils::test_header(Zero::zero()),
 		authority_list,
 		set_id: TEST_GRANDPA_SET_ID,
-		is_halted: false,
+		operating_mode: bp_header_chain::OperatingMode::Normal,
 	};
 
 	bootstrap_bridge::<T, I>(init_data);
@@ -117,4 +121,43 @@ benchmarks_instance_pallet! {
 		assert_eq!(<BestFinalized<T, I>>::get(), expected_hash);
 		assert!(<ImportedHeaders<T, I>>::contains_key(expected_hash));
 	}
+
+	// Benchmark `submit_ancestry_proof` extrinsic with ancestry proof of `a` headers.
+	submit_ancestry_proof {
+		let a in 1..MAX_ANCESTRY_PROOF_LENGTH;
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = prepare_benchmark_data::<T, I>(1, 1);
+
+		let first_number: u32 = header_number::<T, I, _>();
+		let mut parent_hash = Default::default();
+		let mut ancestry_proof = Vec::new();
+		for number in first_number..first_number + a {
+			let mut header: BridgedHeader<T, I> = bp_test_utils::test_header(number.into());
+			header.set_parent_hash(parent_hash);
+			parent_hash = header.hash();
+			ancestry_proof.push(header);
+		}
+		let mut descendant: BridgedHeader<T, I> = bp_test_utils::test_header((first_number + a).into());
+		descendant.set_parent_hash(parent_hash);
+		let descendant_hash = descendant.hash();
+		insert_header::<T, I>(descendant, descendant_hash);
+
+		let expected_hash = ancestry_proof[0].hash();
+		ancestry_proof.reverse();
+	}: submit_ancestry_proof(RawOrigin::Signed(caller), descendant_hash, ancestry_proof)
+	verify {
+		assert!(<ImportedHeaders<T, I>>::contains_key(expected_hash));
+	}
+
+	// Benchmark `prune_headers` extrinsic when all `n` headers are outside of the retention window.
+	prune_headers {
+		let n in 1..(T::HeadersToKeep::get() - 1);
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = prepare_benchmark_data::<T, I>(1, 1);
+		let oldest_index = <OldestImportedHashPointer<T, I>>::get().unwrap_or_default();
+		<HeadersRetentionWindow<T, I>>::put(T::HeadersToKeep::get() - n);
+	}: prune_headers(RawOrigin::Signed(caller), n)
+	verify {
+		assert_eq!(<OldestImportedHashPointer<T, I>>::get(), Some(oldest_index + n));
+	}
 }
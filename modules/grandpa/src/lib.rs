@@ -39,14 +39,14 @@
 use crate::weights::WeightInfo;
 
 use bp_header_chain::justification::GrandpaJustification;
-use bp_header_chain::InitializationData;
+use bp_header_chain::{FinalityPalletState, HeadersPruningGuard, InitializationData, OperatingMode};
 use bp_runtime::{BlockNumberOf, Chain, HashOf, HasherOf, HeaderOf};
 use finality_grandpa::voter_set::VoterSet;
 use frame_support::{ensure, fail};
 use frame_system::{ensure_signed, RawOrigin};
-use sp_finality_grandpa::{ConsensusLog, GRANDPA_ENGINE_ID};
+use sp_finality_grandpa::{AuthorityList, ConsensusLog, SetId, GRANDPA_ENGINE_ID};
 use sp_runtime::traits::{BadOrigin, Header as HeaderT, Zero};
-use sp_std::convert::TryInto;
+use sp_std::{convert::TryInto, vec::Vec};
 
 #[cfg(test)]
 mod mock;
@@ -54,6 +54,8 @@ mod mock;
 /// Pallet containing weights for this pallet.
 pub mod weights;
 
+pub mod migration;
+
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarking;
 
@@ -89,14 +91,36 @@ pub mod pallet {
 		#[pallet::constant]
 		type MaxRequests: Get<u32>;
 
-		/// Maximal number of finalized headers to keep in the storage.
+		/// Default number of finalized headers to keep in the storage.
 		///
 		/// The setting is there to prevent growing the on-chain state indefinitely. Note
 		/// the setting does not relate to block numbers - we will simply keep as much items
 		/// in the storage, so it doesn't guarantee any fixed timeframe for finality headers.
+		///
+		/// The value may be overridden by the pallet owner (see `HeadersRetentionWindow`).
 		#[pallet::constant]
 		type HeadersToKeep: Get<u32>;
 
+		/// Guard that prevents pruning of headers that may still be referenced by other pallets.
+		///
+		/// Headers that are required by the guard are kept in the storage even if they are outside
+		/// of the retention window. They're pruned once the guard releases them.
+		type HeadersPruningGuard: HeadersPruningGuard<BridgedBlockNumber<Self, I>>;
+
+		/// Maximal number of headers outside of the retention window that may be kept by the
+		/// `HeadersPruningGuard`.
+		///
+		/// If the guard requires more headers, oldest of them are pruned anyway. This bounds the
+		/// storage growth when the guard is blocking pruning for a long time.
+		#[pallet::constant]
+		type MaxGuardedHeaders: Get<u32>;
+
+		/// Origin that has the same rights as the pallet owner.
+		///
+		/// It may be used to let multisig or collective manage the pallet. Root and the `PalletOwner`
+		/// account are always allowed to manage the pallet.
+		type OwnerOrigin: EnsureOrigin<Self::Origin>;
+
 		/// Weights gathered through benchmarking.
 		type WeightInfo: WeightInfo;
 	}
@@ -113,6 +137,36 @@ pub mod pallet {
 				.saturating_add(T::DbWeight::get().reads(1))
 				.saturating_add(T::DbWeight::get().writes(1))
 		}
+
+		/// Upgrade storage to the latest version.
+		fn on_runtime_upgrade() -> frame_support::weights::Weight {
+			migration::migrate::<T, I>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()?;
+
+			// remember current authority set id, so we can check that it is not decreased by the upgrade
+			let set_id = <CurrentAuthoritySet<T, I>>::get().set_id;
+			frame_support::storage::unhashed::put(&pre_upgrade_set_id_key::<T, I>(), &set_id);
+
+			Ok(())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()?;
+
+			let pre_upgrade_set_id: Option<sp_finality_grandpa::SetId> =
+				frame_support::storage::unhashed::take(&pre_upgrade_set_id_key::<T, I>());
+			ensure!(
+				pre_upgrade_set_id.unwrap_or(0) <= <CurrentAuthoritySet<T, I>>::get().set_id,
+				"Current authority set id has been decreased by the upgrade"
+			);
+
+			Ok(())
+		}
 	}
 
 	#[pallet::call]
@@ -133,7 +187,7 @@ pub mod pallet {
 			finality_target: BridgedHeader<T, I>,
 			justification: GrandpaJustification<BridgedHeader<T, I>>,
 		) -> DispatchResultWithPostInfo {
-			ensure_operational::<T, I>()?;
+			let operating_mode = ensure_not_halted::<T, I>()?;
 			let _ = ensure_signed(origin)?;
 
 			ensure!(
@@ -141,6 +195,13 @@ pub mod pallet {
 				<Error<T, I>>::TooManyRequests
 			);
 
+			// while the pallet is only accepting mandatory headers, we still need to track authorities
+			// set changes => headers that are scheduling authorities set change are accepted
+			ensure!(
+				operating_mode == OperatingMode::Normal || find_scheduled_change(&finality_target).is_some(),
+				<Error<T, I>>::NonMandatoryHeader
+			);
+
 			let (hash, number) = (finality_target.hash(), finality_target.number());
 			log::trace!(target: "runtime::bridge-grandpa", "Going to try and finalize header {:?}", finality_target);
 
@@ -172,6 +233,68 @@ pub mod pallet {
 			Ok(().into())
 		}
 
+		/// Import finalized ancestor of the already imported header.
+		///
+		/// The `ancestry_proof` is the chain of headers, starting with the parent of the imported
+		/// header with the `descendant_hash` and ending with the header that we want to import. Every
+		/// ancestor of the finalized header is also finalized, so no justification is required. Only
+		/// the last header of the chain is imported.
+		///
+		/// This allows relayers to import only headers that are required (e.g. by message proofs)
+		/// instead of importing every header with its justification.
+		///
+		/// Imported ancestors are kept in their own queue, so they never cause eviction of headers
+		/// that have been imported with justifications.
+		#[pallet::weight(T::WeightInfo::submit_ancestry_proof(
+			ancestry_proof.len().try_into().unwrap_or(u32::MAX),
+		))]
+		pub fn submit_ancestry_proof(
+			origin: OriginFor<T>,
+			descendant_hash: BridgedBlockHash<T, I>,
+			ancestry_proof: Vec<BridgedHeader<T, I>>,
+		) -> DispatchResultWithPostInfo {
+			let operating_mode = ensure_not_halted::<T, I>()?;
+			let _ = ensure_signed(origin)?;
+
+			ensure!(
+				Self::request_count() < T::MaxRequests::get(),
+				<Error<T, I>>::TooManyRequests
+			);
+
+			// headers between mandatory headers are never mandatory
+			ensure!(
+				operating_mode == OperatingMode::Normal,
+				<Error<T, I>>::NonMandatoryHeader
+			);
+
+			let descendant = <ImportedHeaders<T, I>>::get(descendant_hash).ok_or(<Error<T, I>>::UnknownHeader)?;
+			let mut expected_hash = *descendant.parent_hash();
+			let mut ancestor = None;
+			for header in ancestry_proof {
+				ensure!(header.hash() == expected_hash, <Error<T, I>>::InvalidAncestryProof);
+				expected_hash = *header.parent_hash();
+				ancestor = Some(header);
+			}
+
+			let header = ancestor.ok_or(<Error<T, I>>::InvalidAncestryProof)?;
+			let hash = header.hash();
+			ensure!(
+				!<ImportedHeaders<T, I>>::contains_key(hash),
+				<Error<T, I>>::HeaderAlreadyImported
+			);
+
+			<RequestCount<T, I>>::mutate(|count| *count += 1);
+			insert_finalized_ancestor::<T, I>(header, hash);
+			log::info!(
+				target: "runtime::bridge-grandpa",
+				"Succesfully imported finalized ancestor of header {:?}: {:?}",
+				descendant_hash,
+				hash,
+			);
+
+			Ok(().into())
+		}
+
 		/// Bootstrap the bridge pallet with an initial header and authority set from which to sync.
 		///
 		/// The initial configuration provided does not need to be the genesis header of the bridged
@@ -221,22 +344,98 @@ pub mod pallet {
 			Ok(().into())
 		}
 
-		/// Halt or resume all pallet operations.
+		/// Halt or resume all/some pallet operations.
 		///
 		/// May only be called either by root, or by `PalletOwner`.
 		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational))]
-		pub fn set_operational(origin: OriginFor<T>, operational: bool) -> DispatchResultWithPostInfo {
+		pub fn set_operating_mode(origin: OriginFor<T>, operating_mode: OperatingMode) -> DispatchResultWithPostInfo {
 			ensure_owner_or_root::<T, I>(origin)?;
-			<IsHalted<T, I>>::put(operational);
+			<PalletOperatingMode<T, I>>::put(operating_mode);
+			log::info!(
+				target: "runtime::bridge-grandpa",
+				"Setting GRANDPA pallet operating mode to {:?}.",
+				operating_mode,
+			);
 
-			if operational {
-				log::info!(target: "runtime::bridge-grandpa", "Resuming pallet operations.");
-			} else {
-				log::warn!(target: "runtime::bridge-grandpa", "Stopping pallet operations.");
+			Ok(().into())
+		}
+
+		/// Forcibly change the current authorities set.
+		///
+		/// This is a recovery path for cases when the bridged chain has applied a forced authorities
+		/// set change (which is not supported by the `submit_finality_proof`). The new set id must
+		/// be larger than the current set id and the new authorities set must be valid.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::weight((T::DbWeight::get().reads_writes(3, 1), DispatchClass::Operational))]
+		pub fn force_set_authorities(
+			origin: OriginFor<T>,
+			authority_list: AuthorityList,
+			set_id: SetId,
+		) -> DispatchResultWithPostInfo {
+			ensure_owner_or_root::<T, I>(origin)?;
+			ensure!(<InitialHash<T, I>>::exists(), <Error<T, I>>::NotInitialized);
+
+			let current_set_id = <CurrentAuthoritySet<T, I>>::get().set_id;
+			ensure!(set_id > current_set_id, <Error<T, I>>::InvalidAuthoritySetId);
+			ensure!(
+				VoterSet::new(authority_list.iter().cloned()).is_some(),
+				<Error<T, I>>::InvalidAuthoritySet
+			);
+
+			<CurrentAuthoritySet<T, I>>::put(bp_header_chain::AuthoritySet::new(authority_list, set_id));
+			log::warn!(
+				target: "runtime::bridge-grandpa",
+				"Authorities set has been forcibly changed: {} -> {}",
+				current_set_id,
+				set_id,
+			);
+
+			Ok(().into())
+		}
+
+		/// Change number of finalized headers to keep in the storage.
+		///
+		/// If `None` is passed, the `T::HeadersToKeep` is used. Headers that are outside of the new
+		/// retention window are not pruned immediately - use `prune_headers` to prune them.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational))]
+		pub fn set_headers_retention_window(
+			origin: OriginFor<T>,
+			headers_to_keep: Option<u32>,
+		) -> DispatchResultWithPostInfo {
+			ensure_owner_or_root::<T, I>(origin)?;
+			match headers_to_keep {
+				Some(headers_to_keep) => {
+					// we should always keep the best finalized header
+					ensure!(headers_to_keep != 0, <Error<T, I>>::InvalidRetentionWindow);
+					<HeadersRetentionWindow<T, I>>::put(headers_to_keep);
+				}
+				None => <HeadersRetentionWindow<T, I>>::kill(),
 			}
+			log::info!(
+				target: "runtime::bridge-grandpa",
+				"Setting GRANDPA pallet headers retention window to {:?}.",
+				headers_to_keep,
+			);
 
 			Ok(().into())
 		}
+
+		/// Prune at most `max_headers` oldest headers that are outside of the retention window.
+		///
+		/// Headers that are required by the `T::HeadersPruningGuard` are never pruned.
+		#[pallet::weight(T::WeightInfo::prune_headers(*max_headers))]
+		pub fn prune_headers(origin: OriginFor<T>, max_headers: u32) -> DispatchResultWithPostInfo {
+			ensure_not_halted::<T, I>()?;
+			let _ = ensure_signed(origin)?;
+
+			let pruned_headers = prune_old_headers::<T, I>(max_headers);
+			log::trace!(target: "runtime::bridge-grandpa", "Pruned {} old headers", pruned_headers);
+
+			Ok(Some(T::WeightInfo::prune_headers(pruned_headers)).into())
+		}
 	}
 
 	/// The current number of requests which have written to storage.
@@ -258,15 +457,40 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type BestFinalized<T: Config<I>, I: 'static = ()> = StorageValue<_, BridgedBlockHash<T, I>, ValueQuery>;
 
-	/// A ring buffer of imported hashes. Ordered by the insertion time.
+	/// A queue of imported hashes. Ordered by the insertion time.
 	#[pallet::storage]
 	pub(super) type ImportedHashes<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Identity, u32, BridgedBlockHash<T, I>>;
 
-	/// Current ring buffer position.
+	/// Position in the `ImportedHashes` where the next imported hash will be inserted.
 	#[pallet::storage]
 	pub(super) type ImportedHashesPointer<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
 
+	/// Position of the oldest not yet pruned hash in the `ImportedHashes`.
+	///
+	/// The value is missing if the storage hasn't been migrated from the ring buffer layout yet.
+	#[pallet::storage]
+	pub(super) type OldestImportedHashPointer<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, OptionQuery>;
+
+	/// A queue of hashes, imported using ancestry proofs. Ordered by the insertion time.
+	#[pallet::storage]
+	pub(super) type ImportedAncestorHashes<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, u32, BridgedBlockHash<T, I>>;
+
+	/// Position in the `ImportedAncestorHashes` where the next imported hash will be inserted.
+	#[pallet::storage]
+	pub(super) type ImportedAncestorHashesPointer<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
+
+	/// Position of the oldest not yet pruned hash in the `ImportedAncestorHashes`.
+	#[pallet::storage]
+	pub(super) type OldestImportedAncestorHashPointer<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, ValueQuery>;
+
+	/// Number of finalized headers to keep in the storage.
+	///
+	/// If it is `None`, then `T::HeadersToKeep` headers are kept.
+	#[pallet::storage]
+	pub(super) type HeadersRetentionWindow<T: Config<I>, I: 'static = ()> = StorageValue<_, u32, OptionQuery>;
+
 	/// Headers which have been imported into the pallet.
 	#[pallet::storage]
 	pub(super) type ImportedHeaders<T: Config<I>, I: 'static = ()> =
@@ -286,9 +510,12 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type PalletOwner<T: Config<I>, I: 'static = ()> = StorageValue<_, T::AccountId, OptionQuery>;
 
-	/// If true, all pallet transactions are failed immediately.
+	/// The current operating mode of the pallet.
+	///
+	/// Depending on the mode either all, some, or no transactions will be allowed.
 	#[pallet::storage]
-	pub(super) type IsHalted<T: Config<I>, I: 'static = ()> = StorageValue<_, bool, ValueQuery>;
+	#[pallet::getter(fn operating_mode)]
+	pub(super) type PalletOperatingMode<T: Config<I>, I: 'static = ()> = StorageValue<_, OperatingMode, ValueQuery>;
 
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
@@ -320,7 +547,7 @@ pub mod pallet {
 			} else {
 				// Since the bridge hasn't been initialized we shouldn't allow anyone to perform
 				// transactions.
-				<IsHalted<T, I>>::put(true);
+				<PalletOperatingMode<T, I>>::put(OperatingMode::Halted);
 			}
 		}
 	}
@@ -347,8 +574,20 @@ pub mod pallet {
 		AlreadyInitialized,
 		/// All pallet operations are halted.
 		Halted,
+		/// The pallet is only accepting mandatory headers and the header is not mandatory.
+		NonMandatoryHeader,
+		/// The new authority set id must be larger than the current authority set id.
+		InvalidAuthoritySetId,
+		/// The headers retention window must keep at least one header.
+		InvalidRetentionWindow,
+		/// The ancestry proof is empty or headers of the proof are not forming a chain.
+		InvalidAncestryProof,
+		/// The header has already been imported.
+		HeaderAlreadyImported,
 		/// The storage proof doesn't contains storage root. So it is invalid for given header.
 		StorageRootMismatch,
+		/// The storage proof contains duplicate nodes.
+		DuplicateNodesInStorageProof,
 	}
 
 	/// Check the given header for a GRANDPA scheduled authority set change. If a change
@@ -434,18 +673,157 @@ pub mod pallet {
 	/// Note this function solely takes care of updating the storage and pruning old entries,
 	/// but does not verify the validaty of such import.
 	pub(crate) fn insert_header<T: Config<I>, I: 'static>(header: BridgedHeader<T, I>, hash: BridgedBlockHash<T, I>) {
-		let index = <ImportedHashesPointer<T, I>>::get();
-		let pruning = <ImportedHashes<T, I>>::try_get(index);
 		<BestFinalized<T, I>>::put(hash);
+		insert_finalized_header::<T, I>(header, hash);
+	}
+
+	/// Import a previously verified header to the storage, without changing the best finalized
+	/// header.
+	///
+	/// Note this function solely takes care of updating the storage and pruning old entries,
+	/// but does not verify the validity of such import.
+	pub(crate) fn insert_finalized_header<T: Config<I>, I: 'static>(
+		header: BridgedHeader<T, I>,
+		hash: BridgedBlockHash<T, I>,
+	) {
+		let index = <ImportedHashesPointer<T, I>>::get();
 		<ImportedHeaders<T, I>>::insert(hash, header);
 		<ImportedHashes<T, I>>::insert(index, hash);
+		<ImportedHashesPointer<T, I>>::put(index.wrapping_add(1));
+
+		// Remove old headers. We prune at most two headers here, so once the guard stops blocking
+		// pruning, the number of stored headers is slowly getting back to the retention window. If
+		// the retention window has been decreased, the `prune_headers` call may be used to get rid
+		// of remaining old headers faster.
+		let headers_to_keep = Pallet::<T, I>::headers_to_keep();
+		let oldest_required_header = T::HeadersPruningGuard::oldest_required_header();
+		prune_finalized_headers::<T, I>(2, headers_to_keep, oldest_required_header);
+	}
+
+	/// Import a previously verified finalized ancestor to the storage.
+	///
+	/// Ancestors are stored in the separate queue, so importing them never leads to pruning
+	/// of headers that have been imported with justifications.
+	pub(crate) fn insert_finalized_ancestor<T: Config<I>, I: 'static>(
+		header: BridgedHeader<T, I>,
+		hash: BridgedBlockHash<T, I>,
+	) {
+		let index = <ImportedAncestorHashesPointer<T, I>>::get();
+		<ImportedHeaders<T, I>>::insert(hash, header);
+		<ImportedAncestorHashes<T, I>>::insert(index, hash);
+		<ImportedAncestorHashesPointer<T, I>>::put(index.wrapping_add(1));
+
+		let headers_to_keep = Pallet::<T, I>::headers_to_keep();
+		let oldest_required_header = T::HeadersPruningGuard::oldest_required_header();
+		prune_finalized_ancestors::<T, I>(2, headers_to_keep, oldest_required_header);
+	}
+
+	/// Prune at most `max_headers` oldest headers that are outside of the retention window.
+	///
+	/// Headers that have been imported with justifications are pruned first. Pruning stops at
+	/// the first header that is required by the `T::HeadersPruningGuard`. Returns number of
+	/// pruned headers.
+	pub(crate) fn prune_old_headers<T: Config<I>, I: 'static>(max_headers: u32) -> u32 {
+		let headers_to_keep = Pallet::<T, I>::headers_to_keep();
+		let oldest_required_header = T::HeadersPruningGuard::oldest_required_header();
+		let pruned_headers = prune_finalized_headers::<T, I>(max_headers, headers_to_keep, oldest_required_header);
+		pruned_headers
+			+ prune_finalized_ancestors::<T, I>(max_headers - pruned_headers, headers_to_keep, oldest_required_header)
+	}
+
+	/// Prune at most `max_headers` oldest headers from the `ImportedHashes` queue.
+	fn prune_finalized_headers<T: Config<I>, I: 'static>(
+		max_headers: u32,
+		headers_to_keep: u32,
+		oldest_required_header: Option<BridgedBlockNumber<T, I>>,
+	) -> u32 {
+		let (oldest_index, pruned_headers) = prune_headers_queue::<T, I>(
+			<ImportedHashesPointer<T, I>>::get(),
+			<OldestImportedHashPointer<T, I>>::get().unwrap_or_default(),
+			max_headers,
+			headers_to_keep,
+			oldest_required_header,
+			<ImportedHashes<T, I>>::get,
+			<ImportedHashes<T, I>>::remove,
+		);
+		<OldestImportedHashPointer<T, I>>::put(oldest_index);
+		pruned_headers
+	}
+
+	/// Prune at most `max_headers` oldest headers from the `ImportedAncestorHashes` queue.
+	fn prune_finalized_ancestors<T: Config<I>, I: 'static>(
+		max_headers: u32,
+		headers_to_keep: u32,
+		oldest_required_header: Option<BridgedBlockNumber<T, I>>,
+	) -> u32 {
+		let (oldest_index, pruned_headers) = prune_headers_queue::<T, I>(
+			<ImportedAncestorHashesPointer<T, I>>::get(),
+			<OldestImportedAncestorHashPointer<T, I>>::get(),
+			max_headers,
+			headers_to_keep,
+			oldest_required_header,
+			<ImportedAncestorHashes<T, I>>::get,
+			<ImportedAncestorHashes<T, I>>::remove,
+		);
+		<OldestImportedAncestorHashPointer<T, I>>::put(oldest_index);
+		pruned_headers
+	}
+
+	/// Prune at most `max_headers` oldest headers of the queue, stored at
+	/// `[oldest_index; next_index)` indices, so that at most `headers_to_keep` headers are left.
+	///
+	/// Headers that are required by the `T::HeadersPruningGuard` are only kept while there are at
+	/// most `headers_to_keep + T::MaxGuardedHeaders` headers in the queue. Otherwise they are pruned
+	/// with a warning.
+	///
+	/// Returns new index of the oldest header in the queue and number of pruned headers.
+	fn prune_headers_queue<T: Config<I>, I: 'static>(
+		next_index: u32,
+		mut oldest_index: u32,
+		max_headers: u32,
+		headers_to_keep: u32,
+		oldest_required_header: Option<BridgedBlockNumber<T, I>>,
+		hash_at: impl Fn(u32) -> Option<BridgedBlockHash<T, I>>,
+		remove_hash_at: impl Fn(u32),
+	) -> (u32, u32) {
+		let max_guarded_headers = headers_to_keep.saturating_add(T::MaxGuardedHeaders::get());
+		let mut pruned_headers = 0;
+		while pruned_headers < max_headers && next_index.wrapping_sub(oldest_index) > headers_to_keep {
+			let hash = match hash_at(oldest_index) {
+				Some(hash) => hash,
+				None => break,
+			};
+			if let Some(oldest_required_header) = oldest_required_header {
+				let is_required = <ImportedHeaders<T, I>>::get(hash)
+					.map(|header| *header.number() >= oldest_required_header)
+					.unwrap_or(false);
+				let is_guarded = next_index.wrapping_sub(oldest_index) <= max_guarded_headers;
+				if is_required && is_guarded {
+					log::trace!(
+						target: "runtime::bridge-grandpa",
+						"Not pruning old header {:?}: it is required by the pruning guard.",
+						hash,
+					);
+					break;
+				}
+				if is_required {
+					log::warn!(
+						target: "runtime::bridge-grandpa",
+						"Pruning old header {:?} that is required by the pruning guard: there are more than {} guarded headers.",
+						hash,
+						T::MaxGuardedHeaders::get(),
+					);
+				}
+			}
 
-		// Update ring buffer pointer and remove old header.
-		<ImportedHashesPointer<T, I>>::put((index + 1) % T::HeadersToKeep::get());
-		if let Ok(hash) = pruning {
 			log::debug!(target: "runtime::bridge-grandpa", "Pruning old header: {:?}.", hash);
+			remove_hash_at(oldest_index);
 			<ImportedHeaders<T, I>>::remove(hash);
+			oldest_index = oldest_index.wrapping_add(1);
+			pruned_headers += 1;
 		}
+
+		(oldest_index, pruned_headers)
 	}
 
 	/// Since this writes to storage with no real checks this should only be used in functions that
@@ -457,18 +835,19 @@ pub mod pallet {
 			header,
 			authority_list,
 			set_id,
-			is_halted,
+			operating_mode,
 		} = init_params;
 
 		let initial_hash = header.hash();
 		<InitialHash<T, I>>::put(initial_hash);
 		<ImportedHashesPointer<T, I>>::put(0);
+		<OldestImportedHashPointer<T, I>>::put(0);
 		insert_header::<T, I>(header, initial_hash);
 
 		let authority_set = bp_header_chain::AuthoritySet::new(authority_list, set_id);
 		<CurrentAuthoritySet<T, I>>::put(authority_set);
 
-		<IsHalted<T, I>>::put(is_halted);
+		<PalletOperatingMode<T, I>>::put(operating_mode);
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -496,6 +875,11 @@ pub mod pallet {
 
 	/// Ensure that the origin is either root, or `PalletOwner`.
 	fn ensure_owner_or_root<T: Config<I>, I: 'static>(origin: T::Origin) -> Result<(), BadOrigin> {
+		let origin = match T::OwnerOrigin::try_origin(origin) {
+			Ok(_) => return Ok(()),
+			Err(origin) => origin,
+		};
+
 		match origin.into() {
 			Ok(RawOrigin::Root) => Ok(()),
 			Ok(RawOrigin::Signed(ref signer)) if Some(signer) == <PalletOwner<T, I>>::get().as_ref() => Ok(()),
@@ -503,12 +887,11 @@ pub mod pallet {
 		}
 	}
 
-	/// Ensure that the pallet is in operational mode (not halted).
-	fn ensure_operational<T: Config<I>, I: 'static>() -> Result<(), Error<T, I>> {
-		if <IsHalted<T, I>>::get() {
-			Err(<Error<T, I>>::Halted)
-		} else {
-			Ok(())
+	/// Ensure that the pallet is not halted and return its current operating mode.
+	fn ensure_not_halted<T: Config<I>, I: 'static>() -> Result<OperatingMode, Error<T, I>> {
+		match <PalletOperatingMode<T, I>>::get() {
+			OperatingMode::Halted => Err(<Error<T, I>>::Halted),
+			operating_mode => Ok(operating_mode),
 		}
 	}
 }
@@ -531,27 +914,161 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		})
 	}
 
+	/// Get the number of finalized headers to keep in the storage.
+	pub fn headers_to_keep() -> u32 {
+		<HeadersRetentionWindow<T, I>>::get().unwrap_or_else(T::HeadersToKeep::get)
+	}
+
+	/// Ensure that the stored header chain is internally consistent.
+	///
+	/// This is expensive (it reads all stored headers), so it is only supposed to be used when
+	/// testing runtime upgrades.
+	#[cfg(any(feature = "try-runtime", test))]
+	pub fn do_try_state() -> Result<(), &'static str> {
+		// if pallet is not initialized, there must be no headers in the storage
+		if !<InitialHash<T, I>>::exists() {
+			ensure!(
+				<ImportedHeaders<T, I>>::iter().next().is_none(),
+				"Pallet is not initialized, but has imported headers"
+			);
+			return Ok(());
+		}
+
+		// until storage is migrated, imported hashes are stored in the ring buffer of the
+		// `T::HeadersToKeep` size
+		let (oldest_index, queue_len) = match <OldestImportedHashPointer<T, I>>::get() {
+			Some(oldest_index) => (
+				oldest_index,
+				<ImportedHashesPointer<T, I>>::get().wrapping_sub(oldest_index),
+			),
+			None => (0, T::HeadersToKeep::get()),
+		};
+
+		let best_finalized_header = <ImportedHeaders<T, I>>::get(<BestFinalized<T, I>>::get())
+			.ok_or("Best finalized header is missing from the storage")?;
+
+		let mut imported_hashes = 0;
+		for (index, hash) in <ImportedHashes<T, I>>::iter() {
+			ensure!(
+				index.wrapping_sub(oldest_index) < queue_len,
+				"Imported hash index is out of bounds"
+			);
+			ensure!(
+				<ImportedHeaders<T, I>>::contains_key(hash),
+				"Imported hash is missing from imported headers"
+			);
+			imported_hashes += 1;
+		}
+
+		let oldest_ancestor_index = <OldestImportedAncestorHashPointer<T, I>>::get();
+		let ancestors_queue_len = <ImportedAncestorHashesPointer<T, I>>::get().wrapping_sub(oldest_ancestor_index);
+		for (index, hash) in <ImportedAncestorHashes<T, I>>::iter() {
+			ensure!(
+				index.wrapping_sub(oldest_ancestor_index) < ancestors_queue_len,
+				"Imported ancestor hash index is out of bounds"
+			);
+			ensure!(
+				<ImportedHeaders<T, I>>::contains_key(hash),
+				"Imported ancestor hash is missing from imported headers"
+			);
+			imported_hashes += 1;
+		}
+
+		let mut imported_headers = 0;
+		for (hash, header) in <ImportedHeaders<T, I>>::iter() {
+			ensure!(header.hash() == hash, "Imported header is stored under wrong hash");
+			ensure!(
+				header.number() <= best_finalized_header.number(),
+				"Imported header is better than the best finalized header"
+			);
+			imported_headers += 1;
+		}
+		ensure!(
+			imported_hashes == imported_headers,
+			"Number of imported hashes doesn't match number of imported headers"
+		);
+
+		Ok(())
+	}
+
+	/// Get state of the pallet: best finalized header, current authorities set id and operating mode.
+	///
+	/// Returns `None` if the pallet has not been initialized yet.
+	pub fn finality_pallet_state() -> Option<FinalityPalletState<BridgedBlockNumber<T, I>, BridgedBlockHash<T, I>>> {
+		let best_finalized_hash = <BestFinalized<T, I>>::get();
+		let best_finalized_header = <ImportedHeaders<T, I>>::get(best_finalized_hash)?;
+		Some(FinalityPalletState {
+			best_finalized: (*best_finalized_header.number(), best_finalized_hash),
+			authority_set_id: <CurrentAuthoritySet<T, I>>::get().set_id,
+			operating_mode: <PalletOperatingMode<T, I>>::get(),
+		})
+	}
+
 	/// Check if a particular header is known to the bridge pallet.
 	pub fn is_known_header(hash: BridgedBlockHash<T, I>) -> bool {
 		<ImportedHeaders<T, I>>::contains_key(hash)
 	}
 
+	/// Get finalized header with given hash, if it is known to the bridge pallet.
+	pub fn finalized_header(hash: BridgedBlockHash<T, I>) -> Option<BridgedHeader<T, I>> {
+		<ImportedHeaders<T, I>>::get(hash)
+	}
+
 	/// Verify that the passed storage proof is valid, given it is crafted using
 	/// known finalized header. If the proof is valid, then the `parse` callback
-	/// is called and the function returns its result.
+	/// is called and the function returns its result. After reading all required
+	/// values, the `parse` callback should ensure that the proof has no unused nodes.
 	pub fn parse_finalized_storage_proof<R>(
 		hash: BridgedBlockHash<T, I>,
 		storage_proof: sp_trie::StorageProof,
 		parse: impl FnOnce(bp_runtime::StorageProofChecker<BridgedBlockHasher<T, I>>) -> R,
 	) -> Result<R, sp_runtime::DispatchError> {
 		let header = <ImportedHeaders<T, I>>::get(hash).ok_or(Error::<T, I>::UnknownHeader)?;
-		let storage_proof_checker = bp_runtime::StorageProofChecker::new(*header.state_root(), storage_proof)
-			.map_err(|_| Error::<T, I>::StorageRootMismatch)?;
+		let storage_proof_checker =
+			bp_runtime::StorageProofChecker::new(*header.state_root(), storage_proof).map_err(|err| match err {
+				bp_runtime::StorageProofError::DuplicateNodesInProof => Error::<T, I>::DuplicateNodesInStorageProof,
+				_ => Error::<T, I>::StorageRootMismatch,
+			})?;
 
 		Ok(parse(storage_proof_checker))
 	}
 }
 
+/// Storage key where the authority set id is stored before runtime upgrade.
+#[cfg(feature = "try-runtime")]
+fn pre_upgrade_set_id_key<T: Config<I>, I: 'static>() -> sp_std::vec::Vec<u8> {
+	let mut key = <CurrentAuthoritySet<T, I>>::hashed_key().to_vec();
+	key.extend_from_slice(b":pre-upgrade");
+	key
+}
+
+/// Storage keys of the pallet storage items that may be read by relayers.
+pub mod storage_keys {
+	use super::*;
+	use sp_core::storage::StorageKey;
+
+	/// Storage key prefix, shared by all storage items of the pallet.
+	pub fn pallet_prefix_key<T: Config<I>, I: 'static>() -> StorageKey {
+		StorageKey(<PalletOperatingMode<T, I>>::hashed_key()[..16].to_vec())
+	}
+
+	/// Storage key of the pallet operating mode in the runtime storage.
+	pub fn operating_mode_key<T: Config<I>, I: 'static>() -> StorageKey {
+		StorageKey(<PalletOperatingMode<T, I>>::hashed_key().to_vec())
+	}
+
+	/// Storage key of the best finalized header hash in the runtime storage. The value is missing
+	/// until the pallet is initialized.
+	pub fn best_finalized_key<T: Config<I>, I: 'static>() -> StorageKey {
+		StorageKey(<BestFinalized<T, I>>::hashed_key().to_vec())
+	}
+
+	/// Storage key of the current authorities set in the runtime storage.
+	pub fn current_authority_set_key<T: Config<I>, I: 'static>() -> StorageKey {
+		StorageKey(<CurrentAuthoritySet<T, I>>::hashed_key().to_vec())
+	}
+}
+
 pub(crate) fn find_scheduled_change<H: HeaderT>(header: &H) -> Option<sp_finality_grandpa::ScheduledChange<H::Number>> {
 	use sp_runtime::generic::OpaqueDigestItemId;
 
@@ -593,14 +1110,17 @@ pub fn initialize_for_benchmarks<T: Config<I>, I: 'static>(header: BridgedHeader
 		header,
 		authority_list: sp_std::vec::Vec::new(), // we don't verify any proofs in external benchmarks
 		set_id: 0,
-		is_halted: false,
+		operating_mode: OperatingMode::Normal,
 	});
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::mock::{run_test, test_header, Origin, TestHash, TestHeader, TestNumber, TestRuntime};
+	use crate::mock::{
+		run_test, test_header, Origin, TestHash, TestHeader, TestHeadersPruningGuard, TestNumber, TestRuntime,
+		TEST_OWNER_ORIGIN_ACCOUNT,
+	};
 	use bp_test_utils::{
 		authority_list, make_default_justification, make_justification_for_header, JustificationGeneratorParams, ALICE,
 		BOB,
@@ -623,7 +1143,7 @@ mod tests {
 			header: genesis,
 			authority_list: authority_list(),
 			set_id: 1,
-			is_halted: false,
+			operating_mode: OperatingMode::Normal,
 		};
 
 		Pallet::<TestRuntime>::initialize(origin, init_data.clone()).map(|_| init_data)
@@ -644,6 +1164,30 @@ mod tests {
 		let _ = Pallet::<TestRuntime>::on_initialize(current_number);
 	}
 
+	/// Import chain of `len` headers on top of the initial header. Only the last header of the
+	/// chain is imported, all other headers are returned (in reverse order) along with it.
+	fn import_chain_top(len: u8) -> (TestHeader, Vec<TestHeader>) {
+		let mut ancestors = vec![test_header(0)];
+		for number in 1..len {
+			let mut header = test_header(number.into());
+			header.set_parent_hash(ancestors.last().unwrap().hash());
+			ancestors.push(header);
+		}
+
+		let mut chain_top = test_header(len.into());
+		chain_top.set_parent_hash(ancestors.last().unwrap().hash());
+		let justification = make_default_justification(&chain_top);
+		assert_ok!(Pallet::<TestRuntime>::submit_finality_proof(
+			Origin::signed(1),
+			chain_top.clone(),
+			justification,
+		));
+
+		ancestors.remove(0);
+		ancestors.reverse();
+		(chain_top, ancestors)
+	}
+
 	fn change_log(delay: u64) -> Digest<TestHash> {
 		let consensus_log = ConsensusLog::<TestNumber>::ScheduledChange(sp_finality_grandpa::ScheduledChange {
 			next_authorities: vec![(ALICE.into(), 1), (BOB.into(), 1)],
@@ -699,7 +1243,7 @@ mod tests {
 				CurrentAuthoritySet::<TestRuntime>::get().authorities,
 				init_data.authority_list
 			);
-			assert!(!IsHalted::<TestRuntime>::get());
+			assert_eq!(PalletOperatingMode::<TestRuntime>::get(), OperatingMode::Normal);
 		})
 	}
 
@@ -721,29 +1265,55 @@ mod tests {
 
 			assert_ok!(Pallet::<TestRuntime>::set_owner(Origin::root(), Some(1)));
 			assert_noop!(
-				Pallet::<TestRuntime>::set_operational(Origin::signed(2), false),
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(2), OperatingMode::Halted),
 				DispatchError::BadOrigin,
 			);
-			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::root(), false));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Halted
+			));
 
 			assert_ok!(Pallet::<TestRuntime>::set_owner(Origin::signed(1), None));
 			assert_noop!(
-				Pallet::<TestRuntime>::set_operational(Origin::signed(1), true),
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
 				DispatchError::BadOrigin,
 			);
 			assert_noop!(
-				Pallet::<TestRuntime>::set_operational(Origin::signed(2), true),
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(2), OperatingMode::Normal),
 				DispatchError::BadOrigin,
 			);
-			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::root(), true));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Normal
+			));
 		});
 	}
 
 	#[test]
 	fn pallet_may_be_halted_by_root() {
 		run_test(|| {
-			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::root(), false));
-			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::root(), true));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Halted
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Normal
+			));
+		});
+	}
+
+	#[test]
+	fn pallet_may_be_halted_by_owner_origin() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(TEST_OWNER_ORIGIN_ACCOUNT),
+				OperatingMode::Halted
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(TEST_OWNER_ORIGIN_ACCOUNT),
+				OperatingMode::Normal
+			));
 		});
 	}
 
@@ -752,35 +1322,89 @@ mod tests {
 		run_test(|| {
 			PalletOwner::<TestRuntime>::put(2);
 
-			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::signed(2), false));
-			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::signed(2), true));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(2),
+				OperatingMode::Halted
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(2),
+				OperatingMode::Normal
+			));
 
 			assert_noop!(
-				Pallet::<TestRuntime>::set_operational(Origin::signed(1), false),
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Halted),
 				DispatchError::BadOrigin,
 			);
 			assert_noop!(
-				Pallet::<TestRuntime>::set_operational(Origin::signed(1), true),
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
 				DispatchError::BadOrigin,
 			);
 
-			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::signed(2), false));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(2),
+				OperatingMode::Halted
+			));
 			assert_noop!(
-				Pallet::<TestRuntime>::set_operational(Origin::signed(1), true),
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
 				DispatchError::BadOrigin,
 			);
 		});
 	}
 
+	#[test]
+	fn finality_pallet_state_works() {
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::finality_pallet_state(), None);
+
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(1));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Halted
+			));
+
+			let state = Pallet::<TestRuntime>::finality_pallet_state().unwrap();
+			assert_eq!(state.best_finalized, (1, test_header(1).hash()));
+			assert_eq!(state.authority_set_id, 1);
+			assert_eq!(state.operating_mode, OperatingMode::Halted);
+			assert!(state.is_halted());
+		})
+	}
+
 	#[test]
 	fn pallet_rejects_transactions_if_halted() {
 		run_test(|| {
-			<IsHalted<TestRuntime>>::put(true);
+			<PalletOperatingMode<TestRuntime>>::put(OperatingMode::Halted);
 
 			assert_noop!(submit_finality_proof(1), Error::<TestRuntime>::Halted,);
 		})
 	}
 
+	#[test]
+	fn pallet_rejects_non_mandatory_headers_if_only_accepting_mandatory_headers() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::RejectingNonMandatoryHeaders
+			));
+
+			assert_noop!(submit_finality_proof(1), Error::<TestRuntime>::NonMandatoryHeader);
+
+			// header that schedules authorities set change is still accepted
+			let mut header = test_header(2);
+			header.digest = change_log(0);
+			let justification = make_default_justification(&header);
+			assert_ok!(Pallet::<TestRuntime>::submit_finality_proof(
+				Origin::signed(1),
+				header.clone(),
+				justification
+			));
+			assert_eq!(<BestFinalized<TestRuntime>>::get(), header.hash());
+			assert_eq!(<CurrentAuthoritySet<TestRuntime>>::get().set_id, 2);
+		})
+	}
+
 	#[test]
 	fn pallet_rejects_header_if_not_initialized_yet() {
 		run_test(|| {
@@ -846,7 +1470,7 @@ mod tests {
 				header: genesis,
 				authority_list: invalid_authority_list,
 				set_id: 1,
-				is_halted: false,
+				operating_mode: OperatingMode::Normal,
 			};
 
 			assert_ok!(Pallet::<TestRuntime>::initialize(Origin::root(), init_data));
@@ -949,6 +1573,54 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn try_state_succeeds_on_uninitialized_pallet() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::do_try_state());
+		})
+	}
+
+	#[test]
+	fn try_state_succeeds_after_old_headers_are_pruned() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			for header in 1..=7 {
+				assert_ok!(submit_finality_proof(header));
+				next_block();
+			}
+
+			assert_ok!(Pallet::<TestRuntime>::do_try_state());
+		})
+	}
+
+	#[test]
+	fn try_state_detects_missing_best_finalized_header() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(1));
+
+			<ImportedHeaders<TestRuntime>>::remove(test_header(1).hash());
+			assert_eq!(
+				Pallet::<TestRuntime>::do_try_state(),
+				Err("Best finalized header is missing from the storage"),
+			);
+		})
+	}
+
+	#[test]
+	fn try_state_detects_header_stored_under_wrong_hash() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(1));
+
+			<ImportedHeaders<TestRuntime>>::insert(test_header(0).hash(), test_header(1));
+			assert_eq!(
+				Pallet::<TestRuntime>::do_try_state(),
+				Err("Imported header is stored under wrong hash"),
+			);
+		})
+	}
+
 	#[test]
 	fn parse_finalized_storage_proof_rejects_proof_on_unknown_header() {
 		run_test(|| {
@@ -1084,4 +1756,338 @@ mod tests {
 			);
 		})
 	}
+
+	#[test]
+	fn authorities_may_be_forcibly_changed_by_owner_or_root() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let new_authorities = vec![(ALICE.into(), 1), (BOB.into(), 1)];
+
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(Origin::signed(1), new_authorities.clone(), 2),
+				DispatchError::BadOrigin,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::force_set_authorities(
+				Origin::root(),
+				new_authorities.clone(),
+				2
+			));
+			assert_eq!(
+				CurrentAuthoritySet::<TestRuntime>::get(),
+				bp_header_chain::AuthoritySet::new(new_authorities.clone(), 2),
+			);
+
+			PalletOwner::<TestRuntime>::put(2);
+			assert_ok!(Pallet::<TestRuntime>::force_set_authorities(
+				Origin::signed(2),
+				new_authorities.clone(),
+				3
+			));
+			assert_eq!(
+				CurrentAuthoritySet::<TestRuntime>::get(),
+				bp_header_chain::AuthoritySet::new(new_authorities, 3),
+			);
+		})
+	}
+
+	#[test]
+	fn force_set_authorities_performs_sanity_checks() {
+		run_test(|| {
+			let new_authorities = vec![(ALICE.into(), 1), (BOB.into(), 1)];
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(Origin::root(), new_authorities.clone(), 2),
+				Error::<TestRuntime>::NotInitialized,
+			);
+
+			initialize_substrate_bridge();
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(Origin::root(), new_authorities.clone(), 1),
+				Error::<TestRuntime>::InvalidAuthoritySetId,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(Origin::root(), vec![], 2),
+				Error::<TestRuntime>::InvalidAuthoritySet,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(Origin::root(), vec![(ALICE.into(), 0)], 2),
+				Error::<TestRuntime>::InvalidAuthoritySet,
+			);
+		})
+	}
+
+	#[test]
+	fn imports_header_with_valid_ancestry_proof() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let (chain_top, ancestors) = import_chain_top(4);
+
+			assert_ok!(Pallet::<TestRuntime>::submit_ancestry_proof(
+				Origin::signed(1),
+				chain_top.hash(),
+				ancestors[..2].to_vec(),
+			));
+
+			assert_eq!(Pallet::<TestRuntime>::best_finalized(), chain_top);
+			assert!(Pallet::<TestRuntime>::is_known_header(ancestors[1].hash()));
+			assert!(!Pallet::<TestRuntime>::is_known_header(ancestors[0].hash()));
+			assert!(!Pallet::<TestRuntime>::is_known_header(ancestors[2].hash()));
+			assert_ok!(Pallet::<TestRuntime>::do_try_state());
+		})
+	}
+
+	#[test]
+	fn ancestry_proofs_never_evict_headers_imported_with_justifications() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let (chain_top, ancestors) = import_chain_top(20);
+
+			for i in 0..ancestors.len() {
+				next_block();
+				assert_ok!(Pallet::<TestRuntime>::submit_ancestry_proof(
+					Origin::signed(1),
+					chain_top.hash(),
+					ancestors[..=i].to_vec(),
+				));
+			}
+
+			// headers, imported with justifications, are still there
+			assert!(Pallet::<TestRuntime>::is_known_header(test_header(0).hash()));
+			assert!(Pallet::<TestRuntime>::is_known_header(chain_top.hash()));
+			// and only latest `HeadersToKeep` ancestors are kept
+			let (pruned_ancestors, kept_ancestors) = ancestors.split_at(ancestors.len() - 5);
+			assert!(pruned_ancestors
+				.iter()
+				.all(|ancestor| !Pallet::<TestRuntime>::is_known_header(ancestor.hash())));
+			assert!(kept_ancestors
+				.iter()
+				.all(|ancestor| Pallet::<TestRuntime>::is_known_header(ancestor.hash())));
+			assert_ok!(Pallet::<TestRuntime>::do_try_state());
+		})
+	}
+
+	#[test]
+	fn rejects_invalid_ancestry_proof() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let (chain_top, ancestors) = import_chain_top(4);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_ancestry_proof(Origin::signed(1), chain_top.hash(), vec![]),
+				Error::<TestRuntime>::InvalidAncestryProof,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_ancestry_proof(
+					Origin::signed(1),
+					chain_top.hash(),
+					vec![ancestors[1].clone(), ancestors[0].clone()],
+				),
+				Error::<TestRuntime>::InvalidAncestryProof,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_ancestry_proof(
+					Origin::signed(1),
+					ancestors[0].hash(),
+					vec![ancestors[1].clone()],
+				),
+				Error::<TestRuntime>::UnknownHeader,
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_ancestry_proof_of_already_imported_header() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let (chain_top, ancestors) = import_chain_top(2);
+
+			// the initial header is already imported
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_ancestry_proof(
+					Origin::signed(1),
+					chain_top.hash(),
+					vec![ancestors[0].clone(), test_header(0)],
+				),
+				Error::<TestRuntime>::HeaderAlreadyImported,
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_ancestry_proof_if_only_accepting_mandatory_headers() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			let (chain_top, ancestors) = import_chain_top(4);
+
+			PalletOperatingMode::<TestRuntime>::put(OperatingMode::RejectingNonMandatoryHeaders);
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_ancestry_proof(Origin::signed(1), chain_top.hash(), ancestors),
+				Error::<TestRuntime>::NonMandatoryHeader,
+			);
+		})
+	}
+
+	#[test]
+	fn headers_retention_window_may_be_changed_by_owner_or_root() {
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::headers_to_keep(), 5);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::set_headers_retention_window(Origin::signed(1), Some(2)),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_headers_retention_window(Origin::root(), Some(0)),
+				Error::<TestRuntime>::InvalidRetentionWindow,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::set_headers_retention_window(
+				Origin::root(),
+				Some(2)
+			));
+			assert_eq!(Pallet::<TestRuntime>::headers_to_keep(), 2);
+
+			PalletOwner::<TestRuntime>::put(2);
+			assert_ok!(Pallet::<TestRuntime>::set_headers_retention_window(
+				Origin::signed(2),
+				None
+			));
+			assert_eq!(Pallet::<TestRuntime>::headers_to_keep(), 5);
+		})
+	}
+
+	#[test]
+	fn prune_headers_prunes_headers_outside_of_decreased_retention_window() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			for header in 1..=4 {
+				assert_ok!(submit_finality_proof(header));
+				next_block();
+			}
+
+			assert_ok!(Pallet::<TestRuntime>::set_headers_retention_window(
+				Origin::root(),
+				Some(2)
+			));
+			assert_ok!(
+				Pallet::<TestRuntime>::prune_headers(Origin::signed(1), 10),
+				PostDispatchInfo {
+					actual_weight: Some(<TestRuntime as Config>::WeightInfo::prune_headers(3)),
+					pays_fee: frame_support::weights::Pays::Yes,
+				},
+			);
+
+			for header in 0..=2 {
+				assert!(!Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+			for header in 3..=4 {
+				assert!(Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+			assert_ok!(Pallet::<TestRuntime>::do_try_state());
+		})
+	}
+
+	#[test]
+	fn headers_required_by_pruning_guard_are_not_pruned() {
+		run_test(|| {
+			TestHeadersPruningGuard::set_oldest_required_header(Some(1));
+			initialize_substrate_bridge();
+			for header in 1..=7 {
+				assert_ok!(submit_finality_proof(header));
+				next_block();
+			}
+
+			// header#0 is pruned, but header#1 and all its descendants are kept
+			assert!(!Pallet::<TestRuntime>::is_known_header(test_header(0).hash()));
+			for header in 1..=7 {
+				assert!(Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+
+			// once the guard releases headers, they may be pruned
+			TestHeadersPruningGuard::set_oldest_required_header(None);
+			assert_ok!(Pallet::<TestRuntime>::prune_headers(Origin::signed(1), 10));
+			for header in 1..=2 {
+				assert!(!Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+			for header in 3..=7 {
+				assert!(Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+			assert_ok!(Pallet::<TestRuntime>::do_try_state());
+		})
+	}
+
+	#[test]
+	fn pruning_guard_keeps_at_most_max_guarded_headers() {
+		run_test(|| {
+			TestHeadersPruningGuard::set_oldest_required_header(Some(1));
+			initialize_substrate_bridge();
+			for header in 1..=12 {
+				assert_ok!(submit_finality_proof(header));
+				next_block();
+			}
+
+			// at most `HeadersToKeep + MaxGuardedHeaders` headers are kept
+			for header in 0..=4 {
+				assert!(!Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+			for header in 5..=12 {
+				assert!(Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+
+			// once the guard releases headers, every import prunes two headers until the number
+			// of stored headers is back to the retention window
+			TestHeadersPruningGuard::set_oldest_required_header(None);
+			for header in 13..=15 {
+				assert_ok!(submit_finality_proof(header));
+				next_block();
+			}
+			for header in 0..=10 {
+				assert!(!Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+			for header in 11..=15 {
+				assert!(Pallet::<TestRuntime>::is_known_header(test_header(header).hash()));
+			}
+			assert_ok!(Pallet::<TestRuntime>::do_try_state());
+		})
+	}
+
+	#[test]
+	fn operating_mode_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage and relayers
+		// are no longer able to read pallet operating mode.
+		assert_eq!(
+			storage_keys::operating_mode_key::<TestRuntime, ()>().0,
+			hex_literal::hex!("5f9cc45b7a00c5899361e1c6099678dc0f4cf0917788d791142ff6c1f216e7b3").to_vec(),
+		);
+	}
+
+	#[test]
+	fn best_finalized_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage and relayers
+		// are no longer able to check whether the pallet is initialized.
+		assert_eq!(
+			storage_keys::best_finalized_key::<TestRuntime, ()>().0,
+			hex_literal::hex!("5f9cc45b7a00c5899361e1c6099678dca4ebafdd473c549fdb24c5c991c5591c").to_vec(),
+		);
+	}
+
+	#[test]
+	fn current_authority_set_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage and relayers
+		// are no longer able to optimize justifications before submission.
+		assert_eq!(
+			storage_keys::current_authority_set_key::<TestRuntime, ()>().0,
+			hex_literal::hex!("5f9cc45b7a00c5899361e1c6099678dc24a7b8b5717ea33346fa595a66ccbcb0").to_vec(),
+		);
+	}
+
+	#[test]
+	fn pallet_prefix_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage and relayers
+		// are no longer able to read pallet storage.
+		assert_eq!(
+			storage_keys::pallet_prefix_key::<TestRuntime, ()>().0,
+			hex_literal::hex!("5f9cc45b7a00c5899361e1c6099678dc").to_vec(),
+		);
+	}
 }
@@ -0,0 +1,210 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Migrations of the bridge GRANDPA pallet storage.
+
+use crate::{
+	storage_keys, Config, ImportedHashes, ImportedHashesPointer, InitialHash, OldestImportedHashPointer,
+	PalletOperatingMode,
+};
+
+use bp_header_chain::OperatingMode;
+use frame_support::{storage::unhashed, traits::Get, weights::Weight, StorageHasher, Twox128};
+use sp_std::vec::Vec;
+
+/// Name of the storage item with the pallet halt flag. It has been replaced with the
+/// `PalletOperatingMode` storage item.
+const IS_HALTED_STORAGE_NAME: &[u8] = b"IsHalted";
+
+/// Upgrade the pallet storage to the latest version.
+///
+/// Does nothing if the storage is already at the latest version.
+pub fn migrate<T: Config<I>, I: 'static>() -> Weight {
+	migrate_halt_flag::<T, I>().saturating_add(migrate_imported_hashes::<T, I>())
+}
+
+/// Replace the pallet halt flag with the pallet operating mode.
+fn migrate_halt_flag<T: Config<I>, I: 'static>() -> Weight {
+	let is_halted = match unhashed::take::<bool>(&is_halted_key::<T, I>()) {
+		Some(is_halted) => is_halted,
+		None => return T::DbWeight::get().reads(1),
+	};
+
+	let operating_mode = if is_halted {
+		OperatingMode::Halted
+	} else {
+		OperatingMode::Normal
+	};
+	PalletOperatingMode::<T, I>::put(operating_mode);
+
+	log::info!(
+		target: "runtime::bridge-grandpa",
+		"Pallet halt flag has been replaced with the {:?} operating mode",
+		operating_mode,
+	);
+
+	T::DbWeight::get().reads_writes(1, 2)
+}
+
+/// Convert the ring buffer of imported hashes into the queue.
+///
+/// In the ring buffer, hashes are stored at `[0; T::HeadersToKeep)` indices and the
+/// `ImportedHashesPointer` points to the oldest hash (if the buffer is full). In the queue, hashes
+/// are stored at `[OldestImportedHashPointer; ImportedHashesPointer)` indices. So if the buffer is
+/// full, we move hashes at `[0; ImportedHashesPointer)` to the end of the queue.
+fn migrate_imported_hashes<T: Config<I>, I: 'static>() -> Weight {
+	if <OldestImportedHashPointer<T, I>>::exists() {
+		return T::DbWeight::get().reads(1);
+	}
+	if !<InitialHash<T, I>>::exists() {
+		return T::DbWeight::get().reads(2);
+	}
+
+	let headers_to_keep = T::HeadersToKeep::get();
+	let ring_buffer_pointer = <ImportedHashesPointer<T, I>>::get();
+	let is_ring_buffer_full = <ImportedHashes<T, I>>::contains_key(ring_buffer_pointer);
+	let (oldest_index, next_index) = if is_ring_buffer_full {
+		for index in 0..ring_buffer_pointer {
+			if let Some(hash) = <ImportedHashes<T, I>>::take(index) {
+				<ImportedHashes<T, I>>::insert(headers_to_keep + index, hash);
+			}
+		}
+		(ring_buffer_pointer, headers_to_keep + ring_buffer_pointer)
+	} else {
+		(0, ring_buffer_pointer)
+	};
+	<OldestImportedHashPointer<T, I>>::put(oldest_index);
+	<ImportedHashesPointer<T, I>>::put(next_index);
+
+	log::info!(
+		target: "runtime::bridge-grandpa",
+		"Ring buffer of imported hashes has been converted into the queue: [{}; {})",
+		oldest_index,
+		next_index,
+	);
+
+	let moved_hashes = if is_ring_buffer_full {
+		ring_buffer_pointer as u64
+	} else {
+		0
+	};
+	T::DbWeight::get().reads_writes(4 + moved_hashes, 2 + 2 * moved_hashes)
+}
+
+/// Storage key of the pallet halt flag.
+fn is_halted_key<T: Config<I>, I: 'static>() -> Vec<u8> {
+	let mut key = storage_keys::pallet_prefix_key::<T, I>().0;
+	key.extend_from_slice(&Twox128::hash(IS_HALTED_STORAGE_NAME));
+	key
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{run_test, TestHash, TestRuntime};
+	use sp_std::collections::btree_map::BTreeMap;
+
+	#[test]
+	fn is_halted_key_computed_properly() {
+		assert_eq!(
+			is_halted_key::<TestRuntime, ()>(),
+			hex_literal::hex!("5f9cc45b7a00c5899361e1c6099678dc9611a984bbd04e2fd39f97bbc006115f").to_vec(),
+		);
+	}
+
+	#[test]
+	fn migrates_halt_flag_to_operating_mode() {
+		run_test(|| {
+			unhashed::put(&is_halted_key::<TestRuntime, ()>(), &true);
+
+			migrate::<TestRuntime, ()>();
+
+			assert_eq!(PalletOperatingMode::<TestRuntime>::get(), OperatingMode::Halted);
+			assert_eq!(unhashed::get_raw(&is_halted_key::<TestRuntime, ()>()), None);
+
+			unhashed::put(&is_halted_key::<TestRuntime, ()>(), &false);
+
+			migrate::<TestRuntime, ()>();
+
+			assert_eq!(PalletOperatingMode::<TestRuntime>::get(), OperatingMode::Normal);
+		});
+	}
+
+	#[test]
+	fn does_nothing_if_storage_is_at_latest_version() {
+		run_test(|| {
+			PalletOperatingMode::<TestRuntime>::put(OperatingMode::RejectingNonMandatoryHeaders);
+
+			migrate::<TestRuntime, ()>();
+
+			assert_eq!(
+				PalletOperatingMode::<TestRuntime>::get(),
+				OperatingMode::RejectingNonMandatoryHeaders
+			);
+		});
+	}
+
+	#[test]
+	fn converts_full_ring_buffer_into_queue() {
+		run_test(|| {
+			// ring buffer of 5 hashes, where the oldest hash is at index 2
+			InitialHash::<TestRuntime>::put(TestHash::from([1u8; 32]));
+			for index in 0..5u8 {
+				ImportedHashes::<TestRuntime>::insert(index as u32, TestHash::from([index; 32]));
+			}
+			ImportedHashesPointer::<TestRuntime>::put(2);
+
+			migrate::<TestRuntime, ()>();
+
+			assert_eq!(OldestImportedHashPointer::<TestRuntime>::get(), Some(2));
+			assert_eq!(ImportedHashesPointer::<TestRuntime>::get(), 7);
+			assert_eq!(
+				ImportedHashes::<TestRuntime>::iter().collect::<BTreeMap<_, _>>(),
+				vec![
+					(2, TestHash::from([2u8; 32])),
+					(3, TestHash::from([3u8; 32])),
+					(4, TestHash::from([4u8; 32])),
+					(5, TestHash::from([0u8; 32])),
+					(6, TestHash::from([1u8; 32])),
+				]
+				.into_iter()
+				.collect(),
+			);
+		});
+	}
+
+	#[test]
+	fn converts_partially_filled_ring_buffer_into_queue() {
+		run_test(|| {
+			InitialHash::<TestRuntime>::put(TestHash::from([1u8; 32]));
+			for index in 0..3u8 {
+				ImportedHashes::<TestRuntime>::insert(index as u32, TestHash::from([index; 32]));
+			}
+			ImportedHashesPointer::<TestRuntime>::put(3);
+
+			migrate::<TestRuntime, ()>();
+
+			assert_eq!(OldestImportedHashPointer::<TestRuntime>::get(), Some(0));
+			assert_eq!(ImportedHashesPointer::<TestRuntime>::get(), 3);
+			assert_eq!(ImportedHashes::<TestRuntime>::iter().count(), 3);
+
+			// second migration does nothing
+			migrate::<TestRuntime, ()>();
+			assert_eq!(OldestImportedHashPointer::<TestRuntime>::get(), Some(0));
+			assert_eq!(ImportedHashesPointer::<TestRuntime>::get(), 3);
+		});
+	}
+}
@@ -17,13 +17,15 @@
 // From construct_runtime macro
 #![allow(clippy::from_over_into)]
 
+use bp_header_chain::HeadersPruningGuard;
 use bp_runtime::Chain;
-use frame_support::{construct_runtime, parameter_types, weights::Weight};
+use frame_support::{construct_runtime, ord_parameter_types, parameter_types, weights::Weight};
 use sp_runtime::{
 	testing::{Header, H256},
 	traits::{BlakeTwo256, IdentityLookup},
 	Perbill,
 };
+use std::cell::RefCell;
 
 pub type AccountId = u64;
 pub type TestHeader = crate::BridgedHeader<TestRuntime, ()>;
@@ -82,17 +84,48 @@ impl frame_system::Config for TestRuntime {
 parameter_types! {
 	pub const MaxRequests: u32 = 2;
 	pub const HeadersToKeep: u32 = 5;
+	pub const MaxGuardedHeaders: u32 = 3;
 	pub const SessionLength: u64 = 5;
 	pub const NumValidators: u32 = 5;
 }
 
+/// Account that is accepted by the `OwnerOrigin`.
+pub const TEST_OWNER_ORIGIN_ACCOUNT: AccountId = 300;
+
+ord_parameter_types! {
+	pub const OwnerOriginAccount: AccountId = TEST_OWNER_ORIGIN_ACCOUNT;
+}
+
 impl grandpa::Config for TestRuntime {
 	type BridgedChain = TestBridgedChain;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
+	type HeadersPruningGuard = TestHeadersPruningGuard;
+	type MaxGuardedHeaders = MaxGuardedHeaders;
+	type OwnerOrigin = frame_system::EnsureSignedBy<OwnerOriginAccount, AccountId>;
 	type WeightInfo = ();
 }
 
+thread_local! {
+	static OLDEST_REQUIRED_HEADER: RefCell<Option<TestNumber>> = RefCell::new(None);
+}
+
+/// Headers pruning guard that is used in tests.
+pub struct TestHeadersPruningGuard;
+
+impl TestHeadersPruningGuard {
+	/// Set number of the oldest header that must be kept in the storage.
+	pub fn set_oldest_required_header(number: Option<TestNumber>) {
+		OLDEST_REQUIRED_HEADER.with(|oldest_required_header| *oldest_required_header.borrow_mut() = number);
+	}
+}
+
+impl HeadersPruningGuard<TestNumber> for TestHeadersPruningGuard {
+	fn oldest_required_header() -> Option<TestNumber> {
+		OLDEST_REQUIRED_HEADER.with(|oldest_required_header| *oldest_required_header.borrow())
+	}
+}
+
 #[derive(Debug)]
 pub struct TestBridgedChain;
 
@@ -104,6 +137,7 @@ impl Chain for TestBridgedChain {
 }
 
 pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
+	TestHeadersPruningGuard::set_oldest_required_header(None);
 	sp_io::TestExternalities::new(Default::default()).execute_with(test)
 }
 
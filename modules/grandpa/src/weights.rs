@@ -49,6 +49,8 @@ use sp_std::marker::PhantomData;
 /// Weight functions needed for pallet_bridge_grandpa.
 pub trait WeightInfo {
 	fn submit_finality_proof(p: u32, v: u32) -> Weight;
+	fn prune_headers(n: u32) -> Weight;
+	fn submit_ancestry_proof(a: u32) -> Weight;
 }
 
 /// Weights for pallet_bridge_grandpa using the Rialto node and recommended hardware.
@@ -61,6 +63,20 @@ impl<T: frame_system::Config> WeightInfo for RialtoWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(7 as Weight))
 			.saturating_add(T::DbWeight::get().writes(6 as Weight))
 	}
+	fn prune_headers(n: u32) -> Weight {
+		(21_316_000 as Weight)
+			.saturating_add((8_247_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+	}
+	fn submit_ancestry_proof(a: u32) -> Weight {
+		(39_104_000 as Weight)
+			.saturating_add((2_613_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
 }
 
 // For backwards compatibility and tests
@@ -72,4 +88,18 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
 	}
+	fn prune_headers(n: u32) -> Weight {
+		(21_316_000 as Weight)
+			.saturating_add((8_247_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+	}
+	fn submit_ancestry_proof(a: u32) -> Weight {
+		(39_104_000 as Weight)
+			.saturating_add((2_613_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
 }
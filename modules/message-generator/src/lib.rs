@@ -0,0 +1,461 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bridge Message Generator Pallet
+//!
+//! This pallet is meant to be used in test (Millau and Rialto) runtimes only. Once started, it
+//! sends a configured number of outbound messages at the beginning of every block, using the
+//! `MessagesBridge` of the messages pallet. Payload sizes and declared dispatch weights of
+//! generated messages vary within configured ranges, but are derived from the number of messages
+//! that have already been generated. So the same sequence of `start` calls always leads to the
+//! same sequence of messages, which allows reproducible load testing of the relay and of the
+//! messages pallet weight limits.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use bp_messages::{source_chain::MessagesBridge, LaneId};
+use codec::{Decode, Encode};
+use frame_support::{weights::Weight, RuntimeDebug};
+use sp_std::convert::TryInto;
+
+#[cfg(test)]
+mod mock;
+
+// Re-export in crate namespace for `construct_runtime!`
+pub use pallet::*;
+
+/// Message generator settings.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct GeneratorSettings<Fee> {
+	/// Lane to send messages over.
+	pub lane: LaneId,
+	/// Number of messages that are sent at every block.
+	pub messages_per_block: u32,
+	/// Minimal size of generated message payload.
+	pub min_payload_size: u32,
+	/// Maximal size of generated message payload.
+	pub max_payload_size: u32,
+	/// Minimal dispatch weight, declared by generated messages.
+	pub min_dispatch_weight: Weight,
+	/// Maximal dispatch weight, declared by generated messages.
+	pub max_dispatch_weight: Weight,
+	/// Delivery and dispatch fee that is paid for every generated message.
+	pub fee: Fee,
+	/// Number of blocks during which generator will be active. If `None`, generator works until
+	/// it is stopped explicitly.
+	pub remaining_blocks: Option<u32>,
+}
+
+/// Builder of generated message payloads.
+pub trait MessagePayloadBuilder<Payload> {
+	/// Build message payload with given encoded size (approximately) and declared dispatch weight.
+	fn build_payload(payload_size: u32, dispatch_weight: Weight) -> Payload;
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+		/// Type of generated outbound message payloads.
+		type OutboundPayload: Parameter;
+		/// Type of fee that is paid for every generated message.
+		type OutboundMessageFee: Parameter + Copy;
+		/// Messages bridge that is used to send generated messages.
+		type MessagesBridge: MessagesBridge<Self::AccountId, Self::OutboundMessageFee, Self::OutboundPayload>;
+		/// Generated messages payload builder.
+		type PayloadBuilder: MessagePayloadBuilder<Self::OutboundPayload>;
+
+		/// Maximal number of messages that may be generated at every block.
+		#[pallet::constant]
+		type MaxMessagesPerBlock: Get<u32>;
+		/// Maximal size of generated message payload.
+		#[pallet::constant]
+		type MaxPayloadSize: Get<u32>;
+		/// Weight of single `MessagesBridge::send_message_from_pallet` call.
+		#[pallet::constant]
+		type SendMessageWeight: Get<Weight>;
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			let mut settings = match Settings::<T>::get() {
+				Some(settings) => settings,
+				None => return T::DbWeight::get().reads(1),
+			};
+
+			let mut generated_messages = GeneratedMessages::<T>::get();
+			let mut sent_messages = 0;
+			for _ in 0..settings.messages_per_block {
+				let payload_size = select_in_range(
+					generated_messages,
+					b"size",
+					settings.min_payload_size.into(),
+					settings.max_payload_size.into(),
+				) as u32;
+				let dispatch_weight = select_in_range(
+					generated_messages,
+					b"weight",
+					settings.min_dispatch_weight,
+					settings.max_dispatch_weight,
+				);
+				let payload = T::PayloadBuilder::build_payload(payload_size, dispatch_weight);
+				match T::MessagesBridge::send_message_from_pallet(settings.lane, payload, settings.fee) {
+					Ok(_) => {
+						generated_messages = generated_messages.wrapping_add(1);
+						sent_messages += 1;
+					}
+					Err(error) => {
+						log::trace!(
+							target: "runtime::bridge-message-generator",
+							"Failed to send generated message over lane {:?}: {:?}",
+							settings.lane,
+							error,
+						);
+						break;
+					}
+				}
+			}
+
+			GeneratedMessages::<T>::put(generated_messages);
+			Self::deposit_event(Event::MessagesGenerated(settings.lane, sent_messages));
+
+			match settings.remaining_blocks {
+				Some(remaining_blocks) if remaining_blocks <= 1 => {
+					Settings::<T>::kill();
+					Self::deposit_event(Event::GeneratorStopped);
+				}
+				Some(remaining_blocks) => {
+					settings.remaining_blocks = Some(remaining_blocks - 1);
+					Settings::<T>::put(&settings);
+				}
+				None => (),
+			}
+
+			T::DbWeight::get()
+				.reads_writes(2, 2)
+				.saturating_add(T::SendMessageWeight::get().saturating_mul(settings.messages_per_block.into()))
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Start (or restart with new settings) message generator.
+		///
+		/// May only be called by root.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn start(
+			origin: OriginFor<T>,
+			settings: GeneratorSettings<T::OutboundMessageFee>,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(
+				settings.messages_per_block != 0 && settings.messages_per_block <= T::MaxMessagesPerBlock::get(),
+				Error::<T>::InvalidMessagesPerBlock,
+			);
+			ensure!(
+				settings.min_payload_size <= settings.max_payload_size
+					&& settings.max_payload_size <= T::MaxPayloadSize::get(),
+				Error::<T>::InvalidPayloadSize,
+			);
+			ensure!(
+				settings.min_dispatch_weight <= settings.max_dispatch_weight,
+				Error::<T>::InvalidDispatchWeight,
+			);
+			ensure!(settings.remaining_blocks != Some(0), Error::<T>::InvalidRemainingBlocks);
+
+			log::info!(
+				target: "runtime::bridge-message-generator",
+				"Starting message generator with settings: {:?}",
+				settings,
+			);
+
+			Settings::<T>::put(settings);
+			Self::deposit_event(Event::GeneratorStarted);
+
+			Ok(().into())
+		}
+
+		/// Stop message generator.
+		///
+		/// May only be called by root.
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn stop(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			ensure!(Settings::<T>::exists(), Error::<T>::NotStarted);
+
+			log::info!(target: "runtime::bridge-message-generator", "Stopping message generator");
+
+			Settings::<T>::kill();
+			Self::deposit_event(Event::GeneratorStopped);
+
+			Ok(().into())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		/// Message generator has been started.
+		GeneratorStarted,
+		/// Message generator has been stopped.
+		GeneratorStopped,
+		/// Given number of messages has been generated and sent over the lane.
+		MessagesGenerated(LaneId, u32),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Number of messages per block is zero or exceeds `MaxMessagesPerBlock`.
+		InvalidMessagesPerBlock,
+		/// Payload size range is empty or exceeds `MaxPayloadSize`.
+		InvalidPayloadSize,
+		/// Dispatch weight range is empty.
+		InvalidDispatchWeight,
+		/// Generator is asked to be active during zero blocks.
+		InvalidRemainingBlocks,
+		/// Generator is not started.
+		NotStarted,
+	}
+
+	/// Active generator settings. If `None`, generator is stopped.
+	#[pallet::storage]
+	#[pallet::getter(fn settings)]
+	pub type Settings<T: Config> = StorageValue<_, GeneratorSettings<T::OutboundMessageFee>, OptionQuery>;
+
+	/// Total number of messages that have been generated by this pallet. It is used as a seed
+	/// when selecting size and weight of the next message.
+	#[pallet::storage]
+	#[pallet::getter(fn generated_messages)]
+	pub type GeneratedMessages<T: Config> = StorageValue<_, u64, ValueQuery>;
+}
+
+/// Deterministically select value from the `[min; max]` range, using message index as a seed.
+fn select_in_range(message_index: u64, salt: &[u8], min: u64, max: u64) -> u64 {
+	let range = max - min;
+	if range == 0 {
+		return min;
+	}
+
+	let hash = sp_io::hashing::blake2_256(&(message_index, salt).encode());
+	let random = u64::from_le_bytes(hash[..8].try_into().expect("hash has 32 bytes; qed"));
+	min + random % range.saturating_add(1)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{run_test, sent_messages, Origin, TestRuntime, FAILING_LANE, TEST_LANE};
+	use frame_support::{assert_noop, assert_ok, traits::Hooks};
+	use sp_runtime::DispatchError;
+
+	fn settings() -> GeneratorSettings<u64> {
+		GeneratorSettings {
+			lane: TEST_LANE,
+			messages_per_block: 3,
+			min_payload_size: 10,
+			max_payload_size: 100,
+			min_dispatch_weight: 1_000,
+			max_dispatch_weight: 2_000,
+			fee: 42,
+			remaining_blocks: None,
+		}
+	}
+
+	fn next_block() {
+		let number = frame_system::Pallet::<TestRuntime>::block_number() + 1;
+		frame_system::Pallet::<TestRuntime>::set_block_number(number);
+		Pallet::<TestRuntime>::on_initialize(number);
+	}
+
+	#[test]
+	fn start_and_stop_are_root_only() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::start(Origin::signed(1), settings()),
+				DispatchError::BadOrigin
+			);
+			assert_noop!(Pallet::<TestRuntime>::stop(Origin::signed(1)), DispatchError::BadOrigin);
+		});
+	}
+
+	#[test]
+	fn start_rejects_invalid_settings() {
+		run_test(|| {
+			let check = |settings, error: Error<TestRuntime>| {
+				assert_noop!(Pallet::<TestRuntime>::start(Origin::root(), settings), error);
+			};
+
+			check(
+				GeneratorSettings {
+					messages_per_block: 0,
+					..settings()
+				},
+				Error::InvalidMessagesPerBlock,
+			);
+			check(
+				GeneratorSettings {
+					messages_per_block: 1_000,
+					..settings()
+				},
+				Error::InvalidMessagesPerBlock,
+			);
+			check(
+				GeneratorSettings {
+					min_payload_size: 101,
+					..settings()
+				},
+				Error::InvalidPayloadSize,
+			);
+			check(
+				GeneratorSettings {
+					max_payload_size: 1_000_000,
+					..settings()
+				},
+				Error::InvalidPayloadSize,
+			);
+			check(
+				GeneratorSettings {
+					min_dispatch_weight: 2_001,
+					..settings()
+				},
+				Error::InvalidDispatchWeight,
+			);
+			check(
+				GeneratorSettings {
+					remaining_blocks: Some(0),
+					..settings()
+				},
+				Error::InvalidRemainingBlocks,
+			);
+		});
+	}
+
+	#[test]
+	fn stop_fails_when_generator_is_not_started() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::stop(Origin::root()),
+				Error::<TestRuntime>::NotStarted
+			);
+		});
+	}
+
+	#[test]
+	fn generator_sends_configured_number_of_messages_until_stopped() {
+		run_test(|| {
+			next_block();
+			assert!(sent_messages().is_empty());
+
+			assert_ok!(Pallet::<TestRuntime>::start(Origin::root(), settings()));
+			next_block();
+			next_block();
+
+			let messages = sent_messages();
+			assert_eq!(messages.len(), 6);
+			assert_eq!(Pallet::<TestRuntime>::generated_messages(), 6);
+			for (lane, (payload_size, dispatch_weight), fee) in messages {
+				assert_eq!(lane, TEST_LANE);
+				assert!((10..=100).contains(&payload_size));
+				assert!((1_000..=2_000).contains(&dispatch_weight));
+				assert_eq!(fee, 42);
+			}
+
+			assert_ok!(Pallet::<TestRuntime>::stop(Origin::root()));
+			next_block();
+			assert_eq!(sent_messages().len(), 6);
+		});
+	}
+
+	#[test]
+	fn generator_stops_after_configured_number_of_blocks() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::start(
+				Origin::root(),
+				GeneratorSettings {
+					remaining_blocks: Some(2),
+					..settings()
+				}
+			));
+
+			next_block();
+			assert_eq!(
+				Pallet::<TestRuntime>::settings().and_then(|s| s.remaining_blocks),
+				Some(1)
+			);
+			next_block();
+			assert_eq!(Pallet::<TestRuntime>::settings(), None);
+			next_block();
+			assert_eq!(sent_messages().len(), 6);
+		});
+	}
+
+	#[test]
+	fn generated_messages_are_deterministic() {
+		let generate = || {
+			run_test(|| {
+				assert_ok!(Pallet::<TestRuntime>::start(Origin::root(), settings()));
+				next_block();
+				next_block();
+				sent_messages()
+			})
+		};
+
+		let messages = generate();
+		assert_eq!(messages, generate());
+
+		// sizes and weights are actually varied
+		assert!(messages.iter().any(|(_, payload, _)| *payload != messages[0].1));
+	}
+
+	#[test]
+	fn generator_stops_sending_messages_in_block_when_bridge_rejects_message() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::start(
+				Origin::root(),
+				GeneratorSettings {
+					lane: FAILING_LANE,
+					..settings()
+				}
+			));
+
+			next_block();
+			assert!(sent_messages().is_empty());
+			assert_eq!(Pallet::<TestRuntime>::generated_messages(), 0);
+		});
+	}
+
+	#[test]
+	fn select_in_range_works() {
+		assert_eq!(select_in_range(0, b"size", 10, 10), 10);
+		for index in 0..100 {
+			assert!((10..=20).contains(&select_in_range(index, b"size", 10, 20)));
+		}
+		assert_ne!(
+			select_in_range(0, b"size", 0, u64::MAX),
+			select_in_range(0, b"weight", 0, u64::MAX)
+		);
+	}
+}
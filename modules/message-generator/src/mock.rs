@@ -0,0 +1,140 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+// From construct_runtime macro
+#![allow(clippy::from_over_into)]
+
+use crate::{self as message_generator, MessagePayloadBuilder};
+
+use bp_messages::{source_chain::MessagesBridge, LaneId, MessageNonce};
+use frame_support::{construct_runtime, parameter_types, weights::Weight};
+use sp_runtime::{
+	testing::{Header, H256},
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::cell::RefCell;
+
+pub type AccountId = u64;
+pub type TestPayload = (u32, Weight);
+pub type SentMessage = (LaneId, TestPayload, u64);
+
+type Block = frame_system::mocking::MockBlock<TestRuntime>;
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
+
+/// Lane that is used in tests.
+pub const TEST_LANE: LaneId = [0, 0, 0, 1];
+/// Lane that rejects all messages.
+pub const FAILING_LANE: LaneId = [0, 0, 0, 2];
+
+construct_runtime! {
+	pub enum TestRuntime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		MessageGenerator: message_generator::{Pallet, Call, Storage, Event},
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for TestRuntime {
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type DbWeight = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const MaxMessagesPerBlock: u32 = 10;
+	pub const MaxPayloadSize: u32 = 1024;
+	pub const SendMessageWeight: Weight = 100;
+}
+
+impl message_generator::Config for TestRuntime {
+	type Event = Event;
+	type OutboundPayload = TestPayload;
+	type OutboundMessageFee = u64;
+	type MessagesBridge = TestMessagesBridge;
+	type PayloadBuilder = TestPayloadBuilder;
+	type MaxMessagesPerBlock = MaxMessagesPerBlock;
+	type MaxPayloadSize = MaxPayloadSize;
+	type SendMessageWeight = SendMessageWeight;
+}
+
+thread_local! {
+	static SENT_MESSAGES: RefCell<Vec<SentMessage>> = RefCell::new(Vec::new());
+}
+
+/// Return all messages that have been sent by the message generator in current test.
+pub fn sent_messages() -> Vec<SentMessage> {
+	SENT_MESSAGES.with(|messages| messages.borrow().clone())
+}
+
+/// Messages bridge that records all sent messages.
+pub struct TestMessagesBridge;
+
+impl MessagesBridge<AccountId, u64, TestPayload> for TestMessagesBridge {
+	type Error = &'static str;
+
+	fn send_message_from_pallet(lane: LaneId, message: TestPayload, fee: u64) -> Result<MessageNonce, Self::Error> {
+		if lane == FAILING_LANE {
+			return Err("lane is closed");
+		}
+
+		SENT_MESSAGES.with(|messages| {
+			let mut messages = messages.borrow_mut();
+			messages.push((lane, message, fee));
+			Ok(messages.len() as MessageNonce)
+		})
+	}
+}
+
+/// Payload builder that encodes payload size and dispatch weight in the payload itself.
+pub struct TestPayloadBuilder;
+
+impl MessagePayloadBuilder<TestPayload> for TestPayloadBuilder {
+	fn build_payload(payload_size: u32, dispatch_weight: Weight) -> TestPayload {
+		(payload_size, dispatch_weight)
+	}
+}
+
+pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
+	SENT_MESSAGES.with(|messages| messages.borrow_mut().clear());
+	sp_io::TestExternalities::new(Default::default()).execute_with(test)
+}
@@ -0,0 +1,166 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC interface of the messages module.
+//!
+//! The RPC allows relayers, that have no access to the `state_getReadProof` method (or that don't
+//! want to compute storage keys themselves), to get everything that is required to build the
+//! messages delivery transaction in a single call.
+
+use bp_messages::{LaneId, MessageNonce, OutboundMessagesApi};
+use bp_runtime::ChainId;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use sc_client_api::ProofProvider;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Error code, returned when the block is not (yet) finalized.
+const NOT_FINALIZED_BLOCK_ERROR: i64 = 1;
+/// Error code, returned when the runtime API call has failed.
+const RUNTIME_ERROR: i64 = 2;
+/// Error code, returned when the storage proof can't be generated.
+const PROOF_ERROR: i64 = 3;
+/// Error code, returned when the messages pallet instance is unknown to the runtime.
+const UNKNOWN_INSTANCE_ERROR: i64 = 4;
+
+/// Outbound messages along with the storage proof of these messages.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboundMessagesWithProof {
+	/// Nonces and encoded payloads of the messages.
+	pub messages: Vec<(MessageNonce, Bytes)>,
+	/// Storage proof of the messages (and of the outbound lane state, if requested).
+	pub proof: Vec<Bytes>,
+}
+
+/// Messages RPC methods.
+#[rpc]
+pub trait MessagesApi<BlockHash> {
+	/// Returns payloads of outbound messages in the inclusive `begin..=end` nonce range, along with
+	/// the storage proof of these messages, anchored at the given finalized block.
+	///
+	/// The `instance` selects the messages pallet instance, that is sending messages to the bridged
+	/// chain. If `include_outbound_lane_state` is true, the proof also includes the outbound lane state.
+	#[rpc(name = "bridgeMessages_proveOutboundMessages")]
+	fn prove_outbound_messages(
+		&self,
+		instance: ChainId,
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		include_outbound_lane_state: bool,
+		at: BlockHash,
+	) -> RpcResult<OutboundMessagesWithProof>;
+}
+
+/// Implementation of the messages RPC methods.
+pub struct Messages<Client, Block> {
+	client: Arc<Client>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Client, Block> Messages<Client, Block> {
+	/// Create new messages RPC handler.
+	pub fn new(client: Arc<Client>) -> Self {
+		Messages {
+			client,
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<Client, Block> MessagesApi<Block::Hash> for Messages<Client, Block>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block> + ProofProvider<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	Client::Api: OutboundMessagesApi<Block>,
+{
+	fn prove_outbound_messages(
+		&self,
+		instance: ChainId,
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		include_outbound_lane_state: bool,
+		at: Block::Hash,
+	) -> RpcResult<OutboundMessagesWithProof> {
+		ensure_finalized(&*self.client, at)?;
+
+		let at = BlockId::Hash(at);
+		let outbound_messages = self
+			.client
+			.runtime_api()
+			.outbound_messages(&at, instance, lane, begin, end, include_outbound_lane_state)
+			.map_err(|e| rpc_error(RUNTIME_ERROR, "Failed to read outbound messages", e))?
+			.map_err(|e| rpc_error(UNKNOWN_INSTANCE_ERROR, "Unknown messages pallet instance", e))?;
+		let proof = self
+			.client
+			.read_proof(&at, &mut outbound_messages.storage_keys.iter().map(|key| &key[..]))
+			.map_err(|e| rpc_error(PROOF_ERROR, "Failed to generate outbound messages proof", e))?;
+
+		Ok(OutboundMessagesWithProof {
+			messages: outbound_messages
+				.messages
+				.into_iter()
+				.map(|(nonce, payload)| (nonce, Bytes(payload)))
+				.collect(),
+			proof: proof.iter_nodes().map(Bytes).collect(),
+		})
+	}
+}
+
+/// Ensure that the block with given hash is finalized.
+///
+/// Proofs, anchored at non-finalized blocks, would be rejected by the bridged chain until the block
+/// is finalized there, or forever, if the block is never finalized.
+fn ensure_finalized<Client, Block>(client: &Client, hash: Block::Hash) -> RpcResult<()>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>,
+{
+	let not_finalized = || rpc_error(NOT_FINALIZED_BLOCK_ERROR, "Block is not finalized", hash);
+	let number = *client
+		.header(BlockId::Hash(hash))
+		.map_err(|e| rpc_error(NOT_FINALIZED_BLOCK_ERROR, "Failed to read block header", e))?
+		.ok_or_else(not_finalized)?
+		.number();
+	let is_finalized = number <= client.info().finalized_number
+		&& client
+			.hash(number)
+			.map_err(|e| rpc_error(NOT_FINALIZED_BLOCK_ERROR, "Failed to read canonical block hash", e))?
+			== Some(hash);
+	if !is_finalized {
+		return Err(not_finalized());
+	}
+
+	Ok(())
+}
+
+/// Create RPC error with given code, message and debug representation of the cause.
+fn rpc_error(code: i64, message: &str, cause: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(code),
+		message: message.into(),
+		data: Some(format!("{:?}", cause).into()),
+	}
+}
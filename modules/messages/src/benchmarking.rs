@@ -37,6 +37,9 @@ const SEED: u32 = 0;
 pub struct Pallet<T: Config<I>, I: crate::Instance>(crate::Pallet<T, I>);
 
 /// Proof size requirements.
+///
+/// Storage proofs with unused nodes are rejected by the runtime, so the trie is grown up to the
+/// required size, but only nodes that are required to read proved values are included in the proof.
 pub enum ProofSize {
 	/// The proof is expected to be minimal. If value size may be changed, then it is expected to
 	/// have given size.
@@ -321,6 +324,7 @@ benchmarks_instance! {
 				oldest_unpruned_nonce: 21,
 				latest_received_nonce: 20,
 				latest_generated_nonce: 21,
+				..Default::default()
 			}),
 			size: ProofSize::Minimal(EXPECTED_DEFAULT_MESSAGE_LENGTH),
 		});
@@ -422,6 +426,7 @@ benchmarks_instance! {
 			inbound_lane_data: InboundLaneData {
 				relayers: vec![(1, 1, relayer_id.clone())].into_iter().collect(),
 				last_confirmed_nonce: 0,
+				..Default::default()
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -460,6 +465,7 @@ benchmarks_instance! {
 			inbound_lane_data: InboundLaneData {
 				relayers: vec![(1, 2, relayer_id.clone())].into_iter().collect(),
 				last_confirmed_nonce: 0,
+				..Default::default()
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -500,6 +506,7 @@ benchmarks_instance! {
 					(2, 2, relayer2_id.clone()),
 				].into_iter().collect(),
 				last_confirmed_nonce: 0,
+				..Default::default()
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -684,6 +691,7 @@ benchmarks_instance! {
 				oldest_unpruned_nonce: 21,
 				latest_received_nonce: 20,
 				latest_generated_nonce: 21,
+				..Default::default()
 			}),
 			size: ProofSize::Minimal(0),
 		});
@@ -733,6 +741,7 @@ benchmarks_instance! {
 			inbound_lane_data: InboundLaneData {
 				relayers: vec![(1, i as MessageNonce, relayer_id.clone())].into_iter().collect(),
 				last_confirmed_nonce: 0,
+				..Default::default()
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -779,6 +788,7 @@ benchmarks_instance! {
 					.map(|(j, relayer_id)| (j as MessageNonce + 1, j as MessageNonce + 1, relayer_id.clone()))
 					.collect(),
 				last_confirmed_nonce: 0,
+				..Default::default()
 			},
 			size: ProofSize::Minimal(0),
 		});
@@ -788,6 +798,109 @@ benchmarks_instance! {
 			ensure_relayer_rewarded::<T, I>(&relayer_id, &prev_balance);
 		}
 	}
+
+	// Benchmark `receive_messages_delivery_proof` extrinsic with following conditions:
+	// * single relayer is rewarded for relaying single message;
+	// * proof includes extra trie nodes, so its size is at least `i` bytes.
+	//
+	// Results of this benchmark may be used to check how proof size affects `receive_messages_delivery_proof`
+	// performance.
+	receive_delivery_proof_with_extra_nodes {
+		let i in 0..T::maximal_message_size();
+
+		let relayers_fund_id = crate::Pallet::<T, I>::relayer_fund_account_id();
+		let relayer_id: T::AccountId = account("relayer", 0, SEED);
+		let relayer_balance = T::account_balance(&relayer_id);
+		T::endow_account(&relayers_fund_id);
+
+		// send message that we're going to confirm
+		send_regular_message::<T, I>();
+
+		let relayers_state = UnrewardedRelayersState {
+			unrewarded_relayer_entries: 1,
+			messages_in_oldest_entry: 1,
+			total_messages: 1,
+		};
+		let proof = T::prepare_message_delivery_proof(MessageDeliveryProofParams {
+			lane: T::bench_lane_id(),
+			inbound_lane_data: InboundLaneData {
+				relayers: vec![(1, 1, relayer_id.clone())].into_iter().collect(),
+				last_confirmed_nonce: 0,
+				..Default::default()
+			},
+			size: ProofSize::HasExtraNodes(i as _),
+		});
+	}: receive_messages_delivery_proof(RawOrigin::Signed(relayer_id.clone()), proof, relayers_state)
+	verify {
+		ensure_relayer_rewarded::<T, I>(&relayer_id, &relayer_balance);
+	}
+
+	// Benchmark `receive_messages_delivery_proof` extrinsic with the worst possible conditions:
+	// * there are `i` unrewarded relayer entries at the inbound lane and every relayer is rewarded;
+	// * maximal number of messages is confirmed by the proof;
+	// * proof includes extra trie nodes, so its size is at least maximal message size.
+	//
+	// Results of this benchmark may be used to check that the `receive_messages_delivery_proof` weight
+	// formula covers confirmations that are crafted by adversarial relayers.
+	receive_delivery_proof_worst_case {
+		// there actually should be used values of `MaxUnrewardedRelayerEntriesAtInboundLane` and
+		// `MaxUnconfirmedMessagesAtInboundLane` from the bridged chain
+		let i in 1..T::MaxUnrewardedRelayerEntriesAtInboundLane::get()
+			.try_into()
+			.expect("Value of MaxUnrewardedRelayerEntriesAtInboundLane is too large");
+		let messages_count = sp_std::cmp::max(T::MaxUnconfirmedMessagesAtInboundLane::get(), i as MessageNonce);
+
+		let relayers_fund_id = crate::Pallet::<T, I>::relayer_fund_account_id();
+		let confirmation_relayer_id = account("relayer", 0, SEED);
+		let relayers: Vec<(T::AccountId, T::OutboundMessageFee)> = (1..=i)
+			.map(|j| {
+				let relayer_id = account("relayer", j + 1, SEED);
+				let relayer_balance = T::account_balance(&relayer_id);
+				(relayer_id, relayer_balance)
+			})
+			.collect();
+		T::endow_account(&relayers_fund_id);
+
+		// send messages that we're going to confirm
+		for _ in 1..=messages_count {
+			send_regular_message::<T, I>();
+		}
+
+		// every relayer, except the last one, has delivered single message and the last relayer has
+		// delivered all remaining messages
+		let relayers_entries = relayers
+			.iter()
+			.enumerate()
+			.map(|(j, (relayer_id, _))| {
+				let begin = j as MessageNonce + 1;
+				let end = if j as u32 + 1 == i { messages_count } else { begin };
+				(begin, end, relayer_id.clone())
+			})
+			.collect();
+		let relayers_state = UnrewardedRelayersState {
+			unrewarded_relayer_entries: i as MessageNonce,
+			messages_in_oldest_entry: if i == 1 { messages_count } else { 1 },
+			total_messages: messages_count,
+		};
+		let proof = T::prepare_message_delivery_proof(MessageDeliveryProofParams {
+			lane: T::bench_lane_id(),
+			inbound_lane_data: InboundLaneData {
+				relayers: relayers_entries,
+				last_confirmed_nonce: 0,
+				..Default::default()
+			},
+			size: ProofSize::HasExtraNodes(T::maximal_message_size()),
+		});
+	}: receive_messages_delivery_proof(RawOrigin::Signed(confirmation_relayer_id), proof, relayers_state)
+	verify {
+		assert_eq!(
+			crate::Pallet::<T, I>::outbound_latest_received_nonce(T::bench_lane_id()),
+			messages_count,
+		);
+		for (relayer_id, prev_balance) in relayers {
+			ensure_relayer_rewarded::<T, I>(&relayer_id, &prev_balance);
+		}
+	}
 }
 
 fn send_regular_message<T: Config<I>, I: Instance>() {
@@ -816,6 +929,7 @@ fn receive_messages<T: Config<I>, I: Instance>(nonce: MessageNonce) {
 	inbound_lane_storage.set_data(InboundLaneData {
 		relayers: vec![(1, nonce, T::bridged_relayer_id())].into_iter().collect(),
 		last_confirmed_nonce: 0,
+		..Default::default()
 	});
 }
 
@@ -18,8 +18,9 @@
 
 use bp_messages::{
 	target_chain::{DispatchMessage, DispatchMessageData, MessageDispatch},
-	InboundLaneData, LaneId, MessageKey, MessageNonce, OutboundLaneData,
+	InboundLaneData, LaneId, LaneOrdering, MessageKey, MessageNonce, OutboundLaneData, Weight,
 };
+use sp_runtime::RuntimeDebug;
 use sp_std::prelude::PartialEq;
 
 /// Inbound lane storage.
@@ -31,16 +32,44 @@ pub trait InboundLaneStorage {
 
 	/// Lane id.
 	fn id(&self) -> LaneId;
+	/// Return ordering of messages at the lane.
+	fn ordering(&self) -> LaneOrdering;
+	/// Return maximal dispatch weight of messages at the lane, if it is limited.
+	fn max_dispatch_weight(&self) -> Option<Weight>;
 	/// Return maximal number of unrewarded relayer entries in inbound lane.
 	fn max_unrewarded_relayer_entries(&self) -> MessageNonce;
 	/// Return maximal number of unconfirmed messages in inbound lane.
 	fn max_unconfirmed_messages(&self) -> MessageNonce;
+	/// Return true if there are too many messages, waiting for dispatch at the lane.
+	fn is_dispatch_queue_full(&self) -> bool;
 	/// Get lane data from the storage.
 	fn data(&self) -> InboundLaneData<Self::Relayer>;
 	/// Update lane data in the storage.
 	fn set_data(&mut self, data: InboundLaneData<Self::Relayer>);
 }
 
+/// Result of single message receival.
+#[derive(RuntimeDebug, PartialEq, Eq)]
+pub enum ReceivalResult {
+	/// Message has been received and dispatched. Unspent dispatch weight is attached.
+	Dispatched(Weight),
+	/// Message has been received, but it hasn't been dispatched, because its dispatch weight
+	/// exceeds the lane limit.
+	DispatchSkipped,
+	/// Message has already been received at the lane. It is not an error, because several
+	/// relayers may compete to deliver the same message.
+	AlreadyReceived,
+	/// Message nonce is not the next expected nonce at the ordered lane - there's a gap
+	/// between last delivered message and this message.
+	NonceGap,
+	/// There are too many unrewarded relayer entries at the lane.
+	TooManyUnrewardedRelayers,
+	/// There are too many unconfirmed messages at the lane.
+	TooManyUnconfirmedMessages,
+	/// There are too many messages waiting for dispatch at the lane.
+	TooManyQueuedMessages,
+}
+
 /// Inbound messages lane.
 pub struct InboundLane<S> {
 	storage: S,
@@ -84,37 +113,51 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 			}
 			_ => {}
 		}
+		// Finally, forget about undispatched messages that are confirmed now.
+		data.undispatched_messages.retain(|nonce| *nonce > new_confirmed_nonce);
 
 		self.storage.set_data(data);
 		Some(outbound_lane_data.latest_received_nonce)
 	}
 
 	/// Receive new message.
-	pub fn receive_message<P: MessageDispatch<S::MessageFee>>(
+	pub fn receive_message<P: MessageDispatch<AccountId, S::MessageFee>, AccountId>(
 		&mut self,
 		relayer: S::Relayer,
+		relayer_at_this_chain: &AccountId,
 		nonce: MessageNonce,
 		message_data: DispatchMessageData<P::DispatchPayload, S::MessageFee>,
-	) -> bool {
+	) -> ReceivalResult {
 		let mut data = self.storage.data();
-		let is_correct_message = nonce == data.last_delivered_nonce() + 1;
-		if !is_correct_message {
-			return false;
+		let last_delivered_nonce = data.last_delivered_nonce();
+		if nonce <= last_delivered_nonce {
+			return ReceivalResult::AlreadyReceived;
+		}
+		// messages of unordered lane may be skipped - they're never delivered then
+		if nonce != last_delivered_nonce + 1 && self.storage.ordering() == LaneOrdering::Ordered {
+			return ReceivalResult::NonceGap;
 		}
 
 		// if there are more unrewarded relayer entries than we may accept, reject this message
 		if data.relayers.len() as MessageNonce >= self.storage.max_unrewarded_relayer_entries() {
-			return false;
+			return ReceivalResult::TooManyUnrewardedRelayers;
 		}
 
 		// if there are more unconfirmed messages than we may accept, reject this message
 		let unconfirmed_messages_count = nonce.saturating_sub(data.last_confirmed_nonce);
 		if unconfirmed_messages_count > self.storage.max_unconfirmed_messages() {
-			return false;
+			return ReceivalResult::TooManyUnconfirmedMessages;
 		}
 
+		// if there are too many messages waiting for dispatch, reject this message
+		if self.storage.is_dispatch_queue_full() {
+			return ReceivalResult::TooManyQueuedMessages;
+		}
+
+		// entries never cover skipped messages, so the relayer isn't rewarded for messages that
+		// haven't been delivered
 		let push_new = match data.relayers.back_mut() {
-			Some((_, nonce_high, last_relayer)) if last_relayer == &relayer => {
+			Some((_, nonce_high, last_relayer)) if last_relayer == &relayer && *nonce_high + 1 == nonce => {
 				*nonce_high = nonce;
 				false
 			}
@@ -123,18 +166,27 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 		if push_new {
 			data.relayers.push_back((nonce, nonce, relayer));
 		}
+		// messages with undecodable payloads (including noop messages) are never dispatched and
+		// the source chain needs to know that
+		if message_data.payload.is_err() {
+			data.undispatched_messages.push(nonce);
+		}
 
 		self.storage.set_data(data);
 
-		P::dispatch(DispatchMessage {
+		let message = DispatchMessage {
 			key: MessageKey {
 				lane_id: self.storage.id(),
 				nonce,
 			},
 			data: message_data,
-		});
-
-		true
+		};
+		if let Some(max_dispatch_weight) = self.storage.max_dispatch_weight() {
+			if P::dispatch_weight(&message) > max_dispatch_weight {
+				return ReceivalResult::DispatchSkipped;
+			}
+		}
+		ReceivalResult::Dispatched(P::dispatch(relayer_at_this_chain, message))
 	}
 }
 
@@ -147,18 +199,23 @@ mod tests {
 			message_data, run_test, TestMessageDispatch, TestRuntime, REGULAR_PAYLOAD, TEST_LANE_ID, TEST_RELAYER_A,
 			TEST_RELAYER_B, TEST_RELAYER_C,
 		},
-		DefaultInstance, RuntimeInboundLaneStorage,
+		DefaultInstance, LaneMessageLimits, LaneOrderings, RuntimeInboundLaneStorage,
 	};
+	use bp_messages::MessageData;
 
 	fn receive_regular_message(
 		lane: &mut InboundLane<RuntimeInboundLaneStorage<TestRuntime, DefaultInstance>>,
 		nonce: MessageNonce,
 	) {
-		assert!(lane.receive_message::<TestMessageDispatch>(
-			TEST_RELAYER_A,
-			nonce,
-			message_data(REGULAR_PAYLOAD).into()
-		));
+		assert_eq!(
+			lane.receive_message::<TestMessageDispatch, _>(
+				TEST_RELAYER_A,
+				&TEST_RELAYER_A,
+				nonce,
+				message_data(REGULAR_PAYLOAD).into()
+			),
+			ReceivalResult::Dispatched(0),
+		);
 	}
 
 	#[test]
@@ -269,39 +326,141 @@ mod tests {
 	fn fails_to_receive_message_with_incorrect_nonce() {
 		run_test(|| {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
-			assert!(!lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				10,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					10,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::NonceGap,
+			);
 			assert_eq!(lane.storage.data().last_delivered_nonce(), 0);
 		});
 	}
 
+	#[test]
+	fn unordered_lane_receives_message_with_nonce_gap() {
+		run_test(|| {
+			LaneOrderings::<DefaultInstance>::insert(TEST_LANE_ID, LaneOrdering::Unordered);
+
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			receive_regular_message(&mut lane, 1);
+			receive_regular_message(&mut lane, 10);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 10);
+			assert_eq!(
+				lane.storage.data().relayers,
+				vec![(1, 1, TEST_RELAYER_A), (10, 10, TEST_RELAYER_A)]
+			);
+
+			// skipped messages are never delivered
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					5,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::AlreadyReceived,
+			);
+		});
+	}
+
+	#[test]
+	fn message_above_lane_dispatch_weight_limit_is_received_without_dispatch() {
+		run_test(|| {
+			LaneMessageLimits::<DefaultInstance>::insert(
+				TEST_LANE_ID,
+				bp_messages::MessageLimits {
+					max_payload_size: 1024,
+					max_dispatch_weight: REGULAR_PAYLOAD.1 - 1,
+				},
+			);
+
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::DispatchSkipped,
+			);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 1);
+			assert_eq!(lane.storage.data().relayers, vec![(1, 1, TEST_RELAYER_A)]);
+		});
+	}
+
+	#[test]
+	fn undispatched_messages_are_tracked_until_confirmed() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			receive_regular_message(&mut lane, 1);
+			for nonce in 2..=3 {
+				assert_eq!(
+					lane.receive_message::<TestMessageDispatch, _>(
+						TEST_RELAYER_A,
+						&TEST_RELAYER_A,
+						nonce,
+						MessageData {
+							payload: Vec::new(),
+							fee: 1,
+						}
+						.into()
+					),
+					ReceivalResult::Dispatched(0),
+				);
+			}
+			assert_eq!(lane.storage.data().undispatched_messages, vec![2, 3]);
+
+			assert_eq!(
+				lane.receive_state_update(OutboundLaneData {
+					latest_received_nonce: 2,
+					..Default::default()
+				}),
+				Some(2),
+			);
+			assert_eq!(lane.storage.data().undispatched_messages, vec![3]);
+		});
+	}
+
 	#[test]
 	fn fails_to_receive_messages_above_unrewarded_relayer_entries_limit_per_lane() {
 		run_test(|| {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
 			let max_nonce = <TestRuntime as crate::Config>::MaxUnrewardedRelayerEntriesAtInboundLane::get();
 			for current_nonce in 1..max_nonce + 1 {
-				assert!(lane.receive_message::<TestMessageDispatch>(
-					TEST_RELAYER_A + current_nonce,
-					current_nonce,
-					message_data(REGULAR_PAYLOAD).into()
-				));
+				assert_eq!(
+					lane.receive_message::<TestMessageDispatch, _>(
+						TEST_RELAYER_A + current_nonce,
+						&(TEST_RELAYER_A + current_nonce),
+						current_nonce,
+						message_data(REGULAR_PAYLOAD).into()
+					),
+					ReceivalResult::Dispatched(0),
+				);
 			}
 			// Fails to dispatch new message from different than latest relayer.
-			assert!(!lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A + max_nonce + 1,
-				max_nonce + 1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A + max_nonce + 1,
+					&(TEST_RELAYER_A + max_nonce + 1),
+					max_nonce + 1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::TooManyUnrewardedRelayers,
+			);
 			// Fails to dispatch new messages from latest relayer. Prevents griefing attacks.
-			assert!(!lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A + max_nonce,
-				max_nonce + 1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A + max_nonce,
+					&(TEST_RELAYER_A + max_nonce),
+					max_nonce + 1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::TooManyUnrewardedRelayers,
+			);
 		});
 	}
 
@@ -311,24 +470,36 @@ mod tests {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
 			let max_nonce = <TestRuntime as crate::Config>::MaxUnconfirmedMessagesAtInboundLane::get();
 			for current_nonce in 1..=max_nonce {
-				assert!(lane.receive_message::<TestMessageDispatch>(
-					TEST_RELAYER_A,
-					current_nonce,
-					message_data(REGULAR_PAYLOAD).into()
-				));
+				assert_eq!(
+					lane.receive_message::<TestMessageDispatch, _>(
+						TEST_RELAYER_A,
+						&TEST_RELAYER_A,
+						current_nonce,
+						message_data(REGULAR_PAYLOAD).into()
+					),
+					ReceivalResult::Dispatched(0),
+				);
 			}
 			// Fails to dispatch new message from different than latest relayer.
-			assert!(!lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_B,
-				max_nonce + 1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_B,
+					&TEST_RELAYER_B,
+					max_nonce + 1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::TooManyUnconfirmedMessages,
+			);
 			// Fails to dispatch new messages from latest relayer.
-			assert!(!lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				max_nonce + 1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					max_nonce + 1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::TooManyUnconfirmedMessages,
+			);
 		});
 	}
 
@@ -336,21 +507,33 @@ mod tests {
 	fn correctly_receives_following_messages_from_two_relayers_alternately() {
 		run_test(|| {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
-			assert!(lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
-			assert!(lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_B,
-				2,
-				message_data(REGULAR_PAYLOAD).into()
-			));
-			assert!(lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				3,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::Dispatched(0),
+			);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_B,
+					&TEST_RELAYER_B,
+					2,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::Dispatched(0),
+			);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					3,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::Dispatched(0),
+			);
 			assert_eq!(
 				lane.storage.data().relayers,
 				vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B), (3, 3, TEST_RELAYER_A)]
@@ -362,16 +545,24 @@ mod tests {
 	fn rejects_same_message_from_two_different_relayers() {
 		run_test(|| {
 			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
-			assert!(lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_A,
-				1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
-			assert!(!lane.receive_message::<TestMessageDispatch>(
-				TEST_RELAYER_B,
-				1,
-				message_data(REGULAR_PAYLOAD).into()
-			));
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::Dispatched(0),
+			);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					TEST_RELAYER_B,
+					&TEST_RELAYER_B,
+					1,
+					message_data(REGULAR_PAYLOAD).into()
+				),
+				ReceivalResult::AlreadyReceived,
+			);
 		});
 	}
 
@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Implementation of `MessageDeliveryAndDispatchPayment` trait on top of `Currency` trait.
+//! Implementations of `MessageDeliveryAndDispatchPayment` trait on top of `Currency` and
+//! `fungibles::Transfer` traits.
 //!
 //! The payment is first transferred to a special `relayers-fund` account and only transferred
 //! to the actual relayer in case confirmation is received.
@@ -24,7 +25,9 @@ use bp_messages::{
 	MessageNonce,
 };
 use codec::Encode;
-use frame_support::traits::{Currency as CurrencyT, ExistenceRequirement, Get};
+use frame_support::traits::{
+	tokens::fungibles::Transfer as FungiblesTransfer, Currency as CurrencyT, ExistenceRequirement, Get,
+};
 use num_traits::Zero;
 use sp_runtime::traits::Saturating;
 use sp_std::fmt::Debug;
@@ -89,6 +92,21 @@ where
 		.map_err(Into::into)
 	}
 
+	fn refund_delivery_and_dispatch_fee(
+		submitter: &T::AccountId,
+		fee: &Currency::Balance,
+		relayer_fund_account: &T::AccountId,
+	) -> Result<(), Self::Error> {
+		Currency::transfer(
+			relayer_fund_account,
+			submitter,
+			*fee,
+			// the relayer fund account must stay above ED (needs to be pre-funded)
+			ExistenceRequirement::KeepAlive,
+		)
+		.map_err(Into::into)
+	}
+
 	fn pay_relayers_rewards(
 		confirmation_relayer: &T::AccountId,
 		relayers_rewards: RelayersRewards<T::AccountId, Currency::Balance>,
@@ -103,6 +121,86 @@ where
 	}
 }
 
+/// Instant message payments made in given asset.
+///
+/// Works exactly like the `InstantCurrencyPayments`, but fees and rewards are paid in the
+/// `GetAssetId` asset of the `Assets` (which may be e.g. `pallet-assets`) instead of the native
+/// token. This allows bridges to pay relayers in a stable asset or in a bridged token.
+///
+/// NOTE The `relayers-fund` account must always hold at least minimal balance of the asset.
+pub struct InstantAssetPayments<T, Assets, GetAssetId, GetConfirmationFee, RootAccount> {
+	_phantom: sp_std::marker::PhantomData<(T, Assets, GetAssetId, GetConfirmationFee, RootAccount)>,
+}
+
+impl<T, Assets, GetAssetId, GetConfirmationFee, RootAccount>
+	MessageDeliveryAndDispatchPayment<T::AccountId, Assets::Balance>
+	for InstantAssetPayments<T, Assets, GetAssetId, GetConfirmationFee, RootAccount>
+where
+	T: frame_system::Config,
+	Assets: FungiblesTransfer<T::AccountId>,
+	Assets::Balance: From<MessageNonce>,
+	GetAssetId: Get<Assets::AssetId>,
+	GetConfirmationFee: Get<Assets::Balance>,
+	RootAccount: Get<Option<T::AccountId>>,
+{
+	type Error = &'static str;
+
+	fn initialize(relayer_fund_account: &T::AccountId) -> usize {
+		let asset_id = GetAssetId::get();
+		assert!(
+			Assets::balance(asset_id, relayer_fund_account) >= Assets::minimum_balance(asset_id),
+			"The relayer fund account ({:?}) must hold the asset for the message lanes pallet to work correctly.",
+			relayer_fund_account,
+		);
+		2
+	}
+
+	fn pay_delivery_and_dispatch_fee(
+		submitter: &Sender<T::AccountId>,
+		fee: &Assets::Balance,
+		relayer_fund_account: &T::AccountId,
+	) -> Result<(), Self::Error> {
+		let root_account = RootAccount::get();
+		let account = match submitter {
+			Sender::Signed(submitter) => submitter,
+			Sender::Root | Sender::None => root_account
+				.as_ref()
+				.ok_or("Sending messages using Root or None origin is disallowed.")?,
+		};
+
+		// it's fine for the submitter to go below minimal balance and die
+		Assets::transfer(GetAssetId::get(), account, relayer_fund_account, *fee, false)
+			.map(drop)
+			.map_err(Into::into)
+	}
+
+	fn refund_delivery_and_dispatch_fee(
+		submitter: &T::AccountId,
+		fee: &Assets::Balance,
+		relayer_fund_account: &T::AccountId,
+	) -> Result<(), Self::Error> {
+		// the relayer fund account must stay above minimal balance (needs to be pre-funded)
+		Assets::transfer(GetAssetId::get(), relayer_fund_account, submitter, *fee, true)
+			.map(drop)
+			.map_err(Into::into)
+	}
+
+	fn pay_relayers_rewards(
+		confirmation_relayer: &T::AccountId,
+		relayers_rewards: RelayersRewards<T::AccountId, Assets::Balance>,
+		relayer_fund_account: &T::AccountId,
+	) {
+		split_relayers_rewards(
+			confirmation_relayer,
+			relayers_rewards,
+			GetConfirmationFee::get(),
+			|relayer, reward| {
+				pay_relayer_reward_in_asset::<Assets, _>(GetAssetId::get(), relayer_fund_account, relayer, reward)
+			},
+		);
+	}
+}
+
 /// Pay rewards to given relayers, optionally rewarding confirmation relayer.
 fn pay_relayers_rewards<Currency, AccountId>(
 	confirmation_relayer: &AccountId,
@@ -113,9 +211,28 @@ fn pay_relayers_rewards<Currency, AccountId>(
 	AccountId: Debug + Default + Encode + PartialEq,
 	Currency: CurrencyT<AccountId>,
 	Currency::Balance: From<u64>,
+{
+	split_relayers_rewards(
+		confirmation_relayer,
+		relayers_rewards,
+		confirmation_fee,
+		|relayer, reward| pay_relayer_reward::<Currency, _>(relayer_fund_account, relayer, reward),
+	);
+}
+
+/// Split rewards between given relayers, optionally rewarding confirmation relayer, and pay them
+/// using `pay_relayer_reward`.
+fn split_relayers_rewards<AccountId, Balance>(
+	confirmation_relayer: &AccountId,
+	relayers_rewards: RelayersRewards<AccountId, Balance>,
+	confirmation_fee: Balance,
+	pay_relayer_reward: impl Fn(&AccountId, Balance),
+) where
+	AccountId: PartialEq,
+	Balance: Copy + Zero + Saturating + From<u64> + PartialOrd,
 {
 	// reward every relayer except `confirmation_relayer`
-	let mut confirmation_relayer_reward = Currency::Balance::zero();
+	let mut confirmation_relayer_reward = Balance::zero();
 	for (relayer, reward) in relayers_rewards {
 		let mut relayer_reward = reward.reward;
 
@@ -138,11 +255,11 @@ fn pay_relayers_rewards<Currency, AccountId>(
 			continue;
 		}
 
-		pay_relayer_reward::<Currency, _>(relayer_fund_account, &relayer, relayer_reward);
+		pay_relayer_reward(&relayer, relayer_reward);
 	}
 
 	// finally - pay reward to confirmation relayer
-	pay_relayer_reward::<Currency, _>(relayer_fund_account, confirmation_relayer, confirmation_relayer_reward);
+	pay_relayer_reward(confirmation_relayer, confirmation_relayer_reward);
 }
 
 /// Transfer funds from relayers fund account to given relayer.
@@ -183,19 +300,144 @@ fn pay_relayer_reward<Currency, AccountId>(
 	}
 }
 
+/// Transfer asset from relayers fund account to given relayer.
+fn pay_relayer_reward_in_asset<Assets, AccountId>(
+	asset_id: Assets::AssetId,
+	relayer_fund_account: &AccountId,
+	relayer_account: &AccountId,
+	reward: Assets::Balance,
+) where
+	AccountId: Debug,
+	Assets: FungiblesTransfer<AccountId>,
+{
+	if reward.is_zero() {
+		return;
+	}
+
+	// the relayer fund account must stay above minimal balance (needs to be pre-funded)
+	let pay_result = Assets::transfer(asset_id, relayer_fund_account, relayer_account, reward, true);
+
+	match pay_result {
+		Ok(_) => log::trace!(
+			target: "runtime::bridge-messages",
+			"Rewarded relayer {:?} with {:?} of asset",
+			relayer_account,
+			reward,
+		),
+		Err(error) => log::trace!(
+			target: "runtime::bridge-messages",
+			"Failed to pay relayer {:?} reward {:?} in asset: {:?}",
+			relayer_account,
+			reward,
+			error,
+		),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use crate::mock::{run_test, AccountId as TestAccountId, Balance as TestBalance, TestRuntime};
 	use bp_messages::source_chain::RelayerRewards;
+	use frame_support::{
+		parameter_types,
+		traits::tokens::{fungibles::Inspect as FungiblesInspect, DepositConsequence, WithdrawConsequence},
+	};
+	use sp_runtime::DispatchError;
+	use std::{cell::RefCell, collections::BTreeMap};
 
 	type Balances = pallet_balances::Pallet<TestRuntime>;
+	type AssetPayments =
+		InstantAssetPayments<TestRuntime, TestAssets, TestAssetId, TestConfirmationFee, TestRootAccount>;
 
 	const RELAYER_1: TestAccountId = 1;
 	const RELAYER_2: TestAccountId = 2;
 	const RELAYER_3: TestAccountId = 3;
 	const RELAYERS_FUND_ACCOUNT: TestAccountId = crate::mock::ENDOWED_ACCOUNT;
 
+	const TEST_ASSET_ID: u32 = 42;
+	const TEST_ASSET_MINIMUM_BALANCE: TestBalance = 10;
+
+	parameter_types! {
+		pub const TestAssetId: u32 = TEST_ASSET_ID;
+		pub const TestConfirmationFee: TestBalance = 10;
+		pub const TestRootAccount: Option<TestAccountId> = None;
+	}
+
+	thread_local! {
+		static ASSET_BALANCES: RefCell<BTreeMap<TestAccountId, TestBalance>> = RefCell::new(BTreeMap::new());
+	}
+
+	/// `fungibles` implementation that only knows about single `TEST_ASSET_ID` asset.
+	struct TestAssets;
+
+	impl TestAssets {
+		fn set_balance(who: TestAccountId, balance: TestBalance) {
+			ASSET_BALANCES.with(|balances| balances.borrow_mut().insert(who, balance));
+		}
+	}
+
+	impl FungiblesInspect<TestAccountId> for TestAssets {
+		type AssetId = u32;
+		type Balance = TestBalance;
+
+		fn total_issuance(_asset: u32) -> TestBalance {
+			ASSET_BALANCES.with(|balances| balances.borrow().values().sum())
+		}
+
+		fn minimum_balance(_asset: u32) -> TestBalance {
+			TEST_ASSET_MINIMUM_BALANCE
+		}
+
+		fn balance(_asset: u32, who: &TestAccountId) -> TestBalance {
+			ASSET_BALANCES.with(|balances| balances.borrow().get(who).cloned().unwrap_or(0))
+		}
+
+		fn reducible_balance(asset: u32, who: &TestAccountId, keep_alive: bool) -> TestBalance {
+			let balance = Self::balance(asset, who);
+			if keep_alive {
+				balance.saturating_sub(TEST_ASSET_MINIMUM_BALANCE)
+			} else {
+				balance
+			}
+		}
+
+		fn can_deposit(_asset: u32, _who: &TestAccountId, _amount: TestBalance) -> DepositConsequence {
+			DepositConsequence::Success
+		}
+
+		fn can_withdraw(asset: u32, who: &TestAccountId, amount: TestBalance) -> WithdrawConsequence<TestBalance> {
+			if amount > Self::balance(asset, who) {
+				WithdrawConsequence::NoFunds
+			} else {
+				WithdrawConsequence::Success
+			}
+		}
+	}
+
+	impl FungiblesTransfer<TestAccountId> for TestAssets {
+		fn transfer(
+			asset: u32,
+			source: &TestAccountId,
+			dest: &TestAccountId,
+			amount: TestBalance,
+			keep_alive: bool,
+		) -> Result<TestBalance, DispatchError> {
+			assert_eq!(asset, TEST_ASSET_ID);
+			if amount > Self::reducible_balance(asset, source, keep_alive) {
+				return Err(DispatchError::Other("InsufficientBalance"));
+			}
+
+			Self::set_balance(*source, Self::balance(asset, source) - amount);
+			Self::set_balance(*dest, Self::balance(asset, dest) + amount);
+			Ok(amount)
+		}
+	}
+
+	fn asset_balance(who: TestAccountId) -> TestBalance {
+		TestAssets::balance(TEST_ASSET_ID, &who)
+	}
+
 	fn relayers_rewards() -> RelayersRewards<TestAccountId, TestBalance> {
 		vec![
 			(
@@ -248,4 +490,116 @@ mod tests {
 			assert_eq!(Balances::free_balance(&RELAYER_3), 200);
 		});
 	}
+
+	#[test]
+	fn asset_payments_are_initialized_if_relayers_fund_holds_minimal_balance() {
+		run_test(|| {
+			TestAssets::set_balance(RELAYERS_FUND_ACCOUNT, TEST_ASSET_MINIMUM_BALANCE);
+			AssetPayments::initialize(&RELAYERS_FUND_ACCOUNT);
+		});
+	}
+
+	#[test]
+	#[should_panic(expected = "must hold the asset for the message lanes pallet to work correctly")]
+	fn asset_payments_are_not_initialized_if_relayers_fund_holds_less_than_minimal_balance() {
+		run_test(|| {
+			TestAssets::set_balance(RELAYERS_FUND_ACCOUNT, TEST_ASSET_MINIMUM_BALANCE - 1);
+			AssetPayments::initialize(&RELAYERS_FUND_ACCOUNT);
+		});
+	}
+
+	#[test]
+	fn asset_payments_pays_delivery_and_dispatch_fee() {
+		run_test(|| {
+			TestAssets::set_balance(RELAYER_1, 100);
+
+			assert_eq!(
+				AssetPayments::pay_delivery_and_dispatch_fee(&Sender::Signed(RELAYER_1), &100, &RELAYERS_FUND_ACCOUNT),
+				Ok(()),
+			);
+			assert_eq!(asset_balance(RELAYER_1), 0);
+			assert_eq!(asset_balance(RELAYERS_FUND_ACCOUNT), 100);
+		});
+	}
+
+	#[test]
+	fn asset_payments_rejects_fee_payment_if_submitter_has_no_funds() {
+		run_test(|| {
+			TestAssets::set_balance(RELAYER_1, 50);
+
+			assert_eq!(
+				AssetPayments::pay_delivery_and_dispatch_fee(&Sender::Signed(RELAYER_1), &100, &RELAYERS_FUND_ACCOUNT),
+				Err("InsufficientBalance"),
+			);
+			assert_eq!(asset_balance(RELAYER_1), 50);
+			assert_eq!(asset_balance(RELAYERS_FUND_ACCOUNT), 0);
+		});
+	}
+
+	#[test]
+	fn asset_payments_rejects_fee_payment_from_root_if_root_account_is_not_configured() {
+		run_test(|| {
+			assert_eq!(
+				AssetPayments::pay_delivery_and_dispatch_fee(&Sender::Root, &100, &RELAYERS_FUND_ACCOUNT),
+				Err("Sending messages using Root or None origin is disallowed."),
+			);
+		});
+	}
+
+	#[test]
+	fn asset_payments_refunds_delivery_and_dispatch_fee() {
+		run_test(|| {
+			TestAssets::set_balance(RELAYERS_FUND_ACCOUNT, TEST_ASSET_MINIMUM_BALANCE + 100);
+
+			assert_eq!(
+				AssetPayments::refund_delivery_and_dispatch_fee(&RELAYER_1, &100, &RELAYERS_FUND_ACCOUNT),
+				Ok(()),
+			);
+			assert_eq!(asset_balance(RELAYER_1), 100);
+			assert_eq!(asset_balance(RELAYERS_FUND_ACCOUNT), TEST_ASSET_MINIMUM_BALANCE);
+		});
+	}
+
+	#[test]
+	fn asset_payments_does_not_refund_fee_if_relayers_fund_would_go_below_minimal_balance() {
+		run_test(|| {
+			TestAssets::set_balance(RELAYERS_FUND_ACCOUNT, TEST_ASSET_MINIMUM_BALANCE + 100);
+
+			assert_eq!(
+				AssetPayments::refund_delivery_and_dispatch_fee(&RELAYER_1, &101, &RELAYERS_FUND_ACCOUNT),
+				Err("InsufficientBalance"),
+			);
+			assert_eq!(asset_balance(RELAYER_1), 0);
+			assert_eq!(asset_balance(RELAYERS_FUND_ACCOUNT), TEST_ASSET_MINIMUM_BALANCE + 100);
+		});
+	}
+
+	#[test]
+	fn asset_payments_pays_relayers_rewards() {
+		run_test(|| {
+			TestAssets::set_balance(RELAYERS_FUND_ACCOUNT, TEST_ASSET_MINIMUM_BALANCE + 200);
+
+			AssetPayments::pay_relayers_rewards(&RELAYER_3, relayers_rewards(), &RELAYERS_FUND_ACCOUNT);
+
+			assert_eq!(asset_balance(RELAYER_1), 80);
+			assert_eq!(asset_balance(RELAYER_2), 70);
+			assert_eq!(asset_balance(RELAYER_3), 50);
+			assert_eq!(asset_balance(RELAYERS_FUND_ACCOUNT), TEST_ASSET_MINIMUM_BALANCE);
+		});
+	}
+
+	#[test]
+	fn asset_payments_does_not_pay_rewards_if_relayers_fund_would_go_below_minimal_balance() {
+		run_test(|| {
+			TestAssets::set_balance(RELAYERS_FUND_ACCOUNT, TEST_ASSET_MINIMUM_BALANCE + 100);
+
+			AssetPayments::pay_relayers_rewards(&RELAYER_3, relayers_rewards(), &RELAYERS_FUND_ACCOUNT);
+
+			// RELAYER_1 reward is paid, but there are not enough funds to pay other rewards
+			assert_eq!(asset_balance(RELAYER_1), 80);
+			assert_eq!(asset_balance(RELAYER_2), 0);
+			assert_eq!(asset_balance(RELAYER_3), 0);
+			assert_eq!(asset_balance(RELAYERS_FUND_ACCOUNT), TEST_ASSET_MINIMUM_BALANCE + 20);
+		});
+	}
 }
@@ -16,7 +16,7 @@
 
 //! Runtime module that allows sending and receiving messages using lane concept:
 //!
-//! 1) the message is sent using `send_message()` call;
+//! 1) the message is sent using `send_message()` call (or using `MessagesBridge` API by other pallets);
 //! 2) every outbound message is assigned nonce;
 //! 3) the messages are stored in the storage;
 //! 4) external component (relay) delivers messages to bridged chain;
@@ -42,34 +42,56 @@ pub use crate::weights_ext::{
 	EXPECTED_DEFAULT_MESSAGE_LENGTH,
 };
 
-use crate::inbound_lane::{InboundLane, InboundLaneStorage};
+use crate::inbound_lane::{InboundLane, InboundLaneStorage, ReceivalResult};
+use crate::migration::StorageVersion;
 use crate::outbound_lane::{OutboundLane, OutboundLaneStorage};
 use crate::weights::WeightInfo;
 
 use bp_messages::{
-	source_chain::{LaneMessageVerifier, MessageDeliveryAndDispatchPayment, RelayersRewards, TargetHeaderChain},
-	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages, SourceHeaderChain},
-	total_unrewarded_messages, InboundLaneData, LaneId, MessageData, MessageKey, MessageNonce, OperatingMode,
-	OutboundLaneData, Parameter as MessagesParameter, UnrewardedRelayersState,
+	source_chain::{
+		LaneMessageVerifier, MessageDeliveryAndDispatchPayment, MessagesBridge, OnDeliveryConfirmed, RelayersRewards,
+		TargetHeaderChain,
+	},
+	target_chain::{
+		DispatchMessage, DispatchMessageData, MessageDispatch, ProvedLaneMessages, ProvedMessages, SourceHeaderChain,
+	},
+	DispatchMode, IdempotencyKey, InboundLaneData, LaneCongestion, LaneId, LaneInfo, LaneOrdering, LaneState,
+	LaneStatistics, MessageData, MessageKey, MessageLimits, MessageNonce, OperatingMode, OutboundLaneData,
+	OutboundMessagesWithStorageKeys, Parameter as MessagesParameter, ProofRejectionReason, SenderQuota,
+	SenderQuotaUsage, UnrewardedRelayersState, VersionedMessagePayload,
 };
 use bp_runtime::Size;
 use codec::{Decode, Encode};
 use frame_support::{
-	decl_error, decl_event, decl_module, decl_storage, ensure,
-	traits::Get,
+	decl_error, decl_event, decl_module, decl_storage,
+	dispatch::DispatchResultWithPostInfo,
+	ensure,
+	traits::{EnsureOrigin, Get},
 	weights::{DispatchClass, Weight},
 	Parameter, StorageMap,
 };
 use frame_system::{ensure_signed, RawOrigin};
 use num_traits::{SaturatingAdd, Zero};
-use sp_runtime::{traits::BadOrigin, DispatchResult};
-use sp_std::{cell::RefCell, cmp::PartialOrd, marker::PhantomData, prelude::*};
+use sp_runtime::{
+	traits::{AtLeast32BitUnsigned, BadOrigin},
+	DispatchError, DispatchResult, FixedPointNumber, FixedU128, Perbill, RuntimeDebug,
+};
+use sp_std::{
+	cell::RefCell,
+	cmp::PartialOrd,
+	collections::{btree_map::BTreeMap, vec_deque::VecDeque},
+	convert::TryFrom,
+	marker::PhantomData,
+	ops::Deref,
+	prelude::*,
+};
 
 mod inbound_lane;
 mod outbound_lane;
 mod weights_ext;
 
 pub mod instant_payments;
+pub mod migration;
 pub mod weights;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -91,6 +113,10 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	///
 	/// All pallet parameters may only be updated either by the root, or by the pallet owner.
 	type Parameter: MessagesParameter;
+	/// Origin that has the same rights as the pallet owner. It may be used to let multisig or
+	/// collective manage the pallet. Root and the `PalletOwner` account are always allowed to
+	/// manage the pallet.
+	type OwnerOrigin: EnsureOrigin<Self::Origin>;
 
 	/// Maximal number of messages that may be pruned during maintenance. Maintenance occurs
 	/// whenever new message is sent. The reason is that if you want to use lane, you should
@@ -103,6 +129,10 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// This constant limits maximal number of entries in the `InboundLaneData::relayers`. Keep
 	/// in mind that the same relayer account may take several (non-consecutive) entries in this
 	/// set.
+	///
+	/// Stored inbound lanes fail to decode if they have more entries than allowed. The storage
+	/// migration refuses to upgrade lanes that are over the bound and halts the pallet, emitting
+	/// the `InboundLaneNotMigrated` event for every such lane.
 	type MaxUnrewardedRelayerEntriesAtInboundLane: Get<MessageNonce>;
 	/// Maximal number of unconfirmed messages at inbound lane. Unconfirmed means that the
 	/// message has been delivered, but either confirmations haven't been delivered back to the
@@ -118,16 +148,22 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	/// that is declaring more messages than this value, will be rejected. Even if these messages are
 	/// from different lanes.
 	type MaxUnconfirmedMessagesAtInboundLane: Get<MessageNonce>;
+	/// Maximal number of relayers that may be allowed to deliver messages over the single lane
+	/// (see `set_lane_relayers`).
+	type MaxLaneRelayers: Get<u32>;
+	/// Maximal number of inbound messages that may be waiting for dispatch at the single lane with
+	/// deferred dispatch mode. Messages that are delivered to the lane with full queue are rejected.
+	type MaxQueuedMessagesAtInboundLane: Get<MessageNonce>;
 
 	/// Payload type of outbound messages. This payload is dispatched on the bridged chain.
 	type OutboundPayload: Parameter + Size;
 	/// Message fee type of outbound messages. This fee is paid on this chain.
-	type OutboundMessageFee: Default + From<u64> + PartialOrd + Parameter + SaturatingAdd + Zero;
+	type OutboundMessageFee: AtLeast32BitUnsigned + Default + From<u64> + PartialOrd + Parameter + SaturatingAdd + Zero;
 
 	/// Payload type of inbound messages. This payload is dispatched on this chain.
-	type InboundPayload: Decode;
+	type InboundPayload: Decode + Encode;
 	/// Message fee type of inbound messages. This fee is paid on the bridged chain.
-	type InboundMessageFee: Decode;
+	type InboundMessageFee: Decode + Encode;
 	/// Identifier of relayer that deliver messages to this chain. Relayer reward is paid on the bridged chain.
 	type InboundRelayer: Parameter;
 
@@ -144,13 +180,53 @@ pub trait Config<I = DefaultInstance>: frame_system::Config {
 	type LaneMessageVerifier: LaneMessageVerifier<Self::AccountId, Self::OutboundPayload, Self::OutboundMessageFee>;
 	/// Message delivery payment.
 	type MessageDeliveryAndDispatchPayment: MessageDeliveryAndDispatchPayment<Self::AccountId, Self::OutboundMessageFee>;
+	/// Handler for messages delivery confirmation.
+	type OnDeliveryConfirmed: OnDeliveryConfirmed;
+	/// Share of the delivery relayers rewards that is paid to the relayer, which has submitted the
+	/// messages delivery confirmation transaction.
+	///
+	/// The share is deducted from rewards of all other relayers, included in the delivery proof, and is
+	/// paid in addition to anything that `MessageDeliveryAndDispatchPayment` pays to the confirmation
+	/// relayer. E.g. to split message fees 80/20 between delivery and confirmation relayers, use
+	/// `Perbill::from_percent(20)`. The relayer that has submitted both transactions receives the
+	/// whole fee.
+	type ConfirmationRelayerRewardShare: Get<Perbill>;
+	/// Number of blocks between updates of lanes fees from relayers bids. At every update, the
+	/// lowest bid of relayers becomes the fee that must be paid by senders of messages over the
+	/// lane. If zero, lanes fees are never updated.
+	type FeeAuctionPeriod: Get<Self::BlockNumber>;
+	/// Account that receives fees of undelivered messages, pruned by the `prune_undelivered_messages`
+	/// call, if they can't be refunded to the message submitter. This happens if the message has been
+	/// sent by root, or if the fee has been increased by other account. If `None`, these fees stay at
+	/// the relayers fund account.
+	type UndeliveredMessagesFeeRecipient: Get<Option<Self::AccountId>>;
+	/// Number of blocks during which the idempotency key, used by the message sender, is remembered.
+	/// Messages that are sent by the same sender with the same idempotency key within this period are
+	/// ignored (see `send_message_with_idempotency_key`). If zero, idempotency keys are never remembered.
+	type IdempotencyKeyLifetime: Get<Self::BlockNumber>;
+	/// Number of queued (sent, but not yet confirmed) messages at the outbound lane, starting from
+	/// which the lane is considered congested. The lane stops being congested when the number of
+	/// queued messages drops below the half of this value. If zero, lanes are never congested.
+	type LaneCongestionThreshold: Get<MessageNonce>;
+	/// Number of the bridged chain header.
+	type BridgedHeaderNumber: Parameter + Copy + Default;
+	/// Number of the best finalized bridged chain header, known to this chain.
+	///
+	/// It is remembered when the message is sent. Bridged chain headers, starting from this one,
+	/// may be used to prove delivery of the message, so they must be kept until the delivery is
+	/// confirmed (see `Pallet::oldest_required_bridged_header`).
+	type BestFinalizedBridgedHeaderNumber: Get<Self::BridgedHeaderNumber>;
 
 	// Types that are used by inbound_lane (on target chain).
 
 	/// Source header chain, as it is represented on target chain.
 	type SourceHeaderChain: SourceHeaderChain<Self::InboundMessageFee>;
 	/// Message dispatch.
-	type MessageDispatch: MessageDispatch<Self::InboundMessageFee, DispatchPayload = Self::InboundPayload>;
+	type MessageDispatch: MessageDispatch<
+		Self::AccountId,
+		Self::InboundMessageFee,
+		DispatchPayload = Self::InboundPayload,
+	>;
 }
 
 /// Shortcut to messages proof type for Config.
@@ -161,6 +237,11 @@ type MessagesDeliveryProofOf<T, I> = <<T as Config<I>>::TargetHeaderChain as Tar
 	<T as Config<I>>::OutboundPayload,
 	<T as frame_system::Config>::AccountId,
 >>::MessagesDeliveryProof;
+/// Shortcut to messages delivery proof of several lanes type for Config.
+type MessagesDeliveryBatchProofOf<T, I> = <<T as Config<I>>::TargetHeaderChain as TargetHeaderChain<
+	<T as Config<I>>::OutboundPayload,
+	<T as frame_system::Config>::AccountId,
+>>::MessagesDeliveryBatchProof;
 
 decl_error! {
 	pub enum Error for Pallet<T: Config<I>, I: Instance> {
@@ -172,20 +253,97 @@ decl_error! {
 		MessageRejectedByLaneVerifier,
 		/// Submitter has failed to pay fee for delivering and dispatching messages.
 		FailedToWithdrawMessageFee,
-		/// The transaction brings too many messages.
+		/// The transaction brings too many messages (the messages proof is oversized).
 		TooManyMessagesInTheProof,
-		/// Invalid messages has been submitted.
+		/// Invalid messages has been submitted and there's no more specific error for that.
 		InvalidMessagesProof,
 		/// Invalid messages dispatch weight has been declared by the relayer.
 		InvalidMessagesDispatchWeight,
-		/// Invalid messages delivery proof has been submitted.
+		/// Invalid messages delivery proof has been submitted and there's no more specific error for that.
 		InvalidMessagesDeliveryProof,
 		/// The relayer has declared invalid unrewarded relayers state in the `receive_messages_delivery_proof` call.
 		InvalidUnrewardedRelayersState,
 		/// The message someone is trying to work with (i.e. increase fee) is already-delivered.
 		MessageIsAlreadyDelivered,
 		/// The message someone is trying to work with (i.e. increase fee) is not yet sent.
-		MessageIsNotYetSent
+		MessageIsNotYetSent,
+		/// The bridged header, used to build the proof, is unknown to this chain.
+		UnknownBridgedHeader,
+		/// The storage proof doesn't match the storage root of the bridged header.
+		StorageRootMismatch,
+		/// The messages proof contains neither messages, nor outbound lane state.
+		EmptyMessagesProof,
+		/// The number of messages in the proof doesn't match the declared `messages_count`.
+		MessagesCountMismatch,
+		/// The message is missing from the messages proof.
+		MissingMessageInTheProof,
+		/// The message from the messages proof can't be decoded.
+		FailedToDecodeMessage,
+		/// The lane state is missing from the proof.
+		MissingLaneStateInTheProof,
+		/// The lane state from the proof can't be decoded.
+		FailedToDecodeLaneState,
+		/// None of proved messages has been accepted, because there's a gap between the latest
+		/// delivered nonce and nonces of proved messages.
+		MessagesNonceGap,
+		/// None of proved messages has been accepted, because there are too many unrewarded relayer
+		/// entries at the inbound lane.
+		TooManyUnrewardedRelayerEntries,
+		/// None of proved messages has been accepted, because there are too many unconfirmed
+		/// messages at the inbound lane.
+		TooManyUnconfirmedMessages,
+		/// The storage proof has duplicate or unused nodes.
+		ExtraNodesInTheProof,
+		/// The lane is not accepting new messages, because it is closing or closed.
+		LaneIsNotOpened,
+		/// The lane is closed and all lane operations are rejected.
+		LaneIsClosed,
+		/// The lane can't be switched to the requested state.
+		InvalidLaneStateTransition,
+		/// The lane can't be closed, because it still has undelivered or unconfirmed messages.
+		LaneIsNotDrained,
+		/// Only closed lanes may be reaped.
+		LaneIsNotClosed,
+		/// The transaction submitter is not allowed to deliver messages over the lane.
+		RelayerIsNotAllowed,
+		/// All operations on the lane are halted.
+		LaneIsHalted,
+		/// The operation may only be performed on the halted lane.
+		LaneIsNotHalted,
+		/// The lane is not accepting new outbound messages.
+		LaneIsRejectingOutboundMessages,
+		/// The lane has been already opened by the `open_lane` call.
+		LaneIsAlreadyOpened,
+		/// The lane has not been opened by the `open_lane` call.
+		UnknownLane,
+		/// There are too many pending (sent, but not yet confirmed) messages at the lane.
+		TooManyPendingMessages,
+		/// The message has been sent by other account, or it has been topped up by other account, or
+		/// it has been already cancelled.
+		MessageIsNotCancellable,
+		/// Failed to refund fee of the cancelled message.
+		FailedToRefundMessageFee,
+		/// The message has been cancelled.
+		MessageIsCancelled,
+		/// The message has been sent without TTL, or it has been topped up by other account, or it has
+		/// been already cancelled or expired.
+		MessageIsNotExpirable,
+		/// The message TTL has not ended yet.
+		MessageIsNotExpired,
+		/// The account is not in the set of relayers that are allowed to deliver messages over the lane.
+		UnknownRelayer,
+		/// The set of relayers that are allowed to deliver messages over the lane is too large.
+		TooManyLaneRelayers,
+		/// Message payload is larger than the lane allows.
+		MessageIsTooLarge,
+		/// Message fee is lower than the current fee of the lane.
+		TooLowMessageFee,
+		/// The sender has exceeded its quota of messages at the lane.
+		SenderQuotaExceeded,
+		/// The pallet storage has not been upgraded to the latest version, so it may only be halted.
+		StorageNotMigrated,
+		/// There are too many inbound messages waiting for dispatch at the lane with deferred dispatch mode.
+		TooManyQueuedMessages,
 	}
 }
 
@@ -202,12 +360,143 @@ decl_storage! {
 		///
 		/// Depending on the mode either all, some, or no transactions will be allowed.
 		pub PalletOperatingMode get(fn operating_mode) config(): OperatingMode;
+		/// Version of the pallet storage.
+		///
+		/// It is used to select upgrade paths that are applied to the storage at runtime upgrade
+		/// (see the `migration` module).
+		pub PalletStorageVersion get(fn storage_version) build(|_| StorageVersion::LATEST): StorageVersion;
 		/// Map of lane id => inbound lane data.
-		pub InboundLanes: map hasher(blake2_128_concat) LaneId => InboundLaneData<T::InboundRelayer>;
+		pub InboundLanes: map hasher(blake2_128_concat) LaneId => StoredInboundLaneData<T, I>;
 		/// Map of lane id => outbound lane data.
 		pub OutboundLanes: map hasher(blake2_128_concat) LaneId => OutboundLaneData;
 		/// All queued outbound messages.
 		pub OutboundMessages: map hasher(blake2_128_concat) MessageKey => Option<MessageData<T::OutboundMessageFee>>;
+		/// Accounts that have sent (and paid for) queued outbound messages.
+		///
+		/// The entry is only used to authorize message cancellation, so it is removed when the message
+		/// is cancelled. There are no entries for messages that have been sent by root.
+		pub OutboundMessageSubmitters: map hasher(blake2_128_concat) MessageKey => Option<T::AccountId>;
+		/// Numbers of blocks, starting from which queued outbound messages may be expired.
+		///
+		/// There are only entries for messages that have been sent with TTL. The entry is removed
+		/// when the message is cancelled or expired.
+		pub OutboundMessageExpirations: map hasher(blake2_128_concat) MessageKey => Option<T::BlockNumber>;
+		/// Fees that have been added to queued outbound messages by accounts other than the submitter.
+		///
+		/// Messages that have been topped up by other accounts may not be cancelled or expired, because
+		/// the whole fee would be refunded to the submitter. The entry is removed when the message is
+		/// pruned.
+		pub OutboundMessageFeeTopUps: map hasher(blake2_128_concat) MessageKey => T::OutboundMessageFee;
+		/// Accounts that are refunded when delivery of cancelled (or expired) outbound messages is
+		/// confirmed.
+		///
+		/// The payload of the cancelled message is replaced with the noop payload, but the bridged chain
+		/// may still receive the original message, proved before the cancellation. So the fee is only
+		/// refunded when the bridged chain confirms that the message hasn't been dispatched. Otherwise,
+		/// relayers are rewarded for delivering the message, as usual.
+		pub PendingMessageRefunds: map hasher(blake2_128_concat) MessageKey => Option<T::AccountId>;
+		/// Map of lane id => accounts that are allowed to submit messages proofs for this lane.
+		///
+		/// If there's no entry for the lane, any account may deliver messages over this lane. There
+		/// are at most `MaxLaneRelayers` accounts in the set.
+		pub LaneRelayers: map hasher(blake2_128_concat) LaneId => Option<Vec<T::AccountId>>;
+		/// Accounts that are in the `LaneRelayers` set: lane id => relayer => is allowed.
+		///
+		/// It is used to check whether the relayer is allowed to deliver messages without reading the
+		/// whole set.
+		pub AllowedLaneRelayers: double_map hasher(blake2_128_concat) LaneId, hasher(blake2_128_concat) T::AccountId
+			=> bool;
+		/// Map of lane id => multiplier that is applied to the minimal fee of messages, sent over this lane.
+		///
+		/// If there's no entry for the lane, the multiplier is `1.0`.
+		pub LaneFeeMultipliers: map hasher(blake2_128_concat) LaneId => Option<FixedU128>;
+		/// Map of lane id => operating mode of this lane.
+		///
+		/// If there's no entry for the lane, the lane operates in the `OperatingMode::Normal` mode.
+		/// The pallet-level operating mode (`PalletOperatingMode`) has priority over lane modes.
+		pub LaneOperatingModes: map hasher(blake2_128_concat) LaneId => Option<OperatingMode>;
+		/// Map of lane id => ordering of messages at this lane.
+		///
+		/// If there's no entry for the lane, the lane is ordered.
+		pub LaneOrderings: map hasher(blake2_128_concat) LaneId => Option<LaneOrdering>;
+		/// Map of lane id => mode of inbound messages dispatch at this lane.
+		///
+		/// If there's no entry for the lane, messages are dispatched immediately.
+		pub LaneDispatchModes: map hasher(blake2_128_concat) LaneId => Option<DispatchMode>;
+		/// Inbound messages of lanes with deferred dispatch mode, that are waiting for dispatch:
+		/// (lane id, queue index) => message.
+		pub QueuedInboundMessages: double_map hasher(blake2_128_concat) LaneId, hasher(blake2_128_concat) u64
+			=> Option<QueuedInboundMessage<T::AccountId, T::InboundPayload, T::InboundMessageFee>>;
+		/// Map of lane id => (index of the oldest message, index that will be assigned to the next
+		/// message) of the lane queue in the `QueuedInboundMessages`.
+		///
+		/// If there's no entry for the lane, the lane queue is empty. Number of messages in the lane
+		/// queue is bounded by the `MaxQueuedMessagesAtInboundLane`.
+		pub QueuedInboundMessagesRanges: map hasher(blake2_128_concat) LaneId => Option<(u64, u64)>;
+		/// Lanes that have queued inbound messages, in the order they are served by the dispatcher.
+		pub QueuedInboundLanes: Vec<LaneId>;
+		/// Map of lane id => (nonce of the oldest unconfirmed message, number of the best finalized
+		/// bridged chain header at the time when the oldest unconfirmed message has been sent).
+		///
+		/// If there's no entry for the lane, all sent messages of the lane are confirmed. When delivery
+		/// of the oldest message is confirmed, the header number is kept, because later messages have
+		/// been sent when the best finalized header was not older than this header.
+		pub OldestUnconfirmedMessages: map hasher(blake2_128_concat) LaneId
+			=> Option<(MessageNonce, T::BridgedHeaderNumber)>;
+		/// Map of lane id => limits of messages at this lane.
+		///
+		/// If there's no entry for the lane, only chain-wide limits are applied.
+		pub LaneMessageLimits: map hasher(blake2_128_concat) LaneId => Option<MessageLimits>;
+		/// Bids of relayers for delivering messages over the lane: lane id => relayer => fee.
+		pub LaneFeeBids: double_map hasher(blake2_128_concat) LaneId, hasher(blake2_128_concat) T::AccountId
+			=> Option<T::OutboundMessageFee>;
+		/// Map of lane id => minimal fee that must be paid by senders of messages over the lane.
+		///
+		/// The fee is the lowest relayers bid at the time of last fee update (see `FeeAuctionPeriod`).
+		/// If there's no entry for the lane, the fee is only checked by the `LaneMessageVerifier`.
+		pub LaneRequiredFees: map hasher(blake2_128_concat) LaneId => Option<T::OutboundMessageFee>;
+		/// Map of lane id => statistics of the lane.
+		pub LanesStatistics: map hasher(blake2_128_concat) LaneId => LaneStatistics<T::OutboundMessageFee>;
+		/// Map of lane id => true if the outbound lane is congested (see `LaneCongestionThreshold`).
+		pub CongestedLanes: map hasher(blake2_128_concat) LaneId => bool;
+		/// Map of lane id => quota of messages that a single account may send over the lane.
+		pub LaneSenderQuotas: map hasher(blake2_128_concat) LaneId => Option<SenderQuota<T::BlockNumber>>;
+		/// Usage of the lane sender quota: lane id => sender => usage.
+		pub SenderQuotaUsages: double_map hasher(blake2_128_concat) LaneId, hasher(blake2_128_concat) T::AccountId
+			=> SenderQuotaUsage<T::BlockNumber>;
+		/// Accounts that are not limited by the lane sender quota: lane id => sender => is exempt.
+		pub SenderQuotaExemptions: double_map hasher(blake2_128_concat) LaneId, hasher(blake2_128_concat) T::AccountId
+			=> bool;
+		/// Messages that have been sent with idempotency keys: sender => key => (lane id, nonce, block
+		/// number when the key expires).
+		///
+		/// The entry is removed at the beginning of the block when the key expires.
+		pub SentIdempotencyKeys get(fn sent_idempotency_key):
+			double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) IdempotencyKey
+			=> Option<(LaneId, MessageNonce, T::BlockNumber)>;
+		/// Idempotency keys that expire at given block: block number => (sender, key) pairs.
+		pub IdempotencyKeysExpirations: map hasher(blake2_128_concat) T::BlockNumber
+			=> Vec<(T::AccountId, IdempotencyKey)>;
+		/// Map of lane id => information about the lane that has been opened by the `open_lane` call.
+		///
+		/// Lanes that are missing from this map are still operational - they're implicitly created
+		/// on first use. The entry is removed when the lane is reaped.
+		pub Lanes: map hasher(blake2_128_concat) LaneId => Option<LaneInfo<T::AccountId, T::BlockNumber>>;
+		/// Fee that has been paid for the messages delivery confirmation transaction, which is
+		/// currently being dispatched.
+		///
+		/// The value is set by the signed extension (see `RefundConfirmationTransactionFee` in the
+		/// `bridge-runtime-common` crate) before the transaction is dispatched and is removed right
+		/// after dispatch. When it is set, the confirmation relayer is reimbursed from the fees of
+		/// messages that are confirmed by the transaction.
+		pub ConfirmationTransactionFee: Option<T::OutboundMessageFee>;
+		/// Number of new messages that have been delivered by the current delivery transaction.
+		///
+		/// The value is set to zero by the signed extension (see `RefundDeliveryTransactionFee` in the
+		/// `bridge-runtime-common` crate) before the transaction is dispatched and is removed right
+		/// after dispatch. When it is set, the `receive_messages_proof` call updates it, so that the
+		/// extension knows whether the delivery transaction fee may be refunded from the relayers pot.
+		pub DeliveredMessagesInTransaction: Option<MessageNonce>;
 	}
 	add_extra_genesis {
 		config(phantom): sp_std::marker::PhantomData<I>;
@@ -230,8 +519,65 @@ decl_event!(
 		ParameterUpdated(Parameter),
 		/// Message has been accepted and is waiting to be delivered.
 		MessageAccepted(LaneId, MessageNonce),
+		/// Message with the recently used idempotency key has been ignored. The lane and the nonce
+		/// of the original message are reported.
+		DuplicateMessageIgnored(LaneId, MessageNonce),
+		/// Message has been cancelled. Its fee is refunded to the submitter when the bridged chain
+		/// confirms that the message hasn't been dispatched.
+		MessageCancelled(LaneId, MessageNonce),
+		/// The bridged chain has confirmed that the cancelled (or expired) message hasn't been
+		/// dispatched and its fee has been refunded.
+		MessageFeeRefunded(LaneId, MessageNonce),
+		/// The cancelled (or expired) message has been dispatched by the bridged chain, because it has
+		/// been delivered before the cancellation. Its fee is paid to relayers.
+		MessageDispatchedBeforeCancellation(LaneId, MessageNonce),
+		/// Message TTL has ended. Its fee is refunded to the submitter when the bridged chain confirms
+		/// that the message hasn't been dispatched.
+		MessageExpired(LaneId, MessageNonce),
 		/// Messages in the inclusive range have been delivered and processed by the bridged chain.
 		MessagesDelivered(LaneId, MessageNonce, MessageNonce),
+		/// Messages in the inclusive range have been skipped by relayers at the unordered lane, so
+		/// they are never delivered. Their fees are refunded.
+		MessagesSkipped(LaneId, MessageNonce, MessageNonce),
+		/// State of the lane has been changed.
+		LaneStateChanged(LaneId, LaneState),
+		/// Lane has been opened.
+		LaneOpened(LaneId, AccountId),
+		/// Storage of the closed lane has been removed.
+		LaneReaped(LaneId),
+		/// Undelivered messages in the inclusive range have been pruned and their fees have been refunded.
+		UndeliveredMessagesPruned(LaneId, MessageNonce, MessageNonce),
+		/// Set of relayers that are allowed to deliver messages over the lane has been updated.
+		LaneRelayersUpdated(LaneId),
+		/// Fee multiplier of the lane has been updated.
+		LaneFeeMultiplierUpdated(LaneId),
+		/// Operating mode of the lane has been changed.
+		LaneOperatingModeChanged(LaneId, OperatingMode),
+		/// Ordering of messages at the lane has been changed.
+		LaneOrderingChanged(LaneId, LaneOrdering),
+		/// Mode of inbound messages dispatch at the lane has been changed.
+		LaneDispatchModeChanged(LaneId, DispatchMode),
+		/// Limits of messages at the lane have been changed.
+		LaneMessageLimitsChanged(LaneId, Option<MessageLimits>),
+		/// Relayer has changed its bid for delivering messages over the lane.
+		FeeBidChanged(LaneId, AccountId),
+		/// Quota of messages that a single account may send over the lane has been changed.
+		LaneSenderQuotaChanged(LaneId),
+		/// Account has been exempted from (if `true`), or returned to (if `false`) the lane sender quota.
+		SenderQuotaExemptionChanged(LaneId, AccountId, bool),
+		/// Number of queued messages at the outbound lane has reached the congestion threshold. The
+		/// number of queued messages is reported.
+		LaneCongested(LaneId, MessageNonce),
+		/// Number of queued messages at the outbound lane has dropped below the half of the congestion
+		/// threshold. The number of queued messages is reported.
+		LaneUncongested(LaneId, MessageNonce),
+		/// Queued inbound message has been removed from the queue without dispatch, because its
+		/// dispatch weight exceeds the maximal weight of the queued message.
+		QueuedMessageSkipped(LaneId, MessageNonce),
+		/// Storage migration has been refused, because the inbound lane has more unrewarded relayer
+		/// entries (reported) than the `MaxUnrewardedRelayerEntriesAtInboundLane` allows. The pallet
+		/// is halted until the runtime with larger bound is deployed.
+		InboundLaneNotMigrated(LaneId, MessageNonce),
 		/// Phantom member, never used.
 		Dummy(PhantomData<(AccountId, I)>),
 	}
@@ -242,12 +588,44 @@ decl_module! {
 		/// Deposit one of this module's events by using the default implementation.
 		fn deposit_event() = default;
 
-		/// Ensure runtime invariants.
+		/// Forget expired idempotency keys and update lanes fees from relayers bids.
+		fn on_initialize(block_number: T::BlockNumber) -> Weight {
+			let mut weight = prune_expired_idempotency_keys::<T, I>(block_number);
+
+			let fee_auction_period = T::FeeAuctionPeriod::get();
+			if !fee_auction_period.is_zero() && (block_number % fee_auction_period).is_zero() {
+				weight = weight.saturating_add(update_lanes_fees::<T, I>());
+			}
+
+			weight
+		}
+
+		/// Dispatch queued inbound messages using spare weight of the block.
+		fn on_idle(_block_number: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			if PalletOperatingMode::<I>::get() == OperatingMode::Halted {
+				return T::DbWeight::get().reads(1);
+			}
+
+			dispatch_queued_inbound_messages::<T, I>(remaining_weight)
+		}
+
+		/// Upgrade storage to the latest version and ensure runtime invariants.
 		fn on_runtime_upgrade() -> Weight {
+			let migration_weight = migration::migrate::<T, I>();
 			let reads = T::MessageDeliveryAndDispatchPayment::initialize(
 				&Self::relayer_fund_account_id()
 			);
-			T::DbWeight::get().reads(reads as u64)
+			migration_weight.saturating_add(T::DbWeight::get().reads(reads as u64))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
 		}
 
 		/// Change `PalletOwner`.
@@ -270,10 +648,15 @@ decl_module! {
 
 		/// Halt or resume all/some pallet operations.
 		///
-		/// May only be called either by root, or by `PalletOwner`.
-		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
+		/// May only be called either by root, or by `PalletOwner`. Pallet operations can't be
+		/// resumed until the storage is upgraded to the latest version.
+		#[weight = (T::DbWeight::get().reads_writes(2, 1), DispatchClass::Operational)]
 		pub fn set_operating_mode(origin, operating_mode: OperatingMode) {
 			ensure_owner_or_root::<T, I>(origin)?;
+			ensure!(
+				operating_mode == OperatingMode::Halted || PalletStorageVersion::<I>::get() == StorageVersion::LATEST,
+				Error::<T, I>::StorageNotMigrated,
+			);
 			<PalletOperatingMode<I>>::put(operating_mode);
 			log::info!(
 				target: "runtime::bridge-messages",
@@ -294,1382 +677,5994 @@ decl_module! {
 			Self::deposit_event(RawEvent::ParameterUpdated(parameter));
 		}
 
-		/// Send message over lane.
-		#[weight = T::WeightInfo::send_message_weight(payload)]
-		pub fn send_message(
-			origin,
-			lane_id: LaneId,
-			payload: T::OutboundPayload,
-			delivery_and_dispatch_fee: T::OutboundMessageFee,
-		) -> DispatchResult {
-			ensure_normal_operating_mode::<T, I>()?;
-			let submitter = origin.into().map_err(|_| BadOrigin)?;
-
-			// let's first check if message can be delivered to target chain
-			T::TargetHeaderChain::verify_message(&payload)
-				.map_err(|err| {
-					log::trace!(
-						target: "runtime::bridge-messages",
-						"Message to lane {:?} is rejected by target chain: {:?}",
-						lane_id,
-						err,
-					);
-
-					Error::<T, I>::MessageRejectedByChainVerifier
-				})?;
+		/// Change state of the lane.
+		///
+		/// The state of both inbound and outbound lanes with given id is changed. The lane may only
+		/// be closed when all messages, sent over the lane, are delivered and confirmed and all
+		/// messages, received over the lane, are confirmed at the bridged chain.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(3, 3), DispatchClass::Operational)]
+		pub fn set_lane_state(origin, lane_id: LaneId, lane_state: LaneState) {
+			ensure_owner_or_root::<T, I>(origin)?;
 
-			// now let's enforce any additional lane rules
-			let mut lane = outbound_lane::<T, I>(lane_id);
-			T::LaneMessageVerifier::verify_message(
-				&submitter,
-				&delivery_and_dispatch_fee,
-				&lane_id,
-				&lane.data(),
-				&payload,
-			).map_err(|err| {
-				log::trace!(
-					target: "runtime::bridge-messages",
-					"Message to lane {:?} is rejected by lane verifier: {:?}",
-					lane_id,
-					err,
+			let mut outbound_lane_data = OutboundLanes::<I>::get(&lane_id);
+			let mut inbound_lane_data = InboundLanes::<T, I>::get(&lane_id).into_inner();
+			ensure!(
+				outbound_lane_data.state.can_change_to(lane_state),
+				Error::<T, I>::InvalidLaneStateTransition
+			);
+			if lane_state.is_closed() {
+				ensure!(
+					outbound_lane_data.is_drained() && inbound_lane_data.is_drained(),
+					Error::<T, I>::LaneIsNotDrained
 				);
+			}
 
-				Error::<T, I>::MessageRejectedByLaneVerifier
-			})?;
-
-			// let's withdraw delivery and dispatch fee from submitter
-			T::MessageDeliveryAndDispatchPayment::pay_delivery_and_dispatch_fee(
-				&submitter,
-				&delivery_and_dispatch_fee,
-				&Self::relayer_fund_account_id(),
-			).map_err(|err| {
-				log::trace!(
-					target: "runtime::bridge-messages",
-					"Message to lane {:?} is rejected because submitter {:?} is unable to pay fee {:?}: {:?}",
-					lane_id,
-					submitter,
-					delivery_and_dispatch_fee,
-					err,
-				);
+			outbound_lane_data.state = lane_state;
+			inbound_lane_data.state = lane_state;
+			OutboundLanes::<I>::insert(&lane_id, outbound_lane_data);
+			InboundLanes::<T, I>::insert(
+				&lane_id,
+				StoredInboundLaneData::<T, I>::try_new(inbound_lane_data)
+					.expect("only lane state is changed; relayers entries are read from the storage; qed"),
+			);
 
-				Error::<T, I>::FailedToWithdrawMessageFee
-			})?;
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Setting lane {:?} state to {:?}.",
+				lane_id,
+				lane_state,
+			);
+			Self::deposit_event(RawEvent::LaneStateChanged(lane_id, lane_state));
+		}
 
-			// finally, save message in outbound storage and emit event
-			let encoded_payload = payload.encode();
-			let encoded_payload_len = encoded_payload.len();
-			let nonce = lane.send_message(MessageData {
-				payload: encoded_payload,
-				fee: delivery_and_dispatch_fee,
-			});
-			lane.prune_messages(T::MaxMessagesToPruneAtOnce::get());
+		/// Open new lane.
+		///
+		/// The lane is registered with given owner and limits. The lane owner may later close the
+		/// lane using the `close_lane` call. Closed lane may not be opened again until it is reaped.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(3, 2), DispatchClass::Operational)]
+		pub fn open_lane(origin, lane_id: LaneId, owner: T::AccountId, max_pending_messages: MessageNonce) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			ensure!(!Lanes::<T, I>::contains_key(&lane_id), Error::<T, I>::LaneIsAlreadyOpened);
+			ensure!(
+				OutboundLanes::<I>::get(&lane_id).state.is_accepting_messages(),
+				Error::<T, I>::LaneIsNotOpened
+			);
 
-			log::trace!(
+			log::info!(
 				target: "runtime::bridge-messages",
-				"Accepted message {} to lane {:?}. Message size: {:?}",
-				nonce,
+				"Opening lane {:?} with owner {:?} and at most {} pending messages.",
 				lane_id,
-				encoded_payload_len,
+				owner,
+				max_pending_messages,
+			);
+			Lanes::<T, I>::insert(
+				&lane_id,
+				LaneInfo {
+					owner: owner.clone(),
+					opened_at: frame_system::Pallet::<T>::block_number(),
+					max_pending_messages,
+				},
+			);
+			Self::deposit_event(RawEvent::LaneOpened(lane_id, owner));
+		}
+
+		/// Start closing the lane that has been opened by the `open_lane` call.
+		///
+		/// The lane is switched to the `Closing` state, so no new messages are accepted. Once all
+		/// queued messages are delivered and confirmed, the lane may be closed and reaped using
+		/// `set_lane_state` and `reap_lane` calls.
+		///
+		/// May only be called either by root, or by `PalletOwner`, or by the lane owner.
+		#[weight = (T::DbWeight::get().reads_writes(4, 3), DispatchClass::Operational)]
+		pub fn close_lane(origin, lane_id: LaneId) {
+			ensure!(Lanes::<T, I>::contains_key(&lane_id), Error::<T, I>::UnknownLane);
+			ensure_lane_owner_or_root::<T, I>(origin, &lane_id)?;
+
+			let mut outbound_lane_data = OutboundLanes::<I>::get(&lane_id);
+			let mut inbound_lane_data = InboundLanes::<T, I>::get(&lane_id).into_inner();
+			ensure!(
+				outbound_lane_data.state.can_change_to(LaneState::Closing),
+				Error::<T, I>::InvalidLaneStateTransition
 			);
 
-			Self::deposit_event(RawEvent::MessageAccepted(lane_id, nonce));
+			outbound_lane_data.state = LaneState::Closing;
+			inbound_lane_data.state = LaneState::Closing;
+			OutboundLanes::<I>::insert(&lane_id, outbound_lane_data);
+			InboundLanes::<T, I>::insert(
+				&lane_id,
+				StoredInboundLaneData::<T, I>::try_new(inbound_lane_data)
+					.expect("only lane state is changed; relayers entries are read from the storage; qed"),
+			);
 
-			Ok(())
+			log::info!(target: "runtime::bridge-messages", "Closing lane {:?}.", lane_id);
+			Self::deposit_event(RawEvent::LaneStateChanged(lane_id, LaneState::Closing));
 		}
 
-		/// Pay additional fee for the message.
-		#[weight = T::WeightInfo::increase_message_fee()]
-		pub fn increase_message_fee(
-			origin,
-			lane_id: LaneId,
-			nonce: MessageNonce,
-			additional_fee: T::OutboundMessageFee,
-		) -> DispatchResult {
-			ensure_not_halted::<T, I>()?;
-			// if someone tries to pay for already-delivered message, we're rejecting this intention
-			// (otherwise this additional fee will be locked forever in relayers fund)
-			//
-			// if someone tries to pay for not-yet-sent message, we're rejeting this intention, or
-			// we're risking to have mess in the storage
-			let lane = outbound_lane::<T, I>(lane_id);
-			ensure!(nonce > lane.data().latest_received_nonce, Error::<T, I>::MessageIsAlreadyDelivered);
-			ensure!(nonce <= lane.data().latest_generated_nonce, Error::<T, I>::MessageIsNotYetSent);
+		/// Remove storage of the closed lane.
+		///
+		/// Messages of the lane that are still in the storage are pruned first (at most
+		/// `MaxMessagesToPruneAtOnce` messages per call). Once all messages are pruned, the lane
+		/// data is removed, so the call may need to be repeated.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (
+			T::DbWeight::get().reads_writes(3, 5u64.saturating_add(T::MaxMessagesToPruneAtOnce::get())),
+			DispatchClass::Operational,
+		)]
+		pub fn reap_lane(origin, lane_id: LaneId) {
+			ensure_owner_or_root::<T, I>(origin)?;
 
-			// withdraw additional fee from submitter
-			let submitter = origin.into().map_err(|_| BadOrigin)?;
-			T::MessageDeliveryAndDispatchPayment::pay_delivery_and_dispatch_fee(
-				&submitter,
-				&additional_fee,
-				&Self::relayer_fund_account_id(),
-			).map_err(|err| {
+			let mut lane = outbound_lane::<T, I>(lane_id);
+			ensure!(lane.data().state.is_closed(), Error::<T, I>::LaneIsNotClosed);
+
+			lane.prune_messages(T::MaxMessagesToPruneAtOnce::get());
+			let lane_data = lane.data();
+			if lane_data.oldest_unpruned_nonce <= lane_data.latest_generated_nonce {
 				log::trace!(
 					target: "runtime::bridge-messages",
-					"Submitter {:?} can't pay additional fee {:?} for the message {:?}/{:?}: {:?}",
-					submitter,
-					additional_fee,
+					"Pruned messages of closed lane {:?} up to {}",
 					lane_id,
-					nonce,
-					err,
+					lane_data.oldest_unpruned_nonce,
 				);
+				return Ok(());
+			}
 
-				Error::<T, I>::FailedToWithdrawMessageFee
-			})?;
-
-			// and finally update fee in the storage
-			let message_key = MessageKey { lane_id, nonce };
-			OutboundMessages::<T, I>::mutate(message_key, |message_data| {
-				// saturating_add is fine here - overflow here means that someone controls all
-				// chain funds, which shouldn't ever happen + `pay_delivery_and_dispatch_fee`
-				// above will fail before we reach here
-				let message_data = message_data
-					.as_mut()
-					.expect("the message is sent and not yet delivered; so it is in the storage; qed");
-				message_data.fee = message_data.fee.saturating_add(&additional_fee);
-			});
+			OutboundLanes::<I>::remove(&lane_id);
+			InboundLanes::<T, I>::remove(&lane_id);
+			OldestUnconfirmedMessages::<T, I>::remove(&lane_id);
+			Lanes::<T, I>::remove(&lane_id);
 
-			Ok(())
+			log::info!(target: "runtime::bridge-messages", "Reaped closed lane {:?}.", lane_id);
+			Self::deposit_event(RawEvent::LaneReaped(lane_id));
 		}
 
-		/// Receive messages proof from bridged chain.
+		/// Prune undelivered messages of the halted lane, refunding their fees.
 		///
-		/// The weight of the call assumes that the transaction always brings outbound lane
-		/// state update. Because of that, the submitter (relayer) has no benefit of not including
-		/// this data in the transaction, so reward confirmations lags should be minimal.
-		#[weight = T::WeightInfo::receive_messages_proof_weight(proof, *messages_count, *dispatch_weight)]
-		pub fn receive_messages_proof(
-			origin,
-			relayer_id: T::InboundRelayer,
-			proof: MessagesProofOf<T, I>,
-			messages_count: u32,
-			dispatch_weight: Weight,
-		) -> DispatchResult {
-			ensure_not_halted::<T, I>()?;
-			let _ = ensure_signed(origin)?;
-
-			// reject transactions that are declaring too many messages
+		/// At most `MaxMessagesToPruneAtOnce` messages, starting from the `begin` nonce, are pruned.
+		/// Messages are delivered in order, so pruned messages are not removed from the lane. Instead,
+		/// their payloads are replaced with empty (noop) payloads, exactly like payloads of cancelled
+		/// messages. The fee is refunded to the message submitter, or to the
+		/// `UndeliveredMessagesFeeRecipient` (see its documentation for details).
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (
+			T::DbWeight::get().reads_writes(
+				2u64.saturating_add(T::MaxMessagesToPruneAtOnce::get().saturating_mul(4)),
+				T::MaxMessagesToPruneAtOnce::get().saturating_mul(4),
+			),
+			DispatchClass::Operational,
+		)]
+		pub fn prune_undelivered_messages(origin, lane_id: LaneId, begin: MessageNonce) {
+			ensure_owner_or_root::<T, I>(origin)?;
 			ensure!(
-				MessageNonce::from(messages_count) <= T::MaxUnconfirmedMessagesAtInboundLane::get(),
-				Error::<T, I>::TooManyMessagesInTheProof
+				Self::lane_operating_mode(&lane_id) == OperatingMode::Halted,
+				Error::<T, I>::LaneIsNotHalted
 			);
 
-			// verify messages proof && convert proof into messages
-			let messages = verify_and_decode_messages_proof::<
-				T::SourceHeaderChain,
-				T::InboundMessageFee,
-				T::InboundPayload,
-			>(proof, messages_count)
-				.map_err(|err| {
+			let lane = outbound_lane::<T, I>(lane_id);
+			let lane_data = lane.data();
+			ensure!(begin > lane_data.latest_received_nonce, Error::<T, I>::MessageIsAlreadyDelivered);
+			ensure!(begin <= lane_data.latest_generated_nonce, Error::<T, I>::MessageIsNotYetSent);
+
+			let end = sp_std::cmp::min(
+				begin.saturating_add(T::MaxMessagesToPruneAtOnce::get()).saturating_sub(1),
+				lane_data.latest_generated_nonce,
+			);
+			for nonce in begin..=end {
+				let message_key = MessageKey { lane_id, nonce };
+				if let Err(err) = prune_undelivered_message::<T, I>(&message_key) {
 					log::trace!(
 						target: "runtime::bridge-messages",
-						"Rejecting invalid messages proof: {:?}",
+						"Failed to prune undelivered message {:?}: {:?}",
+						message_key,
 						err,
 					);
+				}
+			}
 
-					Error::<T, I>::InvalidMessagesProof
-				})?;
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Pruned undelivered messages [{}; {}] of lane {:?}.",
+				begin,
+				end,
+				lane_id,
+			);
+			Self::deposit_event(RawEvent::UndeliveredMessagesPruned(lane_id, begin, end));
+		}
 
-			// verify that relayer is paying actual dispatch weight
-			let actual_dispatch_weight: Weight = messages
-				.values()
-				.map(|lane_messages| lane_messages
-					.messages
-					.iter()
-					.map(T::MessageDispatch::dispatch_weight)
-					.fold(0, |sum, weight| sum.saturating_add(&weight))
-				)
-				.fold(0, |sum, weight| sum.saturating_add(weight));
-			if dispatch_weight < actual_dispatch_weight {
-				log::trace!(
-					target: "runtime::bridge-messages",
-					"Rejecting messages proof because of dispatch weight mismatch: declared={}, expected={}",
-					dispatch_weight,
-					actual_dispatch_weight,
+		/// Restrict set of accounts that are allowed to submit messages proofs for the lane.
+		///
+		/// If `relayers` is `None`, any account may deliver messages over the lane (this is the
+		/// default). There may be at most `MaxLaneRelayers` relayers in the set.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = lane_relayers_update_weight::<T, I>(
+			T::MaxLaneRelayers::get(),
+			relayers.as_ref().map(|relayers| relayers.len() as u32).unwrap_or(0),
+		)]
+		pub fn set_lane_relayers(origin, lane_id: LaneId, relayers: Option<Vec<T::AccountId>>) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			if let Some(ref relayers) = relayers {
+				ensure!(
+					relayers.len() <= T::MaxLaneRelayers::get() as usize,
+					Error::<T, I>::TooManyLaneRelayers
 				);
-
-				return Err(Error::<T, I>::InvalidMessagesDispatchWeight.into());
 			}
 
-			// dispatch messages and (optionally) update lane(s) state(s)
-			let mut total_messages = 0;
-			let mut valid_messages = 0;
-			for (lane_id, lane_data) in messages {
-				let mut lane = inbound_lane::<T, I>(lane_id);
+			for relayer in LaneRelayers::<T, I>::take(&lane_id).unwrap_or_default() {
+				AllowedLaneRelayers::<T, I>::remove(&lane_id, &relayer);
+			}
+			match relayers {
+				Some(relayers) => {
+					log::info!(
+						target: "runtime::bridge-messages",
+						"Restricting relayers of lane {:?} to: {:?}",
+						lane_id,
+						relayers,
+					);
 
-				if let Some(lane_state) = lane_data.lane_state {
-					let updated_latest_confirmed_nonce = lane.receive_state_update(lane_state);
-					if let Some(updated_latest_confirmed_nonce) = updated_latest_confirmed_nonce {
-						log::trace!(
-							target: "runtime::bridge-messages",
-							"Received lane {:?} state update: latest_confirmed_nonce={}",
-							lane_id,
-							updated_latest_confirmed_nonce,
-						);
+					let mut unique_relayers = Vec::with_capacity(relayers.len());
+					for relayer in relayers {
+						if !AllowedLaneRelayers::<T, I>::get(&lane_id, &relayer) {
+							AllowedLaneRelayers::<T, I>::insert(&lane_id, &relayer, true);
+							unique_relayers.push(relayer);
+						}
 					}
-				}
-
-				for message in lane_data.messages {
-					debug_assert_eq!(message.key.lane_id, lane_id);
+					LaneRelayers::<T, I>::insert(&lane_id, unique_relayers);
+				},
+				None => {
+					log::info!(
+						target: "runtime::bridge-messages",
+						"Allowing any relayer to deliver messages over lane {:?}",
+						lane_id,
+					);
+				},
+			}
+			Self::deposit_event(RawEvent::LaneRelayersUpdated(lane_id));
+		}
 
-					total_messages += 1;
-					if lane.receive_message::<T::MessageDispatch>(relayer_id.clone(), message.key.nonce, message.data) {
-						valid_messages += 1;
-					}
+		/// Add account to the set of relayers that are allowed to submit messages proofs for the lane.
+		///
+		/// If the set is not yet restricted (see `set_lane_relayers`), then after this call only the
+		/// `relayer` is allowed to deliver messages over the lane.
+		///
+		/// May only be called either by root, or by `PalletOwner`, or by the owner of the lane that
+		/// has been opened by the `open_lane` call.
+		#[weight = lane_relayers_update_weight::<T, I>(0, 1)]
+		pub fn add_lane_relayer(origin, lane_id: LaneId, relayer: T::AccountId) {
+			ensure_lane_owner_or_root::<T, I>(origin, &lane_id)?;
+			if !AllowedLaneRelayers::<T, I>::get(&lane_id, &relayer) {
+				LaneRelayers::<T, I>::try_mutate(&lane_id, |relayers| {
+					let relayers = relayers.get_or_insert_with(Vec::new);
+					ensure!(
+						relayers.len() < T::MaxLaneRelayers::get() as usize,
+						Error::<T, I>::TooManyLaneRelayers
+					);
+					relayers.push(relayer.clone());
+					Ok::<_, Error<T, I>>(())
+				})?;
+				AllowedLaneRelayers::<T, I>::insert(&lane_id, &relayer, true);
+			}
+
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Allowing relayer {:?} to deliver messages over lane {:?}",
+				relayer,
+				lane_id,
+			);
+			Self::deposit_event(RawEvent::LaneRelayersUpdated(lane_id));
+		}
+
+		/// Remove account from the set of relayers that are allowed to submit messages proofs for the lane.
+		///
+		/// If the last relayer is removed, then nobody is allowed to deliver messages over the lane
+		/// (use `set_lane_relayers` to lift the restriction).
+		///
+		/// May only be called either by root, or by `PalletOwner`, or by the owner of the lane that
+		/// has been opened by the `open_lane` call.
+		#[weight = lane_relayers_update_weight::<T, I>(1, 0)]
+		pub fn remove_lane_relayer(origin, lane_id: LaneId, relayer: T::AccountId) {
+			ensure_lane_owner_or_root::<T, I>(origin, &lane_id)?;
+			ensure!(AllowedLaneRelayers::<T, I>::get(&lane_id, &relayer), Error::<T, I>::UnknownRelayer);
+			LaneRelayers::<T, I>::mutate(&lane_id, |relayers| {
+				if let Some(relayers) = relayers.as_mut() {
+					relayers.retain(|allowed_relayer| *allowed_relayer != relayer);
 				}
+			});
+			AllowedLaneRelayers::<T, I>::remove(&lane_id, &relayer);
+
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Disallowing relayer {:?} to deliver messages over lane {:?}",
+				relayer,
+				lane_id,
+			);
+			Self::deposit_event(RawEvent::LaneRelayersUpdated(lane_id));
+		}
+
+		/// Update multiplier that is applied to the minimal fee of messages, sent over the lane.
+		///
+		/// If `multiplier` is `None`, the default multiplier (`1.0`) is used.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
+		pub fn set_lane_fee_multiplier(origin, lane_id: LaneId, multiplier: Option<FixedU128>) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			match multiplier {
+				Some(multiplier) => {
+					log::info!(
+						target: "runtime::bridge-messages",
+						"Setting fee multiplier of lane {:?} to: {:?}",
+						lane_id,
+						multiplier,
+					);
+					LaneFeeMultipliers::<I>::insert(&lane_id, multiplier);
+				},
+				None => {
+					log::info!(
+						target: "runtime::bridge-messages",
+						"Resetting fee multiplier of lane {:?}",
+						lane_id,
+					);
+					LaneFeeMultipliers::<I>::remove(&lane_id);
+				},
 			}
+			Self::deposit_event(RawEvent::LaneFeeMultiplierUpdated(lane_id));
+		}
 
-			log::trace!(
+		/// Halt or resume operations on the single lane.
+		///
+		/// Operations on other lanes are not affected.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational)]
+		pub fn set_lane_operating_mode(origin, lane_id: LaneId, operating_mode: OperatingMode) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			log::info!(
 				target: "runtime::bridge-messages",
-				"Received messages: total={}, valid={}",
-				total_messages,
-				valid_messages,
+				"Setting operating mode of lane {:?} to: {:?}",
+				lane_id,
+				operating_mode,
+			);
+			if operating_mode == OperatingMode::Normal {
+				LaneOperatingModes::<I>::remove(&lane_id);
+			} else {
+				LaneOperatingModes::<I>::insert(&lane_id, operating_mode);
+			}
+			Self::deposit_event(RawEvent::LaneOperatingModeChanged(lane_id, operating_mode));
+		}
+
+		/// Change ordering of messages at the lane.
+		///
+		/// The ordering only affects inbound lane, but it should be changed at both sides of the
+		/// bridge, so that relayers of the other side know that they may skip messages.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 2), DispatchClass::Operational)]
+		pub fn set_lane_ordering(origin, lane_id: LaneId, ordering: LaneOrdering) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Setting ordering of lane {:?} to: {:?}",
+				lane_id,
+				ordering,
+			);
+			if ordering == LaneOrdering::Ordered {
+				LaneOrderings::<I>::remove(&lane_id);
+			} else {
+				LaneOrderings::<I>::insert(&lane_id, ordering);
+			}
+			Self::deposit_event(RawEvent::LaneOrderingChanged(lane_id, ordering));
+		}
+
+		/// Change mode of inbound messages dispatch at the lane.
+		///
+		/// Messages that are already queued are dispatched even if the lane is switched back to the
+		/// immediate dispatch.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[weight = (T::DbWeight::get().reads_writes(1, 2), DispatchClass::Operational)]
+		pub fn set_lane_dispatch_mode(origin, lane_id: LaneId, dispatch_mode: DispatchMode) {
+			ensure_owner_or_root::<T, I>(origin)?;
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Setting dispatch mode of lane {:?} to: {:?}",
+				lane_id,
+				dispatch_mode,
+			);
+			if dispatch_mode == DispatchMode::Immediate {
+				LaneDispatchModes::<I>::remove(&lane_id);
+			} else {
+				LaneDispatchModes::<I>::insert(&lane_id, dispatch_mode);
+			}
+			Self::deposit_event(RawEvent::LaneDispatchModeChanged(lane_id, dispatch_mode));
+		}
+
+		/// Dispatch inbound messages that have been queued at lanes with deferred dispatch mode.
+		///
+		/// Lanes are served in round-robin fashion and messages of every lane are dispatched in the
+		/// order they have been received, while the `dispatch_weight` is enough to read and dispatch
+		/// the next message. Anyone may call this function - e.g. the message sender may use it when
+		/// blocks have no spare weight to dispatch queued messages.
+		#[weight = T::DbWeight::get().reads_writes(1, 1).saturating_add(*dispatch_weight)]
+		pub fn dispatch_queued_messages(origin, dispatch_weight: Weight) -> DispatchResultWithPostInfo {
+			ensure_not_halted::<T, I>()?;
+			let _ = ensure_signed(origin)?;
+
+			let max_weight = T::DbWeight::get().reads_writes(1, 1).saturating_add(dispatch_weight);
+			let actual_weight = dispatch_queued_inbound_messages::<T, I>(max_weight);
+
+			Ok(Some(actual_weight).into())
+		}
+
+		/// Change limits of messages at the lane.
+		///
+		/// The payload size limit is applied to outbound messages and the dispatch weight limit is
+		/// applied to inbound messages of the lane. If `limits` is `None`, only chain-wide limits
+		/// are applied (this is the default).
+		///
+		/// May only be called either by root, or by `PalletOwner`, or by the owner of the lane that
+		/// has been opened by the `open_lane` call.
+		#[weight = (T::DbWeight::get().reads_writes(2, 1), DispatchClass::Operational)]
+		pub fn set_lane_message_limits(origin, lane_id: LaneId, limits: Option<MessageLimits>) {
+			ensure_lane_owner_or_root::<T, I>(origin, &lane_id)?;
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Setting message limits of lane {:?} to: {:?}",
+				lane_id,
+				limits,
+			);
+			match limits {
+				Some(limits) => LaneMessageLimits::<I>::insert(&lane_id, limits),
+				None => LaneMessageLimits::<I>::remove(&lane_id),
+			}
+			Self::deposit_event(RawEvent::LaneMessageLimitsChanged(lane_id, limits));
+		}
+
+		/// Change quota of messages that a single account may send over the lane.
+		///
+		/// If `quota` is `None`, accounts may send any number of messages over the lane (this is the
+		/// default). Messages that have been sent within the current quota period are still counted
+		/// after the change.
+		///
+		/// May only be called either by root, or by `PalletOwner`, or by the owner of the lane that
+		/// has been opened by the `open_lane` call.
+		#[weight = (T::DbWeight::get().reads_writes(2, 1), DispatchClass::Operational)]
+		pub fn set_lane_sender_quota(origin, lane_id: LaneId, quota: Option<SenderQuota<T::BlockNumber>>) {
+			ensure_lane_owner_or_root::<T, I>(origin, &lane_id)?;
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Setting sender quota of lane {:?} to: {:?}",
+				lane_id,
+				quota,
+			);
+			match quota {
+				Some(quota) => LaneSenderQuotas::<T, I>::insert(&lane_id, quota),
+				None => LaneSenderQuotas::<T, I>::remove(&lane_id),
+			}
+			Self::deposit_event(RawEvent::LaneSenderQuotaChanged(lane_id));
+		}
+
+		/// Exempt account from the lane sender quota (if `is_exempt` is `true`), or return it back
+		/// under the quota (if `is_exempt` is `false`).
+		///
+		/// May only be called either by root, or by `PalletOwner`, or by the owner of the lane that
+		/// has been opened by the `open_lane` call.
+		#[weight = (T::DbWeight::get().reads_writes(2, 1), DispatchClass::Operational)]
+		pub fn set_sender_quota_exemption(origin, lane_id: LaneId, sender: T::AccountId, is_exempt: bool) {
+			ensure_lane_owner_or_root::<T, I>(origin, &lane_id)?;
+			log::info!(
+				target: "runtime::bridge-messages",
+				"Setting sender quota exemption of {:?} at lane {:?} to: {}",
+				sender,
+				lane_id,
+				is_exempt,
 			);
+			if is_exempt {
+				SenderQuotaExemptions::<T, I>::insert(&lane_id, &sender, true);
+			} else {
+				SenderQuotaExemptions::<T, I>::remove(&lane_id, &sender);
+			}
+			Self::deposit_event(RawEvent::SenderQuotaExemptionChanged(lane_id, sender, is_exempt));
+		}
+
+		/// Send message over lane.
+		#[weight = T::WeightInfo::send_message_weight(payload).saturating_add(T::DbWeight::get().reads_writes(2, 1))]
+		pub fn send_message(
+			origin,
+			lane_id: LaneId,
+			payload: T::OutboundPayload,
+			delivery_and_dispatch_fee: T::OutboundMessageFee,
+		) -> DispatchResult {
+			let submitter = origin.into().map_err(|_| BadOrigin)?;
+			send_message::<T, I>(submitter, lane_id, payload, delivery_and_dispatch_fee, None).map(drop)
+		}
+
+		/// Send message over lane, limiting its lifetime.
+		///
+		/// If the message delivery isn't confirmed within `ttl` blocks, anyone may expire the message
+		/// using the `expire_message` call. The fee of the expired message is refunded to the submitter
+		/// once the bridged chain confirms that the message hasn't been dispatched.
+		#[weight = T::WeightInfo::send_message_weight(payload).saturating_add(T::DbWeight::get().reads_writes(2, 2))]
+		pub fn send_message_with_ttl(
+			origin,
+			lane_id: LaneId,
+			payload: T::OutboundPayload,
+			delivery_and_dispatch_fee: T::OutboundMessageFee,
+			ttl: T::BlockNumber,
+		) -> DispatchResult {
+			let submitter = origin.into().map_err(|_| BadOrigin)?;
+			send_message::<T, I>(submitter, lane_id, payload, delivery_and_dispatch_fee, Some(ttl)).map(drop)
+		}
+
+		/// Send message over lane, unless the message with the same idempotency key has been recently
+		/// sent by the same account.
+		///
+		/// If the sender has used the same key within last `IdempotencyKeyLifetime` blocks, the message
+		/// is not queued and the fee is not paid. Instead, the `DuplicateMessageIgnored` event with the
+		/// nonce of the original message is emitted. This protects senders from double-sending messages
+		/// when transactions are retried.
+		#[weight = T::WeightInfo::send_message_weight(payload).saturating_add(T::DbWeight::get().reads_writes(4, 3))]
+		pub fn send_message_with_idempotency_key(
+			origin,
+			lane_id: LaneId,
+			payload: T::OutboundPayload,
+			delivery_and_dispatch_fee: T::OutboundMessageFee,
+			idempotency_key: IdempotencyKey,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let current_block = frame_system::Pallet::<T>::block_number();
+			if let Some((sent_lane_id, sent_nonce, expires_at)) = Self::sent_idempotency_key(&sender, idempotency_key) {
+				if current_block < expires_at {
+					log::trace!(
+						target: "runtime::bridge-messages",
+						"Ignoring message from {:?} with idempotency key {:?}. It has been sent as {:?}/{}",
+						sender,
+						idempotency_key,
+						sent_lane_id,
+						sent_nonce,
+					);
+
+					Self::deposit_event(RawEvent::DuplicateMessageIgnored(sent_lane_id, sent_nonce));
+					return Ok(());
+				}
+			}
+
+			let nonce = send_message::<T, I>(
+				RawOrigin::Signed(sender.clone()),
+				lane_id,
+				payload,
+				delivery_and_dispatch_fee,
+				None,
+			)?;
+
+			let key_lifetime = T::IdempotencyKeyLifetime::get();
+			if !key_lifetime.is_zero() {
+				let expires_at = sp_runtime::traits::Saturating::saturating_add(current_block, key_lifetime);
+				SentIdempotencyKeys::<T, I>::insert(&sender, idempotency_key, (lane_id, nonce, expires_at));
+				IdempotencyKeysExpirations::<T, I>::mutate(expires_at, |keys| keys.push((sender, idempotency_key)));
+			}
 
 			Ok(())
 		}
 
-		/// Receive messages delivery proof from bridged chain.
-		#[weight = T::WeightInfo::receive_messages_delivery_proof_weight(proof, relayers_state)]
-		pub fn receive_messages_delivery_proof(
+		/// Pay additional fee for the message.
+		///
+		/// May be called by any account, not only by the message submitter. E.g. the receiving party
+		/// may use it to speed up delivery of the underpriced message. Once the message has been topped
+		/// up by other account, it may not be cancelled or expired by its submitter. Cancelled messages
+		/// may not be topped up.
+		#[weight = T::WeightInfo::increase_message_fee().saturating_add(T::DbWeight::get().reads(1))]
+		pub fn increase_message_fee(
 			origin,
-			proof: MessagesDeliveryProofOf<T, I>,
-			relayers_state: UnrewardedRelayersState,
+			lane_id: LaneId,
+			nonce: MessageNonce,
+			additional_fee: T::OutboundMessageFee,
 		) -> DispatchResult {
 			ensure_not_halted::<T, I>()?;
+			ensure_lane_not_halted::<T, I>(&lane_id)?;
+			// if someone tries to pay for already-delivered message, we're rejecting this intention
+			// (otherwise this additional fee will be locked forever in relayers fund)
+			//
+			// if someone tries to pay for not-yet-sent message, we're rejeting this intention, or
+			// we're risking to have mess in the storage
+			let lane = outbound_lane::<T, I>(lane_id);
+			ensure!(nonce > lane.data().latest_received_nonce, Error::<T, I>::MessageIsAlreadyDelivered);
+			ensure!(nonce <= lane.data().latest_generated_nonce, Error::<T, I>::MessageIsNotYetSent);
 
-			let confirmation_relayer = ensure_signed(origin)?;
-			let (lane_id, lane_data) = T::TargetHeaderChain::verify_messages_delivery_proof(proof).map_err(|err| {
+			// the whole fee of the cancelled message is refunded to its submitter
+			ensure!(
+				!PendingMessageRefunds::<T, I>::contains_key(MessageKey { lane_id, nonce }),
+				Error::<T, I>::MessageIsCancelled
+			);
+
+			// withdraw additional fee from submitter
+			let submitter = origin.into().map_err(|_| BadOrigin)?;
+			T::MessageDeliveryAndDispatchPayment::pay_delivery_and_dispatch_fee(
+				&submitter,
+				&additional_fee,
+				&Self::relayer_fund_account_id(),
+			).map_err(|err| {
 				log::trace!(
 					target: "runtime::bridge-messages",
-					"Rejecting invalid messages delivery proof: {:?}",
+					"Submitter {:?} can't pay additional fee {:?} for the message {:?}/{:?}: {:?}",
+					submitter,
+					additional_fee,
+					lane_id,
+					nonce,
 					err,
 				);
 
-				Error::<T, I>::InvalidMessagesDeliveryProof
+				Error::<T, I>::FailedToWithdrawMessageFee
 			})?;
 
-			// verify that the relayer has declared correct `lane_data::relayers` state
-			// (we only care about total number of entries and messages, because this affects call weight)
-			ensure!(
-				total_unrewarded_messages(&lane_data.relayers)
-					.unwrap_or(MessageNonce::MAX) == relayers_state.total_messages
-					&& lane_data.relayers.len() as MessageNonce == relayers_state.unrewarded_relayer_entries,
-				Error::<T, I>::InvalidUnrewardedRelayersState
-			);
-
-			// mark messages as delivered
-			let mut lane = outbound_lane::<T, I>(lane_id);
-			let mut relayers_rewards: RelayersRewards<_, T::OutboundMessageFee> = RelayersRewards::new();
-			let last_delivered_nonce = lane_data.last_delivered_nonce();
-			let received_range = lane.confirm_delivery(last_delivered_nonce);
-			if let Some(received_range) = received_range {
-				Self::deposit_event(RawEvent::MessagesDelivered(lane_id, received_range.0, received_range.1));
-
-				// remember to reward relayers that have delivered messages
-				// this loop is bounded by `T::MaxUnrewardedRelayerEntriesAtInboundLane` on the bridged chain
-				for (nonce_low, nonce_high, relayer) in lane_data.relayers {
-					let nonce_begin = sp_std::cmp::max(nonce_low, received_range.0);
-					let nonce_end = sp_std::cmp::min(nonce_high, received_range.1);
-
-					// loop won't proceed if current entry is ahead of received range (begin > end).
-					// this loop is bound by `T::MaxUnconfirmedMessagesAtInboundLane` on the bridged chain
-					let mut relayer_reward = relayers_rewards.entry(relayer).or_default();
-					for nonce in nonce_begin..nonce_end + 1 {
-						let message_data = OutboundMessages::<T, I>::get(MessageKey {
-							lane_id,
-							nonce,
-						}).expect("message was just confirmed; we never prune unconfirmed messages; qed");
-						relayer_reward.reward = relayer_reward.reward.saturating_add(&message_data.fee);
-						relayer_reward.messages += 1;
-					}
-				}
+			// and finally update fee in the storage
+			let message_key = MessageKey { lane_id, nonce };
+			let is_paid_by_submitter = match submitter {
+				RawOrigin::Signed(ref submitter) => {
+					OutboundMessageSubmitters::<T, I>::get(&message_key).as_ref() == Some(submitter)
+				},
+				_ => false,
+			};
+			if !is_paid_by_submitter {
+				OutboundMessageFeeTopUps::<T, I>::mutate(&message_key, |top_ups| {
+					*top_ups = top_ups.saturating_add(&additional_fee);
+				});
 			}
+			OutboundMessages::<T, I>::mutate(message_key, |message_data| {
+				// saturating_add is fine here - overflow here means that someone controls all
+				// chain funds, which shouldn't ever happen + `pay_delivery_and_dispatch_fee`
+				// above will fail before we reach here
+				let message_data = message_data
+					.as_mut()
+					.expect("the message is sent and not yet delivered; so it is in the storage; qed");
+				message_data.fee = message_data.fee.saturating_add(&additional_fee);
+			});
 
-			// if some new messages have been confirmed, reward relayers
-			if !relayers_rewards.is_empty() {
-				let relayer_fund_account = Self::relayer_fund_account_id();
-				<T as Config<I>>::MessageDeliveryAndDispatchPayment::pay_relayers_rewards(
-					&confirmation_relayer,
-					relayers_rewards,
-					&relayer_fund_account,
-				);
-			}
+			Ok(())
+		}
+
+		/// Cancel the message that has not yet been delivered to the bridged chain.
+		///
+		/// Messages are delivered in order, so the cancelled message is not removed from the lane.
+		/// Instead, its payload is replaced with an empty (noop) payload, which is delivered to the
+		/// bridged chain as any other message, but is never dispatched. The bridged chain may still
+		/// receive the original message, if it has been proved before the cancellation. So the
+		/// delivery and dispatch fee is refunded to the submitter only when the bridged chain confirms
+		/// that the message hasn't been dispatched. Relayers are not rewarded for delivering such
+		/// message. The weight of this call includes the cost of this refund.
+		///
+		/// May only be called by the account that has sent the message. Messages that have been topped
+		/// up by other accounts (see `increase_message_fee`) can't be cancelled.
+		#[weight = T::DbWeight::get().reads_writes(8, 7)]
+		pub fn cancel_message(origin, lane_id: LaneId, nonce: MessageNonce) -> DispatchResult {
+			ensure_not_halted::<T, I>()?;
+			ensure_lane_not_halted::<T, I>(&lane_id)?;
+			let submitter = ensure_signed(origin)?;
+
+			// the message that has been already delivered (or is not yet sent) can't be cancelled
+			let lane = outbound_lane::<T, I>(lane_id);
+			ensure!(nonce > lane.data().latest_received_nonce, Error::<T, I>::MessageIsAlreadyDelivered);
+			ensure!(nonce <= lane.data().latest_generated_nonce, Error::<T, I>::MessageIsNotYetSent);
+
+			let message_key = MessageKey { lane_id, nonce };
+			ensure!(
+				OutboundMessageSubmitters::<T, I>::get(&message_key).as_ref() == Some(&submitter),
+				Error::<T, I>::MessageIsNotCancellable
+			);
+			ensure!(
+				OutboundMessageFeeTopUps::<T, I>::get(&message_key).is_zero(),
+				Error::<T, I>::MessageIsNotCancellable
+			);
+			cancel_outbound_message::<T, I>(&message_key, Some(submitter.clone()));
 
 			log::trace!(
 				target: "runtime::bridge-messages",
-				"Received messages delivery proof up to (and including) {} at lane {:?}",
-				last_delivered_nonce,
+				"Message {:?}/{} has been cancelled by {:?}",
 				lane_id,
+				nonce,
+				submitter,
 			);
+			Self::deposit_event(RawEvent::MessageCancelled(lane_id, nonce));
 
 			Ok(())
 		}
-	}
-}
 
-impl<T: Config<I>, I: Instance> Pallet<T, I> {
-	/// Get stored data of the outbound message with given nonce.
-	pub fn outbound_message_data(lane: LaneId, nonce: MessageNonce) -> Option<MessageData<T::OutboundMessageFee>> {
-		OutboundMessages::<T, I>::get(MessageKey { lane_id: lane, nonce })
-	}
+		/// Expire the message which TTL has ended before it has been delivered to the bridged chain.
+		///
+		/// The expired message is replaced with an empty (noop) payload, exactly like the cancelled
+		/// message. The delivery and dispatch fee is refunded to the submitter when the bridged chain
+		/// confirms that the message hasn't been dispatched (see `cancel_message` for details). Messages
+		/// that have been sent by root have no submitter, so their fee is paid to relayers, as usual.
+		/// Messages that have been topped up by accounts other than the submitter never expire.
+		///
+		/// May be called by any account.
+		#[weight = T::DbWeight::get().reads_writes(9, 7)]
+		pub fn expire_message(origin, lane_id: LaneId, nonce: MessageNonce) -> DispatchResult {
+			ensure_not_halted::<T, I>()?;
+			ensure_lane_not_halted::<T, I>(&lane_id)?;
+			ensure_signed(origin)?;
 
-	/// Get nonce of latest generated message at given outbound lane.
-	pub fn outbound_latest_generated_nonce(lane: LaneId) -> MessageNonce {
-		OutboundLanes::<I>::get(&lane).latest_generated_nonce
-	}
+			// the message that has been already delivered (or is not yet sent) can't be expired
+			let lane = outbound_lane::<T, I>(lane_id);
+			ensure!(nonce > lane.data().latest_received_nonce, Error::<T, I>::MessageIsAlreadyDelivered);
+			ensure!(nonce <= lane.data().latest_generated_nonce, Error::<T, I>::MessageIsNotYetSent);
 
-	/// Get nonce of latest confirmed message at given outbound lane.
-	pub fn outbound_latest_received_nonce(lane: LaneId) -> MessageNonce {
-		OutboundLanes::<I>::get(&lane).latest_received_nonce
-	}
+			let message_key = MessageKey { lane_id, nonce };
+			let expires_at = OutboundMessageExpirations::<T, I>::get(&message_key)
+				.ok_or(Error::<T, I>::MessageIsNotExpirable)?;
+			ensure!(
+				OutboundMessageFeeTopUps::<T, I>::get(&message_key).is_zero(),
+				Error::<T, I>::MessageIsNotExpirable
+			);
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= expires_at,
+				Error::<T, I>::MessageIsNotExpired
+			);
+			let submitter = OutboundMessageSubmitters::<T, I>::get(&message_key);
+			cancel_outbound_message::<T, I>(&message_key, submitter);
 
-	/// Get nonce of latest received message at given inbound lane.
-	pub fn inbound_latest_received_nonce(lane: LaneId) -> MessageNonce {
-		InboundLanes::<T, I>::get(&lane).last_delivered_nonce()
-	}
-
-	/// Get nonce of latest confirmed message at given inbound lane.
-	pub fn inbound_latest_confirmed_nonce(lane: LaneId) -> MessageNonce {
-		InboundLanes::<T, I>::get(&lane).last_confirmed_nonce
-	}
+			log::trace!(
+				target: "runtime::bridge-messages",
+				"Message {:?}/{} has expired at block {:?}",
+				lane_id,
+				nonce,
+				expires_at,
+			);
+			Self::deposit_event(RawEvent::MessageExpired(lane_id, nonce));
 
-	/// Get state of unrewarded relayers set.
-	pub fn inbound_unrewarded_relayers_state(lane: bp_messages::LaneId) -> bp_messages::UnrewardedRelayersState {
-		let relayers = InboundLanes::<T, I>::get(&lane).relayers;
-		bp_messages::UnrewardedRelayersState {
-			unrewarded_relayer_entries: relayers.len() as _,
-			messages_in_oldest_entry: relayers.front().map(|(begin, end, _)| 1 + end - begin).unwrap_or(0),
-			total_messages: total_unrewarded_messages(&relayers).unwrap_or(MessageNonce::MAX),
+			Ok(())
 		}
-	}
 
-	/// AccountId of the shared relayer fund account.
-	///
-	/// This account is passed to `MessageDeliveryAndDispatchPayment` trait, and depending
-	/// on the implementation it can be used to store relayers rewards.
-	/// See [InstantCurrencyPayments] for a concrete implementation.
-	pub fn relayer_fund_account_id() -> T::AccountId {
-		use sp_runtime::traits::Convert;
-		let encoded_id = bp_runtime::derive_relayer_fund_account_id(bp_runtime::NO_INSTANCE_ID);
-		T::AccountIdConverter::convert(encoded_id)
-	}
-}
+		/// Change bid of the relayer for delivering messages over the lane. If `bid` is `None`,
+		/// the bid is withdrawn.
+		///
+		/// The lowest bid of all relayers becomes the fee that must be paid by senders of messages
+		/// over the lane at the next fee update (see `FeeAuctionPeriod`). If the set of relayers of
+		/// the lane is restricted, only allowed relayers may bid.
+		#[weight = T::DbWeight::get().reads_writes(2, 1)]
+		pub fn set_fee_bid(origin, lane_id: LaneId, bid: Option<T::OutboundMessageFee>) {
+			ensure_not_halted::<T, I>()?;
+			let relayer = ensure_signed(origin)?;
+			ensure!(Self::is_relayer_allowed(&lane_id, &relayer), Error::<T, I>::RelayerIsNotAllowed);
 
-/// Getting storage keys for messages and lanes states. These keys are normally used when building
-/// messages and lanes states proofs.
-///
-/// Keep in mind that all functions in this module are **NOT** using passed `T` argument, so any
-/// runtime can be passed. E.g. if you're verifying proof from Runtime1 in Runtime2, you only have
-/// access to Runtime2 and you may pass it to the functions, where required. This is because our
-/// maps are not using any Runtime-specific data in the keys.
-///
-/// On the other side, passing correct instance is required. So if proof has been crafted by the
-/// Instance1, you should verify it using Instance1. This is inconvenient if you're using different
-/// instances on different sides of the bridge. I.e. in Runtime1 it is Instance2, but on Runtime2
-/// it is Instance42. But there's no other way, but to craft this key manually (which is what I'm
-/// trying to avoid here) - by using strings like "Instance2", "OutboundMessages", etc.
-pub mod storage_keys {
-	use super::*;
-	use frame_support::storage::generator::StorageMap;
-	use sp_core::storage::StorageKey;
+			log::trace!(
+				target: "runtime::bridge-messages",
+				"Relayer {:?} has changed its bid for lane {:?} to: {:?}",
+				relayer,
+				lane_id,
+				bid,
+			);
+			match bid {
+				Some(bid) => LaneFeeBids::<T, I>::insert(&lane_id, &relayer, bid),
+				None => LaneFeeBids::<T, I>::remove(&lane_id, &relayer),
+			}
+			Self::deposit_event(RawEvent::FeeBidChanged(lane_id, relayer));
+		}
 
-	/// Storage key of the outbound message in the runtime storage.
-	pub fn message_key<T: Config<I>, I: Instance>(lane: &LaneId, nonce: MessageNonce) -> StorageKey {
-		let message_key = MessageKey { lane_id: *lane, nonce };
-		let raw_storage_key = OutboundMessages::<T, I>::storage_map_final_key(message_key);
-		StorageKey(raw_storage_key)
-	}
+		/// Receive messages proof from bridged chain.
+		///
+		/// The weight of the call assumes that the transaction always brings outbound lane
+		/// state update. Because of that, the submitter (relayer) has no benefit of not including
+		/// this data in the transaction, so reward confirmations lags should be minimal.
+		///
+		/// The dispatch weight that has not been spent (because it has been overestimated by
+		/// the message submitter or the relayer, or because the message has not been dispatched)
+		/// is refunded to the relayer.
+		#[weight = T::WeightInfo::receive_messages_proof_weight(proof, *messages_count, *dispatch_weight)]
+		pub fn receive_messages_proof(
+			origin,
+			relayer_id: T::InboundRelayer,
+			proof: MessagesProofOf<T, I>,
+			messages_count: u32,
+			dispatch_weight: Weight,
+		) -> DispatchResultWithPostInfo {
+			ensure_not_halted::<T, I>()?;
+			let submitter = ensure_signed(origin)?;
+			let declared_weight = T::WeightInfo::receive_messages_proof_weight(&proof, messages_count, dispatch_weight);
+			let proof_size = proof.size_hint();
 
-	/// Storage key of the outbound message lane state in the runtime storage.
-	pub fn outbound_lane_data_key<I: Instance>(lane: &LaneId) -> StorageKey {
-		StorageKey(OutboundLanes::<I>::storage_map_final_key(*lane))
-	}
+			// reject transactions that are declaring too many messages
+			ensure!(
+				MessageNonce::from(messages_count) <= T::MaxUnconfirmedMessagesAtInboundLane::get(),
+				Error::<T, I>::TooManyMessagesInTheProof
+			);
 
-	/// Storage key of the inbound message lane state in the runtime storage.
-	pub fn inbound_lane_data_key<T: Config<I>, I: Instance>(lane: &LaneId) -> StorageKey {
-		StorageKey(InboundLanes::<T, I>::storage_map_final_key(*lane))
-	}
-}
+			// verify messages proof && convert proof into messages
+			let (messages, traversed_nodes) = verify_and_decode_messages_proof::<
+				T::SourceHeaderChain,
+				T::InboundMessageFee,
+				T::InboundPayload,
+			>(proof, messages_count)
+				.map_err(|err| {
+					log::trace!(
+						target: "runtime::bridge-messages",
+						"Rejecting invalid messages proof: {:?}",
+						err,
+					);
 
-/// Ensure that the origin is either root, or `PalletOwner`.
-fn ensure_owner_or_root<T: Config<I>, I: Instance>(origin: T::Origin) -> Result<(), BadOrigin> {
-	match origin.into() {
-		Ok(RawOrigin::Root) => Ok(()),
-		Ok(RawOrigin::Signed(ref signer)) if Some(signer) == Pallet::<T, I>::module_owner().as_ref() => Ok(()),
-		_ => Err(BadOrigin),
-	}
-}
+					proof_rejection_error::<T, I>(err.into(), Error::<T, I>::InvalidMessagesProof)
+				})?;
 
-/// Ensure that the pallet is in normal operational mode.
-fn ensure_normal_operating_mode<T: Config<I>, I: Instance>() -> Result<(), Error<T, I>> {
-	if PalletOperatingMode::<I>::get() != OperatingMode::Normal {
-		Err(Error::<T, I>::Halted)
-	} else {
-		Ok(())
-	}
-}
+			// verify that relayer is paying actual dispatch weight
+			let actual_dispatch_weight: Weight = messages
+				.iter()
+				.map(|(lane_id, lane_messages)| {
+					let dispatch_mode = Self::lane_dispatch_mode(lane_id);
+					lane_messages
+						.messages
+						.iter()
+						.map(|message| message_dispatch_weight::<T, I>(dispatch_mode, message))
+						.fold(0, |sum, weight| sum.saturating_add(weight))
+				})
+				.fold(0, |sum, weight| sum.saturating_add(weight));
+			if dispatch_weight < actual_dispatch_weight {
+				log::trace!(
+					target: "runtime::bridge-messages",
+					"Rejecting messages proof because of dispatch weight mismatch: declared={}, expected={}",
+					dispatch_weight,
+					actual_dispatch_weight,
+				);
 
-/// Ensure that the pallet is not halted.
-fn ensure_not_halted<T: Config<I>, I: Instance>() -> Result<(), Error<T, I>> {
-	if PalletOperatingMode::<I>::get() == OperatingMode::Halted {
-		Err(Error::<T, I>::Halted)
-	} else {
-		Ok(())
-	}
-}
+				return Err(Error::<T, I>::InvalidMessagesDispatchWeight.into());
+			}
 
-/// Creates new inbound lane object, backed by runtime storage.
-fn inbound_lane<T: Config<I>, I: Instance>(lane_id: LaneId) -> InboundLane<RuntimeInboundLaneStorage<T, I>> {
-	InboundLane::new(inbound_lane_storage::<T, I>(lane_id))
-}
+			// closed and halted lanes are not accepting any messages (and lane state updates) and some
+			// lanes may only be served by selected relayers
+			for lane_id in messages.keys() {
+				ensure!(!InboundLanes::<T, I>::get(lane_id).state.is_closed(), Error::<T, I>::LaneIsClosed);
+				ensure_lane_not_halted::<T, I>(lane_id)?;
+				ensure!(
+					Self::is_relayer_allowed(lane_id, &submitter),
+					Error::<T, I>::RelayerIsNotAllowed
+				);
+			}
 
-/// Creates new runtime inbound lane storage.
-fn inbound_lane_storage<T: Config<I>, I: Instance>(lane_id: LaneId) -> RuntimeInboundLaneStorage<T, I> {
-	RuntimeInboundLaneStorage {
-		lane_id,
-		cached_data: RefCell::new(None),
-		_phantom: Default::default(),
-	}
-}
+			// dispatch messages and (optionally) update lane(s) state(s)
+			let mut total_messages = 0;
+			let mut valid_messages = 0;
+			let mut first_rejection = None;
+			let mut unspent_weight = dispatch_weight.saturating_sub(actual_dispatch_weight);
+			for (lane_id, lane_data) in messages {
+				let mut lane = inbound_lane::<T, I>(lane_id);
+				let mut lane_delivered_messages = 0;
+				let dispatch_mode = Self::lane_dispatch_mode(&lane_id);
 
-/// Creates new outbound lane object, backed by runtime storage.
-fn outbound_lane<T: Config<I>, I: Instance>(lane_id: LaneId) -> OutboundLane<RuntimeOutboundLaneStorage<T, I>> {
-	OutboundLane::new(RuntimeOutboundLaneStorage {
-		lane_id,
-		_phantom: Default::default(),
-	})
-}
+				if let Some(lane_state) = lane_data.lane_state {
+					let updated_latest_confirmed_nonce = lane.receive_state_update(lane_state);
+					if let Some(updated_latest_confirmed_nonce) = updated_latest_confirmed_nonce {
+						log::trace!(
+							target: "runtime::bridge-messages",
+							"Received lane {:?} state update: latest_confirmed_nonce={}",
+							lane_id,
+							updated_latest_confirmed_nonce,
+						);
+					}
+				}
 
-/// Runtime inbound lane storage.
-struct RuntimeInboundLaneStorage<T: Config<I>, I = DefaultInstance> {
-	lane_id: LaneId,
-	cached_data: RefCell<Option<InboundLaneData<T::InboundRelayer>>>,
-	_phantom: PhantomData<I>,
-}
+				for message in lane_data.messages {
+					debug_assert_eq!(message.key.lane_id, lane_id);
 
-impl<T: Config<I>, I: Instance> InboundLaneStorage for RuntimeInboundLaneStorage<T, I> {
-	type MessageFee = T::InboundMessageFee;
-	type Relayer = T::InboundRelayer;
+					total_messages += 1;
+					let nonce = message.key.nonce;
+					let message_dispatch_weight = message_dispatch_weight::<T, I>(dispatch_mode, &message);
+					let receival_result = match dispatch_mode {
+						DispatchMode::Immediate => lane.receive_message::<T::MessageDispatch, T::AccountId>(
+							relayer_id.clone(),
+							&submitter,
+							nonce,
+							message.data,
+						),
+						DispatchMode::Deferred => lane.receive_message::<DeferredMessageDispatch<T, I>, T::AccountId>(
+							relayer_id.clone(),
+							&submitter,
+							nonce,
+							message.data,
+						),
+					};
+					let rejection = match receival_result {
+						ReceivalResult::Dispatched(message_unspent_weight) => {
+							valid_messages += 1;
+							lane_delivered_messages += 1;
+							unspent_weight = unspent_weight.saturating_add(message_unspent_weight);
+							continue;
+						},
+						ReceivalResult::DispatchSkipped => {
+							valid_messages += 1;
+							lane_delivered_messages += 1;
+							unspent_weight = unspent_weight.saturating_add(message_dispatch_weight);
+							continue;
+						},
+						// other relayer has delivered this message before us
+						ReceivalResult::AlreadyReceived => {
+							unspent_weight = unspent_weight.saturating_add(message_dispatch_weight);
+							continue;
+						},
+						ReceivalResult::NonceGap => Error::<T, I>::MessagesNonceGap,
+						ReceivalResult::TooManyUnrewardedRelayers => Error::<T, I>::TooManyUnrewardedRelayerEntries,
+						ReceivalResult::TooManyUnconfirmedMessages => Error::<T, I>::TooManyUnconfirmedMessages,
+						ReceivalResult::TooManyQueuedMessages => Error::<T, I>::TooManyQueuedMessages,
+					};
+					unspent_weight = unspent_weight.saturating_add(message_dispatch_weight);
 
-	fn id(&self) -> LaneId {
-		self.lane_id
-	}
+					log::trace!(
+						target: "runtime::bridge-messages",
+						"Message {:?}/{} has been rejected by the inbound lane: {:?}",
+						lane_id,
+						nonce,
+						rejection,
+					);
+					first_rejection.get_or_insert(rejection);
+				}
 
-	fn max_unrewarded_relayer_entries(&self) -> MessageNonce {
-		T::MaxUnrewardedRelayerEntriesAtInboundLane::get()
-	}
+				if lane_delivered_messages != 0 {
+					LanesStatistics::<T, I>::mutate(&lane_id, |statistics| {
+						statistics.delivered_messages =
+							statistics.delivered_messages.saturating_add(lane_delivered_messages);
+					});
+				}
+			}
 
-	fn max_unconfirmed_messages(&self) -> MessageNonce {
-		T::MaxUnconfirmedMessagesAtInboundLane::get()
-	}
+			log::trace!(
+				target: "runtime::bridge-messages",
+				"Received messages: total={}, valid={}",
+				total_messages,
+				valid_messages,
+			);
 
-	fn data(&self) -> InboundLaneData<T::InboundRelayer> {
-		match self.cached_data.clone().into_inner() {
-			Some(data) => data,
-			None => {
-				let data = InboundLanes::<T, I>::get(&self.lane_id);
-				*self.cached_data.try_borrow_mut().expect(
-					"we're in the single-threaded environment;\
-						we have no recursive borrows; qed",
-				) = Some(data.clone());
-				data
+			// if the inbound lane has rejected all messages, let the relayer know why (the lane
+			// state update, if any, is still applied, because it is proved)
+			if valid_messages == 0 {
+				if let Some(rejection) = first_rejection {
+					return Err(rejection.into());
+				}
 			}
-		}
-	}
 
-	fn set_data(&mut self, data: InboundLaneData<T::InboundRelayer>) {
-		*self.cached_data.try_borrow_mut().expect(
-			"we're in the single-threaded environment;\
-				we have no recursive borrows; qed",
-		) = Some(data.clone());
-		InboundLanes::<T, I>::insert(&self.lane_id, data)
-	}
-}
+			// let the signed extension know how many new messages have been delivered (transactions
+			// that are delivering already received messages must not be refunded from the pot)
+			if DeliveredMessagesInTransaction::<I>::exists() {
+				DeliveredMessagesInTransaction::<I>::put(valid_messages);
+			}
 
-/// Runtime outbound lane storage.
-struct RuntimeOutboundLaneStorage<T, I = DefaultInstance> {
-	lane_id: LaneId,
-	_phantom: PhantomData<(T, I)>,
-}
+			// if the proof has been verified by traversing less nodes than expected, refund the
+			// difference
+			let traversed_nodes_refund = traversed_nodes
+				.map(|traversed_nodes| {
+					T::WeightInfo::receive_messages_proof_traversed_nodes_refund(
+						proof_size,
+						messages_count,
+						traversed_nodes,
+					)
+				})
+				.unwrap_or(0);
+
+			Ok(Some(
+				declared_weight
+					.saturating_sub(unspent_weight)
+					.saturating_sub(traversed_nodes_refund),
+			)
+			.into())
+		}
 
-impl<T: Config<I>, I: Instance> OutboundLaneStorage for RuntimeOutboundLaneStorage<T, I> {
-	type MessageFee = T::OutboundMessageFee;
+		/// Receive messages delivery proof from bridged chain.
+		#[weight = T::WeightInfo::receive_messages_delivery_proof_weight(proof, relayers_state)
+			.saturating_add(oldest_unconfirmed_message_update_weight::<T, I>())
+			.saturating_add(pending_message_refunds_weight::<T, I>(relayers_state))
+			.saturating_add(skipped_messages_refunds_weight::<T, I>(relayers_state.total_messages))]
+		pub fn receive_messages_delivery_proof(
+			origin,
+			proof: MessagesDeliveryProofOf<T, I>,
+			relayers_state: UnrewardedRelayersState,
+		) -> DispatchResultWithPostInfo {
+			ensure_not_halted::<T, I>()?;
 
-	fn id(&self) -> LaneId {
-		self.lane_id
-	}
+			let confirmation_relayer = ensure_signed(origin)?;
+			let declared_weight = T::WeightInfo::receive_messages_delivery_proof_weight(&proof, &relayers_state)
+				.saturating_add(oldest_unconfirmed_message_update_weight::<T, I>())
+				.saturating_add(pending_message_refunds_weight::<T, I>(&relayers_state))
+				.saturating_add(skipped_messages_refunds_weight::<T, I>(relayers_state.total_messages));
+			let proof_size = proof.size_hint();
+			let ((lane_id, lane_data), traversed_nodes) =
+				T::TargetHeaderChain::verify_messages_delivery_proof_with_traversed_nodes(proof).map_err(|err| {
+					log::trace!(
+						target: "runtime::bridge-messages",
+						"Rejecting invalid messages delivery proof: {:?}",
+						err,
+					);
 
-	fn data(&self) -> OutboundLaneData {
-		OutboundLanes::<I>::get(&self.lane_id)
-	}
+					proof_rejection_error::<T, I>(err.into(), Error::<T, I>::InvalidMessagesDeliveryProof)
+				})?;
+			ensure_lane_not_halted::<T, I>(&lane_id)?;
 
-	fn set_data(&mut self, data: OutboundLaneData) {
-		OutboundLanes::<I>::insert(&self.lane_id, data)
+			// verify that the relayer has declared correct `lane_data::relayers` state
+			// (we only care about total number of entries and messages, because this affects call weight)
+			ensure!(
+				lane_data.total_unconfirmed_messages() == relayers_state.total_messages
+					&& lane_data.relayers.len() as MessageNonce == relayers_state.unrewarded_relayer_entries,
+				Error::<T, I>::InvalidUnrewardedRelayersState
+			);
+
+			// mark messages as delivered
+			ensure!(!OutboundLanes::<I>::get(&lane_id).state.is_closed(), Error::<T, I>::LaneIsClosed);
+			let mut relayers_rewards = RelayersRewards::new();
+			let (handler_weight, skipped_messages) =
+				confirm_delivery::<T, I>(lane_id, lane_data, &mut relayers_rewards);
+
+			// if some new messages have been confirmed, reward relayers
+			reward_relayers::<T, I>(&confirmation_relayer, relayers_rewards);
+
+			// if the proof has been verified by traversing less nodes than expected, refund the
+			// difference
+			let traversed_nodes_refund = traversed_nodes
+				.map(|traversed_nodes| {
+					T::WeightInfo::receive_messages_delivery_proof_traversed_nodes_refund(proof_size, traversed_nodes)
+				})
+				.unwrap_or(0);
+
+			// fees of delivered messages are not refunded, so we refund the weight of these refunds
+			let delivered_messages = relayers_state.total_messages.saturating_sub(skipped_messages);
+			let unspent_refunds_weight = skipped_messages_refunds_weight::<T, I>(delivered_messages);
+
+			Ok(Some(
+				declared_weight
+					.saturating_sub(traversed_nodes_refund)
+					.saturating_sub(unspent_refunds_weight)
+					.saturating_add(handler_weight),
+			)
+			.into())
+		}
+
+		/// Receive messages delivery proof of several lanes from bridged chain.
+		///
+		/// The single proof covers states of several inbound lanes at the bridged chain, so the
+		/// finality and storage proof overhead is paid once for all lanes. The `relayers_state`
+		/// is the total state of unrewarded relayers sets of all proved lanes.
+		#[weight = T::WeightInfo::receive_messages_delivery_proof_weight(proof, relayers_state)
+			.saturating_add(oldest_unconfirmed_message_update_weight::<T, I>())
+			.saturating_add(pending_message_refunds_weight::<T, I>(relayers_state))
+			.saturating_add(skipped_messages_refunds_weight::<T, I>(relayers_state.total_messages))]
+		pub fn receive_messages_delivery_batch_proof(
+			origin,
+			proof: MessagesDeliveryBatchProofOf<T, I>,
+			relayers_state: UnrewardedRelayersState,
+		) -> DispatchResultWithPostInfo {
+			ensure_not_halted::<T, I>()?;
+
+			let confirmation_relayer = ensure_signed(origin)?;
+			let declared_weight = T::WeightInfo::receive_messages_delivery_proof_weight(&proof, &relayers_state)
+				.saturating_add(oldest_unconfirmed_message_update_weight::<T, I>())
+				.saturating_add(pending_message_refunds_weight::<T, I>(&relayers_state))
+				.saturating_add(skipped_messages_refunds_weight::<T, I>(relayers_state.total_messages));
+			let lanes = T::TargetHeaderChain::verify_messages_delivery_batch_proof(proof).map_err(|err| {
+				log::trace!(
+					target: "runtime::bridge-messages",
+					"Rejecting invalid messages delivery batch proof: {:?}",
+					err,
+				);
+
+				proof_rejection_error::<T, I>(err.into(), Error::<T, I>::InvalidMessagesDeliveryProof)
+			})?;
+
+			// every lane may only be proved once
+			let mut lane_ids = lanes.iter().map(|(lane_id, _)| *lane_id).collect::<Vec<_>>();
+			lane_ids.sort_unstable();
+			lane_ids.dedup();
+			ensure!(
+				!lanes.is_empty() && lane_ids.len() == lanes.len(),
+				Error::<T, I>::InvalidMessagesDeliveryProof
+			);
+
+			// verify that the relayer has declared correct total state of `lane_data::relayers`
+			let (total_unrewarded_relayer_entries, total_messages) = lanes.iter().fold(
+				(0 as MessageNonce, 0 as MessageNonce),
+				|(entries, messages), (_, lane_data)| (
+					entries.saturating_add(lane_data.relayers.len() as MessageNonce),
+					messages.saturating_add(lane_data.total_unconfirmed_messages()),
+				),
+			);
+			ensure!(
+				total_messages == relayers_state.total_messages
+					&& total_unrewarded_relayer_entries == relayers_state.unrewarded_relayer_entries,
+				Error::<T, I>::InvalidUnrewardedRelayersState
+			);
+
+			// all checks are made before any lane is touched, so either all lanes are updated,
+			// or none of them
+			for lane_id in &lane_ids {
+				ensure_lane_not_halted::<T, I>(lane_id)?;
+				ensure!(!OutboundLanes::<I>::get(lane_id).state.is_closed(), Error::<T, I>::LaneIsClosed);
+			}
+
+			// mark messages as delivered (the declared weight only covers update of the oldest
+			// unconfirmed message of the single lane)
+			let mut relayers_rewards = RelayersRewards::new();
+			let mut handlers_weight: Weight = oldest_unconfirmed_message_update_weight::<T, I>()
+				.saturating_mul(lane_ids.len().saturating_sub(1) as Weight);
+			let mut skipped_messages: MessageNonce = 0;
+			for (lane_id, lane_data) in lanes {
+				let (handler_weight, lane_skipped_messages) =
+					confirm_delivery::<T, I>(lane_id, lane_data, &mut relayers_rewards);
+				handlers_weight = handlers_weight.saturating_add(handler_weight);
+				skipped_messages = skipped_messages.saturating_add(lane_skipped_messages);
+			}
+
+			// if some new messages have been confirmed, reward relayers
+			reward_relayers::<T, I>(&confirmation_relayer, relayers_rewards);
+
+			// fees of delivered messages are not refunded, so we refund the weight of these refunds
+			let delivered_messages = relayers_state.total_messages.saturating_sub(skipped_messages);
+			let unspent_refunds_weight = skipped_messages_refunds_weight::<T, I>(delivered_messages);
+
+			Ok(Some(
+				declared_weight
+					.saturating_sub(unspent_refunds_weight)
+					.saturating_add(handlers_weight),
+			)
+			.into())
+		}
 	}
+}
 
-	#[cfg(test)]
-	fn message(&self, nonce: &MessageNonce) -> Option<MessageData<T::OutboundMessageFee>> {
-		OutboundMessages::<T, I>::get(MessageKey {
-			lane_id: self.lane_id,
-			nonce: *nonce,
-		})
+impl<T: Config<I>, I: Instance> Pallet<T, I> {
+	/// Get stored data of the outbound message with given nonce.
+	pub fn outbound_message_data(lane: LaneId, nonce: MessageNonce) -> Option<MessageData<T::OutboundMessageFee>> {
+		OutboundMessages::<T, I>::get(MessageKey { lane_id: lane, nonce })
 	}
 
-	fn save_message(&mut self, nonce: MessageNonce, mesage_data: MessageData<T::OutboundMessageFee>) {
-		OutboundMessages::<T, I>::insert(
-			MessageKey {
-				lane_id: self.lane_id,
-				nonce,
-			},
-			mesage_data,
-		);
+	/// Get payloads and storage keys of stored outbound messages in the `begin..=end` nonce range.
+	///
+	/// The range is clamped to messages that are currently stored at the lane, so this function
+	/// never reads more than `latest_generated_nonce - oldest_unpruned_nonce + 1` storage entries.
+	pub fn outbound_messages_with_storage_keys(
+		lane: LaneId,
+		begin: MessageNonce,
+		end: MessageNonce,
+		include_outbound_lane_state: bool,
+	) -> OutboundMessagesWithStorageKeys {
+		let lane_data = OutboundLanes::<I>::get(&lane);
+		let begin = sp_std::cmp::max(begin, lane_data.oldest_unpruned_nonce);
+		let end = sp_std::cmp::min(end, lane_data.latest_generated_nonce);
+
+		let mut result = OutboundMessagesWithStorageKeys::default();
+		for nonce in begin..=end {
+			if let Some(message_data) = Self::outbound_message_data(lane, nonce) {
+				result.messages.push((nonce, message_data.payload));
+				result
+					.storage_keys
+					.push(storage_keys::message_key::<T, I>(&lane, nonce).0);
+			}
+		}
+		if include_outbound_lane_state {
+			result
+				.storage_keys
+				.push(storage_keys::outbound_lane_data_key::<I>(&lane).0);
+		}
+
+		result
 	}
 
-	fn remove_message(&mut self, nonce: &MessageNonce) {
-		OutboundMessages::<T, I>::remove(MessageKey {
-			lane_id: self.lane_id,
-			nonce: *nonce,
-		});
+	/// Get number of the oldest bridged chain header that may be used to prove delivery of sent,
+	/// but not yet confirmed messages. Returns `None` if all sent messages are confirmed.
+	///
+	/// Reads one entry per every lane that has unconfirmed messages.
+	pub fn oldest_required_bridged_header() -> Option<T::BridgedHeaderNumber> {
+		OldestUnconfirmedMessages::<T, I>::iter()
+			.map(|(_, (_, number))| number)
+			.min()
 	}
-}
 
-/// Verify messages proof and return proved messages with decoded payload.
-fn verify_and_decode_messages_proof<Chain: SourceHeaderChain<Fee>, Fee, DispatchPayload: Decode>(
-	proof: Chain::MessagesProof,
-	messages_count: u32,
-) -> Result<ProvedMessages<DispatchMessage<DispatchPayload, Fee>>, Chain::Error> {
-	// `receive_messages_proof` weight formula and `MaxUnconfirmedMessagesAtInboundLane` check
-	// guarantees that the `message_count` is sane and Vec<Message> may be allocated.
-	// (tx with too many messages will either be rejected from the pool, or will fail earlier)
-	Chain::verify_messages_proof(proof, messages_count).map(|messages_by_lane| {
-		messages_by_lane
-			.into_iter()
-			.map(|(lane, lane_data)| {
-				(
-					lane,
-					ProvedLaneMessages {
-						lane_state: lane_data.lane_state,
-						messages: lane_data.messages.into_iter().map(Into::into).collect(),
-					},
-				)
+	/// Get nonce of latest generated message at given outbound lane.
+	pub fn outbound_latest_generated_nonce(lane: LaneId) -> MessageNonce {
+		OutboundLanes::<I>::get(&lane).latest_generated_nonce
+	}
+
+	/// Get nonce of latest confirmed message at given outbound lane.
+	pub fn outbound_latest_received_nonce(lane: LaneId) -> MessageNonce {
+		OutboundLanes::<I>::get(&lane).latest_received_nonce
+	}
+
+	/// Get nonce of latest received message at given inbound lane.
+	pub fn inbound_latest_received_nonce(lane: LaneId) -> MessageNonce {
+		InboundLanes::<T, I>::get(&lane).last_delivered_nonce()
+	}
+
+	/// Get nonce of latest confirmed message at given inbound lane.
+	pub fn inbound_latest_confirmed_nonce(lane: LaneId) -> MessageNonce {
+		InboundLanes::<T, I>::get(&lane).last_confirmed_nonce
+	}
+
+	/// Get full state of given inbound lane.
+	pub fn inbound_lane_data(lane: LaneId) -> InboundLaneData<T::InboundRelayer> {
+		InboundLanes::<T, I>::get(&lane).into_inner()
+	}
+
+	/// Get statistics of given lane.
+	pub fn lane_statistics(lane: LaneId) -> LaneStatistics<T::OutboundMessageFee> {
+		LanesStatistics::<T, I>::get(&lane)
+	}
+
+	/// Get congestion of given outbound lane.
+	pub fn lane_congestion(lane: LaneId) -> LaneCongestion {
+		let lane_data = OutboundLanes::<I>::get(&lane);
+		LaneCongestion {
+			queued_messages: lane_data
+				.latest_generated_nonce
+				.saturating_sub(lane_data.latest_received_nonce),
+			soft_cap: T::LaneCongestionThreshold::get(),
+			is_congested: CongestedLanes::<I>::get(&lane),
+		}
+	}
+
+	/// Get state of unrewarded relayers set.
+	pub fn inbound_unrewarded_relayers_state(lane: bp_messages::LaneId) -> bp_messages::UnrewardedRelayersState {
+		let lane_data = InboundLanes::<T, I>::get(&lane).into_inner();
+		bp_messages::UnrewardedRelayersState {
+			unrewarded_relayer_entries: lane_data.relayers.len() as _,
+			messages_in_oldest_entry: lane_data
+				.relayers
+				.front()
+				.map(|(_, end, _)| end.saturating_sub(lane_data.last_confirmed_nonce))
+				.unwrap_or(0),
+			total_messages: lane_data.total_unconfirmed_messages(),
+		}
+	}
+
+	/// Returns true if given account is allowed to deliver messages over the lane.
+	pub fn is_relayer_allowed(lane: &LaneId, relayer: &T::AccountId) -> bool {
+		!LaneRelayers::<T, I>::contains_key(lane) || AllowedLaneRelayers::<T, I>::get(lane, relayer)
+	}
+
+	/// Returns true if the lane has been registered by the `open_lane` call and is not yet reaped.
+	pub fn is_lane_registered(lane: &LaneId) -> bool {
+		Lanes::<T, I>::contains_key(lane)
+	}
+
+	/// Returns multiplier that is applied to the minimal fee of messages, sent over the lane.
+	pub fn lane_fee_multiplier(lane: &LaneId) -> FixedU128 {
+		LaneFeeMultipliers::<I>::get(lane).unwrap_or_else(FixedU128::one)
+	}
+
+	/// Returns operating mode of the lane.
+	pub fn lane_operating_mode(lane: &LaneId) -> OperatingMode {
+		LaneOperatingModes::<I>::get(lane).unwrap_or_default()
+	}
+
+	/// Returns ordering of messages at the lane.
+	pub fn lane_ordering(lane: &LaneId) -> LaneOrdering {
+		LaneOrderings::<I>::get(lane).unwrap_or_default()
+	}
+
+	/// Returns mode of inbound messages dispatch at the lane.
+	pub fn lane_dispatch_mode(lane: &LaneId) -> DispatchMode {
+		LaneDispatchModes::<I>::get(lane).unwrap_or_default()
+	}
+
+	/// Returns minimal fee that must be paid by senders of messages over the lane, if lane has it.
+	pub fn lane_required_fee(lane: &LaneId) -> Option<T::OutboundMessageFee> {
+		LaneRequiredFees::<T, I>::get(lane)
+	}
+
+	/// Returns quota of messages that a single account may send over the lane, if it is configured.
+	pub fn lane_sender_quota(lane: &LaneId) -> Option<SenderQuota<T::BlockNumber>> {
+		LaneSenderQuotas::<T, I>::get(lane)
+	}
+
+	/// Returns limits of messages at the lane, if they're configured.
+	pub fn lane_message_limits(lane: &LaneId) -> Option<MessageLimits> {
+		LaneMessageLimits::<I>::get(lane)
+	}
+
+	/// AccountId of the shared relayer fund account.
+	///
+	/// This account is passed to `MessageDeliveryAndDispatchPayment` trait, and depending
+	/// on the implementation it can be used to store relayers rewards.
+	/// See [InstantCurrencyPayments] for a concrete implementation.
+	pub fn relayer_fund_account_id() -> T::AccountId {
+		use sp_runtime::traits::Convert;
+		let encoded_id = bp_runtime::derive_relayer_fund_account_id(bp_runtime::NO_INSTANCE_ID);
+		T::AccountIdConverter::convert(encoded_id)
+	}
+
+	/// AccountId of the pot that pays fees for messages, sent by other runtime pallets.
+	///
+	/// Messages that are sent using `MessagesBridge::send_message_from_pallet` are submitted
+	/// on behalf of this account. So delivery and dispatch fee is paid from this account and
+	/// the message payload must use this account as the message origin.
+	pub fn pallets_fee_pot_account_id() -> T::AccountId {
+		use sp_runtime::traits::Convert;
+		let encoded_id = bp_runtime::derive_pallets_fee_pot_account_id(bp_runtime::NO_INSTANCE_ID);
+		T::AccountIdConverter::convert(encoded_id)
+	}
+
+	/// AccountId of the pot that refunds fees of messages delivery transactions.
+	///
+	/// The pot may be funded by anyone (e.g. by governance or by message senders). Fees are refunded
+	/// by the `RefundDeliveryTransactionFee` signed extension of the `bridge-runtime-common` crate.
+	pub fn relayers_pot_account_id() -> T::AccountId {
+		use sp_runtime::traits::Convert;
+		let encoded_id = bp_runtime::derive_relayers_pot_account_id(bp_runtime::NO_INSTANCE_ID);
+		T::AccountIdConverter::convert(encoded_id)
+	}
+
+	/// Ensure that invariants of all inbound and outbound lanes are held.
+	///
+	/// This is expensive (it reads all lanes and all queued messages), so it is only supposed
+	/// to be used when testing runtime upgrades.
+	#[cfg(any(feature = "try-runtime", test))]
+	pub fn do_try_state() -> Result<(), &'static str> {
+		use frame_support::storage::IterableStorageMap;
+
+		for (lane_id, lane_data) in InboundLanes::<T, I>::iter() {
+			ensure_inbound_lane_invariants(&lane_data, Self::lane_ordering(&lane_id)).map_err(|err| {
+				log::error!(
+					target: "runtime::bridge-messages",
+					"Invariant of inbound lane {:?} is broken: {}",
+					lane_id,
+					err,
+				);
+				err
+			})?;
+		}
+
+		for (lane_id, lane_data) in OutboundLanes::<I>::iter() {
+			ensure_outbound_lane_invariants(&lane_data, |nonce| {
+				OutboundMessages::<T, I>::contains_key(MessageKey { lane_id, nonce })
 			})
-			.collect()
-	})
+			.map_err(|err| {
+				log::error!(
+					target: "runtime::bridge-messages",
+					"Invariant of outbound lane {:?} is broken: {}",
+					lane_id,
+					err,
+				);
+				err
+			})?;
+		}
+
+		Ok(())
+	}
 }
 
-#[cfg(test)]
-mod tests {
+/// Ensure that the inbound lane invariants are held.
+///
+/// Unrewarded relayers entries must have valid nonce ranges. Ranges must be contiguous and must
+/// cover messages in the (last_confirmed_nonce; last_delivered_nonce] range. At unordered lanes,
+/// there may be gaps (skipped messages) between ranges, but ranges must never overlap.
+#[cfg(any(feature = "try-runtime", test))]
+fn ensure_inbound_lane_invariants<RelayerId>(
+	lane_data: &InboundLaneData<RelayerId>,
+	ordering: LaneOrdering,
+) -> Result<(), &'static str> {
+	let mut expected_nonce_low = None;
+	for (nonce_low, nonce_high, _) in &lane_data.relayers {
+		ensure!(
+			*nonce_low > lane_data.last_confirmed_nonce,
+			"Unrewarded relayer entry covers already confirmed messages"
+		);
+		ensure!(
+			nonce_low <= nonce_high,
+			"Unrewarded relayer entry has invalid nonces range"
+		);
+		let is_overlapping = expected_nonce_low
+			.map(|expected| *nonce_low < expected)
+			.unwrap_or(false);
+		ensure!(!is_overlapping, "Unrewarded relayer entries overlap");
+		let is_contiguous = expected_nonce_low
+			.map(|expected| expected == *nonce_low)
+			.unwrap_or(true);
+		ensure!(
+			is_contiguous || ordering == LaneOrdering::Unordered,
+			"Unrewarded relayer entries are not contiguous"
+		);
+
+		expected_nonce_low = nonce_high.checked_add(1);
+	}
+
+	Ok(())
+}
+
+/// Ensure that the outbound lane invariants are held.
+///
+/// Nonces must be monotonic (oldest_unpruned_nonce <= latest_received_nonce + 1 and
+/// latest_received_nonce <= latest_generated_nonce) and all messages in the
+/// [oldest_unpruned_nonce; latest_generated_nonce] range must be stored.
+#[cfg(any(feature = "try-runtime", test))]
+fn ensure_outbound_lane_invariants(
+	lane_data: &OutboundLaneData,
+	is_message_stored: impl Fn(MessageNonce) -> bool,
+) -> Result<(), &'static str> {
+	ensure!(
+		lane_data.latest_received_nonce <= lane_data.latest_generated_nonce,
+		"Latest received nonce is larger than latest generated nonce"
+	);
+	ensure!(
+		lane_data.oldest_unpruned_nonce <= lane_data.latest_received_nonce.saturating_add(1),
+		"Oldest unpruned nonce is larger than next to latest received nonce"
+	);
+	ensure!(
+		(lane_data.oldest_unpruned_nonce..=lane_data.latest_generated_nonce).all(is_message_stored),
+		"Message that is not yet pruned is missing from the storage"
+	);
+
+	Ok(())
+}
+
+impl<T: Config<I>, I: Instance> MessagesBridge<T::AccountId, T::OutboundMessageFee, T::OutboundPayload>
+	for Pallet<T, I>
+{
+	type Error = DispatchError;
+
+	fn send_message_from_pallet(
+		lane: LaneId,
+		message: T::OutboundPayload,
+		delivery_and_dispatch_fee: T::OutboundMessageFee,
+	) -> Result<MessageNonce, Self::Error> {
+		send_message::<T, I>(
+			RawOrigin::Signed(Self::pallets_fee_pot_account_id()),
+			lane,
+			message,
+			delivery_and_dispatch_fee,
+			None,
+		)
+	}
+}
+
+/// Getting storage keys for messages and lanes states. These keys are normally used when building
+/// messages and lanes states proofs.
+///
+/// Keep in mind that all functions in this module are **NOT** using passed `T` argument, so any
+/// runtime can be passed. E.g. if you're verifying proof from Runtime1 in Runtime2, you only have
+/// access to Runtime2 and you may pass it to the functions, where required. This is because our
+/// maps are not using any Runtime-specific data in the keys.
+///
+/// On the other side, passing correct instance is required. So if proof has been crafted by the
+/// Instance1, you should verify it using Instance1. This is inconvenient if you're using different
+/// instances on different sides of the bridge. I.e. in Runtime1 it is Instance2, but on Runtime2
+/// it is Instance42. But there's no other way, but to craft this key manually (which is what I'm
+/// trying to avoid here) - by using strings like "Instance2", "OutboundMessages", etc.
+pub mod storage_keys {
 	use super::*;
-	use crate::mock::{
-		message, run_test, Event as TestEvent, Origin, TestMessageDeliveryAndDispatchPayment,
-		TestMessagesDeliveryProof, TestMessagesParameter, TestMessagesProof, TestPayload, TestRuntime,
-		TokenConversionRate, PAYLOAD_REJECTED_BY_TARGET_CHAIN, REGULAR_PAYLOAD, TEST_LANE_ID, TEST_RELAYER_A,
-		TEST_RELAYER_B,
-	};
-	use bp_messages::UnrewardedRelayersState;
-	use frame_support::{assert_noop, assert_ok};
-	use frame_system::{EventRecord, Pallet as System, Phase};
-	use hex_literal::hex;
-	use sp_runtime::DispatchError;
+	use frame_support::storage::generator::{StorageMap, StorageValue};
+	use sp_core::storage::StorageKey;
 
-	fn get_ready_for_events() {
-		System::<TestRuntime>::set_block_number(1);
-		System::<TestRuntime>::reset_events();
+	/// Storage key prefix, shared by all storage items of the pallet.
+	pub fn pallet_prefix_key<I: Instance>() -> StorageKey {
+		StorageKey(PalletOperatingMode::<I>::storage_value_final_key()[..16].to_vec())
 	}
 
-	fn send_regular_message() {
-		get_ready_for_events();
+	/// Storage key of the pallet operating mode in the runtime storage.
+	pub fn operating_mode_key<I: Instance>() -> StorageKey {
+		StorageKey(PalletOperatingMode::<I>::storage_value_final_key().to_vec())
+	}
 
-		assert_ok!(Pallet::<TestRuntime>::send_message(
-			Origin::signed(1),
-			TEST_LANE_ID,
-			REGULAR_PAYLOAD,
-			REGULAR_PAYLOAD.1,
-		));
+	/// Storage key of the outbound message in the runtime storage.
+	pub fn message_key<T: Config<I>, I: Instance>(lane: &LaneId, nonce: MessageNonce) -> StorageKey {
+		let message_key = MessageKey { lane_id: *lane, nonce };
+		let raw_storage_key = OutboundMessages::<T, I>::storage_map_final_key(message_key);
+		StorageKey(raw_storage_key)
+	}
 
-		// check event with assigned nonce
-		assert_eq!(
-			System::<TestRuntime>::events(),
-			vec![EventRecord {
-				phase: Phase::Initialization,
-				event: TestEvent::pallet_bridge_messages(RawEvent::MessageAccepted(TEST_LANE_ID, 1)),
-				topics: vec![],
-			}],
-		);
+	/// Storage key of the outbound message lane state in the runtime storage.
+	pub fn outbound_lane_data_key<I: Instance>(lane: &LaneId) -> StorageKey {
+		StorageKey(OutboundLanes::<I>::storage_map_final_key(*lane))
+	}
 
-		// check that fee has been withdrawn from submitter
-		assert!(TestMessageDeliveryAndDispatchPayment::is_fee_paid(1, REGULAR_PAYLOAD.1));
+	/// Storage key of the inbound message lane state in the runtime storage.
+	pub fn inbound_lane_data_key<T: Config<I>, I: Instance>(lane: &LaneId) -> StorageKey {
+		StorageKey(InboundLanes::<T, I>::storage_map_final_key(*lane))
 	}
 
-	fn receive_messages_delivery_proof() {
-		System::<TestRuntime>::set_block_number(1);
-		System::<TestRuntime>::reset_events();
+	/// Storage key of the lane operating mode in the runtime storage.
+	pub fn lane_operating_mode_key<I: Instance>(lane: &LaneId) -> StorageKey {
+		StorageKey(LaneOperatingModes::<I>::storage_map_final_key(*lane))
+	}
+}
+
+/// Send message over lane.
+///
+/// This function is used by the `send_message` and `send_message_with_ttl` calls and by the
+/// `MessagesBridge` implementation.
+fn send_message<T: Config<I>, I: Instance>(
+	submitter: RawOrigin<T::AccountId>,
+	lane_id: LaneId,
+	payload: T::OutboundPayload,
+	delivery_and_dispatch_fee: T::OutboundMessageFee,
+	ttl: Option<T::BlockNumber>,
+) -> Result<MessageNonce, DispatchError> {
+	ensure_normal_operating_mode::<T, I>()?;
+	match Pallet::<T, I>::lane_operating_mode(&lane_id) {
+		OperatingMode::Normal => (),
+		OperatingMode::RejectingOutboundMessages => return Err(Error::<T, I>::LaneIsRejectingOutboundMessages.into()),
+		OperatingMode::Halted => return Err(Error::<T, I>::LaneIsHalted.into()),
+	}
+
+	// let's first check if message can be delivered to target chain
+	T::TargetHeaderChain::verify_message(&payload).map_err(|err| {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Message to lane {:?} is rejected by target chain: {:?}",
+			lane_id,
+			err,
+		);
+
+		Error::<T, I>::MessageRejectedByChainVerifier
+	})?;
+
+	// now let's enforce any additional lane rules
+	let mut lane = outbound_lane::<T, I>(lane_id);
+	ensure!(
+		lane.data().state.is_accepting_messages(),
+		Error::<T, I>::LaneIsNotOpened
+	);
+	if let Some(lane_info) = Lanes::<T, I>::get(&lane_id) {
+		let lane_data = lane.data();
+		let pending_messages = lane_data
+			.latest_generated_nonce
+			.saturating_sub(lane_data.latest_received_nonce);
+		ensure!(
+			pending_messages < lane_info.max_pending_messages,
+			Error::<T, I>::TooManyPendingMessages
+		);
+	}
+	if let Some(limits) = Pallet::<T, I>::lane_message_limits(&lane_id) {
+		ensure!(
+			payload.encoded_size() <= limits.max_payload_size as usize,
+			Error::<T, I>::MessageIsTooLarge
+		);
+	}
+	if let Some(lane_fee) = Pallet::<T, I>::lane_required_fee(&lane_id) {
+		ensure!(delivery_and_dispatch_fee >= lane_fee, Error::<T, I>::TooLowMessageFee);
+	}
+	let sender_quota_usage = match submitter {
+		RawOrigin::Signed(ref sender) => check_sender_quota::<T, I>(&lane_id, sender, &payload)?,
+		_ => None,
+	};
+	T::LaneMessageVerifier::verify_message(&submitter, &delivery_and_dispatch_fee, &lane_id, &lane.data(), &payload)
+		.map_err(|err| {
+			log::trace!(
+				target: "runtime::bridge-messages",
+				"Message to lane {:?} is rejected by lane verifier: {:?}",
+				lane_id,
+				err,
+			);
+
+			Error::<T, I>::MessageRejectedByLaneVerifier
+		})?;
+
+	// let's withdraw delivery and dispatch fee from submitter
+	T::MessageDeliveryAndDispatchPayment::pay_delivery_and_dispatch_fee(
+		&submitter,
+		&delivery_and_dispatch_fee,
+		&Pallet::<T, I>::relayer_fund_account_id(),
+	)
+	.map_err(|err| {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Message to lane {:?} is rejected because submitter {:?} is unable to pay fee {:?}: {:?}",
+			lane_id,
+			submitter,
+			delivery_and_dispatch_fee,
+			err,
+		);
+
+		Error::<T, I>::FailedToWithdrawMessageFee
+	})?;
+
+	// finally, save message in outbound storage and emit event
+	let encoded_payload = VersionedMessagePayload::encode_payload(&payload);
+	let encoded_payload_len = encoded_payload.len();
+	let nonce = lane.send_message(MessageData {
+		payload: encoded_payload,
+		fee: delivery_and_dispatch_fee.clone(),
+	});
+	if let RawOrigin::Signed(submitter) = submitter {
+		if let Some(sender_quota_usage) = sender_quota_usage {
+			SenderQuotaUsages::<T, I>::insert(&lane_id, &submitter, sender_quota_usage);
+		}
+		OutboundMessageSubmitters::<T, I>::insert(MessageKey { lane_id, nonce }, submitter);
+	}
+	if !OldestUnconfirmedMessages::<T, I>::contains_key(&lane_id) {
+		let best_finalized_bridged_header = T::BestFinalizedBridgedHeaderNumber::get();
+		OldestUnconfirmedMessages::<T, I>::insert(&lane_id, (nonce, best_finalized_bridged_header));
+	}
+	if let Some(ttl) = ttl {
+		let current_block = frame_system::Pallet::<T>::block_number();
+		let expires_at = sp_runtime::traits::Saturating::saturating_add(current_block, ttl);
+		OutboundMessageExpirations::<T, I>::insert(MessageKey { lane_id, nonce }, expires_at);
+	}
+	lane.prune_messages(T::MaxMessagesToPruneAtOnce::get());
+	LanesStatistics::<T, I>::mutate(&lane_id, |statistics| {
+		statistics.sent_messages = statistics.sent_messages.saturating_add(1);
+		statistics.collected_fees = statistics.collected_fees.saturating_add(&delivery_and_dispatch_fee);
+	});
+
+	log::trace!(
+		target: "runtime::bridge-messages",
+		"Accepted message {} to lane {:?}. Message size: {:?}",
+		nonce,
+		lane_id,
+		encoded_payload_len,
+	);
+
+	Pallet::<T, I>::deposit_event(RawEvent::MessageAccepted(lane_id, nonce));
+	update_lane_congestion::<T, I>(lane_id);
+
+	Ok(nonce)
+}
+
+/// Update congestion flag of the outbound lane and emit `LaneCongested` or `LaneUncongested` event if
+/// the lane has crossed the congestion threshold.
+fn update_lane_congestion<T: Config<I>, I: Instance>(lane_id: LaneId) {
+	let congestion = Pallet::<T, I>::lane_congestion(lane_id);
+	let is_above_threshold = congestion.soft_cap != 0 && congestion.queued_messages >= congestion.soft_cap;
+	let is_below_half_of_threshold =
+		congestion.soft_cap == 0 || congestion.queued_messages.saturating_mul(2) < congestion.soft_cap;
+
+	if !congestion.is_congested && is_above_threshold {
+		CongestedLanes::<I>::insert(&lane_id, true);
+		Pallet::<T, I>::deposit_event(RawEvent::LaneCongested(lane_id, congestion.queued_messages));
+	} else if congestion.is_congested && is_below_half_of_threshold {
+		CongestedLanes::<I>::remove(&lane_id);
+		Pallet::<T, I>::deposit_event(RawEvent::LaneUncongested(lane_id, congestion.queued_messages));
+	}
+}
+
+/// Check that the sender may send the message without exceeding the lane sender quota.
+///
+/// Returns updated usage of the quota that needs to be saved when the message is accepted. Returns
+/// `None` if the sender isn't limited by the quota.
+fn check_sender_quota<T: Config<I>, I: Instance>(
+	lane_id: &LaneId,
+	sender: &T::AccountId,
+	payload: &T::OutboundPayload,
+) -> Result<Option<SenderQuotaUsage<T::BlockNumber>>, Error<T, I>> {
+	let quota = match Pallet::<T, I>::lane_sender_quota(lane_id) {
+		Some(quota) => quota,
+		None => return Ok(None),
+	};
+	if SenderQuotaExemptions::<T, I>::get(lane_id, sender) {
+		return Ok(None);
+	}
+
+	let current_block = frame_system::Pallet::<T>::block_number();
+	let mut usage = SenderQuotaUsages::<T, I>::get(lane_id, sender);
+	let period_end = sp_runtime::traits::Saturating::saturating_add(usage.period_start, quota.period);
+	if usage.messages == 0 || current_block >= period_end {
+		usage = SenderQuotaUsage {
+			period_start: current_block,
+			messages: 0,
+			weight: 0,
+		};
+	}
+
+	usage.messages = usage.messages.saturating_add(1);
+	usage.weight = usage.weight.saturating_add(T::WeightInfo::send_message_weight(payload));
+	if usage.messages > quota.max_messages || usage.weight > quota.max_weight {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Message to lane {:?} is rejected because sender {:?} has exceeded its quota {:?}: {:?}",
+			lane_id,
+			sender,
+			quota,
+			usage,
+		);
+
+		return Err(Error::<T, I>::SenderQuotaExceeded);
+	}
+
+	Ok(Some(usage))
+}
+
+/// Replace queued outbound message with the noop message. The fee stays at the relayers fund
+/// account until delivery of the message is confirmed (see `PendingMessageRefunds`).
+///
+/// This function is used both by the `cancel_message` and by the `expire_message` calls. If there's
+/// no account to refund the fee to, the fee is paid to relayers when the delivery is confirmed.
+fn cancel_outbound_message<T: Config<I>, I: Instance>(message_key: &MessageKey, refund_to: Option<T::AccountId>) {
+	OutboundMessages::<T, I>::mutate(message_key, |message_data| {
+		if let Some(message_data) = message_data.as_mut() {
+			message_data.payload = Vec::new();
+		}
+	});
+	OutboundMessageSubmitters::<T, I>::remove(message_key);
+	OutboundMessageExpirations::<T, I>::remove(message_key);
+	if let Some(refund_to) = refund_to {
+		PendingMessageRefunds::<T, I>::insert(message_key, refund_to);
+	}
+}
+
+/// Refund fee of the cancelled message, if the bridged chain has confirmed that the message hasn't
+/// been dispatched.
+///
+/// Returns true if relayers must not be rewarded for delivering the message. Does nothing and
+/// returns false if the message hasn't been cancelled.
+fn settle_cancelled_message<T: Config<I>, I: Instance>(
+	message_key: &MessageKey,
+	message_fee: &T::OutboundMessageFee,
+	undispatched_messages: &[MessageNonce],
+) -> bool {
+	let refund_to = match PendingMessageRefunds::<T, I>::take(message_key) {
+		Some(refund_to) => refund_to,
+		None => return false,
+	};
+
+	// the original message has been proved and delivered before it has been cancelled
+	if undispatched_messages.binary_search(&message_key.nonce).is_err() {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Cancelled message {:?} has been dispatched by the bridged chain",
+			message_key,
+		);
+		Pallet::<T, I>::deposit_event(RawEvent::MessageDispatchedBeforeCancellation(
+			message_key.lane_id,
+			message_key.nonce,
+		));
+		return false;
+	}
+
+	// if the refund fails, the fee stays at the relayers fund account
+	let refund_result = T::MessageDeliveryAndDispatchPayment::refund_delivery_and_dispatch_fee(
+		&refund_to,
+		message_fee,
+		&Pallet::<T, I>::relayer_fund_account_id(),
+	);
+	match refund_result {
+		Ok(_) => Pallet::<T, I>::deposit_event(RawEvent::MessageFeeRefunded(message_key.lane_id, message_key.nonce)),
+		Err(err) => log::error!(
+			target: "runtime::bridge-messages",
+			"Failed to refund fee {:?} of the cancelled message {:?} to {:?}: {:?}",
+			message_fee,
+			message_key,
+			refund_to,
+			err,
+		),
+	}
+
+	true
+}
+
+/// Replace undelivered message with the noop message, refunding its fee.
+///
+/// The part of the fee that has been paid by the submitter is refunded to the submitter. The rest
+/// is transferred to the `UndeliveredMessagesFeeRecipient` account (if it is configured). Messages
+/// that have already been replaced with noop messages are ignored.
+fn prune_undelivered_message<T: Config<I>, I: Instance>(message_key: &MessageKey) -> Result<(), Error<T, I>> {
+	let mut message_data = OutboundMessages::<T, I>::get(message_key)
+		.expect("the message is sent and not yet delivered; so it is in the storage; qed");
+	if message_data.payload.is_empty() && message_data.fee.is_zero() {
+		return Ok(());
+	}
+
+	let relayer_fund_account = Pallet::<T, I>::relayer_fund_account_id();
+	let submitter =
+		OutboundMessageSubmitters::<T, I>::get(message_key).or_else(|| PendingMessageRefunds::<T, I>::get(message_key));
+	let top_ups = OutboundMessageFeeTopUps::<T, I>::get(message_key);
+	let (submitter_refund, recipient_refund) = match submitter {
+		Some(_) => (
+			sp_runtime::traits::Saturating::saturating_sub(message_data.fee.clone(), top_ups.clone()),
+			top_ups,
+		),
+		None => (Zero::zero(), message_data.fee.clone()),
+	};
+
+	if let Some(ref submitter) = submitter {
+		if !submitter_refund.is_zero() {
+			T::MessageDeliveryAndDispatchPayment::refund_delivery_and_dispatch_fee(
+				submitter,
+				&submitter_refund,
+				&relayer_fund_account,
+			)
+			.map_err(|err| {
+				log::trace!(
+					target: "runtime::bridge-messages",
+					"Failed to refund fee {:?} of the pruned message {:?} to {:?}: {:?}",
+					submitter_refund,
+					message_key,
+					submitter,
+					err,
+				);
+
+				Error::<T, I>::FailedToRefundMessageFee
+			})?;
+		}
+	}
+	if let Some(recipient) = T::UndeliveredMessagesFeeRecipient::get() {
+		if !recipient_refund.is_zero() {
+			// if the transfer fails, the fee stays at the relayers fund account, exactly like when
+			// there's no recipient at all
+			let transfer_result = T::MessageDeliveryAndDispatchPayment::refund_delivery_and_dispatch_fee(
+				&recipient,
+				&recipient_refund,
+				&relayer_fund_account,
+			);
+			if let Err(err) = transfer_result {
+				log::trace!(
+					target: "runtime::bridge-messages",
+					"Failed to transfer fee {:?} of the pruned message {:?} to {:?}: {:?}",
+					recipient_refund,
+					message_key,
+					recipient,
+					err,
+				);
+			}
+		}
+	}
+
+	message_data.payload = Vec::new();
+	message_data.fee = Zero::zero();
+	OutboundMessages::<T, I>::insert(message_key, message_data);
+	OutboundMessageSubmitters::<T, I>::remove(message_key);
+	OutboundMessageExpirations::<T, I>::remove(message_key);
+	OutboundMessageFeeTopUps::<T, I>::remove(message_key);
+	PendingMessageRefunds::<T, I>::remove(message_key);
+
+	Ok(())
+}
+
+/// Move share of delivery relayers rewards to the relayer that has submitted delivery confirmation.
+fn reward_confirmation_relayer<AccountId, Fee>(
+	confirmation_relayer: &AccountId,
+	relayers_rewards: &mut RelayersRewards<AccountId, Fee>,
+	confirmation_reward_share: Perbill,
+) where
+	AccountId: Clone + Ord,
+	Fee: AtLeast32BitUnsigned + Clone,
+{
+	if confirmation_reward_share.is_zero() {
+		return;
+	}
+
+	let mut confirmation_reward = Fee::zero();
+	for (relayer, reward) in relayers_rewards.iter_mut() {
+		if relayer == confirmation_relayer {
+			continue;
+		}
+
+		// share is never larger than 100%, so subtraction is safe here
+		let relayer_confirmation_reward = confirmation_reward_share * reward.reward.clone();
+		reward.reward = reward.reward.clone() - relayer_confirmation_reward.clone();
+		confirmation_reward = confirmation_reward.saturating_add(&relayer_confirmation_reward);
+	}
+
+	if !confirmation_reward.is_zero() {
+		let confirmation_relayer_reward = relayers_rewards.entry(confirmation_relayer.clone()).or_default();
+		confirmation_relayer_reward.reward = confirmation_relayer_reward.reward.saturating_add(&confirmation_reward);
+	}
+}
+
+/// Reimburse confirmation transaction fee to the relayer that has submitted delivery confirmation.
+///
+/// The fee is deducted from rewards of other relayers. If the confirmed messages fees are not enough
+/// to cover the whole transaction fee, the confirmation relayer receives all of them.
+fn reimburse_confirmation_transaction_fee<AccountId, Fee>(
+	confirmation_relayer: &AccountId,
+	relayers_rewards: &mut RelayersRewards<AccountId, Fee>,
+	confirmation_transaction_fee: Fee,
+) where
+	AccountId: Clone + Ord,
+	Fee: AtLeast32BitUnsigned + Clone,
+{
+	let mut remaining_fee = confirmation_transaction_fee;
+	let mut reimbursement = Fee::zero();
+	for (relayer, reward) in relayers_rewards.iter_mut() {
+		if remaining_fee.is_zero() {
+			break;
+		}
+		if relayer == confirmation_relayer {
+			// confirmation relayer is already receiving its own reward
+			continue;
+		}
+
+		let relayer_reimbursement = sp_std::cmp::min(reward.reward.clone(), remaining_fee.clone());
+		reward.reward = reward.reward.clone() - relayer_reimbursement.clone();
+		remaining_fee = remaining_fee - relayer_reimbursement.clone();
+		reimbursement = reimbursement.saturating_add(&relayer_reimbursement);
+	}
+
+	if !reimbursement.is_zero() {
+		let confirmation_relayer_reward = relayers_rewards.entry(confirmation_relayer.clone()).or_default();
+		confirmation_relayer_reward.reward = confirmation_relayer_reward.reward.saturating_add(&reimbursement);
+	}
+}
+
+/// Ensure that the origin is either root, or `PalletOwner`.
+fn ensure_owner_or_root<T: Config<I>, I: Instance>(origin: T::Origin) -> Result<(), BadOrigin> {
+	let origin = match T::OwnerOrigin::try_origin(origin) {
+		Ok(_) => return Ok(()),
+		Err(origin) => origin,
+	};
+
+	match origin.into() {
+		Ok(RawOrigin::Root) => Ok(()),
+		Ok(RawOrigin::Signed(ref signer)) if Some(signer) == Pallet::<T, I>::module_owner().as_ref() => Ok(()),
+		_ => Err(BadOrigin),
+	}
+}
+
+/// Ensure that the origin is either root, or `PalletOwner`, or owner of the lane that has been
+/// opened by the `open_lane` call.
+fn ensure_lane_owner_or_root<T: Config<I>, I: Instance>(origin: T::Origin, lane_id: &LaneId) -> Result<(), BadOrigin> {
+	if let Ok(signer) = ensure_signed(origin.clone()) {
+		if Lanes::<T, I>::get(lane_id)
+			.map(|lane_info| lane_info.owner == signer)
+			.unwrap_or(false)
+		{
+			return Ok(());
+		}
+	}
+
+	ensure_owner_or_root::<T, I>(origin)
+}
+
+/// Ensure that the pallet is in normal operational mode.
+fn ensure_normal_operating_mode<T: Config<I>, I: Instance>() -> Result<(), Error<T, I>> {
+	if PalletOperatingMode::<I>::get() != OperatingMode::Normal {
+		Err(Error::<T, I>::Halted)
+	} else {
+		Ok(())
+	}
+}
+
+/// Ensure that the pallet is not halted.
+fn ensure_not_halted<T: Config<I>, I: Instance>() -> Result<(), Error<T, I>> {
+	if PalletOperatingMode::<I>::get() == OperatingMode::Halted {
+		Err(Error::<T, I>::Halted)
+	} else {
+		Ok(())
+	}
+}
+
+/// Ensure that operations on the lane are not halted.
+fn ensure_lane_not_halted<T: Config<I>, I: Instance>(lane: &LaneId) -> Result<(), Error<T, I>> {
+	if Pallet::<T, I>::lane_operating_mode(lane) == OperatingMode::Halted {
+		Err(Error::<T, I>::LaneIsHalted)
+	} else {
+		Ok(())
+	}
+}
+
+/// Creates new inbound lane object, backed by runtime storage.
+fn inbound_lane<T: Config<I>, I: Instance>(lane_id: LaneId) -> InboundLane<RuntimeInboundLaneStorage<T, I>> {
+	InboundLane::new(inbound_lane_storage::<T, I>(lane_id))
+}
+
+/// Creates new runtime inbound lane storage.
+fn inbound_lane_storage<T: Config<I>, I: Instance>(lane_id: LaneId) -> RuntimeInboundLaneStorage<T, I> {
+	RuntimeInboundLaneStorage {
+		lane_id,
+		cached_data: RefCell::new(None),
+		_phantom: Default::default(),
+	}
+}
+
+/// Creates new outbound lane object, backed by runtime storage.
+fn outbound_lane<T: Config<I>, I: Instance>(lane_id: LaneId) -> OutboundLane<RuntimeOutboundLaneStorage<T, I>> {
+	OutboundLane::new(RuntimeOutboundLaneStorage {
+		lane_id,
+		_phantom: Default::default(),
+	})
+}
+
+/// Forget idempotency keys that expire at given block.
+///
+/// Returns weight of the pruning.
+fn prune_expired_idempotency_keys<T: Config<I>, I: Instance>(block_number: T::BlockNumber) -> Weight {
+	let expired_keys = IdempotencyKeysExpirations::<T, I>::take(block_number);
+	let expired_keys_count = expired_keys.len() as Weight;
+	for (sender, idempotency_key) in expired_keys {
+		SentIdempotencyKeys::<T, I>::remove(sender, idempotency_key);
+	}
+
+	T::DbWeight::get().reads_writes(1, expired_keys_count.saturating_add(1))
+}
+
+/// Set fee of every lane, that has relayers bids, to the lowest bid.
+///
+/// Returns weight of the update.
+fn update_lanes_fees<T: Config<I>, I: Instance>() -> Weight {
+	use frame_support::storage::{IterableStorageDoubleMap, IterableStorageMap};
+
+	let mut bids = 0u64;
+	let mut lanes_fees = BTreeMap::new();
+	for (lane_id, _, bid) in LaneFeeBids::<T, I>::iter() {
+		bids += 1;
+		let lane_fee = lanes_fees.entry(lane_id).or_insert_with(|| bid.clone());
+		if bid < *lane_fee {
+			*lane_fee = bid;
+		}
+	}
+
+	let previous_lanes = LaneRequiredFees::<T, I>::drain().count() as u64;
+	let updated_lanes = lanes_fees.len() as u64;
+	for (lane_id, lane_fee) in lanes_fees {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Updated fee of lane {:?}: {:?}",
+			lane_id,
+			lane_fee,
+		);
+		LaneRequiredFees::<T, I>::insert(lane_id, lane_fee);
+	}
+
+	T::DbWeight::get().reads_writes(
+		bids.saturating_add(previous_lanes),
+		previous_lanes.saturating_add(updated_lanes),
+	)
+}
+
+/// Mark messages of the outbound lane as delivered and remember rewards of relayers that have
+/// delivered these messages. Fees of messages that have been skipped by relayers are refunded.
+///
+/// Returns weight of the `T::OnDeliveryConfirmed` handler calls and number of skipped messages.
+fn confirm_delivery<T: Config<I>, I: Instance>(
+	lane_id: LaneId,
+	lane_data: InboundLaneData<T::AccountId>,
+	relayers_rewards: &mut RelayersRewards<T::AccountId, T::OutboundMessageFee>,
+) -> (Weight, MessageNonce) {
+	let mut handler_weight: Weight = 0;
+	let mut delivered_messages = 0;
+	let mut skipped_messages = 0;
+	let mut lane = outbound_lane::<T, I>(lane_id);
+	let last_delivered_nonce = lane_data.last_delivered_nonce();
+	let received_range = lane.confirm_delivery(last_delivered_nonce);
+	if let Some(received_range) = received_range {
+		// this loop is bounded by `T::MaxUnrewardedRelayerEntriesAtInboundLane` on the bridged chain
+		let ranges = split_received_range(received_range, lane_data.last_confirmed_nonce, &lane_data.relayers);
+		for (begin, end, is_delivered) in ranges {
+			if is_delivered {
+				delivered_messages += end - begin + 1;
+				Pallet::<T, I>::deposit_event(RawEvent::MessagesDelivered(lane_id, begin, end));
+				handler_weight = handler_weight
+					.saturating_add(T::OnDeliveryConfirmed::on_messages_delivered(&lane_id, &(begin..=end)));
+				continue;
+			}
+
+			// messages that have been skipped at the unordered lane are never delivered, so their
+			// fees are refunded exactly like fees of pruned undelivered messages.
+			// this loop is bound by `T::MaxUnconfirmedMessagesAtInboundLane` on the bridged chain
+			skipped_messages += end - begin + 1;
+			Pallet::<T, I>::deposit_event(RawEvent::MessagesSkipped(lane_id, begin, end));
+			for nonce in begin..=end {
+				let message_key = MessageKey { lane_id, nonce };
+				if let Err(err) = prune_undelivered_message::<T, I>(&message_key) {
+					log::error!(
+						target: "runtime::bridge-messages",
+						"Failed to refund fee of the skipped message {:?}: {:?}",
+						message_key,
+						err,
+					);
+				}
+			}
+		}
+
+		// remember to reward relayers that have delivered messages
+		// this loop is bounded by `T::MaxUnrewardedRelayerEntriesAtInboundLane` on the bridged chain
+		let mut lane_rewards = T::OutboundMessageFee::zero();
+		for (nonce_low, nonce_high, relayer) in lane_data.relayers {
+			let nonce_begin = sp_std::cmp::max(nonce_low, received_range.0);
+			let nonce_end = sp_std::cmp::min(nonce_high, received_range.1);
+
+			// loop won't proceed if current entry is ahead of received range (begin > end).
+			// this loop is bound by `T::MaxUnconfirmedMessagesAtInboundLane` on the bridged chain
+			let mut relayer_reward = relayers_rewards.entry(relayer).or_default();
+			for nonce in nonce_begin..nonce_end + 1 {
+				let message_key = MessageKey { lane_id, nonce };
+				let message_data = OutboundMessages::<T, I>::get(&message_key)
+					.expect("message was just confirmed; we never prune unconfirmed messages; qed");
+				relayer_reward.messages += 1;
+
+				// fee of the cancelled message that hasn't been dispatched is refunded to its submitter
+				if settle_cancelled_message::<T, I>(&message_key, &message_data.fee, &lane_data.undispatched_messages) {
+					continue;
+				}
+
+				relayer_reward.reward = relayer_reward.reward.saturating_add(&message_data.fee);
+				lane_rewards = lane_rewards.saturating_add(&message_data.fee);
+			}
+		}
+
+		LanesStatistics::<T, I>::mutate(&lane_id, |statistics| {
+			statistics.confirmed_messages = statistics.confirmed_messages.saturating_add(delivered_messages);
+			statistics.paid_rewards = statistics.paid_rewards.saturating_add(&lane_rewards);
+		});
+		update_lane_congestion::<T, I>(lane_id);
+		update_oldest_unconfirmed_message::<T, I>(lane_id, received_range.1);
+	}
+
+	log::trace!(
+		target: "runtime::bridge-messages",
+		"Received messages delivery proof up to (and including) {} at lane {:?}",
+		last_delivered_nonce,
+		lane_id,
+	);
+
+	(handler_weight, skipped_messages)
+}
+
+/// Split received range of nonces into ranges of delivered and skipped messages.
+///
+/// Entries of the unrewarded relayers set only cover messages that have been delivered. So at
+/// lanes with `LaneOrdering::Unordered`, unconfirmed messages that aren't covered by any entry
+/// have been skipped by relayers. Returns sorted `(begin, end, is_delivered)` ranges that cover
+/// the whole received range.
+fn split_received_range<RelayerId>(
+	received_range: (MessageNonce, MessageNonce),
+	last_confirmed_nonce: MessageNonce,
+	relayers: &VecDeque<(MessageNonce, MessageNonce, RelayerId)>,
+) -> Vec<(MessageNonce, MessageNonce, bool)> {
+	let (received_begin, received_end) = received_range;
+	let mut ranges: Vec<(MessageNonce, MessageNonce, bool)> = Vec::new();
+	let mut next_nonce = received_begin;
+	// we don't know which messages have been skipped before `last_confirmed_nonce`, but the
+	// bridged chain only confirms messages that we have already marked as received
+	if next_nonce <= last_confirmed_nonce {
+		let end = sp_std::cmp::min(last_confirmed_nonce, received_end);
+		ranges.push((next_nonce, end, true));
+		next_nonce = end + 1;
+	}
+	for (nonce_low, nonce_high, _) in relayers {
+		let begin = sp_std::cmp::max(*nonce_low, next_nonce);
+		let end = sp_std::cmp::min(*nonce_high, received_end);
+		if begin > end {
+			continue;
+		}
+
+		if begin > next_nonce {
+			ranges.push((next_nonce, begin - 1, false));
+		}
+		match ranges.last_mut() {
+			Some((_, last_end, true)) if *last_end + 1 == begin => *last_end = end,
+			_ => ranges.push((begin, end, true)),
+		}
+		next_nonce = end + 1;
+	}
+	if next_nonce <= received_end {
+		ranges.push((next_nonce, received_end, false));
+	}
+
+	ranges
+}
+
+/// Update the oldest unconfirmed message of the lane after delivery of messages up to (and
+/// including) `latest_received_nonce` has been confirmed.
+fn update_oldest_unconfirmed_message<T: Config<I>, I: Instance>(lane_id: LaneId, latest_received_nonce: MessageNonce) {
+	let latest_generated_nonce = OutboundLanes::<I>::get(&lane_id).latest_generated_nonce;
+	OldestUnconfirmedMessages::<T, I>::mutate_exists(&lane_id, |entry| {
+		if latest_received_nonce >= latest_generated_nonce {
+			*entry = None;
+		} else if let Some((oldest_unconfirmed_nonce, _)) = entry {
+			*oldest_unconfirmed_nonce = sp_std::cmp::max(*oldest_unconfirmed_nonce, latest_received_nonce + 1);
+		}
+	});
+}
+
+/// Weight of the `update_oldest_unconfirmed_message` call.
+fn oldest_unconfirmed_message_update_weight<T: Config<I>, I: Instance>() -> Weight {
+	T::DbWeight::get().reads_writes(2, 1)
+}
+
+/// Weight of reading `PendingMessageRefunds` entries of all messages that are confirmed by the
+/// delivery proof. Refunds themselves are paid by the `cancel_message` call.
+fn pending_message_refunds_weight<T: Config<I>, I: Instance>(relayers_state: &UnrewardedRelayersState) -> Weight {
+	T::DbWeight::get().reads(relayers_state.total_messages)
+}
+
+/// Weight of refunding fees of given number of messages that have been skipped by relayers.
+///
+/// Any unconfirmed message may have been skipped at the unordered lane, so delivery proof calls
+/// declare this weight for all unconfirmed messages and refund it for delivered messages.
+fn skipped_messages_refunds_weight<T: Config<I>, I: Instance>(messages: MessageNonce) -> Weight {
+	// see `prune_undelivered_messages`
+	T::DbWeight::get().reads_writes(messages.saturating_mul(4), messages.saturating_mul(4))
+}
+
+/// Weight of the call that removes and adds given number of relayers to the set of relayers of
+/// the lane.
+fn lane_relayers_update_weight<T: Config<I>, I: Instance>(removed_relayers: u32, added_relayers: u32) -> Weight {
+	let updated_relayers = (removed_relayers as Weight).saturating_add(added_relayers as Weight);
+	T::DbWeight::get().reads_writes(updated_relayers.saturating_add(3), updated_relayers.saturating_add(1))
+}
+
+/// Pay rewards to relayers that have delivered confirmed messages and to the relayer that has
+/// confirmed delivery. Does nothing if no new messages have been confirmed.
+fn reward_relayers<T: Config<I>, I: Instance>(
+	confirmation_relayer: &T::AccountId,
+	mut relayers_rewards: RelayersRewards<T::AccountId, T::OutboundMessageFee>,
+) {
+	if relayers_rewards.is_empty() {
+		return;
+	}
+
+	reward_confirmation_relayer(
+		confirmation_relayer,
+		&mut relayers_rewards,
+		T::ConfirmationRelayerRewardShare::get(),
+	);
+	if let Some(confirmation_transaction_fee) = ConfirmationTransactionFee::<T, I>::get() {
+		reimburse_confirmation_transaction_fee(
+			confirmation_relayer,
+			&mut relayers_rewards,
+			confirmation_transaction_fee,
+		);
+	}
+
+	let relayer_fund_account = Pallet::<T, I>::relayer_fund_account_id();
+	<T as Config<I>>::MessageDeliveryAndDispatchPayment::pay_relayers_rewards(
+		confirmation_relayer,
+		relayers_rewards,
+		&relayer_fund_account,
+	);
+}
+
+/// Inbound lane data, that is stored in the runtime storage.
+///
+/// Number of unrewarded relayer entries of the stored lane is bounded by the
+/// `MaxUnrewardedRelayerEntriesAtInboundLane` constant and number of undispatched messages is
+/// bounded by the `MaxUnconfirmedMessagesAtInboundLane` constant. The wrapper may only be
+/// constructed from the lane data that respects these bounds and it fails to decode if any bound
+/// is violated. So the size of the stored value (and the size of its storage proof) is limited.
+pub struct StoredInboundLaneData<T: Config<I>, I: Instance>(InboundLaneData<T::InboundRelayer>, PhantomData<I>);
+
+impl<T: Config<I>, I: Instance> StoredInboundLaneData<T, I> {
+	/// Wrap inbound lane data. Returns `None` if the lane has more unrewarded relayer entries or
+	/// undispatched messages than allowed.
+	pub fn try_new(data: InboundLaneData<T::InboundRelayer>) -> Option<Self> {
+		if data.relayers.len() as MessageNonce > T::MaxUnrewardedRelayerEntriesAtInboundLane::get() {
+			return None;
+		}
+		if data.undispatched_messages.len() as MessageNonce > T::MaxUnconfirmedMessagesAtInboundLane::get() {
+			return None;
+		}
+
+		Some(StoredInboundLaneData(data, PhantomData))
+	}
+
+	/// Returns maximal encoded size of the stored lane data, given encoded size of the relayer
+	/// identifier.
+	///
+	/// Returns `None` if size overflows `u32` limits.
+	pub fn max_encoded_size(relayer_id_encoded_size: u32) -> Option<u32> {
+		let max_relayers_entries = T::MaxUnrewardedRelayerEntriesAtInboundLane::get();
+		let max_undispatched_messages = T::MaxUnconfirmedMessagesAtInboundLane::get();
+		InboundLaneData::<T::InboundRelayer>::encoded_size_hint(
+			relayer_id_encoded_size,
+			u32::try_from(max_relayers_entries).ok()?,
+			u32::try_from(max_undispatched_messages).ok()?,
+		)
+	}
+
+	/// Unwrap inbound lane data.
+	pub fn into_inner(self) -> InboundLaneData<T::InboundRelayer> {
+		self.0
+	}
+}
+
+impl<T: Config<I>, I: Instance> Deref for StoredInboundLaneData<T, I> {
+	type Target = InboundLaneData<T::InboundRelayer>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl<T: Config<I>, I: Instance> Default for StoredInboundLaneData<T, I> {
+	fn default() -> Self {
+		StoredInboundLaneData(Default::default(), PhantomData)
+	}
+}
+
+impl<T: Config<I>, I: Instance> Encode for StoredInboundLaneData<T, I> {
+	fn size_hint(&self) -> usize {
+		self.0.size_hint()
+	}
+
+	fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+		self.0.using_encoded(f)
+	}
+}
+
+impl<T: Config<I>, I: Instance> codec::EncodeLike for StoredInboundLaneData<T, I> {}
+
+impl<T: Config<I>, I: Instance> Decode for StoredInboundLaneData<T, I> {
+	fn decode<Input: codec::Input>(input: &mut Input) -> Result<Self, codec::Error> {
+		let data = InboundLaneData::decode(input)?;
+		Self::try_new(data)
+			.ok_or_else(|| "Too many unrewarded relayer entries or undispatched messages at the inbound lane".into())
+	}
+}
+
+/// Inbound message that is waiting for dispatch at the lane with deferred dispatch mode.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct QueuedInboundMessage<AccountId, Payload, Fee> {
+	/// Message key.
+	pub key: MessageKey,
+	/// Account of the relayer that has delivered the message. If message dispatch fee is paid at
+	/// this chain, this account receives the dispatch fee.
+	pub relayer: AccountId,
+	/// Decoded message payload.
+	pub payload: Payload,
+	/// Message delivery and dispatch fee, paid by the submitter.
+	pub fee: Fee,
+}
+
+/// Weight that the relayer pays for the dispatch of the message, delivered over the lane with
+/// given dispatch mode.
+///
+/// Messages of lanes with deferred dispatch mode are only put to the lane queue by the delivery
+/// transaction, so the relayer doesn't pay for their dispatch.
+fn message_dispatch_weight<T: Config<I>, I: Instance>(
+	dispatch_mode: DispatchMode,
+	message: &DispatchMessage<T::InboundPayload, T::InboundMessageFee>,
+) -> Weight {
+	match dispatch_mode {
+		DispatchMode::Immediate => T::MessageDispatch::dispatch_weight(message),
+		DispatchMode::Deferred => T::DbWeight::get().reads_writes(2, 3),
+	}
+}
+
+/// Maximal dispatch weight of the message that may be queued at the lane with deferred dispatch mode.
+///
+/// Every queued message must fit into the single `dispatch_queued_messages` call. Otherwise it would
+/// block the lane queue forever.
+fn max_queued_message_dispatch_weight<T: Config<I>, I: Instance>() -> Weight {
+	let block_weights = <T as frame_system::Config>::BlockWeights::get();
+	let normal_class_weights = block_weights.get(DispatchClass::Normal);
+	let max_extrinsic_weight = normal_class_weights.max_extrinsic.unwrap_or(block_weights.max_block);
+	let db_weight = T::DbWeight::get();
+	max_extrinsic_weight
+		.saturating_sub(normal_class_weights.base_extrinsic)
+		.saturating_sub(db_weight.reads_writes(1, 1))
+		.saturating_sub(db_weight.reads_writes(2, 2))
+}
+
+/// Message dispatch that puts messages to the lane queue of the `QueuedInboundMessages` instead of
+/// dispatching them.
+struct DeferredMessageDispatch<T, I>(PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: Instance> MessageDispatch<T::AccountId, T::InboundMessageFee> for DeferredMessageDispatch<T, I> {
+	type DispatchPayload = T::InboundPayload;
+
+	fn dispatch_weight(message: &DispatchMessage<T::InboundPayload, T::InboundMessageFee>) -> Weight {
+		T::MessageDispatch::dispatch_weight(message)
+	}
+
+	fn dispatch(
+		relayer_account: &T::AccountId,
+		message: DispatchMessage<T::InboundPayload, T::InboundMessageFee>,
+	) -> Weight {
+		let payload = match message.data.payload {
+			Ok(payload) => payload,
+			Err(error) => {
+				// there's nothing to dispatch later, so let the dispatcher handle invalid payload now
+				T::MessageDispatch::dispatch(
+					relayer_account,
+					DispatchMessage {
+						key: message.key,
+						data: DispatchMessageData {
+							payload: Err(error),
+							fee: message.data.fee,
+						},
+					},
+				);
+				return 0;
+			}
+		};
+
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Queueing message {:?}/{} for deferred dispatch",
+			message.key.lane_id,
+			message.key.nonce,
+		);
+
+		let lane_id = message.key.lane_id;
+		let (head, tail) = QueuedInboundMessagesRanges::<I>::get(&lane_id).unwrap_or_else(|| {
+			QueuedInboundLanes::<I>::mutate(|lanes| lanes.push(lane_id));
+			(0, 0)
+		});
+		QueuedInboundMessages::<T, I>::insert(
+			&lane_id,
+			tail,
+			QueuedInboundMessage {
+				key: message.key,
+				relayer: relayer_account.clone(),
+				payload,
+				fee: message.data.fee,
+			},
+		);
+		QueuedInboundMessagesRanges::<I>::insert(&lane_id, (head, tail + 1));
+
+		0
+	}
+}
+
+/// Dispatch queued inbound messages, while the next message fits into the `max_weight`. Returns
+/// weight that has been actually used.
+///
+/// Lanes are served in round-robin fashion, so the busy lane can't starve other lanes. Messages of
+/// every lane are dispatched in the order they have been received.
+fn dispatch_queued_inbound_messages<T: Config<I>, I: Instance>(max_weight: Weight) -> Weight {
+	let db_weight = T::DbWeight::get();
+	let mut used_weight = db_weight.reads_writes(1, 1);
+	if used_weight > max_weight {
+		return 0;
+	}
+
+	let max_message_dispatch_weight = max_queued_message_dispatch_weight::<T, I>();
+	let mut lanes: VecDeque<LaneId> = QueuedInboundLanes::<I>::get().into();
+	let mut processed_messages = 0;
+	while let Some(lane_id) = lanes.front().cloned() {
+		let (head, tail) = QueuedInboundMessagesRanges::<I>::get(&lane_id)
+			.expect("all lanes in the QueuedInboundLanes have non-empty queues; qed");
+		let QueuedInboundMessage {
+			key,
+			relayer,
+			payload,
+			fee,
+		} = QueuedInboundMessages::<T, I>::get(&lane_id, head)
+			.expect("all messages in the [head; tail) range are in the lane queue; qed");
+		let message = DispatchMessage {
+			key,
+			data: DispatchMessageData {
+				payload: Ok(payload),
+				fee,
+			},
+		};
+
+		// messages that are too heavy are not queued, but the limit may have been lowered after
+		// the message has been queued. Such message would never fit, so we skip it
+		let message_dispatch_weight = T::MessageDispatch::dispatch_weight(&message);
+		let is_skipped = message_dispatch_weight > max_message_dispatch_weight;
+		let message_weight = if is_skipped {
+			db_weight.reads_writes(2, 2)
+		} else {
+			db_weight.reads_writes(2, 2).saturating_add(message_dispatch_weight)
+		};
+		if used_weight.saturating_add(message_weight) > max_weight {
+			break;
+		}
+
+		let unspent_weight = if is_skipped {
+			log::trace!(
+				target: "runtime::bridge-messages",
+				"Skipping queued message {:?}/{}: dispatch weight {} exceeds the limit {}",
+				message.key.lane_id,
+				message.key.nonce,
+				message_dispatch_weight,
+				max_message_dispatch_weight,
+			);
+
+			Pallet::<T, I>::deposit_event(RawEvent::QueuedMessageSkipped(message.key.lane_id, message.key.nonce));
+			0
+		} else {
+			T::MessageDispatch::dispatch(&relayer, message)
+		};
+		QueuedInboundMessages::<T, I>::remove(&lane_id, head);
+		used_weight = used_weight.saturating_add(message_weight.saturating_sub(unspent_weight));
+		processed_messages += 1;
+
+		// the lane is moved to the back of the list, so other lanes are served before its next message
+		lanes.pop_front();
+		if head + 1 == tail {
+			QueuedInboundMessagesRanges::<I>::remove(&lane_id);
+		} else {
+			QueuedInboundMessagesRanges::<I>::insert(&lane_id, (head + 1, tail));
+			lanes.push_back(lane_id);
+		}
+	}
+
+	if processed_messages != 0 {
+		log::trace!(
+			target: "runtime::bridge-messages",
+			"Processed {} queued inbound messages",
+			processed_messages,
+		);
+
+		QueuedInboundLanes::<I>::put(Vec::from(lanes));
+	}
+
+	used_weight
+}
+
+/// Runtime inbound lane storage.
+struct RuntimeInboundLaneStorage<T: Config<I>, I = DefaultInstance> {
+	lane_id: LaneId,
+	cached_data: RefCell<Option<InboundLaneData<T::InboundRelayer>>>,
+	_phantom: PhantomData<I>,
+}
+
+impl<T: Config<I>, I: Instance> InboundLaneStorage for RuntimeInboundLaneStorage<T, I> {
+	type MessageFee = T::InboundMessageFee;
+	type Relayer = T::InboundRelayer;
+
+	fn id(&self) -> LaneId {
+		self.lane_id
+	}
+
+	fn ordering(&self) -> LaneOrdering {
+		Pallet::<T, I>::lane_ordering(&self.lane_id)
+	}
+
+	fn max_dispatch_weight(&self) -> Option<Weight> {
+		let lane_max_dispatch_weight =
+			Pallet::<T, I>::lane_message_limits(&self.lane_id).map(|limits| limits.max_dispatch_weight);
+		match Pallet::<T, I>::lane_dispatch_mode(&self.lane_id) {
+			DispatchMode::Immediate => lane_max_dispatch_weight,
+			// messages that will never fit into the `dispatch_queued_messages` call are not queued
+			DispatchMode::Deferred => {
+				let max_queued_message_dispatch_weight = max_queued_message_dispatch_weight::<T, I>();
+				Some(
+					lane_max_dispatch_weight
+						.map(|weight| sp_std::cmp::min(weight, max_queued_message_dispatch_weight))
+						.unwrap_or(max_queued_message_dispatch_weight),
+				)
+			}
+		}
+	}
+
+	fn max_unrewarded_relayer_entries(&self) -> MessageNonce {
+		T::MaxUnrewardedRelayerEntriesAtInboundLane::get()
+	}
+
+	fn max_unconfirmed_messages(&self) -> MessageNonce {
+		T::MaxUnconfirmedMessagesAtInboundLane::get()
+	}
+
+	fn is_dispatch_queue_full(&self) -> bool {
+		match Pallet::<T, I>::lane_dispatch_mode(&self.lane_id) {
+			DispatchMode::Immediate => false,
+			DispatchMode::Deferred => {
+				let (head, tail) = QueuedInboundMessagesRanges::<I>::get(&self.lane_id).unwrap_or((0, 0));
+				tail.saturating_sub(head) >= T::MaxQueuedMessagesAtInboundLane::get()
+			}
+		}
+	}
+
+	fn data(&self) -> InboundLaneData<T::InboundRelayer> {
+		match self.cached_data.clone().into_inner() {
+			Some(data) => data,
+			None => {
+				let data = InboundLanes::<T, I>::get(&self.lane_id).into_inner();
+				*self.cached_data.try_borrow_mut().expect(
+					"we're in the single-threaded environment;\
+						we have no recursive borrows; qed",
+				) = Some(data.clone());
+				data
+			}
+		}
+	}
+
+	fn set_data(&mut self, data: InboundLaneData<T::InboundRelayer>) {
+		*self.cached_data.try_borrow_mut().expect(
+			"we're in the single-threaded environment;\
+				we have no recursive borrows; qed",
+		) = Some(data.clone());
+		InboundLanes::<T, I>::insert(
+			&self.lane_id,
+			StoredInboundLaneData::<T, I>::try_new(data)
+				.expect("InboundLane never adds relayers entries over the max_unrewarded_relayer_entries; qed"),
+		)
+	}
+}
+
+/// Runtime outbound lane storage.
+struct RuntimeOutboundLaneStorage<T, I = DefaultInstance> {
+	lane_id: LaneId,
+	_phantom: PhantomData<(T, I)>,
+}
+
+impl<T: Config<I>, I: Instance> OutboundLaneStorage for RuntimeOutboundLaneStorage<T, I> {
+	type MessageFee = T::OutboundMessageFee;
+
+	fn id(&self) -> LaneId {
+		self.lane_id
+	}
+
+	fn data(&self) -> OutboundLaneData {
+		OutboundLanes::<I>::get(&self.lane_id)
+	}
+
+	fn set_data(&mut self, data: OutboundLaneData) {
+		OutboundLanes::<I>::insert(&self.lane_id, data)
+	}
+
+	#[cfg(test)]
+	fn message(&self, nonce: &MessageNonce) -> Option<MessageData<T::OutboundMessageFee>> {
+		OutboundMessages::<T, I>::get(MessageKey {
+			lane_id: self.lane_id,
+			nonce: *nonce,
+		})
+	}
+
+	fn save_message(&mut self, nonce: MessageNonce, mesage_data: MessageData<T::OutboundMessageFee>) {
+		OutboundMessages::<T, I>::insert(
+			MessageKey {
+				lane_id: self.lane_id,
+				nonce,
+			},
+			mesage_data,
+		);
+	}
+
+	fn remove_message(&mut self, nonce: &MessageNonce) {
+		let message_key = MessageKey {
+			lane_id: self.lane_id,
+			nonce: *nonce,
+		};
+		OutboundMessages::<T, I>::remove(&message_key);
+		OutboundMessageSubmitters::<T, I>::remove(&message_key);
+		OutboundMessageExpirations::<T, I>::remove(&message_key);
+		OutboundMessageFeeTopUps::<T, I>::remove(&message_key);
+		PendingMessageRefunds::<T, I>::remove(&message_key);
+	}
+}
+
+/// Convert proof rejection reason into the pallet error.
+///
+/// The `other_error` is returned if there's no specific pallet error for the reason.
+fn proof_rejection_error<T: Config<I>, I: Instance>(
+	reason: ProofRejectionReason,
+	other_error: Error<T, I>,
+) -> Error<T, I> {
+	match reason {
+		ProofRejectionReason::UnknownHeader => Error::<T, I>::UnknownBridgedHeader,
+		ProofRejectionReason::StorageRootMismatch => Error::<T, I>::StorageRootMismatch,
+		ProofRejectionReason::Empty => Error::<T, I>::EmptyMessagesProof,
+		ProofRejectionReason::MessagesCountMismatch => Error::<T, I>::MessagesCountMismatch,
+		ProofRejectionReason::MissingMessage => Error::<T, I>::MissingMessageInTheProof,
+		ProofRejectionReason::FailedToDecodeMessage => Error::<T, I>::FailedToDecodeMessage,
+		ProofRejectionReason::MissingLaneState => Error::<T, I>::MissingLaneStateInTheProof,
+		ProofRejectionReason::FailedToDecodeLaneState => Error::<T, I>::FailedToDecodeLaneState,
+		ProofRejectionReason::ExtraProofNodes => Error::<T, I>::ExtraNodesInTheProof,
+		ProofRejectionReason::Other(_) => other_error,
+	}
+}
+
+/// Verify messages proof and return proved messages with decoded payload, along with the number of
+/// trie nodes that have been traversed during verification.
+#[allow(clippy::type_complexity)]
+fn verify_and_decode_messages_proof<Chain: SourceHeaderChain<Fee>, Fee, DispatchPayload: Decode>(
+	proof: Chain::MessagesProof,
+	messages_count: u32,
+) -> Result<(ProvedMessages<DispatchMessage<DispatchPayload, Fee>>, Option<u32>), Chain::Error> {
+	// `receive_messages_proof` weight formula and `MaxUnconfirmedMessagesAtInboundLane` check
+	// guarantees that the `message_count` is sane and Vec<Message> may be allocated.
+	// (tx with too many messages will either be rejected from the pool, or will fail earlier)
+	Chain::verify_messages_proof_with_traversed_nodes(proof, messages_count).map(
+		|(messages_by_lane, traversed_nodes)| {
+			let messages_by_lane = messages_by_lane
+				.into_iter()
+				.map(|(lane, lane_data)| {
+					(
+						lane,
+						ProvedLaneMessages {
+							lane_state: lane_data.lane_state,
+							messages: lane_data.messages.into_iter().map(Into::into).collect(),
+						},
+					)
+				})
+				.collect();
+			(messages_by_lane, traversed_nodes)
+		},
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{
+		message, run_test, BestFinalizedBridgedHeaderNumber, ConfirmationRelayerRewardShare, Event as TestEvent,
+		LaneCongestionThreshold, Origin, TestMessageDeliveryAndDispatchPayment, TestMessagesDeliveryBatchProof,
+		TestMessagesDeliveryProof, TestMessagesParameter, TestMessagesProof, TestOnDeliveryConfirmed, TestPayload,
+		TestRelayer, TestRuntime, TokenConversionRate, DELIVERY_CONFIRMATION_HANDLER_WEIGHT,
+		PAYLOAD_REJECTED_BY_TARGET_CHAIN, PAYLOAD_WITH_UNSPENT_WEIGHT, REGULAR_PAYLOAD, TEST_FEE_RECIPIENT,
+		TEST_LANE_ID, TEST_OWNER_ORIGIN_ACCOUNT, TEST_RELAYER_A, TEST_RELAYER_B, TEST_RELAYER_C,
+	};
+	use bp_messages::UnrewardedRelayersState;
+	use frame_support::{
+		assert_noop, assert_ok,
+		traits::{OnIdle, OnInitialize},
+	};
+	use frame_system::{EventRecord, Pallet as System, Phase};
+	use hex_literal::hex;
+	use sp_runtime::DispatchError;
+
+	fn get_ready_for_events() {
+		System::<TestRuntime>::set_block_number(1);
+		System::<TestRuntime>::reset_events();
+	}
+
+	fn send_regular_message() {
+		get_ready_for_events();
+
+		assert_ok!(Pallet::<TestRuntime>::send_message(
+			Origin::signed(1),
+			TEST_LANE_ID,
+			REGULAR_PAYLOAD,
+			REGULAR_PAYLOAD.1,
+		));
+
+		// check event with assigned nonce
+		assert_eq!(
+			System::<TestRuntime>::events(),
+			vec![EventRecord {
+				phase: Phase::Initialization,
+				event: TestEvent::pallet_bridge_messages(RawEvent::MessageAccepted(TEST_LANE_ID, 1)),
+				topics: vec![],
+			}],
+		);
+
+		// check that fee has been withdrawn from submitter
+		assert!(TestMessageDeliveryAndDispatchPayment::is_fee_paid(1, REGULAR_PAYLOAD.1));
+	}
+
+	fn receive_messages_delivery_proof() {
+		System::<TestRuntime>::set_block_number(1);
+		System::<TestRuntime>::reset_events();
+
+		assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+			Origin::signed(1),
+			TestMessagesDeliveryProof(Ok((
+				TEST_LANE_ID,
+				InboundLaneData {
+					last_confirmed_nonce: 1,
+					..Default::default()
+				},
+			))),
+			Default::default(),
+		));
+
+		assert_eq!(
+			System::<TestRuntime>::events(),
+			vec![EventRecord {
+				phase: Phase::Initialization,
+				event: TestEvent::pallet_bridge_messages(RawEvent::MessagesDelivered(TEST_LANE_ID, 1, 1)),
+				topics: vec![],
+			}],
+		);
+	}
+
+	#[test]
+	fn pallet_owner_may_change_owner() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_ok!(Pallet::<TestRuntime>::set_owner(Origin::root(), Some(1)));
+			assert_noop!(
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(2), OperatingMode::Halted),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Halted
+			));
+
+			assert_ok!(Pallet::<TestRuntime>::set_owner(Origin::signed(1), None));
+			assert_noop!(
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(2), OperatingMode::Normal),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Normal
+			));
+		});
+	}
+
+	#[test]
+	fn pallet_may_be_halted_by_root() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Halted
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Normal
+			));
+		});
+	}
+
+	#[test]
+	fn pallet_may_not_be_resumed_until_storage_is_migrated() {
+		run_test(|| {
+			PalletStorageVersion::<DefaultInstance>::put(StorageVersion::V2);
+			PalletOperatingMode::<DefaultInstance>::put(OperatingMode::Halted);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::set_operating_mode(Origin::root(), OperatingMode::Normal),
+				Error::<TestRuntime, DefaultInstance>::StorageNotMigrated,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_operating_mode(Origin::root(), OperatingMode::RejectingOutboundMessages),
+				Error::<TestRuntime, DefaultInstance>::StorageNotMigrated,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::root(),
+				OperatingMode::Halted
+			));
+		});
+	}
+
+	#[test]
+	fn pallet_may_be_halted_by_owner_origin() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(TEST_OWNER_ORIGIN_ACCOUNT),
+				OperatingMode::Halted
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(TEST_OWNER_ORIGIN_ACCOUNT),
+				OperatingMode::Normal
+			));
+		});
+	}
+
+	#[test]
+	fn pallet_may_be_halted_by_owner() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(2),
+				OperatingMode::Halted
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(2),
+				OperatingMode::Normal
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Halted),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
+				DispatchError::BadOrigin,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
+				Origin::signed(2),
+				OperatingMode::Halted
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
+				DispatchError::BadOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn pallet_parameter_may_be_updated_by_root() {
+		run_test(|| {
+			get_ready_for_events();
+
+			let parameter = TestMessagesParameter::TokenConversionRate(10.into());
+			assert_ok!(Pallet::<TestRuntime>::update_pallet_parameter(
+				Origin::root(),
+				parameter.clone(),
+			));
+
+			assert_eq!(TokenConversionRate::get(), 10.into());
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::pallet_bridge_messages(RawEvent::ParameterUpdated(parameter)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn pallet_parameter_may_be_updated_by_owner() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+			get_ready_for_events();
+
+			let parameter = TestMessagesParameter::TokenConversionRate(10.into());
+			assert_ok!(Pallet::<TestRuntime>::update_pallet_parameter(
+				Origin::signed(2),
+				parameter.clone(),
+			));
+
+			assert_eq!(TokenConversionRate::get(), 10.into());
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::pallet_bridge_messages(RawEvent::ParameterUpdated(parameter)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn pallet_parameter_cant_be_updated_by_arbitrary_submitter() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::update_pallet_parameter(
+					Origin::signed(2),
+					TestMessagesParameter::TokenConversionRate(10.into()),
+				),
+				DispatchError::BadOrigin,
+			);
+
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::update_pallet_parameter(
+					Origin::signed(1),
+					TestMessagesParameter::TokenConversionRate(10.into()),
+				),
+				DispatchError::BadOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn fixed_u128_works_as_i_think() {
+		// this test is here just to be sure that conversion rate may be represented with FixedU128
+		run_test(|| {
+			use sp_runtime::{FixedPointNumber, FixedU128};
+
+			// 1:1 conversion that we use by default for testnets
+			let rialto_token = 1u64;
+			let rialto_token_in_millau_tokens = TokenConversionRate::get().saturating_mul_int(rialto_token);
+			assert_eq!(rialto_token_in_millau_tokens, 1);
+
+			// let's say conversion rate is 1:1.7
+			let conversion_rate = FixedU128::saturating_from_rational(170, 100);
+			let rialto_tokens = 100u64;
+			let rialto_tokens_in_millau_tokens = conversion_rate.saturating_mul_int(rialto_tokens);
+			assert_eq!(rialto_tokens_in_millau_tokens, 170);
+
+			// let's say conversion rate is 1:0.25
+			let conversion_rate = FixedU128::saturating_from_rational(25, 100);
+			let rialto_tokens = 100u64;
+			let rialto_tokens_in_millau_tokens = conversion_rate.saturating_mul_int(rialto_tokens);
+			assert_eq!(rialto_tokens_in_millau_tokens, 25);
+		});
+	}
+
+	#[test]
+	fn pallet_rejects_transactions_if_halted() {
+		run_test(|| {
+			// send message first to be able to check that delivery_proof fails later
+			send_regular_message();
+
+			PalletOperatingMode::<DefaultInstance>::put(OperatingMode::Halted);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::Halted,
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 1,),
+				Error::<TestRuntime, DefaultInstance>::Halted,
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(2, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::Halted,
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							last_confirmed_nonce: 1,
+							..Default::default()
+						},
+					))),
+					Default::default(),
+				),
+				Error::<TestRuntime, DefaultInstance>::Halted,
+			);
+		});
+	}
+
+	#[test]
+	fn pallet_rejects_new_messages_in_rejecting_outbound_messages_operating_mode() {
+		run_test(|| {
+			// send message first to be able to check that delivery_proof fails later
+			send_regular_message();
+
+			PalletOperatingMode::<DefaultInstance>::put(OperatingMode::RejectingOutboundMessages);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::Halted,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::increase_message_fee(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				1,
+				1,
+			));
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			),);
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						last_confirmed_nonce: 1,
+						..Default::default()
+					},
+				))),
+				Default::default(),
+			));
+		});
+	}
+
+	#[test]
+	fn pallet_rejects_transactions_if_lane_is_halted() {
+		run_test(|| {
+			// send message first to be able to check that delivery_proof fails later
+			send_regular_message();
+
+			LaneOperatingModes::<DefaultInstance>::insert(TEST_LANE_ID, OperatingMode::Halted);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::LaneIsHalted,
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 1,),
+				Error::<TestRuntime, DefaultInstance>::LaneIsHalted,
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(2, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::LaneIsHalted,
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							last_confirmed_nonce: 1,
+							..Default::default()
+						},
+					))),
+					Default::default(),
+				),
+				Error::<TestRuntime, DefaultInstance>::LaneIsHalted,
+			);
+
+			// other lanes are not affected
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				[0, 0, 0, 2],
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+		});
+	}
+
+	#[test]
+	fn pallet_rejects_new_messages_if_lane_is_rejecting_outbound_messages() {
+		run_test(|| {
+			// send message first to be able to check that delivery_proof succeeds later
+			send_regular_message();
+
+			LaneOperatingModes::<DefaultInstance>::insert(TEST_LANE_ID, OperatingMode::RejectingOutboundMessages);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::LaneIsRejectingOutboundMessages,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::increase_message_fee(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				1,
+				1,
+			));
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			),);
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						last_confirmed_nonce: 1,
+						..Default::default()
+					},
+				))),
+				Default::default(),
+			));
+		});
+	}
+
+	#[test]
+	fn lane_state_may_only_be_changed_by_owner_or_root() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_state(Origin::signed(1), TEST_LANE_ID, LaneState::Closing),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				LaneState::Closing,
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneState::Opened,
+			));
+		});
+	}
+
+	#[test]
+	fn closing_lane_rejects_new_messages_but_delivers_queued_messages() {
+		run_test(|| {
+			send_regular_message();
+
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneState::Closing,
+			));
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).state,
+				LaneState::Closing
+			);
+			assert_eq!(
+				InboundLanes::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID).state,
+				LaneState::Closing,
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::LaneIsNotOpened,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			receive_messages_delivery_proof();
+		});
+	}
+
+	#[test]
+	fn lane_is_only_closed_when_drained() {
+		run_test(|| {
+			send_regular_message();
+
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_state(Origin::root(), TEST_LANE_ID, LaneState::Closed),
+				Error::<TestRuntime, DefaultInstance>::InvalidLaneStateTransition,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneState::Closing,
+			));
+
+			// outbound message is not yet confirmed
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_state(Origin::root(), TEST_LANE_ID, LaneState::Closed),
+				Error::<TestRuntime, DefaultInstance>::LaneIsNotDrained,
+			);
+			receive_messages_delivery_proof();
+
+			// inbound message is not yet confirmed
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_state(Origin::root(), TEST_LANE_ID, LaneState::Closed),
+				Error::<TestRuntime, DefaultInstance>::LaneIsNotDrained,
+			);
+			let lane_state_proof = TestMessagesProof {
+				result: Ok(vec![(
+					TEST_LANE_ID,
+					ProvedLaneMessages {
+						lane_state: Some(OutboundLaneData {
+							latest_received_nonce: 1,
+							..Default::default()
+						}),
+						messages: Vec::new(),
+					},
+				)]),
+			};
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				lane_state_proof,
+				0,
+				0,
+			));
+
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneState::Closed,
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_state(Origin::root(), TEST_LANE_ID, LaneState::Opened),
+				Error::<TestRuntime, DefaultInstance>::InvalidLaneStateTransition,
+			);
+		});
+	}
+
+	#[test]
+	fn closed_lane_rejects_everything_and_may_be_reaped() {
+		run_test(|| {
+			send_regular_message();
+			receive_messages_delivery_proof();
+
+			assert_noop!(
+				Pallet::<TestRuntime>::reap_lane(Origin::root(), TEST_LANE_ID),
+				Error::<TestRuntime, DefaultInstance>::LaneIsNotClosed,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneState::Closing,
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneState::Closed,
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::LaneIsClosed,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							last_confirmed_nonce: 1,
+							..Default::default()
+						},
+					))),
+					Default::default(),
+				),
+				Error::<TestRuntime, DefaultInstance>::LaneIsClosed,
+			);
+
+			assert!(Pallet::<TestRuntime>::outbound_message_data(TEST_LANE_ID, 1).is_some());
+			assert_ok!(Pallet::<TestRuntime>::reap_lane(Origin::root(), TEST_LANE_ID));
+			assert!(Pallet::<TestRuntime>::outbound_message_data(TEST_LANE_ID, 1).is_none());
+			assert!(!OutboundLanes::<DefaultInstance>::contains_key(&TEST_LANE_ID));
+			assert!(!InboundLanes::<TestRuntime, DefaultInstance>::contains_key(
+				&TEST_LANE_ID
+			));
+		});
+	}
+
+	#[test]
+	fn lane_may_only_be_opened_by_owner_or_root() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+			System::<TestRuntime>::set_block_number(10);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::open_lane(Origin::signed(1), TEST_LANE_ID, 3, 16),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::open_lane(Origin::signed(2), TEST_LANE_ID, 3, 16));
+			assert!(Pallet::<TestRuntime>::is_lane_registered(&TEST_LANE_ID));
+			assert_eq!(
+				Lanes::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID),
+				Some(LaneInfo {
+					owner: 3,
+					opened_at: 10,
+					max_pending_messages: 16,
+				}),
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::open_lane(Origin::root(), TEST_LANE_ID, 3, 16),
+				Error::<TestRuntime, DefaultInstance>::LaneIsAlreadyOpened,
+			);
+		});
+	}
+
+	#[test]
+	fn closing_lane_may_not_be_opened() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneState::Closing,
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::open_lane(Origin::root(), TEST_LANE_ID, 3, 16),
+				Error::<TestRuntime, DefaultInstance>::LaneIsNotOpened,
+			);
+		});
+	}
+
+	#[test]
+	fn registered_lane_rejects_messages_over_pending_messages_limit() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::open_lane(Origin::root(), TEST_LANE_ID, 3, 1));
+			send_regular_message();
+
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::TooManyPendingMessages,
+			);
+
+			receive_messages_delivery_proof();
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+		});
+	}
+
+	#[test]
+	fn lane_may_be_closed_by_lane_owner_and_reaped() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::close_lane(Origin::root(), TEST_LANE_ID),
+				Error::<TestRuntime, DefaultInstance>::UnknownLane,
+			);
+			assert_ok!(Pallet::<TestRuntime>::open_lane(Origin::root(), TEST_LANE_ID, 3, 16));
+			assert_noop!(
+				Pallet::<TestRuntime>::close_lane(Origin::signed(1), TEST_LANE_ID),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::close_lane(Origin::signed(3), TEST_LANE_ID));
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).state,
+				LaneState::Closing
+			);
+			assert_eq!(
+				InboundLanes::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID).state,
+				LaneState::Closing,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::close_lane(Origin::signed(2), TEST_LANE_ID),
+				Error::<TestRuntime, DefaultInstance>::InvalidLaneStateTransition,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::set_lane_state(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneState::Closed,
+			));
+			assert_ok!(Pallet::<TestRuntime>::reap_lane(Origin::root(), TEST_LANE_ID));
+			assert!(!Pallet::<TestRuntime>::is_lane_registered(&TEST_LANE_ID));
+		});
+	}
+
+	#[test]
+	fn lane_relayers_may_only_be_changed_by_owner_or_root() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_relayers(Origin::signed(1), TEST_LANE_ID, Some(vec![1])),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				Some(vec![1]),
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::root(),
+				TEST_LANE_ID,
+				None
+			));
+		});
+	}
+
+	#[test]
+	fn lane_fee_multiplier_may_only_be_changed_by_owner_or_root() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+			let multiplier = FixedU128::saturating_from_rational(3, 2);
+
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_fee_multiplier(&TEST_LANE_ID),
+				FixedU128::one()
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_fee_multiplier(Origin::signed(1), TEST_LANE_ID, Some(multiplier)),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_fee_multiplier(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				Some(multiplier),
+			));
+			assert_eq!(Pallet::<TestRuntime>::lane_fee_multiplier(&TEST_LANE_ID), multiplier);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_fee_multiplier(
+				Origin::root(),
+				TEST_LANE_ID,
+				None
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_fee_multiplier(&TEST_LANE_ID),
+				FixedU128::one()
+			);
+		});
+	}
+
+	#[test]
+	fn lane_operating_mode_may_only_be_changed_by_owner_or_root() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_operating_mode(&TEST_LANE_ID),
+				OperatingMode::Normal
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_operating_mode(Origin::signed(1), TEST_LANE_ID, OperatingMode::Halted),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_operating_mode(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				OperatingMode::Halted,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_operating_mode(&TEST_LANE_ID),
+				OperatingMode::Halted
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_operating_mode(
+				Origin::root(),
+				TEST_LANE_ID,
+				OperatingMode::Normal,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_operating_mode(&TEST_LANE_ID),
+				OperatingMode::Normal
+			);
+			assert!(!LaneOperatingModes::<DefaultInstance>::contains_key(TEST_LANE_ID));
+		});
+	}
+
+	#[test]
+	fn lane_ordering_may_only_be_changed_by_owner_or_root() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_ordering(&TEST_LANE_ID),
+				LaneOrdering::Ordered
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_ordering(Origin::signed(1), TEST_LANE_ID, LaneOrdering::Unordered),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_ordering(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				LaneOrdering::Unordered,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_ordering(&TEST_LANE_ID),
+				LaneOrdering::Unordered
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_ordering(
+				Origin::root(),
+				TEST_LANE_ID,
+				LaneOrdering::Ordered,
+			));
+			assert!(!LaneOrderings::<DefaultInstance>::contains_key(TEST_LANE_ID));
+		});
+	}
+
+	#[test]
+	fn lane_dispatch_mode_may_only_be_changed_by_owner_or_root() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_dispatch_mode(&TEST_LANE_ID),
+				DispatchMode::Immediate
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_dispatch_mode(Origin::signed(1), TEST_LANE_ID, DispatchMode::Deferred),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_dispatch_mode(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				DispatchMode::Deferred,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_dispatch_mode(&TEST_LANE_ID),
+				DispatchMode::Deferred
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_dispatch_mode(
+				Origin::root(),
+				TEST_LANE_ID,
+				DispatchMode::Immediate,
+			));
+			assert!(!LaneDispatchModes::<DefaultInstance>::contains_key(TEST_LANE_ID));
+		});
+	}
+
+	#[test]
+	fn messages_of_deferred_lane_are_queued_and_dispatched_later() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_lane_dispatch_mode(
+				Origin::root(),
+				TEST_LANE_ID,
+				DispatchMode::Deferred,
+			));
+
+			// relayer doesn't pay for dispatch of messages of deferred lane
+			let dispatch_weight = REGULAR_PAYLOAD.1 * 2;
+			let proof: TestMessagesProof = Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into();
+			let declared_weight =
+				<TestRuntime as Config>::WeightInfo::receive_messages_proof_weight(&proof, 2, dispatch_weight);
+			let post_info = Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				proof,
+				2,
+				dispatch_weight,
+			)
+			.unwrap();
+			assert_eq!(post_info.actual_weight, Some(declared_weight - dispatch_weight));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 2);
+			assert_eq!(
+				QueuedInboundMessagesRanges::<DefaultInstance>::get(TEST_LANE_ID),
+				Some((0, 2))
+			);
+			assert_eq!(QueuedInboundLanes::<DefaultInstance>::get(), vec![TEST_LANE_ID]);
+
+			// block has spare weight to dispatch the first message only
+			Pallet::<TestRuntime>::on_idle(1, REGULAR_PAYLOAD.1 + REGULAR_PAYLOAD.1 / 2);
+			assert_eq!(
+				QueuedInboundMessagesRanges::<DefaultInstance>::get(TEST_LANE_ID),
+				Some((1, 2))
+			);
+			assert!(!QueuedInboundMessages::<TestRuntime>::contains_key(TEST_LANE_ID, 0));
+
+			// second message is dispatched by anyone who pays for that
+			assert_ok!(Pallet::<TestRuntime>::dispatch_queued_messages(
+				Origin::signed(1),
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(QueuedInboundMessagesRanges::<DefaultInstance>::get(TEST_LANE_ID), None);
+			assert!(!QueuedInboundMessages::<TestRuntime>::contains_key(TEST_LANE_ID, 1));
+			assert!(QueuedInboundLanes::<DefaultInstance>::get().is_empty());
+		});
+	}
+
+	fn queue_message(lane_id: LaneId, nonce: MessageNonce, payload: TestPayload) {
+		DeferredMessageDispatch::<TestRuntime, DefaultInstance>::dispatch(
+			&1,
+			DispatchMessage {
+				key: MessageKey { lane_id, nonce },
+				data: DispatchMessageData {
+					payload: Ok(payload),
+					fee: 0,
+				},
+			},
+		);
+	}
+
+	#[test]
+	fn deferred_lane_rejects_messages_when_queue_is_full() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_lane_dispatch_mode(
+				Origin::root(),
+				TEST_LANE_ID,
+				DispatchMode::Deferred,
+			));
+
+			let max_queued_messages = <TestRuntime as Config>::MaxQueuedMessagesAtInboundLane::get();
+			for nonce in 1..=max_queued_messages {
+				queue_message(TEST_LANE_ID, nonce, REGULAR_PAYLOAD);
+			}
+
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					1,
+					0,
+				),
+				Error::<TestRuntime, DefaultInstance>::TooManyQueuedMessages,
+			);
+
+			// once the queue has some room, the message is accepted
+			Pallet::<TestRuntime>::on_idle(1, REGULAR_PAYLOAD.1);
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				0,
+			));
+			assert_eq!(
+				QueuedInboundMessagesRanges::<DefaultInstance>::get(TEST_LANE_ID),
+				Some((1, max_queued_messages + 1)),
+			);
+		});
+	}
+
+	#[test]
+	fn queued_messages_of_different_lanes_are_dispatched_in_round_robin_fashion() {
+		run_test(|| {
+			let busy_lane_id = TEST_LANE_ID;
+			let other_lane_id = [0, 0, 0, 2];
+			queue_message(busy_lane_id, 1, REGULAR_PAYLOAD);
+			queue_message(busy_lane_id, 2, REGULAR_PAYLOAD);
+			queue_message(busy_lane_id, 3, REGULAR_PAYLOAD);
+			queue_message(other_lane_id, 1, REGULAR_PAYLOAD);
+			assert_eq!(
+				QueuedInboundLanes::<DefaultInstance>::get(),
+				vec![busy_lane_id, other_lane_id],
+			);
+
+			// block has spare weight to dispatch two messages: one of every lane
+			Pallet::<TestRuntime>::on_idle(1, REGULAR_PAYLOAD.1 * 2);
+			assert_eq!(
+				QueuedInboundMessagesRanges::<DefaultInstance>::get(busy_lane_id),
+				Some((1, 3))
+			);
+			assert_eq!(QueuedInboundMessagesRanges::<DefaultInstance>::get(other_lane_id), None);
+			assert_eq!(QueuedInboundLanes::<DefaultInstance>::get(), vec![busy_lane_id]);
+		});
+	}
+
+	#[test]
+	fn deferred_lane_does_not_queue_messages_that_never_fit_into_dispatch_call() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_lane_dispatch_mode(
+				Origin::root(),
+				TEST_LANE_ID,
+				DispatchMode::Deferred,
+			));
+
+			let heavy_payload = TestPayload(
+				0,
+				max_queued_message_dispatch_weight::<TestRuntime, DefaultInstance>() + 1,
+			);
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, heavy_payload), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				0,
+			));
+
+			// heavy message is received, but it isn't queued
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 2);
+			assert_eq!(
+				QueuedInboundMessagesRanges::<DefaultInstance>::get(TEST_LANE_ID),
+				Some((0, 1))
+			);
+
+			// so it doesn't block dispatch of other messages
+			Pallet::<TestRuntime>::on_idle(1, REGULAR_PAYLOAD.1 * 2);
+			assert_eq!(QueuedInboundMessagesRanges::<DefaultInstance>::get(TEST_LANE_ID), None);
+		});
+	}
+
+	#[test]
+	fn queued_messages_that_never_fit_into_dispatch_call_are_skipped() {
+		run_test(|| {
+			get_ready_for_events();
+
+			// the message has been queued before the limit has been lowered
+			let heavy_payload = TestPayload(
+				0,
+				max_queued_message_dispatch_weight::<TestRuntime, DefaultInstance>() + 1,
+			);
+			queue_message(TEST_LANE_ID, 1, heavy_payload);
+			queue_message(TEST_LANE_ID, 2, REGULAR_PAYLOAD);
+
+			// heavy message is skipped and the next message is dispatched
+			Pallet::<TestRuntime>::on_idle(1, REGULAR_PAYLOAD.1 * 2);
+			assert_eq!(QueuedInboundMessagesRanges::<DefaultInstance>::get(TEST_LANE_ID), None);
+			assert!(!QueuedInboundMessages::<TestRuntime>::contains_key(TEST_LANE_ID, 0));
+			assert!(!QueuedInboundMessages::<TestRuntime>::contains_key(TEST_LANE_ID, 1));
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::pallet_bridge_messages(RawEvent::QueuedMessageSkipped(TEST_LANE_ID, 1)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn send_message_rejects_messages_above_lane_payload_size_limit() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			let limits = MessageLimits {
+				max_payload_size: REGULAR_PAYLOAD.encoded_size() as u32 - 1,
+				max_dispatch_weight: REGULAR_PAYLOAD.1,
+			};
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_message_limits(Origin::signed(1), TEST_LANE_ID, Some(limits)),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_message_limits(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				Some(limits),
+			));
+			assert_eq!(Pallet::<TestRuntime>::lane_message_limits(&TEST_LANE_ID), Some(limits));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::MessageIsTooLarge,
+			);
+
+			// when limits are removed, the message is accepted
+			assert_ok!(Pallet::<TestRuntime>::set_lane_message_limits(
+				Origin::root(),
+				TEST_LANE_ID,
+				None
+			));
+			assert!(!LaneMessageLimits::<DefaultInstance>::contains_key(TEST_LANE_ID));
+			send_regular_message();
+		});
+	}
+
+	#[test]
+	fn lane_fee_is_updated_from_relayers_bids() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_fee_bid(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				Some(100)
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_fee_bid(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				Some(80)
+			));
+
+			// fee is only updated at the end of auction period
+			assert_eq!(Pallet::<TestRuntime>::on_initialize(9), 0);
+			assert_eq!(Pallet::<TestRuntime>::lane_required_fee(&TEST_LANE_ID), None);
+			Pallet::<TestRuntime>::on_initialize(10);
+			assert_eq!(Pallet::<TestRuntime>::lane_required_fee(&TEST_LANE_ID), Some(80));
+
+			// messages with lower fee are rejected
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(Origin::signed(1), TEST_LANE_ID, REGULAR_PAYLOAD, 79),
+				Error::<TestRuntime, DefaultInstance>::TooLowMessageFee,
+			);
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				80,
+			));
+
+			// when all bids are withdrawn, lane fee is removed at the end of next auction period
+			assert_ok!(Pallet::<TestRuntime>::set_fee_bid(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				None
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_fee_bid(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				None
+			));
+			Pallet::<TestRuntime>::on_initialize(20);
+			assert_eq!(Pallet::<TestRuntime>::lane_required_fee(&TEST_LANE_ID), None);
+		});
+	}
+
+	#[test]
+	fn set_fee_bid_is_only_accepted_from_allowed_relayers() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::root(),
+				TEST_LANE_ID,
+				Some(vec![2, 3]),
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::set_fee_bid(Origin::signed(1), TEST_LANE_ID, Some(100)),
+				Error::<TestRuntime, DefaultInstance>::RelayerIsNotAllowed,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_fee_bid(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				Some(100)
+			));
+			assert_eq!(LaneFeeBids::<TestRuntime>::get(TEST_LANE_ID, 2), Some(100));
+		});
+	}
+
+	#[test]
+	fn send_message_rejects_messages_above_sender_quota() {
+		run_test(|| {
+			let send_message = |sender| {
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(sender),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				)
+			};
+
+			assert_ok!(Pallet::<TestRuntime>::set_lane_sender_quota(
+				Origin::root(),
+				TEST_LANE_ID,
+				Some(SenderQuota {
+					period: 10,
+					max_messages: 2,
+					max_weight: Weight::MAX,
+				}),
+			));
+
+			// the sender may send only two messages within the period, but other senders aren't affected
+			assert_ok!(send_message(1));
+			assert_ok!(send_message(1));
+			assert_noop!(
+				send_message(1),
+				Error::<TestRuntime, DefaultInstance>::SenderQuotaExceeded
+			);
+			assert_ok!(send_message(2));
+
+			// exempt sender isn't limited by the quota
+			assert_ok!(Pallet::<TestRuntime>::set_sender_quota_exemption(
+				Origin::root(),
+				TEST_LANE_ID,
+				1,
+				true,
+			));
+			assert_ok!(send_message(1));
+			assert_ok!(Pallet::<TestRuntime>::set_sender_quota_exemption(
+				Origin::root(),
+				TEST_LANE_ID,
+				1,
+				false,
+			));
+			assert_noop!(
+				send_message(1),
+				Error::<TestRuntime, DefaultInstance>::SenderQuotaExceeded
+			);
+
+			// when the period ends, the quota is reset
+			System::<TestRuntime>::set_block_number(System::<TestRuntime>::block_number() + 10);
+			assert_ok!(send_message(1));
+		});
+	}
+
+	#[test]
+	fn send_message_rejects_messages_above_sender_weight_quota() {
+		run_test(|| {
+			let message_weight = <TestRuntime as Config>::WeightInfo::send_message_weight(&REGULAR_PAYLOAD);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_sender_quota(
+				Origin::root(),
+				TEST_LANE_ID,
+				Some(SenderQuota {
+					period: 10,
+					max_messages: MessageNonce::MAX,
+					max_weight: message_weight,
+				}),
+			));
+
+			send_regular_message();
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::SenderQuotaExceeded,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_is_only_accepted_from_allowed_relayers() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::root(),
+				TEST_LANE_ID,
+				Some(vec![2, 3]),
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::RelayerIsNotAllowed,
+			);
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(2),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+
+			// when restriction is removed, anyone may deliver messages
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::root(),
+				TEST_LANE_ID,
+				None
+			));
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(2, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+		});
+	}
+
+	#[test]
+	fn lane_relayers_may_be_added_and_removed() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::add_lane_relayer(Origin::root(), TEST_LANE_ID, 2));
+			assert_ok!(Pallet::<TestRuntime>::add_lane_relayer(Origin::root(), TEST_LANE_ID, 3));
+			assert_ok!(Pallet::<TestRuntime>::add_lane_relayer(Origin::root(), TEST_LANE_ID, 3));
+			assert_eq!(
+				LaneRelayers::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID),
+				Some(vec![2, 3])
+			);
+			assert!(!Pallet::<TestRuntime>::is_relayer_allowed(&TEST_LANE_ID, &1));
+
+			assert_ok!(Pallet::<TestRuntime>::remove_lane_relayer(
+				Origin::root(),
+				TEST_LANE_ID,
+				2
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::remove_lane_relayer(Origin::root(), TEST_LANE_ID, 2),
+				Error::<TestRuntime, DefaultInstance>::UnknownRelayer,
+			);
+			assert_ok!(Pallet::<TestRuntime>::remove_lane_relayer(
+				Origin::root(),
+				TEST_LANE_ID,
+				3
+			));
+
+			// nobody is allowed to deliver messages when the last relayer is removed
+			assert_eq!(
+				LaneRelayers::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID),
+				Some(vec![])
+			);
+			assert!(!Pallet::<TestRuntime>::is_relayer_allowed(&TEST_LANE_ID, &3));
+		});
+	}
+
+	#[test]
+	fn lane_relayers_may_be_added_by_lane_owner() {
+		run_test(|| {
+			PalletOwner::<TestRuntime>::put(2);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::add_lane_relayer(Origin::signed(3), TEST_LANE_ID, 4),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::add_lane_relayer(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				4
+			));
+
+			assert_ok!(Pallet::<TestRuntime>::open_lane(Origin::root(), TEST_LANE_ID, 3, 16));
+			assert_ok!(Pallet::<TestRuntime>::add_lane_relayer(
+				Origin::signed(3),
+				TEST_LANE_ID,
+				5
+			));
+			assert_ok!(Pallet::<TestRuntime>::remove_lane_relayer(
+				Origin::signed(3),
+				TEST_LANE_ID,
+				4
+			));
+			assert_eq!(
+				LaneRelayers::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID),
+				Some(vec![5])
+			);
+		});
+	}
+
+	#[test]
+	fn lane_relayers_set_is_bounded() {
+		run_test(|| {
+			let max_relayers = <TestRuntime as Config>::MaxLaneRelayers::get() as u64;
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_relayers(
+					Origin::root(),
+					TEST_LANE_ID,
+					Some((1..=max_relayers + 1).collect()),
+				),
+				Error::<TestRuntime, DefaultInstance>::TooManyLaneRelayers,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::root(),
+				TEST_LANE_ID,
+				Some((1..=max_relayers).collect()),
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::add_lane_relayer(Origin::root(), TEST_LANE_ID, max_relayers + 1),
+				Error::<TestRuntime, DefaultInstance>::TooManyLaneRelayers,
+			);
+			// adding relayer that is already in the set is fine
+			assert_ok!(Pallet::<TestRuntime>::add_lane_relayer(Origin::root(), TEST_LANE_ID, 1));
+		});
+	}
+
+	#[test]
+	fn set_lane_relayers_replaces_previous_set() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::root(),
+				TEST_LANE_ID,
+				Some(vec![1, 2, 2]),
+			));
+			assert_eq!(
+				LaneRelayers::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID),
+				Some(vec![1, 2])
+			);
+			assert!(Pallet::<TestRuntime>::is_relayer_allowed(&TEST_LANE_ID, &2));
+
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::root(),
+				TEST_LANE_ID,
+				Some(vec![3]),
+			));
+			assert!(!Pallet::<TestRuntime>::is_relayer_allowed(&TEST_LANE_ID, &2));
+			assert!(Pallet::<TestRuntime>::is_relayer_allowed(&TEST_LANE_ID, &3));
+
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers(
+				Origin::root(),
+				TEST_LANE_ID,
+				None
+			));
+			assert!(Pallet::<TestRuntime>::is_relayer_allowed(&TEST_LANE_ID, &2));
+			assert!(!AllowedLaneRelayers::<TestRuntime, DefaultInstance>::get(
+				&TEST_LANE_ID,
+				&3
+			));
+		});
+	}
+
+	#[test]
+	fn send_message_works() {
+		run_test(|| {
+			send_regular_message();
+		});
+	}
+
+	#[test]
+	fn send_message_with_idempotency_key_ignores_duplicates() {
+		run_test(|| {
+			let send_message_with_key = |sender| {
+				assert_ok!(Pallet::<TestRuntime>::send_message_with_idempotency_key(
+					Origin::signed(sender),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1,
+					[42; 32],
+				));
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).latest_generated_nonce
+			};
+
+			get_ready_for_events();
+			assert_eq!(send_message_with_key(1), 1);
+
+			// resubmission within the key lifetime is ignored
+			System::<TestRuntime>::reset_events();
+			assert_eq!(send_message_with_key(1), 1);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::pallet_bridge_messages(RawEvent::DuplicateMessageIgnored(TEST_LANE_ID, 1)),
+					topics: vec![],
+				}],
+			);
+
+			// other sender may use the same key
+			assert_eq!(send_message_with_key(2), 2);
+
+			// when the key expires, the message is sent again
+			System::<TestRuntime>::set_block_number(11);
+			Pallet::<TestRuntime>::on_initialize(11);
+			assert_eq!(Pallet::<TestRuntime>::sent_idempotency_key(1, [42; 32]), None);
+			assert_eq!(send_message_with_key(1), 3);
+		});
+	}
+
+	#[test]
+	fn send_message_from_pallet_works() {
+		run_test(|| {
+			get_ready_for_events();
+
+			assert_eq!(
+				Pallet::<TestRuntime>::send_message_from_pallet(TEST_LANE_ID, REGULAR_PAYLOAD, REGULAR_PAYLOAD.1),
+				Ok(1),
+			);
+
+			// check event with assigned nonce
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::pallet_bridge_messages(RawEvent::MessageAccepted(TEST_LANE_ID, 1)),
+					topics: vec![],
+				}],
+			);
+
+			// check that fee has been withdrawn from the pallets fee pot
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_paid(
+				Pallet::<TestRuntime>::pallets_fee_pot_account_id(),
+				REGULAR_PAYLOAD.1,
+			));
+		});
+	}
+
+	#[test]
+	fn send_message_from_pallet_is_rejected_if_halted() {
+		run_test(|| {
+			PalletOperatingMode::<DefaultInstance>::put(OperatingMode::Halted);
+
+			assert_eq!(
+				Pallet::<TestRuntime>::send_message_from_pallet(TEST_LANE_ID, REGULAR_PAYLOAD, REGULAR_PAYLOAD.1),
+				Err(Error::<TestRuntime, DefaultInstance>::Halted.into()),
+			);
+		});
+	}
+
+	#[test]
+	fn chain_verifier_rejects_invalid_message_in_send_message() {
+		run_test(|| {
+			// messages with this payload are rejected by target chain verifier
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					PAYLOAD_REJECTED_BY_TARGET_CHAIN,
+					PAYLOAD_REJECTED_BY_TARGET_CHAIN.1
+				),
+				Error::<TestRuntime, DefaultInstance>::MessageRejectedByChainVerifier,
+			);
+		});
+	}
+
+	#[test]
+	fn lane_verifier_rejects_invalid_message_in_send_message() {
+		run_test(|| {
+			// messages with zero fee are rejected by lane verifier
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(Origin::signed(1), TEST_LANE_ID, REGULAR_PAYLOAD, 0),
+				Error::<TestRuntime, DefaultInstance>::MessageRejectedByLaneVerifier,
+			);
+		});
+	}
+
+	#[test]
+	fn message_send_fails_if_submitter_cant_pay_message_fee() {
+		run_test(|| {
+			TestMessageDeliveryAndDispatchPayment::reject_payments();
+			assert_noop!(
+				Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					REGULAR_PAYLOAD.1
+				),
+				Error::<TestRuntime, DefaultInstance>::FailedToWithdrawMessageFee,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_works() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 1);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_refunds_unspent_dispatch_weight() {
+		run_test(|| {
+			// relayer declares larger dispatch weight than required
+			let extra_dispatch_weight = 10;
+			let dispatch_weight = REGULAR_PAYLOAD.1 + PAYLOAD_WITH_UNSPENT_WEIGHT.1 + extra_dispatch_weight;
+			let proof: TestMessagesProof = Ok(vec![
+				message(1, REGULAR_PAYLOAD),
+				message(2, PAYLOAD_WITH_UNSPENT_WEIGHT),
+			])
+			.into();
+			let declared_weight =
+				<TestRuntime as Config>::WeightInfo::receive_messages_proof_weight(&proof, 2, dispatch_weight);
+
+			let post_info = Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				proof,
+				2,
+				dispatch_weight,
+			)
+			.unwrap();
+			assert_eq!(
+				post_info.actual_weight,
+				Some(declared_weight - extra_dispatch_weight - PAYLOAD_WITH_UNSPENT_WEIGHT.1 / 2),
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_updates_confirmed_message_nonce() {
+		run_test(|| {
+			// say we have received 10 messages && last confirmed message is 8
+			InboundLanes::<TestRuntime, DefaultInstance>::insert(
+				TEST_LANE_ID,
+				StoredInboundLaneData::try_new(InboundLaneData {
+					last_confirmed_nonce: 8,
+					relayers: vec![(9, 9, TEST_RELAYER_A), (10, 10, TEST_RELAYER_B)]
+						.into_iter()
+						.collect(),
+					..Default::default()
+				})
+				.unwrap(),
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_unrewarded_relayers_state(TEST_LANE_ID),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					messages_in_oldest_entry: 1,
+					total_messages: 2,
+				},
+			);
+
+			// message proof includes outbound lane state with latest confirmed message updated to 9
+			let mut message_proof: TestMessagesProof = Ok(vec![message(11, REGULAR_PAYLOAD)]).into();
+			message_proof.result.as_mut().unwrap()[0].1.lane_state = Some(OutboundLaneData {
+				latest_received_nonce: 9,
+				..Default::default()
+			});
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				message_proof,
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(TEST_LANE_ID).into_inner(),
+				InboundLaneData {
+					last_confirmed_nonce: 9,
+					relayers: vec![(10, 10, TEST_RELAYER_B), (11, 11, TEST_RELAYER_A)]
+						.into_iter()
+						.collect(),
+					..Default::default()
+				},
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_unrewarded_relayers_state(TEST_LANE_ID),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					messages_in_oldest_entry: 1,
+					total_messages: 2,
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_invalid_dispatch_weight() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1 - 1,
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDispatchWeight,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_invalid_proof() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Err(()).into(),
+					1,
+					0,
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidMessagesProof,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_proof_with_too_many_messages() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					u32::MAX,
+					0,
+				),
+				Error::<TestRuntime, DefaultInstance>::TooManyMessagesInTheProof,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_proof_with_nonce_gap() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(2, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::MessagesNonceGap,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_proof_if_lane_has_too_many_unrewarded_relayer_entries() {
+		run_test(|| {
+			let max_entries = <TestRuntime as Config>::MaxUnrewardedRelayerEntriesAtInboundLane::get();
+			InboundLanes::<TestRuntime, DefaultInstance>::insert(
+				TEST_LANE_ID,
+				StoredInboundLaneData::try_new(InboundLaneData {
+					last_confirmed_nonce: 0,
+					relayers: (1..=max_entries).map(|nonce| (nonce, nonce, TEST_RELAYER_B)).collect(),
+					..Default::default()
+				})
+				.unwrap(),
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
+					Origin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(max_entries + 1, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.1,
+				),
+				Error::<TestRuntime, DefaultInstance>::TooManyUnrewardedRelayerEntries,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_ignores_already_delivered_messages() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_B,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(TEST_LANE_ID).into_inner(),
+				InboundLaneData {
+					last_confirmed_nonce: 0,
+					relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+					..Default::default()
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_reports_delivered_messages_to_signed_extension() {
+		run_test(|| {
+			// nothing is reported when the value is not set by the signed extension
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(DeliveredMessagesInTransaction::<DefaultInstance>::get(), None);
+
+			// already delivered messages are not reported
+			DeliveredMessagesInTransaction::<DefaultInstance>::put(0);
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_B,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(DeliveredMessagesInTransaction::<DefaultInstance>::get(), Some(0));
+
+			// new messages are reported
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_B,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				REGULAR_PAYLOAD.1 * 2,
+			));
+			assert_eq!(DeliveredMessagesInTransaction::<DefaultInstance>::get(), Some(1));
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_works() {
+		run_test(|| {
+			send_regular_message();
+			receive_messages_delivery_proof();
+
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).latest_received_nonce,
+				1,
+			);
+		});
+	}
+
+	#[test]
+	fn oldest_required_bridged_header_is_updated_when_delivery_is_confirmed() {
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::oldest_required_bridged_header(), None);
+
+			BestFinalizedBridgedHeaderNumber::set(&10);
+			send_regular_message();
+			BestFinalizedBridgedHeaderNumber::set(&20);
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(Pallet::<TestRuntime>::oldest_required_bridged_header(), Some(10));
+			assert_eq!(
+				OldestUnconfirmedMessages::<TestRuntime, DefaultInstance>::get(TEST_LANE_ID),
+				Some((1, 10)),
+			);
+
+			// the second message has been sent when the best finalized header was not older than
+			// the header, required by the first message, so this header is still required
+			receive_messages_delivery_proof();
+			assert_eq!(Pallet::<TestRuntime>::oldest_required_bridged_header(), Some(10));
+			assert_eq!(
+				OldestUnconfirmedMessages::<TestRuntime, DefaultInstance>::get(TEST_LANE_ID),
+				Some((2, 10)),
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						last_confirmed_nonce: 2,
+						..Default::default()
+					},
+				))),
+				Default::default(),
+			));
+			assert_eq!(Pallet::<TestRuntime>::oldest_required_bridged_header(), None);
+			assert!(!OldestUnconfirmedMessages::<TestRuntime, DefaultInstance>::contains_key(TEST_LANE_ID));
+		});
+	}
+
+	#[test]
+	fn stuck_lane_does_not_affect_unconfirmed_messages_of_other_lanes() {
+		run_test(|| {
+			let stuck_lane_id = [0, 0, 0, 2];
+			BestFinalizedBridgedHeaderNumber::set(&10);
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				stuck_lane_id,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+			BestFinalizedBridgedHeaderNumber::set(&20);
+			send_regular_message();
+			assert_eq!(Pallet::<TestRuntime>::oldest_required_bridged_header(), Some(10));
+
+			// confirmation of messages of the other lane removes its entry
+			receive_messages_delivery_proof();
+			assert!(!OldestUnconfirmedMessages::<TestRuntime, DefaultInstance>::contains_key(TEST_LANE_ID));
+			assert_eq!(
+				OldestUnconfirmedMessages::<TestRuntime, DefaultInstance>::get(stuck_lane_id),
+				Some((1, 10)),
+			);
+			assert_eq!(Pallet::<TestRuntime>::oldest_required_bridged_header(), Some(10));
+		});
+	}
+
+	#[test]
+	fn lane_statistics_are_updated() {
+		run_test(|| {
+			send_regular_message();
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 1,
+					..Default::default()
+				},
+			));
+
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_statistics(TEST_LANE_ID),
+				LaneStatistics {
+					sent_messages: 1,
+					delivered_messages: 1,
+					confirmed_messages: 1,
+					collected_fees: REGULAR_PAYLOAD.1,
+					paid_rewards: REGULAR_PAYLOAD.1,
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn lane_congestion_events_are_emitted_when_threshold_is_crossed() {
+		run_test(|| {
+			LaneCongestionThreshold::set(&2);
+			get_ready_for_events();
+
+			// first message doesn't make the lane congested
+			send_regular_message();
+			assert!(!Pallet::<TestRuntime>::lane_congestion(TEST_LANE_ID).is_congested);
+
+			// second message does
+			System::<TestRuntime>::reset_events();
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::pallet_bridge_messages(RawEvent::MessageAccepted(TEST_LANE_ID, 2)),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::pallet_bridge_messages(RawEvent::LaneCongested(TEST_LANE_ID, 2)),
+						topics: vec![],
+					},
+				],
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_congestion(TEST_LANE_ID),
+				LaneCongestion {
+					queued_messages: 2,
+					soft_cap: 2,
+					is_congested: true,
+				},
+			);
+
+			// when delivery of both messages is confirmed, the lane is no longer congested
+			System::<TestRuntime>::reset_events();
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 2, TEST_RELAYER_A)].into_iter().collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 2,
+					..Default::default()
+				},
+			));
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::pallet_bridge_messages(RawEvent::MessagesDelivered(TEST_LANE_ID, 1, 2)),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::pallet_bridge_messages(RawEvent::LaneUncongested(TEST_LANE_ID, 0)),
+						topics: vec![],
+					},
+				],
+			);
+			assert!(!Pallet::<TestRuntime>::lane_congestion(TEST_LANE_ID).is_congested);
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_batch_proof_confirms_all_lanes() {
+		run_test(|| {
+			const OTHER_LANE_ID: LaneId = [0, 0, 0, 2];
+
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				1000,
+			));
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				OTHER_LANE_ID,
+				REGULAR_PAYLOAD,
+				2000,
+			));
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_batch_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryBatchProof(Ok(vec![
+					(
+						TEST_LANE_ID,
+						InboundLaneData {
+							relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+							..Default::default()
+						},
+					),
+					(
+						OTHER_LANE_ID,
+						InboundLaneData {
+							relayers: vec![(1, 1, TEST_RELAYER_B)].into_iter().collect(),
+							..Default::default()
+						},
+					),
+				])),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					total_messages: 2,
+					..Default::default()
+				},
+			));
+
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).latest_received_nonce,
+				1
+			);
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&OTHER_LANE_ID).latest_received_nonce,
+				1
+			);
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_A,
+				1000
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_B,
+				2000
+			));
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_batch_proof_rejects_invalid_proofs() {
+		run_test(|| {
+			send_regular_message();
+
+			let lane_data = InboundLaneData {
+				relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+				..Default::default()
+			};
+			let relayers_state = UnrewardedRelayersState {
+				unrewarded_relayer_entries: 1,
+				total_messages: 1,
+				..Default::default()
+			};
+
+			// empty proof is rejected
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_batch_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryBatchProof(Ok(vec![])),
+					Default::default(),
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDeliveryProof,
+			);
+
+			// the same lane may not be proved twice
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_batch_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryBatchProof(Ok(vec![
+						(TEST_LANE_ID, lane_data.clone()),
+						(TEST_LANE_ID, lane_data.clone()),
+					])),
+					UnrewardedRelayersState {
+						unrewarded_relayer_entries: 2,
+						total_messages: 2,
+						..Default::default()
+					},
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDeliveryProof,
+			);
+
+			// declared relayers state must match total state of all lanes
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_batch_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryBatchProof(Ok(vec![(TEST_LANE_ID, lane_data.clone())])),
+					Default::default(),
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidUnrewardedRelayersState,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_batch_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryBatchProof(Ok(vec![(TEST_LANE_ID, lane_data)])),
+				relayers_state,
+			));
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_rewards_relayers() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				1000,
+			));
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				2000,
+			));
+
+			// this reports delivery of message 1 => reward is paid to TEST_RELAYER_A
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 1,
+					..Default::default()
+				},
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_A,
+				1000
+			));
+			assert!(!TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_B,
+				2000
+			));
 
-		assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
-			Origin::signed(1),
-			TestMessagesDeliveryProof(Ok((
-				TEST_LANE_ID,
-				InboundLaneData {
-					last_confirmed_nonce: 1,
+			// this reports delivery of both message 1 and message 2 => reward is paid only to TEST_RELAYER_B
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
+							.into_iter()
+							.collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					total_messages: 2,
 					..Default::default()
 				},
-			))),
-			Default::default(),
-		));
-
-		assert_eq!(
-			System::<TestRuntime>::events(),
-			vec![EventRecord {
-				phase: Phase::Initialization,
-				event: TestEvent::pallet_bridge_messages(RawEvent::MessagesDelivered(TEST_LANE_ID, 1, 1)),
-				topics: vec![],
-			}],
-		);
+			));
+			assert!(!TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_A,
+				1000
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_B,
+				2000
+			));
+		});
 	}
 
 	#[test]
-	fn pallet_owner_may_change_owner() {
+	fn receive_messages_delivery_proof_splits_rewards_with_confirmation_relayer() {
 		run_test(|| {
-			PalletOwner::<TestRuntime>::put(2);
+			ConfirmationRelayerRewardShare::set(&Perbill::from_percent(10));
 
-			assert_ok!(Pallet::<TestRuntime>::set_owner(Origin::root(), Some(1)));
-			assert_noop!(
-				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(2), OperatingMode::Halted),
-				DispatchError::BadOrigin,
-			);
-			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
-				Origin::root(),
-				OperatingMode::Halted
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				1000,
+			));
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				2000,
 			));
 
-			assert_ok!(Pallet::<TestRuntime>::set_owner(Origin::signed(1), None));
-			assert_noop!(
-				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
-				DispatchError::BadOrigin,
-			);
-			assert_noop!(
-				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(2), OperatingMode::Normal),
-				DispatchError::BadOrigin,
-			);
-			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
-				Origin::root(),
-				OperatingMode::Normal
+			// TEST_RELAYER_C confirms delivery of messages 1 and 2 => it receives 10% of both rewards
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(TEST_RELAYER_C),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
+							.into_iter()
+							.collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					total_messages: 2,
+					..Default::default()
+				},
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_A,
+				900
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_B,
+				1800
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_C,
+				300
 			));
 		});
 	}
 
 	#[test]
-	fn pallet_may_be_halted_by_root() {
+	fn receive_messages_delivery_proof_reimburses_confirmation_transaction_fee() {
 		run_test(|| {
-			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
-				Origin::root(),
-				OperatingMode::Halted
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				1000,
 			));
-			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
-				Origin::root(),
-				OperatingMode::Normal
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				2000,
+			));
+
+			// TEST_RELAYER_C has paid 1500 for confirmation transaction => this fee is deducted
+			// from other relayers rewards
+			ConfirmationTransactionFee::<TestRuntime, DefaultInstance>::put(1500);
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				Origin::signed(TEST_RELAYER_C),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
+							.into_iter()
+							.collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					total_messages: 2,
+					..Default::default()
+				},
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(TEST_RELAYER_A, 0));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_B,
+				1500
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_C,
+				1500
 			));
 		});
 	}
 
 	#[test]
-	fn pallet_may_be_halted_by_owner() {
+	fn receive_messages_delivery_proof_rejects_invalid_proof() {
 		run_test(|| {
-			PalletOwner::<TestRuntime>::put(2);
-
-			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
-				Origin::signed(2),
-				OperatingMode::Halted
-			));
-			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
-				Origin::signed(2),
-				OperatingMode::Normal
-			));
-
-			assert_noop!(
-				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Halted),
-				DispatchError::BadOrigin,
-			);
-			assert_noop!(
-				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
-				DispatchError::BadOrigin,
-			);
-
-			assert_ok!(Pallet::<TestRuntime>::set_operating_mode(
-				Origin::signed(2),
-				OperatingMode::Halted
-			));
 			assert_noop!(
-				Pallet::<TestRuntime>::set_operating_mode(Origin::signed(1), OperatingMode::Normal),
-				DispatchError::BadOrigin,
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Err(())),
+					Default::default(),
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDeliveryProof,
 			);
 		});
 	}
 
 	#[test]
-	fn pallet_parameter_may_be_updated_by_root() {
+	fn receive_messages_delivery_proof_rejects_proof_if_declared_relayers_state_is_invalid() {
 		run_test(|| {
-			get_ready_for_events();
-
-			let parameter = TestMessagesParameter::TokenConversionRate(10.into());
-			assert_ok!(Pallet::<TestRuntime>::update_pallet_parameter(
-				Origin::root(),
-				parameter.clone(),
-			));
+			// when number of relayers entires is invalid
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
+								.into_iter()
+								.collect(),
+							..Default::default()
+						}
+					))),
+					UnrewardedRelayersState {
+						unrewarded_relayer_entries: 1,
+						total_messages: 2,
+						..Default::default()
+					},
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidUnrewardedRelayersState,
+			);
 
-			assert_eq!(TokenConversionRate::get(), 10.into());
-			assert_eq!(
-				System::<TestRuntime>::events(),
-				vec![EventRecord {
-					phase: Phase::Initialization,
-					event: TestEvent::pallet_bridge_messages(RawEvent::ParameterUpdated(parameter)),
-					topics: vec![],
-				}],
+			// when number of messages is invalid
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					Origin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
+								.into_iter()
+								.collect(),
+							..Default::default()
+						}
+					))),
+					UnrewardedRelayersState {
+						unrewarded_relayer_entries: 2,
+						total_messages: 1,
+						..Default::default()
+					},
+				),
+				Error::<TestRuntime, DefaultInstance>::InvalidUnrewardedRelayersState,
 			);
 		});
 	}
 
 	#[test]
-	fn pallet_parameter_may_be_updated_by_owner() {
+	fn receive_messages_accepts_single_message_with_invalid_payload() {
 		run_test(|| {
-			PalletOwner::<TestRuntime>::put(2);
-			get_ready_for_events();
+			let mut invalid_message = message(1, REGULAR_PAYLOAD);
+			invalid_message.data.payload = Vec::new();
 
-			let parameter = TestMessagesParameter::TokenConversionRate(10.into());
-			assert_ok!(Pallet::<TestRuntime>::update_pallet_parameter(
-				Origin::signed(2),
-				parameter.clone(),
-			));
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![invalid_message]).into(),
+				1,
+				0, // weight may be zero in this case (all messages are improperly encoded)
+			),);
 
-			assert_eq!(TokenConversionRate::get(), 10.into());
-			assert_eq!(
-				System::<TestRuntime>::events(),
-				vec![EventRecord {
-					phase: Phase::Initialization,
-					event: TestEvent::pallet_bridge_messages(RawEvent::ParameterUpdated(parameter)),
-					topics: vec![],
-				}],
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(&TEST_LANE_ID).last_delivered_nonce(),
+				1,
 			);
 		});
 	}
 
 	#[test]
-	fn pallet_parameter_cant_be_updated_by_arbitrary_submitter() {
+	fn receive_messages_accepts_batch_with_message_with_invalid_payload() {
 		run_test(|| {
-			assert_noop!(
-				Pallet::<TestRuntime>::update_pallet_parameter(
-					Origin::signed(2),
-					TestMessagesParameter::TokenConversionRate(10.into()),
-				),
-				DispatchError::BadOrigin,
-			);
+			let mut invalid_message = message(2, REGULAR_PAYLOAD);
+			invalid_message.data.payload = Vec::new();
 
-			PalletOwner::<TestRuntime>::put(2);
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
+				Origin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![
+					message(1, REGULAR_PAYLOAD),
+					invalid_message,
+					message(3, REGULAR_PAYLOAD),
+				])
+				.into(),
+				3,
+				REGULAR_PAYLOAD.1 + REGULAR_PAYLOAD.1,
+			),);
 
-			assert_noop!(
-				Pallet::<TestRuntime>::update_pallet_parameter(
-					Origin::signed(1),
-					TestMessagesParameter::TokenConversionRate(10.into()),
-				),
-				DispatchError::BadOrigin,
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(&TEST_LANE_ID).last_delivered_nonce(),
+				3,
 			);
 		});
 	}
 
 	#[test]
-	fn fixed_u128_works_as_i_think() {
-		// this test is here just to be sure that conversion rate may be represented with FixedU128
-		run_test(|| {
-			use sp_runtime::{FixedPointNumber, FixedU128};
+	fn storage_message_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking all
+		// previously crafted messages proofs.
+		let storage_key = storage_keys::message_key::<TestRuntime, DefaultInstance>(&*b"test", 42).0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed018a395e6242c6813b196ca31ed0547ea79446af0e09063bd4a7874aef8a997cec746573742a00000000000000").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
+	}
 
-			// 1:1 conversion that we use by default for testnets
-			let rialto_token = 1u64;
-			let rialto_token_in_millau_tokens = TokenConversionRate::get().saturating_mul_int(rialto_token);
-			assert_eq!(rialto_token_in_millau_tokens, 1);
+	#[test]
+	fn outbound_lane_data_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking all
+		// previously crafted outbound lane state proofs.
+		let storage_key = storage_keys::outbound_lane_data_key::<DefaultInstance>(&*b"test").0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed0196c246acb9b55077390e3ca723a0ca1f44a8995dd50b6657a037a7839304535b74657374").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
+	}
 
-			// let's say conversion rate is 1:1.7
-			let conversion_rate = FixedU128::saturating_from_rational(170, 100);
-			let rialto_tokens = 100u64;
-			let rialto_tokens_in_millau_tokens = conversion_rate.saturating_mul_int(rialto_tokens);
-			assert_eq!(rialto_tokens_in_millau_tokens, 170);
+	#[test]
+	fn inbound_lane_data_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking all
+		// previously crafted inbound lane state proofs.
+		let storage_key = storage_keys::inbound_lane_data_key::<TestRuntime, DefaultInstance>(&*b"test").0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed01e5f83cf83f2127eb47afdc35d6e43fab44a8995dd50b6657a037a7839304535b74657374").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
+	}
 
-			// let's say conversion rate is 1:0.25
-			let conversion_rate = FixedU128::saturating_from_rational(25, 100);
-			let rialto_tokens = 100u64;
-			let rialto_tokens_in_millau_tokens = conversion_rate.saturating_mul_int(rialto_tokens);
-			assert_eq!(rialto_tokens_in_millau_tokens, 25);
-		});
+	#[test]
+	fn lane_operating_mode_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage and relayers
+		// are no longer able to read lane operating modes.
+		let storage_key = storage_keys::lane_operating_mode_key::<DefaultInstance>(&*b"test").0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed01421884be2ba562ac021181f17f1b00f044a8995dd50b6657a037a7839304535b74657374").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
 	}
 
 	#[test]
-	fn pallet_rejects_transactions_if_halted() {
-		run_test(|| {
-			// send message first to be able to check that delivery_proof fails later
-			send_regular_message();
+	fn pallet_prefix_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage and relayers
+		// are no longer able to read pallet storage.
+		let storage_key = storage_keys::pallet_prefix_key::<DefaultInstance>().0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed01").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
+	}
 
-			PalletOperatingMode::<DefaultInstance>::put(OperatingMode::Halted);
+	#[test]
+	fn operating_mode_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage and relayers
+		// are no longer able to read pallet operating mode.
+		let storage_key = storage_keys::operating_mode_key::<DefaultInstance>().0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed010f4cf0917788d791142ff6c1f216e7b3").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
+	}
 
-			assert_noop!(
-				Pallet::<TestRuntime>::send_message(
-					Origin::signed(1),
-					TEST_LANE_ID,
-					REGULAR_PAYLOAD,
-					REGULAR_PAYLOAD.1,
-				),
-				Error::<TestRuntime, DefaultInstance>::Halted,
-			);
+	#[test]
+	fn outbound_messages_with_storage_keys_returns_stored_messages() {
+		run_test(|| {
+			send_regular_message();
+			send_regular_message();
+			send_regular_message();
+			OutboundLanes::<DefaultInstance>::mutate(&TEST_LANE_ID, |lane| lane.oldest_unpruned_nonce = 2);
+			OutboundMessages::<TestRuntime>::remove(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 1,
+			});
 
-			assert_noop!(
-				Pallet::<TestRuntime>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 1,),
-				Error::<TestRuntime, DefaultInstance>::Halted,
+			let message_key = |nonce| storage_keys::message_key::<TestRuntime, DefaultInstance>(&TEST_LANE_ID, nonce).0;
+			let payload = VersionedMessagePayload::encode_payload(&REGULAR_PAYLOAD);
+			assert_eq!(
+				Pallet::<TestRuntime>::outbound_messages_with_storage_keys(TEST_LANE_ID, 1, 100, false),
+				OutboundMessagesWithStorageKeys {
+					messages: vec![(2, payload.clone()), (3, payload.clone())],
+					storage_keys: vec![message_key(2), message_key(3)],
+				},
 			);
-
-			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_proof(
-					Origin::signed(1),
-					TEST_RELAYER_A,
-					Ok(vec![message(2, REGULAR_PAYLOAD)]).into(),
-					1,
-					REGULAR_PAYLOAD.1,
-				),
-				Error::<TestRuntime, DefaultInstance>::Halted,
+			assert_eq!(
+				Pallet::<TestRuntime>::outbound_messages_with_storage_keys(TEST_LANE_ID, 3, 3, true),
+				OutboundMessagesWithStorageKeys {
+					messages: vec![(3, payload)],
+					storage_keys: vec![
+						message_key(3),
+						storage_keys::outbound_lane_data_key::<DefaultInstance>(&TEST_LANE_ID).0,
+					],
+				},
 			);
-
-			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					Origin::signed(1),
-					TestMessagesDeliveryProof(Ok((
-						TEST_LANE_ID,
-						InboundLaneData {
-							last_confirmed_nonce: 1,
-							..Default::default()
-						},
-					))),
-					Default::default(),
-				),
-				Error::<TestRuntime, DefaultInstance>::Halted,
+			assert_eq!(
+				Pallet::<TestRuntime>::outbound_messages_with_storage_keys(TEST_LANE_ID, 4, 2, false),
+				OutboundMessagesWithStorageKeys::default(),
 			);
 		});
 	}
 
 	#[test]
-	fn pallet_rejects_new_messages_in_rejecting_outbound_messages_operating_mode() {
+	fn try_state_succeeds_after_messages_are_sent_received_and_confirmed() {
 		run_test(|| {
-			// send message first to be able to check that delivery_proof fails later
 			send_regular_message();
-
-			PalletOperatingMode::<DefaultInstance>::put(OperatingMode::RejectingOutboundMessages);
-
-			assert_noop!(
-				Pallet::<TestRuntime>::send_message(
-					Origin::signed(1),
-					TEST_LANE_ID,
-					REGULAR_PAYLOAD,
-					REGULAR_PAYLOAD.1,
-				),
-				Error::<TestRuntime, DefaultInstance>::Halted,
-			);
-
-			assert_ok!(Pallet::<TestRuntime>::increase_message_fee(
-				Origin::signed(1),
-				TEST_LANE_ID,
-				1,
-				1,
-			));
-
+			send_regular_message();
+			receive_messages_delivery_proof();
 			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
 				Origin::signed(1),
 				TEST_RELAYER_A,
 				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
 				1,
 				REGULAR_PAYLOAD.1,
-			),);
-
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
-				Origin::signed(1),
-				TestMessagesDeliveryProof(Ok((
-					TEST_LANE_ID,
-					InboundLaneData {
-						last_confirmed_nonce: 1,
-						..Default::default()
-					},
-				))),
-				Default::default(),
 			));
+
+			assert_ok!(Pallet::<TestRuntime>::do_try_state());
 		});
 	}
 
 	#[test]
-	fn send_message_works() {
+	fn stored_inbound_lane_data_is_bounded() {
 		run_test(|| {
-			send_regular_message();
+			let max_entries = <TestRuntime as Config>::MaxUnrewardedRelayerEntriesAtInboundLane::get();
+			let lane_data = |entries| InboundLaneData {
+				relayers: (1..=entries).map(|nonce| (nonce, nonce, TEST_RELAYER_A)).collect(),
+				..Default::default()
+			};
+
+			type Stored = StoredInboundLaneData<TestRuntime, DefaultInstance>;
+
+			let stored = Stored::try_new(lane_data(max_entries));
+			assert_eq!(stored.map(|stored| stored.into_inner()), Some(lane_data(max_entries)));
+			assert!(Stored::try_new(lane_data(max_entries + 1)).is_none());
+			assert!(Stored::decode(&mut &lane_data(max_entries + 1).encode()[..]).is_err());
 		});
 	}
 
 	#[test]
-	fn chain_verifier_rejects_invalid_message_in_send_message() {
+	fn try_state_detects_broken_inbound_lane() {
 		run_test(|| {
-			// messages with this payload are rejected by target chain verifier
-			assert_noop!(
-				Pallet::<TestRuntime>::send_message(
-					Origin::signed(1),
-					TEST_LANE_ID,
-					PAYLOAD_REJECTED_BY_TARGET_CHAIN,
-					PAYLOAD_REJECTED_BY_TARGET_CHAIN.1
-				),
-				Error::<TestRuntime, DefaultInstance>::MessageRejectedByChainVerifier,
+			InboundLanes::<TestRuntime>::insert(
+				TEST_LANE_ID,
+				StoredInboundLaneData::try_new(InboundLaneData {
+					last_confirmed_nonce: 1,
+					relayers: vec![(2, 3, TEST_RELAYER_A), (5, 5, TEST_RELAYER_B)]
+						.into_iter()
+						.collect(),
+					..Default::default()
+				})
+				.unwrap(),
+			);
+
+			assert_eq!(
+				Pallet::<TestRuntime>::do_try_state(),
+				Err("Unrewarded relayer entries are not contiguous"),
 			);
 		});
 	}
 
 	#[test]
-	fn lane_verifier_rejects_invalid_message_in_send_message() {
+	fn inbound_lane_invariants_are_checked() {
+		let lane_data =
+			|last_confirmed_nonce, relayers: Vec<(MessageNonce, MessageNonce, TestRelayer)>| InboundLaneData {
+				last_confirmed_nonce,
+				relayers: relayers.into_iter().collect(),
+				..Default::default()
+			};
+
+		let ordered = LaneOrdering::Ordered;
+		let unordered = LaneOrdering::Unordered;
+
+		assert_eq!(ensure_inbound_lane_invariants(&lane_data(0, vec![]), ordered), Ok(()));
+		assert_eq!(
+			ensure_inbound_lane_invariants(
+				&lane_data(1, vec![(2, 3, TEST_RELAYER_A), (4, 4, TEST_RELAYER_B)]),
+				ordered,
+			),
+			Ok(()),
+		);
+		assert_eq!(
+			ensure_inbound_lane_invariants(&lane_data(2, vec![(2, 3, TEST_RELAYER_A)]), ordered),
+			Err("Unrewarded relayer entry covers already confirmed messages"),
+		);
+		assert_eq!(
+			ensure_inbound_lane_invariants(&lane_data(1, vec![(3, 2, TEST_RELAYER_A)]), ordered),
+			Err("Unrewarded relayer entry has invalid nonces range"),
+		);
+		assert_eq!(
+			ensure_inbound_lane_invariants(
+				&lane_data(1, vec![(2, 3, TEST_RELAYER_A), (3, 4, TEST_RELAYER_B)]),
+				unordered,
+			),
+			Err("Unrewarded relayer entries overlap"),
+		);
+		assert_eq!(
+			ensure_inbound_lane_invariants(
+				&lane_data(1, vec![(2, 3, TEST_RELAYER_A), (5, 5, TEST_RELAYER_B)]),
+				ordered,
+			),
+			Err("Unrewarded relayer entries are not contiguous"),
+		);
+		assert_eq!(
+			ensure_inbound_lane_invariants(
+				&lane_data(1, vec![(2, 3, TEST_RELAYER_A), (5, 5, TEST_RELAYER_B)]),
+				unordered,
+			),
+			Ok(()),
+		);
+	}
+
+	#[test]
+	fn received_range_is_split_into_delivered_and_skipped_ranges() {
+		let relayers =
+			|entries: Vec<(MessageNonce, MessageNonce, TestRelayer)>| -> VecDeque<_> { entries.into_iter().collect() };
+
+		// all messages of ordered lane are delivered
+		assert_eq!(
+			split_received_range(
+				(1, 4),
+				0,
+				&relayers(vec![(1, 2, TEST_RELAYER_A), (3, 4, TEST_RELAYER_B)])
+			),
+			vec![(1, 4, true)],
+		);
+		// messages that have been confirmed by the bridged chain are delivered
+		assert_eq!(split_received_range((1, 2), 2, &relayers(vec![])), vec![(1, 2, true)]);
+		// messages that are not covered by entries are skipped
+		assert_eq!(
+			split_received_range(
+				(2, 10),
+				1,
+				&relayers(vec![(3, 4, TEST_RELAYER_A), (7, 8, TEST_RELAYER_B)])
+			),
+			vec![(2, 2, false), (3, 4, true), (5, 6, false), (7, 8, true), (9, 10, false)],
+		);
+		// entries that are out of the received range are ignored
+		assert_eq!(
+			split_received_range(
+				(3, 5),
+				0,
+				&relayers(vec![(1, 3, TEST_RELAYER_A), (5, 6, TEST_RELAYER_B)])
+			),
+			vec![(3, 3, true), (4, 4, false), (5, 5, true)],
+		);
+	}
+
+	#[test]
+	fn outbound_lane_invariants_are_checked() {
+		let lane_data = |oldest_unpruned_nonce, latest_received_nonce, latest_generated_nonce| OutboundLaneData {
+			oldest_unpruned_nonce,
+			latest_received_nonce,
+			latest_generated_nonce,
+			..Default::default()
+		};
+		let stored_up_to_3 = |nonce| nonce <= 3;
+
+		assert_eq!(
+			ensure_outbound_lane_invariants(&lane_data(1, 0, 0), stored_up_to_3),
+			Ok(())
+		);
+		assert_eq!(
+			ensure_outbound_lane_invariants(&lane_data(2, 2, 3), stored_up_to_3),
+			Ok(())
+		);
+		assert_eq!(
+			ensure_outbound_lane_invariants(&lane_data(1, 3, 2), stored_up_to_3),
+			Err("Latest received nonce is larger than latest generated nonce"),
+		);
+		assert_eq!(
+			ensure_outbound_lane_invariants(&lane_data(3, 1, 3), stored_up_to_3),
+			Err("Oldest unpruned nonce is larger than next to latest received nonce"),
+		);
+		assert_eq!(
+			ensure_outbound_lane_invariants(&lane_data(1, 1, 4), stored_up_to_3),
+			Err("Message that is not yet pruned is missing from the storage"),
+		);
+	}
+
+	#[test]
+	fn actual_dispatch_weight_does_not_overlow() {
 		run_test(|| {
-			// messages with zero fee are rejected by lane verifier
-			assert_noop!(
-				Pallet::<TestRuntime>::send_message(Origin::signed(1), TEST_LANE_ID, REGULAR_PAYLOAD, 0),
-				Error::<TestRuntime, DefaultInstance>::MessageRejectedByLaneVerifier,
-			);
-		});
-	}
+			let message1 = message(1, TestPayload(0, Weight::MAX / 2));
+			let message2 = message(2, TestPayload(0, Weight::MAX / 2));
+			let message3 = message(2, TestPayload(0, Weight::MAX / 2));
 
-	#[test]
-	fn message_send_fails_if_submitter_cant_pay_message_fee() {
-		run_test(|| {
-			TestMessageDeliveryAndDispatchPayment::reject_payments();
 			assert_noop!(
-				Pallet::<TestRuntime>::send_message(
+				Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
 					Origin::signed(1),
-					TEST_LANE_ID,
-					REGULAR_PAYLOAD,
-					REGULAR_PAYLOAD.1
+					TEST_RELAYER_A,
+					// this may cause overflow if source chain storage is invalid
+					Ok(vec![message1, message2, message3]).into(),
+					3,
+					100,
 				),
-				Error::<TestRuntime, DefaultInstance>::FailedToWithdrawMessageFee,
+				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDispatchWeight,
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_proof_works() {
+	fn delivery_confirmation_handler_is_called_when_messages_are_delivered() {
 		run_test(|| {
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
-				Origin::signed(1),
-				TEST_RELAYER_A,
-				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
-				1,
-				REGULAR_PAYLOAD.1,
-			));
+			send_regular_message();
+			assert!(!TestOnDeliveryConfirmed::is_called(TEST_LANE_ID, 1..=1));
 
-			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 1);
+			receive_messages_delivery_proof();
+			assert!(TestOnDeliveryConfirmed::is_called(TEST_LANE_ID, 1..=1));
 		});
 	}
 
 	#[test]
-	fn receive_messages_proof_updates_confirmed_message_nonce() {
+	fn delivery_confirmation_handler_weight_is_added_to_actual_call_weight() {
 		run_test(|| {
-			// say we have received 10 messages && last confirmed message is 8
-			InboundLanes::<TestRuntime, DefaultInstance>::insert(
+			send_regular_message();
+
+			let proof = TestMessagesDeliveryProof(Ok((
 				TEST_LANE_ID,
 				InboundLaneData {
-					last_confirmed_nonce: 8,
-					relayers: vec![(9, 9, TEST_RELAYER_A), (10, 10, TEST_RELAYER_B)]
-						.into_iter()
-						.collect(),
+					relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+					..Default::default()
 				},
-			);
+			)));
+			let relayers_state = UnrewardedRelayersState {
+				unrewarded_relayer_entries: 1,
+				total_messages: 1,
+				..Default::default()
+			};
+			let declared_weight =
+				<TestRuntime as Config>::WeightInfo::receive_messages_delivery_proof_weight(&proof, &relayers_state)
+					.saturating_add(oldest_unconfirmed_message_update_weight::<TestRuntime, DefaultInstance>())
+					.saturating_add(pending_message_refunds_weight::<TestRuntime, DefaultInstance>(
+						&relayers_state,
+					));
+			let post_info =
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(Origin::signed(1), proof, relayers_state)
+					.expect("delivery proof is valid");
 			assert_eq!(
-				Pallet::<TestRuntime>::inbound_unrewarded_relayers_state(TEST_LANE_ID),
-				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 2,
-					messages_in_oldest_entry: 1,
-					total_messages: 2,
-				},
+				post_info.actual_weight,
+				Some(declared_weight + DELIVERY_CONFIRMATION_HANDLER_WEIGHT),
 			);
+		});
+	}
 
-			// message proof includes outbound lane state with latest confirmed message updated to 9
-			let mut message_proof: TestMessagesProof = Ok(vec![message(11, REGULAR_PAYLOAD)]).into();
-			message_proof.result.as_mut().unwrap()[0].1.lane_state = Some(OutboundLaneData {
-				latest_received_nonce: 9,
-				..Default::default()
-			});
+	#[test]
+	fn fees_of_messages_skipped_at_unordered_lane_are_refunded() {
+		run_test(|| {
+			LaneOrderings::<DefaultInstance>::insert(TEST_LANE_ID, LaneOrdering::Unordered);
+			for fee in vec![1000, 2000, 3000] {
+				assert_ok!(Pallet::<TestRuntime>::send_message(
+					Origin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+					fee,
+				));
+			}
 
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+			// message 2 has been skipped by relayers
+			get_ready_for_events();
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
 				Origin::signed(1),
-				TEST_RELAYER_A,
-				message_proof,
-				1,
-				REGULAR_PAYLOAD.1,
-			));
-
-			assert_eq!(
-				InboundLanes::<TestRuntime>::get(TEST_LANE_ID),
-				InboundLaneData {
-					last_confirmed_nonce: 9,
-					relayers: vec![(10, 10, TEST_RELAYER_B), (11, 11, TEST_RELAYER_A)]
-						.into_iter()
-						.collect(),
-				},
-			);
-			assert_eq!(
-				Pallet::<TestRuntime>::inbound_unrewarded_relayers_state(TEST_LANE_ID),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![(1, 1, TEST_RELAYER_A), (3, 3, TEST_RELAYER_A)]
+							.into_iter()
+							.collect(),
+						..Default::default()
+					}
+				))),
 				UnrewardedRelayersState {
 					unrewarded_relayer_entries: 2,
 					messages_in_oldest_entry: 1,
-					total_messages: 2,
+					total_messages: 3,
 				},
+			));
+
+			assert_eq!(
+				System::<TestRuntime>::events()
+					.into_iter()
+					.map(|event_record| event_record.event)
+					.collect::<Vec<_>>(),
+				vec![
+					TestEvent::pallet_bridge_messages(RawEvent::MessagesDelivered(TEST_LANE_ID, 1, 1)),
+					TestEvent::pallet_bridge_messages(RawEvent::MessagesSkipped(TEST_LANE_ID, 2, 2)),
+					TestEvent::pallet_bridge_messages(RawEvent::MessagesDelivered(TEST_LANE_ID, 3, 3)),
+				],
 			);
+			assert!(TestOnDeliveryConfirmed::is_called(TEST_LANE_ID, 3..=3));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_refunded(1, 2000));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_A,
+				4000
+			));
+			let statistics = Pallet::<TestRuntime>::lane_statistics(TEST_LANE_ID);
+			assert_eq!(statistics.confirmed_messages, 2);
+			assert_eq!(statistics.paid_rewards, 4000);
 		});
 	}
 
 	#[test]
-	fn receive_messages_proof_rejects_invalid_dispatch_weight() {
+	fn increase_message_fee_fails_if_message_is_already_delivered() {
 		run_test(|| {
+			send_regular_message();
+			receive_messages_delivery_proof();
+
 			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_proof(
-					Origin::signed(1),
-					TEST_RELAYER_A,
-					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
-					1,
-					REGULAR_PAYLOAD.1 - 1,
-				),
-				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDispatchWeight,
+				Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 100,),
+				Error::<TestRuntime, DefaultInstance>::MessageIsAlreadyDelivered,
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_proof_rejects_invalid_proof() {
+	fn increase_message_fee_fails_if_message_is_not_yet_sent() {
 		run_test(|| {
 			assert_noop!(
-				Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
-					Origin::signed(1),
-					TEST_RELAYER_A,
-					Err(()).into(),
-					1,
-					0,
-				),
-				Error::<TestRuntime, DefaultInstance>::InvalidMessagesProof,
+				Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 100,),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotYetSent,
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_proof_rejects_proof_with_too_many_messages() {
+	fn increase_message_fee_fails_if_submitter_cant_pay_additional_fee() {
 		run_test(|| {
+			send_regular_message();
+
+			TestMessageDeliveryAndDispatchPayment::reject_payments();
+
 			assert_noop!(
-				Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
-					Origin::signed(1),
-					TEST_RELAYER_A,
-					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
-					u32::MAX,
-					0,
-				),
-				Error::<TestRuntime, DefaultInstance>::TooManyMessagesInTheProof,
+				Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 100,),
+				Error::<TestRuntime, DefaultInstance>::FailedToWithdrawMessageFee,
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_delivery_proof_works() {
+	fn increase_message_fee_succeeds() {
 		run_test(|| {
 			send_regular_message();
-			receive_messages_delivery_proof();
 
-			assert_eq!(
-				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID).latest_received_nonce,
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(
+				Origin::signed(1),
+				TEST_LANE_ID,
 				1,
-			);
+				100,
+			),);
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_paid(1, 100));
 		});
 	}
 
 	#[test]
-	fn receive_messages_delivery_proof_rewards_relayers() {
+	fn increase_message_fee_may_be_called_by_other_account() {
 		run_test(|| {
-			assert_ok!(Pallet::<TestRuntime>::send_message(
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::send_message_with_ttl(
 				Origin::signed(1),
 				TEST_LANE_ID,
 				REGULAR_PAYLOAD,
-				1000,
+				REGULAR_PAYLOAD.1,
+				10,
 			));
-			assert_ok!(Pallet::<TestRuntime>::send_message(
-				Origin::signed(1),
+
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(
+				Origin::signed(2),
 				TEST_LANE_ID,
-				REGULAR_PAYLOAD,
-				2000,
+				1,
+				100,
 			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_paid(2, 100));
+			assert_eq!(
+				Pallet::<TestRuntime>::outbound_message_data(TEST_LANE_ID, 1).map(|message_data| message_data.fee),
+				Some(REGULAR_PAYLOAD.1 + 100),
+			);
 
-			// this reports delivery of message 1 => reward is paid to TEST_RELAYER_A
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
-				Origin::signed(1),
-				TestMessagesDeliveryProof(Ok((
-					TEST_LANE_ID,
-					InboundLaneData {
-						relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
-						..Default::default()
-					}
-				))),
-				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 1,
-					total_messages: 1,
-					..Default::default()
-				},
-			));
-			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
-				TEST_RELAYER_A,
-				1000
-			));
-			assert!(!TestMessageDeliveryAndDispatchPayment::is_reward_paid(
-				TEST_RELAYER_B,
-				2000
-			));
+			// topped up message can't be cancelled or expired, because the whole fee would be refunded
+			// to the submitter
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::cancel_message(Origin::signed(1), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotCancellable,
+			);
+			System::<TestRuntime>::set_block_number(11);
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::expire_message(Origin::signed(2), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotExpirable,
+			);
+		});
+	}
 
-			// this reports delivery of both message 1 and message 2 => reward is paid only to TEST_RELAYER_B
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
-				Origin::signed(1),
-				TestMessagesDeliveryProof(Ok((
-					TEST_LANE_ID,
-					InboundLaneData {
-						relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
-							.into_iter()
-							.collect(),
-						..Default::default()
-					}
-				))),
-				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 2,
-					total_messages: 2,
-					..Default::default()
-				},
-			));
-			assert!(!TestMessageDeliveryAndDispatchPayment::is_reward_paid(
-				TEST_RELAYER_A,
-				1000
-			));
-			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
-				TEST_RELAYER_B,
-				2000
-			));
+	#[test]
+	fn cancel_message_fails_if_message_is_already_delivered() {
+		run_test(|| {
+			send_regular_message();
+			receive_messages_delivery_proof();
+
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::cancel_message(Origin::signed(1), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsAlreadyDelivered,
+			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_delivery_proof_rejects_invalid_proof() {
+	fn cancel_message_fails_if_message_is_not_yet_sent() {
 		run_test(|| {
 			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					Origin::signed(1),
-					TestMessagesDeliveryProof(Err(())),
-					Default::default(),
-				),
-				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDeliveryProof,
+				Pallet::<TestRuntime, DefaultInstance>::cancel_message(Origin::signed(1), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotYetSent,
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_delivery_proof_rejects_proof_if_declared_relayers_state_is_invalid() {
+	fn cancel_message_fails_if_called_by_other_account() {
 		run_test(|| {
-			// when number of relayers entires is invalid
-			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					Origin::signed(1),
-					TestMessagesDeliveryProof(Ok((
-						TEST_LANE_ID,
-						InboundLaneData {
-							relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
-								.into_iter()
-								.collect(),
-							..Default::default()
-						}
-					))),
-					UnrewardedRelayersState {
-						unrewarded_relayer_entries: 1,
-						total_messages: 2,
-						..Default::default()
-					},
-				),
-				Error::<TestRuntime, DefaultInstance>::InvalidUnrewardedRelayersState,
-			);
+			send_regular_message();
 
-			// when number of messages is invalid
 			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					Origin::signed(1),
-					TestMessagesDeliveryProof(Ok((
-						TEST_LANE_ID,
-						InboundLaneData {
-							relayers: vec![(1, 1, TEST_RELAYER_A), (2, 2, TEST_RELAYER_B)]
-								.into_iter()
-								.collect(),
-							..Default::default()
-						}
-					))),
-					UnrewardedRelayersState {
-						unrewarded_relayer_entries: 2,
-						total_messages: 1,
-						..Default::default()
-					},
-				),
-				Error::<TestRuntime, DefaultInstance>::InvalidUnrewardedRelayersState,
+				Pallet::<TestRuntime, DefaultInstance>::cancel_message(Origin::signed(2), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotCancellable,
 			);
 		});
 	}
 
+	fn receive_delivery_proof_of_cancelled_message(undispatched_messages: Vec<MessageNonce>) {
+		System::<TestRuntime>::set_block_number(1);
+		System::<TestRuntime>::reset_events();
+
+		assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+			Origin::signed(1),
+			TestMessagesDeliveryProof(Ok((
+				TEST_LANE_ID,
+				InboundLaneData {
+					relayers: vec![(1, 1, TEST_RELAYER_A)].into_iter().collect(),
+					undispatched_messages,
+					..Default::default()
+				}
+			))),
+			UnrewardedRelayersState {
+				unrewarded_relayer_entries: 1,
+				total_messages: 1,
+				..Default::default()
+			},
+		));
+	}
+
 	#[test]
-	fn receive_messages_accepts_single_message_with_invalid_payload() {
+	fn cancel_message_replaces_message_with_noop() {
 		run_test(|| {
-			let mut invalid_message = message(1, REGULAR_PAYLOAD);
-			invalid_message.data.payload = Vec::new();
+			send_regular_message();
 
-			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::cancel_message(
 				Origin::signed(1),
-				TEST_RELAYER_A,
-				Ok(vec![invalid_message]).into(),
+				TEST_LANE_ID,
 				1,
-				0, // weight may be zero in this case (all messages are improperly encoded)
-			),);
-
+			));
 			assert_eq!(
-				InboundLanes::<TestRuntime>::get(&TEST_LANE_ID).last_delivered_nonce(),
+				Pallet::<TestRuntime>::outbound_message_data(TEST_LANE_ID, 1),
+				Some(MessageData {
+					payload: Vec::new(),
+					fee: REGULAR_PAYLOAD.1,
+				}),
+			);
+
+			// the fee is not refunded until the bridged chain confirms that the message hasn't been
+			// dispatched
+			assert!(!TestMessageDeliveryAndDispatchPayment::is_fee_refunded(
 				1,
+				REGULAR_PAYLOAD.1
+			));
+
+			// the message may only be cancelled once and it can't be topped up
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::cancel_message(Origin::signed(1), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotCancellable,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(Origin::signed(2), TEST_LANE_ID, 1, 100),
+				Error::<TestRuntime, DefaultInstance>::MessageIsCancelled,
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_accepts_batch_with_message_with_invalid_payload() {
+	fn fee_of_cancelled_message_is_refunded_when_bridged_chain_confirms_that_it_is_not_dispatched() {
 		run_test(|| {
-			let mut invalid_message = message(2, REGULAR_PAYLOAD);
-			invalid_message.data.payload = Vec::new();
-
-			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
+			send_regular_message();
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::cancel_message(
 				Origin::signed(1),
-				TEST_RELAYER_A,
-				Ok(vec![
-					message(1, REGULAR_PAYLOAD),
-					invalid_message,
-					message(3, REGULAR_PAYLOAD),
-				])
-				.into(),
-				3,
-				REGULAR_PAYLOAD.1 + REGULAR_PAYLOAD.1,
-			),);
+				TEST_LANE_ID,
+				1,
+			));
 
-			assert_eq!(
-				InboundLanes::<TestRuntime>::get(&TEST_LANE_ID).last_delivered_nonce(),
-				3,
-			);
+			receive_delivery_proof_of_cancelled_message(vec![1]);
+
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_refunded(
+				1,
+				REGULAR_PAYLOAD.1
+			));
+			assert!(!TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_A,
+				REGULAR_PAYLOAD.1
+			));
+			assert!(System::<TestRuntime>::events().contains(&EventRecord {
+				phase: Phase::Initialization,
+				event: TestEvent::pallet_bridge_messages(RawEvent::MessageFeeRefunded(TEST_LANE_ID, 1)),
+				topics: vec![],
+			}));
+			assert!(!PendingMessageRefunds::<TestRuntime>::contains_key(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 1
+			}));
 		});
 	}
 
 	#[test]
-	fn storage_message_key_computed_properly() {
-		// If this test fails, then something has been changed in module storage that is breaking all
-		// previously crafted messages proofs.
-		let storage_key = storage_keys::message_key::<TestRuntime, DefaultInstance>(&*b"test", 42).0;
-		assert_eq!(
-			storage_key,
-			hex!("dd16c784ebd3390a9bc0357c7511ed018a395e6242c6813b196ca31ed0547ea79446af0e09063bd4a7874aef8a997cec746573742a00000000000000").to_vec(),
-			"Unexpected storage key: {}",
-			hex::encode(&storage_key),
-		);
-	}
+	fn fee_of_cancelled_message_is_paid_to_relayers_if_it_has_been_dispatched() {
+		run_test(|| {
+			send_regular_message();
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::cancel_message(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				1,
+			));
 
-	#[test]
-	fn outbound_lane_data_key_computed_properly() {
-		// If this test fails, then something has been changed in module storage that is breaking all
-		// previously crafted outbound lane state proofs.
-		let storage_key = storage_keys::outbound_lane_data_key::<DefaultInstance>(&*b"test").0;
-		assert_eq!(
-			storage_key,
-			hex!("dd16c784ebd3390a9bc0357c7511ed0196c246acb9b55077390e3ca723a0ca1f44a8995dd50b6657a037a7839304535b74657374").to_vec(),
-			"Unexpected storage key: {}",
-			hex::encode(&storage_key),
-		);
-	}
+			// the original message has been delivered before cancellation
+			receive_delivery_proof_of_cancelled_message(vec![]);
 
-	#[test]
-	fn inbound_lane_data_key_computed_properly() {
-		// If this test fails, then something has been changed in module storage that is breaking all
-		// previously crafted inbound lane state proofs.
-		let storage_key = storage_keys::inbound_lane_data_key::<TestRuntime, DefaultInstance>(&*b"test").0;
-		assert_eq!(
-			storage_key,
-			hex!("dd16c784ebd3390a9bc0357c7511ed01e5f83cf83f2127eb47afdc35d6e43fab44a8995dd50b6657a037a7839304535b74657374").to_vec(),
-			"Unexpected storage key: {}",
-			hex::encode(&storage_key),
-		);
+			assert!(!TestMessageDeliveryAndDispatchPayment::is_fee_refunded(
+				1,
+				REGULAR_PAYLOAD.1
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_A,
+				REGULAR_PAYLOAD.1
+			));
+			assert!(System::<TestRuntime>::events().contains(&EventRecord {
+				phase: Phase::Initialization,
+				event: TestEvent::pallet_bridge_messages(RawEvent::MessageDispatchedBeforeCancellation(
+					TEST_LANE_ID,
+					1
+				)),
+				topics: vec![],
+			}));
+		});
 	}
 
 	#[test]
-	fn actual_dispatch_weight_does_not_overlow() {
+	fn prune_undelivered_messages_refunds_fees() {
 		run_test(|| {
-			let message1 = message(1, TestPayload(0, Weight::MAX / 2));
-			let message2 = message(2, TestPayload(0, Weight::MAX / 2));
-			let message3 = message(2, TestPayload(0, Weight::MAX / 2));
+			send_regular_message();
+			assert_ok!(Pallet::<TestRuntime>::send_message(
+				Origin::root(),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+			));
 
+			// messages may only be pruned at halted lanes
 			assert_noop!(
-				Pallet::<TestRuntime, DefaultInstance>::receive_messages_proof(
-					Origin::signed(1),
-					TEST_RELAYER_A,
-					// this may cause overflow if source chain storage is invalid
-					Ok(vec![message1, message2, message3]).into(),
-					3,
-					100,
-				),
-				Error::<TestRuntime, DefaultInstance>::InvalidMessagesDispatchWeight,
+				Pallet::<TestRuntime>::prune_undelivered_messages(Origin::root(), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::LaneIsNotHalted,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_operating_mode(
+				Origin::root(),
+				TEST_LANE_ID,
+				OperatingMode::Halted,
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::prune_undelivered_messages(Origin::signed(1), TEST_LANE_ID, 1),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::prune_undelivered_messages(Origin::root(), TEST_LANE_ID, 3),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotYetSent,
 			);
+
+			// fee of the message, sent by root, goes to the fee recipient
+			assert_ok!(Pallet::<TestRuntime>::prune_undelivered_messages(
+				Origin::root(),
+				TEST_LANE_ID,
+				2,
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_refunded(
+				TEST_FEE_RECIPIENT,
+				REGULAR_PAYLOAD.1
+			));
+
+			// fee of the regular message is refunded to the submitter and already pruned message is ignored
+			assert_ok!(Pallet::<TestRuntime>::prune_undelivered_messages(
+				Origin::root(),
+				TEST_LANE_ID,
+				1,
+			));
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_refunded(
+				1,
+				REGULAR_PAYLOAD.1
+			));
+			for nonce in 1..=2 {
+				assert_eq!(
+					Pallet::<TestRuntime>::outbound_message_data(TEST_LANE_ID, nonce),
+					Some(MessageData {
+						payload: Vec::new(),
+						fee: 0,
+					}),
+				);
+			}
 		});
 	}
 
 	#[test]
-	fn increase_message_fee_fails_if_message_is_already_delivered() {
+	fn expire_message_fails_if_message_has_no_ttl() {
 		run_test(|| {
 			send_regular_message();
-			receive_messages_delivery_proof();
 
 			assert_noop!(
-				Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 100,),
-				Error::<TestRuntime, DefaultInstance>::MessageIsAlreadyDelivered,
+				Pallet::<TestRuntime, DefaultInstance>::expire_message(Origin::signed(2), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotExpirable,
 			);
 		});
 	}
 
 	#[test]
-	fn increase_message_fee_fails_if_message_is_not_yet_sent() {
+	fn expire_message_fails_if_ttl_has_not_ended() {
 		run_test(|| {
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::send_message_with_ttl(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+				10,
+			));
+
+			System::<TestRuntime>::set_block_number(10);
 			assert_noop!(
-				Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 100,),
-				Error::<TestRuntime, DefaultInstance>::MessageIsNotYetSent,
+				Pallet::<TestRuntime, DefaultInstance>::expire_message(Origin::signed(2), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotExpired,
 			);
 		});
 	}
 
 	#[test]
-	fn increase_message_fee_fails_if_submitter_cant_pay_additional_fee() {
+	fn expire_message_replaces_message_with_noop_and_refunds_fee_when_confirmed() {
 		run_test(|| {
-			send_regular_message();
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::send_message_with_ttl(
+				Origin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+				10,
+			));
 
-			TestMessageDeliveryAndDispatchPayment::reject_payments();
+			System::<TestRuntime>::set_block_number(11);
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::expire_message(
+				Origin::signed(2),
+				TEST_LANE_ID,
+				1,
+			));
+			assert!(!TestMessageDeliveryAndDispatchPayment::is_fee_refunded(
+				1,
+				REGULAR_PAYLOAD.1
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::outbound_message_data(TEST_LANE_ID, 1),
+				Some(MessageData {
+					payload: Vec::new(),
+					fee: REGULAR_PAYLOAD.1,
+				}),
+			);
 
+			// the expired message can't be cancelled or expired again
 			assert_noop!(
-				Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(Origin::signed(1), TEST_LANE_ID, 1, 100,),
-				Error::<TestRuntime, DefaultInstance>::FailedToWithdrawMessageFee,
+				Pallet::<TestRuntime, DefaultInstance>::cancel_message(Origin::signed(1), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotCancellable,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime, DefaultInstance>::expire_message(Origin::signed(2), TEST_LANE_ID, 1),
+				Error::<TestRuntime, DefaultInstance>::MessageIsNotExpirable,
 			);
+
+			// the fee is refunded when the bridged chain confirms that the message hasn't been dispatched
+			receive_delivery_proof_of_cancelled_message(vec![1]);
+			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_refunded(
+				1,
+				REGULAR_PAYLOAD.1
+			));
 		});
 	}
 
 	#[test]
-	fn increase_message_fee_succeeds() {
+	fn fee_of_expired_message_sent_by_root_is_paid_to_relayers() {
 		run_test(|| {
-			send_regular_message();
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::send_message_with_ttl(
+				Origin::root(),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+				REGULAR_PAYLOAD.1,
+				10,
+			));
 
-			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::increase_message_fee(
-				Origin::signed(1),
+			System::<TestRuntime>::set_block_number(11);
+			assert_ok!(Pallet::<TestRuntime, DefaultInstance>::expire_message(
+				Origin::signed(2),
 				TEST_LANE_ID,
 				1,
-				100,
-			),);
-			assert!(TestMessageDeliveryAndDispatchPayment::is_fee_paid(1, 100));
+			));
+
+			receive_delivery_proof_of_cancelled_message(vec![1]);
+			assert!(TestMessageDeliveryAndDispatchPayment::is_reward_paid(
+				TEST_RELAYER_A,
+				REGULAR_PAYLOAD.1
+			));
 		});
 	}
 }
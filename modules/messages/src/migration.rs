@@ -0,0 +1,520 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Migrations of the messages pallet storage.
+//!
+//! Every change of the stored lanes data format must introduce new `StorageVersion` and add an
+//! explicit upgrade path from the previous version to the `migrate` function.
+
+use crate::{
+	AllowedLaneRelayers, Config, InboundLanes, Instance, LaneRelayers, OutboundLanes, Pallet, PalletOperatingMode,
+	PalletStorageVersion, RawEvent, StoredInboundLaneData,
+};
+
+use bp_messages::{InboundLaneData, LaneId, LaneState, MessageNonce, OperatingMode, OutboundLaneData};
+use codec::{Decode, Encode};
+use frame_support::{
+	storage::{unhashed, IterableStorageMap},
+	traits::Get,
+	weights::Weight,
+	RuntimeDebug, StorageDoubleMap, StorageMap, StorageValue,
+};
+use sp_std::vec::Vec;
+
+/// Version of the messages pallet storage.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum StorageVersion {
+	/// Inbound and outbound lanes data has no `state` field.
+	///
+	/// This is the version of all chains that have been started before the storage version has been
+	/// introduced.
+	V1,
+	/// Inbound and outbound lanes data has `state` field.
+	V2,
+	/// Number of unrewarded relayer entries of inbound lanes is bounded by the
+	/// `MaxUnrewardedRelayerEntriesAtInboundLane`.
+	V3,
+	/// Sets of lane relayers are bounded by the `MaxLaneRelayers` and are indexed by the
+	/// `AllowedLaneRelayers` map.
+	V4,
+	/// Inbound lanes data has `undispatched_messages` field.
+	V5,
+}
+
+impl StorageVersion {
+	/// Latest version of the pallet storage. It is set at genesis.
+	pub const LATEST: StorageVersion = StorageVersion::V5;
+}
+
+impl Default for StorageVersion {
+	fn default() -> Self {
+		StorageVersion::V1
+	}
+}
+
+/// Upgrade the pallet storage to the latest version.
+///
+/// Upgrade paths are applied one after another, so storage of any version is upgraded to the
+/// latest version. Does nothing if the storage is already at the latest version. If some upgrade
+/// path refuses to migrate the storage, it stays at the previous version and the pallet is halted.
+pub fn migrate<T: Config<I>, I: Instance>() -> Weight {
+	let mut version = PalletStorageVersion::<I>::get();
+	let mut weight = T::DbWeight::get().reads(1);
+	if version == StorageVersion::LATEST {
+		return weight;
+	}
+
+	if version == StorageVersion::V1 {
+		weight = weight.saturating_add(migrate_v1_to_v2::<T, I>());
+		version = StorageVersion::V2;
+	}
+	if version == StorageVersion::V2 {
+		let (migration_weight, is_migrated) = migrate_v2_to_v3::<T, I>();
+		weight = weight.saturating_add(migration_weight);
+		if is_migrated {
+			version = StorageVersion::V3;
+		}
+	}
+	if version == StorageVersion::V3 {
+		weight = weight.saturating_add(migrate_v3_to_v4::<T, I>());
+		version = StorageVersion::V4;
+	}
+	if version == StorageVersion::V4 {
+		weight = weight.saturating_add(migrate_v4_to_v5::<T, I>());
+		version = StorageVersion::V5;
+	}
+
+	if version == StorageVersion::LATEST {
+		log::info!(
+			target: "runtime::bridge-messages",
+			"Messages pallet storage has been upgraded to {:?}",
+			version,
+		);
+	} else {
+		log::error!(
+			target: "runtime::bridge-messages",
+			"Messages pallet storage upgrade has stopped at {:?}. The pallet is halted",
+			version,
+		);
+	}
+
+	PalletStorageVersion::<I>::put(version);
+	weight.saturating_add(T::DbWeight::get().writes(1))
+}
+
+/// Storage types of the `V1` version.
+mod v1 {
+	use bp_messages::MessageNonce;
+	use codec::{Decode, Encode};
+	use sp_std::collections::vec_deque::VecDeque;
+
+	/// Inbound lane data without the `state` field.
+	#[derive(Encode, Decode)]
+	pub struct InboundLaneData<RelayerId> {
+		pub relayers: VecDeque<(MessageNonce, MessageNonce, RelayerId)>,
+		pub last_confirmed_nonce: MessageNonce,
+	}
+
+	/// Outbound lane data without the `state` field.
+	#[derive(Encode, Decode)]
+	pub struct OutboundLaneData {
+		pub oldest_unpruned_nonce: MessageNonce,
+		pub latest_received_nonce: MessageNonce,
+		pub latest_generated_nonce: MessageNonce,
+	}
+}
+
+/// Storage types of the `V2`, `V3` and `V4` versions.
+mod v2 {
+	use bp_messages::{LaneState, MessageNonce};
+	use codec::{Decode, Encode};
+	use sp_std::collections::vec_deque::VecDeque;
+
+	/// Inbound lane data without the `undispatched_messages` field.
+	#[derive(Encode, Decode)]
+	pub struct InboundLaneData<RelayerId> {
+		pub relayers: VecDeque<(MessageNonce, MessageNonce, RelayerId)>,
+		pub last_confirmed_nonce: MessageNonce,
+		pub state: LaneState,
+	}
+}
+
+/// Add `state` field to all inbound and outbound lanes. Lanes could not be closed before the
+/// field has been added, so all lanes are `Opened`.
+///
+/// Number of unrewarded relayer entries of inbound lanes is bounded by the following
+/// `migrate_v2_to_v3` migration.
+fn migrate_v1_to_v2<T: Config<I>, I: Instance>() -> Weight {
+	let mut lanes =
+		translate_outdated_inbound_lanes::<T, I, v1::InboundLaneData<T::InboundRelayer>, _, _>(|_, old_data| {
+			Some(v2::InboundLaneData {
+				relayers: old_data.relayers,
+				last_confirmed_nonce: old_data.last_confirmed_nonce,
+				state: LaneState::Opened,
+			})
+		});
+	OutboundLanes::<I>::translate::<v1::OutboundLaneData, _>(|_, old_data| {
+		lanes += 1;
+
+		Some(OutboundLaneData {
+			oldest_unpruned_nonce: old_data.oldest_unpruned_nonce,
+			latest_received_nonce: old_data.latest_received_nonce,
+			latest_generated_nonce: old_data.latest_generated_nonce,
+			state: LaneState::Opened,
+		})
+	});
+
+	T::DbWeight::get().reads_writes(lanes, lanes)
+}
+
+/// Ensure that all stored inbound lanes respect the `MaxUnrewardedRelayerEntriesAtInboundLane`
+/// bound. The storage format is not changed.
+///
+/// The stored lane fails to decode if it has more unrewarded relayer entries than allowed, which
+/// may only happen if the lane has been stored before the bound has been enforced. Entries can't
+/// be dropped, because relayers of dropped entries would never be rewarded. So if there's at least
+/// one such lane, the migration is refused: the `InboundLaneNotMigrated` event is emitted for every
+/// such lane and the pallet is halted.
+///
+/// Returns `false` if the migration has been refused.
+fn migrate_v2_to_v3<T: Config<I>, I: Instance>() -> (Weight, bool) {
+	let max_relayers_entries = T::MaxUnrewardedRelayerEntriesAtInboundLane::get();
+	let mut lanes = 0u64;
+	let mut refused_lanes = 0u64;
+	for lane_id in InboundLanes::<T, I>::iter_keys() {
+		lanes += 1;
+
+		let storage_key = InboundLanes::<T, I>::hashed_key_for(&lane_id);
+		let relayers_entries = unhashed::get::<v2::InboundLaneData<T::InboundRelayer>>(&storage_key)
+			.map(|data| data.relayers.len() as MessageNonce)
+			.unwrap_or(0);
+		if relayers_entries > max_relayers_entries {
+			log::error!(
+				target: "runtime::bridge-messages",
+				"Refusing to migrate inbound lane {:?} with {} (max {}) unrewarded relayer entries",
+				lane_id,
+				relayers_entries,
+				max_relayers_entries,
+			);
+
+			refused_lanes += 1;
+			Pallet::<T, I>::deposit_event(RawEvent::InboundLaneNotMigrated(lane_id, relayers_entries));
+		}
+	}
+
+	if refused_lanes != 0 {
+		PalletOperatingMode::<I>::put(OperatingMode::Halted);
+		return (
+			T::DbWeight::get().reads_writes(lanes, refused_lanes.saturating_add(1)),
+			false,
+		);
+	}
+
+	(T::DbWeight::get().reads(lanes), true)
+}
+
+/// Fill the `AllowedLaneRelayers` index from the `LaneRelayers` sets.
+///
+/// Duplicate relayers are removed from the sets and sets that have more than `MaxLaneRelayers`
+/// relayers are truncated.
+fn migrate_v3_to_v4<T: Config<I>, I: Instance>() -> Weight {
+	let max_relayers = T::MaxLaneRelayers::get() as usize;
+	let mut lanes = 0u64;
+	let mut relayers_count = 0u64;
+	LaneRelayers::<T, I>::translate::<Vec<T::AccountId>, _>(|lane_id, relayers| {
+		lanes += 1;
+
+		let mut allowed_relayers = Vec::with_capacity(sp_std::cmp::min(relayers.len(), max_relayers));
+		for relayer in relayers {
+			if allowed_relayers.len() == max_relayers {
+				log::error!(
+					target: "runtime::bridge-messages",
+					"Dropping relayers of lane {:?} that are above the {} limit",
+					lane_id,
+					max_relayers,
+				);
+				break;
+			}
+			if !allowed_relayers.contains(&relayer) {
+				AllowedLaneRelayers::<T, I>::insert(&lane_id, &relayer, true);
+				allowed_relayers.push(relayer);
+			}
+		}
+
+		relayers_count += allowed_relayers.len() as u64;
+		Some(allowed_relayers)
+	});
+
+	T::DbWeight::get().reads_writes(lanes, lanes.saturating_add(relayers_count))
+}
+
+/// Add empty `undispatched_messages` field to all inbound lanes.
+///
+/// Undispatched messages have not been tracked before, so the source chain treats all messages
+/// that have been delivered before the upgrade as dispatched.
+fn migrate_v4_to_v5<T: Config<I>, I: Instance>() -> Weight {
+	let mut lanes = 0u64;
+	InboundLanes::<T, I>::translate::<v2::InboundLaneData<T::InboundRelayer>, _>(|_, old_data| {
+		lanes += 1;
+
+		StoredInboundLaneData::try_new(InboundLaneData {
+			relayers: old_data.relayers,
+			last_confirmed_nonce: old_data.last_confirmed_nonce,
+			state: old_data.state,
+			undispatched_messages: Vec::new(),
+		})
+	});
+
+	T::DbWeight::get().reads_writes(lanes, lanes)
+}
+
+/// Translate all stored inbound lanes from one outdated data format to another outdated format.
+///
+/// The `InboundLanes::translate` may only be used to translate lanes to the latest format, so we
+/// need to read and write raw storage values here. Lanes are removed if the `f` returns `None`.
+///
+/// Returns number of translated lanes.
+fn translate_outdated_inbound_lanes<T, I, O, V, F>(mut f: F) -> u64
+where
+	T: Config<I>,
+	I: Instance,
+	O: Decode,
+	V: Encode,
+	F: FnMut(LaneId, O) -> Option<V>,
+{
+	let lane_ids = InboundLanes::<T, I>::iter_keys().collect::<Vec<_>>();
+	for lane_id in &lane_ids {
+		let storage_key = InboundLanes::<T, I>::hashed_key_for(lane_id);
+		match unhashed::get::<O>(&storage_key).and_then(|old_data| f(*lane_id, old_data)) {
+			Some(new_data) => unhashed::put(&storage_key, &new_data),
+			None => unhashed::kill(&storage_key),
+		}
+	}
+
+	lane_ids.len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{run_test, Event as TestEvent, TestRelayer, TestRuntime, TEST_LANE_ID, TEST_RELAYER_A};
+	use crate::DefaultInstance;
+	use frame_support::{storage::unhashed, StorageMap};
+	use frame_system::{EventRecord, Pallet as System, Phase};
+
+	fn insert_v1_lanes() {
+		unhashed::put(
+			&InboundLanes::<TestRuntime, DefaultInstance>::hashed_key_for(&TEST_LANE_ID),
+			&v1::InboundLaneData::<TestRelayer> {
+				relayers: vec![(1, 2, TEST_RELAYER_A)].into_iter().collect(),
+				last_confirmed_nonce: 0,
+			},
+		);
+		unhashed::put(
+			&OutboundLanes::<DefaultInstance>::hashed_key_for(&TEST_LANE_ID),
+			&v1::OutboundLaneData {
+				oldest_unpruned_nonce: 2,
+				latest_received_nonce: 1,
+				latest_generated_nonce: 3,
+			},
+		);
+	}
+
+	#[test]
+	fn migrates_lanes_from_v1_to_v2() {
+		run_test(|| {
+			insert_v1_lanes();
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), StorageVersion::LATEST);
+			assert_eq!(
+				InboundLanes::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID).into_inner(),
+				InboundLaneData {
+					relayers: vec![(1, 2, TEST_RELAYER_A)].into_iter().collect(),
+					last_confirmed_nonce: 0,
+					state: LaneState::Opened,
+					undispatched_messages: Vec::new(),
+				},
+			);
+			assert_eq!(
+				OutboundLanes::<DefaultInstance>::get(&TEST_LANE_ID),
+				OutboundLaneData {
+					oldest_unpruned_nonce: 2,
+					latest_received_nonce: 1,
+					latest_generated_nonce: 3,
+					state: LaneState::Opened,
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn refuses_to_migrate_lanes_from_v1_to_latest_version_with_unbounded_relayers() {
+		run_test(|| {
+			let max_entries = <TestRuntime as Config>::MaxUnrewardedRelayerEntriesAtInboundLane::get();
+			unhashed::put(
+				&InboundLanes::<TestRuntime, DefaultInstance>::hashed_key_for(&TEST_LANE_ID),
+				&v1::InboundLaneData::<TestRelayer> {
+					relayers: (1..=max_entries + 2)
+						.map(|nonce| (nonce, nonce, TEST_RELAYER_A))
+						.collect(),
+					last_confirmed_nonce: 0,
+				},
+			);
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), StorageVersion::V2);
+			assert_eq!(PalletOperatingMode::<DefaultInstance>::get(), OperatingMode::Halted);
+			let inbound_lane_key = InboundLanes::<TestRuntime, DefaultInstance>::hashed_key_for(&TEST_LANE_ID);
+			let lane_data = unhashed::get::<v2::InboundLaneData<TestRelayer>>(&inbound_lane_key).unwrap();
+			assert_eq!(lane_data.last_confirmed_nonce, 0);
+			assert_eq!(lane_data.relayers.len() as MessageNonce, max_entries + 2);
+		});
+	}
+
+	fn insert_v2_lane(relayers_entries: MessageNonce) {
+		unhashed::put(
+			&InboundLanes::<TestRuntime, DefaultInstance>::hashed_key_for(&TEST_LANE_ID),
+			&v2::InboundLaneData::<TestRelayer> {
+				relayers: (1..=relayers_entries)
+					.map(|nonce| (nonce, nonce, TEST_RELAYER_A))
+					.collect(),
+				last_confirmed_nonce: 0,
+				state: LaneState::Opened,
+			},
+		);
+	}
+
+	#[test]
+	fn migrates_lanes_from_v2_to_v3() {
+		run_test(|| {
+			let max_entries = <TestRuntime as Config>::MaxUnrewardedRelayerEntriesAtInboundLane::get();
+			PalletStorageVersion::<DefaultInstance>::put(StorageVersion::V2);
+			insert_v2_lane(max_entries);
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), StorageVersion::LATEST);
+			assert_eq!(PalletOperatingMode::<DefaultInstance>::get(), OperatingMode::Normal);
+			let lane_data = InboundLanes::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID).into_inner();
+			assert_eq!(lane_data.last_confirmed_nonce, 0);
+			assert_eq!(lane_data.relayers.len() as MessageNonce, max_entries);
+		});
+	}
+
+	#[test]
+	fn refuses_to_migrate_lanes_from_v2_to_v3_if_there_are_too_many_relayers_entries() {
+		run_test(|| {
+			let max_entries = <TestRuntime as Config>::MaxUnrewardedRelayerEntriesAtInboundLane::get();
+			PalletStorageVersion::<DefaultInstance>::put(StorageVersion::V2);
+			insert_v2_lane(max_entries + 2);
+			let inbound_lane_key = InboundLanes::<TestRuntime, DefaultInstance>::hashed_key_for(&TEST_LANE_ID);
+			let raw_inbound_lane_data = unhashed::get_raw(&inbound_lane_key);
+
+			System::<TestRuntime>::set_block_number(1);
+			System::<TestRuntime>::reset_events();
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), StorageVersion::V2);
+			assert_eq!(PalletOperatingMode::<DefaultInstance>::get(), OperatingMode::Halted);
+			assert_eq!(unhashed::get_raw(&inbound_lane_key), raw_inbound_lane_data);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::pallet_bridge_messages(RawEvent::InboundLaneNotMigrated(
+						TEST_LANE_ID,
+						max_entries + 2
+					)),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn migrates_lane_relayers_from_v3_to_v4() {
+		run_test(|| {
+			let max_relayers = <TestRuntime as Config>::MaxLaneRelayers::get() as u64;
+			PalletStorageVersion::<DefaultInstance>::put(StorageVersion::V3);
+			LaneRelayers::<TestRuntime, DefaultInstance>::insert(
+				&TEST_LANE_ID,
+				vec![1, 1].into_iter().chain(1..=max_relayers + 1).collect::<Vec<_>>(),
+			);
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), StorageVersion::LATEST);
+			assert_eq!(
+				LaneRelayers::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID),
+				Some((1..=max_relayers).collect()),
+			);
+			assert!(AllowedLaneRelayers::<TestRuntime, DefaultInstance>::get(
+				&TEST_LANE_ID,
+				&max_relayers
+			));
+			assert!(!AllowedLaneRelayers::<TestRuntime, DefaultInstance>::get(
+				&TEST_LANE_ID,
+				&(max_relayers + 1)
+			));
+		});
+	}
+
+	#[test]
+	fn migrates_inbound_lanes_from_v4_to_v5() {
+		run_test(|| {
+			PalletStorageVersion::<DefaultInstance>::put(StorageVersion::V4);
+			unhashed::put(
+				&InboundLanes::<TestRuntime, DefaultInstance>::hashed_key_for(&TEST_LANE_ID),
+				&v2::InboundLaneData::<TestRelayer> {
+					relayers: vec![(2, 3, TEST_RELAYER_A)].into_iter().collect(),
+					last_confirmed_nonce: 1,
+					state: LaneState::Closing,
+				},
+			);
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(PalletStorageVersion::<DefaultInstance>::get(), StorageVersion::V5);
+			assert_eq!(
+				InboundLanes::<TestRuntime, DefaultInstance>::get(&TEST_LANE_ID).into_inner(),
+				InboundLaneData {
+					relayers: vec![(2, 3, TEST_RELAYER_A)].into_iter().collect(),
+					last_confirmed_nonce: 1,
+					state: LaneState::Closing,
+					undispatched_messages: Vec::new(),
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn does_nothing_if_storage_is_at_latest_version() {
+		run_test(|| {
+			PalletStorageVersion::<DefaultInstance>::put(StorageVersion::LATEST);
+			insert_v1_lanes();
+			let outbound_lane_key = OutboundLanes::<DefaultInstance>::hashed_key_for(&TEST_LANE_ID);
+			let raw_outbound_lane_data = unhashed::get_raw(&outbound_lane_key);
+
+			migrate::<TestRuntime, DefaultInstance>();
+
+			assert_eq!(unhashed::get_raw(&outbound_lane_key), raw_outbound_lane_data);
+		});
+	}
+}
@@ -21,22 +21,23 @@ use crate::Config;
 
 use bp_messages::{
 	source_chain::{
-		LaneMessageVerifier, MessageDeliveryAndDispatchPayment, RelayersRewards, Sender, TargetHeaderChain,
+		LaneMessageVerifier, MessageDeliveryAndDispatchPayment, OnDeliveryConfirmed, RelayersRewards, Sender,
+		TargetHeaderChain,
 	},
 	target_chain::{DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages, SourceHeaderChain},
 	InboundLaneData, LaneId, Message, MessageData, MessageKey, MessageNonce, OutboundLaneData,
-	Parameter as MessagesParameter,
+	Parameter as MessagesParameter, VersionedMessagePayload,
 };
 use bp_runtime::Size;
 use codec::{Decode, Encode};
-use frame_support::{parameter_types, weights::Weight};
+use frame_support::{ord_parameter_types, parameter_types, weights::Weight};
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header as SubstrateHeader,
 	traits::{BlakeTwo256, IdentityLookup},
 	FixedU128, Perbill,
 };
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, ops::RangeInclusive};
 
 pub type AccountId = u64;
 pub type Balance = u64;
@@ -123,7 +124,19 @@ parameter_types! {
 	pub const MaxMessagesToPruneAtOnce: u64 = 10;
 	pub const MaxUnrewardedRelayerEntriesAtInboundLane: u64 = 16;
 	pub const MaxUnconfirmedMessagesAtInboundLane: u64 = 32;
+	pub const MaxLaneRelayers: u32 = 4;
+	pub const MaxQueuedMessagesAtInboundLane: u64 = 4;
 	pub storage TokenConversionRate: FixedU128 = 1.into();
+	pub storage ConfirmationRelayerRewardShare: Perbill = Perbill::zero();
+	pub const FeeAuctionPeriod: u64 = 10;
+	pub const UndeliveredMessagesFeeRecipient: Option<AccountId> = Some(TEST_FEE_RECIPIENT);
+	pub const IdempotencyKeyLifetime: u64 = 10;
+	pub storage LaneCongestionThreshold: MessageNonce = 0;
+	pub storage BestFinalizedBridgedHeaderNumber: u64 = 0;
+}
+
+ord_parameter_types! {
+	pub const OwnerOriginAccount: AccountId = TEST_OWNER_ORIGIN_ACCOUNT;
 }
 
 #[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
@@ -143,9 +156,12 @@ impl Config for TestRuntime {
 	type Event = Event;
 	type WeightInfo = ();
 	type Parameter = TestMessagesParameter;
+	type OwnerOrigin = frame_system::EnsureSignedBy<OwnerOriginAccount, AccountId>;
 	type MaxMessagesToPruneAtOnce = MaxMessagesToPruneAtOnce;
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type MaxLaneRelayers = MaxLaneRelayers;
+	type MaxQueuedMessagesAtInboundLane = MaxQueuedMessagesAtInboundLane;
 
 	type OutboundPayload = TestPayload;
 	type OutboundMessageFee = TestMessageFee;
@@ -159,6 +175,14 @@ impl Config for TestRuntime {
 	type TargetHeaderChain = TestTargetHeaderChain;
 	type LaneMessageVerifier = TestLaneMessageVerifier;
 	type MessageDeliveryAndDispatchPayment = TestMessageDeliveryAndDispatchPayment;
+	type OnDeliveryConfirmed = TestOnDeliveryConfirmed;
+	type ConfirmationRelayerRewardShare = ConfirmationRelayerRewardShare;
+	type FeeAuctionPeriod = FeeAuctionPeriod;
+	type UndeliveredMessagesFeeRecipient = UndeliveredMessagesFeeRecipient;
+	type IdempotencyKeyLifetime = IdempotencyKeyLifetime;
+	type LaneCongestionThreshold = LaneCongestionThreshold;
+	type BridgedHeaderNumber = u64;
+	type BestFinalizedBridgedHeaderNumber = BestFinalizedBridgedHeaderNumber;
 
 	type SourceHeaderChain = TestSourceHeaderChain;
 	type MessageDispatch = TestMessageDispatch;
@@ -182,6 +206,12 @@ pub const TEST_RELAYER_B: AccountId = 101;
 /// Account id of additional test relayer - C.
 pub const TEST_RELAYER_C: AccountId = 102;
 
+/// Account that receives fees of pruned undelivered messages, that can't be refunded to submitters.
+pub const TEST_FEE_RECIPIENT: AccountId = 200;
+
+/// Account that is accepted by the `OwnerOrigin`.
+pub const TEST_OWNER_ORIGIN_ACCOUNT: AccountId = 300;
+
 /// Error that is returned by all test implementations.
 pub const TEST_ERROR: &str = "Test error";
 
@@ -194,6 +224,9 @@ pub const REGULAR_PAYLOAD: TestPayload = TestPayload(0, 50);
 /// Payload that is rejected by `TestTargetHeaderChain`.
 pub const PAYLOAD_REJECTED_BY_TARGET_CHAIN: TestPayload = TestPayload(1, 50);
 
+/// Payload that only spends half of its dispatch weight.
+pub const PAYLOAD_WITH_UNSPENT_WEIGHT: TestPayload = TestPayload(2, 50);
+
 /// Vec of proved messages, grouped by lane.
 pub type MessagesByLaneVec = Vec<(LaneId, ProvedLaneMessages<Message<TestMessageFee>>)>;
 
@@ -238,6 +271,16 @@ impl Size for TestMessagesDeliveryProof {
 	}
 }
 
+/// Messages delivery proof of several lanes used in tests.
+#[derive(Debug, Encode, Decode, Eq, Clone, PartialEq)]
+pub struct TestMessagesDeliveryBatchProof(pub Result<Vec<(LaneId, InboundLaneData<TestRelayer>)>, ()>);
+
+impl Size for TestMessagesDeliveryBatchProof {
+	fn size_hint(&self) -> u32 {
+		0
+	}
+}
+
 /// Target header chain that is used in tests.
 #[derive(Debug, Default)]
 pub struct TestTargetHeaderChain;
@@ -246,6 +289,7 @@ impl TargetHeaderChain<TestPayload, TestRelayer> for TestTargetHeaderChain {
 	type Error = &'static str;
 
 	type MessagesDeliveryProof = TestMessagesDeliveryProof;
+	type MessagesDeliveryBatchProof = TestMessagesDeliveryBatchProof;
 
 	fn verify_message(payload: &TestPayload) -> Result<(), Self::Error> {
 		if *payload == PAYLOAD_REJECTED_BY_TARGET_CHAIN {
@@ -260,6 +304,12 @@ impl TargetHeaderChain<TestPayload, TestRelayer> for TestTargetHeaderChain {
 	) -> Result<(LaneId, InboundLaneData<TestRelayer>), Self::Error> {
 		proof.0.map_err(|_| TEST_ERROR)
 	}
+
+	fn verify_messages_delivery_batch_proof(
+		proof: Self::MessagesDeliveryBatchProof,
+	) -> Result<Vec<(LaneId, InboundLaneData<TestRelayer>)>, Self::Error> {
+		proof.0.map_err(|_| TEST_ERROR)
+	}
 }
 
 /// Lane message verifier that is used in tests.
@@ -299,6 +349,11 @@ impl TestMessageDeliveryAndDispatchPayment {
 		frame_support::storage::unhashed::get(b":message-fee:") == Some((Sender::Signed(submitter), fee))
 	}
 
+	/// Returns true if given fee has been refunded to given submitter.
+	pub fn is_fee_refunded(submitter: AccountId, fee: TestMessageFee) -> bool {
+		frame_support::storage::unhashed::get(b":message-fee-refund:") == Some((submitter, fee))
+	}
+
 	/// Returns true if given relayer has been rewarded with given balance. The reward-paid flag is
 	/// cleared after the call.
 	pub fn is_reward_paid(relayer: AccountId, fee: TestMessageFee) -> bool {
@@ -323,6 +378,19 @@ impl MessageDeliveryAndDispatchPayment<AccountId, TestMessageFee> for TestMessag
 		Ok(())
 	}
 
+	fn refund_delivery_and_dispatch_fee(
+		submitter: &AccountId,
+		fee: &TestMessageFee,
+		_relayer_fund_account: &AccountId,
+	) -> Result<(), Self::Error> {
+		if frame_support::storage::unhashed::get(b":reject-message-fee:") == Some(true) {
+			return Err(TEST_ERROR);
+		}
+
+		frame_support::storage::unhashed::put(b":message-fee-refund:", &(submitter, fee));
+		Ok(())
+	}
+
 	fn pay_relayers_rewards(
 		_confirmation_relayer: &AccountId,
 		relayers_rewards: RelayersRewards<AccountId, TestMessageFee>,
@@ -335,6 +403,28 @@ impl MessageDeliveryAndDispatchPayment<AccountId, TestMessageFee> for TestMessag
 	}
 }
 
+/// Weight that is returned by the `TestOnDeliveryConfirmed` handler.
+pub const DELIVERY_CONFIRMATION_HANDLER_WEIGHT: Weight = 7;
+
+/// Messages delivery confirmation handler that is used in tests.
+#[derive(Debug, Default)]
+pub struct TestOnDeliveryConfirmed;
+
+impl TestOnDeliveryConfirmed {
+	/// Returns true if the handler has been called with given lane and range of messages.
+	pub fn is_called(lane: LaneId, messages: RangeInclusive<MessageNonce>) -> bool {
+		frame_support::storage::unhashed::get(b":delivered-messages:")
+			== Some((lane, *messages.start(), *messages.end()))
+	}
+}
+
+impl OnDeliveryConfirmed for TestOnDeliveryConfirmed {
+	fn on_messages_delivered(lane: &LaneId, messages: &RangeInclusive<MessageNonce>) -> Weight {
+		frame_support::storage::unhashed::put(b":delivered-messages:", &(lane, messages.start(), messages.end()));
+		DELIVERY_CONFIRMATION_HANDLER_WEIGHT
+	}
+}
+
 /// Source header chain that is used in tests.
 #[derive(Debug)]
 pub struct TestSourceHeaderChain;
@@ -359,7 +449,7 @@ impl SourceHeaderChain<TestMessageFee> for TestSourceHeaderChain {
 #[derive(Debug)]
 pub struct TestMessageDispatch;
 
-impl MessageDispatch<TestMessageFee> for TestMessageDispatch {
+impl MessageDispatch<AccountId, TestMessageFee> for TestMessageDispatch {
 	type DispatchPayload = TestPayload;
 
 	fn dispatch_weight(message: &DispatchMessage<TestPayload, TestMessageFee>) -> Weight {
@@ -369,7 +459,12 @@ impl MessageDispatch<TestMessageFee> for TestMessageDispatch {
 		}
 	}
 
-	fn dispatch(_message: DispatchMessage<TestPayload, TestMessageFee>) {}
+	fn dispatch(_relayer_account: &AccountId, message: DispatchMessage<TestPayload, TestMessageFee>) -> Weight {
+		match message.data.payload.as_ref() {
+			Ok(payload) if *payload == PAYLOAD_WITH_UNSPENT_WEIGHT => payload.1 / 2,
+			_ => 0,
+		}
+	}
 }
 
 /// Return test lane message with given nonce and payload.
@@ -386,7 +481,7 @@ pub fn message(nonce: MessageNonce, payload: TestPayload) -> Message<TestMessage
 /// Return message data with valid fee for given payload.
 pub fn message_data(payload: TestPayload) -> MessageData<TestMessageFee> {
 	MessageData {
-		payload: payload.encode(),
+		payload: VersionedMessagePayload::encode_payload(&payload),
 		fee: 1,
 	}
 }
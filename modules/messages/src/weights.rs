@@ -67,6 +67,8 @@ pub trait WeightInfo {
 	fn receive_multiple_messages_proof_with_outbound_lane_state(i: u32) -> Weight;
 	fn receive_delivery_proof_for_multiple_messages_by_single_relayer(i: u32) -> Weight;
 	fn receive_delivery_proof_for_multiple_messages_by_multiple_relayers(i: u32) -> Weight;
+	fn receive_delivery_proof_with_extra_nodes(i: u32) -> Weight;
+	fn receive_delivery_proof_worst_case(i: u32) -> Weight;
 }
 
 /// Weights for pallet_bridge_messages using the Rialto node and recommended hardware.
@@ -177,6 +179,20 @@ impl<T: frame_system::Config> WeightInfo for RialtoWeight<T> {
 			.saturating_add(T::DbWeight::get().writes(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(i as Weight)))
 	}
+	fn receive_delivery_proof_with_extra_nodes(i: u32) -> Weight {
+		(124_512_000 as Weight)
+			.saturating_add((9_000 as Weight).saturating_mul(i as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn receive_delivery_proof_worst_case(i: u32) -> Weight {
+		(1_207_342_000 as Weight)
+			.saturating_add((59_914_000 as Weight).saturating_mul(i as Weight))
+			.saturating_add(T::DbWeight::get().reads(133 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(i as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(i as Weight)))
+	}
 }
 
 // For backwards compatibility and tests
@@ -286,4 +302,18 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
 			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(i as Weight)))
 	}
+	fn receive_delivery_proof_with_extra_nodes(i: u32) -> Weight {
+		(124_512_000 as Weight)
+			.saturating_add((9_000 as Weight).saturating_mul(i as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn receive_delivery_proof_worst_case(i: u32) -> Weight {
+		(1_207_342_000 as Weight)
+			.saturating_add((59_914_000 as Weight).saturating_mul(i as Weight))
+			.saturating_add(RocksDbWeight::get().reads(133 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(i as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(i as Weight)))
+	}
 }
@@ -25,6 +25,13 @@ use frame_support::weights::Weight;
 /// Size of the message being delivered in benchmarks.
 pub const EXPECTED_DEFAULT_MESSAGE_LENGTH: u32 = 128;
 
+/// Expected average size of the trie node in storage proofs.
+///
+/// Every traversed trie node needs to be looked up, hashed and decoded, so the cost of storage
+/// proof verification depends on the number of traversed nodes rather than on the proof size.
+/// Branch nodes may be up to ~530 bytes long, but most of nodes are much shorter.
+pub const EXPECTED_TRIE_NODE_SIZE: u32 = 128;
+
 /// We assume that size of signed extensions on all our chains and size of all 'small' arguments of calls
 /// we're checking here would fit 1KB.
 const SIGNED_EXTENSIONS_SIZE: u32 = 1024;
@@ -187,9 +194,7 @@ pub trait WeightInfoExt: WeightInfo {
 		let messages_dispatch_weight = dispatch_weight;
 
 		// proof size overhead weight
-		let expected_proof_size = EXPECTED_DEFAULT_MESSAGE_LENGTH
-			.saturating_mul(messages_count.saturating_sub(1))
-			.saturating_add(Self::expected_extra_storage_proof_size());
+		let expected_proof_size = Self::expected_messages_proof_size(messages_count);
 		let actual_proof_size = proof.size_hint();
 		let proof_size_overhead =
 			Self::storage_proof_size_overhead(actual_proof_size.saturating_sub(expected_proof_size));
@@ -221,8 +226,39 @@ pub trait WeightInfoExt: WeightInfo {
 			.saturating_add(proof_size_overhead)
 	}
 
+	/// Weight that is refunded to the submitter of message delivery extrinsic, if its storage proof
+	/// has been verified by traversing given number of trie nodes.
+	fn receive_messages_proof_traversed_nodes_refund(
+		proof_size: u32,
+		messages_count: u32,
+		traversed_nodes: u32,
+	) -> Weight {
+		Self::storage_proof_traversed_nodes_refund(
+			Self::expected_messages_proof_size(messages_count),
+			proof_size,
+			traversed_nodes,
+		)
+	}
+
+	/// Weight that is refunded to the submitter of confirmation delivery extrinsic, if its storage
+	/// proof has been verified by traversing given number of trie nodes.
+	fn receive_messages_delivery_proof_traversed_nodes_refund(proof_size: u32, traversed_nodes: u32) -> Weight {
+		Self::storage_proof_traversed_nodes_refund(
+			Self::expected_extra_storage_proof_size(),
+			proof_size,
+			traversed_nodes,
+		)
+	}
+
 	// Functions that are used by extrinsics weights formulas.
 
+	/// Returns size of messages proof that is already included in the messages delivery weight.
+	fn expected_messages_proof_size(messages_count: u32) -> u32 {
+		EXPECTED_DEFAULT_MESSAGE_LENGTH
+			.saturating_mul(messages_count.saturating_sub(1))
+			.saturating_add(Self::expected_extra_storage_proof_size())
+	}
+
 	/// Returns weight of message send transaction (`send_message`).
 	fn send_message_overhead() -> Weight {
 		Self::send_minimal_message_worst_case()
@@ -304,6 +340,24 @@ pub trait WeightInfoExt: WeightInfo {
 			(Self::receive_single_message_proof_16_kb() - Self::receive_single_message_proof_1_kb()) / (15 * 1024);
 		proof_size_in_bytes * byte_weight
 	}
+
+	/// Returns weight that is refunded when storage proof of given size has been verified by traversing
+	/// given number of trie nodes (either in `receive_messages_proof` or `receive_messages_delivery_proof`).
+	///
+	/// The declared weight of the proof size overhead assumes that the verification cost is proportional
+	/// to the proof size. Proofs with unused nodes are rejected, so the actual cost is defined by the
+	/// number of traversed nodes. If the proof is made of few large nodes, the difference is refunded.
+	fn storage_proof_traversed_nodes_refund(
+		expected_proof_size: u32,
+		actual_proof_size: u32,
+		traversed_nodes: u32,
+	) -> Weight {
+		let declared_overhead_size = actual_proof_size.saturating_sub(expected_proof_size);
+		let traversed_overhead_size = traversed_nodes
+			.saturating_mul(EXPECTED_TRIE_NODE_SIZE)
+			.saturating_sub(expected_proof_size);
+		Self::storage_proof_size_overhead(declared_overhead_size.saturating_sub(traversed_overhead_size))
+	}
 }
 
 impl WeightInfoExt for () {
@@ -317,3 +371,29 @@ impl<T: frame_system::Config> WeightInfoExt for crate::weights::RialtoWeight<T>
 		bp_rialto::EXTRA_STORAGE_PROOF_SIZE
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn storage_proof_traversed_nodes_refund_works() {
+		// proof of expected size => nothing to refund
+		assert_eq!(
+			<() as WeightInfoExt>::storage_proof_traversed_nodes_refund(1024, 1024, 1),
+			0
+		);
+
+		// large proof made of few large nodes => part of proof size overhead is refunded
+		assert_eq!(
+			<() as WeightInfoExt>::storage_proof_traversed_nodes_refund(100, 100 + 4096, 4),
+			<() as WeightInfoExt>::storage_proof_size_overhead(4096 - (4 * EXPECTED_TRIE_NODE_SIZE - 100)),
+		);
+
+		// large proof made of many small nodes => nothing to refund
+		assert_eq!(
+			<() as WeightInfoExt>::storage_proof_traversed_nodes_refund(100, 100 + 4096, 1000),
+			0,
+		);
+	}
+}
@@ -0,0 +1,777 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parachains Bridge Pallet
+//!
+//! This pallet tracks finalized heads of the bridged relay chain parachains. Heads are read from
+//! the `paras` pallet storage of the relay chain, using storage proofs that are generated at relay
+//! chain blocks, finalized by the linked `pallet-bridge-grandpa` instance.
+//!
+//! Imported heads may be used to verify storage proofs of the parachain state (see
+//! `Pallet::parse_finalized_storage_proof`) and are exposed via the `HeaderChain` interface (see
+//! `ParachainHeaders`), so messages pallets may target the parachain, not the relay chain itself.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Runtime-generated enums
+#![allow(clippy::large_enum_variant)]
+
+use crate::weights::WeightInfo;
+
+use bp_header_chain::{AuthoritySet, HeaderChain};
+use bp_parachains::{parachain_head_storage_key_at_source, BestParaHead, ParaHash, ParaHead, ParaHeadsProof, ParaId};
+use bp_runtime::{Chain, HasherOf, HeaderOf};
+use codec::Decode;
+use frame_support::traits::Get;
+use frame_system::{ensure_signed, RawOrigin};
+use sp_runtime::traits::{BadOrigin, Header as HeaderT};
+use sp_std::{convert::TryInto, marker::PhantomData, prelude::*};
+
+#[cfg(test)]
+mod mock;
+
+/// Pallet containing weights for this pallet.
+pub mod weights;
+
+// Re-export in crate namespace for `construct_runtime!`
+pub use pallet::*;
+
+/// Instance of the bridge GRANDPA pallet, that is tracking the relay chain.
+pub type GrandpaPalletInstanceOf<T, I> = <T as Config<I>>::BridgesGrandpaPalletInstance;
+/// Block number of the bridged relay chain.
+pub type RelayBlockNumber<T, I> = pallet_bridge_grandpa::BridgedBlockNumber<T, GrandpaPalletInstanceOf<T, I>>;
+/// Block hash of the bridged relay chain.
+pub type RelayBlockHash<T, I> = pallet_bridge_grandpa::BridgedBlockHash<T, GrandpaPalletInstanceOf<T, I>>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config<I: 'static = ()>: pallet_bridge_grandpa::Config<Self::BridgesGrandpaPalletInstance> {
+		/// Instance of the bridge GRANDPA pallet, that this pallet is linked to.
+		///
+		/// The GRANDPA pallet instance must be configured to import headers of the relay chain, that
+		/// we're bridging with.
+		type BridgesGrandpaPalletInstance: 'static;
+
+		/// Name of the `paras` pallet in the `construct_runtime!()` call at the bridged relay chain.
+		#[pallet::constant]
+		type ParasPalletName: Get<&'static str>;
+
+		/// Maximal number of finalized heads of the single parachain to keep in the storage.
+		///
+		/// The setting is there to prevent growing the on-chain state indefinitely. Note
+		/// the setting does not relate to block numbers - we will simply keep as much items
+		/// in the storage, so it doesn't guarantee any fixed timeframe for finality headers.
+		#[pallet::constant]
+		type HeadsToKeep: Get<u32>;
+
+		/// Origin that has the same rights as the pallet owner.
+		///
+		/// It may be used to let multisig or collective manage the pallet. Root and the `PalletOwner`
+		/// account are always allowed to manage the pallet.
+		type OwnerOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Weights gathered through benchmarking.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
+
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {}
+
+	#[pallet::call]
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
+		/// Submit proof of parachain heads, read from the `paras` pallet storage at the finalized
+		/// relay chain block.
+		///
+		/// The relay chain block must be imported by the linked GRANDPA pallet instance. Heads of
+		/// parachains that are missing from the relay chain storage are ignored. Heads, which are
+		/// older than the best known heads of the same parachains, are also ignored.
+		#[pallet::weight(T::WeightInfo::submit_parachain_heads(
+			parachains.len().try_into().unwrap_or(u32::MAX),
+		))]
+		pub fn submit_parachain_heads(
+			origin: OriginFor<T>,
+			relay_block_hash: RelayBlockHash<T, I>,
+			parachains: Vec<ParaId>,
+			parachain_heads_proof: ParaHeadsProof,
+		) -> DispatchResultWithPostInfo {
+			ensure_operational::<T, I>()?;
+			let _ = ensure_signed(origin)?;
+
+			// we'll need the relay chain block number to reject outdated heads
+			let relay_block =
+				pallet_bridge_grandpa::Pallet::<T, T::BridgesGrandpaPalletInstance>::finalized_header(relay_block_hash)
+					.ok_or(Error::<T, I>::UnknownRelayChainBlock)?;
+			let relay_block_number = *relay_block.number();
+
+			// read all heads first, so that the storage isn't changed if the proof is invalid
+			let parachain_heads =
+				pallet_bridge_grandpa::Pallet::<T, T::BridgesGrandpaPalletInstance>::parse_finalized_storage_proof(
+					relay_block_hash,
+					sp_trie::StorageProof::new(parachain_heads_proof.0),
+					move |storage| {
+						let mut parachain_heads = Vec::with_capacity(parachains.len());
+						for parachain in parachains {
+							let storage_key =
+								parachain_head_storage_key_at_source(T::ParasPalletName::get(), parachain);
+							let parachain_head = match storage.read_value(storage_key.0.as_ref()) {
+								Ok(Some(raw_parachain_head)) => ParaHead::decode(&mut &raw_parachain_head[..]).ok(),
+								Ok(None) | Err(_) => None,
+							};
+							match parachain_head {
+								Some(parachain_head) => parachain_heads.push((parachain, parachain_head)),
+								None => log::trace!(
+									target: "runtime::bridge-parachains",
+									"The head of parachain {:?} is missing from the proof of relay chain block {:?}",
+									parachain,
+									relay_block_hash,
+								),
+							}
+						}
+
+						// reject proofs that are padded with unrelated nodes
+						storage
+							.ensure_no_unused_nodes()
+							.map(|_| parachain_heads)
+							.map_err(|_| Error::<T, I>::InvalidStorageProof)
+					},
+				)
+				.map_err(|_| Error::<T, I>::InvalidStorageProof)??;
+
+			for (parachain, parachain_head) in parachain_heads {
+				update_parachain_head::<T, I>(parachain, relay_block_number, parachain_head);
+			}
+
+			Ok(().into())
+		}
+
+		/// Change `PalletOwner`.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational))]
+		pub fn set_owner(origin: OriginFor<T>, new_owner: Option<T::AccountId>) -> DispatchResultWithPostInfo {
+			ensure_owner_or_root::<T, I>(origin)?;
+			match new_owner {
+				Some(new_owner) => {
+					PalletOwner::<T, I>::put(&new_owner);
+					log::info!(target: "runtime::bridge-parachains", "Setting pallet Owner to: {:?}", new_owner);
+				}
+				None => {
+					PalletOwner::<T, I>::kill();
+					log::info!(target: "runtime::bridge-parachains", "Removed Owner of pallet.");
+				}
+			}
+
+			Ok(().into())
+		}
+
+		/// Halt or resume all pallet operations.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational))]
+		pub fn set_operational(origin: OriginFor<T>, operational: bool) -> DispatchResultWithPostInfo {
+			ensure_owner_or_root::<T, I>(origin)?;
+			<IsHalted<T, I>>::put(!operational);
+
+			if operational {
+				log::info!(target: "runtime::bridge-parachains", "Resuming pallet operations.");
+			} else {
+				log::warn!(target: "runtime::bridge-parachains", "Stopping pallet operations.");
+			}
+
+			Ok(().into())
+		}
+	}
+
+	/// Best known heads of parachains.
+	#[pallet::storage]
+	pub(super) type BestParaHeads<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, ParaId, BestParaHead<RelayBlockNumber<T, I>>>;
+
+	/// Parachain heads which have been imported into the pallet.
+	#[pallet::storage]
+	pub(super) type ImportedParaHeads<T: Config<I>, I: 'static = ()> =
+		StorageDoubleMap<_, Blake2_128Concat, ParaId, Blake2_128Concat, ParaHash, ParaHead>;
+
+	/// A ring buffer of imported parachain head hashes. Ordered by the insertion time.
+	#[pallet::storage]
+	pub(super) type ImportedParaHashes<T: Config<I>, I: 'static = ()> =
+		StorageDoubleMap<_, Blake2_128Concat, ParaId, Twox64Concat, u32, ParaHash>;
+
+	/// Optional pallet owner.
+	///
+	/// Pallet owner has a right to halt all pallet operations and then resume it. If it is
+	/// `None`, then there are no direct ways to halt/resume pallet operations, but other
+	/// runtime methods may still be used to do that (i.e. democracy::referendum to update halt
+	/// flag directly or call the `halt_operations`).
+	#[pallet::storage]
+	pub(super) type PalletOwner<T: Config<I>, I: 'static = ()> = StorageValue<_, T::AccountId, OptionQuery>;
+
+	/// If true, all pallet transactions are failed immediately.
+	#[pallet::storage]
+	pub(super) type IsHalted<T: Config<I>, I: 'static = ()> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
+		/// Optional module owner account.
+		pub owner: Option<T::AccountId>,
+		/// Should the pallet block transaction immediately after genesis.
+		pub is_halted: bool,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config<I>, I: 'static> Default for GenesisConfig<T, I> {
+		fn default() -> Self {
+			Self {
+				owner: None,
+				is_halted: false,
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config<I>, I: 'static> GenesisBuild<T, I> for GenesisConfig<T, I> {
+		fn build(&self) {
+			if let Some(ref owner) = self.owner {
+				<PalletOwner<T, I>>::put(owner);
+			}
+
+			<IsHalted<T, I>>::put(self.is_halted);
+		}
+	}
+
+	#[pallet::error]
+	pub enum Error<T, I = ()> {
+		/// The relay chain block is unknown to the linked GRANDPA pallet.
+		UnknownRelayChainBlock,
+		/// The storage proof is invalid for the relay chain block or contains unused nodes.
+		InvalidStorageProof,
+		/// The parachain head is unknown to the pallet.
+		UnknownParaHead,
+		/// The parachain head can't be decoded as the parachain header.
+		FailedToDecodeParaHead,
+		/// All pallet operations are halted.
+		Halted,
+	}
+
+	/// Update the best head of the parachain, if the new head is better than the known one.
+	///
+	/// Note this function solely takes care of updating the storage and pruning old entries,
+	/// but does not verify the validity of such import.
+	pub(crate) fn update_parachain_head<T: Config<I>, I: 'static>(
+		parachain: ParaId,
+		relay_block_number: RelayBlockNumber<T, I>,
+		parachain_head: ParaHead,
+	) {
+		let head_hash = parachain_head.hash();
+		BestParaHeads::<T, I>::mutate(parachain, |stored_best_head| {
+			let next_imported_hash_position = match stored_best_head {
+				Some(best_head) if best_head.at_relay_block_number >= relay_block_number => {
+					log::trace!(
+						target: "runtime::bridge-parachains",
+						"The head of parachain {:?} is ignored, because it is read from the relay block {:?}. Best \
+						head is read from the relay block {:?}",
+						parachain,
+						relay_block_number,
+						best_head.at_relay_block_number,
+					);
+					return;
+				}
+				Some(best_head) if best_head.head_hash == head_hash => {
+					// the parachain hasn't produced new blocks since we've seen it last time
+					best_head.at_relay_block_number = relay_block_number;
+					return;
+				}
+				Some(best_head) => best_head.next_imported_hash_position,
+				None => 0,
+			};
+
+			let head_hash_to_prune = ImportedParaHashes::<T, I>::try_get(parachain, next_imported_hash_position);
+			ImportedParaHeads::<T, I>::insert(parachain, head_hash, parachain_head);
+			ImportedParaHashes::<T, I>::insert(parachain, next_imported_hash_position, head_hash);
+			*stored_best_head = Some(BestParaHead {
+				at_relay_block_number: relay_block_number,
+				head_hash,
+				next_imported_hash_position: (next_imported_hash_position + 1) % T::HeadsToKeep::get(),
+			});
+
+			log::trace!(
+				target: "runtime::bridge-parachains",
+				"Updated head of parachain {:?} to {:?}",
+				parachain,
+				head_hash,
+			);
+
+			if let Ok(head_hash_to_prune) = head_hash_to_prune {
+				if head_hash_to_prune != head_hash {
+					log::debug!(
+						target: "runtime::bridge-parachains",
+						"Pruning old head of parachain {:?}: {:?}.",
+						parachain,
+						head_hash_to_prune,
+					);
+					ImportedParaHeads::<T, I>::remove(parachain, head_hash_to_prune);
+				}
+			}
+		});
+	}
+
+	/// Ensure that the origin is either root, or `PalletOwner`.
+	fn ensure_owner_or_root<T: Config<I>, I: 'static>(origin: T::Origin) -> Result<(), BadOrigin> {
+		let origin = match T::OwnerOrigin::try_origin(origin) {
+			Ok(_) => return Ok(()),
+			Err(origin) => origin,
+		};
+
+		match origin.into() {
+			Ok(RawOrigin::Root) => Ok(()),
+			Ok(RawOrigin::Signed(ref signer)) if Some(signer) == <PalletOwner<T, I>>::get().as_ref() => Ok(()),
+			_ => Err(BadOrigin),
+		}
+	}
+
+	/// Ensure that the pallet is in operational mode (not halted).
+	fn ensure_operational<T: Config<I>, I: 'static>() -> Result<(), Error<T, I>> {
+		if <IsHalted<T, I>>::get() {
+			Err(<Error<T, I>>::Halted)
+		} else {
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Get the best known head of the parachain.
+	pub fn best_parachain_head(parachain: ParaId) -> Option<ParaHead> {
+		let best_para_head = BestParaHeads::<T, I>::get(parachain)?;
+		ImportedParaHeads::<T, I>::get(parachain, best_para_head.head_hash)
+	}
+
+	/// Get the parachain head with given hash, if it is known to the pallet.
+	pub fn parachain_head(parachain: ParaId, hash: ParaHash) -> Option<ParaHead> {
+		ImportedParaHeads::<T, I>::get(parachain, hash)
+	}
+
+	/// Verify that the passed storage proof is valid, given it is crafted using known finalized
+	/// head of the parachain. The parachain head is decoded as the header of the chain `C`.
+	///
+	/// If the proof is valid, then the `parse` callback is called and the function returns its
+	/// result. After reading all required values, the `parse` callback should ensure that the
+	/// proof has no unused nodes.
+	pub fn parse_finalized_storage_proof<C: Chain, R>(
+		parachain: ParaId,
+		hash: ParaHash,
+		storage_proof: sp_trie::StorageProof,
+		parse: impl FnOnce(bp_runtime::StorageProofChecker<HasherOf<C>>) -> R,
+	) -> Result<R, sp_runtime::DispatchError> {
+		let parachain_head = Self::parachain_head(parachain, hash).ok_or(Error::<T, I>::UnknownParaHead)?;
+		let parachain_header =
+			HeaderOf::<C>::decode(&mut &parachain_head.0[..]).map_err(|_| Error::<T, I>::FailedToDecodeParaHead)?;
+		let storage_proof_checker = bp_runtime::StorageProofChecker::new(*parachain_header.state_root(), storage_proof)
+			.map_err(|_| Error::<T, I>::InvalidStorageProof)?;
+
+		Ok(parse(storage_proof_checker))
+	}
+}
+
+/// Finalized heads of the single parachain, exposed via the `HeaderChain` interface.
+///
+/// Parachain blocks are finalized by the relay chain, so there's no parachain authority set. Heads
+/// may only be imported using the `submit_parachain_heads` call, so `append_header` always fails.
+pub struct ParachainHeaders<T, I, C, Para>(PhantomData<(T, I, C, Para)>);
+
+impl<T, I, C, Para> HeaderChain<HeaderOf<C>, &'static str> for ParachainHeaders<T, I, C, Para>
+where
+	T: Config<I>,
+	I: 'static,
+	C: Chain,
+	Para: Get<ParaId>,
+{
+	fn best_finalized() -> HeaderOf<C> {
+		Pallet::<T, I>::best_parachain_head(Para::get())
+			.and_then(|parachain_head| HeaderOf::<C>::decode(&mut &parachain_head.0[..]).ok())
+			.unwrap_or_else(|| {
+				HeaderOf::<C>::new(
+					Default::default(),
+					Default::default(),
+					Default::default(),
+					Default::default(),
+					Default::default(),
+				)
+			})
+	}
+
+	fn authority_set() -> AuthoritySet {
+		AuthoritySet::default()
+	}
+
+	fn append_header(_header: HeaderOf<C>) -> Result<(), &'static str> {
+		Err("Parachain heads may only be imported using relay chain storage proofs")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{
+		prove_storage, run_test, Origin, RelayBlockHeader, TestBridgedChain, TestNumber, TestRuntime,
+		TEST_OWNER_ORIGIN_ACCOUNT,
+	};
+	use bp_header_chain::{InitializationData, OperatingMode};
+	use bp_test_utils::{authority_list, make_default_justification};
+	use codec::Encode;
+	use frame_support::{assert_noop, assert_ok};
+	use sp_core::H256;
+	use sp_trie::StorageProof;
+
+	type RelayChainGrandpa = pallet_bridge_grandpa::Pallet<TestRuntime>;
+
+	frame_support::parameter_types! {
+		pub const FirstParachain: ParaId = ParaId(1);
+	}
+
+	fn parachain_header(parachain: u32, head_number: TestNumber, state_root: H256) -> RelayBlockHeader {
+		RelayBlockHeader::new(
+			head_number,
+			Default::default(),
+			state_root,
+			H256::from_low_u64_be(parachain as u64),
+			Default::default(),
+		)
+	}
+
+	fn head_data(parachain: u32, head_number: TestNumber) -> ParaHead {
+		ParaHead(parachain_header(parachain, head_number, Default::default()).encode())
+	}
+
+	fn prepare_parachain_heads_proof(heads: Vec<(u32, ParaHead)>) -> (H256, ParaHeadsProof) {
+		let (state_root, proof) = prove_storage(
+			heads
+				.into_iter()
+				.map(|(parachain, head)| {
+					let storage_key = parachain_head_storage_key_at_source("Paras", ParaId(parachain));
+					(storage_key.0, head.encode())
+				})
+				.collect(),
+		);
+		(state_root, ParaHeadsProof(proof))
+	}
+
+	fn import_relay_chain_header(number: TestNumber, state_root: H256) -> H256 {
+		let header = RelayBlockHeader::new(
+			number,
+			Default::default(),
+			state_root,
+			Default::default(),
+			Default::default(),
+		);
+		let hash = header.hash();
+		if number == 0 {
+			assert_ok!(RelayChainGrandpa::initialize(
+				Origin::root(),
+				InitializationData {
+					header,
+					authority_list: authority_list(),
+					set_id: 1,
+					operating_mode: OperatingMode::Normal,
+				},
+			));
+		} else {
+			let justification = make_default_justification(&header);
+			assert_ok!(RelayChainGrandpa::submit_finality_proof(
+				Origin::signed(1),
+				header,
+				justification
+			));
+		}
+		hash
+	}
+
+	fn submit_parachain_heads(
+		relay_block_hash: H256,
+		parachains: Vec<u32>,
+		proof: ParaHeadsProof,
+	) -> frame_support::dispatch::DispatchResultWithPostInfo {
+		Pallet::<TestRuntime>::submit_parachain_heads(
+			Origin::signed(1),
+			relay_block_hash,
+			parachains.into_iter().map(ParaId).collect(),
+			proof,
+		)
+	}
+
+	#[test]
+	fn imports_initial_parachain_heads() {
+		run_test(|| {
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 0)), (2, head_data(2, 0))]);
+			let relay_block_hash = import_relay_chain_header(0, state_root);
+
+			// parachain 3 is missing from the relay chain storage => it is ignored
+			assert_ok!(submit_parachain_heads(relay_block_hash, vec![1, 2, 3], proof));
+
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(BestParaHead {
+					at_relay_block_number: 0,
+					head_hash: head_data(1, 0).hash(),
+					next_imported_hash_position: 1,
+				})
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::best_parachain_head(ParaId(1)),
+				Some(head_data(1, 0))
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::best_parachain_head(ParaId(2)),
+				Some(head_data(2, 0))
+			);
+			assert_eq!(BestParaHeads::<TestRuntime>::get(ParaId(3)), None);
+		});
+	}
+
+	#[test]
+	fn imports_parachain_heads_is_able_to_progress() {
+		run_test(|| {
+			let (state_root_5, proof_5) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+			let (state_root_10, proof_10) = prepare_parachain_heads_proof(vec![(1, head_data(1, 10))]);
+
+			let relay_block_hash_0 = import_relay_chain_header(0, state_root_5);
+			assert_ok!(submit_parachain_heads(relay_block_hash_0, vec![1], proof_5));
+
+			let relay_block_hash_1 = import_relay_chain_header(1, state_root_10);
+			assert_ok!(submit_parachain_heads(relay_block_hash_1, vec![1], proof_10));
+
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(BestParaHead {
+					at_relay_block_number: 1,
+					head_hash: head_data(1, 10).hash(),
+					next_imported_hash_position: 0,
+				})
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::parachain_head(ParaId(1), head_data(1, 5).hash()),
+				Some(head_data(1, 5))
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::parachain_head(ParaId(1), head_data(1, 10).hash()),
+				Some(head_data(1, 10))
+			);
+		});
+	}
+
+	#[test]
+	fn ignores_parachain_head_from_older_relay_chain_block() {
+		run_test(|| {
+			let (state_root_5, proof_5) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+			let (state_root_10, proof_10) = prepare_parachain_heads_proof(vec![(1, head_data(1, 10))]);
+
+			let relay_block_hash_0 = import_relay_chain_header(0, state_root_5);
+			let relay_block_hash_1 = import_relay_chain_header(1, state_root_10);
+			assert_ok!(submit_parachain_heads(relay_block_hash_1, vec![1], proof_10));
+			assert_ok!(submit_parachain_heads(relay_block_hash_0, vec![1], proof_5));
+
+			assert_eq!(
+				Pallet::<TestRuntime>::best_parachain_head(ParaId(1)),
+				Some(head_data(1, 10))
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::parachain_head(ParaId(1), head_data(1, 5).hash()),
+				None
+			);
+		});
+	}
+
+	#[test]
+	fn only_updates_relay_block_number_if_parachain_head_is_unchanged() {
+		run_test(|| {
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+
+			let relay_block_hash_0 = import_relay_chain_header(0, state_root);
+			let relay_block_hash_1 = import_relay_chain_header(1, state_root);
+			assert_ok!(submit_parachain_heads(relay_block_hash_0, vec![1], proof.clone()));
+			assert_ok!(submit_parachain_heads(relay_block_hash_1, vec![1], proof));
+
+			assert_eq!(
+				BestParaHeads::<TestRuntime>::get(ParaId(1)),
+				Some(BestParaHead {
+					at_relay_block_number: 1,
+					head_hash: head_data(1, 5).hash(),
+					next_imported_hash_position: 1,
+				})
+			);
+		});
+	}
+
+	#[test]
+	fn prunes_old_parachain_heads() {
+		run_test(|| {
+			let heads_to_keep = <TestRuntime as Config>::HeadsToKeep::get();
+
+			// import exactly `HeadsToKeep` heads
+			for i in 0..heads_to_keep {
+				let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, i as _))]);
+				let relay_block_hash = import_relay_chain_header(i as _, state_root);
+				assert_ok!(submit_parachain_heads(relay_block_hash, vec![1], proof));
+			}
+
+			// all heads are in the storage
+			for i in 0..heads_to_keep {
+				assert!(ImportedParaHeads::<TestRuntime>::contains_key(
+					ParaId(1),
+					head_data(1, i as _).hash()
+				));
+			}
+
+			// import next head
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, heads_to_keep as _))]);
+			let relay_block_hash = import_relay_chain_header(heads_to_keep as _, state_root);
+			assert_ok!(submit_parachain_heads(relay_block_hash, vec![1], proof));
+
+			// the oldest head has been pruned
+			assert!(!ImportedParaHeads::<TestRuntime>::contains_key(
+				ParaId(1),
+				head_data(1, 0).hash()
+			));
+			for i in 1..=heads_to_keep {
+				assert!(ImportedParaHeads::<TestRuntime>::contains_key(
+					ParaId(1),
+					head_data(1, i as _).hash()
+				));
+			}
+		});
+	}
+
+	#[test]
+	fn rejects_heads_from_unknown_relay_chain_block() {
+		run_test(|| {
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 0))]);
+			import_relay_chain_header(0, state_root);
+
+			assert_noop!(
+				submit_parachain_heads(H256::repeat_byte(42), vec![1], proof),
+				Error::<TestRuntime>::UnknownRelayChainBlock
+			);
+		});
+	}
+
+	#[test]
+	fn rejects_invalid_storage_proof() {
+		run_test(|| {
+			let (state_root, _) = prepare_parachain_heads_proof(vec![(1, head_data(1, 0))]);
+			let (_, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+			let relay_block_hash = import_relay_chain_header(0, state_root);
+
+			assert_noop!(
+				submit_parachain_heads(relay_block_hash, vec![1], proof),
+				Error::<TestRuntime>::InvalidStorageProof
+			);
+		});
+	}
+
+	#[test]
+	fn rejects_storage_proof_with_unused_nodes() {
+		run_test(|| {
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 0)), (2, head_data(2, 0))]);
+			let relay_block_hash = import_relay_chain_header(0, state_root);
+
+			assert_noop!(
+				submit_parachain_heads(relay_block_hash, vec![1], proof),
+				Error::<TestRuntime>::InvalidStorageProof
+			);
+		});
+	}
+
+	#[test]
+	fn rejects_heads_if_pallet_is_halted() {
+		run_test(|| {
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 0))]);
+			let relay_block_hash = import_relay_chain_header(0, state_root);
+
+			assert_ok!(Pallet::<TestRuntime>::set_operational(
+				Origin::signed(TEST_OWNER_ORIGIN_ACCOUNT),
+				false
+			));
+			assert_noop!(
+				submit_parachain_heads(relay_block_hash, vec![1], proof.clone()),
+				Error::<TestRuntime>::Halted
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::set_operational(Origin::root(), true));
+			assert_ok!(submit_parachain_heads(relay_block_hash, vec![1], proof));
+		});
+	}
+
+	#[test]
+	fn parse_finalized_storage_proof_works() {
+		run_test(|| {
+			let (parachain_state_root, parachain_storage_proof) =
+				prove_storage(vec![(b"key".to_vec(), b"value".to_vec())]);
+			let parachain_head = ParaHead(parachain_header(1, 0, parachain_state_root).encode());
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, parachain_head.clone())]);
+			let relay_block_hash = import_relay_chain_header(0, state_root);
+			assert_ok!(submit_parachain_heads(relay_block_hash, vec![1], proof));
+
+			assert_eq!(
+				Pallet::<TestRuntime>::parse_finalized_storage_proof::<TestBridgedChain, _>(
+					ParaId(1),
+					parachain_head.hash(),
+					StorageProof::new(parachain_storage_proof.clone()),
+					|storage| storage.read_value(b"key").map_err(drop),
+				),
+				Ok(Ok(Some(b"value".to_vec()))),
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::parse_finalized_storage_proof::<TestBridgedChain, _>(
+					ParaId(2),
+					parachain_head.hash(),
+					StorageProof::new(parachain_storage_proof),
+					|_| (),
+				),
+				Error::<TestRuntime>::UnknownParaHead
+			);
+		});
+	}
+
+	#[test]
+	fn parachain_headers_are_exposed_via_header_chain_interface() {
+		type FirstParachainHeaders = ParachainHeaders<TestRuntime, (), TestBridgedChain, FirstParachain>;
+
+		run_test(|| {
+			assert_eq!(
+				FirstParachainHeaders::best_finalized(),
+				parachain_header(0, 0, Default::default())
+			);
+
+			let (state_root, proof) = prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+			let relay_block_hash = import_relay_chain_header(0, state_root);
+			assert_ok!(submit_parachain_heads(relay_block_hash, vec![1], proof));
+
+			assert_eq!(
+				FirstParachainHeaders::best_finalized(),
+				parachain_header(1, 5, Default::default())
+			);
+			assert_eq!(FirstParachainHeaders::authority_set(), AuthoritySet::default());
+			assert!(FirstParachainHeaders::append_header(parachain_header(1, 6, Default::default())).is_err());
+		});
+	}
+}
@@ -0,0 +1,152 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+// From construct_runtime macro
+#![allow(clippy::from_over_into)]
+
+use bp_runtime::Chain;
+use frame_support::{construct_runtime, ord_parameter_types, parameter_types, weights::Weight};
+use sp_runtime::{
+	testing::{Header, H256},
+	traits::{BlakeTwo256, IdentityLookup},
+	Perbill,
+};
+use sp_trie::{record_all_keys, trie_types::TrieDBMut, Layout, MemoryDB, Recorder, TrieMut};
+
+pub type AccountId = u64;
+pub type TestNumber = u64;
+pub type RelayBlockHeader = Header;
+
+type Block = frame_system::mocking::MockBlock<TestRuntime>;
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
+
+use crate as pallet_bridge_parachains;
+
+construct_runtime! {
+	pub enum TestRuntime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Grandpa: pallet_bridge_grandpa::{Pallet},
+		Parachains: pallet_bridge_parachains::{Pallet},
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+impl frame_system::Config for TestRuntime {
+	type Origin = Origin;
+	type Index = u64;
+	type Call = Call;
+	type BlockNumber = TestNumber;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type BaseCallFilter = ();
+	type SystemWeightInfo = ();
+	type DbWeight = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const MaxRequests: u32 = 16;
+	pub const HeadersToKeep: u32 = 5;
+	pub const MaxGuardedHeaders: u32 = 5;
+}
+
+impl pallet_bridge_grandpa::Config for TestRuntime {
+	type BridgedChain = TestBridgedChain;
+	type MaxRequests = MaxRequests;
+	type HeadersToKeep = HeadersToKeep;
+	type HeadersPruningGuard = ();
+	type MaxGuardedHeaders = MaxGuardedHeaders;
+	type OwnerOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const ParasPalletName: &'static str = "Paras";
+	pub const HeadsToKeep: u32 = 2;
+}
+
+/// Account that is accepted by the `OwnerOrigin`.
+pub const TEST_OWNER_ORIGIN_ACCOUNT: AccountId = 300;
+
+ord_parameter_types! {
+	pub const OwnerOriginAccount: AccountId = TEST_OWNER_ORIGIN_ACCOUNT;
+}
+
+impl pallet_bridge_parachains::Config for TestRuntime {
+	type BridgesGrandpaPalletInstance = ();
+	type ParasPalletName = ParasPalletName;
+	type HeadsToKeep = HeadsToKeep;
+	type OwnerOrigin = frame_system::EnsureSignedBy<OwnerOriginAccount, AccountId>;
+	type WeightInfo = ();
+}
+
+/// Bridged chain. In tests the same chain type is used for both relay chain and parachains.
+#[derive(Debug)]
+pub struct TestBridgedChain;
+
+impl Chain for TestBridgedChain {
+	type BlockNumber = <TestRuntime as frame_system::Config>::BlockNumber;
+	type Hash = <TestRuntime as frame_system::Config>::Hash;
+	type Hasher = <TestRuntime as frame_system::Config>::Hashing;
+	type Header = <TestRuntime as frame_system::Config>::Header;
+}
+
+pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
+	sp_io::TestExternalities::new(Default::default()).execute_with(test)
+}
+
+/// Build storage trie with given key-value pairs and return its root and proof of all its values.
+pub fn prove_storage(storage: Vec<(Vec<u8>, Vec<u8>)>) -> (H256, Vec<Vec<u8>>) {
+	let mut root = H256::default();
+	let mut mdb = MemoryDB::default();
+	{
+		let mut trie = TrieDBMut::<BlakeTwo256>::new(&mut mdb, &mut root);
+		for (key, value) in storage {
+			trie.insert(&key, &value).expect("TrieMut::insert should not fail");
+		}
+	}
+
+	let mut proof_recorder = Recorder::<H256>::new();
+	record_all_keys::<Layout<BlakeTwo256>, _>(&mdb, &root, &mut proof_recorder)
+		.expect("record_all_keys should not fail");
+	(
+		root,
+		proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect(),
+	)
+}
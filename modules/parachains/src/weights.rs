@@ -0,0 +1,58 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights for pallet_bridge_parachains.
+//!
+//! The pallet has no benchmarks yet, so these weights are estimated using weights of the
+//! `pallet_bridge_grandpa` and `pallet_bridge_messages` storage proof verification.
+
+#![allow(clippy::all)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_bridge_parachains.
+pub trait WeightInfo {
+	fn submit_parachain_heads(p: u32) -> Weight;
+}
+
+/// Weights for pallet_bridge_parachains using the Rialto node and recommended hardware.
+pub struct RialtoWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for RialtoWeight<T> {
+	fn submit_parachain_heads(p: u32) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((25_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(T::DbWeight::get().writes((4 as Weight).saturating_mul(p as Weight)))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn submit_parachain_heads(p: u32) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((25_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((4 as Weight).saturating_mul(p as Weight)))
+	}
+}
@@ -0,0 +1,234 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Primitives of the BEEFY bridge pallet.
+//!
+//! BEEFY validators are signing commitments, which payload is the root of the chain MMR. Every
+//! MMR leaf has a short representation of the next BEEFY validator set, so the light client
+//! may follow validator set handoffs, given MMR proof of the latest leaf.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{H160, H256};
+use sp_io::hashing::keccak_256;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+pub use mmr::{verify_mmr_leaf_proof, MmrLeafProof};
+
+pub mod mmr;
+
+/// Identifier of the BEEFY validator set.
+pub type ValidatorSetId = u64;
+
+/// Ethereum address of the BEEFY validator.
+///
+/// Validators are identified by their addresses, because MMR leaves are committing to the
+/// merkle root of addresses of the next validator set.
+pub type BeefyValidatorAddress = H160;
+
+/// Recoverable secp256k1 signature of the BEEFY validator.
+pub type BeefyValidatorSignature = sp_core::ecdsa::Signature;
+
+/// Root of the bridged chain MMR. It is the payload of BEEFY commitments.
+pub type MmrRootHash = H256;
+
+/// Commitment that is signed by BEEFY validators.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone)]
+pub struct Commitment<BlockNumber> {
+	/// Root of the bridged chain MMR at the `block_number`.
+	pub payload: MmrRootHash,
+	/// Number of the block the commitment is made for.
+	pub block_number: BlockNumber,
+	/// Identifier of the validator set that is signing the commitment.
+	pub validator_set_id: ValidatorSetId,
+}
+
+impl<BlockNumber: Encode> Commitment<BlockNumber> {
+	/// Return hash of the commitment, which is signed by validators.
+	pub fn hash(&self) -> H256 {
+		keccak_256(&self.encode()).into()
+	}
+}
+
+/// Commitment with validators signatures.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone)]
+pub struct SignedCommitment<BlockNumber> {
+	/// The commitment.
+	pub commitment: Commitment<BlockNumber>,
+	/// Signatures of validators, in the same order as validators in the set. Validators that have
+	/// not signed the commitment have `None` here.
+	pub signatures: Vec<Option<BeefyValidatorSignature>>,
+}
+
+/// BEEFY validator set.
+#[derive(Default, Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BeefyAuthoritySet {
+	/// Addresses of validators.
+	pub validators: Vec<BeefyValidatorAddress>,
+	/// Monotonic identifier of the validator set.
+	pub id: ValidatorSetId,
+}
+
+impl BeefyAuthoritySet {
+	/// Create new BEEFY validator set.
+	pub fn new(validators: Vec<BeefyValidatorAddress>, id: ValidatorSetId) -> Self {
+		Self { validators, id }
+	}
+
+	/// Return number of signatures that are required to accept the commitment, signed by this set.
+	///
+	/// It is more than 2/3 of validators.
+	pub fn signatures_required(&self) -> usize {
+		let validators = self.validators.len();
+		validators - validators.saturating_sub(1) / 3
+	}
+
+	/// Return short representation of this set, that is stored in MMR leaves.
+	pub fn to_next_authority_set(&self) -> BeefyNextAuthoritySet {
+		BeefyNextAuthoritySet {
+			id: self.id,
+			len: self.validators.len() as u32,
+			root: merkle_root(self.validators.iter()),
+		}
+	}
+}
+
+/// Short representation of the BEEFY validator set, that is stored in MMR leaves.
+#[derive(Default, Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BeefyNextAuthoritySet {
+	/// Identifier of the validator set.
+	pub id: ValidatorSetId,
+	/// Number of validators in the set.
+	pub len: u32,
+	/// Merkle root of validators addresses.
+	pub root: H256,
+}
+
+/// Leaf of the bridged chain MMR.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone)]
+pub struct BeefyMmrLeaf<BlockNumber, Hash> {
+	/// Version of the leaf format.
+	pub version: u8,
+	/// Number and hash of the parent of the block, which has added this leaf.
+	pub parent_number_and_hash: (BlockNumber, Hash),
+	/// Next BEEFY validator set.
+	pub beefy_next_authority_set: BeefyNextAuthoritySet,
+	/// Merkle root of heads of parachains, registered at the block.
+	pub parachain_heads: H256,
+}
+
+impl<BlockNumber: Encode, Hash: Encode> BeefyMmrLeaf<BlockNumber, Hash> {
+	/// Return hash of the leaf, that is stored in the MMR.
+	pub fn hash(&self) -> H256 {
+		keccak_256(&self.encode()).into()
+	}
+}
+
+/// Data required for initializing the BEEFY bridge pallet.
+#[derive(Default, Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct InitializationData<BlockNumber> {
+	/// Number of the block from which we should start syncing.
+	pub best_block_number: BlockNumber,
+	/// The current validator set.
+	pub authority_set: BeefyAuthoritySet,
+	/// The next validator set.
+	pub next_authority_set: BeefyNextAuthoritySet,
+	/// Should the pallet block transaction immediately after initialization.
+	pub is_halted: bool,
+}
+
+/// Recover address of the validator that has signed given message.
+///
+/// Returns `None` if the signature is invalid.
+pub fn recover_validator_address(signature: &BeefyValidatorSignature, message: &H256) -> Option<BeefyValidatorAddress> {
+	let public = sp_io::crypto::secp256k1_ecdsa_recover(&signature.0, message.as_fixed_bytes()).ok()?;
+	Some(H160::from_slice(&keccak_256(&public)[12..]))
+}
+
+/// Compute root of the binary merkle tree, built over given leaves.
+///
+/// Leaves are hashed with `keccak256` before they're inserted into the tree. If there's an odd
+/// number of nodes at some level, the last node is promoted to the next level.
+pub fn merkle_root<I, T>(leaves: I) -> H256
+where
+	I: IntoIterator<Item = T>,
+	T: AsRef<[u8]>,
+{
+	let mut nodes = leaves
+		.into_iter()
+		.map(|leaf| H256::from(keccak_256(leaf.as_ref())))
+		.collect::<Vec<_>>();
+	if nodes.is_empty() {
+		return H256::zero();
+	}
+
+	while nodes.len() > 1 {
+		nodes = nodes
+			.chunks(2)
+			.map(|pair| match pair {
+				[left, right] => merge_hashes(left, right),
+				_ => pair[0],
+			})
+			.collect();
+	}
+
+	nodes[0]
+}
+
+/// Compute hash of two concatenated hashes.
+pub(crate) fn merge_hashes(left: &H256, right: &H256) -> H256 {
+	let mut concat = [0u8; 64];
+	concat[..32].copy_from_slice(left.as_bytes());
+	concat[32..].copy_from_slice(right.as_bytes());
+	keccak_256(&concat).into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn signatures_required_is_more_than_two_thirds() {
+		let set_of = |len: usize| BeefyAuthoritySet::new(vec![H160::zero(); len], 0);
+		assert_eq!(set_of(1).signatures_required(), 1);
+		assert_eq!(set_of(3).signatures_required(), 3);
+		assert_eq!(set_of(4).signatures_required(), 3);
+		assert_eq!(set_of(100).signatures_required(), 67);
+	}
+
+	#[test]
+	fn merkle_root_works() {
+		let leaves = vec![[1u8; 20], [2u8; 20], [3u8; 20]];
+		let hashes = leaves
+			.iter()
+			.map(|leaf| H256::from(keccak_256(leaf)))
+			.collect::<Vec<_>>();
+
+		assert_eq!(merkle_root(Vec::<[u8; 20]>::new()), H256::zero());
+		assert_eq!(merkle_root(&leaves[..1]), hashes[0]);
+		assert_eq!(
+			merkle_root(&leaves),
+			merge_hashes(&merge_hashes(&hashes[0], &hashes[1]), &hashes[2]),
+		);
+	}
+}
@@ -0,0 +1,93 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of the bridged chain MMR proofs.
+
+use crate::{merge_hashes, MmrRootHash};
+
+use codec::{Decode, Encode};
+use sp_core::H256;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Proof of the single leaf membership in the MMR.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone)]
+pub struct MmrLeafProof {
+	/// Index of the leaf the proof is for.
+	pub leaf_index: u64,
+	/// Number of leaves in the MMR, when the proof has been generated.
+	pub leaf_count: u64,
+	/// Proof items (does not include the leaf hash).
+	pub items: Vec<H256>,
+}
+
+/// MMR nodes merging strategy of the bridged chain MMR.
+struct KeccakMerge;
+
+impl mmr_lib::Merge for KeccakMerge {
+	type Item = H256;
+
+	fn merge(left: &H256, right: &H256) -> H256 {
+		merge_hashes(left, right)
+	}
+}
+
+/// Verify that the leaf with given hash is a member of the MMR with given root.
+pub fn verify_mmr_leaf_proof(root: MmrRootHash, leaf_hash: H256, proof: MmrLeafProof) -> bool {
+	if proof.leaf_index >= proof.leaf_count {
+		return false;
+	}
+
+	let mmr_size = mmr_lib::leaf_index_to_mmr_size(proof.leaf_count - 1);
+	let leaf_position = mmr_lib::leaf_index_to_pos(proof.leaf_index);
+	mmr_lib::MerkleProof::<H256, KeccakMerge>::new(mmr_size, proof.items)
+		.verify(root, vec![(leaf_position, leaf_hash)])
+		.unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn verifies_proof_of_single_leaf_mmr() {
+		let leaf = H256::repeat_byte(1);
+		let proof = MmrLeafProof {
+			leaf_index: 0,
+			leaf_count: 1,
+			items: vec![],
+		};
+
+		assert!(verify_mmr_leaf_proof(leaf, leaf, proof.clone()));
+		assert!(!verify_mmr_leaf_proof(H256::repeat_byte(2), leaf, proof));
+	}
+
+	#[test]
+	fn verifies_proof_of_two_leaves_mmr() {
+		let (leaf0, leaf1) = (H256::repeat_byte(1), H256::repeat_byte(2));
+		let root = merge_hashes(&leaf0, &leaf1);
+		let proof = |leaf_index, items| MmrLeafProof {
+			leaf_index,
+			leaf_count: 2,
+			items,
+		};
+
+		assert!(verify_mmr_leaf_proof(root, leaf0, proof(0, vec![leaf1])));
+		assert!(verify_mmr_leaf_proof(root, leaf1, proof(1, vec![leaf0])));
+		assert!(!verify_mmr_leaf_proof(root, leaf1, proof(1, vec![leaf1])));
+		assert!(!verify_mmr_leaf_proof(root, leaf1, proof(2, vec![leaf0])));
+	}
+}
@@ -0,0 +1,129 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Primitives of bridge registry module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// RuntimeApi generated functions
+#![allow(clippy::too_many_arguments)]
+// Generated by `DecodeLimit::decode_with_depth_limit`
+#![allow(clippy::unnecessary_mut_passed)]
+
+use bp_messages::LaneId;
+use bp_runtime::ChainId;
+use codec::{Decode, Encode};
+use frame_support::{Parameter, RuntimeDebug};
+use sp_api::decl_runtime_apis;
+use sp_core::H256;
+use sp_std::prelude::*;
+
+/// Name of the `BridgeRegistryApi::bridges` runtime method.
+pub const BRIDGE_REGISTRY_BRIDGES_METHOD: &str = "BridgeRegistryApi_bridges";
+/// Name of the `BridgeRegistryApi::bridge` runtime method.
+pub const BRIDGE_REGISTRY_BRIDGE_METHOD: &str = "BridgeRegistryApi_bridge";
+
+/// Kind of the bridge pallet.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum BridgePalletKind {
+	/// Pallet that tracks finality of the bridged chain headers (e.g. `pallet-bridge-grandpa`).
+	Finality,
+	/// Pallet that sends and receives messages to/from the bridged chain (e.g. `pallet-bridge-messages`).
+	Messages,
+	/// Pallet that dispatches messages, received from the bridged chain (e.g. `pallet-bridge-dispatch`).
+	Dispatch,
+}
+
+/// Bridge pallet instance, deployed in the runtime.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct BridgePallet {
+	/// Kind of the pallet.
+	pub kind: BridgePalletKind,
+	/// Name of the pallet instance, as it is declared in the `construct_runtime!` (e.g. `BridgeRialtoMessages`).
+	pub name: Vec<u8>,
+}
+
+/// Information about bridge, deployed in the runtime.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct BridgeInfo<BlockNumber> {
+	/// Human-readable bridge name (e.g. `Millau-Rialto`).
+	pub name: Vec<u8>,
+	/// Bridge pallet instances.
+	pub pallets: Vec<BridgePallet>,
+	/// Lanes that are served by the bridge.
+	pub lanes: Vec<LaneId>,
+	/// Genesis hash of the bridged chain.
+	pub bridged_chain_genesis_hash: H256,
+	/// Number of the block at which the bridge has been registered.
+	pub registered_at: BlockNumber,
+}
+
+impl<BlockNumber> BridgeInfo<BlockNumber> {
+	/// Returns name of the first pallet instance of given kind.
+	pub fn pallet(&self, kind: BridgePalletKind) -> Option<&[u8]> {
+		self.pallets
+			.iter()
+			.find(|pallet| pallet.kind == kind)
+			.map(|pallet| &pallet.name[..])
+	}
+}
+
+decl_runtime_apis! {
+	/// API for querying bridges that are deployed in the runtime.
+	///
+	/// Every bridge is identified by the `ChainId` that is also used to select bridge pallet
+	/// instances in other bridge runtime APIs.
+	pub trait BridgeRegistryApi<BlockNumber: Parameter> {
+		/// Returns all registered bridges.
+		fn bridges() -> Vec<(ChainId, BridgeInfo<BlockNumber>)>;
+		/// Returns bridge with given id, if it is registered.
+		fn bridge(bridge_id: ChainId) -> Option<BridgeInfo<BlockNumber>>;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bridge_pallet_is_selected_by_kind() {
+		let info = BridgeInfo {
+			name: b"Millau-Rialto".to_vec(),
+			pallets: vec![
+				BridgePallet {
+					kind: BridgePalletKind::Finality,
+					name: b"BridgeRialtoGrandpa".to_vec(),
+				},
+				BridgePallet {
+					kind: BridgePalletKind::Messages,
+					name: b"BridgeRialtoMessages".to_vec(),
+				},
+			],
+			lanes: vec![[0, 0, 0, 0]],
+			bridged_chain_genesis_hash: Default::default(),
+			registered_at: 0u32,
+		};
+
+		assert_eq!(
+			info.pallet(BridgePalletKind::Finality),
+			Some(&b"BridgeRialtoGrandpa"[..])
+		);
+		assert_eq!(
+			info.pallet(BridgePalletKind::Messages),
+			Some(&b"BridgeRialtoMessages"[..])
+		);
+		assert_eq!(info.pallet(BridgePalletKind::Dispatch), None);
+	}
+}
@@ -22,8 +22,9 @@
 
 mod millau_hash;
 
+use bp_header_chain::FinalityPalletState;
 use bp_messages::{LaneId, MessageDetails, MessageNonce, UnrewardedRelayersState};
-use bp_runtime::Chain;
+use bp_runtime::{Chain, ChainId, UnknownBridgeInstance};
 use frame_support::{
 	weights::{constants::WEIGHT_PER_SECOND, DispatchClass, Weight},
 	Parameter, RuntimeDebug,
@@ -240,6 +241,8 @@ pub fn max_extrinsic_size() -> u32 {
 
 /// Name of the `MillauFinalityApi::best_finalized` runtime method.
 pub const BEST_FINALIZED_MILLAU_HEADER_METHOD: &str = "MillauFinalityApi_best_finalized";
+/// Name of the `MillauFinalityApi::finality_pallet_state` runtime method.
+pub const MILLAU_FINALITY_PALLET_STATE_METHOD: &str = "MillauFinalityApi_finality_pallet_state";
 
 /// Name of the `ToMillauOutboundLaneApi::estimate_message_delivery_and_dispatch_fee` runtime method.
 pub const TO_MILLAU_ESTIMATE_MESSAGE_FEE_METHOD: &str =
@@ -263,17 +266,37 @@ sp_api::decl_runtime_apis! {
 	///
 	/// This API is implemented by runtimes that are bridging with the Millau chain, not the
 	/// Millau runtime itself.
+	///
+	/// The runtime may host several bridge pallet instances that are tracking Millau headers. Since
+	/// version 2 of the API, the `instance` argument of every method selects the instance that is
+	/// queried. Methods return an error if the instance is unknown.
+	#[api_version(2)]
 	pub trait MillauFinalityApi {
 		/// Returns number and hash of the best finalized header known to the bridge module.
+		fn best_finalized(instance: ChainId) -> Result<(BlockNumber, Hash), UnknownBridgeInstance>;
+		/// Returns number and hash of the best finalized header known to the bridge module.
+		#[changed_in(2)]
 		fn best_finalized() -> (BlockNumber, Hash);
 		/// Returns true if the header is known to the runtime.
+		fn is_known_header(instance: ChainId, hash: Hash) -> Result<bool, UnknownBridgeInstance>;
+		/// Returns true if the header is known to the runtime.
+		#[changed_in(2)]
 		fn is_known_header(hash: Hash) -> bool;
+		/// Returns state of the bridge module or `None` if it is not yet initialized.
+		fn finality_pallet_state(
+			instance: ChainId,
+		) -> Result<Option<FinalityPalletState<BlockNumber, Hash>>, UnknownBridgeInstance>;
 	}
 
 	/// Outbound message lane API for messages that are sent to Millau chain.
 	///
 	/// This API is implemented by runtimes that are sending messages to Millau chain, not the
 	/// Millau runtime itself.
+	///
+	/// The runtime may host several messages pallet instances that are sending messages to Millau.
+	/// Since version 2 of the API, the `instance` argument of every method selects the instance that
+	/// is queried. Methods return an error if the instance is unknown.
+	#[api_version(2)]
 	pub trait ToMillauOutboundLaneApi<OutboundMessageFee: Parameter, OutboundPayload: Parameter> {
 		/// Estimate message delivery and dispatch fee that needs to be paid by the sender on
 		/// this chain.
@@ -284,6 +307,14 @@ sp_api::decl_runtime_apis! {
 		/// to be accepted to the lane. It may be good idea to pay a bit over this price to account
 		/// future exchange rate changes and guarantee that relayer would deliver your message
 		/// to the target chain.
+		fn estimate_message_delivery_and_dispatch_fee(
+			instance: ChainId,
+			lane_id: LaneId,
+			payload: OutboundPayload,
+		) -> Result<Option<OutboundMessageFee>, UnknownBridgeInstance>;
+		/// Estimate message delivery and dispatch fee that needs to be paid by the sender on
+		/// this chain.
+		#[changed_in(2)]
 		fn estimate_message_delivery_and_dispatch_fee(
 			lane_id: LaneId,
 			payload: OutboundPayload,
@@ -293,14 +324,29 @@ sp_api::decl_runtime_apis! {
 		///
 		/// If some (or all) messages are missing from the storage, they'll also will
 		/// be missing from the resulting vector. The vector is ordered by the nonce.
+		fn message_details(
+			instance: ChainId,
+			lane: LaneId,
+			begin: MessageNonce,
+			end: MessageNonce,
+		) -> Result<Vec<MessageDetails<OutboundMessageFee>>, UnknownBridgeInstance>;
+		/// Returns dispatch weight, encoded payload size and delivery+dispatch fee of all
+		/// messages in given inclusive range.
+		#[changed_in(2)]
 		fn message_details(
 			lane: LaneId,
 			begin: MessageNonce,
 			end: MessageNonce,
 		) -> Vec<MessageDetails<OutboundMessageFee>>;
 		/// Returns nonce of the latest message, received by bridged chain.
+		fn latest_received_nonce(instance: ChainId, lane: LaneId) -> Result<MessageNonce, UnknownBridgeInstance>;
+		/// Returns nonce of the latest message, received by bridged chain.
+		#[changed_in(2)]
 		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
 		/// Returns nonce of the latest message, generated by given lane.
+		fn latest_generated_nonce(instance: ChainId, lane: LaneId) -> Result<MessageNonce, UnknownBridgeInstance>;
+		/// Returns nonce of the latest message, generated by given lane.
+		#[changed_in(2)]
 		fn latest_generated_nonce(lane: LaneId) -> MessageNonce;
 	}
 
@@ -308,12 +354,29 @@ sp_api::decl_runtime_apis! {
 	///
 	/// This API is implemented by runtimes that are receiving messages from Millau chain, not the
 	/// Millau runtime itself.
+	///
+	/// The runtime may host several messages pallet instances that are receiving messages from
+	/// Millau. Since version 2 of the API, the `instance` argument of every method selects the
+	/// instance that is queried. Methods return an error if the instance is unknown.
+	#[api_version(2)]
 	pub trait FromMillauInboundLaneApi {
 		/// Returns nonce of the latest message, received by given lane.
+		fn latest_received_nonce(instance: ChainId, lane: LaneId) -> Result<MessageNonce, UnknownBridgeInstance>;
+		/// Returns nonce of the latest message, received by given lane.
+		#[changed_in(2)]
 		fn latest_received_nonce(lane: LaneId) -> MessageNonce;
 		/// Nonce of latest message that has been confirmed to the bridged chain.
+		fn latest_confirmed_nonce(instance: ChainId, lane: LaneId) -> Result<MessageNonce, UnknownBridgeInstance>;
+		/// Nonce of latest message that has been confirmed to the bridged chain.
+		#[changed_in(2)]
 		fn latest_confirmed_nonce(lane: LaneId) -> MessageNonce;
 		/// State of the unrewarded relayers set at given lane.
+		fn unrewarded_relayers_state(
+			instance: ChainId,
+			lane: LaneId,
+		) -> Result<UnrewardedRelayersState, UnknownBridgeInstance>;
+		/// State of the unrewarded relayers set at given lane.
+		#[changed_in(2)]
 		fn unrewarded_relayers_state(lane: LaneId) -> UnrewardedRelayersState;
 	}
 }
@@ -20,6 +20,7 @@
 // Runtime-generated DecodeLimit::decode_all_with_depth_limit
 #![allow(clippy::unnecessary_mut_passed)]
 
+use bp_header_chain::FinalityPalletState;
 use bp_messages::{LaneId, MessageDetails, MessageNonce, UnrewardedRelayersState};
 use bp_runtime::Chain;
 use sp_std::prelude::*;
@@ -94,6 +95,8 @@ pub fn derive_account_from_rococo_id(id: bp_runtime::SourceAccount<AccountId>) -
 pub const BEST_FINALIZED_WESTEND_HEADER_METHOD: &str = "WestendFinalityApi_best_finalized";
 /// Name of the `WestendFinalityApi::is_known_header` runtime method.
 pub const IS_KNOWN_WESTEND_HEADER_METHOD: &str = "WestendFinalityApi_is_known_header";
+/// Name of the `WestendFinalityApi::finality_pallet_state` runtime method.
+pub const WESTEND_FINALITY_PALLET_STATE_METHOD: &str = "WestendFinalityApi_finality_pallet_state";
 
 /// Name of the `ToWestendOutboundLaneApi::estimate_message_delivery_and_dispatch_fee` runtime method.
 pub const TO_WESTEND_ESTIMATE_MESSAGE_FEE_METHOD: &str =
@@ -129,6 +132,8 @@ sp_api::decl_runtime_apis! {
 		fn best_finalized() -> (BlockNumber, Hash);
 		/// Returns true if the header is known to the runtime.
 		fn is_known_header(hash: Hash) -> bool;
+		/// Returns state of the bridge module or `None` if it is not yet initialized.
+		fn finality_pallet_state() -> Option<FinalityPalletState<BlockNumber, Hash>>;
 	}
 
 	/// Outbound message lane API for messages that are sent to Westend chain.
@@ -21,7 +21,7 @@
 #![allow(clippy::unnecessary_mut_passed)]
 
 pub use parity_bytes::Bytes;
-pub use primitive_types::{H160, H256, H512, U128, U256};
+pub use primitive_types::{H160, H256, H512, U128, U256, U512};
 pub use rlp::encode as rlp_encode;
 
 use codec::{Decode, Encode};
@@ -30,7 +30,7 @@ use fixed_hash::construct_fixed_hash;
 use rlp::{Decodable, DecoderError, Rlp, RlpStream};
 use sp_io::hashing::keccak_256;
 use sp_runtime::RuntimeDebug;
-use sp_std::prelude::*;
+use sp_std::{convert::TryInto, prelude::*};
 
 use impl_rlp::impl_fixed_hash_rlp;
 #[cfg(feature = "std")]
@@ -54,6 +54,10 @@ pub type RawTransactionReceipt = Vec<u8>;
 /// An ethereum address.
 pub type Address = H160;
 
+/// EIP-2718 type of EIP-1559 transactions.
+pub const EIP1559_TRANSACTION_TYPE: u8 = 2;
+
+pub mod receipt_proof;
 pub mod signatures;
 
 /// Complete header id.
@@ -128,6 +132,25 @@ pub struct UnsignedTransaction {
 	pub payload: Bytes,
 }
 
+/// Unsigned portion of EIP-1559 (type 2) ethereum transaction.
+#[derive(Clone, PartialEq, RuntimeDebug)]
+pub struct UnsignedEip1559Transaction {
+	/// Sender nonce.
+	pub nonce: U256,
+	/// Maximal fee per gas that is paid to the block author.
+	pub max_priority_fee_per_gas: U256,
+	/// Maximal total fee per gas (base fee and priority fee) that sender agrees to pay.
+	pub max_fee_per_gas: U256,
+	/// Gas limit.
+	pub gas: U256,
+	/// Transaction destination address. None if it is contract creation transaction.
+	pub to: Option<Address>,
+	/// Value.
+	pub value: U256,
+	/// Associated data.
+	pub payload: Bytes,
+}
+
 /// Information describing execution of a transaction.
 #[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug)]
 pub struct Receipt {
@@ -260,6 +283,19 @@ impl AuraHeader {
 			.and_then(|x| Rlp::new(x).as_list::<SealedEmptyStep>().ok())
 	}
 
+	/// Get Ethash mix hash of this header.
+	pub fn mix_hash(&self) -> Option<H256> {
+		self.seal.get(0).and_then(|x| Rlp::new(x).as_val().ok())
+	}
+
+	/// Get Ethash nonce of this header.
+	pub fn nonce(&self) -> Option<[u8; 8]> {
+		self.seal
+			.get(1)
+			.and_then(|x| Rlp::new(x).data().ok())
+			.and_then(|x| x.try_into().ok())
+	}
+
 	/// Returns header RLP with or without seals.
 	fn rlp(&self, with_seal: bool) -> Bytes {
 		let mut s = RlpStream::new();
@@ -342,6 +378,40 @@ impl UnsignedTransaction {
 	}
 }
 
+impl UnsignedEip1559Transaction {
+	/// Returns message that has to be signed to sign this transaction.
+	pub fn message(&self, chain_id: u64) -> H256 {
+		keccak_256(&self.rlp(chain_id)).into()
+	}
+
+	/// Returns typed (EIP-2718) unsigned transaction encoding.
+	pub fn rlp(&self, chain_id: u64) -> Bytes {
+		let mut stream = RlpStream::new_list(9);
+		self.rlp_to(chain_id, &mut stream);
+
+		let mut encoded = vec![EIP1559_TRANSACTION_TYPE];
+		encoded.extend_from_slice(&stream.out());
+		encoded
+	}
+
+	/// Encode to given rlp stream.
+	pub fn rlp_to(&self, chain_id: u64, stream: &mut RlpStream) {
+		stream.append(&chain_id);
+		stream.append(&self.nonce);
+		stream.append(&self.max_priority_fee_per_gas);
+		stream.append(&self.max_fee_per_gas);
+		stream.append(&self.gas);
+		match self.to {
+			Some(to) => stream.append(&to),
+			None => stream.append(&""),
+		};
+		stream.append(&self.value);
+		stream.append(&self.payload);
+		// we never use access lists
+		stream.begin_list(0);
+	}
+}
+
 impl Receipt {
 	/// Decode status from raw transaction receipt RLP.
 	pub fn is_successful_raw_receipt(raw_receipt: &[u8]) -> Result<bool, DecoderError> {
@@ -362,6 +432,51 @@ impl Receipt {
 		}
 	}
 
+	/// Decode receipt from its raw RLP.
+	///
+	/// Receipts of typed (EIP-2718) transactions are prefixed with the transaction type, which is
+	/// ignored here.
+	pub fn decode_rlp(raw_receipt: &[u8]) -> Result<Self, DecoderError> {
+		let raw_receipt = match raw_receipt.split_first() {
+			Some((transaction_type, rest)) if *transaction_type < 0x80 => rest,
+			_ => raw_receipt,
+		};
+
+		let rlp = Rlp::new(raw_receipt);
+		let (outcome, offset) = match rlp.item_count()? {
+			3 => (TransactionOutcome::Unknown, 0),
+			4 => {
+				let first = rlp.at(0)?;
+				let outcome = if first.is_data() && first.data()?.len() <= 1 {
+					TransactionOutcome::StatusCode(first.as_val()?)
+				} else {
+					TransactionOutcome::StateRoot(first.as_val()?)
+				};
+				(outcome, 1)
+			}
+			_ => return Err(DecoderError::RlpIncorrectListLen),
+		};
+		let log_bloom: EthBloom = rlp.val_at(offset + 1)?;
+		let logs = rlp
+			.at(offset + 2)?
+			.iter()
+			.map(|log| {
+				Ok(LogEntry {
+					address: log.val_at(0)?,
+					topics: log.list_at(1)?,
+					data: log.val_at(2)?,
+				})
+			})
+			.collect::<Result<_, DecoderError>>()?;
+
+		Ok(Receipt {
+			gas_used: rlp.val_at(offset)?,
+			log_bloom: Bloom(*log_bloom.data()),
+			logs,
+			outcome,
+		})
+	}
+
 	/// Returns receipt RLP.
 	pub fn rlp(&self) -> Bytes {
 		let mut s = RlpStream::new();
@@ -666,6 +781,48 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn receipt_decode_works() {
+		let log = LogEntry {
+			address: [1u8; 20].into(),
+			topics: vec![[2u8; 32].into(), [3u8; 32].into()],
+			data: vec![4u8; 64],
+		};
+		let receipts = vec![
+			Receipt {
+				outcome: TransactionOutcome::Unknown,
+				gas_used: 100.into(),
+				log_bloom: log.bloom(),
+				logs: vec![log.clone()],
+			},
+			Receipt {
+				outcome: TransactionOutcome::StateRoot([5u8; 32].into()),
+				gas_used: 100.into(),
+				log_bloom: Default::default(),
+				logs: Vec::new(),
+			},
+			Receipt {
+				outcome: TransactionOutcome::StatusCode(1),
+				gas_used: 100.into(),
+				log_bloom: log.bloom(),
+				logs: vec![log.clone(), log],
+			},
+		];
+
+		for receipt in receipts {
+			let raw_receipt = receipt.rlp();
+			assert_eq!(Receipt::decode_rlp(&raw_receipt), Ok(receipt.clone()));
+
+			// receipt of typed transaction
+			let typed_raw_receipt = sp_std::iter::once(EIP1559_TRANSACTION_TYPE)
+				.chain(raw_receipt)
+				.collect::<Vec<_>>();
+			assert_eq!(Receipt::decode_rlp(&typed_raw_receipt), Ok(receipt));
+		}
+
+		assert!(Receipt::decode_rlp(&[]).is_err());
+	}
+
 	#[test]
 	fn is_successful_raw_receipt_works() {
 		assert!(Receipt::is_successful_raw_receipt(&[]).is_err());
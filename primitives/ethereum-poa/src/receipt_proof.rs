@@ -0,0 +1,375 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+//
+//! Ethereum receipts inclusion proofs.
+//!
+//! Receipts of the block are stored in the Merkle-Patricia trie, where key is the RLP-encoded index
+//! of the transaction within the block and value is the raw transaction receipt. The root of this
+//! trie is stored in the `receipts_root` field of the block header. The proof is the set of trie
+//! nodes that are referenced (by hash) on the path from the root to the receipt.
+
+use crate::{rlp_encode, Bytes, RawTransactionReceipt, H256};
+
+use codec::{Decode, Encode};
+use rlp::{DecoderError, Rlp, RlpStream};
+use sp_io::hashing::keccak_256;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Trie nodes that are encoded in the parent node instead of being referenced by hash must be
+/// shorter than this.
+const MAX_INLINE_NODE_LEN: usize = 32;
+
+/// Ethereum transaction receipt inclusion proof.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct ReceiptInclusionProof {
+	/// Hash of the block with the receipt.
+	pub block: H256,
+	/// Index of the transaction (and its receipt) within the block.
+	pub index: u64,
+	/// RLP-encoded trie nodes on the path from the receipts root to the receipt.
+	pub proof: Vec<Bytes>,
+}
+
+/// Receipt proof verification error.
+#[derive(RuntimeDebug, PartialEq)]
+pub enum ReceiptProofError {
+	/// The proof is missing trie node that is required to verify the receipt.
+	MissingNode,
+	/// The proof contains trie node that can't be decoded.
+	InvalidNode,
+	/// The trie doesn't contain receipt with given index.
+	MissingReceipt,
+}
+
+impl From<DecoderError> for ReceiptProofError {
+	fn from(_: DecoderError) -> Self {
+		ReceiptProofError::InvalidNode
+	}
+}
+
+/// Reference to the trie node.
+enum NodeRef<'a> {
+	/// Node is referenced by hash of its encoding.
+	Hash(H256),
+	/// Node is encoded in the parent node.
+	Inline(&'a [u8]),
+}
+
+/// Generate proof of receipt with given index, using all raw receipts of the block.
+///
+/// Returns `None` if there's no receipt with given index.
+pub fn prove_receipt(receipts: &[RawTransactionReceipt], index: u64) -> Option<Vec<Bytes>> {
+	if index >= receipts.len() as u64 {
+		return None;
+	}
+
+	let mut items = receipts
+		.iter()
+		.enumerate()
+		.map(|(item_index, receipt)| (receipt_key(item_index as u64), &receipt[..]))
+		.collect::<Vec<_>>();
+	items.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+
+	let proved_key = receipt_key(index);
+	let mut proof = Vec::new();
+	build_node(&items, 0, Some(&proved_key), &mut proof);
+	// nodes are collected from the leaf to the root
+	proof.reverse();
+	Some(proof)
+}
+
+/// Verify receipt inclusion proof against given receipts root.
+///
+/// Returns raw receipt if proof is valid.
+pub fn verify_receipt_proof(
+	receipts_root: &H256,
+	index: u64,
+	proof: &[Bytes],
+) -> Result<RawTransactionReceipt, ReceiptProofError> {
+	let key = receipt_key(index);
+	let mut key = &key[..];
+	let mut node_ref = NodeRef::Hash(*receipts_root);
+	loop {
+		let encoded_node = match node_ref {
+			NodeRef::Hash(hash) => proof
+				.iter()
+				.find(|node| keccak_256(node) == hash.0)
+				.map(|node| &node[..])
+				.ok_or(ReceiptProofError::MissingNode)?,
+			NodeRef::Inline(encoded_node) => encoded_node,
+		};
+
+		let node = Rlp::new(encoded_node);
+		match node.item_count()? {
+			// branch node
+			17 => match key.split_first() {
+				Some((nibble, rest)) => {
+					node_ref = child_ref(node.at(*nibble as usize)?)?;
+					key = rest;
+				}
+				None => return non_empty_value(node.at(16)?),
+			},
+			// leaf or extension node
+			2 => {
+				let (path, is_leaf) = decode_hex_prefix(node.at(0)?.data()?)?;
+				if !key.starts_with(&path) {
+					return Err(ReceiptProofError::MissingReceipt);
+				}
+
+				key = &key[path.len()..];
+				match is_leaf {
+					true if key.is_empty() => return non_empty_value(node.at(1)?),
+					true => return Err(ReceiptProofError::MissingReceipt),
+					false => node_ref = child_ref(node.at(1)?)?,
+				}
+			}
+			_ => return Err(ReceiptProofError::InvalidNode),
+		}
+	}
+}
+
+/// Returns nibbles of the trie key of receipt with given index.
+fn receipt_key(index: u64) -> Vec<u8> {
+	to_nibbles(&rlp_encode(&index)).collect()
+}
+
+/// Split bytes into nibbles.
+fn to_nibbles(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+	bytes
+		.iter()
+		.flat_map(|byte| sp_std::iter::once(byte >> 4).chain(sp_std::iter::once(byte & 0x0f)))
+}
+
+/// Build trie node from given (sorted by key) items and return its encoding.
+///
+/// If the node is on the path to `proved_key`, it is added to the proof.
+fn build_node(items: &[(Vec<u8>, &[u8])], depth: usize, proved_key: Option<&[u8]>, proof: &mut Vec<Bytes>) -> Bytes {
+	let encoded_node = encode_node(items, depth, proved_key, proof);
+	// the root node is always referenced by hash (from the header)
+	if proved_key.is_some() && (depth == 0 || encoded_node.len() >= MAX_INLINE_NODE_LEN) {
+		proof.push(encoded_node.clone());
+	}
+	encoded_node
+}
+
+/// Encode trie node from given (sorted by key) items.
+fn encode_node(items: &[(Vec<u8>, &[u8])], depth: usize, proved_key: Option<&[u8]>, proof: &mut Vec<Bytes>) -> Bytes {
+	let mut stream = RlpStream::new();
+
+	// single item => leaf node
+	if let [(key, value)] = items {
+		stream.begin_list(2);
+		stream.append(&encode_hex_prefix(&key[depth..], true));
+		stream.append(&value.to_vec());
+		return stream.out().to_vec();
+	}
+
+	// all items share the same path => extension node
+	let first_key = &items[0].0[depth..];
+	let last_key = &items[items.len() - 1].0[depth..];
+	let shared_path_len = first_key.iter().zip(last_key).take_while(|(a, b)| a == b).count();
+	if shared_path_len != 0 {
+		let shared_path = &first_key[..shared_path_len];
+		let child_proved_key = proved_key.filter(|key| key[depth..].starts_with(shared_path));
+		let child = build_node(items, depth + shared_path_len, child_proved_key, proof);
+		stream.begin_list(2);
+		stream.append(&encode_hex_prefix(shared_path, false));
+		append_child(&mut stream, &child);
+		return stream.out().to_vec();
+	}
+
+	// otherwise => branch node. Since items are sorted, the item with key that ends at this node
+	// (if any) is the first one
+	stream.begin_list(17);
+	let (value, mut begin) = match items[0].0.len() == depth {
+		true => (Some(items[0].1), 1),
+		false => (None, 0),
+	};
+	for nibble in 0..16u8 {
+		let children = items[begin..]
+			.iter()
+			.take_while(|(key, _)| key[depth] == nibble)
+			.count();
+		let end = begin + children;
+		if children == 0 {
+			stream.append_empty_data();
+			continue;
+		}
+
+		let child_proved_key = proved_key.filter(|key| key.get(depth) == Some(&nibble));
+		let child = build_node(&items[begin..end], depth + 1, child_proved_key, proof);
+		append_child(&mut stream, &child);
+		begin = end;
+	}
+	match value {
+		Some(value) => stream.append(&value.to_vec()),
+		None => stream.append_empty_data(),
+	};
+	stream.out().to_vec()
+}
+
+/// Append reference to the child node to the parent node encoding.
+fn append_child(stream: &mut RlpStream, encoded_child: &[u8]) {
+	if encoded_child.len() < MAX_INLINE_NODE_LEN {
+		stream.append_raw(encoded_child, 1);
+	} else {
+		stream.append(&H256::from(keccak_256(encoded_child)));
+	}
+}
+
+/// Decode reference to the child node.
+fn child_ref<'a>(child: Rlp<'a>) -> Result<NodeRef<'a>, ReceiptProofError> {
+	if child.is_list() {
+		return Ok(NodeRef::Inline(child.as_raw()));
+	}
+
+	let hash = child.data()?;
+	match hash.len() {
+		0 => Err(ReceiptProofError::MissingReceipt),
+		32 => Ok(NodeRef::Hash(H256::from_slice(hash))),
+		_ => Err(ReceiptProofError::InvalidNode),
+	}
+}
+
+/// Returns value, stored in the trie node.
+fn non_empty_value(value: Rlp) -> Result<RawTransactionReceipt, ReceiptProofError> {
+	match value.data()? {
+		value if value.is_empty() => Err(ReceiptProofError::MissingReceipt),
+		value => Ok(value.to_vec()),
+	}
+}
+
+/// Encode nibbles using hex-prefix encoding.
+fn encode_hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+	let flags = if is_leaf { 0x20 } else { 0x00 };
+	let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+	let even_nibbles = match nibbles.len() % 2 {
+		0 => {
+			encoded.push(flags);
+			nibbles
+		}
+		_ => {
+			encoded.push(flags | 0x10 | nibbles[0]);
+			&nibbles[1..]
+		}
+	};
+	encoded.extend(even_nibbles.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+	encoded
+}
+
+/// Decode hex-prefix encoded nibbles. Returns nibbles and leaf flag.
+fn decode_hex_prefix(encoded: &[u8]) -> Result<(Vec<u8>, bool), ReceiptProofError> {
+	let (first, rest) = encoded.split_first().ok_or(ReceiptProofError::InvalidNode)?;
+	let flags = first >> 4;
+	if flags > 3 {
+		return Err(ReceiptProofError::InvalidNode);
+	}
+
+	let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+	if flags & 0x01 != 0 {
+		nibbles.push(first & 0x0f);
+	}
+	nibbles.extend(to_nibbles(rest));
+	Ok((nibbles, flags & 0x02 != 0))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::compute_merkle_root;
+
+	fn receipts(count: u64) -> Vec<RawTransactionReceipt> {
+		(0..count)
+			.map(|index| {
+				// short receipts are encoded in parent nodes, so we need both short and long ones
+				let len = if index % 2 == 0 { 4 } else { 64 };
+				vec![index as u8; len]
+			})
+			.collect()
+	}
+
+	#[test]
+	fn receipt_proof_works() {
+		for count in &[1, 2, 3, 16, 17, 128, 129, 300] {
+			let receipts = receipts(*count);
+			let receipts_root = compute_merkle_root(receipts.iter());
+			for index in 0..*count {
+				let proof = prove_receipt(&receipts, index).unwrap();
+				assert_eq!(H256::from(keccak_256(&proof[0])), receipts_root);
+				assert_eq!(
+					verify_receipt_proof(&receipts_root, index, &proof),
+					Ok(receipts[index as usize].clone()),
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn proof_of_missing_receipt_is_not_generated() {
+		assert_eq!(prove_receipt(&[], 0), None);
+		assert_eq!(prove_receipt(&receipts(10), 10), None);
+	}
+
+	#[test]
+	fn receipt_proof_is_not_accepted_for_other_receipt() {
+		let receipts = receipts(128);
+		let receipts_root = compute_merkle_root(receipts.iter());
+		let proof = prove_receipt(&receipts, 1).unwrap();
+		assert!(verify_receipt_proof(&receipts_root, 42, &proof).is_err());
+		assert_eq!(
+			verify_receipt_proof(&receipts_root, 128, &proof),
+			Err(ReceiptProofError::MissingReceipt),
+		);
+	}
+
+	#[test]
+	fn receipt_proof_is_not_accepted_for_other_root() {
+		let receipts = receipts(16);
+		let proof = prove_receipt(&receipts, 1).unwrap();
+		assert_eq!(
+			verify_receipt_proof(&compute_merkle_root(receipts(17).iter()), 1, &proof),
+			Err(ReceiptProofError::MissingNode),
+		);
+	}
+
+	#[test]
+	fn incomplete_receipt_proof_is_not_accepted() {
+		let receipts = receipts(128);
+		let receipts_root = compute_merkle_root(receipts.iter());
+		let mut proof = prove_receipt(&receipts, 1).unwrap();
+		assert!(proof.len() > 1);
+		proof.pop();
+		assert_eq!(
+			verify_receipt_proof(&receipts_root, 1, &proof),
+			Err(ReceiptProofError::MissingNode),
+		);
+	}
+
+	#[test]
+	fn modified_receipt_proof_is_not_accepted() {
+		let receipts = receipts(128);
+		let receipts_root = compute_merkle_root(receipts.iter());
+		let mut proof = prove_receipt(&receipts, 1).unwrap();
+		let leaf = proof.last_mut().unwrap();
+		let last_byte = leaf.len() - 1;
+		leaf[last_byte] ^= 0xff;
+		assert_eq!(
+			verify_receipt_proof(&receipts_root, 1, &proof),
+			Err(ReceiptProofError::MissingNode),
+		);
+	}
+}
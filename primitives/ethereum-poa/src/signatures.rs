@@ -23,8 +23,8 @@
 pub use secp256k1::SecretKey;
 
 use crate::{
-	public_to_address, rlp_encode, step_validator, Address, AuraHeader, RawTransaction, UnsignedTransaction, H256,
-	H520, U256,
+	public_to_address, rlp_encode, step_validator, Address, AuraHeader, RawTransaction, UnsignedEip1559Transaction,
+	UnsignedTransaction, EIP1559_TRANSACTION_TYPE, H256, H520, U256,
 };
 
 use secp256k1::{Message, PublicKey};
@@ -78,6 +78,28 @@ impl SignTransaction for UnsignedTransaction {
 	}
 }
 
+impl SignTransaction for UnsignedEip1559Transaction {
+	fn sign_by(self, author: &SecretKey, chain_id: Option<u64>) -> RawTransaction {
+		// EIP-1559 transactions are always replay-protected, so `None` means chain with id 0
+		let chain_id = chain_id.unwrap_or_default();
+		let message = self.message(chain_id);
+		let signature = sign(author, message);
+		let signature_r = U256::from_big_endian(&signature.as_fixed_bytes()[..32][..]);
+		let signature_s = U256::from_big_endian(&signature.as_fixed_bytes()[32..64][..]);
+		let signature_y_parity = signature.as_fixed_bytes()[64];
+
+		let mut stream = rlp::RlpStream::new_list(12);
+		self.rlp_to(chain_id, &mut stream);
+		stream.append(&signature_y_parity);
+		stream.append(&signature_r);
+		stream.append(&signature_s);
+
+		let mut raw_transaction = vec![EIP1559_TRANSACTION_TYPE];
+		raw_transaction.extend_from_slice(&stream.out());
+		raw_transaction
+	}
+}
+
 /// Return author's signature over given message.
 pub fn sign(author: &SecretKey, message: H256) -> H520 {
 	let (signature, recovery_id) = secp256k1::sign(&Message::parse(message.as_fixed_bytes()), author);
@@ -140,4 +162,36 @@ mod tests {
 			}),
 		);
 	}
+
+	#[test]
+	fn eip1559_transaction_signed_properly() {
+		let signer = SecretKey::parse(&[1u8; 32]).unwrap();
+		let signer_address = secret_to_address(&signer);
+		let unsigned = UnsignedEip1559Transaction {
+			nonce: 100.into(),
+			max_priority_fee_per_gas: 200.into(),
+			max_fee_per_gas: 300.into(),
+			gas: 400.into(),
+			to: Some([42u8; 20].into()),
+			value: 500.into(),
+			payload: vec![1, 2, 3],
+		};
+		let raw_tx = unsigned.clone().sign_by(&signer, Some(42));
+		assert_eq!(raw_tx[0], EIP1559_TRANSACTION_TYPE);
+
+		// all fields of unsigned transaction are followed by signature
+		let tx_rlp = rlp::Rlp::new(&raw_tx[1..]);
+		assert_eq!(tx_rlp.item_count().unwrap(), 12);
+		assert_eq!(tx_rlp.val_at::<u64>(0).unwrap(), 42);
+		assert_eq!(tx_rlp.val_at::<U256>(3).unwrap(), unsigned.max_fee_per_gas);
+		assert_eq!(tx_rlp.val_at::<Vec<u8>>(7).unwrap(), unsigned.payload);
+
+		// signature is produced by the signer
+		let mut signature = [0u8; 65];
+		tx_rlp.val_at::<U256>(10).unwrap().to_big_endian(&mut signature[0..32]);
+		tx_rlp.val_at::<U256>(11).unwrap().to_big_endian(&mut signature[32..64]);
+		signature[64] = tx_rlp.val_at(9).unwrap();
+		let public = sp_io::crypto::secp256k1_ecdsa_recover(&signature, unsigned.message(42).as_fixed_bytes()).unwrap();
+		assert_eq!(public_to_address(&public), signer_address);
+	}
 }
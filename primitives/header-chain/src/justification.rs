@@ -56,9 +56,16 @@ pub enum Error {
 	/// Justification is finalizing unexpected header.
 	InvalidJustificationTarget,
 	/// The authority has provided an invalid signature.
-	InvalidAuthoritySignature,
+	InvalidAuthoritySignature(AuthorityId),
 	/// The justification contains precommit for header that is not a descendant of the commit header.
 	PrecommitIsNotCommitDescendant,
+	/// The justification contains precommit for header, which ancestry (up to the commit header) is
+	/// missing from the `votes_ancestries` field.
+	UnknownPrecommitAncestry,
+	/// The justification contains vote of authority that is not in the authorities set.
+	UnknownAuthorityVote(AuthorityId),
+	/// The justification contains several votes of the same authority.
+	DuplicateAuthorityVote(AuthorityId),
 	/// The cumulative weight of all votes in the justification is not enough to justify commit
 	/// header finalization.
 	TooLowCumulativeWeight,
@@ -66,6 +73,28 @@ pub enum Error {
 	ExtraHeadersInVotesAncestries,
 }
 
+/// Justification verification mode.
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum VerificationMode {
+	/// Votes of unknown authorities and repeated votes of the same authority are ignored. Any other
+	/// invalid vote fails verification. This matches `finality_grandpa::validate_commit` behavior.
+	Default,
+	/// Any redundant vote (vote of unknown authority or repeated vote of the same authority) fails
+	/// verification.
+	Strict,
+	/// All invalid votes are filtered out. Verification succeeds if remaining votes have enough
+	/// cumulative weight.
+	FilterInvalidVotes,
+}
+
+/// Outcome of successful justification verification.
+#[derive(RuntimeDebug, Default, PartialEq)]
+pub struct VerificationOutcome {
+	/// Errors of votes that have been ignored or filtered out during verification, in the same
+	/// order as they appear in the justification.
+	pub ignored_votes: Vec<Error>,
+}
+
 /// Decode justification target.
 pub fn decode_justification_target<Header: HeaderT>(
 	raw_justification: &[u8],
@@ -82,6 +111,28 @@ pub fn verify_justification<Header: HeaderT>(
 	authorities_set: &VoterSet<AuthorityId>,
 	justification: &GrandpaJustification<Header>,
 ) -> Result<(), Error>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	verify_justification_with_mode(
+		finalized_target,
+		authorities_set_id,
+		authorities_set,
+		justification,
+		VerificationMode::Default,
+	)
+	.map(drop)
+}
+
+/// Verify that justification, that is generated by given authority set, finalizes given header,
+/// handling invalid votes according to given verification mode.
+pub fn verify_justification_with_mode<Header: HeaderT>(
+	finalized_target: (Header::Hash, Header::Number),
+	authorities_set_id: SetId,
+	authorities_set: &VoterSet<AuthorityId>,
+	justification: &GrandpaJustification<Header>,
+	mode: VerificationMode,
+) -> Result<VerificationOutcome, Error>
 where
 	Header::Number: finality_grandpa::BlockNumberOps,
 {
@@ -90,6 +141,7 @@ where
 		return Err(Error::InvalidJustificationTarget);
 	}
 
+	let mut outcome = VerificationOutcome::default();
 	let mut chain = AncestryChain::new(&justification.votes_ancestries);
 	let mut signature_buffer = Vec::new();
 	let mut votes = BTreeSet::new();
@@ -100,6 +152,7 @@ where
 			Some(authority_info) => authority_info,
 			None => {
 				// just ignore precommit from unknown authority as `finality_grandpa::import_precommit` does
+				ignore_vote(mode, &mut outcome, Error::UnknownAuthorityVote(signed.id.clone()), true)?;
 				continue;
 			}
 		};
@@ -109,24 +162,51 @@ where
 		// there's a lot of code in `validate_commit` and `import_precommit` functions inside
 		// `finality-grandpa` crate (mostly related to reporing equivocations). But the only thing that we
 		// care about is that only first vote from the authority is accepted
-		if !votes.insert(signed.id.clone()) {
+		if votes.contains(&signed.id) {
+			ignore_vote(
+				mode,
+				&mut outcome,
+				Error::DuplicateAuthorityVote(signed.id.clone()),
+				true,
+			)?;
 			continue;
 		}
 
-		// everything below this line can't just `continue`, because state is already altered
-
 		// all precommits must be for block higher than the target
 		if signed.precommit.target_number < justification.commit.target_number {
-			return Err(Error::PrecommitIsNotCommitDescendant);
+			ignore_vote(mode, &mut outcome, Error::PrecommitIsNotCommitDescendant, false)?;
+			continue;
 		}
 		// all precommits must be for target block descendents
-		chain = chain.ensure_descendant(&justification.commit.target_hash, &signed.precommit.target_hash)?;
+		if let Err(error) = chain.ensure_descendant(&justification.commit.target_hash, &signed.precommit.target_hash) {
+			ignore_vote(mode, &mut outcome, error, false)?;
+			continue;
+		}
+		// verify authority signature
+		if !sp_finality_grandpa::check_message_signature_with_buffer(
+			&finality_grandpa::Message::Precommit(signed.precommit.clone()),
+			&signed.id,
+			&signed.signature,
+			justification.round,
+			authorities_set_id,
+			&mut signature_buffer,
+		) {
+			ignore_vote(
+				mode,
+				&mut outcome,
+				Error::InvalidAuthoritySignature(signed.id.clone()),
+				false,
+			)?;
+			continue;
+		}
+
 		// since we know now that the precommit target is the descendant of the justification target,
 		// we may increase 'weight' of the justification target
 		//
 		// there's a lot of code in the `VoteGraph::insert` method inside `finality-grandpa` crate,
 		// but in the end it is only used to find GHOST, which we don't care about. The only thing
 		// that we care about is that the justification target has enough weight
+		votes.insert(signed.id.clone());
 		cumulative_weight = cumulative_weight.checked_add(authority_info.weight().0.into()).expect(
 			"sum of weights of ALL authorities is expected not to overflow - this is guaranteed by\
 				existence of VoterSet;\
@@ -135,31 +215,137 @@ where
 				thus we'll never overflow the u64::MAX;\
 				qed",
 		);
-		// verify authority signature
+	}
+
+	// check that there are no extra headers in the justification
+	if !chain.unvisited.is_empty() {
+		return Err(Error::ExtraHeadersInVotesAncestries);
+	}
+
+	// check that the cumulative weight of validators voted for the justification target (or one
+	// of its descendents) is larger than required threshold.
+	let threshold = authorities_set.threshold().0.into();
+	if cumulative_weight >= threshold {
+		Ok(outcome)
+	} else {
+		Err(Error::TooLowCumulativeWeight)
+	}
+}
+
+/// Optimize justification, so that it only contains votes that are required to justify the
+/// commit target.
+///
+/// Invalid and redundant votes are removed. Once the cumulative weight of remaining votes reaches
+/// the threshold, all subsequent votes are removed too. Headers of `votes_ancestries` that are not
+/// required to prove ancestry of remaining votes are also removed. The optimized justification is
+/// accepted by `verify_justification` in all verification modes.
+pub fn optimize_justification<Header: HeaderT>(
+	finalized_target: (Header::Hash, Header::Number),
+	authorities_set_id: SetId,
+	authorities_set: &VoterSet<AuthorityId>,
+	justification: GrandpaJustification<Header>,
+) -> Result<GrandpaJustification<Header>, Error>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	// ensure that it is justification for the expected header
+	if (justification.commit.target_hash, justification.commit.target_number) != finalized_target {
+		return Err(Error::InvalidJustificationTarget);
+	}
+
+	let GrandpaJustification {
+		round,
+		commit,
+		mut votes_ancestries,
+	} = justification;
+	let threshold = authorities_set.threshold().0.into();
+	let mut chain = AncestryChain::new(&votes_ancestries);
+	let mut signature_buffer = Vec::new();
+	let mut votes = BTreeSet::new();
+	let mut cumulative_weight = 0u64;
+	let mut precommits = Vec::new();
+	for signed in commit.precommits {
+		// we already have enough votes => all remaining votes are redundant
+		if cumulative_weight >= threshold {
+			break;
+		}
+
+		// votes of unknown authorities and repeated votes are redundant
+		let authority_info = match authorities_set.get(&signed.id) {
+			Some(authority_info) => authority_info,
+			None => continue,
+		};
+		if votes.contains(&signed.id) {
+			continue;
+		}
+
+		// invalid votes are removed. Signature is checked before the ancestry, because ancestry
+		// check marks votes ancestries as visited and we don't want to keep headers that are only
+		// used by removed votes
+		if signed.precommit.target_number < commit.target_number {
+			continue;
+		}
 		if !sp_finality_grandpa::check_message_signature_with_buffer(
 			&finality_grandpa::Message::Precommit(signed.precommit.clone()),
 			&signed.id,
 			&signed.signature,
-			justification.round,
+			round,
 			authorities_set_id,
 			&mut signature_buffer,
 		) {
-			return Err(Error::InvalidAuthoritySignature);
+			continue;
+		}
+		if chain
+			.ensure_descendant(&commit.target_hash, &signed.precommit.target_hash)
+			.is_err()
+		{
+			continue;
 		}
+
+		votes.insert(signed.id.clone());
+		cumulative_weight = cumulative_weight.saturating_add(authority_info.weight().0.into());
+		precommits.push(signed);
 	}
 
-	// check that there are no extra headers in the justification
-	if !chain.unvisited.is_empty() {
-		return Err(Error::ExtraHeadersInVotesAncestries);
+	if cumulative_weight < threshold {
+		return Err(Error::TooLowCumulativeWeight);
 	}
 
-	// check that the cumulative weight of validators voted for the justification target (or one
-	// of its descendents) is larger than required threshold.
-	let threshold = authorities_set.threshold().0.into();
-	if cumulative_weight >= threshold {
+	// only leave headers that are required to prove ancestry of remaining votes
+	votes_ancestries.retain(|header| !chain.unvisited.contains(&header.hash()));
+
+	Ok(GrandpaJustification {
+		round,
+		commit: finality_grandpa::Commit {
+			target_hash: commit.target_hash,
+			target_number: commit.target_number,
+			precommits,
+		},
+		votes_ancestries,
+	})
+}
+
+/// Either ignore invalid vote, or fail justification verification, depending on verification mode.
+///
+/// Redundant votes are votes that are ignored by the `finality-grandpa` crate (i.e. votes of unknown
+/// authorities and repeated votes of the same authority).
+fn ignore_vote(
+	mode: VerificationMode,
+	outcome: &mut VerificationOutcome,
+	error: Error,
+	is_redundant_vote: bool,
+) -> Result<(), Error> {
+	let should_ignore = match mode {
+		VerificationMode::Default => is_redundant_vote,
+		VerificationMode::Strict => false,
+		VerificationMode::FilterInvalidVotes => true,
+	};
+
+	if should_ignore {
+		outcome.ignored_votes.push(error);
 		Ok(())
 	} else {
-		Err(Error::TooLowCumulativeWeight)
+		Err(error)
 	}
 }
 
@@ -186,12 +372,15 @@ impl<Header: HeaderT> AncestryChain<Header> {
 		AncestryChain { parents, unvisited }
 	}
 
-	/// Returns `Err(_)` if `precommit_target` is a descendant of the `commit_target` block and `Ok(_)` otherwise.
+	/// Returns `Ok(_)` if `precommit_target` is a descendant of the `commit_target` block and `Err(_)` otherwise.
+	///
+	/// Headers that are visited by the failed call are left unvisited.
 	pub fn ensure_descendant(
-		mut self,
+		&mut self,
 		commit_target: &Header::Hash,
 		precommit_target: &Header::Hash,
-	) -> Result<Self, Error> {
+	) -> Result<(), Error> {
+		let mut visited = Vec::new();
 		let mut current_hash = *precommit_target;
 		loop {
 			if current_hash == *commit_target {
@@ -199,6 +388,9 @@ impl<Header: HeaderT> AncestryChain<Header> {
 			}
 
 			let is_visited_before = !self.unvisited.remove(&current_hash);
+			if !is_visited_before {
+				visited.push(current_hash);
+			}
 			current_hash = match self.parents.get(&current_hash) {
 				Some(parent_hash) => {
 					if is_visited_before {
@@ -206,14 +398,17 @@ impl<Header: HeaderT> AncestryChain<Header> {
 						// `is_visited_before` means that it has been visited before in some of previous calls
 						// => since we assume that previous call has finished with `true`, this also will
 						//    be finished with `true`
-						return Ok(self);
+						return Ok(());
 					}
 
 					*parent_hash
 				}
-				None => return Err(Error::PrecommitIsNotCommitDescendant),
+				None => {
+					self.unvisited.extend(visited);
+					return Err(Error::UnknownPrecommitAncestry);
+				}
 			};
 		}
-		Ok(self)
+		Ok(())
 	}
 }
@@ -55,6 +55,50 @@ impl AuthoritySet {
 	}
 }
 
+/// Operating mode of the bridge GRANDPA pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum OperatingMode {
+	/// Normal mode, when all finalized headers are accepted.
+	#[codec(index = 0)]
+	Normal,
+	/// The pallet is only accepting mandatory headers (headers that are scheduling authorities set
+	/// change). All other headers are rejected.
+	///
+	/// This mode may be used to freeze header import during incidents, without losing track of
+	/// the bridged chain authorities set. Once the incident is resolved, the mode may be switched
+	/// back to `Normal`.
+	#[codec(index = 1)]
+	RejectingNonMandatoryHeaders,
+	/// The pallet is halted. All operations (except operating mode change) are prohibited.
+	#[codec(index = 2)]
+	Halted,
+}
+
+impl Default for OperatingMode {
+	fn default() -> Self {
+		OperatingMode::Normal
+	}
+}
+
+/// State of the bridge GRANDPA pallet, that is exposed to relayers by runtime APIs.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone)]
+pub struct FinalityPalletState<Number, Hash> {
+	/// Number and hash of the best finalized header known to the pallet.
+	pub best_finalized: (Number, Hash),
+	/// Identifier of the current authorities set.
+	pub authority_set_id: SetId,
+	/// Pallet operating mode.
+	pub operating_mode: OperatingMode,
+}
+
+impl<Number, Hash> FinalityPalletState<Number, Hash> {
+	/// Returns true if all pallet operations are halted.
+	pub fn is_halted(&self) -> bool {
+		self.operating_mode == OperatingMode::Halted
+	}
+}
+
 /// Data required for initializing the bridge pallet.
 ///
 /// The bridge needs to know where to start its sync from, and this provides that initial context.
@@ -67,8 +111,8 @@ pub struct InitializationData<H: HeaderT> {
 	pub authority_list: AuthorityList,
 	/// The ID of the initial authority set.
 	pub set_id: SetId,
-	/// Should the pallet block transaction immediately after initialization.
-	pub is_halted: bool,
+	/// Pallet operating mode right after initialization.
+	pub operating_mode: OperatingMode,
 }
 
 /// base trait for verifying transaction inclusion proofs.
@@ -110,6 +154,23 @@ impl<H: Default, E> HeaderChain<H, E> for () {
 	}
 }
 
+/// A trait for pallets which may still need finalized headers of the bridged chain, that are
+/// outside of the header chain retention window.
+///
+/// It is used to guarantee that headers, referenced by proofs that are not yet confirmed (e.g.
+/// message delivery proofs), are not pruned while relayers are preparing or submitting them.
+pub trait HeadersPruningGuard<Number> {
+	/// Returns number of the oldest header that must be kept in the storage. Returns `None` if all
+	/// headers that are outside of the retention window may be pruned.
+	fn oldest_required_header() -> Option<Number>;
+}
+
+impl<Number> HeadersPruningGuard<Number> for () {
+	fn oldest_required_header() -> Option<Number> {
+		None
+	}
+}
+
 /// Abstract finality proof that is justifying block finality.
 pub trait FinalityProof<Number>: Clone + Send + Sync + Debug {
 	/// Return number of header that this proof is generated for.
@@ -131,3 +192,22 @@ pub fn find_grandpa_authorities_scheduled_change<H: HeaderT>(
 	// the right kind of consensus log.
 	header.digest().convert_first(|l| l.try_to(id).and_then(filter_log))
 }
+
+/// Find header digest that forcibly changes GRANDPA authorities set.
+///
+/// Returns the median last finalized block number (see `ConsensusLog::ForcedChange`) and the
+/// scheduled change itself.
+pub fn find_grandpa_authorities_forced_change<H: HeaderT>(
+	header: &H,
+) -> Option<(H::Number, sp_finality_grandpa::ScheduledChange<H::Number>)> {
+	let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
+
+	let filter_log = |log: ConsensusLog<H::Number>| match log {
+		ConsensusLog::ForcedChange(delay, change) => Some((delay, change)),
+		_ => None,
+	};
+
+	// find the first consensus digest with the right ID which converts to
+	// the right kind of consensus log.
+	header.digest().convert_first(|l| l.try_to(id).and_then(filter_log))
+}
@@ -155,7 +155,7 @@ fn same_result_when_precommit_target_is_not_descendant_of_commit_target() {
 			&full_voter_set(),
 			&justification,
 		),
-		Err(Error::PrecommitIsNotCommitDescendant),
+		Err(Error::UnknownPrecommitAncestry),
 	);
 	// original implementation returns empty GHOST
 	assert_matches!(
@@ -16,7 +16,10 @@
 
 //! Tests for Grandpa Justification code.
 
-use bp_header_chain::justification::{verify_justification, Error};
+use bp_header_chain::justification::{
+	optimize_justification, verify_justification, verify_justification_with_mode, Error, VerificationMode,
+	VerificationOutcome,
+};
 use bp_test_utils::*;
 
 type TestHeader = sp_runtime::testing::Header;
@@ -145,7 +148,9 @@ fn justification_with_invalid_authority_signature_rejected() {
 			&voter_set(),
 			&justification,
 		),
-		Err(Error::InvalidAuthoritySignature),
+		Err(Error::InvalidAuthoritySignature(
+			justification.commit.precommits[0].id.clone()
+		)),
 	);
 }
 
@@ -189,3 +194,235 @@ fn justification_is_invalid_if_we_dont_meet_threshold() {
 		Err(Error::TooLowCumulativeWeight),
 	);
 }
+
+#[test]
+fn redundant_votes_are_only_rejected_in_strict_mode() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	let duplicate_vote = justification.commit.precommits[0].clone();
+	justification.commit.precommits.push(duplicate_vote.clone());
+
+	let verify = |mode| {
+		verify_justification_with_mode::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&justification,
+			mode,
+		)
+	};
+	assert_eq!(
+		verify(VerificationMode::Default),
+		Ok(VerificationOutcome {
+			ignored_votes: vec![Error::DuplicateAuthorityVote(duplicate_vote.id.clone())],
+		}),
+	);
+	assert_eq!(
+		verify(VerificationMode::Strict),
+		Err(Error::DuplicateAuthorityVote(duplicate_vote.id)),
+	);
+}
+
+#[test]
+fn invalid_votes_are_filtered_if_remaining_weight_is_enough() {
+	use finality_grandpa::voter_set::VoterSet;
+	use sp_finality_grandpa::AuthorityId;
+
+	let authorities = vec![(ALICE, 1), (BOB, 1), (CHARLIE, 1), (DAVE, 1)];
+	let voter_set = VoterSet::new(authorities.iter().map(|(id, w)| (AuthorityId::from(*id), *w))).unwrap();
+	let params = JustificationGeneratorParams {
+		header: test_header(1),
+		round: TEST_GRANDPA_ROUND,
+		set_id: TEST_GRANDPA_SET_ID,
+		authorities,
+		ancestors: 4,
+		forks: 4,
+	};
+	let mut justification = make_justification_for_header::<TestHeader>(params);
+	justification.commit.precommits[0].signature = Default::default();
+	let invalid_vote_author = justification.commit.precommits[0].id.clone();
+
+	let verify = |mode| {
+		verify_justification_with_mode::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set,
+			&justification,
+			mode,
+		)
+	};
+	assert_eq!(
+		verify(VerificationMode::Default),
+		Err(Error::InvalidAuthoritySignature(invalid_vote_author.clone())),
+	);
+	assert_eq!(
+		verify(VerificationMode::FilterInvalidVotes),
+		Ok(VerificationOutcome {
+			ignored_votes: vec![Error::InvalidAuthoritySignature(invalid_vote_author)],
+		}),
+	);
+}
+
+#[test]
+fn filtered_justification_is_rejected_if_remaining_weight_is_not_enough() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	justification.commit.precommits[0].signature = Default::default();
+
+	assert_eq!(
+		verify_justification_with_mode::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&justification,
+			VerificationMode::FilterInvalidVotes,
+		),
+		Err(Error::TooLowCumulativeWeight),
+	);
+}
+
+#[test]
+fn optimizer_removes_redundant_and_invalid_votes() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	let valid_votes = justification.commit.precommits.clone();
+	justification.commit.precommits.insert(
+		0,
+		signed_precommit::<TestHeader>(
+			&EVE,
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_ROUND,
+			TEST_GRANDPA_SET_ID,
+		),
+	);
+	justification.commit.precommits.insert(1, valid_votes[0].clone());
+	justification.commit.precommits[1].signature = Default::default();
+	justification.commit.precommits.push(valid_votes[0].clone());
+
+	let optimized_justification = optimize_justification::<TestHeader>(
+		header_id::<TestHeader>(1),
+		TEST_GRANDPA_SET_ID,
+		&voter_set(),
+		justification,
+	)
+	.unwrap();
+	assert_eq!(optimized_justification.commit.precommits, valid_votes);
+	assert_eq!(
+		verify_justification_with_mode::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&optimized_justification,
+			VerificationMode::Strict,
+		),
+		Ok(VerificationOutcome::default()),
+	);
+}
+
+#[test]
+fn optimizer_only_leaves_votes_required_to_reach_threshold() {
+	use finality_grandpa::voter_set::VoterSet;
+	use sp_finality_grandpa::AuthorityId;
+
+	let authorities = vec![(ALICE, 1), (BOB, 1), (CHARLIE, 1), (DAVE, 1), (EVE, 1), (FERDIE, 1)];
+	let voter_set = VoterSet::new(authorities.iter().map(|(id, w)| (AuthorityId::from(*id), *w))).unwrap();
+	let params = JustificationGeneratorParams {
+		header: test_header(1),
+		round: TEST_GRANDPA_ROUND,
+		set_id: TEST_GRANDPA_SET_ID,
+		authorities,
+		ancestors: 6,
+		forks: 6,
+	};
+	let justification = make_justification_for_header::<TestHeader>(params);
+	let optimized_justification = optimize_justification::<TestHeader>(
+		header_id::<TestHeader>(1),
+		TEST_GRANDPA_SET_ID,
+		&voter_set,
+		justification,
+	)
+	.unwrap();
+
+	// threshold of 6 authorities with equal weights is 5 votes and every vote is made for
+	// different fork, so we only need 5 votes and 5 ancestry headers
+	assert_eq!(optimized_justification.commit.precommits.len(), 5);
+	assert_eq!(optimized_justification.votes_ancestries.len(), 5);
+	assert_eq!(
+		verify_justification_with_mode::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set,
+			&optimized_justification,
+			VerificationMode::Strict,
+		),
+		Ok(VerificationOutcome::default()),
+	);
+}
+
+#[test]
+fn optimizer_rejects_justification_if_valid_votes_weight_is_not_enough() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	justification.commit.precommits[0].signature = Default::default();
+
+	assert_eq!(
+		optimize_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			justification,
+		),
+		Err(Error::TooLowCumulativeWeight),
+	);
+}
+
+#[test]
+fn malformed_justifications_are_rejected() {
+	let malformations = || {
+		vec![
+			(
+				JustificationMalformation::WrongRound,
+				Error::InvalidAuthoritySignature(ALICE.into()),
+			),
+			(
+				JustificationMalformation::ForgedSignature(1),
+				Error::InvalidAuthoritySignature(BOB.into()),
+			),
+			(
+				JustificationMalformation::AncestorPrecommit(0),
+				Error::PrecommitIsNotCommitDescendant,
+			),
+			(
+				JustificationMalformation::NonCanonicalAncestry(2),
+				Error::UnknownPrecommitAncestry,
+			),
+			(
+				JustificationMalformation::MissingAncestry(0),
+				Error::UnknownPrecommitAncestry,
+			),
+			(
+				JustificationMalformation::ExtraAncestry,
+				Error::ExtraHeadersInVotesAncestries,
+			),
+			(
+				JustificationMalformation::NotEnoughWeight,
+				Error::TooLowCumulativeWeight,
+			),
+		]
+	};
+
+	for mode in vec![VerificationMode::Default, VerificationMode::Strict] {
+		for (malformation, expected_error) in malformations() {
+			let justification = make_malformed_justification::<TestHeader>(Default::default(), malformation);
+			assert_eq!(
+				verify_justification_with_mode::<TestHeader>(
+					header_id::<TestHeader>(1),
+					TEST_GRANDPA_SET_ID,
+					&voter_set(),
+					&justification,
+					mode,
+				),
+				Err(expected_error),
+				"{:?} is not rejected as expected in {:?} mode",
+				malformation,
+				mode,
+			);
+		}
+	}
+}
@@ -19,7 +19,8 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
-use bp_runtime::{ChainId, Size};
+use bp_messages::MessagePayloadDecodeError;
+use bp_runtime::{ChainId, DispatchFeePayment, Size};
 use codec::{Decode, Encode};
 use frame_support::RuntimeDebug;
 use sp_std::prelude::*;
@@ -31,7 +32,7 @@ pub type Weight = u64;
 pub type SpecVersion = u32;
 
 /// A generic trait to dispatch arbitrary messages delivered over the bridge.
-pub trait MessageDispatch<MessageId> {
+pub trait MessageDispatch<AccountId, MessageId> {
 	/// A type of the message to be dispatched.
 	type Message: codec::Decode;
 
@@ -50,9 +51,23 @@ pub trait MessageDispatch<MessageId> {
 	///
 	/// If message is `Ok`, then it should be dispatched. If it is `Err`, then it's just
 	/// a sign that some other component has rejected the message even before it has
-	/// reached `dispatch` method (right now this may only be caused if we fail to decode
-	/// the whole message).
-	fn dispatch(source_chain: ChainId, target_chain: ChainId, id: MessageId, message: Result<Self::Message, ()>);
+	/// reached `dispatch` method (right now this may only be caused if we fail to extract
+	/// the message from its versioned envelope).
+	///
+	/// If message declares that the dispatch fee is paid at the target chain, the `pay_dispatch_fee`
+	/// callback is called with the dispatch origin account and the declared dispatch weight before
+	/// dispatch. The message is not dispatched if the callback returns error.
+	///
+	/// Returns unspent dispatch weight - the difference between the dispatch weight, declared by
+	/// the message, and the weight that has actually been spent. If the message hasn't been
+	/// dispatched at all, the whole declared weight is unspent.
+	fn dispatch<P: FnOnce(&AccountId, Weight) -> Result<(), ()>>(
+		source_chain: ChainId,
+		target_chain: ChainId,
+		id: MessageId,
+		message: Result<Self::Message, MessagePayloadDecodeError>,
+		pay_dispatch_fee: P,
+	) -> Weight;
 }
 
 /// Origin of a Call when it is dispatched on the target chain.
@@ -110,6 +125,8 @@ pub struct MessagePayload<SourceChainAccountId, TargetChainAccountPublic, Target
 	pub weight: Weight,
 	/// Call origin to be used during dispatch.
 	pub origin: CallOrigin<SourceChainAccountId, TargetChainAccountPublic, TargetChainSignature>,
+	/// Where the message dispatch fee is paid?
+	pub dispatch_fee_payment: DispatchFeePayment,
 	/// The call itself.
 	pub call: Call,
 }
@@ -15,6 +15,14 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Primitives of messages module.
+//!
+//! Most of structures, defined here, are exchanged between bridged chains (using storage proofs)
+//! or between the runtime and the relay (using runtime APIs). Chains of the bridge are upgraded
+//! independently, so their encoding is a part of the wire format and must never be changed in-place.
+//! Instead, new structure (or new enum variant with new index) must be introduced. The encoding of
+//! every such structure is guarded by the golden-encoding tests at the bottom of this file and all
+//! structures are decoded using `DecodeAll`, so that the chain that doesn't know about appended
+//! fields rejects the data instead of silently dropping these fields.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 // RuntimeApi generated functions
@@ -22,6 +30,7 @@
 // Generated by `DecodeLimit::decode_with_depth_limit`
 #![allow(clippy::unnecessary_mut_passed)]
 
+use bp_runtime::{ChainId, DispatchFeePayment, UnknownBridgeInstance};
 use codec::{Decode, Encode};
 use frame_support::RuntimeDebug;
 use sp_std::{collections::vec_deque::VecDeque, prelude::*};
@@ -37,6 +46,7 @@ pub use frame_support::weights::Weight;
 #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub enum OperatingMode {
 	/// Normal mode, when all operations are allowed.
+	#[codec(index = 0)]
 	Normal,
 	/// The pallet is not accepting outbound messages. Inbound messages and receival proofs
 	/// are still accepted.
@@ -45,8 +55,10 @@ pub enum OperatingMode {
 	/// failures, the pallet owner may stop accepting new messages, while continuing to deliver
 	/// queued messages to the bridged chain. Once upgrade is completed, the mode may be switched
 	/// back to `Normal`.
+	#[codec(index = 1)]
 	RejectingOutboundMessages,
 	/// The pallet is halted. All operations (except operating mode change) are prohibited.
+	#[codec(index = 2)]
 	Halted,
 }
 
@@ -56,6 +68,174 @@ impl Default for OperatingMode {
 	}
 }
 
+/// State of the lane.
+///
+/// The lane state is controlled by the lane owner (which is the pallet owner or root). The
+/// lane may go from `Opened` to `Closing` (and back), and from `Closing` to `Closed` - once all
+/// messages sent over the lane are delivered and confirmed. `Closed` state is final.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum LaneState {
+	/// The lane is opened and all operations are allowed.
+	#[codec(index = 0)]
+	Opened,
+	/// The lane is being closed. New messages are rejected, but already queued messages are
+	/// still delivered and confirmed.
+	#[codec(index = 1)]
+	Closing,
+	/// The lane is closed. All lane operations are rejected and lane storage may be reaped.
+	#[codec(index = 2)]
+	Closed,
+}
+
+impl Default for LaneState {
+	fn default() -> Self {
+		LaneState::Opened
+	}
+}
+
+impl LaneState {
+	/// Returns true if new messages may be sent over the lane.
+	pub fn is_accepting_messages(&self) -> bool {
+		*self == LaneState::Opened
+	}
+
+	/// Returns true if the lane is closed.
+	pub fn is_closed(&self) -> bool {
+		*self == LaneState::Closed
+	}
+
+	/// Returns true if lane may be switched from this state to the `new_state`.
+	pub fn can_change_to(&self, new_state: LaneState) -> bool {
+		matches!(
+			(*self, new_state),
+			(LaneState::Opened, LaneState::Closing)
+				| (LaneState::Closing, LaneState::Opened)
+				| (LaneState::Closing, LaneState::Closed)
+		)
+	}
+}
+
+/// Ordering of messages at the lane.
+///
+/// The ordering only affects the inbound lane, so it must be the same at both sides of the bridge.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum LaneOrdering {
+	/// Messages are delivered in order of their nonces. If some message can't be delivered, all
+	/// messages that follow it are blocked.
+	#[codec(index = 0)]
+	Ordered,
+	/// Messages are delivered in order of their nonces, but there may be gaps. Messages that have
+	/// been skipped by relayers are never delivered (so the lane is lossy).
+	///
+	/// This ordering is only suitable for idempotent payloads (like oracle updates), where
+	/// delivery of the latest message is more important than delivery of all messages.
+	#[codec(index = 1)]
+	Unordered,
+}
+
+impl Default for LaneOrdering {
+	fn default() -> Self {
+		LaneOrdering::Ordered
+	}
+}
+
+/// Mode of inbound messages dispatch at the lane.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum DispatchMode {
+	/// Messages are dispatched by the delivery transaction. The relayer pays for the dispatch.
+	#[codec(index = 0)]
+	Immediate,
+	/// Messages are queued by the delivery transaction and dispatched later - either when the
+	/// block has some spare weight, or by the separate transaction. So the weight of the delivery
+	/// transaction doesn't depend on the weight of messages dispatch.
+	#[codec(index = 1)]
+	Deferred,
+}
+
+impl Default for DispatchMode {
+	fn default() -> Self {
+		DispatchMode::Immediate
+	}
+}
+
+/// Information about the lane that has been explicitly opened by the `open_lane` call.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct LaneInfo<AccountId, BlockNumber> {
+	/// Lane owner. The owner may close the lane.
+	pub owner: AccountId,
+	/// Number of the block where the lane has been opened.
+	pub opened_at: BlockNumber,
+	/// Maximal number of messages that may be queued (sent, but not yet confirmed) at the
+	/// outbound lane. Any messages over this limit are rejected.
+	pub max_pending_messages: MessageNonce,
+}
+
+/// Per-lane limits of messages, that are configured by the lane owner.
+///
+/// Limits may only make lane rules stricter - chain-wide limits (e.g. maximal size of the
+/// incoming message) are still applied.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct MessageLimits {
+	/// Maximal size of encoded payload of the outbound message. Larger messages are rejected
+	/// by the `send_message` call.
+	pub max_payload_size: u32,
+	/// Maximal dispatch weight of the inbound message. Messages with larger dispatch weight are
+	/// still received (so the lane isn't blocked), but they're not dispatched.
+	pub max_dispatch_weight: Weight,
+}
+
+/// Per-lane quota of messages that a single account may send within the period.
+///
+/// The quota is applied to messages, sent by signed accounts only. Messages that are sent by root
+/// or by the runtime itself are never limited.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct SenderQuota<BlockNumber> {
+	/// Number of blocks in the quota period. Usage of the quota is reset when the period ends.
+	pub period: BlockNumber,
+	/// Maximal number of messages that the account may send within the period.
+	pub max_messages: MessageNonce,
+	/// Maximal cumulative weight of `send_message` calls that the account may submit within the period.
+	pub max_weight: Weight,
+}
+
+/// Usage of the `SenderQuota` by the account.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct SenderQuotaUsage<BlockNumber> {
+	/// Number of the block where the current quota period has started.
+	pub period_start: BlockNumber,
+	/// Number of messages that the account has sent within the current period.
+	pub messages: MessageNonce,
+	/// Cumulative weight of `send_message` calls that the account has submitted within the current period.
+	pub weight: Weight,
+}
+
+/// Statistics of the lane, that is maintained by the messages pallet.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct LaneStatistics<Fee> {
+	/// Number of messages that have been sent over the outbound lane.
+	pub sent_messages: MessageNonce,
+	/// Number of messages that have been delivered to the inbound lane.
+	pub delivered_messages: MessageNonce,
+	/// Number of outbound messages whose delivery has been confirmed by the bridged chain.
+	pub confirmed_messages: MessageNonce,
+	/// Total fees that have been paid by submitters of outbound messages.
+	pub collected_fees: Fee,
+	/// Total rewards that have been paid to relayers for delivering outbound messages.
+	pub paid_rewards: Fee,
+}
+
+/// Congestion of the outbound lane.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct LaneCongestion {
+	/// Number of messages that have been sent over the lane, but their delivery is not yet confirmed.
+	pub queued_messages: MessageNonce,
+	/// Number of queued messages, starting from which the lane is considered congested. Zero if
+	/// the lane is never considered congested.
+	pub soft_cap: MessageNonce,
+	/// True if the lane is currently congested.
+	pub is_congested: bool,
+}
+
 /// Messages pallet parameter.
 pub trait Parameter: frame_support::Parameter {
 	/// Save parameter value in the runtime storage.
@@ -68,12 +248,80 @@ pub type LaneId = [u8; 4];
 /// Message nonce. Valid messages will never have 0 nonce.
 pub type MessageNonce = u64;
 
+/// Idempotency key of the outbound message.
+///
+/// The key is chosen by the message sender. The same key, used by the same sender, may be used to
+/// detect resubmissions of the same message.
+pub type IdempotencyKey = [u8; 32];
+
 /// Message id as a tuple.
 pub type MessageId = (LaneId, MessageNonce);
 
 /// Opaque message payload. We only decode this payload when it is dispatched.
 pub type MessagePayload = Vec<u8>;
 
+/// Version of the message payload envelope.
+pub type MessagePayloadVersion = u8;
+
+/// Version of the message payload envelope, that is used to encode new messages. Messages with
+/// other versions are rejected at the target chain.
+pub const CURRENT_MESSAGE_PAYLOAD_VERSION: MessagePayloadVersion = 1;
+
+/// Versioned envelope of the message payload.
+///
+/// Every message payload is wrapped into the envelope before it is stored at the outbound lane.
+/// The version allows the target chain to reject messages that it is unable to interpret, instead
+/// of failing to decode them.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct VersionedMessagePayload {
+	/// Version of the envelope.
+	pub version: MessagePayloadVersion,
+	/// Encoded payload.
+	pub payload: MessagePayload,
+}
+
+/// Error that may happen when the message payload is extracted from the versioned envelope.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum MessagePayloadDecodeError {
+	/// Failed to decode the envelope itself.
+	InvalidEnvelope,
+	/// The payload has been encoded using unknown version of the envelope.
+	UnknownVersion(MessagePayloadVersion),
+	/// Failed to decode the payload of known version.
+	InvalidPayload,
+}
+
+impl VersionedMessagePayload {
+	/// Wrap encoded payload into the envelope of current version.
+	pub fn new(payload: MessagePayload) -> Self {
+		VersionedMessagePayload {
+			version: CURRENT_MESSAGE_PAYLOAD_VERSION,
+			payload,
+		}
+	}
+
+	/// Encode payload, wrap it into the envelope of current version and return encoded envelope.
+	pub fn encode_payload<P: Encode>(payload: &P) -> MessagePayload {
+		Self::new(payload.encode()).encode()
+	}
+
+	/// Decode envelope and extract payload from it.
+	pub fn decode_payload<P: Decode>(mut encoded_envelope: &[u8]) -> Result<P, MessagePayloadDecodeError> {
+		Self::decode(&mut encoded_envelope)
+			.map_err(|_| MessagePayloadDecodeError::InvalidEnvelope)?
+			.into_payload()
+	}
+
+	/// Decode payload of the envelope.
+	pub fn into_payload<P: Decode>(self) -> Result<P, MessagePayloadDecodeError> {
+		if self.version != CURRENT_MESSAGE_PAYLOAD_VERSION {
+			return Err(MessagePayloadDecodeError::UnknownVersion(self.version));
+		}
+
+		P::decode(&mut &self.payload[..]).map_err(|_| MessagePayloadDecodeError::InvalidPayload)
+	}
+}
+
 /// Message key (unique message identifier) as it is stored in the storage.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
 pub struct MessageKey {
@@ -131,6 +379,19 @@ pub struct InboundLaneData<RelayerId> {
 	/// This value is updated indirectly when an `OutboundLane` state of the source
 	/// chain is received alongside with new messages delivery.
 	pub last_confirmed_nonce: MessageNonce,
+
+	/// State of the inbound lane. Messages are not accepted by the `Closed` lane.
+	pub state: LaneState,
+
+	/// Nonces of delivered, but not yet confirmed messages, that have not been dispatched, because
+	/// their payload can't be decoded (ordered by message nonce).
+	///
+	/// Noop messages, that replace cancelled messages at the source chain, have empty payloads, so
+	/// they're never dispatched. This set allows the source chain to distinguish cancelled messages
+	/// that have been delivered as noop messages from messages that have been delivered (and
+	/// dispatched) before they have been cancelled. All nonces of the set are in the
+	/// `(self.last_confirmed_nonce; self.last_delivered_nonce()]` range.
+	pub undispatched_messages: Vec<MessageNonce>,
 }
 
 impl<RelayerId> Default for InboundLaneData<RelayerId> {
@@ -138,20 +399,31 @@ impl<RelayerId> Default for InboundLaneData<RelayerId> {
 		InboundLaneData {
 			relayers: VecDeque::new(),
 			last_confirmed_nonce: 0,
+			state: LaneState::Opened,
+			undispatched_messages: Vec::new(),
 		}
 	}
 }
 
 impl<RelayerId> InboundLaneData<RelayerId> {
-	/// Returns approximate size of the struct, given number of entries in the `relayers` set and
-	/// size of each entry.
+	/// Returns approximate size of the struct, given number of entries in the `relayers` set, size
+	/// of each entry and number of undispatched messages.
 	///
 	/// Returns `None` if size overflows `u32` limits.
-	pub fn encoded_size_hint(relayer_id_encoded_size: u32, relayers_entries: u32) -> Option<u32> {
+	pub fn encoded_size_hint(
+		relayer_id_encoded_size: u32,
+		relayers_entries: u32,
+		undispatched_messages: u32,
+	) -> Option<u32> {
 		let message_nonce_size = 8;
 		let relayers_entry_size = relayer_id_encoded_size.checked_add(2 * message_nonce_size)?;
+		let lane_state_size = 1;
 		let relayers_size = relayers_entries.checked_mul(relayers_entry_size)?;
-		relayers_size.checked_add(message_nonce_size)
+		let undispatched_messages_size = undispatched_messages.checked_mul(message_nonce_size)?;
+		relayers_size
+			.checked_add(message_nonce_size)?
+			.checked_add(lane_state_size)?
+			.checked_add(undispatched_messages_size)
 	}
 
 	/// Nonce of the last message that has been delivered to this (target) chain.
@@ -161,6 +433,20 @@ impl<RelayerId> InboundLaneData<RelayerId> {
 			.map(|(_, last_nonce, _)| *last_nonce)
 			.unwrap_or(self.last_confirmed_nonce)
 	}
+
+	/// Returns number of messages in the `last_confirmed_nonce + 1..=last_delivered_nonce` range.
+	///
+	/// At lanes with `LaneOrdering::Unordered`, the range may include messages that have been
+	/// skipped by relayers. So the result may be larger than the number of messages, covered by
+	/// the `relayers` set.
+	pub fn total_unconfirmed_messages(&self) -> MessageNonce {
+		self.last_delivered_nonce().saturating_sub(self.last_confirmed_nonce)
+	}
+
+	/// Returns true if all messages, delivered to this lane, are confirmed at the source chain.
+	pub fn is_drained(&self) -> bool {
+		self.relayers.is_empty()
+	}
 }
 
 /// Message details, returned by runtime APIs.
@@ -172,8 +458,11 @@ pub struct MessageDetails<OutboundMessageFee> {
 	pub dispatch_weight: Weight,
 	/// Size of the encoded message.
 	pub size: u32,
-	/// Delivery+dispatch fee paid by the message submitter at the source chain.
+	/// Delivery+dispatch fee paid by the message submitter at the source chain. If the dispatch
+	/// fee is paid at the target chain, this is the delivery fee only.
 	pub delivery_and_dispatch_fee: OutboundMessageFee,
+	/// Where the fee for dispatching message is paid?
+	pub dispatch_fee_payment: DispatchFeePayment,
 }
 
 /// Gist of `InboundLaneData::relayers` field used by runtime APIs.
@@ -181,13 +470,25 @@ pub struct MessageDetails<OutboundMessageFee> {
 pub struct UnrewardedRelayersState {
 	/// Number of entries in the `InboundLaneData::relayers` set.
 	pub unrewarded_relayer_entries: MessageNonce,
-	/// Number of messages in the oldest entry of `InboundLaneData::relayers`. This is the
-	/// minimal number of reward proofs required to push out this entry from the set.
+	/// Number of messages in the oldest entry of `InboundLaneData::relayers`, including messages
+	/// that have been skipped before this entry. This is the minimal number of reward proofs
+	/// required to push out this entry from the set.
 	pub messages_in_oldest_entry: MessageNonce,
-	/// Total number of messages in the relayers vector.
+	/// Total number of unconfirmed messages at the lane (see
+	/// `InboundLaneData::total_unconfirmed_messages`).
 	pub total_messages: MessageNonce,
 }
 
+/// Outbound messages and storage keys that need to be proved to deliver these messages to the
+/// bridged chain. Returned by the `OutboundMessagesApi::outbound_messages` runtime API.
+#[derive(Clone, Default, Encode, Decode, RuntimeDebug, PartialEq, Eq)]
+pub struct OutboundMessagesWithStorageKeys {
+	/// Nonces and payloads of outbound messages, sorted by nonce.
+	pub messages: Vec<(MessageNonce, MessagePayload)>,
+	/// Storage keys of the messages and (optionally) of the outbound lane state.
+	pub storage_keys: Vec<Vec<u8>>,
+}
+
 /// Outbound lane data.
 #[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
 pub struct OutboundLaneData {
@@ -198,6 +499,8 @@ pub struct OutboundLaneData {
 	pub latest_received_nonce: MessageNonce,
 	/// Nonce of latest message, generated by us.
 	pub latest_generated_nonce: MessageNonce,
+	/// State of the outbound lane. New messages are only accepted by the `Opened` lane.
+	pub state: LaneState,
 }
 
 impl Default for OutboundLaneData {
@@ -207,10 +510,126 @@ impl Default for OutboundLaneData {
 			oldest_unpruned_nonce: 1,
 			latest_received_nonce: 0,
 			latest_generated_nonce: 0,
+			state: LaneState::Opened,
 		}
 	}
 }
 
+impl OutboundLaneData {
+	/// Returns true if all messages, sent over the lane, are delivered and confirmed.
+	pub fn is_drained(&self) -> bool {
+		self.latest_received_nonce == self.latest_generated_nonce
+	}
+}
+
+/// Reason why the messages proof or the messages delivery proof has been rejected.
+///
+/// Every reason (except the `Other`) has the matching error in the messages pallet, so the
+/// failure may be diagnosed from the `ExtrinsicFailed` event alone.
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum ProofRejectionReason {
+	/// The bridged header, used to build the proof, is unknown to this chain.
+	UnknownHeader,
+	/// The storage proof doesn't match the storage root of the bridged header.
+	StorageRootMismatch,
+	/// The proof contains neither messages, nor lane state.
+	Empty,
+	/// The number of messages in the proof doesn't match the declared value.
+	MessagesCountMismatch,
+	/// The message is missing from the proof.
+	MissingMessage,
+	/// The message is in the proof, but it can't be decoded.
+	FailedToDecodeMessage,
+	/// The lane state is missing from the proof.
+	MissingLaneState,
+	/// The lane state is in the proof, but it can't be decoded.
+	FailedToDecodeLaneState,
+	/// The storage proof has duplicate or unused nodes.
+	ExtraProofNodes,
+	/// Other (chain-specific) reason.
+	Other(&'static str),
+}
+
+impl From<&'static str> for ProofRejectionReason {
+	fn from(reason: &'static str) -> Self {
+		ProofRejectionReason::Other(reason)
+	}
+}
+
+impl From<ProofRejectionReason> for &'static str {
+	fn from(reason: ProofRejectionReason) -> &'static str {
+		match reason {
+			ProofRejectionReason::UnknownHeader => "Bridged header is unknown",
+			ProofRejectionReason::StorageRootMismatch => "Storage proof doesn't match bridged header storage root",
+			ProofRejectionReason::Empty => "Messages proof is empty",
+			ProofRejectionReason::MessagesCountMismatch => "Declared messages count doesn't match actual value",
+			ProofRejectionReason::MissingMessage => "Message is missing from the proof",
+			ProofRejectionReason::FailedToDecodeMessage => "Failed to decode message from the proof",
+			ProofRejectionReason::MissingLaneState => "Lane state is missing from the proof",
+			ProofRejectionReason::FailedToDecodeLaneState => "Failed to decode lane state from the proof",
+			ProofRejectionReason::ExtraProofNodes => "Storage proof has duplicate or unused nodes",
+			ProofRejectionReason::Other(reason) => reason,
+		}
+	}
+}
+
+sp_api::decl_runtime_apis! {
+	/// Chain-agnostic API for reading outbound messages, that is used to build messages proofs.
+	///
+	/// The runtime may host several messages pallet instances. The `instance` argument of every
+	/// method selects the instance that is queried. Methods return an error if the instance is unknown.
+	pub trait OutboundMessagesApi {
+		/// Returns payloads of messages in the inclusive `begin..=end` nonce range, along with storage
+		/// keys that need to be proved to deliver these messages. Messages that are already pruned or
+		/// not yet generated are not included.
+		///
+		/// If `include_outbound_lane_state` is true, the key of the outbound lane state is also returned.
+		fn outbound_messages(
+			instance: ChainId,
+			lane: LaneId,
+			begin: MessageNonce,
+			end: MessageNonce,
+			include_outbound_lane_state: bool,
+		) -> Result<OutboundMessagesWithStorageKeys, UnknownBridgeInstance>;
+	}
+
+	/// Chain-agnostic API for reading inbound lane state.
+	///
+	/// The runtime may host several messages pallet instances. The `instance` argument of every
+	/// method selects the instance that is queried. Methods return an error if the instance is unknown.
+	pub trait InboundLaneApi<InboundRelayer> where InboundRelayer: codec::Codec {
+		/// Returns decoded state of the inbound lane, including all unrewarded relayers entries. If the
+		/// lane is unknown, the default (empty) lane state is returned.
+		fn inbound_lane_data(
+			instance: ChainId,
+			lane: LaneId,
+		) -> Result<InboundLaneData<InboundRelayer>, UnknownBridgeInstance>;
+	}
+
+	/// Chain-agnostic API for reading lane statistics.
+	///
+	/// The runtime may host several messages pallet instances. The `instance` argument of every
+	/// method selects the instance that is queried. Methods return an error if the instance is unknown.
+	pub trait LaneStatisticsApi<OutboundMessageFee> where OutboundMessageFee: codec::Codec {
+		/// Returns statistics of the lane. If the lane is unknown, zero statistics is returned.
+		fn lane_statistics(
+			instance: ChainId,
+			lane: LaneId,
+		) -> Result<LaneStatistics<OutboundMessageFee>, UnknownBridgeInstance>;
+	}
+
+	/// Chain-agnostic API for reading congestion of outbound lanes.
+	///
+	/// Message senders may use this API to back off when the bridge is saturated. The runtime may
+	/// host several messages pallet instances. The `instance` argument of every method selects the
+	/// instance that is queried. Methods return an error if the instance is unknown.
+	pub trait LaneCongestionApi {
+		/// Returns congestion of the outbound lane. If the lane is unknown, the lane without queued
+		/// messages is returned.
+		fn lane_congestion(instance: ChainId, lane: LaneId) -> Result<LaneCongestion, UnknownBridgeInstance>;
+	}
+}
+
 /// Returns total number of messages in the `InboundLaneData::relayers` vector.
 ///
 /// Returns `None` if there are more messages that `MessageNonce` may fit (i.e. `MessageNonce + 1`).
@@ -232,6 +651,16 @@ pub fn total_unrewarded_messages<RelayerId>(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use codec::DecodeAll;
+
+	fn assert_wire_format<T: Encode + Decode + PartialEq + std::fmt::Debug>(value: T, expected_encoding: Vec<u8>) {
+		assert_eq!(value.encode(), expected_encoding);
+		assert_eq!(T::decode_all(&expected_encoding[..]).ok(), Some(value));
+
+		let mut encoding_with_unknown_field = expected_encoding;
+		encoding_with_unknown_field.push(0);
+		assert!(T::decode_all(&encoding_with_unknown_field[..]).is_err());
+	}
 
 	#[test]
 	fn total_unrewarded_messages_does_not_overflow() {
@@ -245,12 +674,30 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn lane_state_transitions_are_restricted() {
+		use LaneState::*;
+
+		assert!(Opened.can_change_to(Closing));
+		assert!(Closing.can_change_to(Opened));
+		assert!(Closing.can_change_to(Closed));
+
+		assert!(!Opened.can_change_to(Opened));
+		assert!(!Opened.can_change_to(Closed));
+		assert!(!Closing.can_change_to(Closing));
+		assert!(!Closed.can_change_to(Opened));
+		assert!(!Closed.can_change_to(Closing));
+		assert!(!Closed.can_change_to(Closed));
+	}
+
 	#[test]
 	fn inbound_lane_data_returns_correct_hint() {
-		let expected_size = InboundLaneData::<u8>::encoded_size_hint(1, 13);
+		let expected_size = InboundLaneData::<u8>::encoded_size_hint(1, 13, 5);
 		let actual_size = InboundLaneData {
 			relayers: (1u8..=13u8).map(|i| (i as _, i as _, i)).collect(),
 			last_confirmed_nonce: 13,
+			undispatched_messages: (9..=13).collect(),
+			..Default::default()
 		}
 		.encode()
 		.len();
@@ -262,4 +709,244 @@ mod tests {
 			expected_size,
 		);
 	}
+
+	#[test]
+	fn operating_mode_wire_format_is_stable() {
+		assert_wire_format(OperatingMode::Normal, vec![0]);
+		assert_wire_format(OperatingMode::RejectingOutboundMessages, vec![1]);
+		assert_wire_format(OperatingMode::Halted, vec![2]);
+	}
+
+	#[test]
+	fn lane_state_wire_format_is_stable() {
+		assert_wire_format(LaneState::Opened, vec![0]);
+		assert_wire_format(LaneState::Closing, vec![1]);
+		assert_wire_format(LaneState::Closed, vec![2]);
+	}
+
+	#[test]
+	fn lane_ordering_wire_format_is_stable() {
+		assert_wire_format(LaneOrdering::Ordered, vec![0]);
+		assert_wire_format(LaneOrdering::Unordered, vec![1]);
+	}
+
+	#[test]
+	fn dispatch_mode_wire_format_is_stable() {
+		assert_wire_format(DispatchMode::Immediate, vec![0]);
+		assert_wire_format(DispatchMode::Deferred, vec![1]);
+	}
+
+	#[test]
+	fn lane_info_wire_format_is_stable() {
+		assert_wire_format(
+			LaneInfo {
+				owner: 1u8,
+				opened_at: 2u32,
+				max_pending_messages: 3,
+			},
+			vec![
+				// owner
+				1, // opened_at
+				2, 0, 0, 0, // max_pending_messages
+				3, 0, 0, 0, 0, 0, 0, 0,
+			],
+		);
+	}
+
+	#[test]
+	fn message_limits_wire_format_is_stable() {
+		assert_wire_format(
+			MessageLimits {
+				max_payload_size: 1,
+				max_dispatch_weight: 2,
+			},
+			vec![
+				// max_payload_size
+				1, 0, 0, 0, // max_dispatch_weight
+				2, 0, 0, 0, 0, 0, 0, 0,
+			],
+		);
+	}
+
+	#[test]
+	fn versioned_message_payload_wire_format_is_stable() {
+		assert_wire_format(
+			VersionedMessagePayload {
+				version: 1,
+				payload: vec![2, 3],
+			},
+			vec![
+				// version
+				1, // payload
+				8, 2, 3,
+			],
+		);
+	}
+
+	#[test]
+	fn versioned_message_payload_is_decoded() {
+		let encoded_envelope = VersionedMessagePayload::encode_payload(&42u32);
+		assert_eq!(
+			VersionedMessagePayload::decode_payload::<u32>(&encoded_envelope),
+			Ok(42)
+		);
+
+		let mut encoded_envelope_of_unknown_version = encoded_envelope.clone();
+		encoded_envelope_of_unknown_version[0] = CURRENT_MESSAGE_PAYLOAD_VERSION + 1;
+		assert_eq!(
+			VersionedMessagePayload::decode_payload::<u32>(&encoded_envelope_of_unknown_version),
+			Err(MessagePayloadDecodeError::UnknownVersion(
+				CURRENT_MESSAGE_PAYLOAD_VERSION + 1
+			)),
+		);
+
+		assert_eq!(
+			VersionedMessagePayload::decode_payload::<u64>(&encoded_envelope),
+			Err(MessagePayloadDecodeError::InvalidPayload),
+		);
+		assert_eq!(
+			VersionedMessagePayload::decode_payload::<u32>(&[]),
+			Err(MessagePayloadDecodeError::InvalidEnvelope),
+		);
+	}
+
+	#[test]
+	fn lane_congestion_wire_format_is_stable() {
+		assert_wire_format(
+			LaneCongestion {
+				queued_messages: 1,
+				soft_cap: 2,
+				is_congested: true,
+			},
+			vec![
+				// queued_messages
+				1, 0, 0, 0, 0, 0, 0, 0, // soft_cap
+				2, 0, 0, 0, 0, 0, 0, 0, // is_congested
+				1,
+			],
+		);
+	}
+
+	#[test]
+	fn lane_statistics_wire_format_is_stable() {
+		assert_wire_format(
+			LaneStatistics::<u64> {
+				sent_messages: 1,
+				delivered_messages: 2,
+				confirmed_messages: 3,
+				collected_fees: 4,
+				paid_rewards: 5,
+			},
+			vec![
+				// sent_messages
+				1, 0, 0, 0, 0, 0, 0, 0, // delivered_messages
+				2, 0, 0, 0, 0, 0, 0, 0, // confirmed_messages
+				3, 0, 0, 0, 0, 0, 0, 0, // collected_fees
+				4, 0, 0, 0, 0, 0, 0, 0, // paid_rewards
+				5, 0, 0, 0, 0, 0, 0, 0,
+			],
+		);
+	}
+
+	#[test]
+	fn sender_quota_wire_format_is_stable() {
+		assert_wire_format(
+			SenderQuota::<u32> {
+				period: 1,
+				max_messages: 2,
+				max_weight: 3,
+			},
+			vec![
+				// period
+				1, 0, 0, 0, // max_messages
+				2, 0, 0, 0, 0, 0, 0, 0, // max_weight
+				3, 0, 0, 0, 0, 0, 0, 0,
+			],
+		);
+	}
+
+	#[test]
+	fn message_key_and_data_wire_format_is_stable() {
+		assert_wire_format(
+			MessageKey {
+				lane_id: *b"test",
+				nonce: 1,
+			},
+			vec![b't', b'e', b's', b't', 1, 0, 0, 0, 0, 0, 0, 0],
+		);
+		assert_wire_format(
+			MessageData {
+				payload: vec![42],
+				fee: 5u64,
+			},
+			vec![4, 42, 5, 0, 0, 0, 0, 0, 0, 0],
+		);
+	}
+
+	#[test]
+	fn inbound_lane_data_wire_format_is_stable() {
+		assert_wire_format(
+			InboundLaneData {
+				relayers: vec![(1, 2, 3u8)].into_iter().collect(),
+				last_confirmed_nonce: 4,
+				state: LaneState::Closed,
+				undispatched_messages: vec![5],
+			},
+			vec![
+				// relayers: compact length, begin nonce, end nonce and relayer id of the single entry
+				4, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, // last_confirmed_nonce
+				4, 0, 0, 0, 0, 0, 0, 0, // state
+				2, // undispatched_messages: compact length and the single nonce
+				4, 5, 0, 0, 0, 0, 0, 0, 0,
+			],
+		);
+	}
+
+	#[test]
+	fn outbound_lane_data_wire_format_is_stable() {
+		assert_wire_format(
+			OutboundLaneData {
+				oldest_unpruned_nonce: 1,
+				latest_received_nonce: 2,
+				latest_generated_nonce: 3,
+				state: LaneState::Closing,
+			},
+			vec![
+				// oldest_unpruned_nonce, latest_received_nonce and latest_generated_nonce
+				1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, // state
+				1,
+			],
+		);
+	}
+
+	#[test]
+	fn runtime_api_structures_wire_format_is_stable() {
+		assert_wire_format(
+			MessageDetails {
+				nonce: 1,
+				dispatch_weight: 2,
+				size: 3,
+				delivery_and_dispatch_fee: 4u64,
+				dispatch_fee_payment: DispatchFeePayment::AtTargetChain,
+			},
+			vec![
+				1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 1,
+			],
+		);
+		assert_wire_format(
+			UnrewardedRelayersState {
+				unrewarded_relayer_entries: 1,
+				messages_in_oldest_entry: 2,
+				total_messages: 3,
+			},
+			vec![1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0],
+		);
+		assert_wire_format(
+			OutboundMessagesWithStorageKeys {
+				messages: vec![(1, vec![2])],
+				storage_keys: vec![vec![3]],
+			},
+			vec![4, 1, 0, 0, 0, 0, 0, 0, 0, 4, 2, 4, 4, 3],
+		);
+	}
 }
@@ -16,11 +16,11 @@
 
 //! Primitives of messages module, that are used on the source chain.
 
-use crate::{InboundLaneData, LaneId, MessageNonce, OutboundLaneData};
+use crate::{InboundLaneData, LaneId, MessageNonce, OutboundLaneData, ProofRejectionReason};
 
 use bp_runtime::Size;
-use frame_support::{Parameter, RuntimeDebug};
-use sp_std::{collections::btree_map::BTreeMap, fmt::Debug};
+use frame_support::{weights::Weight, Parameter, RuntimeDebug};
+use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, marker::PhantomData, ops::RangeInclusive, prelude::*};
 
 /// The sender of the message on the source chain.
 pub type Sender<AccountId> = frame_system::RawOrigin<AccountId>;
@@ -44,10 +44,12 @@ pub struct RelayerRewards<Balance> {
 /// that's stuck) and/or processing messages without paying fees.
 pub trait TargetHeaderChain<Payload, AccountId> {
 	/// Error type.
-	type Error: Debug + Into<&'static str>;
+	type Error: Debug + Into<&'static str> + Into<ProofRejectionReason>;
 
 	/// Proof that messages have been received by target chain.
 	type MessagesDeliveryProof: Parameter + Size;
+	/// Proof that messages of several lanes have been received by target chain.
+	type MessagesDeliveryBatchProof: Parameter + Size;
 
 	/// Verify message payload before we accept it.
 	///
@@ -67,6 +69,103 @@ pub trait TargetHeaderChain<Payload, AccountId> {
 	fn verify_messages_delivery_proof(
 		proof: Self::MessagesDeliveryProof,
 	) -> Result<(LaneId, InboundLaneData<AccountId>), Self::Error>;
+
+	/// Verify messages delivery proof and return lane && its inbound state, along with the number
+	/// of trie nodes that have been traversed during verification (if it is a trie proof).
+	fn verify_messages_delivery_proof_with_traversed_nodes(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<((LaneId, InboundLaneData<AccountId>), Option<u32>), Self::Error> {
+		Self::verify_messages_delivery_proof(proof).map(|lane| (lane, None))
+	}
+
+	/// Verify messages delivery proof of several lanes and return ids and states of all proved
+	/// lanes.
+	fn verify_messages_delivery_batch_proof(
+		proof: Self::MessagesDeliveryBatchProof,
+	) -> Result<Vec<(LaneId, InboundLaneData<AccountId>)>, Self::Error>;
+}
+
+/// Verifier of messages that are sent to the target chain.
+///
+/// It is a part of `TargetHeaderChain` that doesn't depend on the way how messages delivery is
+/// proved (see `TargetHeaderChainAdapter`).
+pub trait ChainMessageVerifier<Payload> {
+	/// Verify message payload before we accept it (see `TargetHeaderChain::verify_message`).
+	fn verify_message(payload: &Payload) -> Result<(), &'static str>;
+}
+
+/// Verifier of messages delivery proofs.
+///
+/// It is a part of `TargetHeaderChain` that only cares about the way how messages delivery is
+/// proved. So the proof may be a storage proof of Substrate-based chain, an event (log) proof of
+/// EVM-based chain or anything else, while the outbound lane accounting is the same for all lanes
+/// (see `TargetHeaderChainAdapter`).
+pub trait MessagesDeliveryProofVerifier<AccountId> {
+	/// Error type.
+	type Error: Debug + Into<&'static str> + Into<ProofRejectionReason>;
+
+	/// Proof that messages have been received by target chain.
+	type MessagesDeliveryProof: Parameter + Size;
+	/// Proof that messages of several lanes have been received by target chain.
+	type MessagesDeliveryBatchProof: Parameter + Size;
+
+	/// Verify messages delivery proof and return lane && its inbound state.
+	fn verify_messages_delivery_proof(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<(LaneId, InboundLaneData<AccountId>), Self::Error>;
+
+	/// Verify messages delivery proof and return lane && its inbound state, along with the number
+	/// of trie nodes that have been traversed during verification (if it is a trie proof).
+	fn verify_messages_delivery_proof_with_traversed_nodes(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<((LaneId, InboundLaneData<AccountId>), Option<u32>), Self::Error> {
+		Self::verify_messages_delivery_proof(proof).map(|lane| (lane, None))
+	}
+
+	/// Verify messages delivery proof of several lanes and return ids and states of all proved
+	/// lanes.
+	fn verify_messages_delivery_batch_proof(
+		proof: Self::MessagesDeliveryBatchProof,
+	) -> Result<Vec<(LaneId, InboundLaneData<AccountId>)>, Self::Error>;
+}
+
+/// `TargetHeaderChain` implementation that is built from separate message verifier and messages
+/// delivery proof verifier.
+pub struct TargetHeaderChainAdapter<MessageVerifier, ProofVerifier>(PhantomData<(MessageVerifier, ProofVerifier)>);
+
+impl<Payload, AccountId, MessageVerifier, ProofVerifier> TargetHeaderChain<Payload, AccountId>
+	for TargetHeaderChainAdapter<MessageVerifier, ProofVerifier>
+where
+	MessageVerifier: ChainMessageVerifier<Payload>,
+	ProofVerifier: MessagesDeliveryProofVerifier<AccountId>,
+	ProofVerifier::Error: From<&'static str>,
+{
+	type Error = ProofVerifier::Error;
+
+	type MessagesDeliveryProof = ProofVerifier::MessagesDeliveryProof;
+	type MessagesDeliveryBatchProof = ProofVerifier::MessagesDeliveryBatchProof;
+
+	fn verify_message(payload: &Payload) -> Result<(), Self::Error> {
+		MessageVerifier::verify_message(payload).map_err(Into::into)
+	}
+
+	fn verify_messages_delivery_proof(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<(LaneId, InboundLaneData<AccountId>), Self::Error> {
+		ProofVerifier::verify_messages_delivery_proof(proof)
+	}
+
+	fn verify_messages_delivery_proof_with_traversed_nodes(
+		proof: Self::MessagesDeliveryProof,
+	) -> Result<((LaneId, InboundLaneData<AccountId>), Option<u32>), Self::Error> {
+		ProofVerifier::verify_messages_delivery_proof_with_traversed_nodes(proof)
+	}
+
+	fn verify_messages_delivery_batch_proof(
+		proof: Self::MessagesDeliveryBatchProof,
+	) -> Result<Vec<(LaneId, InboundLaneData<AccountId>)>, Self::Error> {
+		ProofVerifier::verify_messages_delivery_batch_proof(proof)
+	}
 }
 
 /// Lane message verifier.
@@ -116,6 +215,14 @@ pub trait MessageDeliveryAndDispatchPayment<AccountId, Balance> {
 		relayer_fund_account: &AccountId,
 	) -> Result<(), Self::Error>;
 
+	/// Return delivery_and_dispatch_fee of the cancelled message from the relayers-fund account
+	/// back to the message submitter.
+	fn refund_delivery_and_dispatch_fee(
+		submitter: &AccountId,
+		fee: &Balance,
+		relayer_fund_account: &AccountId,
+	) -> Result<(), Self::Error>;
+
 	/// Pay rewards for delivering messages to the given relayers.
 	///
 	/// The implementation may also choose to pay reward to the `confirmation_relayer`, which is
@@ -135,6 +242,48 @@ pub trait MessageDeliveryAndDispatchPayment<AccountId, Balance> {
 	}
 }
 
+/// Handler for messages delivery confirmation.
+///
+/// It may be used by the pallet that is sending messages (e.g. by the token bridge pallet) to
+/// finalize or roll back its state once it knows that the message has been delivered to the
+/// bridged chain.
+pub trait OnDeliveryConfirmed {
+	/// Called when we receive confirmation that messages in the inclusive range have been delivered
+	/// to the bridged chain.
+	///
+	/// Messages that have been skipped by relayers at the lane with `LaneOrdering::Unordered` are
+	/// never reported. So the handler may be called several times for the single confirmation - once
+	/// for every range of delivered messages.
+	///
+	/// The handler is called from the `receive_messages_delivery_proof` call and its cost isn't
+	/// covered by the pallet benchmarks. Returns the weight consumed by the handler, which is added
+	/// to the actual weight of the call.
+	fn on_messages_delivered(lane: &LaneId, messages: &RangeInclusive<MessageNonce>) -> Weight;
+}
+
+impl OnDeliveryConfirmed for () {
+	fn on_messages_delivered(_lane: &LaneId, _messages: &RangeInclusive<MessageNonce>) -> Weight {
+		0
+	}
+}
+
+/// Messages bridge API to be used from other pallets of the same runtime.
+pub trait MessagesBridge<AccountId, Balance, Payload> {
+	/// Error type.
+	type Error: Debug;
+
+	/// Send message over the bridge.
+	///
+	/// Unlike regular message submission, there's no user paying for the message. Instead, the
+	/// delivery and dispatch fee is paid from the pallets fee pot account, and this account is
+	/// treated as the message submitter. Returns nonce of the accepted message.
+	fn send_message_from_pallet(
+		lane: LaneId,
+		message: Payload,
+		delivery_and_dispatch_fee: Balance,
+	) -> Result<MessageNonce, Self::Error>;
+}
+
 /// Structure that may be used in place of `TargetHeaderChain`, `LaneMessageVerifier` and
 /// `MessageDeliveryAndDispatchPayment` on chains, where outbound messages are forbidden.
 pub struct ForbidOutboundMessages;
@@ -146,6 +295,7 @@ impl<Payload, AccountId> TargetHeaderChain<Payload, AccountId> for ForbidOutboun
 	type Error = &'static str;
 
 	type MessagesDeliveryProof = ();
+	type MessagesDeliveryBatchProof = ();
 
 	fn verify_message(_payload: &Payload) -> Result<(), Self::Error> {
 		Err(ALL_OUTBOUND_MESSAGES_REJECTED)
@@ -156,6 +306,12 @@ impl<Payload, AccountId> TargetHeaderChain<Payload, AccountId> for ForbidOutboun
 	) -> Result<(LaneId, InboundLaneData<AccountId>), Self::Error> {
 		Err(ALL_OUTBOUND_MESSAGES_REJECTED)
 	}
+
+	fn verify_messages_delivery_batch_proof(
+		_proof: Self::MessagesDeliveryBatchProof,
+	) -> Result<Vec<(LaneId, InboundLaneData<AccountId>)>, Self::Error> {
+		Err(ALL_OUTBOUND_MESSAGES_REJECTED)
+	}
 }
 
 impl<Submitter, Payload, Fee> LaneMessageVerifier<Submitter, Payload, Fee> for ForbidOutboundMessages {
@@ -183,6 +339,14 @@ impl<AccountId, Balance> MessageDeliveryAndDispatchPayment<AccountId, Balance> f
 		Err(ALL_OUTBOUND_MESSAGES_REJECTED)
 	}
 
+	fn refund_delivery_and_dispatch_fee(
+		_submitter: &AccountId,
+		_fee: &Balance,
+		_relayer_fund_account: &AccountId,
+	) -> Result<(), Self::Error> {
+		Err(ALL_OUTBOUND_MESSAGES_REJECTED)
+	}
+
 	fn pay_relayers_rewards(
 		_confirmation_relayer: &AccountId,
 		_relayers_rewards: RelayersRewards<AccountId, Balance>,
@@ -16,10 +16,13 @@
 
 //! Primitives of messages module, that are used on the target chain.
 
-use crate::{LaneId, Message, MessageData, MessageKey, OutboundLaneData};
+use crate::{
+	LaneId, Message, MessageData, MessageKey, MessagePayloadDecodeError, OutboundLaneData, ProofRejectionReason,
+	VersionedMessagePayload,
+};
 
 use bp_runtime::Size;
-use codec::{Decode, Encode, Error as CodecError};
+use codec::{Decode, Encode};
 use frame_support::{weights::Weight, Parameter, RuntimeDebug};
 use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, prelude::*};
 
@@ -38,8 +41,8 @@ pub struct ProvedLaneMessages<Message> {
 /// Message data with decoded dispatch payload.
 #[derive(RuntimeDebug)]
 pub struct DispatchMessageData<DispatchPayload, Fee> {
-	/// Result of dispatch payload decoding.
-	pub payload: Result<DispatchPayload, CodecError>,
+	/// Result of dispatch payload decoding (see `VersionedMessagePayload`).
+	pub payload: Result<DispatchPayload, MessagePayloadDecodeError>,
 	/// Message delivery and dispatch fee, paid by the submitter.
 	pub fee: Fee,
 }
@@ -60,7 +63,7 @@ pub struct DispatchMessage<DispatchPayload, Fee> {
 /// that's stuck) and/or processing messages without paying fees.
 pub trait SourceHeaderChain<Fee> {
 	/// Error type.
-	type Error: Debug + Into<&'static str>;
+	type Error: Debug + Into<&'static str> + Into<ProofRejectionReason>;
 
 	/// Proof that messages are sent from source chain. This may also include proof
 	/// of corresponding outbound lane states.
@@ -81,10 +84,22 @@ pub trait SourceHeaderChain<Fee> {
 		proof: Self::MessagesProof,
 		messages_count: u32,
 	) -> Result<ProvedMessages<Message<Fee>>, Self::Error>;
+
+	/// Verify messages proof and return proved messages, along with the number of trie nodes that
+	/// have been traversed during verification.
+	///
+	/// The number of traversed nodes is used to compute the actual weight of the delivery
+	/// transaction. If the proof is not a trie proof, `None` is returned.
+	fn verify_messages_proof_with_traversed_nodes(
+		proof: Self::MessagesProof,
+		messages_count: u32,
+	) -> Result<(ProvedMessages<Message<Fee>>, Option<u32>), Self::Error> {
+		Self::verify_messages_proof(proof, messages_count).map(|messages| (messages, None))
+	}
 }
 
 /// Called when inbound message is received.
-pub trait MessageDispatch<Fee> {
+pub trait MessageDispatch<AccountId, Fee> {
 	/// Decoded message payload type. Valid message may contain invalid payload. In this case
 	/// message is delivered, but dispatch fails. Therefore, two separate types of payload
 	/// (opaque `MessagePayload` used in delivery and this `DispatchPayload` used in dispatch).
@@ -100,7 +115,15 @@ pub trait MessageDispatch<Fee> {
 	///
 	/// It is up to the implementers of this trait to determine whether the message
 	/// is invalid (i.e. improperly encoded, has too large weight, ...) or not.
-	fn dispatch(message: DispatchMessage<Self::DispatchPayload, Fee>);
+	///
+	/// The `relayer_account` is the account of the relayer that has submitted the delivery
+	/// transaction at this chain. If message dispatch fee is paid at this (target) chain, this
+	/// account receives the dispatch fee.
+	///
+	/// Returns unspent dispatch weight - the difference between the weight, returned by the
+	/// `dispatch_weight` and the weight that has actually been spent. This weight is refunded
+	/// to the relayer that has submitted the delivery transaction.
+	fn dispatch(relayer_account: &AccountId, message: DispatchMessage<Self::DispatchPayload, Fee>) -> Weight;
 }
 
 impl<Message> Default for ProvedLaneMessages<Message> {
@@ -124,7 +147,7 @@ impl<DispatchPayload: Decode, Fee> From<Message<Fee>> for DispatchMessage<Dispat
 impl<DispatchPayload: Decode, Fee> From<MessageData<Fee>> for DispatchMessageData<DispatchPayload, Fee> {
 	fn from(data: MessageData<Fee>) -> Self {
 		DispatchMessageData {
-			payload: DispatchPayload::decode(&mut &data.payload[..]),
+			payload: VersionedMessagePayload::decode_payload(&data.payload),
 			fee: data.fee,
 		}
 	}
@@ -149,12 +172,14 @@ impl<Fee> SourceHeaderChain<Fee> for ForbidInboundMessages {
 	}
 }
 
-impl<Fee> MessageDispatch<Fee> for ForbidInboundMessages {
+impl<AccountId, Fee> MessageDispatch<AccountId, Fee> for ForbidInboundMessages {
 	type DispatchPayload = ();
 
 	fn dispatch_weight(_message: &DispatchMessage<Self::DispatchPayload, Fee>) -> Weight {
 		Weight::MAX
 	}
 
-	fn dispatch(_message: DispatchMessage<Self::DispatchPayload, Fee>) {}
+	fn dispatch(_relayer_account: &AccountId, _message: DispatchMessage<Self::DispatchPayload, Fee>) -> Weight {
+		0
+	}
 }
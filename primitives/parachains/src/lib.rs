@@ -0,0 +1,92 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Primitives of the parachains bridge pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{storage::StorageKey, H256};
+use sp_io::hashing::{blake2_256, twox_128, twox_64};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Name of the `Heads` storage map of the `paras` pallet at the relay chain.
+pub const PARAS_HEADS_STORAGE_NAME: &str = "Heads";
+
+/// Parachain identifier. It is encoded the same way as the `ParaId` of Polkadot runtimes.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ParaId(pub u32);
+
+/// Parachain head. It is the encoded parachain header.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone, Default)]
+pub struct ParaHead(pub Vec<u8>);
+
+impl ParaHead {
+	/// Return hash of the parachain head (which is the hash of the parachain header).
+	pub fn hash(&self) -> ParaHash {
+		blake2_256(&self.0).into()
+	}
+}
+
+/// Hash of the parachain head.
+pub type ParaHash = H256;
+
+/// Storage proof of parachain heads, generated at the relay chain block.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone, Default)]
+pub struct ParaHeadsProof(pub Vec<Vec<u8>>);
+
+/// Best known head of the parachain.
+#[derive(Encode, Decode, RuntimeDebug, PartialEq, Eq, Clone, Default)]
+pub struct BestParaHead<RelayBlockNumber> {
+	/// Number of the relay chain block, where this head has been read from.
+	pub at_relay_block_number: RelayBlockNumber,
+	/// Hash of the parachain head.
+	pub head_hash: ParaHash,
+	/// Position of the next head hash in the ring buffer of imported heads hashes.
+	pub next_imported_hash_position: u32,
+}
+
+/// Return storage key of the parachain head in the relay chain storage.
+///
+/// The key is computed for the `Heads` map of the `paras` pallet, which uses `twox_64_concat`
+/// hasher. The `paras_pallet_name` is the name of the pallet in the relay chain runtime.
+pub fn parachain_head_storage_key_at_source(paras_pallet_name: &str, para_id: ParaId) -> StorageKey {
+	let encoded_para_id = para_id.encode();
+	let mut key = Vec::with_capacity(32 + 8 + encoded_para_id.len());
+	key.extend_from_slice(&twox_128(paras_pallet_name.as_bytes()));
+	key.extend_from_slice(&twox_128(PARAS_HEADS_STORAGE_NAME.as_bytes()));
+	key.extend_from_slice(&twox_64(&encoded_para_id));
+	key.extend_from_slice(&encoded_para_id);
+	StorageKey(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parachain_head_storage_key_at_source_is_computed_correctly() {
+		let key = parachain_head_storage_key_at_source("Paras", ParaId(42)).0;
+		assert_eq!(key.len(), 32 + 8 + 4);
+		assert_eq!(&key[..16], &twox_128(b"Paras"));
+		assert_eq!(&key[16..32], &twox_128(b"Heads"));
+		assert_eq!(&key[40..], &42u32.to_le_bytes());
+	}
+}
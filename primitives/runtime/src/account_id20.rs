@@ -0,0 +1,178 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Primitives for chains that are using 20-byte (Ethereum-style) accounts.
+//!
+//! Accounts of such chains (e.g. Frontier-based chains) are identified by the last 20 bytes of
+//! the Keccak-256 hash of the uncompressed secp256k1 public key. Signatures are verified by
+//! recovering the public key from the secp256k1 ECDSA signature of the Keccak-256 hash of the
+//! message.
+
+use codec::{Decode, Encode};
+use sp_core::{ecdsa, H160, H256};
+use sp_io::hashing::keccak_256;
+use sp_runtime::{
+	traits::{Convert, IdentifyAccount, Lazy, Verify},
+	RuntimeDebug,
+};
+
+/// 20-byte (Ethereum-style) account identifier.
+#[derive(Clone, Copy, Decode, Default, Encode, Eq, Hash, Ord, PartialEq, PartialOrd, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountId20(pub [u8; 20]);
+
+impl AccountId20 {
+	/// Compute account identifier from the uncompressed secp256k1 public key (without the
+	/// leading `0x04` byte).
+	pub fn from_uncompressed_public(public: &[u8; 64]) -> Self {
+		let mut account = [0u8; 20];
+		account.copy_from_slice(&keccak_256(public)[12..]);
+		AccountId20(account)
+	}
+}
+
+impl sp_std::fmt::Display for AccountId20 {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "0x{}", sp_core::hexdisplay::HexDisplay::from(&self.0))
+	}
+}
+
+impl From<[u8; 20]> for AccountId20 {
+	fn from(account: [u8; 20]) -> Self {
+		AccountId20(account)
+	}
+}
+
+impl From<H160> for AccountId20 {
+	fn from(account: H160) -> Self {
+		AccountId20(account.to_fixed_bytes())
+	}
+}
+
+impl From<AccountId20> for H160 {
+	fn from(account: AccountId20) -> Self {
+		H160(account.0)
+	}
+}
+
+impl AsRef<[u8]> for AccountId20 {
+	fn as_ref(&self) -> &[u8] {
+		&self.0[..]
+	}
+}
+
+/// Account of the 20-byte accounts chain is also the account "public" key, because the public
+/// key is recovered from the signature itself.
+impl IdentifyAccount for AccountId20 {
+	type AccountId = Self;
+
+	fn into_account(self) -> Self {
+		self
+	}
+}
+
+/// Ethereum-style secp256k1 ECDSA signature.
+///
+/// The signature is created over the Keccak-256 hash of the message (as opposed to the regular
+/// Substrate ECDSA signature, which is created over the Blake2-256 hash).
+#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug)]
+pub struct EthereumSignature(pub ecdsa::Signature);
+
+impl From<ecdsa::Signature> for EthereumSignature {
+	fn from(signature: ecdsa::Signature) -> Self {
+		EthereumSignature(signature)
+	}
+}
+
+impl Verify for EthereumSignature {
+	type Signer = AccountId20;
+
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &AccountId20) -> bool {
+		let signature: &[u8; 65] = self.0.as_ref();
+		let message_hash = keccak_256(msg.get());
+		match sp_io::crypto::secp256k1_ecdsa_recover(signature, &message_hash) {
+			Ok(public) => AccountId20::from_uncompressed_public(&public) == *signer,
+			Err(_) => false,
+		}
+	}
+}
+
+/// Converts 256-bit hash (produced by the `derive_account_id` function) into 20-byte account
+/// identifier.
+///
+/// The first 20 bytes of the hash are used as an account identifier.
+pub struct AccountId20Converter;
+
+impl Convert<H256, AccountId20> for AccountId20Converter {
+	fn convert(hash: H256) -> AccountId20 {
+		let mut account = [0u8; 20];
+		account.copy_from_slice(&hash.as_bytes()[..20]);
+		AccountId20(account)
+	}
+}
+
+/// Sign given message with Ethereum-style signature and return signer account.
+#[cfg(feature = "std")]
+pub fn sign_ethereum_message(pair: &ecdsa::Pair, message: &[u8]) -> (AccountId20, EthereumSignature) {
+	let signature = pair.sign_prehashed(&keccak_256(message));
+	let public = sp_io::crypto::secp256k1_ecdsa_recover(signature.as_ref(), &keccak_256(message))
+		.expect("signature has been created by the valid key pair; qed");
+	(
+		AccountId20::from_uncompressed_public(&public),
+		EthereumSignature(signature),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::Pair;
+
+	fn test_pair() -> ecdsa::Pair {
+		let mut seed = [0u8; 32];
+		seed[31] = 1;
+		ecdsa::Pair::from_seed(&seed)
+	}
+
+	#[test]
+	fn account_is_derived_from_public_key() {
+		// the well-known address of the private key `0x00..01`
+		let (account, _) = sign_ethereum_message(&test_pair(), b"message");
+		assert_eq!(account.to_string(), "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf");
+	}
+
+	#[test]
+	fn ethereum_signature_is_verified() {
+		let (account, signature) = sign_ethereum_message(&test_pair(), b"message");
+		assert!(signature.verify(&b"message"[..], &account));
+		assert!(!signature.verify(&b"other message"[..], &account));
+		assert!(!signature.verify(&b"message"[..], &AccountId20([1u8; 20])));
+	}
+
+	#[test]
+	fn substrate_ecdsa_signature_is_rejected() {
+		let pair = test_pair();
+		let (account, _) = sign_ethereum_message(&pair, b"message");
+		let signature = EthereumSignature(pair.sign(b"message"));
+		assert!(!signature.verify(&b"message"[..], &account));
+	}
+
+	#[test]
+	fn hash_is_converted_into_account_id20() {
+		let hash = H256::from([42u8; 32]);
+		assert_eq!(AccountId20Converter::convert(hash), AccountId20([42u8; 20]));
+	}
+}
@@ -18,17 +18,22 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::Encode;
+use codec::{Decode, Encode};
+use frame_support::RuntimeDebug;
 use sp_core::hash::H256;
 use sp_io::hashing::blake2_256;
 use sp_std::convert::TryFrom;
 
+pub use account_id20::{AccountId20, AccountId20Converter, EthereumSignature};
 pub use chain::{BlockNumberOf, Chain, HashOf, HasherOf, HeaderOf};
 pub use storage_proof::{Error as StorageProofError, StorageProofChecker};
 
+#[cfg(feature = "std")]
+pub use account_id20::sign_ethereum_message;
 #[cfg(feature = "std")]
 pub use storage_proof::craft_valid_storage_proof;
 
+mod account_id20;
 mod chain;
 mod storage_proof;
 
@@ -70,6 +75,38 @@ pub const ROOT_ACCOUNT_DERIVATION_PREFIX: &[u8] = b"pallet-bridge/account-deriva
 /// Sometimes we need to be able to identify deployed instance dynamically. This type may be used for that.
 pub type ChainId = [u8; 4];
 
+/// Error that is returned by bridge runtime APIs when they're asked to query unknown bridge pallet
+/// instance.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct UnknownBridgeInstance(pub ChainId);
+
+/// Called by bridge runtime APIs when they're asked to query unknown bridge pallet instance.
+///
+/// Bridge runtime APIs are accepting `ChainId` of the pallet instance that needs to be queried. There's
+/// no sane value that could be returned for an unknown instance, so the error is returned instead.
+pub fn unknown_bridge_instance<T>(instance: ChainId) -> Result<T, UnknownBridgeInstance> {
+	Err(UnknownBridgeInstance(instance))
+}
+
+/// Where message dispatch fee is paid?
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum DispatchFeePayment {
+	/// The dispatch fee is paid at the source chain, as a part of the message delivery and
+	/// dispatch fee.
+	AtSourceChain,
+	/// The dispatch fee is paid at the target chain, by the account that is used to dispatch
+	/// the message. The message submitter only pays for message delivery at the source chain.
+	///
+	/// If the dispatch account has not enough funds to pay the fee, the message is not dispatched.
+	AtTargetChain,
+}
+
+impl Default for DispatchFeePayment {
+	fn default() -> Self {
+		DispatchFeePayment::AtSourceChain
+	}
+}
+
 /// Type of accounts on the source chain.
 pub enum SourceAccount<T> {
 	/// An account that belongs to Root (priviledged origin).
@@ -113,6 +150,29 @@ pub fn derive_relayer_fund_account_id(bridge_id: ChainId) -> H256 {
 	("relayer-fund-account", bridge_id).using_encoded(blake2_256).into()
 }
 
+/// Derive the account ID of the pot that pays for messages, sent by other pallets of the runtime.
+///
+/// This account ID can be used to deposit funds that will be spent on delivery and dispatch fees
+/// of messages that are sent by runtime pallets (without any user involved).
+///
+/// The account ID can be the same across different instances of `pallet-bridge-messages` if the same
+/// `bridge_id` is used.
+pub fn derive_pallets_fee_pot_account_id(bridge_id: ChainId) -> H256 {
+	("pallets-fee-pot-account", bridge_id).using_encoded(blake2_256).into()
+}
+
+/// Derive the account ID of the pot that refunds fees of messages delivery transactions.
+///
+/// This account ID can be used to deposit funds (by governance or by message senders) that will be
+/// spent on refunding fees of transactions that are delivering messages to this chain. So relayers
+/// don't need to hold funds on both bridged chains.
+///
+/// The account ID can be the same across different instances of `pallet-bridge-messages` if the same
+/// `bridge_id` is used.
+pub fn derive_relayers_pot_account_id(bridge_id: ChainId) -> H256 {
+	("relayers-pot-account", bridge_id).using_encoded(blake2_256).into()
+}
+
 /// Anything that has size.
 pub trait Size {
 	/// Return approximate size of this object (in bytes).
@@ -18,18 +18,25 @@
 
 use hash_db::{HashDB, Hasher, EMPTY_PREFIX};
 use sp_runtime::RuntimeDebug;
-use sp_std::vec::Vec;
-use sp_trie::{read_trie_value, Layout, MemoryDB, StorageProof};
+use sp_std::{cell::RefCell, collections::btree_set::BTreeSet, vec::Vec};
+use sp_trie::{read_trie_value_with, Layout, MemoryDB, Recorder, StorageProof};
 
 /// This struct is used to read storage values from a subset of a Merklized database. The "proof"
 /// is a subset of the nodes in the Merkle structure of the database, so that it provides
 /// authentication against a known Merkle root as well as the values in the database themselves.
+///
+/// The checker remembers all trie nodes that have been traversed while reading values. So after
+/// all required values are read, the caller may ensure that the proof has no extra nodes (see
+/// `ensure_no_unused_nodes`). This prevents proof stuffing, when submitter pads the proof with
+/// unrelated (or even undecodable) nodes.
 pub struct StorageProofChecker<H>
 where
 	H: Hasher,
 {
+	proof_nodes_count: usize,
 	root: H::Out,
 	db: MemoryDB<H>,
+	traversed_nodes: RefCell<BTreeSet<Vec<u8>>>,
 }
 
 impl<H> StorageProofChecker<H>
@@ -38,28 +45,77 @@ where
 {
 	/// Constructs a new storage proof checker.
 	///
-	/// This returns an error if the given proof is invalid with respect to the given root.
+	/// This returns an error if the given proof is invalid with respect to the given root, or if
+	/// it has duplicate nodes.
 	pub fn new(root: H::Out, proof: StorageProof) -> Result<Self, Error> {
-		let db = proof.into_memory_db();
+		// every node is a separate item of the encoded proof, so duplicate nodes only increase
+		// proof size without any benefit
+		let proof_nodes = proof.iter_nodes().collect::<Vec<_>>();
+		let proof_nodes_count = proof_nodes.len();
+		if proof_nodes.iter().collect::<BTreeSet<_>>().len() != proof_nodes_count {
+			return Err(Error::DuplicateNodesInProof);
+		}
+
+		let db = StorageProof::new(proof_nodes).into_memory_db();
 		if !db.contains(&root, EMPTY_PREFIX) {
 			return Err(Error::StorageRootMismatch);
 		}
 
-		let checker = StorageProofChecker { root, db };
+		let checker = StorageProofChecker {
+			proof_nodes_count,
+			root,
+			db,
+			traversed_nodes: RefCell::new(BTreeSet::new()),
+		};
 		Ok(checker)
 	}
 
 	/// Reads a value from the available subset of storage. If the value cannot be read due to an
 	/// incomplete or otherwise invalid proof, this returns an error.
 	pub fn read_value(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
-		read_trie_value::<Layout<H>, _>(&self.db, &self.root, key).map_err(|_| Error::StorageValueUnavailable)
+		let mut recorder = Recorder::new();
+		let result = read_trie_value_with::<Layout<H>, _, _>(&self.db, &self.root, key, &mut recorder)
+			.map_err(|_| Error::StorageValueUnavailable);
+		self.traversed_nodes
+			.borrow_mut()
+			.extend(recorder.drain().into_iter().map(|record| record.hash.as_ref().to_vec()));
+		result
+	}
+
+	/// Returns number of distinct proof nodes that have been traversed while reading values.
+	///
+	/// The cost of proof verification is proportional to this number, not to the number of
+	/// nodes in the proof.
+	pub fn traversed_nodes_count(&self) -> usize {
+		self.traversed_nodes.borrow().len()
+	}
+
+	/// Ensure that all proof nodes have been traversed while reading values.
+	///
+	/// Every node that has been provided in the proof, but hasn't been used to read any value, is
+	/// either garbage, or an attempt to inflate the proof size (and the cost of the transaction).
+	/// Nodes with non-canonical (or invalid) encoding may never be traversed, so they're also
+	/// rejected here.
+	pub fn ensure_no_unused_nodes(self) -> Result<(), Error> {
+		if self.traversed_nodes_count() != self.proof_nodes_count {
+			return Err(Error::UnusedNodesInProof);
+		}
+
+		Ok(())
 	}
 }
 
+/// Storage proof verification error.
 #[derive(RuntimeDebug, PartialEq)]
 pub enum Error {
+	/// The proof doesn't contain the storage root node.
 	StorageRootMismatch,
+	/// The value can't be read using the proof.
 	StorageValueUnavailable,
+	/// The proof contains duplicate nodes.
+	DuplicateNodesInProof,
+	/// The proof contains nodes that haven't been traversed while reading values.
+	UnusedNodesInProof,
 }
 
 /// Return valid storage proof and state root.
@@ -109,4 +165,43 @@ pub mod tests {
 			Some(Error::StorageRootMismatch)
 		);
 	}
+
+	#[test]
+	fn proof_with_duplicate_nodes_is_rejected() {
+		let (root, proof) = craft_valid_storage_proof();
+		let mut nodes = proof.iter_nodes().collect::<Vec<_>>();
+		nodes.push(nodes[0].clone());
+
+		assert_eq!(
+			<StorageProofChecker<sp_core::Blake2Hasher>>::new(root, StorageProof::new(nodes)).err(),
+			Some(Error::DuplicateNodesInProof),
+		);
+	}
+
+	#[test]
+	fn proof_with_unused_nodes_is_rejected() {
+		let (root, proof) = craft_valid_storage_proof();
+
+		// all nodes are used when all proved keys are read
+		let checker = <StorageProofChecker<sp_core::Blake2Hasher>>::new(root, proof.clone()).unwrap();
+		checker.read_value(b"key1").unwrap();
+		checker.read_value(b"key2").unwrap();
+		checker.read_value(b"key22").unwrap();
+		assert_eq!(checker.ensure_no_unused_nodes(), Ok(()));
+
+		// some nodes are unused when only some keys are read
+		let checker = <StorageProofChecker<sp_core::Blake2Hasher>>::new(root, proof.clone()).unwrap();
+		checker.read_value(b"key2").unwrap();
+		assert_eq!(checker.ensure_no_unused_nodes(), Err(Error::UnusedNodesInProof));
+
+		// garbage nodes are never used
+		let mut nodes = proof.iter_nodes().collect::<Vec<_>>();
+		nodes.push(vec![42u8; 64]);
+		let checker = <StorageProofChecker<sp_core::Blake2Hasher>>::new(root, StorageProof::new(nodes)).unwrap();
+		checker.read_value(b"key1").unwrap();
+		checker.read_value(b"key2").unwrap();
+		checker.read_value(b"key22").unwrap();
+		assert_eq!(checker.traversed_nodes_count() + 1, checker.proof_nodes_count);
+		assert_eq!(checker.ensure_no_unused_nodes(), Err(Error::UnusedNodesInProof));
+	}
 }
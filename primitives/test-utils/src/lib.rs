@@ -153,6 +153,112 @@ pub fn make_justification_for_header<H: HeaderT>(params: JustificationGeneratorP
 	}
 }
 
+/// Malformation that may be applied to the valid GRANDPA justification.
+///
+/// Malformed justification is still structurally valid (i.e. it may be encoded, decoded and passed
+/// to the verifier), but it must be rejected by the verifier. Indices of precommits and votes
+/// ancestries are wrapped, so any index may be used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JustificationMalformation {
+	/// Justification round is changed, so signatures of all precommits become invalid.
+	WrongRound,
+	/// Signature of the precommit with given index is replaced with the signature of the same
+	/// authority, but for another precommit target.
+	ForgedSignature(u32),
+	/// The precommit with given index is replaced with the signed precommit for the parent of the
+	/// commit target.
+	AncestorPrecommit(u32),
+	/// The precommit with given index is replaced with the signed precommit for the header that is
+	/// not a descendant of the commit target. The header is added to the votes ancestries.
+	NonCanonicalAncestry(u32),
+	/// The votes ancestries header with given index is removed from the justification.
+	MissingAncestry(u32),
+	/// The header, that is not used by any precommit, is added to the votes ancestries.
+	ExtraAncestry,
+	/// Precommits are removed from the end of the commit, until their cumulative weight is less
+	/// than the threshold.
+	NotEnoughWeight,
+}
+
+/// Make a valid GRANDPA justification and then apply given malformation to it.
+///
+/// Note: `AncestorPrecommit` requires non-genesis header and `MissingAncestry` requires at least
+/// one votes ancestry.
+pub fn make_malformed_justification<H: HeaderT>(
+	params: JustificationGeneratorParams<H>,
+	malformation: JustificationMalformation,
+) -> GrandpaJustification<H> {
+	let header = params.header.clone();
+	let (round, set_id) = (params.round, params.set_id);
+	let authorities = params.authorities.clone();
+	let mut justification = make_justification_for_header(params);
+	let precommit_index = |index: u32| index as usize % authorities.len();
+
+	match malformation {
+		JustificationMalformation::WrongRound => {
+			justification.round = justification.round.wrapping_add(1);
+		}
+		JustificationMalformation::ForgedSignature(index) => {
+			let index = precommit_index(index);
+			let forged_target = (*header.parent_hash(), *header.number());
+			justification.commit.precommits[index].signature =
+				signed_precommit::<H>(&authorities[index].0, forged_target, round, set_id).signature;
+		}
+		JustificationMalformation::AncestorPrecommit(index) => {
+			assert!(
+				!header.number().is_zero(),
+				"Genesis header has no ancestors that could be precommitted"
+			);
+			let ancestor = (*header.parent_hash(), *header.number() - One::one());
+			justification.commit.precommits[precommit_index(index)] =
+				signed_precommit::<H>(&authorities[precommit_index(index)].0, ancestor, round, set_id);
+		}
+		JustificationMalformation::NonCanonicalAncestry(index) => {
+			// the header shares parent with the commit target, so it is on a different fork
+			let mut fork_header = test_header::<H>(*header.number() + One::one());
+			fork_header.set_parent_hash(*header.parent_hash());
+			fork_header
+				.digest_mut()
+				.logs
+				.push(sp_runtime::DigestItem::Other(b"non-canonical".to_vec()));
+			let fork_target = (fork_header.hash(), *fork_header.number());
+			justification.votes_ancestries.push(fork_header);
+			justification.commit.precommits[precommit_index(index)] =
+				signed_precommit::<H>(&authorities[precommit_index(index)].0, fork_target, round, set_id);
+		}
+		JustificationMalformation::MissingAncestry(index) => {
+			assert!(
+				!justification.votes_ancestries.is_empty(),
+				"Need at least one votes ancestry to remove"
+			);
+			let index = index as usize % justification.votes_ancestries.len();
+			justification.votes_ancestries.remove(index);
+		}
+		JustificationMalformation::ExtraAncestry => {
+			let mut extra_header = test_header::<H>(*header.number() + One::one());
+			extra_header
+				.digest_mut()
+				.logs
+				.push(sp_runtime::DigestItem::Other(b"extra".to_vec()));
+			justification.votes_ancestries.push(extra_header);
+		}
+		JustificationMalformation::NotEnoughWeight => {
+			let voter_set = finality_grandpa::voter_set::VoterSet::new(
+				authorities.iter().map(|(id, weight)| (AuthorityId::from(*id), *weight)),
+			)
+			.expect("Authorities of valid justification form a valid voter set; qed");
+			let threshold: u64 = voter_set.threshold().0.into();
+			let mut cumulative_weight: u64 = authorities.iter().map(|(_, weight)| *weight).sum();
+			while cumulative_weight >= threshold {
+				justification.commit.precommits.pop();
+				cumulative_weight -= authorities[justification.commit.precommits.len()].1;
+			}
+		}
+	}
+
+	justification
+}
+
 fn generate_chain<H: HeaderT>(fork_id: u32, depth: u32, ancestor: &H) -> Vec<H> {
 	let mut headers = vec![ancestor.clone()];
 
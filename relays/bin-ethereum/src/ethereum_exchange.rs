@@ -14,15 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Relaying proofs of PoA -> Substrate exchange transactions.
+//! Relaying proofs of PoA -> Substrate transactions.
 
-use crate::instances::BridgeInstance;
-use crate::rialto_client::{SubmitEthereumExchangeTransactionProof, SubstrateHighLevelRpc};
+use crate::proof_targets::TransactionProofTarget;
+use crate::rialto_client::{SubmitEthereumTransactionProof, SubstrateHighLevelRpc};
 use crate::rpc_errors::RpcError;
 use crate::substrate_types::into_substrate_ethereum_receipt;
 
 use async_trait::async_trait;
-use bp_currency_exchange::MaybeLockFundsTransaction;
 use exchange_relay::exchange::{
 	relay_single_transaction_proof, SourceBlock, SourceClient, SourceTransaction, TargetClient,
 	TransactionProofPipeline,
@@ -30,7 +29,7 @@ use exchange_relay::exchange::{
 use exchange_relay::exchange_loop::{run as run_loop, InMemoryStorage};
 use relay_ethereum_client::{
 	types::{
-		HeaderId as EthereumHeaderId, HeaderWithTransactions as EthereumHeaderWithTransactions,
+		Address, HeaderId as EthereumHeaderId, HeaderWithTransactions as EthereumHeaderWithTransactions,
 		Transaction as EthereumTransaction, TransactionHash as EthereumTransactionHash, H256, HEADER_ID_PROOF,
 	},
 	Client as EthereumClient, ConnectionParams as EthereumConnectionParams,
@@ -41,7 +40,11 @@ use relay_substrate_client::{
 };
 use relay_utils::{metrics::MetricsParams, relay_loop::Client as RelayClient, HeaderId};
 use rialto_runtime::exchange::EthereumTransactionInclusionProof;
-use std::{sync::Arc, time::Duration};
+use sp_runtime::traits::Header as HeaderT;
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
 
 /// Interval at which we ask Ethereum node for updates.
 const ETHEREUM_TICK_INTERVAL: Duration = Duration::from_secs(10);
@@ -55,6 +58,15 @@ pub enum ExchangeRelayMode {
 	Auto(Option<u64>),
 }
 
+/// Filter of Ethereum transactions, which proofs are relayed in auto-relay mode.
+#[derive(Debug, Clone, Default)]
+pub struct EthereumTransactionsFilter {
+	/// If not empty, only transactions that are sent to one of these addresses are relayed.
+	pub recipients: Vec<Address>,
+	/// If not empty, only transactions that have emitted event (log) with one of these topics are relayed.
+	pub event_topics: Vec<H256>,
+}
+
 /// PoA exchange transaction relay params.
 pub struct EthereumExchangeParams {
 	/// Ethereum connection params.
@@ -65,10 +77,12 @@ pub struct EthereumExchangeParams {
 	pub sub_sign: RialtoSigningParams,
 	/// Relay working mode.
 	pub mode: ExchangeRelayMode,
+	/// Filter of relayed transactions.
+	pub filter: EthereumTransactionsFilter,
 	/// Metrics parameters.
 	pub metrics_params: MetricsParams,
-	/// Instance of the bridge pallet being synchronized.
-	pub instance: Arc<dyn BridgeInstance>,
+	/// Pallet that consumes relayed proofs.
+	pub target: Arc<dyn TransactionProofTarget>,
 }
 
 impl std::fmt::Debug for EthereumExchangeParams {
@@ -78,8 +92,9 @@ impl std::fmt::Debug for EthereumExchangeParams {
 			.field("sub_params", &self.sub_params)
 			.field("sub_sign", &sp_core::Pair::public(&self.sub_sign))
 			.field("mode", &self.mode)
+			.field("filter", &self.filter)
 			.field("metrics_params", &self.metrics_params)
-			.field("instance", &self.instance)
+			.field("target", &self.target)
 			.finish()
 	}
 }
@@ -135,6 +150,7 @@ impl SourceTransaction for EthereumSourceTransaction {
 #[derive(Clone)]
 struct EthereumTransactionsSource {
 	client: EthereumClient,
+	filter: EthereumTransactionsFilter,
 }
 
 #[async_trait]
@@ -216,6 +232,51 @@ impl SourceClient<EthereumToSubstrateExchange> for EthereumTransactionsSource {
 			proof: transaction_proof,
 		})
 	}
+
+	async fn is_matching_transaction(&self, block: &EthereumSourceBlock, tx_index: usize) -> Result<bool, RpcError> {
+		let tx = &block.0.transactions[tx_index];
+		if !self.filter.recipients.is_empty() {
+			let is_matching_recipient = tx.to.map(|to| self.filter.recipients.contains(&to)).unwrap_or(false);
+			if !is_matching_recipient {
+				return Ok(false);
+			}
+		}
+
+		if self.filter.event_topics.is_empty() {
+			return Ok(true);
+		}
+
+		let receipt = self.client.transaction_receipt(tx.hash).await?;
+		Ok(receipt
+			.logs
+			.iter()
+			.any(|log| log.topics.iter().any(|topic| self.filter.event_topics.contains(topic))))
+	}
+}
+
+/// Best finalized PoA header, known to the Substrate runtime at some Substrate block.
+///
+/// The runtime is only asked for the best finalized PoA header when the best Substrate block
+/// changes. Cache entry is keyed by the Substrate block hash, so it is also invalidated when
+/// Substrate node switches to another fork.
+#[derive(Debug, Default)]
+struct BestFinalizedHeaderCache {
+	entry: Option<(rialto_runtime::Hash, EthereumHeaderId)>,
+}
+
+impl BestFinalizedHeaderCache {
+	/// Returns cached best finalized PoA header, if it has been read at given Substrate block.
+	fn get(&self, at_block: &rialto_runtime::Hash) -> Option<EthereumHeaderId> {
+		self.entry
+			.as_ref()
+			.filter(|(cached_at_block, _)| cached_at_block == at_block)
+			.map(|(_, best_finalized)| *best_finalized)
+	}
+
+	/// Remember best finalized PoA header that has been read at given Substrate block.
+	fn update(&mut self, at_block: rialto_runtime::Hash, best_finalized: EthereumHeaderId) {
+		self.entry = Some((at_block, best_finalized));
+	}
 }
 
 /// Substrate node as transactions proof target.
@@ -223,7 +284,47 @@ impl SourceClient<EthereumToSubstrateExchange> for EthereumTransactionsSource {
 struct SubstrateTransactionsTarget {
 	client: SubstrateClient<Rialto>,
 	sign_params: RialtoSigningParams,
-	bridge_instance: Arc<dyn BridgeInstance>,
+	target: Arc<dyn TransactionProofTarget>,
+	best_finalized_cache: Arc<Mutex<BestFinalizedHeaderCache>>,
+}
+
+impl SubstrateTransactionsTarget {
+	/// Create new transactions proof target.
+	fn new(
+		client: SubstrateClient<Rialto>,
+		sign_params: RialtoSigningParams,
+		target: Arc<dyn TransactionProofTarget>,
+	) -> Self {
+		SubstrateTransactionsTarget {
+			client,
+			sign_params,
+			target,
+			best_finalized_cache: Default::default(),
+		}
+	}
+
+	/// Returns best finalized PoA header, known to the Substrate runtime at the best Substrate block.
+	async fn best_ethereum_finalized_block(&self) -> Result<EthereumHeaderId, RpcError> {
+		let best_substrate_block = self.client.best_header().await?.hash();
+		let cached_best_finalized = self
+			.best_finalized_cache
+			.lock()
+			.expect("cache mutex is never poisoned; qed")
+			.get(&best_substrate_block);
+		if let Some(best_finalized) = cached_best_finalized {
+			return Ok(best_finalized);
+		}
+
+		let best_finalized = self
+			.client
+			.best_ethereum_finalized_block_at(best_substrate_block)
+			.await?;
+		self.best_finalized_cache
+			.lock()
+			.expect("cache mutex is never poisoned; qed")
+			.update(best_substrate_block, best_finalized);
+		Ok(best_finalized)
+	}
 }
 
 #[async_trait]
@@ -254,7 +355,7 @@ impl TargetClient<EthereumToSubstrateExchange> for SubstrateTransactionsTarget {
 		//
 		// the fix is easy, but since this code is mostly developed for demonstration purposes,
 		// I'm leaving this KISS-based design here
-		let best_finalized_ethereum_block = self.client.best_ethereum_finalized_block().await?;
+		let best_finalized_ethereum_block = self.best_ethereum_finalized_block().await?;
 		Ok(id.0 <= best_finalized_ethereum_block.0)
 	}
 
@@ -263,33 +364,26 @@ impl TargetClient<EthereumToSubstrateExchange> for SubstrateTransactionsTarget {
 		// it may have already received (some of) proofs that we're going to relay
 		self.client.ensure_synced().await?;
 
-		self.client.best_ethereum_finalized_block().await
+		self.best_ethereum_finalized_block().await
 	}
 
 	async fn filter_transaction_proof(&self, proof: &EthereumTransactionInclusionProof) -> Result<bool, RpcError> {
-		// let's try to parse transaction locally
-		let (raw_tx, raw_tx_receipt) = &proof.proof[proof.index as usize];
-		let parse_result = rialto_runtime::exchange::EthTransaction::parse(raw_tx);
-		if parse_result.is_err() {
+		// let's check if target pallet may accept this proof at all
+		if !self.target.is_acceptable_proof(proof) {
 			return Ok(false);
 		}
 
-		// now let's check if transaction is successful
-		match bp_eth_poa::Receipt::is_successful_raw_receipt(raw_tx_receipt) {
-			Ok(true) => (),
-			_ => return Ok(false),
-		}
-
 		// seems that transaction is relayable - let's check if runtime is able to import it
 		// (we can't if e.g. header is pruned or there's some issue with tx data)
-		self.client.verify_exchange_transaction_proof(proof.clone()).await
+		match self.target.verify_proof_method() {
+			Some(method) => self.client.verify_transaction_proof(method, proof.clone()).await,
+			None => Ok(true),
+		}
 	}
 
 	async fn submit_transaction_proof(&self, proof: EthereumTransactionInclusionProof) -> Result<(), RpcError> {
-		let (sign_params, bridge_instance) = (self.sign_params.clone(), self.bridge_instance.clone());
-		self.client
-			.submit_exchange_transaction_proof(sign_params, bridge_instance, proof)
-			.await
+		let (sign_params, target) = (self.sign_params.clone(), self.target.clone());
+		self.client.submit_transaction_proof(sign_params, target, proof).await
 	}
 }
 
@@ -331,7 +425,7 @@ async fn run_single_transaction_relay(params: EthereumExchangeParams, eth_tx_has
 		eth_params,
 		sub_params,
 		sub_sign,
-		instance,
+		target,
 		..
 	} = params;
 
@@ -342,12 +436,13 @@ async fn run_single_transaction_relay(params: EthereumExchangeParams, eth_tx_has
 		.await
 		.map_err(RpcError::Substrate)?;
 
-	let source = EthereumTransactionsSource { client: eth_client };
-	let target = SubstrateTransactionsTarget {
-		client: sub_client,
-		sign_params: sub_sign,
-		bridge_instance: instance,
+	// the filter is only used by the auto-relay loop - if transaction is explicitly specified, we
+	// always relay its proof
+	let source = EthereumTransactionsSource {
+		client: eth_client,
+		filter: Default::default(),
 	};
+	let target = SubstrateTransactionsTarget::new(sub_client, sub_sign, target);
 
 	relay_single_transaction_proof(&source, &target, eth_tx_hash).await
 }
@@ -360,8 +455,9 @@ async fn run_auto_transactions_relay_loop(
 		eth_params,
 		sub_params,
 		sub_sign,
+		filter,
 		metrics_params,
-		instance,
+		target,
 		..
 	} = params;
 
@@ -386,12 +482,11 @@ async fn run_auto_transactions_relay_loop(
 
 	run_loop(
 		InMemoryStorage::new(eth_start_with_block_number),
-		EthereumTransactionsSource { client: eth_client },
-		SubstrateTransactionsTarget {
-			client: sub_client,
-			sign_params: sub_sign,
-			bridge_instance: instance,
+		EthereumTransactionsSource {
+			client: eth_client,
+			filter,
 		},
+		SubstrateTransactionsTarget::new(sub_client, sub_sign, target),
 		metrics_params,
 		futures::future::pending(),
 	)
@@ -399,3 +494,33 @@ async fn run_auto_transactions_relay_loop(
 
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn best_finalized_header_cache_is_invalidated_when_substrate_best_block_changes() {
+		let mut cache = BestFinalizedHeaderCache::default();
+		let (block1, block2a, block2b) = (
+			rialto_runtime::Hash::from_low_u64_be(1),
+			rialto_runtime::Hash::from_low_u64_be(2),
+			rialto_runtime::Hash::from_low_u64_be(3),
+		);
+
+		// nothing is cached initially
+		assert_eq!(cache.get(&block1), None);
+
+		// value is cached until best Substrate block changes
+		cache.update(block1, HeaderId(10, H256::from_low_u64_be(10)));
+		assert_eq!(cache.get(&block1), Some(HeaderId(10, H256::from_low_u64_be(10))));
+		assert_eq!(cache.get(&block2a), None);
+
+		// when Substrate node switches to another fork, value cached at the previous fork is not used
+		cache.update(block2a, HeaderId(20, H256::from_low_u64_be(20)));
+		assert_eq!(cache.get(&block2b), None);
+		cache.update(block2b, HeaderId(15, H256::from_low_u64_be(15)));
+		assert_eq!(cache.get(&block2a), None);
+		assert_eq!(cache.get(&block2b), Some(HeaderId(15, H256::from_low_u64_be(15))));
+	}
+}
@@ -75,6 +75,7 @@ pub async fn run(params: EthereumExchangeSubmitParams) {
 			})
 			.await
 			.map_err(|err| format!("error estimating gas requirements: {:?}", err))?;
+		// exchange pallet only accepts legacy transactions, so EIP-1559 transactions are never used here
 		let eth_tx_unsigned = UnsignedTransaction {
 			nonce,
 			gas_price: eth_sign.gas_price,
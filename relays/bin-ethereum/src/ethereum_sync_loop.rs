@@ -24,9 +24,10 @@ use crate::substrate_types::{into_substrate_ethereum_header, into_substrate_ethe
 
 use async_trait::async_trait;
 use codec::Encode;
+use futures::stream::{unfold, StreamExt};
 use headers_relay::{
 	sync::{HeadersSyncParams, TargetTransactionMode},
-	sync_loop::{SourceClient, TargetClient},
+	sync_loop::{BestBlockNumbersStream, SourceClient, TargetClient},
 	sync_types::{HeadersSyncPipeline, QueuedHeader, SourceHeader, SubmittedHeaders},
 };
 use relay_ethereum_client::{
@@ -37,7 +38,7 @@ use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::{
 	Chain as SubstrateChain, Client as SubstrateClient, ConnectionParams as SubstrateConnectionParams,
 };
-use relay_utils::{metrics::MetricsParams, relay_loop::Client as RelayClient};
+use relay_utils::{metrics::MetricsParams, relay_loop::Client as RelayClient, MaybeConnectionError};
 
 use std::fmt::Debug;
 use std::{collections::HashSet, sync::Arc, time::Duration};
@@ -59,6 +60,9 @@ pub mod consts {
 	pub const MAX_SUBMITTED_HEADERS: usize = 128;
 	/// Max depth of in-memory headers in all states. Past this depth they will be forgotten (pruned).
 	pub const PRUNE_DEPTH: u32 = 4096;
+	/// Max number of latest downloaded Ethereum headers that are checked for reorgs. Covers all
+	/// headers that may be queued or submitted.
+	pub const REORG_DETECTION_WINDOW: usize = MAX_FUTURE_HEADERS_TO_DOWNLOAD + MAX_SUBMITTED_HEADERS;
 }
 
 /// Ethereum synchronization parameters.
@@ -147,6 +151,32 @@ impl SourceClient<EthereumHeadersSyncPipeline> for EthereumHeadersSource {
 		self.client.best_block_number().await.map_err(Into::into)
 	}
 
+	async fn subscribe_best_block_numbers(
+		&self,
+	) -> Result<Option<BestBlockNumbersStream<EthereumHeadersSyncPipeline>>, RpcError> {
+		let subscription = match self.client.subscribe_new_heads().await {
+			Ok(subscription) => subscription,
+			Err(error) if !error.is_connection_error() => {
+				log::debug!(target: "bridge", "Failed to subscribe to Ethereum new heads: {:?}", error);
+				return Ok(None);
+			}
+			Err(error) => return Err(error.into()),
+		};
+
+		Ok(Some(
+			unfold(subscription, |mut subscription| async move {
+				loop {
+					let header = subscription.next().await?;
+					// headers of pending blocks have no number
+					if let Some(number) = header.number {
+						return Some((number.as_u64(), subscription));
+					}
+				}
+			})
+			.boxed(),
+		))
+	}
+
 	async fn header_by_hash(&self, hash: HeaderHash) -> Result<Header, RpcError> {
 		self.client
 			.header_by_hash(hash)
@@ -55,6 +55,7 @@ impl BridgeInstance for RialtoPoA {
 					(
 						into_substrate_ethereum_header(header.header()),
 						into_substrate_ethereum_receipts(header.extra()),
+						None,
 					)
 				})
 				.collect(),
@@ -67,6 +68,7 @@ impl BridgeInstance for RialtoPoA {
 		let pallet_call = rialto_runtime::BridgeEthPoACall::import_unsigned_header(
 			into_substrate_ethereum_header(header.header()),
 			into_substrate_ethereum_receipts(header.extra()),
+			None,
 		);
 
 		rialto_runtime::Call::BridgeRialtoPoA(pallet_call)
@@ -91,6 +93,7 @@ impl BridgeInstance for Kovan {
 					(
 						into_substrate_ethereum_header(header.header()),
 						into_substrate_ethereum_receipts(header.extra()),
+						None,
 					)
 				})
 				.collect(),
@@ -103,6 +106,7 @@ impl BridgeInstance for Kovan {
 		let pallet_call = rialto_runtime::BridgeEthPoACall::import_unsigned_header(
 			into_substrate_ethereum_header(header.header()),
 			into_substrate_ethereum_receipts(header.extra()),
+			None,
 		);
 
 		rialto_runtime::Call::BridgeKovan(pallet_call)
@@ -22,6 +22,7 @@ mod ethereum_exchange;
 mod ethereum_exchange_submit;
 mod ethereum_sync_loop;
 mod instances;
+mod proof_targets;
 mod rialto_client;
 mod rpc_errors;
 mod substrate_sync_loop;
@@ -153,6 +154,19 @@ fn ethereum_signing_params(matches: &clap::ArgMatches) -> Result<EthereumSigning
 			.parse::<u64>()
 			.map_err(|e| format!("Failed to parse eth-chain-id: {}", e))?;
 	}
+	if let Some(eth_max_fee_per_gas) = matches.value_of("eth-max-fee-per-gas") {
+		params.max_fee_per_gas = Some(
+			relay_ethereum_client::types::U256::from_dec_str(eth_max_fee_per_gas)
+				.map_err(|e| format!("Failed to parse eth-max-fee-per-gas: {:?}", e))?,
+		);
+	}
+	if let Some(eth_max_priority_fee_per_gas) = matches.value_of("eth-max-priority-fee-per-gas") {
+		params.max_priority_fee_per_gas = Some(
+			relay_ethereum_client::types::U256::from_dec_str(eth_max_priority_fee_per_gas)
+				.map_err(|e| format!("Failed to parse eth-max-priority-fee-per-gas: {:?}", e))?,
+		);
+	}
+	params.legacy_transactions = matches.is_present("eth-legacy-transactions");
 	Ok(params)
 }
 
@@ -189,6 +203,7 @@ fn ethereum_sync_params(matches: &clap::ArgMatches) -> Result<EthereumSyncParams
 		max_headers_in_single_submit: MAX_HEADERS_IN_SINGLE_SUBMIT,
 		max_headers_size_in_single_submit: MAX_HEADERS_SIZE_IN_SINGLE_SUBMIT,
 		prune_depth: PRUNE_DEPTH,
+		reorg_detection_window: REORG_DETECTION_WINDOW,
 		target_tx_mode: TargetTransactionMode::Signed,
 	};
 
@@ -242,6 +257,7 @@ fn substrate_sync_params(matches: &clap::ArgMatches) -> Result<SubstrateSyncPara
 			max_headers_in_single_submit: MAX_SUBMITTED_HEADERS,
 			max_headers_size_in_single_submit: std::usize::MAX,
 			prune_depth: PRUNE_DEPTH,
+			reorg_detection_window: REORG_DETECTION_WINDOW,
 			target_tx_mode: TargetTransactionMode::Signed,
 		},
 		eth_contract_address,
@@ -356,13 +372,37 @@ fn ethereum_exchange_params(matches: &clap::ArgMatches) -> Result<EthereumExchan
 		),
 	};
 
+	let filter = ethereum_exchange::EthereumTransactionsFilter {
+		recipients: matches
+			.values_of("eth-tx-recipient")
+			.into_iter()
+			.flatten()
+			.map(|recipient| {
+				recipient
+					.parse()
+					.map_err(|e| format!("Failed to parse eth-tx-recipient: {}", e))
+			})
+			.collect::<Result<_, _>>()?,
+		event_topics: matches
+			.values_of("eth-event-topic")
+			.into_iter()
+			.flatten()
+			.map(|topic| {
+				topic
+					.parse()
+					.map_err(|e| format!("Failed to parse eth-event-topic: {}", e))
+			})
+			.collect::<Result<_, _>>()?,
+	};
+
 	let params = EthereumExchangeParams {
 		eth_params: ethereum_connection_params(matches)?,
 		sub_params: substrate_connection_params(matches)?,
 		sub_sign: rialto_signing_params(matches)?,
 		metrics_params: metrics_params(matches)?,
-		instance: instance_params(matches)?,
+		target: Arc::new(proof_targets::CurrencyExchange(instance_params(matches)?)),
 		mode,
+		filter,
 	};
 
 	log::debug!(target: "bridge", "Ethereum exchange params: {:?}", params);
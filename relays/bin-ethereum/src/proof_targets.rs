@@ -0,0 +1,72 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Target pallets of Ethereum transactions proofs relay.
+//!
+//! Any pallet that is able to verify inclusion proofs of Ethereum transactions may be used as a
+//! target of the proofs relay. This module describes how relay checks proofs before submission
+//! and how it builds `Call`s that submit proofs to such pallets.
+
+use crate::instances::BridgeInstance;
+
+use bp_currency_exchange::MaybeLockFundsTransaction;
+use rialto_runtime::exchange::EthereumTransactionInclusionProof as Proof;
+use rialto_runtime::Call;
+use std::sync::Arc;
+
+/// Name of the runtime API method that checks if currency exchange pallet is able to import proof.
+const EXCH_API_FILTER_TRANSACTION_PROOF: &str = "RialtoCurrencyExchangeApi_filter_transaction_proof";
+
+/// Pallet that consumes proofs of Ethereum transactions.
+pub trait TransactionProofTarget: Send + Sync + std::fmt::Debug {
+	/// Returns true if pallet may accept given proof.
+	///
+	/// This check is performed locally, before calling the runtime. By default all proofs are accepted.
+	fn is_acceptable_proof(&self, _proof: &Proof) -> bool {
+		true
+	}
+	/// Name of the runtime API method that is used to check if pallet is able to import proof in
+	/// its current state. If `None`, proofs are submitted without this check.
+	fn verify_proof_method(&self) -> Option<&'static str>;
+	/// Used to build a `Call` for importing transaction proof to the pallet.
+	fn build_submit_proof_call(&self, proof: Proof) -> Call;
+}
+
+/// Currency exchange pallet that is coupled with given bridge pallet instance.
+#[derive(Debug)]
+pub struct CurrencyExchange(pub Arc<dyn BridgeInstance>);
+
+impl TransactionProofTarget for CurrencyExchange {
+	fn is_acceptable_proof(&self, proof: &Proof) -> bool {
+		// let's try to parse transaction locally
+		let (raw_tx, raw_tx_receipt) = &proof.proof[proof.index as usize];
+		let parse_result = rialto_runtime::exchange::EthTransaction::parse(raw_tx);
+		if parse_result.is_err() {
+			return false;
+		}
+
+		// now let's check if transaction is successful
+		matches!(bp_eth_poa::Receipt::is_successful_raw_receipt(raw_tx_receipt), Ok(true))
+	}
+
+	fn verify_proof_method(&self) -> Option<&'static str> {
+		Some(EXCH_API_FILTER_TRANSACTION_PROOF)
+	}
+
+	fn build_submit_proof_call(&self, proof: Proof) -> Call {
+		self.0.build_currency_exchange_call(proof)
+	}
+}
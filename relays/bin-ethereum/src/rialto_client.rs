@@ -16,6 +16,7 @@
 
 use crate::ethereum_sync_loop::QueuedEthereumHeader;
 use crate::instances::BridgeInstance;
+use crate::proof_targets::TransactionProofTarget;
 use crate::rpc_errors::RpcError;
 
 use async_trait::async_trait;
@@ -24,16 +25,15 @@ use codec::{Decode, Encode};
 use headers_relay::sync_types::SubmittedHeaders;
 use relay_ethereum_client::types::HeaderId as EthereumHeaderId;
 use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
-use relay_substrate_client::{Client as SubstrateClient, TransactionSignScheme};
+use relay_substrate_client::{Client as SubstrateClient, MultiSignerKeyPair, TransactionSignScheme};
 use relay_utils::HeaderId;
-use sp_core::{crypto::Pair, Bytes};
+use sp_core::Bytes;
 use std::{collections::VecDeque, sync::Arc};
 
 const ETH_API_IMPORT_REQUIRES_RECEIPTS: &str = "RialtoPoAHeaderApi_is_import_requires_receipts";
 const ETH_API_IS_KNOWN_BLOCK: &str = "RialtoPoAHeaderApi_is_known_block";
 const ETH_API_BEST_BLOCK: &str = "RialtoPoAHeaderApi_best_block";
 const ETH_API_BEST_FINALIZED_BLOCK: &str = "RialtoPoAHeaderApi_finalized_block";
-const EXCH_API_FILTER_TRANSACTION_PROOF: &str = "RialtoCurrencyExchangeApi_filter_transaction_proof";
 
 type RpcResult<T> = std::result::Result<T, RpcError>;
 
@@ -45,6 +45,8 @@ pub trait SubstrateHighLevelRpc {
 	async fn best_ethereum_block(&self) -> RpcResult<EthereumHeaderId>;
 	/// Returns best finalized Ethereum block that Substrate runtime knows of.
 	async fn best_ethereum_finalized_block(&self) -> RpcResult<EthereumHeaderId>;
+	/// Returns best finalized Ethereum block that Substrate runtime knows of at given Substrate block.
+	async fn best_ethereum_finalized_block_at(&self, at_block: rialto_runtime::Hash) -> RpcResult<EthereumHeaderId>;
 	/// Returns whether or not transactions receipts are required for Ethereum header submission.
 	async fn ethereum_receipts_required(&self, header: SubstrateEthereumHeader) -> RpcResult<bool>;
 	/// Returns whether or not the given Ethereum header is known to the Substrate runtime.
@@ -75,6 +77,17 @@ impl SubstrateHighLevelRpc for SubstrateClient<Rialto> {
 		Ok(best_header_id)
 	}
 
+	async fn best_ethereum_finalized_block_at(&self, at_block: rialto_runtime::Hash) -> RpcResult<EthereumHeaderId> {
+		let call = ETH_API_BEST_FINALIZED_BLOCK.to_string();
+		let data = Bytes(Vec::new());
+
+		let encoded_response = self.state_call(call, data, Some(at_block)).await?;
+		let decoded_response: (u64, bp_eth_poa::H256) = Decode::decode(&mut &encoded_response.0[..])?;
+
+		let best_header_id = HeaderId(decoded_response.0, decoded_response.1);
+		Ok(best_header_id)
+	}
+
 	async fn ethereum_receipts_required(&self, header: SubstrateEthereumHeader) -> RpcResult<bool> {
 		let call = ETH_API_IMPORT_REQUIRES_RECEIPTS.to_string();
 		let data = Bytes(header.encode());
@@ -156,7 +169,7 @@ impl SubmitEthereumHeaders for SubstrateClient<Rialto> {
 	) -> SubmittedHeaders<EthereumHeaderId, RpcError> {
 		let ids = headers.iter().map(|header| header.id()).collect();
 		let submission_result = async {
-			self.submit_signed_extrinsic((*params.public().as_array_ref()).into(), |transaction_nonce| {
+			self.submit_signed_extrinsic(params.account_id(), |transaction_nonce| {
 				Bytes(
 					Rialto::sign_transaction(
 						*self.genesis_hash(),
@@ -217,32 +230,34 @@ impl SubmitEthereumHeaders for SubstrateClient<Rialto> {
 	}
 }
 
-/// A trait for RPC calls which are used to submit proof of Ethereum exchange transaction to a
+/// A trait for RPC calls which are used to submit proof of Ethereum transaction to a
 /// Substrate runtime. These are typically calls which use a combination of other low-level RPC
 /// calls.
 #[async_trait]
-pub trait SubmitEthereumExchangeTransactionProof {
-	/// Pre-verify Ethereum exchange transaction proof.
-	async fn verify_exchange_transaction_proof(
+pub trait SubmitEthereumTransactionProof {
+	/// Pre-verify Ethereum transaction proof using given runtime API method.
+	async fn verify_transaction_proof(
 		&self,
+		method: &'static str,
 		proof: rialto_runtime::exchange::EthereumTransactionInclusionProof,
 	) -> RpcResult<bool>;
-	/// Submits Ethereum exchange transaction proof to Substrate runtime.
-	async fn submit_exchange_transaction_proof(
+	/// Submits Ethereum transaction proof to the target pallet of Substrate runtime.
+	async fn submit_transaction_proof(
 		&self,
 		params: RialtoSigningParams,
-		instance: Arc<dyn BridgeInstance>,
+		target: Arc<dyn TransactionProofTarget>,
 		proof: rialto_runtime::exchange::EthereumTransactionInclusionProof,
 	) -> RpcResult<()>;
 }
 
 #[async_trait]
-impl SubmitEthereumExchangeTransactionProof for SubstrateClient<Rialto> {
-	async fn verify_exchange_transaction_proof(
+impl SubmitEthereumTransactionProof for SubstrateClient<Rialto> {
+	async fn verify_transaction_proof(
 		&self,
+		method: &'static str,
 		proof: rialto_runtime::exchange::EthereumTransactionInclusionProof,
 	) -> RpcResult<bool> {
-		let call = EXCH_API_FILTER_TRANSACTION_PROOF.to_string();
+		let call = method.to_string();
 		let data = Bytes(proof.encode());
 
 		let encoded_response = self.state_call(call, data, None).await?;
@@ -251,19 +266,19 @@ impl SubmitEthereumExchangeTransactionProof for SubstrateClient<Rialto> {
 		Ok(is_allowed)
 	}
 
-	async fn submit_exchange_transaction_proof(
+	async fn submit_transaction_proof(
 		&self,
 		params: RialtoSigningParams,
-		instance: Arc<dyn BridgeInstance>,
+		target: Arc<dyn TransactionProofTarget>,
 		proof: rialto_runtime::exchange::EthereumTransactionInclusionProof,
 	) -> RpcResult<()> {
-		self.submit_signed_extrinsic((*params.public().as_array_ref()).into(), |transaction_nonce| {
+		self.submit_signed_extrinsic(params.account_id(), |transaction_nonce| {
 			Bytes(
 				Rialto::sign_transaction(
 					*self.genesis_hash(),
 					&params,
 					transaction_nonce,
-					instance.build_currency_exchange_call(proof),
+					target.build_submit_proof_call(proof),
 				)
 				.encode(),
 			)
@@ -52,6 +52,8 @@ pub mod consts {
 	pub const MAX_SUBMITTED_HEADERS: usize = 4;
 	/// Max depth of in-memory headers in all states. Past this depth they will be forgotten (pruned).
 	pub const PRUNE_DEPTH: u32 = 256;
+	/// Max number of latest downloaded Substrate headers that are checked for reorgs.
+	pub const REORG_DETECTION_WINDOW: usize = MAX_FUTURE_HEADERS_TO_DOWNLOAD + MAX_SUBMITTED_HEADERS;
 }
 
 /// Substrate synchronization parameters.
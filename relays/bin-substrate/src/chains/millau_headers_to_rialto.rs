@@ -16,37 +16,59 @@
 
 //! Millau-to-Rialto headers sync entrypoint.
 
-use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityToSubstrate};
+use crate::finality_pipeline::{
+	optimize_grandpa_justification, SubstrateFinalitySyncPipeline, SubstrateFinalityToSubstrate,
+};
 
-use bp_header_chain::justification::GrandpaJustification;
+use bp_header_chain::{justification::GrandpaJustification, AuthoritySet};
+use bp_runtime::ChainId;
 use codec::Encode;
 use relay_millau_client::{Millau, SyncHeader as MillauSyncHeader};
 use relay_rialto_client::{Rialto, SigningParams as RialtoSigningParams};
-use relay_substrate_client::{Chain, TransactionSignScheme};
-use sp_core::{Bytes, Pair};
+use relay_substrate_client::{Chain, MultiSignerKeyPair, TransactionSignScheme};
+use sp_core::{storage::StorageKey, Bytes};
 
 /// Millau-to-Rialto finality sync pipeline.
 pub(crate) type MillauFinalityToRialto = SubstrateFinalityToSubstrate<Millau, Rialto, RialtoSigningParams>;
 
 impl SubstrateFinalitySyncPipeline for MillauFinalityToRialto {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_millau::BEST_FINALIZED_MILLAU_HEADER_METHOD;
+	const TARGET_FINALITY_INSTANCE: Option<ChainId> = Some(bp_runtime::MILLAU_CHAIN_ID);
 
 	type TargetChain = Rialto;
 
 	fn transactions_author(&self) -> bp_rialto::AccountId {
-		(*self.target_sign.public().as_array_ref()).into()
+		self.target_sign.account_id()
+	}
+
+	fn target_authority_set_key() -> Option<StorageKey> {
+		Some(pallet_bridge_grandpa::storage_keys::current_authority_set_key::<
+			rialto_runtime::Runtime,
+			rialto_runtime::MillauGrandpaInstance,
+		>())
+	}
+
+	fn optimize_finality_proof(
+		&self,
+		header: &MillauSyncHeader,
+		authority_set: AuthoritySet,
+		proof: GrandpaJustification<bp_millau::Header>,
+	) -> GrandpaJustification<bp_millau::Header> {
+		optimize_grandpa_justification::<Self, bp_millau::Header>(header, authority_set, proof)
 	}
 
 	fn make_submit_finality_proof_transaction(
 		&self,
 		transaction_nonce: <Rialto as Chain>::Index,
+		tip: <Rialto as Chain>::Balance,
 		header: MillauSyncHeader,
 		proof: GrandpaJustification<bp_millau::Header>,
 	) -> Bytes {
 		let call = rialto_runtime::BridgeGrandpaMillauCall::submit_finality_proof(header.into_inner(), proof).into();
 
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Rialto::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction =
+			Rialto::sign_transaction_with_tip(genesis_hash, &self.target_sign, transaction_nonce, call, tip);
 
 		Bytes(transaction.encode())
 	}
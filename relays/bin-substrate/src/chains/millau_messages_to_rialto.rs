@@ -28,13 +28,14 @@ use bridge_runtime_common::messages::target::FromBridgedChainMessagesProof;
 use codec::Encode;
 use frame_support::dispatch::GetDispatchInfo;
 use messages_relay::message_lane::MessageLane;
+use pallet_bridge_messages::RawEvent as MessagesEvent;
 use relay_millau_client::{HeaderId as MillauHeaderId, Millau, SigningParams as MillauSigningParams};
 use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::{
-	metrics::{FloatStorageValueMetric, StorageProofOverheadMetric},
-	Chain, TransactionSignScheme,
+	metrics::{FloatStorageValueMetric, RelayerRewardsMetric, RewardsEvent, StorageProofOverheadMetric},
+	Chain, MultiSignerKeyPair, TransactionSignScheme,
 };
-use sp_core::{Bytes, Pair};
+use sp_core::Bytes;
 use std::{ops::RangeInclusive, time::Duration};
 
 /// Millau-to-Rialto message lane.
@@ -55,11 +56,14 @@ impl SubstrateMessageLane for MillauMessagesToRialto {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_millau::BEST_FINALIZED_MILLAU_HEADER_METHOD;
 	const BEST_FINALIZED_TARGET_HEADER_ID_AT_SOURCE: &'static str = bp_rialto::BEST_FINALIZED_RIALTO_HEADER_METHOD;
 
+	const FINALITY_PALLET_STATE_AT_SOURCE: &'static str = bp_rialto::RIALTO_FINALITY_PALLET_STATE_METHOD;
+	const FINALITY_PALLET_STATE_AT_TARGET: &'static str = bp_millau::MILLAU_FINALITY_PALLET_STATE_METHOD;
+
 	type SourceChain = Millau;
 	type TargetChain = Rialto;
 
 	fn source_transactions_author(&self) -> bp_millau::AccountId {
-		(*self.source_sign.public().as_array_ref()).into()
+		self.source_sign.account_id()
 	}
 
 	fn make_messages_receiving_proof_transaction(
@@ -86,7 +90,7 @@ impl SubstrateMessageLane for MillauMessagesToRialto {
 	}
 
 	fn target_transactions_author(&self) -> bp_rialto::AccountId {
-		(*self.target_sign.public().as_array_ref()).into()
+		self.target_sign.account_id()
 	}
 
 	fn make_messages_delivery_transaction(
@@ -146,7 +150,8 @@ pub async fn run(
 	params: MessagesRelayParams<Millau, MillauSigningParams, Rialto, RialtoSigningParams>,
 ) -> Result<(), String> {
 	let stall_timeout = Duration::from_secs(5 * 60);
-	let relayer_id_at_millau = (*params.source_sign.public().as_array_ref()).into();
+	let race_stall_timeout = Duration::from_secs(2 * 60);
+	let relayer_id_at_millau = params.source_sign.account_id();
 
 	let lane_id = params.lane_id;
 	let source_client = params.source_client;
@@ -157,6 +162,12 @@ pub async fn run(
 		target_sign: params.target_sign,
 		relayer_id_at_source: relayer_id_at_millau,
 	};
+	let relayer_id_at_source = lane.relayer_id_at_source.clone();
+	let relayers_fund_account = pallet_bridge_messages::Pallet::<
+		millau_runtime::Runtime,
+		millau_runtime::WithRialtoMessagesInstance,
+	>::relayer_fund_account_id();
+	let persistent_counters = params.metrics_params.persistent_counters.clone();
 
 	// 2/3 is reserved for proofs and tx overhead
 	let max_messages_size_in_single_batch = bp_rialto::max_extrinsic_size() as usize / 3;
@@ -187,26 +198,28 @@ pub async fn run(
 			target_tick: Rialto::AVERAGE_BLOCK_INTERVAL,
 			reconnect_delay: relay_utils::relay_loop::RECONNECT_DELAY,
 			stall_timeout,
+			race_stall_timeout,
 			delivery_params: messages_relay::message_lane_loop::MessageDeliveryParams {
 				max_unrewarded_relayer_entries_at_target: bp_rialto::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE,
 				max_unconfirmed_nonces_at_target: bp_rialto::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
 				max_messages_in_single_batch,
 				max_messages_weight_in_single_batch,
 				max_messages_size_in_single_batch,
+				skip_outdated_messages: params.skip_outdated_messages,
 			},
 		},
 		MillauSourceClient::new(
 			source_client.clone(),
 			lane.clone(),
 			lane_id,
-			RIALTO_CHAIN_ID,
+			params.source_instance.unwrap_or(RIALTO_CHAIN_ID),
 			params.target_to_source_headers_relay,
 		),
 		RialtoTargetClient::new(
 			params.target_client,
 			lane,
 			lane_id,
-			MILLAU_CHAIN_ID,
+			params.target_instance.unwrap_or(MILLAU_CHAIN_ID),
 			params.source_to_target_headers_relay,
 		),
 		relay_utils::relay_metrics(
@@ -224,6 +237,20 @@ pub async fn run(
 				"Millau storage proof overhead".into(),
 			)
 		})?
+		.standalone_metric(|registry, prefix| {
+			RelayerRewardsMetric::new(
+				registry,
+				prefix,
+				source_client.clone(),
+				lane_id,
+				relayers_fund_account,
+				vec![relayer_id_at_source],
+				parse_millau_rewards_event,
+				persistent_counters,
+				"millau_relayer_rewards".into(),
+				"Rewards earned by the relayer at Millau for delivering messages to Rialto".into(),
+			)
+		})?
 		.standalone_metric(|registry, prefix| {
 			FloatStorageValueMetric::<_, sp_runtime::FixedU128>::new(
 				registry,
@@ -242,3 +269,18 @@ pub async fn run(
 	)
 	.await
 }
+
+/// Parse Millau runtime event that is related to relayer rewards.
+fn parse_millau_rewards_event(
+	event: millau_runtime::Event,
+) -> Option<RewardsEvent<bp_millau::AccountId, bp_millau::Balance>> {
+	match event {
+		millau_runtime::Event::pallet_bridge_messages(MessagesEvent::MessagesDelivered(lane, _, _)) => {
+			Some(RewardsEvent::MessagesDelivered(lane))
+		}
+		millau_runtime::Event::pallet_balances(millau_runtime::BalancesEvent::Transfer(from, to, amount)) => {
+			Some(RewardsEvent::Transfer(from, to, amount))
+		}
+		_ => None,
+	}
+}
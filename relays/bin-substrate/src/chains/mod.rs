@@ -133,6 +133,7 @@ mod tests {
 			Default::default(),
 			call.get_dispatch_info().weight,
 			bp_message_dispatch::CallOrigin::SourceRoot,
+			bp_runtime::DispatchFeePayment::AtSourceChain,
 			&call,
 		);
 		assert_eq!(Millau::verify_message(&payload), Ok(()));
@@ -143,6 +144,7 @@ mod tests {
 			Default::default(),
 			call.get_dispatch_info().weight,
 			bp_message_dispatch::CallOrigin::SourceRoot,
+			bp_runtime::DispatchFeePayment::AtSourceChain,
 			&call,
 		);
 		assert!(Millau::verify_message(&payload).is_err());
@@ -170,6 +172,7 @@ mod tests {
 			Default::default(),
 			maximal_dispatch_weight,
 			bp_message_dispatch::CallOrigin::SourceRoot,
+			bp_runtime::DispatchFeePayment::AtSourceChain,
 			&call,
 		);
 		assert_eq!(Millau::verify_message(&payload), Ok(()));
@@ -178,6 +181,7 @@ mod tests {
 			Default::default(),
 			maximal_dispatch_weight + 1,
 			bp_message_dispatch::CallOrigin::SourceRoot,
+			bp_runtime::DispatchFeePayment::AtSourceChain,
 			&call,
 		);
 		assert!(Millau::verify_message(&payload).is_err());
@@ -195,6 +199,7 @@ mod tests {
 			Default::default(),
 			maximal_dispatch_weight,
 			bp_message_dispatch::CallOrigin::SourceRoot,
+			bp_runtime::DispatchFeePayment::AtSourceChain,
 			&call,
 		);
 		assert_eq!(Rialto::verify_message(&payload), Ok(()));
@@ -203,6 +208,7 @@ mod tests {
 			Default::default(),
 			maximal_dispatch_weight + 1,
 			bp_message_dispatch::CallOrigin::SourceRoot,
+			bp_runtime::DispatchFeePayment::AtSourceChain,
 			&call,
 		);
 		assert!(Rialto::verify_message(&payload).is_err());
@@ -91,7 +91,13 @@ impl CliChain for Rialto {
 				let call = Target::encode_call(&call).map_err(|e| e.to_string())?;
 				let weight = call.get_dispatch_info().weight;
 
-				Ok(send_message::message_payload(spec_version, weight, origin, &call))
+				Ok(send_message::message_payload(
+					spec_version,
+					weight,
+					origin,
+					bp_runtime::DispatchFeePayment::AtSourceChain,
+					&call,
+				))
 			}
 		}
 	}
@@ -16,30 +16,51 @@
 
 //! Rialto-to-Millau headers sync entrypoint.
 
-use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityToSubstrate};
+use crate::finality_pipeline::{
+	optimize_grandpa_justification, SubstrateFinalitySyncPipeline, SubstrateFinalityToSubstrate,
+};
 
-use bp_header_chain::justification::GrandpaJustification;
+use bp_header_chain::{justification::GrandpaJustification, AuthoritySet};
+use bp_runtime::ChainId;
 use codec::Encode;
 use relay_millau_client::{Millau, SigningParams as MillauSigningParams};
 use relay_rialto_client::{Rialto, SyncHeader as RialtoSyncHeader};
-use relay_substrate_client::{Chain, TransactionSignScheme};
-use sp_core::{Bytes, Pair};
+use relay_substrate_client::{Chain, MultiSignerKeyPair, TransactionSignScheme};
+use sp_core::{storage::StorageKey, Bytes};
 
 /// Rialto-to-Millau finality sync pipeline.
 pub(crate) type RialtoFinalityToMillau = SubstrateFinalityToSubstrate<Rialto, Millau, MillauSigningParams>;
 
 impl SubstrateFinalitySyncPipeline for RialtoFinalityToMillau {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_rialto::BEST_FINALIZED_RIALTO_HEADER_METHOD;
+	const TARGET_FINALITY_INSTANCE: Option<ChainId> = Some(bp_runtime::RIALTO_CHAIN_ID);
 
 	type TargetChain = Millau;
 
 	fn transactions_author(&self) -> bp_millau::AccountId {
-		(*self.target_sign.public().as_array_ref()).into()
+		self.target_sign.account_id()
+	}
+
+	fn target_authority_set_key() -> Option<StorageKey> {
+		Some(pallet_bridge_grandpa::storage_keys::current_authority_set_key::<
+			millau_runtime::Runtime,
+			millau_runtime::RialtoGrandpaInstance,
+		>())
+	}
+
+	fn optimize_finality_proof(
+		&self,
+		header: &RialtoSyncHeader,
+		authority_set: AuthoritySet,
+		proof: GrandpaJustification<bp_rialto::Header>,
+	) -> GrandpaJustification<bp_rialto::Header> {
+		optimize_grandpa_justification::<Self, bp_rialto::Header>(header, authority_set, proof)
 	}
 
 	fn make_submit_finality_proof_transaction(
 		&self,
 		transaction_nonce: <Millau as Chain>::Index,
+		tip: <Millau as Chain>::Balance,
 		header: RialtoSyncHeader,
 		proof: GrandpaJustification<bp_rialto::Header>,
 	) -> Bytes {
@@ -50,7 +71,8 @@ impl SubstrateFinalitySyncPipeline for RialtoFinalityToMillau {
 		.into();
 
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Millau::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction =
+			Millau::sign_transaction_with_tip(genesis_hash, &self.target_sign, transaction_nonce, call, tip);
 
 		Bytes(transaction.encode())
 	}
@@ -28,13 +28,14 @@ use bridge_runtime_common::messages::target::FromBridgedChainMessagesProof;
 use codec::Encode;
 use frame_support::dispatch::GetDispatchInfo;
 use messages_relay::message_lane::MessageLane;
+use pallet_bridge_messages::RawEvent as MessagesEvent;
 use relay_millau_client::{HeaderId as MillauHeaderId, Millau, SigningParams as MillauSigningParams};
 use relay_rialto_client::{HeaderId as RialtoHeaderId, Rialto, SigningParams as RialtoSigningParams};
 use relay_substrate_client::{
-	metrics::{FloatStorageValueMetric, StorageProofOverheadMetric},
-	Chain, TransactionSignScheme,
+	metrics::{FloatStorageValueMetric, RelayerRewardsMetric, RewardsEvent, StorageProofOverheadMetric},
+	Chain, MultiSignerKeyPair, TransactionSignScheme,
 };
-use sp_core::{Bytes, Pair};
+use sp_core::Bytes;
 use std::{ops::RangeInclusive, time::Duration};
 
 /// Rialto-to-Millau message lane.
@@ -55,11 +56,14 @@ impl SubstrateMessageLane for RialtoMessagesToMillau {
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str = bp_rialto::BEST_FINALIZED_RIALTO_HEADER_METHOD;
 	const BEST_FINALIZED_TARGET_HEADER_ID_AT_SOURCE: &'static str = bp_millau::BEST_FINALIZED_MILLAU_HEADER_METHOD;
 
+	const FINALITY_PALLET_STATE_AT_SOURCE: &'static str = bp_millau::MILLAU_FINALITY_PALLET_STATE_METHOD;
+	const FINALITY_PALLET_STATE_AT_TARGET: &'static str = bp_rialto::RIALTO_FINALITY_PALLET_STATE_METHOD;
+
 	type SourceChain = Rialto;
 	type TargetChain = Millau;
 
 	fn source_transactions_author(&self) -> bp_rialto::AccountId {
-		(*self.source_sign.public().as_array_ref()).into()
+		self.source_sign.account_id()
 	}
 
 	fn make_messages_receiving_proof_transaction(
@@ -86,7 +90,7 @@ impl SubstrateMessageLane for RialtoMessagesToMillau {
 	}
 
 	fn target_transactions_author(&self) -> bp_millau::AccountId {
-		(*self.target_sign.public().as_array_ref()).into()
+		self.target_sign.account_id()
 	}
 
 	fn make_messages_delivery_transaction(
@@ -146,7 +150,8 @@ pub async fn run(
 	params: MessagesRelayParams<Rialto, RialtoSigningParams, Millau, MillauSigningParams>,
 ) -> Result<(), String> {
 	let stall_timeout = Duration::from_secs(5 * 60);
-	let relayer_id_at_rialto = (*params.source_sign.public().as_array_ref()).into();
+	let race_stall_timeout = Duration::from_secs(2 * 60);
+	let relayer_id_at_rialto = params.source_sign.account_id();
 
 	let lane_id = params.lane_id;
 	let source_client = params.source_client;
@@ -157,6 +162,12 @@ pub async fn run(
 		target_sign: params.target_sign,
 		relayer_id_at_source: relayer_id_at_rialto,
 	};
+	let relayer_id_at_source = lane.relayer_id_at_source.clone();
+	let relayers_fund_account = pallet_bridge_messages::Pallet::<
+		rialto_runtime::Runtime,
+		rialto_runtime::WithMillauMessagesInstance,
+	>::relayer_fund_account_id();
+	let persistent_counters = params.metrics_params.persistent_counters.clone();
 
 	// 2/3 is reserved for proofs and tx overhead
 	let max_messages_size_in_single_batch = bp_millau::max_extrinsic_size() as usize / 3;
@@ -186,26 +197,28 @@ pub async fn run(
 			target_tick: Millau::AVERAGE_BLOCK_INTERVAL,
 			reconnect_delay: relay_utils::relay_loop::RECONNECT_DELAY,
 			stall_timeout,
+			race_stall_timeout,
 			delivery_params: messages_relay::message_lane_loop::MessageDeliveryParams {
 				max_unrewarded_relayer_entries_at_target: bp_millau::MAX_UNREWARDED_RELAYER_ENTRIES_AT_INBOUND_LANE,
 				max_unconfirmed_nonces_at_target: bp_millau::MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE,
 				max_messages_in_single_batch,
 				max_messages_weight_in_single_batch,
 				max_messages_size_in_single_batch,
+				skip_outdated_messages: params.skip_outdated_messages,
 			},
 		},
 		RialtoSourceClient::new(
 			source_client.clone(),
 			lane.clone(),
 			lane_id,
-			MILLAU_CHAIN_ID,
+			params.source_instance.unwrap_or(MILLAU_CHAIN_ID),
 			params.target_to_source_headers_relay,
 		),
 		MillauTargetClient::new(
 			params.target_client,
 			lane,
 			lane_id,
-			RIALTO_CHAIN_ID,
+			params.target_instance.unwrap_or(RIALTO_CHAIN_ID),
 			params.source_to_target_headers_relay,
 		),
 		relay_utils::relay_metrics(
@@ -223,6 +236,20 @@ pub async fn run(
 				"Rialto storage proof overhead".into(),
 			)
 		})?
+		.standalone_metric(|registry, prefix| {
+			RelayerRewardsMetric::new(
+				registry,
+				prefix,
+				source_client.clone(),
+				lane_id,
+				relayers_fund_account,
+				vec![relayer_id_at_source],
+				parse_rialto_rewards_event,
+				persistent_counters,
+				"rialto_relayer_rewards".into(),
+				"Rewards earned by the relayer at Rialto for delivering messages to Millau".into(),
+			)
+		})?
 		.standalone_metric(|registry, prefix| {
 			FloatStorageValueMetric::<_, sp_runtime::FixedU128>::new(
 				registry,
@@ -241,3 +268,18 @@ pub async fn run(
 	)
 	.await
 }
+
+/// Parse Rialto runtime event that is related to relayer rewards.
+fn parse_rialto_rewards_event(
+	event: rialto_runtime::Event,
+) -> Option<RewardsEvent<bp_rialto::AccountId, bp_rialto::Balance>> {
+	match event {
+		rialto_runtime::Event::pallet_bridge_messages(MessagesEvent::MessagesDelivered(lane, _, _)) => {
+			Some(RewardsEvent::MessagesDelivered(lane))
+		}
+		rialto_runtime::Event::pallet_balances(rialto_runtime::BalancesEvent::Transfer(from, to, amount)) => {
+			Some(RewardsEvent::Transfer(from, to, amount))
+		}
+		_ => None,
+	}
+}
@@ -22,10 +22,10 @@ use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityT
 use bp_header_chain::justification::GrandpaJustification;
 use codec::Encode;
 use relay_rococo_client::{Rococo, SyncHeader as RococoSyncHeader};
-use relay_substrate_client::{Chain, TransactionSignScheme};
+use relay_substrate_client::{Chain, MultiSignerKeyPair, TransactionSignScheme};
 use relay_utils::metrics::MetricsParams;
 use relay_wococo_client::{SigningParams as WococoSigningParams, Wococo};
-use sp_core::{Bytes, Pair};
+use sp_core::Bytes;
 
 /// Rococo-to-Wococo finality sync pipeline.
 pub(crate) type RococoFinalityToWococo = SubstrateFinalityToSubstrate<Rococo, Wococo, WococoSigningParams>;
@@ -52,12 +52,13 @@ impl SubstrateFinalitySyncPipeline for RococoFinalityToWococo {
 	}
 
 	fn transactions_author(&self) -> bp_wococo::AccountId {
-		(*self.target_sign.public().as_array_ref()).into()
+		self.target_sign.account_id()
 	}
 
 	fn make_submit_finality_proof_transaction(
 		&self,
 		transaction_nonce: <Wococo as Chain>::Index,
+		tip: <Wococo as Chain>::Balance,
 		header: RococoSyncHeader,
 		proof: GrandpaJustification<bp_rococo::Header>,
 	) -> Bytes {
@@ -66,7 +67,8 @@ impl SubstrateFinalitySyncPipeline for RococoFinalityToWococo {
 			proof,
 		));
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Wococo::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction =
+			Wococo::sign_transaction_with_tip(genesis_hash, &self.target_sign, transaction_nonce, call, tip);
 
 		Bytes(transaction.encode())
 	}
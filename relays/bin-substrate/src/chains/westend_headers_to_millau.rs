@@ -16,15 +16,17 @@
 
 //! Westend-to-Millau headers sync entrypoint.
 
-use crate::finality_pipeline::{SubstrateFinalitySyncPipeline, SubstrateFinalityToSubstrate};
+use crate::finality_pipeline::{
+	optimize_grandpa_justification, SubstrateFinalitySyncPipeline, SubstrateFinalityToSubstrate,
+};
 
-use bp_header_chain::justification::GrandpaJustification;
+use bp_header_chain::{justification::GrandpaJustification, AuthoritySet};
 use codec::Encode;
 use relay_millau_client::{Millau, SigningParams as MillauSigningParams};
-use relay_substrate_client::{Chain, TransactionSignScheme};
+use relay_substrate_client::{Chain, MultiSignerKeyPair, TransactionSignScheme};
 use relay_utils::metrics::MetricsParams;
 use relay_westend_client::{SyncHeader as WestendSyncHeader, Westend};
-use sp_core::{Bytes, Pair};
+use sp_core::{storage::StorageKey, Bytes};
 
 /// Westend-to-Millau finality sync pipeline.
 pub(crate) type WestendFinalityToMillau = SubstrateFinalityToSubstrate<Westend, Millau, MillauSigningParams>;
@@ -39,12 +41,29 @@ impl SubstrateFinalitySyncPipeline for WestendFinalityToMillau {
 	}
 
 	fn transactions_author(&self) -> bp_millau::AccountId {
-		(*self.target_sign.public().as_array_ref()).into()
+		self.target_sign.account_id()
+	}
+
+	fn target_authority_set_key() -> Option<StorageKey> {
+		Some(pallet_bridge_grandpa::storage_keys::current_authority_set_key::<
+			millau_runtime::Runtime,
+			millau_runtime::WestendGrandpaInstance,
+		>())
+	}
+
+	fn optimize_finality_proof(
+		&self,
+		header: &WestendSyncHeader,
+		authority_set: AuthoritySet,
+		proof: GrandpaJustification<bp_westend::Header>,
+	) -> GrandpaJustification<bp_westend::Header> {
+		optimize_grandpa_justification::<Self, bp_westend::Header>(header, authority_set, proof)
 	}
 
 	fn make_submit_finality_proof_transaction(
 		&self,
 		transaction_nonce: <Millau as Chain>::Index,
+		tip: <Millau as Chain>::Balance,
 		header: WestendSyncHeader,
 		proof: GrandpaJustification<bp_westend::Header>,
 	) -> Bytes {
@@ -55,7 +74,8 @@ impl SubstrateFinalitySyncPipeline for WestendFinalityToMillau {
 		.into();
 
 		let genesis_hash = *self.target_client.genesis_hash();
-		let transaction = Millau::sign_transaction(genesis_hash, &self.target_sign, transaction_nonce, call);
+		let transaction =
+			Millau::sign_transaction_with_tip(genesis_hash, &self.target_sign, transaction_nonce, call, tip);
 
 		Bytes(transaction.encode())
 	}
@@ -55,6 +55,12 @@ macro_rules! select_full_bridge {
 				#[allow(unused_imports)]
 				use bp_rialto::derive_account_from_millau_id as derive_account;
 
+				// Default bridge pallet instances at source and target chains
+				#[allow(unused_imports)]
+				use bp_runtime::{
+					MILLAU_CHAIN_ID as TARGET_BRIDGE_INSTANCE, RIALTO_CHAIN_ID as SOURCE_BRIDGE_INSTANCE,
+				};
+
 				// Relay-messages
 				#[allow(unused_imports)]
 				use crate::chains::millau_messages_to_rialto::run as relay_messages;
@@ -62,9 +68,29 @@ macro_rules! select_full_bridge {
 				// Send-message / Estimate-fee
 				#[allow(unused_imports)]
 				use bp_rialto::TO_RIALTO_ESTIMATE_MESSAGE_FEE_METHOD as ESTIMATE_MESSAGE_FEE_METHOD;
+				// Load-test
+				#[allow(unused_imports)]
+				use bp_millau::FROM_MILLAU_LATEST_RECEIVED_NONCE_METHOD as FROM_SOURCE_LATEST_RECEIVED_NONCE_METHOD;
+				#[allow(unused_imports)]
+				use bp_rialto::TO_RIALTO_LATEST_GENERATED_NONCE_METHOD as TO_TARGET_LATEST_GENERATED_NONCE_METHOD;
 				// Send-message
 				#[allow(unused_imports)]
 				use millau_runtime::millau_to_rialto_account_ownership_digest as account_ownership_digest;
+				// Query-rewards
+				#[allow(unused_imports)]
+				use bp_rialto::TO_RIALTO_LATEST_RECEIVED_NONCE_METHOD as TO_TARGET_LATEST_RECEIVED_NONCE_METHOD;
+				#[allow(unused_imports)]
+				use bp_rialto::TO_RIALTO_MESSAGE_DETAILS_METHOD as TO_TARGET_MESSAGE_DETAILS_METHOD;
+				#[allow(unused_imports)]
+				use rialto_runtime::{Runtime as TargetRuntime, WithMillauMessagesInstance as TargetMessagesInstance};
+				// Dump-bridge-state
+				#[allow(unused_imports)]
+				use millau_runtime::{
+					RialtoGrandpaInstance as SourceGrandpaInstance, Runtime as SourceRuntime,
+					WithRialtoMessagesInstance as SourceMessagesInstance,
+				};
+				#[allow(unused_imports)]
+				use rialto_runtime::MillauGrandpaInstance as TargetGrandpaInstance;
 
 				$generic
 			}
@@ -77,6 +103,12 @@ macro_rules! select_full_bridge {
 				#[allow(unused_imports)]
 				use bp_millau::derive_account_from_rialto_id as derive_account;
 
+				// Default bridge pallet instances at source and target chains
+				#[allow(unused_imports)]
+				use bp_runtime::{
+					MILLAU_CHAIN_ID as SOURCE_BRIDGE_INSTANCE, RIALTO_CHAIN_ID as TARGET_BRIDGE_INSTANCE,
+				};
+
 				// Relay-messages
 				#[allow(unused_imports)]
 				use crate::chains::rialto_messages_to_millau::run as relay_messages;
@@ -84,10 +116,30 @@ macro_rules! select_full_bridge {
 				// Send-message / Estimate-fee
 				#[allow(unused_imports)]
 				use bp_millau::TO_MILLAU_ESTIMATE_MESSAGE_FEE_METHOD as ESTIMATE_MESSAGE_FEE_METHOD;
+				// Load-test
+				#[allow(unused_imports)]
+				use bp_millau::TO_MILLAU_LATEST_GENERATED_NONCE_METHOD as TO_TARGET_LATEST_GENERATED_NONCE_METHOD;
+				#[allow(unused_imports)]
+				use bp_rialto::FROM_RIALTO_LATEST_RECEIVED_NONCE_METHOD as FROM_SOURCE_LATEST_RECEIVED_NONCE_METHOD;
 
 				// Send-message
 				#[allow(unused_imports)]
 				use rialto_runtime::rialto_to_millau_account_ownership_digest as account_ownership_digest;
+				// Query-rewards
+				#[allow(unused_imports)]
+				use bp_millau::TO_MILLAU_LATEST_RECEIVED_NONCE_METHOD as TO_TARGET_LATEST_RECEIVED_NONCE_METHOD;
+				#[allow(unused_imports)]
+				use bp_millau::TO_MILLAU_MESSAGE_DETAILS_METHOD as TO_TARGET_MESSAGE_DETAILS_METHOD;
+				#[allow(unused_imports)]
+				use millau_runtime::{Runtime as TargetRuntime, WithRialtoMessagesInstance as TargetMessagesInstance};
+				// Dump-bridge-state
+				#[allow(unused_imports)]
+				use millau_runtime::RialtoGrandpaInstance as TargetGrandpaInstance;
+				#[allow(unused_imports)]
+				use rialto_runtime::{
+					MillauGrandpaInstance as SourceGrandpaInstance, Runtime as SourceRuntime,
+					WithMillauMessagesInstance as SourceMessagesInstance,
+				};
 
 				$generic
 			}
@@ -32,6 +32,11 @@ pub struct DeriveAccount {
 	bridge: FullBridge,
 	/// Source-chain address to derive Target-chain address from.
 	account: AccountId,
+	/// Also display derived address as 20-byte (Ethereum-style) account id.
+	///
+	/// This is the address that is used by target chains with 20-byte accounts (e.g. Frontier-based chains).
+	#[structopt(long)]
+	account_id20: bool,
 }
 
 impl DeriveAccount {
@@ -59,6 +64,13 @@ impl DeriveAccount {
 				derived_account,
 				Target::NAME,
 			);
+			if self.account_id20 {
+				println!(
+					"->Corresponding (derived) 20-byte address:\n{} ({})",
+					derived_account.to_account_id20(),
+					Target::NAME,
+				);
+			}
 
 			Ok(())
 		})
@@ -99,4 +111,16 @@ mod tests {
 		);
 		assert_eq!(millau_derived, millau2_derived);
 	}
+
+	#[test]
+	fn should_derive_account_id20_correctly() {
+		let rialto = "5sauUXUfPjmwxSgmb3tZ5d6yx24eZX4wWJ2JtVUBaQqFbvEU";
+
+		let (_, rialto_derived) = derive_account_cli("RialtoToMillau", rialto);
+
+		assert_eq!(
+			format!("{}", rialto_derived.to_account_id20()),
+			"0xb24eb16d6964771e1bc71892d46b7ade6d7acccf"
+		);
+	}
 }
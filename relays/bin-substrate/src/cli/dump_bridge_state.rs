@@ -0,0 +1,269 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Command that dumps bridge-related storage of both chains into single archive.
+//!
+//! The archive contains raw storage key-value pairs of the finality and messages pallets of both
+//! chains, read at given (or best finalized) blocks. It is meant to be attached to bug reports, so
+//! that issues could be replayed against local test nodes. The dispatch pallet has no storage, so
+//! there's nothing to dump for it.
+
+use crate::cli::bridge::FullBridge;
+use crate::cli::{HexBytes, SourceConnectionParams, TargetConnectionParams};
+use crate::select_full_bridge;
+use codec::{Decode, Encode};
+use relay_substrate_client::{Chain, Client};
+use serde::{Serialize, Serializer};
+use sp_core::storage::StorageKey;
+use std::path::PathBuf;
+use structopt::{clap::arg_enum, StructOpt};
+
+arg_enum! {
+	#[derive(Debug, PartialEq, Eq)]
+	/// Format of the bridge state archive.
+	pub enum DumpFormat {
+		Json,
+		Scale,
+	}
+}
+
+/// Dump bridge state command.
+#[derive(StructOpt, Debug, PartialEq, Eq)]
+pub struct DumpBridgeState {
+	/// A bridge instance to dump state of.
+	#[structopt(possible_values = &FullBridge::variants(), case_insensitive = true)]
+	bridge: FullBridge,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	/// Hex-encoded hash of the source chain block to read storage at. If not passed, the best
+	/// finalized source chain block is used.
+	#[structopt(long)]
+	source_block: Option<HexBytes>,
+	/// Hex-encoded hash of the target chain block to read storage at. If not passed, the best
+	/// finalized target chain block is used.
+	#[structopt(long)]
+	target_block: Option<HexBytes>,
+	/// Format of the archive.
+	#[structopt(long, possible_values = &DumpFormat::variants(), case_insensitive = true, default_value = "Json")]
+	format: DumpFormat,
+	/// Path to the archive file.
+	#[structopt(long)]
+	output: PathBuf,
+}
+
+/// Bridge state archive.
+#[derive(Debug, Encode, Decode, Serialize, PartialEq)]
+struct BridgeStateDump {
+	/// Name of the bridge.
+	bridge: String,
+	/// Bridge-related storage of the source chain.
+	source: ChainStateDump,
+	/// Bridge-related storage of the target chain.
+	target: ChainStateDump,
+}
+
+/// Bridge-related storage of single chain.
+#[derive(Debug, Encode, Decode, Serialize, PartialEq)]
+struct ChainStateDump {
+	/// Name of the chain.
+	chain: String,
+	/// Hash of the block, at which the storage has been read.
+	#[serde(serialize_with = "serialize_hex")]
+	block_hash: Vec<u8>,
+	/// Raw storage entries.
+	storage: Vec<StorageEntry>,
+}
+
+/// Single raw storage entry.
+#[derive(Debug, Encode, Decode, Serialize, PartialEq)]
+struct StorageEntry {
+	/// Storage key.
+	#[serde(serialize_with = "serialize_hex")]
+	key: Vec<u8>,
+	/// Encoded storage value.
+	#[serde(serialize_with = "serialize_hex")]
+	value: Vec<u8>,
+}
+
+impl DumpBridgeState {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		select_full_bridge!(self.bridge, {
+			let source_client = self.source.to_client::<Source>().await?;
+			let target_client = self.target.to_client::<Target>().await?;
+
+			let source = dump_chain_state(
+				&source_client,
+				self.source_block.as_ref(),
+				vec![
+					pallet_bridge_grandpa::storage_keys::pallet_prefix_key::<SourceRuntime, SourceGrandpaInstance>(),
+					pallet_bridge_messages::storage_keys::pallet_prefix_key::<SourceMessagesInstance>(),
+				],
+			)
+			.await?;
+			let target = dump_chain_state(
+				&target_client,
+				self.target_block.as_ref(),
+				vec![
+					pallet_bridge_grandpa::storage_keys::pallet_prefix_key::<TargetRuntime, TargetGrandpaInstance>(),
+					pallet_bridge_messages::storage_keys::pallet_prefix_key::<TargetMessagesInstance>(),
+				],
+			)
+			.await?;
+
+			let dump = BridgeStateDump {
+				bridge: format!("{:?}", self.bridge),
+				source,
+				target,
+			};
+			log::info!(
+				target: "bridge",
+				"Writing {} {} and {} {} storage entries to {}",
+				dump.source.storage.len(),
+				dump.source.chain,
+				dump.target.storage.len(),
+				dump.target.chain,
+				self.output.display(),
+			);
+			std::fs::write(&self.output, encode_dump(&dump, &self.format)?)?;
+			Ok(())
+		})
+	}
+}
+
+/// Read bridge-related storage of given chain.
+async fn dump_chain_state<C: Chain>(
+	client: &Client<C>,
+	at_block: Option<&HexBytes>,
+	prefixes: Vec<StorageKey>,
+) -> anyhow::Result<ChainStateDump> {
+	let at_block = match at_block {
+		Some(at_block) => C::Hash::decode(&mut &at_block.0[..])
+			.map_err(|e| anyhow::format_err!("Invalid {} block hash {:?}: {:?}", C::NAME, at_block, e))?,
+		None => client.best_finalized_header_hash().await?,
+	};
+
+	let mut storage = Vec::new();
+	for prefix in prefixes {
+		let pairs = client.storage_pairs_at(prefix, at_block).await?;
+		storage.extend(pairs.into_iter().map(|(key, value)| StorageEntry {
+			key: key.0,
+			value: value.0,
+		}));
+	}
+
+	Ok(ChainStateDump {
+		chain: C::NAME.into(),
+		block_hash: at_block.encode(),
+		storage,
+	})
+}
+
+/// Encode archive using given format.
+fn encode_dump(dump: &BridgeStateDump, format: &DumpFormat) -> anyhow::Result<Vec<u8>> {
+	Ok(match *format {
+		DumpFormat::Json => serde_json::to_vec_pretty(dump)?,
+		DumpFormat::Scale => dump.encode(),
+	})
+}
+
+/// Serialize raw bytes as 0x-prefixed hex string.
+fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn test_dump() -> BridgeStateDump {
+		BridgeStateDump {
+			bridge: "MillauToRialto".into(),
+			source: ChainStateDump {
+				chain: "Millau".into(),
+				block_hash: vec![1; 32],
+				storage: vec![StorageEntry {
+					key: vec![0x01, 0x02],
+					value: vec![0x03],
+				}],
+			},
+			target: ChainStateDump {
+				chain: "Rialto".into(),
+				block_hash: vec![2; 32],
+				storage: vec![],
+			},
+		}
+	}
+
+	#[test]
+	fn should_parse_cli_options() {
+		// when
+		let res = DumpBridgeState::from_iter(vec![
+			"dump-bridge-state",
+			"MillauToRialto",
+			"--source-port",
+			"1234",
+			"--target-port",
+			"5678",
+			"--source-block",
+			"0102",
+			"--format",
+			"scale",
+			"--output",
+			"bridge-state.scale",
+		]);
+
+		// then
+		assert_eq!(
+			res,
+			DumpBridgeState {
+				bridge: FullBridge::MillauToRialto,
+				source: SourceConnectionParams {
+					source_host: "127.0.0.1".into(),
+					source_port: 1234,
+					source_secure: false,
+				},
+				target: TargetConnectionParams {
+					target_host: "127.0.0.1".into(),
+					target_port: 5678,
+					target_secure: false,
+				},
+				source_block: Some(HexBytes(vec![0x01, 0x02])),
+				target_block: None,
+				format: DumpFormat::Scale,
+				output: "bridge-state.scale".into(),
+			}
+		);
+	}
+
+	#[test]
+	fn scale_dump_is_decodable() {
+		let dump = test_dump();
+		let encoded = encode_dump(&dump, &DumpFormat::Scale).unwrap();
+		assert_eq!(BridgeStateDump::decode(&mut &encoded[..]).unwrap(), dump);
+	}
+
+	#[test]
+	fn json_dump_uses_hex_encoding() {
+		let encoded = encode_dump(&test_dump(), &DumpFormat::Json).unwrap();
+		let json: serde_json::Value = serde_json::from_slice(&encoded).unwrap();
+		assert_eq!(json["source"]["storage"][0]["key"], "0x0102");
+		assert_eq!(json["source"]["storage"][0]["value"], "0x03");
+		assert_eq!(json["target"]["chain"], "Rialto");
+	}
+}
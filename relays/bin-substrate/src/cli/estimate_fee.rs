@@ -15,8 +15,10 @@
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::cli::bridge::FullBridge;
-use crate::cli::{Balance, CliChain, HexBytes, HexLaneId, SourceConnectionParams};
+use crate::cli::{Balance, BridgeInstanceId, CliChain, HexBytes, HexLaneId, SourceConnectionParams};
+use crate::messages_source::decode_bridge_instance_response;
 use crate::select_full_bridge;
+use bp_runtime::ChainId;
 use codec::{Decode, Encode};
 use relay_substrate_client::Chain;
 use structopt::StructOpt;
@@ -32,6 +34,9 @@ pub struct EstimateFee {
 	/// Hex-encoded id of lane that will be delivering the message.
 	#[structopt(long, default_value = "00000000")]
 	lane: HexLaneId,
+	/// Instance of bridge pallets at the source chain. Defaults to instance that bridges with the target chain.
+	#[structopt(long)]
+	source_bridge_instance: Option<BridgeInstanceId>,
 	/// Payload to send over the bridge.
 	#[structopt(flatten)]
 	payload: crate::cli::encode_message::MessagePayload,
@@ -44,17 +49,24 @@ impl EstimateFee {
 			source,
 			bridge,
 			lane,
+			source_bridge_instance,
 			payload,
 		} = self;
 
 		select_full_bridge!(bridge, {
 			let source_client = source.to_client::<Source>().await?;
 			let lane = lane.into();
+			let instance = source_bridge_instance.map(Into::into).unwrap_or(SOURCE_BRIDGE_INSTANCE);
 			let payload = Source::encode_message(payload).map_err(|e| anyhow::format_err!("{:?}", e))?;
 
-			let fee: <Source as Chain>::Balance =
-				estimate_message_delivery_and_dispatch_fee(&source_client, ESTIMATE_MESSAGE_FEE_METHOD, lane, payload)
-					.await?;
+			let fee: <Source as Chain>::Balance = estimate_message_delivery_and_dispatch_fee(
+				&source_client,
+				ESTIMATE_MESSAGE_FEE_METHOD,
+				instance,
+				lane,
+				payload,
+			)
+			.await?;
 
 			log::info!(target: "bridge", "Fee: {:?}", Balance(fee as _));
 			println!("{}", fee);
@@ -66,14 +78,18 @@ impl EstimateFee {
 pub(crate) async fn estimate_message_delivery_and_dispatch_fee<Fee: Decode, C: Chain, P: Encode>(
 	client: &relay_substrate_client::Client<C>,
 	estimate_fee_method: &str,
+	instance: ChainId,
 	lane: bp_messages::LaneId,
 	payload: P,
 ) -> anyhow::Result<Fee> {
 	let encoded_response = client
-		.state_call(estimate_fee_method.into(), (lane, payload).encode().into(), None)
+		.state_call(
+			estimate_fee_method.into(),
+			(instance, lane, payload).encode().into(),
+			None,
+		)
 		.await?;
-	let decoded_response: Option<Fee> =
-		Decode::decode(&mut &encoded_response.0[..]).map_err(relay_substrate_client::Error::ResponseParseFailed)?;
+	let decoded_response: Option<Fee> = decode_bridge_instance_response(estimate_fee_method, &encoded_response.0)?;
 	let fee = decoded_response
 		.ok_or_else(|| anyhow::format_err!("Unable to decode fee from: {:?}", HexBytes(encoded_response.to_vec())))?;
 	Ok(fee)
@@ -110,6 +126,7 @@ mod tests {
 			EstimateFee {
 				bridge: FullBridge::RialtoToMillau,
 				lane: HexLaneId([0, 0, 0, 0]),
+				source_bridge_instance: None,
 				source: SourceConnectionParams {
 					source_host: "127.0.0.1".into(),
 					source_port: 1234,
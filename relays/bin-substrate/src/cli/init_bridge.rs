@@ -131,6 +131,21 @@ macro_rules! select_bridge {
 }
 
 impl InitBridge {
+	/// Create command that initializes given bridge.
+	pub(crate) fn new(
+		bridge: InitBridgeName,
+		source: SourceConnectionParams,
+		target: TargetConnectionParams,
+		target_sign: TargetSigningParams,
+	) -> Self {
+		InitBridge {
+			bridge,
+			source,
+			target,
+			target_sign,
+		}
+	}
+
 	/// Run the command.
 	pub async fn run(self) -> anyhow::Result<()> {
 		select_bridge!(self.bridge, {
@@ -0,0 +1,478 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Load-generation command that produces sustained message traffic over the bridge.
+//!
+//! The command submits configured number of `send_message` transactions at every new source
+//! block, for every selected lane. Delivery latency is measured between the moment when the
+//! message nonce is observed as generated at the source chain and the moment when it is
+//! observed as received at the target chain.
+
+use crate::cli::bridge::FullBridge;
+use crate::cli::encode_call::{self, CliEncodeCall};
+use crate::cli::estimate_fee::estimate_message_delivery_and_dispatch_fee;
+use crate::cli::send_message::{compute_maximal_message_dispatch_weight, message_payload};
+use crate::cli::{
+	Balance, BridgeInstanceParams, CliChain, ExplicitOrMaximal, HexBytes, HexLaneId, SourceConnectionParams,
+	SourceSigningParams, TargetConnectionParams,
+};
+use crate::messages_source::decode_bridge_instance_response;
+use bp_message_dispatch::CallOrigin;
+use bp_messages::{LaneId, MessageNonce};
+use bp_runtime::ChainId;
+use codec::Encode;
+use frame_support::dispatch::GetDispatchInfo;
+use relay_substrate_client::{Chain, Client, TransactionSignScheme};
+use sp_core::{Bytes, Pair};
+use sp_runtime::{
+	traits::{Header as HeaderT, IdentifyAccount},
+	MultiSigner,
+};
+use std::{
+	collections::VecDeque,
+	time::{Duration, Instant},
+};
+use structopt::StructOpt;
+
+/// Generate sustained message traffic over the bridge and report delivery throughput and latency.
+#[derive(StructOpt)]
+pub struct LoadTest {
+	/// A bridge instance to generate messages for.
+	#[structopt(possible_values = &FullBridge::variants(), case_insensitive = true)]
+	bridge: FullBridge,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	#[structopt(flatten)]
+	source_sign: SourceSigningParams,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	/// Hex-encoded ids of lanes to send messages over. Defaults to `00000000`.
+	#[structopt(long = "lane", default_value = "00000000")]
+	lanes: Vec<HexLaneId>,
+	#[structopt(flatten)]
+	bridge_instance: BridgeInstanceParams,
+	/// Number of messages that are sent over every lane at every source block.
+	#[structopt(long, default_value = "1")]
+	messages_per_block: u32,
+	/// Number of source blocks during which messages are sent.
+	#[structopt(long, default_value = "10")]
+	blocks: u32,
+	/// Size of the remark that is sent in every message. If not passed, small UTF8-encoded
+	/// string is generated by relay as remark.
+	#[structopt(long)]
+	remark_size: Option<ExplicitOrMaximal<usize>>,
+	/// Delivery and dispatch fee of every message in source chain base currency units. If not
+	/// passed, determined automatically.
+	#[structopt(long)]
+	fee: Option<Balance>,
+	/// Time (in seconds) to wait for delivery of all generated messages after the last message
+	/// has been sent.
+	#[structopt(long, default_value = "300")]
+	delivery_timeout: u64,
+}
+
+impl LoadTest {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		crate::select_full_bridge!(self.bridge, {
+			let source_client = self.source.to_client::<Source>().await?;
+			let source_sign = self.source_sign.to_keypair::<Source>()?;
+			let target_client = self.target.to_client::<Target>().await?;
+			let source_instance = self.bridge_instance.source_or(SOURCE_BRIDGE_INSTANCE);
+			let target_instance = self.bridge_instance.target_or(TARGET_BRIDGE_INSTANCE);
+
+			let mut remark = encode_call::Call::Remark {
+				remark_payload: None,
+				remark_size: self.remark_size,
+			};
+			encode_call::preprocess_call::<Source, Target>(&mut remark, self.bridge.bridge_instance_index());
+			let target_call = Target::encode_call(&remark)?;
+			let source_sender_public: MultiSigner = source_sign.public().into();
+			let payload = message_payload(
+				Target::RUNTIME_VERSION.spec_version,
+				std::cmp::min(
+					target_call.get_dispatch_info().weight,
+					compute_maximal_message_dispatch_weight(Target::max_extrinsic_weight()),
+				),
+				CallOrigin::SourceAccount(source_sender_public.into_account()),
+				bp_runtime::DispatchFeePayment::AtSourceChain,
+				&target_call,
+			);
+
+			let mut lanes = Vec::with_capacity(self.lanes.len());
+			for lane in self.lanes {
+				let lane_id: LaneId = lane.clone().into();
+				let fee = match self.fee {
+					Some(fee) => fee,
+					None => Balance(
+						estimate_message_delivery_and_dispatch_fee::<<Source as Chain>::Balance, _, _>(
+							&source_client,
+							ESTIMATE_MESSAGE_FEE_METHOD,
+							source_instance,
+							lane_id,
+							payload.clone(),
+						)
+						.await? as _,
+					),
+				};
+				let send_message_call = Source::encode_call(&encode_call::Call::BridgeSendMessage {
+					bridge_instance_index: self.bridge.bridge_instance_index(),
+					lane,
+					payload: HexBytes::encode(&payload),
+					fee,
+				})?;
+				let generated_nonce = read_nonce(
+					&source_client,
+					TO_TARGET_LATEST_GENERATED_NONCE_METHOD,
+					source_instance,
+					lane_id,
+				)
+				.await?;
+				let received_nonce = read_nonce(
+					&target_client,
+					FROM_SOURCE_LATEST_RECEIVED_NONCE_METHOD,
+					target_instance,
+					lane_id,
+				)
+				.await?;
+				lanes.push((
+					lane_id,
+					send_message_call,
+					LaneDeliveryTracker::new(generated_nonce, received_nonce),
+				));
+			}
+
+			log::info!(
+				target: "bridge",
+				"Starting {} -> {} load test: {} messages per block over {} lane(s) during {} blocks",
+				Source::NAME,
+				Target::NAME,
+				self.messages_per_block,
+				lanes.len(),
+				self.blocks,
+			);
+
+			let started_at = Instant::now();
+			let mut submitted_transactions = 0u64;
+			let mut rejected_transactions = 0u64;
+			let mut remaining_blocks = self.blocks;
+			let mut last_source_block = None;
+			let mut delivery_deadline = None;
+			loop {
+				// submit messages if we've seen new source block
+				if remaining_blocks != 0 {
+					let best_source_block = *source_client.best_header().await?.number();
+					if last_source_block.map(|last| best_source_block > last).unwrap_or(true) {
+						last_source_block = Some(best_source_block);
+						remaining_blocks -= 1;
+
+						for (lane_id, send_message_call, _) in &lanes {
+							for _ in 0..self.messages_per_block {
+								let send_message_call = send_message_call.clone();
+								let submit_result = source_client
+									.submit_signed_extrinsic(source_sign.public().into(), |transaction_nonce| {
+										Bytes(
+											Source::sign_transaction(
+												*source_client.genesis_hash(),
+												&source_sign,
+												transaction_nonce,
+												send_message_call,
+											)
+											.encode(),
+										)
+									})
+									.await;
+								match submit_result {
+									Ok(_) => submitted_transactions += 1,
+									Err(error) => {
+										rejected_transactions += 1;
+										log::warn!(
+											target: "bridge",
+											"Failed to submit message over lane {:?}: {:?}",
+											lane_id,
+											error,
+										);
+									}
+								}
+							}
+						}
+
+						if remaining_blocks == 0 {
+							delivery_deadline = Some(Instant::now() + Duration::from_secs(self.delivery_timeout));
+						}
+					}
+				}
+
+				// update delivery state of all lanes
+				for (lane_id, _, tracker) in &mut lanes {
+					let now = Instant::now();
+					let generated_nonce = read_nonce(
+						&source_client,
+						TO_TARGET_LATEST_GENERATED_NONCE_METHOD,
+						source_instance,
+						*lane_id,
+					)
+					.await?;
+					let received_nonce = read_nonce(
+						&target_client,
+						FROM_SOURCE_LATEST_RECEIVED_NONCE_METHOD,
+						target_instance,
+						*lane_id,
+					)
+					.await?;
+					tracker.on_generated(generated_nonce, now);
+					tracker.on_received(received_nonce, now);
+				}
+
+				// stop when all messages are delivered or when we have waited enough
+				if let Some(delivery_deadline) = delivery_deadline {
+					let all_delivered = lanes.iter().all(|(_, _, tracker)| tracker.is_idle())
+						&& lanes
+							.iter()
+							.map(|(_, _, tracker)| tracker.generated)
+							.sum::<MessageNonce>()
+							>= submitted_transactions;
+					if all_delivered {
+						break;
+					}
+					if Instant::now() > delivery_deadline {
+						log::warn!(target: "bridge", "Not all messages have been delivered before timeout");
+						break;
+					}
+				}
+
+				async_std::task::sleep(Source::AVERAGE_BLOCK_INTERVAL / 4).await;
+			}
+
+			let elapsed = started_at.elapsed();
+			let mut latencies = Vec::new();
+			let mut generated = 0;
+			let mut undelivered = 0;
+			for (lane_id, _, tracker) in lanes {
+				println!(
+					"Lane {}: generated {} messages, delivered {} messages, {} are still undelivered",
+					hex::encode(lane_id),
+					tracker.generated,
+					tracker.latencies.len(),
+					tracker.undelivered.len(),
+				);
+				generated += tracker.generated;
+				undelivered += tracker.undelivered.len();
+				latencies.extend(tracker.latencies);
+			}
+
+			println!("{} -> {} load test report", Source::NAME, Target::NAME);
+			println!("Submitted transactions: {}", submitted_transactions);
+			println!("Rejected transactions: {}", rejected_transactions);
+			println!("Generated messages: {}", generated);
+			println!("Delivered messages: {}", latencies.len());
+			println!("Undelivered messages: {}", undelivered);
+			println!("Elapsed time: {:.1}s", elapsed.as_secs_f64());
+			println!(
+				"Throughput: {:.2} messages/s",
+				latencies.len() as f64 / elapsed.as_secs_f64().max(1.0)
+			);
+			match LatencyReport::new(latencies) {
+				Some(report) => println!(
+					"Delivery latency: min {:.1}s, avg {:.1}s, p50 {:.1}s, p95 {:.1}s, max {:.1}s",
+					report.min.as_secs_f64(),
+					report.avg.as_secs_f64(),
+					report.p50.as_secs_f64(),
+					report.p95.as_secs_f64(),
+					report.max.as_secs_f64(),
+				),
+				None => println!("Delivery latency: no messages have been delivered"),
+			}
+		});
+
+		Ok(())
+	}
+}
+
+/// Read nonce using given runtime API method.
+async fn read_nonce<C: Chain>(
+	client: &Client<C>,
+	method: &str,
+	instance: ChainId,
+	lane: LaneId,
+) -> anyhow::Result<MessageNonce> {
+	let encoded_response = client
+		.state_call(method.into(), Bytes((instance, lane).encode()), None)
+		.await?;
+	let nonce: MessageNonce = decode_bridge_instance_response(method, &encoded_response.0)?;
+	Ok(nonce)
+}
+
+/// Tracks delivery of messages, generated over single lane.
+#[derive(Debug)]
+struct LaneDeliveryTracker {
+	/// Latest generated nonce that has been seen at the source chain.
+	latest_generated_nonce: MessageNonce,
+	/// Latest received nonce that has been seen at the target chain.
+	latest_received_nonce: MessageNonce,
+	/// Number of messages that have been generated since tracking has started.
+	generated: MessageNonce,
+	/// Generated, but not yet delivered messages along with the time they have been seen.
+	undelivered: VecDeque<(MessageNonce, Instant)>,
+	/// Delivery latencies of delivered messages.
+	latencies: Vec<Duration>,
+}
+
+impl LaneDeliveryTracker {
+	/// Create new tracker, starting with given nonces.
+	fn new(latest_generated_nonce: MessageNonce, latest_received_nonce: MessageNonce) -> Self {
+		LaneDeliveryTracker {
+			latest_generated_nonce,
+			// messages that have been generated before we have started, are ignored
+			latest_received_nonce: std::cmp::max(latest_generated_nonce, latest_received_nonce),
+			generated: 0,
+			undelivered: VecDeque::new(),
+			latencies: Vec::new(),
+		}
+	}
+
+	/// Returns true if all generated messages have been delivered.
+	fn is_idle(&self) -> bool {
+		self.undelivered.is_empty()
+	}
+
+	/// Called when latest generated nonce is read from the source chain.
+	fn on_generated(&mut self, latest_generated_nonce: MessageNonce, now: Instant) {
+		while self.latest_generated_nonce < latest_generated_nonce {
+			self.latest_generated_nonce += 1;
+			self.generated += 1;
+			if self.latest_generated_nonce > self.latest_received_nonce {
+				self.undelivered.push_back((self.latest_generated_nonce, now));
+			}
+		}
+	}
+
+	/// Called when latest received nonce is read from the target chain.
+	fn on_received(&mut self, latest_received_nonce: MessageNonce, now: Instant) {
+		if latest_received_nonce <= self.latest_received_nonce {
+			return;
+		}
+
+		self.latest_received_nonce = latest_received_nonce;
+		while let Some((nonce, generated_at)) = self.undelivered.front().cloned() {
+			if nonce > latest_received_nonce {
+				break;
+			}
+
+			self.undelivered.pop_front();
+			self.latencies.push(now.duration_since(generated_at));
+		}
+	}
+}
+
+/// Delivery latency statistics.
+#[derive(Debug, PartialEq)]
+struct LatencyReport {
+	min: Duration,
+	avg: Duration,
+	p50: Duration,
+	p95: Duration,
+	max: Duration,
+}
+
+impl LatencyReport {
+	/// Compute latency statistics. Returns `None` if there are no latencies.
+	fn new(mut latencies: Vec<Duration>) -> Option<Self> {
+		if latencies.is_empty() {
+			return None;
+		}
+
+		latencies.sort();
+		let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+		Some(LatencyReport {
+			min: latencies[0],
+			avg: latencies.iter().sum::<Duration>() / latencies.len() as u32,
+			p50: percentile(50),
+			p95: percentile(95),
+			max: latencies[latencies.len() - 1],
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lane_delivery_tracker_computes_latencies() {
+		let started_at = Instant::now();
+		let mut tracker = LaneDeliveryTracker::new(10, 5);
+
+		// messages 6..=10 have been generated before we've started => ignored
+		tracker.on_received(8, started_at);
+		assert!(tracker.latencies.is_empty());
+
+		tracker.on_generated(12, started_at);
+		tracker.on_generated(13, started_at + Duration::from_secs(1));
+		assert_eq!(tracker.generated, 3);
+		assert!(!tracker.is_idle());
+
+		tracker.on_received(12, started_at + Duration::from_secs(5));
+		assert_eq!(tracker.latencies, vec![Duration::from_secs(5), Duration::from_secs(5)]);
+
+		tracker.on_received(13, started_at + Duration::from_secs(7));
+		assert_eq!(
+			tracker.latencies,
+			vec![Duration::from_secs(5), Duration::from_secs(5), Duration::from_secs(6)]
+		);
+		assert!(tracker.is_idle());
+	}
+
+	#[test]
+	fn latency_report_is_computed() {
+		assert_eq!(LatencyReport::new(vec![]), None);
+		assert_eq!(
+			LatencyReport::new((1..=20).rev().map(Duration::from_secs).collect()),
+			Some(LatencyReport {
+				min: Duration::from_secs(1),
+				avg: Duration::from_millis(10_500),
+				p50: Duration::from_secs(10),
+				p95: Duration::from_secs(19),
+				max: Duration::from_secs(20),
+			}),
+		);
+	}
+
+	#[test]
+	fn should_parse_cli_options() {
+		let load_test = LoadTest::from_iter(vec![
+			"load-test",
+			"MillauToRialto",
+			"--source-port",
+			"1234",
+			"--source-signer",
+			"//Alice",
+			"--target-port",
+			"5678",
+			"--lane",
+			"00000000",
+			"--lane",
+			"00000001",
+			"--messages-per-block",
+			"5",
+		]);
+
+		assert_eq!(load_test.bridge, FullBridge::MillauToRialto);
+		assert_eq!(load_test.lanes, vec![HexLaneId([0, 0, 0, 0]), HexLaneId([0, 0, 0, 1])]);
+		assert_eq!(load_test.messages_per_block, 5);
+		assert_eq!(load_test.blocks, 10);
+	}
+}
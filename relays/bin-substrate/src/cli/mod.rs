@@ -16,9 +16,10 @@
 
 //! Deal with CLI args of substrate-to-substrate relay.
 
-use std::convert::TryInto;
+use std::{convert::TryInto, path::PathBuf};
 
 use bp_messages::LaneId;
+use bp_runtime::ChainId;
 use codec::{Decode, Encode};
 use frame_support::weights::Weight;
 use sp_runtime::app_crypto::Ss58Codec;
@@ -31,10 +32,15 @@ pub(crate) mod estimate_fee;
 pub(crate) mod send_message;
 
 mod derive_account;
+mod dump_bridge_state;
 mod init_bridge;
+mod load_test;
+mod query_rewards;
 mod relay_headers;
 mod relay_headers_and_messages;
 mod relay_messages;
+mod run_bridge;
+mod setup;
 
 /// Parse relay CLI args.
 pub fn parse_args() -> Command {
@@ -50,6 +56,11 @@ pub enum Command {
 	/// The on-chain bridge component should have been already initialized with
 	/// `init-bridge` sub-command.
 	RelayHeaders(relay_headers::RelayHeaders),
+	/// Start headers relay from single source node to several target nodes.
+	///
+	/// Source headers and finality proofs are read once and are shared by relay loops of all
+	/// target nodes. Every target node should be initialized with `init-bridge` sub-command.
+	RelayHeadersToManyTargets(relay_headers::RelayHeadersToManyTargets),
 	/// Start messages relay between two chains.
 	///
 	/// Ties up to `Messages` pallets on both chains and starts relaying messages.
@@ -62,6 +73,17 @@ pub enum Command {
 	/// the message relays - i.e. when there are messages or confirmations that needs to be
 	/// relayed between chains.
 	RelayHeadersAndMessages(relay_headers_and_messages::RelayHeadersAndMessages),
+	/// Start all relays of the complete bridge, configured with single TOML file.
+	///
+	/// Starts finality relays in both directions and message relays for all configured lanes in
+	/// both directions. All relays are supervised and share the same metrics endpoint.
+	RunBridge(run_bridge::RunBridge),
+	/// Guided setup of the complete bridge.
+	///
+	/// Checks that both chains are compatible with the relay, initializes finality pallets,
+	/// validates lanes, generates relayer keys and the `run-bridge` configuration file and
+	/// (optionally) starts all relays.
+	Setup(setup::Setup),
 	/// Initialize on-chain bridge pallet with current header data.
 	///
 	/// Sends initialization transaction to bootstrap the bridge with current finalized block data.
@@ -86,6 +108,22 @@ pub enum Command {
 	EstimateFee(estimate_fee::EstimateFee),
 	/// Given a source chain `AccountId`, derive the corresponding `AccountId` for the target chain.
 	DeriveAccount(derive_account::DeriveAccount),
+	/// Generate sustained message traffic over the bridge.
+	///
+	/// Sends given number of messages over selected lanes at every source block and reports
+	/// delivery throughput and latency.
+	LoadTest(load_test::LoadTest),
+	/// List rewards that are owed to the relayer, but are not yet paid.
+	///
+	/// Rewards are paid at the source chain when message delivery confirmation is received.
+	/// This command lists rewards for messages that have been delivered by the relayer, but
+	/// delivery of which is not yet confirmed.
+	QueryRewards(query_rewards::QueryRewards),
+	/// Dump bridge-related storage of both chains into single JSON or SCALE-encoded archive.
+	///
+	/// The archive may be attached to bug reports and used to replay issues against local
+	/// test nodes.
+	DumpBridgeState(dump_bridge_state::DumpBridgeState),
 }
 
 impl Command {
@@ -94,7 +132,12 @@ impl Command {
 		use relay_utils::initialize::{initialize_logger, initialize_relay};
 
 		match self {
-			Self::RelayHeaders(_) | Self::RelayMessages(_) | Self::RelayHeadersAndMessages(_) | Self::InitBridge(_) => {
+			Self::RelayHeaders(_)
+			| Self::RelayHeadersToManyTargets(_)
+			| Self::RelayMessages(_)
+			| Self::RelayHeadersAndMessages(_)
+			| Self::RunBridge(_)
+			| Self::InitBridge(_) => {
 				initialize_relay();
 			}
 			_ => {
@@ -108,14 +151,20 @@ impl Command {
 		self.init_logger();
 		match self {
 			Self::RelayHeaders(arg) => arg.run().await?,
+			Self::RelayHeadersToManyTargets(arg) => arg.run().await?,
 			Self::RelayMessages(arg) => arg.run().await?,
 			Self::RelayHeadersAndMessages(arg) => arg.run().await?,
+			Self::RunBridge(arg) => arg.run().await?,
+			Self::Setup(arg) => arg.run().await?,
 			Self::InitBridge(arg) => arg.run().await?,
 			Self::SendMessage(arg) => arg.run().await?,
 			Self::EncodeCall(arg) => arg.run().await?,
 			Self::EncodeMessage(arg) => arg.run().await?,
 			Self::EstimateFee(arg) => arg.run().await?,
 			Self::DeriveAccount(arg) => arg.run().await?,
+			Self::LoadTest(arg) => arg.run().await?,
+			Self::QueryRewards(arg) => arg.run().await?,
+			Self::DumpBridgeState(arg) => arg.run().await?,
 		}
 		Ok(())
 	}
@@ -133,6 +182,28 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	#[derive(Debug)]
+	/// Where the message dispatch fee is paid.
+	///
+	/// - `AtSourceChain` - the message submitter pays dispatch fee at the source chain, as a part
+	///   of the message delivery and dispatch fee.
+	/// - `AtTargetChain` - the dispatch fee is paid by the dispatch origin account at the target chain.
+	pub enum DispatchFeePayment {
+		AtSourceChain,
+		AtTargetChain,
+	}
+}
+
+impl From<DispatchFeePayment> for bp_runtime::DispatchFeePayment {
+	fn from(dispatch_fee_payment: DispatchFeePayment) -> Self {
+		match dispatch_fee_payment {
+			DispatchFeePayment::AtSourceChain => bp_runtime::DispatchFeePayment::AtSourceChain,
+			DispatchFeePayment::AtTargetChain => bp_runtime::DispatchFeePayment::AtTargetChain,
+		}
+	}
+}
+
 /// Generic balance type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Balance(pub u128);
@@ -219,6 +290,16 @@ impl AccountId {
 	pub fn raw_id(&self) -> sp_runtime::AccountId32 {
 		self.account.clone()
 	}
+
+	/// Returns 20-byte (Ethereum-style) account id that corresponds to this account.
+	///
+	/// Chains that are using 20-byte accounts (e.g. Frontier-based chains) are using first 20 bytes
+	/// of the derived 256-bit account id.
+	pub fn to_account_id20(&self) -> bp_runtime::AccountId20 {
+		use sp_runtime::traits::Convert;
+		let raw_id: [u8; 32] = self.account.clone().into();
+		bp_runtime::AccountId20Converter::convert(raw_id.into())
+	}
 }
 
 /// Bridge-supported network definition.
@@ -230,8 +311,9 @@ pub trait CliChain: relay_substrate_client::Chain {
 
 	/// Crypto keypair type used to send messages.
 	///
-	/// In case of chains supporting multiple cryptos, pick one used by the CLI.
-	type KeyPair: sp_core::crypto::Pair;
+	/// In case of chains supporting multiple cryptos, pick one used by the CLI. It may be either
+	/// sr25519, ed25519 or ECDSA key pair.
+	type KeyPair: relay_substrate_client::MultiSignerKeyPair;
 
 	/// Bridge Message Payload type.
 	///
@@ -268,6 +350,95 @@ impl std::str::FromStr for HexLaneId {
 	}
 }
 
+/// Id of the bridge pallet instance.
+///
+/// May be specified either by name of the bridged chain (e.g. `rialto`) or by the raw
+/// four-character instance id (e.g. `rlto`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BridgeInstanceId(pub ChainId);
+
+impl From<BridgeInstanceId> for ChainId {
+	fn from(instance: BridgeInstanceId) -> ChainId {
+		instance.0
+	}
+}
+
+impl std::str::FromStr for BridgeInstanceId {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let instance = match s.to_lowercase().as_str() {
+			"millau" => bp_runtime::MILLAU_CHAIN_ID,
+			"rialto" => bp_runtime::RIALTO_CHAIN_ID,
+			"polkadot" => bp_runtime::POLKADOT_CHAIN_ID,
+			"kusama" => bp_runtime::KUSAMA_CHAIN_ID,
+			"rococo" => bp_runtime::ROCOCO_CHAIN_ID,
+			"wococo" => bp_runtime::WOCOCO_CHAIN_ID,
+			_ => s
+				.as_bytes()
+				.try_into()
+				.map_err(|_| format!("Expected bridged chain name or 4-character instance id. Got: {}", s))?,
+		};
+		Ok(BridgeInstanceId(instance))
+	}
+}
+
+/// Node websocket endpoint, specified as `host:port`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeEndpoint {
+	/// Connect to the node at given host.
+	pub host: String,
+	/// Connect to the node websocket server at given port.
+	pub port: u16,
+}
+
+impl std::str::FromStr for NodeEndpoint {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let separator = s
+			.rfind(':')
+			.ok_or_else(|| format!("Expected node endpoint in `host:port` format. Got: {}", s))?;
+		let (host, port) = (&s[..separator], &s[separator + 1..]);
+		if host.is_empty() {
+			return Err(format!("Node endpoint host is empty: {}", s));
+		}
+		let port = port
+			.parse()
+			.map_err(|e| format!("Invalid node endpoint port in {}: {}", s, e))?;
+		Ok(NodeEndpoint {
+			host: host.into(),
+			port,
+		})
+	}
+}
+
+/// Bridge pallet instances that need to be queried at both sides of the bridge.
+///
+/// Runtimes may host several instances of the same bridge pallet. By default we're using instances
+/// that are bridging source and target chains with each other.
+#[derive(StructOpt, Debug, Default, PartialEq, Eq)]
+pub struct BridgeInstanceParams {
+	/// Instance of bridge pallets at the source chain. Defaults to instance that bridges with the target chain.
+	#[structopt(long)]
+	pub source_bridge_instance: Option<BridgeInstanceId>,
+	/// Instance of bridge pallets at the target chain. Defaults to instance that bridges with the source chain.
+	#[structopt(long)]
+	pub target_bridge_instance: Option<BridgeInstanceId>,
+}
+
+impl BridgeInstanceParams {
+	/// Returns selected source chain instance or given default.
+	pub fn source_or(&self, default: ChainId) -> ChainId {
+		self.source_bridge_instance.map(Into::into).unwrap_or(default)
+	}
+
+	/// Returns selected target chain instance or given default.
+	pub fn target_or(&self, default: ChainId) -> ChainId {
+		self.target_bridge_instance.map(Into::into).unwrap_or(default)
+	}
+}
+
 /// Nicer formatting for raw bytes vectors.
 #[derive(Default, Encode, Decode, PartialEq, Eq)]
 pub struct HexBytes(pub Vec<u8>);
@@ -311,18 +482,104 @@ pub struct PrometheusParams {
 	/// Expose Prometheus endpoint at given port.
 	#[structopt(long, default_value = "9616")]
 	pub prometheus_port: u16,
+	/// Serve Prometheus endpoint over TLS using given PEM-encoded certificate chain.
+	#[structopt(long, requires("prometheus-tls-private-key"))]
+	pub prometheus_tls_certificate: Option<PathBuf>,
+	/// PEM-encoded private key of the Prometheus endpoint TLS certificate.
+	#[structopt(long, requires("prometheus-tls-certificate"))]
+	pub prometheus_tls_private_key: Option<PathBuf>,
+	/// Require HTTP basic authentication with given user name to access Prometheus endpoint.
+	#[structopt(long, requires("prometheus-password"), conflicts_with("prometheus-token"))]
+	pub prometheus_username: Option<String>,
+	/// Password of the Prometheus endpoint user.
+	#[structopt(long, requires("prometheus-username"))]
+	pub prometheus_password: Option<String>,
+	/// Require given bearer token to access Prometheus endpoint.
+	#[structopt(long)]
+	pub prometheus_token: Option<String>,
+	/// Additionally push metrics to the Prometheus push gateway at given URL.
+	#[structopt(long)]
+	pub prometheus_push_gateway: Option<String>,
+	/// Interval (in seconds) at which metrics are pushed to the push gateway.
+	#[structopt(long, default_value = "15")]
+	pub prometheus_push_interval: u64,
+	/// Value of the `job` label of metrics that are pushed to the push gateway.
+	#[structopt(long, default_value = "substrate-relay")]
+	pub prometheus_push_job: String,
+	/// Value of the `instance` label of metrics that are pushed to the push gateway.
+	#[structopt(long)]
+	pub prometheus_push_instance: Option<String>,
+	/// User name to authenticate at the push gateway.
+	#[structopt(long)]
+	pub prometheus_push_username: Option<String>,
+	/// Password to authenticate at the push gateway.
+	#[structopt(long)]
+	pub prometheus_push_password: Option<String>,
+	/// Store cumulative counters (delivered messages, earned rewards, ...) in given file, so that
+	/// they survive relay restarts.
+	#[structopt(long)]
+	pub prometheus_counters_file: Option<PathBuf>,
 }
 
 impl From<PrometheusParams> for relay_utils::metrics::MetricsParams {
 	fn from(cli_params: PrometheusParams) -> relay_utils::metrics::MetricsParams {
-		if !cli_params.no_prometheus {
+		let metrics_params: relay_utils::metrics::MetricsParams = if !cli_params.no_prometheus {
 			Some(relay_utils::metrics::MetricsAddress {
 				host: cli_params.prometheus_host,
 				port: cli_params.prometheus_port,
+				tls: cli_params
+					.prometheus_tls_certificate
+					.zip(cli_params.prometheus_tls_private_key)
+					.map(
+						|(certificate_path, private_key_path)| relay_utils::metrics::MetricsTls {
+							certificate_path,
+							private_key_path,
+						},
+					),
+				auth: match (
+					cli_params.prometheus_username,
+					cli_params.prometheus_password,
+					cli_params.prometheus_token,
+				) {
+					(Some(username), Some(password), _) => {
+						Some(relay_utils::metrics::MetricsAuth::Basic { username, password })
+					}
+					(_, _, Some(token)) => Some(relay_utils::metrics::MetricsAuth::Token(token)),
+					_ => None,
+				},
 			})
 			.into()
 		} else {
 			None.into()
+		};
+
+		let metrics_params = match cli_params.prometheus_counters_file {
+			Some(path) => match relay_utils::metrics::PersistentCounters::open(path) {
+				Ok(persistent_counters) => metrics_params.persistent_counters(persistent_counters),
+				Err(error) => {
+					log::error!(
+						target: "bridge",
+						"{}. Cumulative counters will be reset on relay restart",
+						error,
+					);
+					metrics_params
+				}
+			},
+			None => metrics_params,
+		};
+
+		match cli_params.prometheus_push_gateway {
+			Some(url) => metrics_params.push_gateway(relay_utils::metrics::PushGatewayParams {
+				url,
+				interval: std::time::Duration::from_secs(cli_params.prometheus_push_interval),
+				job: cli_params.prometheus_push_job,
+				instance: cli_params.prometheus_push_instance,
+				auth: cli_params
+					.prometheus_push_username
+					.zip(cli_params.prometheus_push_password)
+					.map(|(username, password)| relay_utils::metrics::PushGatewayAuth { username, password }),
+			}),
+			None => metrics_params,
 		}
 	}
 }
@@ -377,6 +634,9 @@ macro_rules! declare_chain_options {
 			#[derive(StructOpt, Debug, PartialEq, Eq)]
 			pub struct [<$chain SigningParams>] {
 				#[doc = "The SURI of secret key to use when transactions are submitted to the " $chain " node."]
+				#[doc = ""]
+				#[doc = "The key type (sr25519, ed25519 or ECDSA) is selected by the " $chain " chain."]
+				#[doc = "Note that ECDSA keys only support hard derivation paths (e.g. `//Alice`)."]
 				#[structopt(long)]
 				pub [<$chain_prefix _signer>]: String,
 				#[doc = "The password for the SURI of secret key to use when transactions are submitted to the " $chain " node."]
@@ -456,4 +716,28 @@ mod tests {
 		// then
 		assert_eq!(hex.0, hex2.0);
 	}
+
+	#[test]
+	fn bridge_instance_id_is_parsed_from_chain_name_or_raw_id() {
+		assert_eq!(
+			BridgeInstanceId::from_str("Rialto"),
+			Ok(BridgeInstanceId(bp_runtime::RIALTO_CHAIN_ID))
+		);
+		assert_eq!(BridgeInstanceId::from_str("abcd"), Ok(BridgeInstanceId(*b"abcd")));
+		assert!(BridgeInstanceId::from_str("unknown").is_err());
+	}
+
+	#[test]
+	fn node_endpoint_is_parsed_from_host_and_port() {
+		assert_eq!(
+			NodeEndpoint::from_str("127.0.0.1:9944"),
+			Ok(NodeEndpoint {
+				host: "127.0.0.1".into(),
+				port: 9944,
+			})
+		);
+		assert!(NodeEndpoint::from_str("127.0.0.1").is_err());
+		assert!(NodeEndpoint::from_str(":9944").is_err());
+		assert!(NodeEndpoint::from_str("127.0.0.1:port").is_err());
+	}
 }
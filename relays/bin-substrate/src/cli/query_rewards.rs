@@ -0,0 +1,226 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Command that lists rewards, that are owed to given relayer, but are not yet paid.
+//!
+//! The reward for message delivery is paid at the source chain, when delivery confirmation is
+//! received. So pending rewards are computed from unrewarded relayers entries of the inbound lane
+//! at the target chain (messages that have been delivered by the relayer) and message fees from
+//! the outbound lane at the source chain (only messages that are not yet confirmed there).
+
+use crate::cli::bridge::FullBridge;
+use crate::cli::{
+	AccountId, Balance, BridgeInstanceId, CliChain, HexLaneId, SourceConnectionParams, TargetConnectionParams,
+};
+use crate::messages_source::decode_bridge_instance_response;
+use crate::select_full_bridge;
+use bp_messages::{InboundLaneData, LaneId, MessageDetails, MessageNonce};
+use bp_runtime::ChainId;
+use codec::{Decode, Encode};
+use relay_substrate_client::{Chain, Client};
+use sp_core::Bytes;
+use std::ops::RangeInclusive;
+use structopt::StructOpt;
+
+/// Query pending relayer rewards command.
+#[derive(StructOpt, Debug, PartialEq, Eq)]
+pub struct QueryRewards {
+	/// A bridge instance to query rewards at.
+	#[structopt(possible_values = &FullBridge::variants(), case_insensitive = true)]
+	bridge: FullBridge,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	#[structopt(flatten)]
+	target: TargetConnectionParams,
+	/// Hex-encoded ids of lanes to query rewards at. Defaults to `00000000`.
+	#[structopt(long = "lane", default_value = "00000000")]
+	lanes: Vec<HexLaneId>,
+	/// Instance of bridge pallets at the source chain. Defaults to instance that bridges with the target chain.
+	#[structopt(long)]
+	source_bridge_instance: Option<BridgeInstanceId>,
+	/// Source chain account of the relayer, that is receiving rewards.
+	#[structopt(long)]
+	relayer: AccountId,
+}
+
+impl QueryRewards {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		select_full_bridge!(self.bridge, {
+			let source_client = self.source.to_client::<Source>().await?;
+			let target_client = self.target.to_client::<Target>().await?;
+			let relayer_id = self.relayer.raw_id();
+			let instance = self
+				.source_bridge_instance
+				.map(Into::into)
+				.unwrap_or(SOURCE_BRIDGE_INSTANCE);
+
+			let mut total_messages = 0;
+			let mut total_reward: <Source as Chain>::Balance = 0;
+			for lane in self.lanes.iter().cloned().map(LaneId::from) {
+				let lane_data: InboundLaneData<<Source as Chain>::AccountId> = target_client
+					.storage_value(pallet_bridge_messages::storage_keys::inbound_lane_data_key::<
+						TargetRuntime,
+						TargetMessagesInstance,
+					>(&lane))
+					.await?
+					.unwrap_or_default();
+				let latest_confirmed_nonce =
+					read_nonce(&source_client, TO_TARGET_LATEST_RECEIVED_NONCE_METHOD, instance, lane).await?;
+
+				let mut lane_messages = 0;
+				let mut lane_reward: <Source as Chain>::Balance = 0;
+				for nonces in pending_reward_nonces(&lane_data, &relayer_id, latest_confirmed_nonce) {
+					let messages: Vec<MessageDetails<<Source as Chain>::Balance>> =
+						read_message_details(&source_client, TO_TARGET_MESSAGE_DETAILS_METHOD, instance, lane, nonces)
+							.await?;
+					for message in messages {
+						lane_messages += 1;
+						lane_reward = lane_reward.saturating_add(message.delivery_and_dispatch_fee);
+					}
+				}
+
+				println!(
+					"{} lane {}: {} messages, pending reward {}",
+					Source::NAME,
+					hex::encode(lane),
+					lane_messages,
+					Balance(lane_reward as _),
+				);
+				total_messages += lane_messages;
+				total_reward = total_reward.saturating_add(lane_reward);
+			}
+
+			println!(
+				"{} total: {} messages, pending reward {}",
+				Source::NAME,
+				total_messages,
+				Balance(total_reward as _),
+			);
+			Ok(())
+		})
+	}
+}
+
+/// Returns ranges of messages, that have been delivered by given relayer, but delivery of which is
+/// not yet confirmed at the source chain.
+fn pending_reward_nonces<AccountId: PartialEq>(
+	lane_data: &InboundLaneData<AccountId>,
+	relayer: &AccountId,
+	latest_confirmed_nonce: MessageNonce,
+) -> Vec<RangeInclusive<MessageNonce>> {
+	lane_data
+		.relayers
+		.iter()
+		.filter(|(_, _, entry_relayer)| entry_relayer == relayer)
+		.map(|(begin, end, _)| std::cmp::max(*begin, latest_confirmed_nonce + 1)..=*end)
+		.filter(|nonces| !nonces.is_empty())
+		.collect()
+}
+
+/// Read nonce using given runtime API method.
+async fn read_nonce<C: Chain>(
+	client: &Client<C>,
+	method: &str,
+	instance: ChainId,
+	lane: LaneId,
+) -> anyhow::Result<MessageNonce> {
+	let encoded_response = client
+		.state_call(method.into(), Bytes((instance, lane).encode()), None)
+		.await?;
+	let nonce: MessageNonce = decode_bridge_instance_response(method, &encoded_response.0)?;
+	Ok(nonce)
+}
+
+/// Read details of given messages using given runtime API method.
+async fn read_message_details<C: Chain, Fee: Decode>(
+	client: &Client<C>,
+	method: &str,
+	instance: ChainId,
+	lane: LaneId,
+	nonces: RangeInclusive<MessageNonce>,
+) -> anyhow::Result<Vec<MessageDetails<Fee>>> {
+	let encoded_response = client
+		.state_call(
+			method.into(),
+			Bytes((instance, lane, nonces.start(), nonces.end()).encode()),
+			None,
+		)
+		.await?;
+	let details: Vec<MessageDetails<Fee>> = decode_bridge_instance_response(method, &encoded_response.0)?;
+	Ok(details)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::crypto::Ss58Codec;
+
+	#[test]
+	fn should_parse_cli_options() {
+		// given
+		let alice = sp_keyring::AccountKeyring::Alice.to_account_id().to_ss58check();
+
+		// when
+		let res = QueryRewards::from_iter(vec![
+			"query-rewards",
+			"RialtoToMillau",
+			"--source-port",
+			"1234",
+			"--target-port",
+			"5678",
+			"--lane",
+			"00000001",
+			"--relayer",
+			&alice,
+		]);
+
+		// then
+		assert_eq!(
+			res,
+			QueryRewards {
+				bridge: FullBridge::RialtoToMillau,
+				source: SourceConnectionParams {
+					source_host: "127.0.0.1".into(),
+					source_port: 1234,
+					source_secure: false,
+				},
+				target: TargetConnectionParams {
+					target_host: "127.0.0.1".into(),
+					target_port: 5678,
+					target_secure: false,
+				},
+				lanes: vec![HexLaneId([0, 0, 0, 1])],
+				source_bridge_instance: None,
+				relayer: alice.parse().unwrap(),
+			}
+		);
+	}
+
+	#[test]
+	fn pending_reward_nonces_are_computed() {
+		let lane_data = InboundLaneData {
+			relayers: vec![(1, 3, 100), (4, 5, 200), (6, 10, 100)].into_iter().collect(),
+			..Default::default()
+		};
+
+		assert_eq!(pending_reward_nonces(&lane_data, &100, 0), vec![1..=3, 6..=10]);
+		assert_eq!(pending_reward_nonces(&lane_data, &100, 2), vec![3..=3, 6..=10]);
+		assert_eq!(pending_reward_nonces(&lane_data, &100, 7), vec![8..=10]);
+		assert_eq!(pending_reward_nonces(&lane_data, &200, 5), vec![]);
+		assert_eq!(pending_reward_nonces(&lane_data, &300, 0), vec![]);
+	}
+}
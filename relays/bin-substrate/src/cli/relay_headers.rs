@@ -14,8 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{PrometheusParams, SourceConnectionParams, TargetConnectionParams, TargetSigningParams};
+use crate::cli::{
+	Balance, NodeEndpoint, PrometheusParams, SourceConnectionParams, TargetConnectionParams, TargetSigningParams,
+};
 use crate::finality_pipeline::SubstrateFinalitySyncPipeline;
+
+use finality_relay::FinalitySubmissionBudget;
+use relay_substrate_client::{Chain, Client, TipEscalation};
+use std::time::Duration;
 use structopt::{clap::arg_enum, StructOpt};
 
 /// Start headers relayer process.
@@ -32,6 +38,70 @@ pub struct RelayHeaders {
 	target_sign: TargetSigningParams,
 	#[structopt(flatten)]
 	prometheus_params: PrometheusParams,
+	/// Maximal number of headers that may be submitted to the target chain within the
+	/// submission budget period. If not specified, the number of submitted headers is not limited.
+	#[structopt(long)]
+	max_submitted_headers: Option<usize>,
+	/// Submission budget period (in seconds).
+	#[structopt(long, default_value = "60")]
+	submission_budget_period: u64,
+	/// Number of the first source header to read. If not specified, relay starts reading headers
+	/// from the best source header known to the target chain. Use with caution: if there are
+	/// headers that change GRANDPA authorities set in the skipped range, the relay will stall.
+	#[structopt(long)]
+	start_block: Option<u32>,
+	/// Only relay mandatory headers (headers that are changing GRANDPA authorities set). Make sure
+	/// the target chain pallet accepts them: it may be switched to the operating mode that is
+	/// rejecting all non-mandatory headers.
+	#[structopt(long)]
+	only_mandatory_headers: bool,
+	/// Tip that is paid for mandatory header submission when the target chain fee multiplier is two.
+	/// The actual tip is scaled proportionally to the fee multiplier, and is zero when the target chain
+	/// is not congested.
+	#[structopt(long, requires("mandatory-headers-max-tip"))]
+	mandatory_headers_base_tip: Option<Balance>,
+	/// Maximal tip that may be paid for mandatory header submission.
+	#[structopt(long, requires("mandatory-headers-base-tip"))]
+	mandatory_headers_max_tip: Option<Balance>,
+	/// Cross-check source chain justifications and report GRANDPA equivocations to the source chain.
+	#[structopt(long)]
+	report_equivocations: bool,
+}
+
+/// Start headers relayer process that relays headers from single source node to several target nodes.
+#[derive(StructOpt)]
+pub struct RelayHeadersToManyTargets {
+	/// A bridge instance to relay headers for.
+	#[structopt(possible_values = &RelayHeadersBridge::variants(), case_insensitive = true)]
+	bridge: RelayHeadersBridge,
+	#[structopt(flatten)]
+	source: SourceConnectionParams,
+	/// Websocket endpoint (`host:port`) of the target node. May be specified several times.
+	#[structopt(long = "target", required = true, number_of_values = 1)]
+	targets: Vec<NodeEndpoint>,
+	/// Use secure websocket connections to target nodes.
+	#[structopt(long)]
+	target_secure: bool,
+	#[structopt(flatten)]
+	target_sign: TargetSigningParams,
+	#[structopt(flatten)]
+	prometheus_params: PrometheusParams,
+	/// Maximal number of source headers (with finality proofs) that are cached and shared by
+	/// relay loops of all target nodes.
+	#[structopt(long, default_value = "1024")]
+	source_cache_size: usize,
+	/// Only relay mandatory headers (headers that are changing GRANDPA authorities set).
+	#[structopt(long)]
+	only_mandatory_headers: bool,
+	/// Tip that is paid for mandatory header submission when the target chain fee multiplier is two.
+	#[structopt(long, requires("mandatory-headers-max-tip"))]
+	mandatory_headers_base_tip: Option<Balance>,
+	/// Maximal tip that may be paid for mandatory header submission.
+	#[structopt(long, requires("mandatory-headers-base-tip"))]
+	mandatory_headers_max_tip: Option<Balance>,
+	/// Cross-check source chain justifications and report GRANDPA equivocations to the source chain.
+	#[structopt(long)]
+	report_equivocations: bool,
 }
 
 // TODO [#851] Use kebab-case.
@@ -55,6 +125,11 @@ macro_rules! select_bridge {
 				type Target = relay_rialto_client::Rialto;
 				type Finality = crate::chains::millau_headers_to_rialto::MillauFinalityToRialto;
 
+				fn start_equivocations_detector(source_client: Client<Source>) -> anyhow::Result<()> {
+					relay_substrate_client::equivocation::start_equivocations_detector(source_client);
+					Ok(())
+				}
+
 				$generic
 			}
 			RelayHeadersBridge::RialtoToMillau => {
@@ -62,6 +137,11 @@ macro_rules! select_bridge {
 				type Target = relay_millau_client::Millau;
 				type Finality = crate::chains::rialto_headers_to_millau::RialtoFinalityToMillau;
 
+				fn start_equivocations_detector(source_client: Client<Source>) -> anyhow::Result<()> {
+					relay_substrate_client::equivocation::start_equivocations_detector(source_client);
+					Ok(())
+				}
+
 				$generic
 			}
 			RelayHeadersBridge::WestendToMillau => {
@@ -69,6 +149,13 @@ macro_rules! select_bridge {
 				type Target = relay_millau_client::Millau;
 				type Finality = crate::chains::westend_headers_to_millau::WestendFinalityToMillau;
 
+				fn start_equivocations_detector(_source_client: Client<Source>) -> anyhow::Result<()> {
+					Err(anyhow::format_err!(
+						"Reporting {} GRANDPA equivocations is not supported",
+						Source::NAME,
+					))
+				}
+
 				$generic
 			}
 			RelayHeadersBridge::RococoToWococo => {
@@ -76,6 +163,13 @@ macro_rules! select_bridge {
 				type Target = relay_wococo_client::Wococo;
 				type Finality = crate::chains::rococo_headers_to_wococo::RococoFinalityToWococo;
 
+				fn start_equivocations_detector(_source_client: Client<Source>) -> anyhow::Result<()> {
+					Err(anyhow::format_err!(
+						"Reporting {} GRANDPA equivocations is not supported",
+						Source::NAME,
+					))
+				}
+
 				$generic
 			}
 			RelayHeadersBridge::WococoToRococo => {
@@ -83,6 +177,13 @@ macro_rules! select_bridge {
 				type Target = relay_rococo_client::Rococo;
 				type Finality = crate::chains::wococo_headers_to_rococo::WococoFinalityToRococo;
 
+				fn start_equivocations_detector(_source_client: Client<Source>) -> anyhow::Result<()> {
+					Err(anyhow::format_err!(
+						"Reporting {} GRANDPA equivocations is not supported",
+						Source::NAME,
+					))
+				}
+
 				$generic
 			}
 		}
@@ -97,10 +198,79 @@ impl RelayHeaders {
 			let target_client = self.target.to_client::<Target>().await?;
 			let target_sign = self.target_sign.to_keypair::<Target>()?;
 			let metrics_params = Finality::customize_metrics(self.prometheus_params.into())?;
+			let submission_budget = self.max_submitted_headers.map(|max_headers| FinalitySubmissionBudget {
+				max_headers,
+				period: Duration::from_secs(self.submission_budget_period),
+			});
+			let mandatory_headers_tip_escalation = self
+				.mandatory_headers_base_tip
+				.zip(self.mandatory_headers_max_tip)
+				.map(|(base_tip, max_tip)| TipEscalation {
+					base_tip: base_tip.cast().into(),
+					max_tip: max_tip.cast().into(),
+				});
+			if self.report_equivocations {
+				start_equivocations_detector(source_client.clone())?;
+			}
+
 			let finality = Finality::new(target_client.clone(), target_sign);
 			finality.start_relay_guards();
 
-			crate::finality_pipeline::run(finality, source_client, target_client, metrics_params).await
+			crate::finality_pipeline::run(
+				finality,
+				source_client,
+				target_client,
+				submission_budget,
+				self.start_block,
+				self.only_mandatory_headers,
+				mandatory_headers_tip_escalation,
+				metrics_params,
+			)
+			.await
+		})
+	}
+}
+
+impl RelayHeadersToManyTargets {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		select_bridge!(self.bridge, {
+			let source_client = self.source.to_client::<Source>().await?;
+			let target_sign = self.target_sign.to_keypair::<Target>()?;
+			let metrics_params = Finality::customize_metrics(self.prometheus_params.into())?;
+			let mandatory_headers_tip_escalation = self
+				.mandatory_headers_base_tip
+				.zip(self.mandatory_headers_max_tip)
+				.map(|(base_tip, max_tip)| TipEscalation {
+					base_tip: base_tip.cast().into(),
+					max_tip: max_tip.cast().into(),
+				});
+			if self.report_equivocations {
+				start_equivocations_detector(source_client.clone())?;
+			}
+
+			let mut targets = Vec::with_capacity(self.targets.len());
+			for target in self.targets {
+				let target_client = Client::<Target>::new(relay_substrate_client::ConnectionParams {
+					host: target.host,
+					port: target.port,
+					secure: self.target_secure,
+				})
+				.await;
+				let finality = Finality::new(target_client.clone(), target_sign.clone());
+				finality.start_relay_guards();
+				targets.push((finality, target_client));
+			}
+
+			crate::finality_pipeline::run_to_many_targets(
+				targets,
+				source_client,
+				self.source_cache_size,
+				self.only_mandatory_headers,
+				mandatory_headers_tip_escalation,
+				metrics_params,
+			)
+			.await
 		})
 	}
 }
@@ -16,8 +16,8 @@
 
 use crate::cli::bridge::FullBridge;
 use crate::cli::{
-	HexLaneId, PrometheusParams, SourceConnectionParams, SourceSigningParams, TargetConnectionParams,
-	TargetSigningParams,
+	BridgeInstanceParams, HexLaneId, PrometheusParams, SourceConnectionParams, SourceSigningParams,
+	TargetConnectionParams, TargetSigningParams,
 };
 use crate::messages_lane::MessagesRelayParams;
 use crate::select_full_bridge;
@@ -34,6 +34,12 @@ pub struct RelayMessages {
 	#[structopt(long, default_value = "00000000")]
 	lane: HexLaneId,
 	#[structopt(flatten)]
+	bridge_instance: BridgeInstanceParams,
+	/// Skip outdated messages and only deliver the latest messages that fit into single delivery
+	/// transaction. May only be used at lanes with unordered messages.
+	#[structopt(long)]
+	skip_outdated_messages: bool,
+	#[structopt(flatten)]
 	source: SourceConnectionParams,
 	#[structopt(flatten)]
 	source_sign: SourceSigningParams,
@@ -62,6 +68,9 @@ impl RelayMessages {
 				source_to_target_headers_relay: None,
 				target_to_source_headers_relay: None,
 				lane_id: self.lane.into(),
+				source_instance: self.bridge_instance.source_bridge_instance.map(Into::into),
+				target_instance: self.bridge_instance.target_bridge_instance.map(Into::into),
+				skip_outdated_messages: self.skip_outdated_messages,
 				metrics_params: self.prometheus_params.into(),
 			})
 			.await
@@ -0,0 +1,640 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! All-in-one bridge relay, configured with single TOML file.
+//!
+//! The `run-bridge` command starts every loop that the complete bridge needs: finality relays in
+//! both directions (either complete or on-demand) and message relays for all configured lanes in
+//! both directions. All loops are reporting metrics to the same Prometheus endpoint. Loops are
+//! supervised: if some loop exits, it is restarted and the health endpoint reports failure until
+//! the loop is started again.
+//!
+//! Example configuration:
+//!
+//! ```toml
+//! bridge = "millau-rialto"
+//! lanes = ["00000000"]
+//! relay_all_headers = false
+//!
+//! [left]
+//! host = "127.0.0.1"
+//! port = 9945
+//! signer = "//Alice"
+//!
+//! [right]
+//! host = "127.0.0.1"
+//! port = 9944
+//! signer = "//Bob"
+//!
+//! [prometheus]
+//! port = 9616
+//!
+//! [health]
+//! port = 9617
+//! ```
+
+use crate::cli::{CliChain, HexLaneId};
+use crate::finality_pipeline::SubstrateFinalitySyncPipeline;
+use crate::messages_lane::MessagesRelayParams;
+use crate::on_demand_headers::OnDemandHeadersRelay;
+
+use async_std::net::TcpListener;
+use futures::{future::BoxFuture, AsyncReadExt, AsyncWriteExt, FutureExt, StreamExt, TryFutureExt};
+use relay_utils::metrics::{MetricsAddress, MetricsParams, PersistentCounters};
+use serde::{Deserialize, Serialize};
+use sp_core::crypto::Pair;
+use std::{
+	collections::BTreeMap,
+	net::SocketAddr,
+	path::PathBuf,
+	str::FromStr,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+use structopt::StructOpt;
+
+/// Default delay before restarting exited loop.
+pub(crate) const DEFAULT_RESTART_DELAY_SECS: u64 = 10;
+
+/// Start all loops of the complete bridge.
+#[derive(StructOpt)]
+pub struct RunBridge {
+	/// Path to the TOML file with bridge configuration.
+	#[structopt(long)]
+	config: PathBuf,
+}
+
+/// Bridge configuration.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BridgeConfig {
+	/// The bridge to run.
+	pub(crate) bridge: BridgeName,
+	/// Hex-encoded identifiers of lanes that should be served by the relay.
+	#[serde(default = "default_lanes")]
+	pub(crate) lanes: Vec<String>,
+	/// If true, all finalized headers are relayed in both directions. Otherwise, headers are only
+	/// relayed when they're required by the message relays.
+	#[serde(default)]
+	pub(crate) relay_all_headers: bool,
+	/// Delay (in seconds) before exited loop is restarted.
+	#[serde(default = "default_restart_delay")]
+	pub(crate) restart_delay: u64,
+	/// Left chain connection and signing params.
+	pub(crate) left: ChainConfig,
+	/// Right chain connection and signing params.
+	pub(crate) right: ChainConfig,
+	/// Prometheus endpoint params. Metrics are not exposed if missing.
+	pub(crate) prometheus: Option<PrometheusConfig>,
+	/// Health endpoint params. Health endpoint is not started if missing.
+	pub(crate) health: Option<EndpointConfig>,
+}
+
+/// Supported bridges.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum BridgeName {
+	MillauRialto,
+}
+
+/// Chain connection and signing params.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ChainConfig {
+	/// Connect to the node at given host.
+	#[serde(default = "default_host")]
+	pub(crate) host: String,
+	/// Connect to the node websocket server at given port.
+	pub(crate) port: u16,
+	/// Use secure websocket connection.
+	#[serde(default)]
+	pub(crate) secure: bool,
+	/// The SURI of secret key to use when transactions are submitted to the node.
+	pub(crate) signer: String,
+	/// The password for the SURI of secret key.
+	pub(crate) signer_password: Option<String>,
+}
+
+/// Prometheus endpoint params.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PrometheusConfig {
+	/// Expose Prometheus endpoint at given interface.
+	#[serde(default = "default_host")]
+	pub(crate) host: String,
+	/// Expose Prometheus endpoint at given port.
+	pub(crate) port: u16,
+	/// Store cumulative counters in given file, so that they survive relay restarts.
+	pub(crate) counters_file: Option<PathBuf>,
+}
+
+/// HTTP endpoint params.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EndpointConfig {
+	/// Serve requests at given interface.
+	#[serde(default = "default_host")]
+	pub(crate) host: String,
+	/// Serve requests at given port.
+	pub(crate) port: u16,
+}
+
+fn default_lanes() -> Vec<String> {
+	vec!["00000000".into()]
+}
+
+fn default_restart_delay() -> u64 {
+	DEFAULT_RESTART_DELAY_SECS
+}
+
+fn default_host() -> String {
+	"127.0.0.1".into()
+}
+
+macro_rules! select_bridge {
+	($bridge: expr, $generic: tt) => {
+		match $bridge {
+			BridgeName::MillauRialto => {
+				type Left = relay_millau_client::Millau;
+				type Right = relay_rialto_client::Rialto;
+
+				type LeftToRightFinality = crate::chains::millau_headers_to_rialto::MillauFinalityToRialto;
+				type RightToLeftFinality = crate::chains::rialto_headers_to_millau::RialtoFinalityToMillau;
+
+				type LeftToRightMessages = crate::chains::millau_messages_to_rialto::MillauMessagesToRialto;
+				type RightToLeftMessages = crate::chains::rialto_messages_to_millau::RialtoMessagesToMillau;
+
+				const MAX_MISSING_LEFT_HEADERS_AT_RIGHT: bp_millau::BlockNumber = bp_millau::SESSION_LENGTH;
+				const MAX_MISSING_RIGHT_HEADERS_AT_LEFT: bp_rialto::BlockNumber = bp_rialto::SESSION_LENGTH;
+
+				use crate::chains::millau_messages_to_rialto::run as left_to_right_messages;
+				use crate::chains::rialto_messages_to_millau::run as right_to_left_messages;
+
+				$generic
+			}
+		}
+	};
+}
+
+impl RunBridge {
+	/// Create command that runs the bridge, configured with given file.
+	pub(crate) fn new(config: PathBuf) -> Self {
+		RunBridge { config }
+	}
+
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		let config = std::fs::read_to_string(&self.config)
+			.map_err(|e| anyhow::format_err!("Failed to read {:?}: {}", self.config, e))?;
+		let config = parse_config(&config)?;
+		let lanes = config
+			.lanes
+			.iter()
+			.map(|lane| {
+				HexLaneId::from_str(lane)
+					.map(Into::into)
+					.map_err(|e| anyhow::format_err!("Invalid lane id {}: {}", lane, e))
+			})
+			.collect::<anyhow::Result<Vec<bp_messages::LaneId>>>()?;
+		let restart_delay = Duration::from_secs(config.restart_delay);
+
+		select_bridge!(config.bridge, {
+			let left_client = to_client::<Left>(&config.left).await;
+			let left_sign = to_keypair::<Left>(&config.left)?;
+			let right_client = to_client::<Right>(&config.right).await;
+			let right_sign = to_keypair::<Right>(&config.right)?;
+
+			let metrics_params = to_metrics_params(config.prometheus);
+			let metrics_params = relay_utils::relay_metrics(None, metrics_params).into_params();
+
+			let mut loops: Vec<SupervisedLoop> = Vec::new();
+			let (left_to_right_on_demand_headers, right_to_left_on_demand_headers) = if config.relay_all_headers {
+				let left_to_right_finality = LeftToRightFinality::new(right_client.clone(), right_sign.clone());
+				left_to_right_finality.start_relay_guards();
+				let (source_client, target_client) = (left_client.clone(), right_client.clone());
+				loops.push(SupervisedLoop::new(
+					finality_relay::metrics_prefix::<LeftToRightFinality>(),
+					metrics_params.clone(),
+					move |metrics_params| {
+						crate::finality_pipeline::run(
+							left_to_right_finality.clone(),
+							source_client.clone(),
+							target_client.clone(),
+							None,
+							None,
+							false,
+							None,
+							metrics_params,
+						)
+						.boxed()
+					},
+				));
+
+				let right_to_left_finality = RightToLeftFinality::new(left_client.clone(), left_sign.clone());
+				right_to_left_finality.start_relay_guards();
+				let (source_client, target_client) = (right_client.clone(), left_client.clone());
+				loops.push(SupervisedLoop::new(
+					finality_relay::metrics_prefix::<RightToLeftFinality>(),
+					metrics_params.clone(),
+					move |metrics_params| {
+						crate::finality_pipeline::run(
+							right_to_left_finality.clone(),
+							source_client.clone(),
+							target_client.clone(),
+							None,
+							None,
+							false,
+							None,
+							metrics_params,
+						)
+						.boxed()
+					},
+				));
+
+				(None, None)
+			} else {
+				(
+					Some(OnDemandHeadersRelay::new(
+						left_client.clone(),
+						right_client.clone(),
+						LeftToRightFinality::new(right_client.clone(), right_sign.clone()),
+						MAX_MISSING_LEFT_HEADERS_AT_RIGHT,
+					)),
+					Some(OnDemandHeadersRelay::new(
+						right_client.clone(),
+						left_client.clone(),
+						RightToLeftFinality::new(left_client.clone(), left_sign.clone()),
+						MAX_MISSING_RIGHT_HEADERS_AT_LEFT,
+					)),
+				)
+			};
+
+			for lane in lanes {
+				let (source_client, source_sign) = (left_client.clone(), left_sign.clone());
+				let (target_client, target_sign) = (right_client.clone(), right_sign.clone());
+				let (source_to_target_headers_relay, target_to_source_headers_relay) = (
+					left_to_right_on_demand_headers.clone(),
+					right_to_left_on_demand_headers.clone(),
+				);
+				loops.push(SupervisedLoop::new(
+					messages_relay::message_lane_loop::metrics_prefix::<LeftToRightMessages>(&lane),
+					metrics_params.clone(),
+					move |metrics_params| {
+						left_to_right_messages(MessagesRelayParams {
+							source_client: source_client.clone(),
+							source_sign: source_sign.clone(),
+							target_client: target_client.clone(),
+							target_sign: target_sign.clone(),
+							source_to_target_headers_relay: source_to_target_headers_relay.clone(),
+							target_to_source_headers_relay: target_to_source_headers_relay.clone(),
+							lane_id: lane,
+							source_instance: None,
+							target_instance: None,
+							skip_outdated_messages: false,
+							metrics_params,
+						})
+						.map_err(|e| anyhow::format_err!("{}", e))
+						.boxed()
+					},
+				));
+
+				let (source_client, source_sign) = (right_client.clone(), right_sign.clone());
+				let (target_client, target_sign) = (left_client.clone(), left_sign.clone());
+				let (source_to_target_headers_relay, target_to_source_headers_relay) = (
+					right_to_left_on_demand_headers.clone(),
+					left_to_right_on_demand_headers.clone(),
+				);
+				loops.push(SupervisedLoop::new(
+					messages_relay::message_lane_loop::metrics_prefix::<RightToLeftMessages>(&lane),
+					metrics_params.clone(),
+					move |metrics_params| {
+						right_to_left_messages(MessagesRelayParams {
+							source_client: source_client.clone(),
+							source_sign: source_sign.clone(),
+							target_client: target_client.clone(),
+							target_sign: target_sign.clone(),
+							source_to_target_headers_relay: source_to_target_headers_relay.clone(),
+							target_to_source_headers_relay: target_to_source_headers_relay.clone(),
+							lane_id: lane,
+							source_instance: None,
+							target_instance: None,
+							skip_outdated_messages: false,
+							metrics_params,
+						})
+						.map_err(|e| anyhow::format_err!("{}", e))
+						.boxed()
+					},
+				));
+			}
+
+			relay_utils::relay_metrics(None, metrics_params)
+				.expose()
+				.await
+				.map_err(|e| anyhow::format_err!("{}", e))?;
+
+			let health = HealthState::default();
+			if let Some(health_config) = config.health {
+				let address = format!("{}:{}", health_config.host, health_config.port)
+					.parse::<SocketAddr>()
+					.map_err(|e| anyhow::format_err!("Invalid health endpoint address: {}", e))?;
+				let health = health.clone();
+				async_std::task::spawn(async move {
+					if let Err(error) = serve_health(address, health).await {
+						log::error!(target: "bridge", "Health endpoint has failed: {}", error);
+					}
+				});
+			}
+
+			supervise(loops, health, restart_delay).await
+		})
+	}
+}
+
+/// Parse bridge configuration.
+pub(crate) fn parse_config(config: &str) -> anyhow::Result<BridgeConfig> {
+	toml::from_str(config).map_err(|e| anyhow::format_err!("Invalid bridge configuration: {}", e))
+}
+
+/// Connect to the chain node.
+async fn to_client<Chain: CliChain>(config: &ChainConfig) -> relay_substrate_client::Client<Chain> {
+	relay_substrate_client::Client::new(relay_substrate_client::ConnectionParams {
+		host: config.host.clone(),
+		port: config.port,
+		secure: config.secure,
+	})
+	.await
+}
+
+/// Parse signing params into chain-specific key pair.
+fn to_keypair<Chain: CliChain>(config: &ChainConfig) -> anyhow::Result<Chain::KeyPair> {
+	Chain::KeyPair::from_string(&config.signer, config.signer_password.as_deref())
+		.map_err(|e| anyhow::format_err!("{:?}", e))
+}
+
+/// Convert Prometheus config into metrics params.
+fn to_metrics_params(config: Option<PrometheusConfig>) -> MetricsParams {
+	let config = match config {
+		Some(config) => config,
+		None => return None.into(),
+	};
+
+	let metrics_params: MetricsParams = Some(MetricsAddress {
+		host: config.host,
+		port: config.port,
+		tls: None,
+		auth: None,
+	})
+	.into();
+	match config.counters_file {
+		Some(path) => match PersistentCounters::open(path) {
+			Ok(persistent_counters) => metrics_params.persistent_counters(persistent_counters),
+			Err(error) => {
+				log::error!(
+					target: "bridge",
+					"Failed to open persistent counters file: {}. Counters will be reset on restart",
+					error,
+				);
+				metrics_params
+			}
+		},
+		None => metrics_params,
+	}
+}
+
+/// Factory of the relay loop future.
+type LoopFactory = Box<dyn Fn(MetricsParams) -> BoxFuture<'static, anyhow::Result<()>> + Send>;
+
+/// Relay loop that is restarted by the supervisor when it exits.
+struct SupervisedLoop {
+	/// Loop name.
+	name: String,
+	/// Loop metrics params.
+	metrics_params: MetricsParams,
+	/// Loop future factory.
+	start: LoopFactory,
+}
+
+impl SupervisedLoop {
+	/// Create new supervised loop.
+	fn new(
+		name: String,
+		metrics_params: MetricsParams,
+		start: impl Fn(MetricsParams) -> BoxFuture<'static, anyhow::Result<()>> + Send + 'static,
+	) -> Self {
+		SupervisedLoop {
+			metrics_params: metrics_params.disable().metrics_prefix(name.clone()),
+			name,
+			start: Box::new(start),
+		}
+	}
+}
+
+/// Names of supervised loops, mapped to their state (true if loop is running).
+#[derive(Clone, Default)]
+struct HealthState(Arc<Mutex<BTreeMap<String, bool>>>);
+
+impl HealthState {
+	/// Update loop state.
+	fn set_running(&self, name: &str, is_running: bool) {
+		self.0
+			.lock()
+			.expect("health state mutex is never poisoned")
+			.insert(name.into(), is_running);
+	}
+
+	/// Returns names of loops that are not running.
+	fn failed_loops(&self) -> Vec<String> {
+		self.0
+			.lock()
+			.expect("health state mutex is never poisoned")
+			.iter()
+			.filter(|(_, is_running)| !**is_running)
+			.map(|(name, _)| name.clone())
+			.collect()
+	}
+}
+
+/// Run all loops, restarting them when they exit.
+async fn supervise(loops: Vec<SupervisedLoop>, health: HealthState, restart_delay: Duration) -> anyhow::Result<()> {
+	futures::future::join_all(
+		loops
+			.into_iter()
+			.map(|supervised_loop| supervise_loop(supervised_loop, health.clone(), restart_delay)),
+	)
+	.await;
+	Ok(())
+}
+
+/// Run single loop, restarting it when it exits.
+async fn supervise_loop(supervised_loop: SupervisedLoop, health: HealthState, restart_delay: Duration) {
+	let mut metrics_params = Some(supervised_loop.metrics_params);
+	loop {
+		health.set_running(&supervised_loop.name, true);
+		// metrics of the loop are already registered by the first loop instance, so restarted loop
+		// can't register them again
+		let result = (supervised_loop.start)(metrics_params.take().unwrap_or_else(MetricsParams::disabled)).await;
+		health.set_running(&supervised_loop.name, false);
+
+		log::error!(
+			target: "bridge",
+			"{} loop has exited with result {:?}. Restarting in {}s. Loop metrics won't be updated until relay restart",
+			supervised_loop.name,
+			result,
+			restart_delay.as_secs(),
+		);
+		async_std::task::sleep(restart_delay).await;
+	}
+}
+
+/// Serve health requests at given address until error happens.
+///
+/// Every request is answered with `200 OK` if all loops are running and with
+/// `503 Service Unavailable` (listing failed loops) otherwise.
+async fn serve_health(address: SocketAddr, health: HealthState) -> Result<(), String> {
+	let listener = TcpListener::bind(address)
+		.await
+		.map_err(|err| format!("Failed to bind health endpoint to {}: {}", address, err))?;
+
+	let mut incoming = listener.incoming();
+	while let Some(stream) = incoming.next().await {
+		let mut stream = match stream {
+			Ok(stream) => stream,
+			Err(_) => continue,
+		};
+
+		// we don't care about the request - every request is the health request
+		let mut buffer = [0u8; 1024];
+		let _ = stream.read(&mut buffer).await;
+
+		let (status, body) = health_response(&health.failed_loops());
+		let response = format!(
+			"HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+			status,
+			body.len(),
+			body,
+		);
+		let _ = stream.write_all(response.as_bytes()).await;
+		let _ = stream.close().await;
+	}
+
+	Ok(())
+}
+
+/// Returns health response status and body.
+fn health_response(failed_loops: &[String]) -> (&'static str, String) {
+	if failed_loops.is_empty() {
+		("200 OK", "OK\n".into())
+	} else {
+		(
+			"503 Service Unavailable",
+			format!("Failed loops: {}\n", failed_loops.join(", ")),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bridge_config_is_parsed() {
+		let config = parse_config(
+			r#"
+			bridge = "millau-rialto"
+			relay_all_headers = true
+
+			[left]
+			port = 9945
+			signer = "//Alice"
+
+			[right]
+			host = "rialto-node"
+			port = 9944
+			secure = true
+			signer = "//Bob"
+			signer_password = "password"
+
+			[prometheus]
+			port = 9616
+			counters_file = "counters.json"
+			"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			config,
+			BridgeConfig {
+				bridge: BridgeName::MillauRialto,
+				lanes: vec!["00000000".into()],
+				relay_all_headers: true,
+				restart_delay: DEFAULT_RESTART_DELAY_SECS,
+				left: ChainConfig {
+					host: "127.0.0.1".into(),
+					port: 9945,
+					secure: false,
+					signer: "//Alice".into(),
+					signer_password: None,
+				},
+				right: ChainConfig {
+					host: "rialto-node".into(),
+					port: 9944,
+					secure: true,
+					signer: "//Bob".into(),
+					signer_password: Some("password".into()),
+				},
+				prometheus: Some(PrometheusConfig {
+					host: "127.0.0.1".into(),
+					port: 9616,
+					counters_file: Some("counters.json".into()),
+				}),
+				health: None,
+			},
+		);
+	}
+
+	#[test]
+	fn bridge_config_with_unknown_fields_is_rejected() {
+		assert!(parse_config(
+			r#"
+			bridge = "millau-rialto"
+			unknown = 42
+
+			[left]
+			port = 9945
+			signer = "//Alice"
+
+			[right]
+			port = 9944
+			signer = "//Bob"
+			"#,
+		)
+		.is_err());
+	}
+
+	#[test]
+	fn health_response_lists_failed_loops() {
+		let health = HealthState::default();
+		health.set_running("first", true);
+		health.set_running("second", true);
+		assert_eq!(health_response(&health.failed_loops()), ("200 OK", "OK\n".into()));
+
+		health.set_running("second", false);
+		assert_eq!(
+			health_response(&health.failed_loops()),
+			("503 Service Unavailable", "Failed loops: second\n".into()),
+		);
+	}
+}
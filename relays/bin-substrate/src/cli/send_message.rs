@@ -18,8 +18,8 @@ use crate::cli::bridge::FullBridge;
 use crate::cli::encode_call::{self, CliEncodeCall};
 use crate::cli::estimate_fee::estimate_message_delivery_and_dispatch_fee;
 use crate::cli::{
-	Balance, CliChain, ExplicitOrMaximal, HexBytes, HexLaneId, Origins, SourceConnectionParams, SourceSigningParams,
-	TargetSigningParams,
+	Balance, BridgeInstanceId, CliChain, DispatchFeePayment, ExplicitOrMaximal, HexBytes, HexLaneId, Origins,
+	SourceConnectionParams, SourceSigningParams, TargetSigningParams,
 };
 use bp_message_dispatch::{CallOrigin, MessagePayload};
 use codec::Encode;
@@ -46,6 +46,9 @@ pub struct SendMessage {
 	/// Hex-encoded lane id. Defaults to `00000000`.
 	#[structopt(long, default_value = "00000000")]
 	lane: HexLaneId,
+	/// Instance of bridge pallets at the source chain. Defaults to instance that bridges with the target chain.
+	#[structopt(long)]
+	source_bridge_instance: Option<BridgeInstanceId>,
 	/// Dispatch weight of the message. If not passed, determined automatically.
 	#[structopt(long)]
 	dispatch_weight: Option<ExplicitOrMaximal<Weight>>,
@@ -59,6 +62,9 @@ pub struct SendMessage {
 	/// `SourceAccount`.
 	#[structopt(long, possible_values = &Origins::variants(), default_value = "Source")]
 	origin: Origins,
+	/// Where the message dispatch fee is paid. Defaults to `AtSourceChain`.
+	#[structopt(long, possible_values = &DispatchFeePayment::variants(), default_value = "AtSourceChain")]
+	dispatch_fee_payment: DispatchFeePayment,
 }
 
 impl SendMessage {
@@ -72,6 +78,7 @@ impl SendMessage {
 				ref mut message,
 				dispatch_weight,
 				origin,
+				dispatch_fee_payment,
 				bridge,
 				..
 			} = self;
@@ -111,6 +118,7 @@ impl SendMessage {
 							)
 						}
 					},
+					dispatch_fee_payment.clone().into(),
 					&target_call,
 				)
 			};
@@ -133,6 +141,9 @@ impl SendMessage {
 					estimate_message_delivery_and_dispatch_fee::<<Source as Chain>::Balance, _, _>(
 						&source_client,
 						ESTIMATE_MESSAGE_FEE_METHOD,
+						self.source_bridge_instance
+							.map(Into::into)
+							.unwrap_or(SOURCE_BRIDGE_INSTANCE),
 						lane,
 						payload.clone(),
 					)
@@ -199,6 +210,7 @@ pub(crate) fn message_payload<SAccountId, TPublic, TSignature>(
 	spec_version: u32,
 	weight: Weight,
 	origin: CallOrigin<SAccountId, TPublic, TSignature>,
+	dispatch_fee_payment: bp_runtime::DispatchFeePayment,
 	call: &impl Encode,
 ) -> MessagePayload<SAccountId, TPublic, TSignature, Vec<u8>>
 where
@@ -211,6 +223,7 @@ where
 		spec_version,
 		weight,
 		origin,
+		dispatch_fee_payment,
 		call: HexBytes::encode(call),
 	};
 
@@ -222,12 +235,14 @@ where
 		spec_version,
 		weight,
 		origin,
+		dispatch_fee_payment,
 		call,
 	} = payload;
 	MessagePayload {
 		spec_version,
 		weight,
 		origin,
+		dispatch_fee_payment,
 		call: call.0,
 	}
 }
@@ -268,6 +283,7 @@ mod tests {
 				spec_version: relay_millau_client::Millau::RUNTIME_VERSION.spec_version,
 				weight: 1345000,
 				origin: CallOrigin::SourceAccount(sp_keyring::AccountKeyring::Alice.to_account_id()),
+				dispatch_fee_payment: bp_runtime::DispatchFeePayment::AtSourceChain,
 				call: hex!("0401081234").to_vec(),
 			}
 		);
@@ -311,6 +327,7 @@ mod tests {
 					sp_keyring::AccountKeyring::Bob.into(),
 					signature,
 				),
+				dispatch_fee_payment: bp_runtime::DispatchFeePayment::AtSourceChain,
 				call: hex!("0701081234").to_vec(),
 			}
 		);
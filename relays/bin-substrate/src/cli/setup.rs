@@ -0,0 +1,724 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Guided bootstrap of the complete bridge.
+//!
+//! The `setup` command walks an operator through all steps that are required to start the bridge:
+//!
+//! 1) connects to nodes of both chains and checks that their runtimes are compatible with the relay;
+//! 2) selects (or generates) relayer keys for both chains;
+//! 3) initializes finality pallets in both directions, if they're not yet initialized;
+//! 4) checks that all selected lanes are opened at both chains, reopening closing lanes on request;
+//! 5) writes the `run-bridge` configuration file and (optionally) starts all relays.
+//!
+//! Every question has a default answer, so the command may also be used in non-interactive mode.
+
+use crate::cli::init_bridge::{InitBridge, InitBridgeName};
+use crate::cli::run_bridge::{
+	self, BridgeConfig, BridgeName, ChainConfig, EndpointConfig, PrometheusConfig, RunBridge,
+	DEFAULT_RESTART_DELAY_SECS,
+};
+use crate::cli::{AccountId, CliChain, HexLaneId, SourceConnectionParams, TargetConnectionParams, TargetSigningParams};
+
+use bp_messages::{LaneId, LaneState, OutboundLaneData};
+use bp_runtime::Chain as ChainBase;
+use codec::Encode;
+use relay_substrate_client::{Chain, Client, ConnectionParams, MultiSignerKeyPair, TransactionSignScheme};
+use sp_core::{storage::StorageKey, Bytes, Pair};
+use sp_version::RuntimeVersion;
+use std::{fmt::Display, io::Write, path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+/// Maximal number of blocks to wait for the bridge initialization transaction to be included.
+const MAX_INITIALIZATION_BLOCKS: u32 = 16;
+
+/// Guided bridge setup.
+#[derive(StructOpt)]
+pub struct Setup {
+	/// Path to the TOML file where the generated bridge configuration is written.
+	#[structopt(long, default_value = "bridge.toml")]
+	config: PathBuf,
+	/// Use default answers to all questions.
+	#[structopt(long)]
+	non_interactive: bool,
+	/// Print secret phrases of generated relayer keys. Otherwise, they're only written to the
+	/// configuration file.
+	#[structopt(long)]
+	print_generated_secrets: bool,
+}
+
+macro_rules! select_bridge {
+	($bridge: expr, $generic: tt) => {
+		match $bridge {
+			BridgeName::MillauRialto => {
+				type Left = relay_millau_client::Millau;
+				type Right = relay_rialto_client::Rialto;
+
+				const LEFT_DEFAULT_PORT: u16 = 9945;
+				const RIGHT_DEFAULT_PORT: u16 = 9944;
+
+				const LEFT_TO_RIGHT_BRIDGE: InitBridgeName = InitBridgeName::MillauToRialto;
+				const RIGHT_TO_LEFT_BRIDGE: InitBridgeName = InitBridgeName::RialtoToMillau;
+
+				use millau_runtime::{
+					RialtoGrandpaInstance as LeftGrandpaInstance, Runtime as LeftRuntime,
+					WithRialtoMessagesInstance as LeftMessagesInstance,
+				};
+				use rialto_runtime::{
+					MillauGrandpaInstance as RightGrandpaInstance, Runtime as RightRuntime,
+					WithMillauMessagesInstance as RightMessagesInstance,
+				};
+
+				fn encode_open_lane_at_left(lane: LaneId) -> <Left as Chain>::Call {
+					let open_lane_call =
+						millau_runtime::MessagesCall::<LeftRuntime, LeftMessagesInstance>::set_lane_state(
+							lane,
+							LaneState::Opened,
+						);
+					millau_runtime::SudoCall::sudo(Box::new(open_lane_call.into())).into()
+				}
+
+				fn encode_open_lane_at_right(lane: LaneId) -> <Right as Chain>::Call {
+					let open_lane_call =
+						rialto_runtime::MessagesCall::<RightRuntime, RightMessagesInstance>::set_lane_state(
+							lane,
+							LaneState::Opened,
+						);
+					rialto_runtime::SudoCall::sudo(Box::new(open_lane_call.into())).into()
+				}
+
+				$generic
+			}
+		}
+	};
+}
+
+impl Setup {
+	/// Run the command.
+	pub async fn run(self) -> anyhow::Result<()> {
+		let prompt = Prompt {
+			non_interactive: self.non_interactive,
+		};
+
+		// Millau <> Rialto is the only bridge that may be started with `run-bridge` now
+		let bridge = BridgeName::MillauRialto;
+		select_bridge!(bridge, {
+			println!("Setting up {} <> {} bridge", Left::NAME, Right::NAME);
+
+			// connect to both chains and check that the relay is able to work with their runtimes
+			let mut left = ask_connection::<Left>(&prompt, LEFT_DEFAULT_PORT)?;
+			let mut right = ask_connection::<Right>(&prompt, RIGHT_DEFAULT_PORT)?;
+			let left_client = connect::<Left>(&left).await?;
+			let right_client = connect::<Right>(&right).await?;
+			ensure_runtime_compatible(&prompt, &left_client).await?;
+			ensure_runtime_compatible(&prompt, &right_client).await?;
+
+			// select relayer keys
+			let left_sign = ask_signer::<Left>(&prompt, &mut left, self.print_generated_secrets)?;
+			let right_sign = ask_signer::<Right>(&prompt, &mut right, self.print_generated_secrets)?;
+
+			// initialize finality pallets in both directions
+			initialize_bridge::<Left, Right>(
+				&prompt,
+				LEFT_TO_RIGHT_BRIDGE,
+				&left,
+				&right,
+				&right_client,
+				pallet_bridge_grandpa::storage_keys::best_finalized_key::<RightRuntime, RightGrandpaInstance>(),
+			)
+			.await?;
+			initialize_bridge::<Right, Left>(
+				&prompt,
+				RIGHT_TO_LEFT_BRIDGE,
+				&right,
+				&left,
+				&left_client,
+				pallet_bridge_grandpa::storage_keys::best_finalized_key::<LeftRuntime, LeftGrandpaInstance>(),
+			)
+			.await?;
+
+			// check that lanes are opened at both chains
+			let lanes = ask_lanes(&prompt)?;
+			for lane in lanes.iter().map(|lane| {
+				HexLaneId::from_str(lane)
+					.map(LaneId::from)
+					.expect("validated by ask_lanes; qed")
+			}) {
+				let (left_genesis_hash, left_sign) = (*left_client.genesis_hash(), left_sign.clone());
+				ensure_lane_opened(
+					&prompt,
+					&left_client,
+					lane,
+					pallet_bridge_messages::storage_keys::outbound_lane_data_key::<LeftMessagesInstance>(&lane),
+					left_sign.public().into(),
+					move |transaction_nonce| {
+						Bytes(
+							Left::sign_transaction(
+								left_genesis_hash,
+								&left_sign,
+								transaction_nonce,
+								encode_open_lane_at_left(lane),
+							)
+							.encode(),
+						)
+					},
+				)
+				.await?;
+
+				let (right_genesis_hash, right_sign) = (*right_client.genesis_hash(), right_sign.clone());
+				ensure_lane_opened(
+					&prompt,
+					&right_client,
+					lane,
+					pallet_bridge_messages::storage_keys::outbound_lane_data_key::<RightMessagesInstance>(&lane),
+					right_sign.public().into(),
+					move |transaction_nonce| {
+						Bytes(
+							Right::sign_transaction(
+								right_genesis_hash,
+								&right_sign,
+								transaction_nonce,
+								encode_open_lane_at_right(lane),
+							)
+							.encode(),
+						)
+					},
+				)
+				.await?;
+			}
+
+			// generate configuration file
+			let config = BridgeConfig {
+				bridge,
+				lanes,
+				relay_all_headers: prompt.confirm(
+					"Relay all finalized headers (otherwise headers are only relayed when messages need them)?",
+					false,
+				)?,
+				restart_delay: DEFAULT_RESTART_DELAY_SECS,
+				left,
+				right,
+				prometheus: prompt
+					.ask_optional::<u16>("Prometheus endpoint port (empty to disable)", Some(9616))?
+					.map(|port| PrometheusConfig {
+						host: "127.0.0.1".into(),
+						port,
+						counters_file: None,
+					}),
+				health: prompt
+					.ask_optional::<u16>("Health endpoint port (empty to disable)", Some(9617))?
+					.map(|port| EndpointConfig {
+						host: "127.0.0.1".into(),
+						port,
+					}),
+			};
+			write_config(&self.config, &config)?;
+			println!(
+				"Bridge configuration has been written to {}. It contains secret keys, so keep it safe.",
+				self.config.display(),
+			);
+
+			if !prompt.confirm("Start relays now?", false)? {
+				println!(
+					"Use `substrate-relay run-bridge --config {}` to start relays",
+					self.config.display(),
+				);
+				return Ok(());
+			}
+
+			RunBridge::new(self.config).run().await
+		})
+	}
+}
+
+/// Asks questions and reads answers.
+struct Prompt {
+	/// If true, default answers are used without asking.
+	non_interactive: bool,
+}
+
+impl Prompt {
+	/// Ask question and return trimmed answer. Returns `None` in non-interactive mode.
+	fn read_answer(&self, question: &str, hint: &str) -> anyhow::Result<Option<String>> {
+		if self.non_interactive {
+			println!("{} [{}]", question, hint);
+			return Ok(None);
+		}
+
+		print!("{} [{}]: ", question, hint);
+		std::io::stdout().flush()?;
+		let mut answer = String::new();
+		std::io::stdin().read_line(&mut answer)?;
+		Ok(Some(answer.trim().into()))
+	}
+
+	/// Ask question, the answer to which may be parsed into `T`. Repeats the question until
+	/// valid answer is received. Empty answer means that the `default` value is selected.
+	fn ask<T: FromStr + Display>(&self, question: &str, default: T) -> anyhow::Result<T>
+	where
+		T::Err: Display,
+	{
+		Ok(self
+			.ask_optional(question, Some(default))?
+			.expect("ask_optional only returns None if default is None; qed"))
+	}
+
+	/// Same as `ask`, but the answer may be empty if there's no default value.
+	fn ask_optional<T: FromStr + Display>(&self, question: &str, default: Option<T>) -> anyhow::Result<Option<T>>
+	where
+		T::Err: Display,
+	{
+		let hint = default.as_ref().map(ToString::to_string).unwrap_or_default();
+		loop {
+			match self.read_answer(question, &hint)? {
+				None => return Ok(default),
+				Some(answer) if answer.is_empty() => return Ok(default),
+				Some(answer) => match answer.parse() {
+					Ok(value) => return Ok(Some(value)),
+					Err(error) => println!("Invalid answer: {}", error),
+				},
+			}
+		}
+	}
+
+	/// Ask yes/no question. Empty answer means that the `default` answer is selected.
+	fn confirm(&self, question: &str, default: bool) -> anyhow::Result<bool> {
+		let hint = if default { "Y/n" } else { "y/N" };
+		loop {
+			match self.read_answer(question, hint)? {
+				None => return Ok(default),
+				Some(answer) => match parse_confirmation(&answer, default) {
+					Some(confirmed) => return Ok(confirmed),
+					None => println!("Please answer 'y' or 'n'"),
+				},
+			}
+		}
+	}
+}
+
+/// Parse answer to the yes/no question.
+fn parse_confirmation(answer: &str, default: bool) -> Option<bool> {
+	match answer.to_lowercase().as_str() {
+		"" => Some(default),
+		"y" | "yes" => Some(true),
+		"n" | "no" => Some(false),
+		_ => None,
+	}
+}
+
+/// Ask chain node connection params.
+fn ask_connection<C: Chain>(prompt: &Prompt, default_port: u16) -> anyhow::Result<ChainConfig> {
+	Ok(ChainConfig {
+		host: prompt.ask(&format!("{} node host", C::NAME), String::from("127.0.0.1"))?,
+		port: prompt.ask(&format!("{} node websocket port", C::NAME), default_port)?,
+		secure: prompt.confirm(&format!("Use secure websocket connection to {} node?", C::NAME), false)?,
+		signer: String::new(),
+		signer_password: None,
+	})
+}
+
+/// Connect to the chain node.
+async fn connect<C: Chain>(config: &ChainConfig) -> anyhow::Result<Client<C>> {
+	Client::try_connect(ConnectionParams {
+		host: config.host.clone(),
+		port: config.port,
+		secure: config.secure,
+	})
+	.await
+	.map_err(|e| {
+		anyhow::format_err!(
+			"Failed to connect to {} node at {}:{}: {}",
+			C::NAME,
+			config.host,
+			config.port,
+			e,
+		)
+	})
+}
+
+/// Check that the runtime of the chain matches the runtime that the relay has been built with.
+async fn ensure_runtime_compatible<C: CliChain>(prompt: &Prompt, client: &Client<C>) -> anyhow::Result<()> {
+	let actual_version = client.runtime_version().await?;
+	match runtime_version_mismatch(&actual_version, &C::RUNTIME_VERSION) {
+		None => {
+			println!(
+				"{} runtime (spec version {}) is compatible with the relay",
+				C::NAME,
+				actual_version.spec_version,
+			);
+			Ok(())
+		}
+		Some(mismatch) => {
+			println!("{} runtime is not compatible with the relay: {}", C::NAME, mismatch);
+			if prompt.confirm(
+				"Continue anyway? Transactions, submitted by the relay, may be rejected",
+				false,
+			)? {
+				Ok(())
+			} else {
+				Err(anyhow::format_err!("Incompatible {} runtime: {}", C::NAME, mismatch))
+			}
+		}
+	}
+}
+
+/// Returns description of the mismatch between actual and expected runtime versions.
+fn runtime_version_mismatch(actual: &RuntimeVersion, expected: &RuntimeVersion) -> Option<String> {
+	if actual.spec_name != expected.spec_name {
+		return Some(format!(
+			"spec name is {}, expected {}",
+			actual.spec_name, expected.spec_name
+		));
+	}
+	if actual.spec_version != expected.spec_version {
+		return Some(format!(
+			"spec version is {}, expected {}",
+			actual.spec_version, expected.spec_version
+		));
+	}
+	if actual.transaction_version != expected.transaction_version {
+		return Some(format!(
+			"transaction version is {}, expected {}",
+			actual.transaction_version, expected.transaction_version
+		));
+	}
+
+	None
+}
+
+/// Ask relayer key of given chain, generating new key if operator hasn't provided any.
+fn ask_signer<C: CliChain>(
+	prompt: &Prompt,
+	config: &mut ChainConfig,
+	print_generated_secret: bool,
+) -> anyhow::Result<C::KeyPair> {
+	let suri = prompt.ask_optional::<String>(
+		&format!("SURI of {} relayer key (empty to generate new key)", C::NAME),
+		None,
+	)?;
+	let key_pair = match suri {
+		Some(suri) => {
+			let password = prompt.ask_optional::<String>("Password for the SURI (empty if none)", None)?;
+			let key_pair = C::KeyPair::from_string(&suri, password.as_deref())
+				.map_err(|e| anyhow::format_err!("Invalid {} SURI: {:?}", C::NAME, e))?;
+			config.signer = suri;
+			config.signer_password = password;
+			key_pair
+		}
+		None => {
+			let (key_pair, phrase, _) = C::KeyPair::generate_with_phrase(None);
+			if print_generated_secret {
+				println!("Generated new {} relayer key. Secret phrase: {}", C::NAME, phrase);
+			} else {
+				println!(
+					"Generated new {} relayer key. Its secret phrase is only written to the configuration file",
+					C::NAME,
+				);
+			}
+			config.signer = phrase;
+			key_pair
+		}
+	};
+
+	println!(
+		"{} relayer account: {}. Make sure it has enough funds to pay for transactions",
+		C::NAME,
+		AccountId::from_raw::<C>(key_pair.account_id()),
+	);
+	Ok(key_pair)
+}
+
+/// Initialize finality pallet at the target chain, if it isn't initialized yet.
+async fn initialize_bridge<Source: CliChain, Target: CliChain>(
+	prompt: &Prompt,
+	bridge: InitBridgeName,
+	source: &ChainConfig,
+	target: &ChainConfig,
+	target_client: &Client<Target>,
+	best_finalized_key: StorageKey,
+) -> anyhow::Result<()> {
+	if is_bridge_initialized::<Source, Target>(target_client, best_finalized_key.clone()).await? {
+		println!(
+			"{} -> {} finality bridge is already initialized",
+			Source::NAME,
+			Target::NAME
+		);
+		return Ok(());
+	}
+	let question = format!(
+		"{} -> {} finality bridge is not initialized. Initialize it now (requires {} sudo key)?",
+		Source::NAME,
+		Target::NAME,
+		Target::NAME,
+	);
+	if !prompt.confirm(&question, true)? {
+		println!(
+			"Relays won't be able to run until {} -> {} bridge is initialized",
+			Source::NAME,
+			Target::NAME
+		);
+		return Ok(());
+	}
+
+	InitBridge::new(
+		bridge,
+		SourceConnectionParams {
+			source_host: source.host.clone(),
+			source_port: source.port,
+			source_secure: source.secure,
+		},
+		TargetConnectionParams {
+			target_host: target.host.clone(),
+			target_port: target.port,
+			target_secure: target.secure,
+		},
+		TargetSigningParams {
+			target_signer: target.signer.clone(),
+			target_signer_password: target.signer_password.clone(),
+		},
+	)
+	.run()
+	.await?;
+
+	// initialization errors are only logged, so let's check if the transaction has been included
+	for _ in 0..MAX_INITIALIZATION_BLOCKS {
+		if is_bridge_initialized::<Source, Target>(target_client, best_finalized_key.clone()).await? {
+			println!(
+				"{} -> {} finality bridge has been initialized",
+				Source::NAME,
+				Target::NAME
+			);
+			return Ok(());
+		}
+		async_std::task::sleep(Target::AVERAGE_BLOCK_INTERVAL).await;
+	}
+
+	Err(anyhow::format_err!(
+		"{} -> {} finality bridge has not been initialized in {} blocks",
+		Source::NAME,
+		Target::NAME,
+		MAX_INITIALIZATION_BLOCKS,
+	))
+}
+
+/// Returns true if finality pallet at the target chain is initialized.
+async fn is_bridge_initialized<Source: ChainBase, Target: Chain>(
+	target_client: &Client<Target>,
+	best_finalized_key: StorageKey,
+) -> anyhow::Result<bool> {
+	Ok(target_client
+		.storage_value::<Source::Hash>(best_finalized_key)
+		.await?
+		.is_some())
+}
+
+/// Ask hex-encoded ids of lanes that should be served by the relay.
+fn ask_lanes(prompt: &Prompt) -> anyhow::Result<Vec<String>> {
+	loop {
+		let lanes = prompt.ask("Comma-separated hex-encoded lane ids", String::from("00000000"))?;
+		match parse_lanes(&lanes) {
+			Ok(lanes) => return Ok(lanes),
+			Err(error) if !prompt.non_interactive => println!("{}", error),
+			Err(error) => return Err(error),
+		}
+	}
+}
+
+/// Parse comma-separated list of hex-encoded lane ids.
+fn parse_lanes(lanes: &str) -> anyhow::Result<Vec<String>> {
+	let lanes = lanes.split(',').map(|lane| lane.trim().to_string()).collect::<Vec<_>>();
+	for lane in &lanes {
+		HexLaneId::from_str(lane).map_err(|e| anyhow::format_err!("Invalid lane id {}: {}", lane, e))?;
+	}
+	Ok(lanes)
+}
+
+/// Check that the outbound lane is opened at the chain, reopening it on request if it is closing.
+async fn ensure_lane_opened<C: Chain>(
+	prompt: &Prompt,
+	client: &Client<C>,
+	lane: LaneId,
+	outbound_lane_data_key: StorageKey,
+	signer: C::AccountId,
+	prepare_open_lane_transaction: impl FnOnce(C::Index) -> Bytes,
+) -> anyhow::Result<()> {
+	let lane_data: OutboundLaneData = client.storage_value(outbound_lane_data_key).await?.unwrap_or_default();
+	match lane_data.state {
+		LaneState::Opened => {
+			println!("Lane {} is opened at {}", hex::encode(lane), C::NAME);
+			Ok(())
+		}
+		LaneState::Closing => {
+			let question = format!(
+				"Lane {} is closing at {}. Reopen it (requires {} sudo key)?",
+				hex::encode(lane),
+				C::NAME,
+				C::NAME,
+			);
+			if !prompt.confirm(&question, true)? {
+				return Err(anyhow::format_err!(
+					"Lane {} is closing at {}",
+					hex::encode(lane),
+					C::NAME
+				));
+			}
+
+			let tx_hash = client
+				.submit_signed_extrinsic(signer, prepare_open_lane_transaction)
+				.await?;
+			println!(
+				"Submitted {} transaction {:?} that reopens lane {}",
+				C::NAME,
+				tx_hash,
+				hex::encode(lane),
+			);
+			Ok(())
+		}
+		LaneState::Closed => Err(anyhow::format_err!(
+			"Lane {} is closed at {} and can't be reopened. Please select another lane",
+			hex::encode(lane),
+			C::NAME,
+		)),
+	}
+}
+
+/// Write bridge configuration to the file.
+fn write_config(path: &PathBuf, config: &BridgeConfig) -> anyhow::Result<()> {
+	let encoded_config =
+		toml::to_string(config).map_err(|e| anyhow::format_err!("Failed to encode bridge configuration: {}", e))?;
+	// make sure that `run-bridge` will be able to read the configuration
+	run_bridge::parse_config(&encoded_config)?;
+	write_secret_file(path, encoded_config.as_bytes())
+		.map_err(|e| anyhow::format_err!("Failed to write {:?}: {}", path, e))
+}
+
+/// Write file that contains secrets (relayer keys and passwords), making it readable and writable by
+/// the owner only.
+fn write_secret_file(path: &PathBuf, contents: &[u8]) -> std::io::Result<()> {
+	let mut options = std::fs::OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+	let mut file = options.open(path)?;
+	// the mode is only applied when the file is created, so we need to fix permissions of the
+	// existing file
+	#[cfg(unix)]
+	file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+	file.write_all(contents)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn confirmation_is_parsed() {
+		assert_eq!(parse_confirmation("", true), Some(true));
+		assert_eq!(parse_confirmation("", false), Some(false));
+		assert_eq!(parse_confirmation("Y", false), Some(true));
+		assert_eq!(parse_confirmation("yes", false), Some(true));
+		assert_eq!(parse_confirmation("N", true), Some(false));
+		assert_eq!(parse_confirmation("no", true), Some(false));
+		assert_eq!(parse_confirmation("maybe", true), None);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn secret_file_is_only_accessible_by_owner() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let path = std::env::temp_dir().join(format!("bridge-setup-test-{}.toml", std::process::id()));
+		std::fs::write(&path, b"old").unwrap();
+		std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+		write_secret_file(&path, b"new").unwrap();
+		let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+		let contents = std::fs::read(&path).unwrap();
+		std::fs::remove_file(&path).unwrap();
+
+		assert_eq!(mode & 0o777, 0o600);
+		assert_eq!(contents, b"new");
+	}
+
+	#[test]
+	fn lanes_are_parsed() {
+		assert_eq!(
+			parse_lanes("00000000, 00000001").unwrap(),
+			vec!["00000000".to_string(), "00000001".to_string()],
+		);
+		assert!(parse_lanes("00000000,0000").is_err());
+		assert!(parse_lanes("").is_err());
+	}
+
+	#[test]
+	fn runtime_version_mismatch_is_detected() {
+		let expected = millau_runtime::VERSION;
+		assert_eq!(runtime_version_mismatch(&millau_runtime::VERSION, &expected), None);
+		assert!(runtime_version_mismatch(&rialto_runtime::VERSION, &expected).is_some());
+		assert!(runtime_version_mismatch(
+			&RuntimeVersion {
+				spec_version: expected.spec_version + 1,
+				..millau_runtime::VERSION
+			},
+			&expected,
+		)
+		.is_some());
+		assert!(runtime_version_mismatch(
+			&RuntimeVersion {
+				transaction_version: expected.transaction_version + 1,
+				..millau_runtime::VERSION
+			},
+			&expected,
+		)
+		.is_some());
+	}
+
+	#[test]
+	fn generated_config_is_accepted_by_run_bridge() {
+		let config = BridgeConfig {
+			bridge: BridgeName::MillauRialto,
+			lanes: vec!["00000000".into(), "00000001".into()],
+			relay_all_headers: false,
+			restart_delay: DEFAULT_RESTART_DELAY_SECS,
+			left: ChainConfig {
+				host: "127.0.0.1".into(),
+				port: 9945,
+				secure: false,
+				signer: "//Alice".into(),
+				signer_password: None,
+			},
+			right: ChainConfig {
+				host: "rialto-node".into(),
+				port: 9944,
+				secure: true,
+				signer: "//Bob".into(),
+				signer_password: Some("password".into()),
+			},
+			prometheus: Some(PrometheusConfig {
+				host: "127.0.0.1".into(),
+				port: 9616,
+				counters_file: None,
+			}),
+			health: None,
+		};
+
+		assert_eq!(
+			run_bridge::parse_config(&toml::to_string(&config).unwrap()).unwrap(),
+			config,
+		);
+	}
+}
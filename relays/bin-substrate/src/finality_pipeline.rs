@@ -18,11 +18,20 @@
 
 use crate::finality_target::SubstrateFinalityTarget;
 
-use bp_header_chain::justification::GrandpaJustification;
-use finality_relay::{FinalitySyncParams, FinalitySyncPipeline};
-use relay_substrate_client::{finality_source::FinalitySource, BlockNumberOf, Chain, Client, HashOf, SyncHeader};
+use bp_header_chain::{
+	justification::{optimize_justification, GrandpaJustification},
+	AuthoritySet,
+};
+use bp_runtime::ChainId;
+use finality_grandpa::voter_set::VoterSet;
+use finality_relay::{FinalitySubmissionBudget, FinalitySyncParams, FinalitySyncPipeline, SharedSourceClient};
+use relay_substrate_client::{
+	finality_source::FinalitySource, BlockNumberOf, Chain, Client, FinalityEngine, Grandpa, HashOf, SyncHeader,
+	TipEscalation,
+};
 use relay_utils::{metrics::MetricsParams, BlockNumberBase};
-use sp_core::Bytes;
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::traits::Header as HeaderT;
 use std::{fmt::Debug, marker::PhantomData, time::Duration};
 
 /// Default synchronization loop timeout.
@@ -37,6 +46,10 @@ pub(crate) const RECENT_FINALITY_PROOFS_LIMIT: usize = 4096;
 pub trait SubstrateFinalitySyncPipeline: FinalitySyncPipeline {
 	/// Name of the runtime method that returns id of best finalized source header at target chain.
 	const BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET: &'static str;
+	/// Id of the finality pallet instance at the target chain.
+	///
+	/// Must be `None` if the finality runtime API of the target chain doesn't accept instance id.
+	const TARGET_FINALITY_INSTANCE: Option<ChainId> = None;
 
 	/// Chain with GRANDPA bridge pallet.
 	type TargetChain: Chain;
@@ -56,28 +69,52 @@ pub trait SubstrateFinalitySyncPipeline: FinalitySyncPipeline {
 	/// Returns id of account that we're using to sign transactions at target chain.
 	fn transactions_author(&self) -> <Self::TargetChain as Chain>::AccountId;
 
-	/// Make submit header transaction.
+	/// Storage key of the authorities set that is used by the target chain finality pallet to
+	/// verify finality proofs.
+	///
+	/// If `Some(_)`, the set is read from the target chain before every finality proof submission
+	/// and is passed to the `optimize_finality_proof`.
+	fn target_authority_set_key() -> Option<StorageKey> {
+		None
+	}
+
+	/// Strip data that isn't required to verify the finality proof with given authorities set.
+	///
+	/// The default implementation returns the proof unchanged.
+	fn optimize_finality_proof(
+		&self,
+		_header: &Self::Header,
+		_authority_set: AuthoritySet,
+		proof: Self::FinalityProof,
+	) -> Self::FinalityProof {
+		proof
+	}
+
+	/// Make submit header transaction, paying given tip.
 	fn make_submit_finality_proof_transaction(
 		&self,
 		transaction_nonce: <Self::TargetChain as Chain>::Index,
+		tip: <Self::TargetChain as Chain>::Balance,
 		header: Self::Header,
 		proof: Self::FinalityProof,
 	) -> Bytes;
 }
 
 /// Substrate-to-Substrate finality proof pipeline.
+///
+/// The `E` is the finality engine of the source chain.
 #[derive(Clone)]
-pub struct SubstrateFinalityToSubstrate<SourceChain, TargetChain: Chain, TargetSign> {
+pub struct SubstrateFinalityToSubstrate<SourceChain, TargetChain: Chain, TargetSign, E = Grandpa> {
 	/// Client for the target chain.
 	pub(crate) target_client: Client<TargetChain>,
 	/// Data required to sign target chain transactions.
 	pub(crate) target_sign: TargetSign,
 	/// Unused generic arguments dump.
-	_marker: PhantomData<SourceChain>,
+	_marker: PhantomData<(SourceChain, E)>,
 }
 
-impl<SourceChain, TargetChain: Chain, TargetSign> Debug
-	for SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>
+impl<SourceChain, TargetChain: Chain, TargetSign, E> Debug
+	for SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign, E>
 {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
 		f.debug_struct("SubstrateFinalityToSubstrate")
@@ -86,7 +123,9 @@ impl<SourceChain, TargetChain: Chain, TargetSign> Debug
 	}
 }
 
-impl<SourceChain, TargetChain: Chain, TargetSign> SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign> {
+impl<SourceChain, TargetChain: Chain, TargetSign, E>
+	SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign, E>
+{
 	/// Create new Substrate-to-Substrate headers pipeline.
 	pub fn new(target_client: Client<TargetChain>, target_sign: TargetSign) -> Self {
 		SubstrateFinalityToSubstrate {
@@ -97,41 +136,47 @@ impl<SourceChain, TargetChain: Chain, TargetSign> SubstrateFinalityToSubstrate<S
 	}
 }
 
-impl<SourceChain, TargetChain, TargetSign> FinalitySyncPipeline
-	for SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>
+impl<SourceChain, TargetChain, TargetSign, E> FinalitySyncPipeline
+	for SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign, E>
 where
 	SourceChain: Clone + Chain + Debug,
 	BlockNumberOf<SourceChain>: BlockNumberBase,
 	TargetChain: Clone + Chain + Debug,
 	TargetSign: 'static + Clone + Send + Sync,
+	E: FinalityEngine<SourceChain::Header>,
 {
 	const SOURCE_NAME: &'static str = SourceChain::NAME;
 	const TARGET_NAME: &'static str = TargetChain::NAME;
 
 	type Hash = HashOf<SourceChain>;
 	type Number = BlockNumberOf<SourceChain>;
-	type Header = SyncHeader<SourceChain::Header>;
-	type FinalityProof = GrandpaJustification<SourceChain::Header>;
+	type Header = SyncHeader<SourceChain::Header, E>;
+	type FinalityProof = E::FinalityProof;
 }
 
 /// Run Substrate-to-Substrate finality sync.
-pub async fn run<SourceChain, TargetChain, P>(
+pub async fn run<SourceChain, TargetChain, P, E>(
 	pipeline: P,
 	source_client: Client<SourceChain>,
 	target_client: Client<TargetChain>,
+	submission_budget: Option<FinalitySubmissionBudget>,
+	start_block: Option<u32>,
+	only_mandatory_headers: bool,
+	mandatory_headers_tip_escalation: Option<TipEscalation<TargetChain::Balance>>,
 	metrics_params: MetricsParams,
 ) -> anyhow::Result<()>
 where
 	P: SubstrateFinalitySyncPipeline<
 		Hash = HashOf<SourceChain>,
 		Number = BlockNumberOf<SourceChain>,
-		Header = SyncHeader<SourceChain::Header>,
-		FinalityProof = GrandpaJustification<SourceChain::Header>,
+		Header = SyncHeader<SourceChain::Header, E>,
+		FinalityProof = E::FinalityProof,
 		TargetChain = TargetChain,
 	>,
 	SourceChain: Clone + Chain,
 	BlockNumberOf<SourceChain>: BlockNumberBase,
 	TargetChain: Clone + Chain,
+	E: FinalityEngine<SourceChain::Header>,
 {
 	log::info!(
 		target: "bridge",
@@ -141,12 +186,15 @@ where
 	);
 
 	finality_relay::run(
-		FinalitySource::new(source_client, None),
-		SubstrateFinalityTarget::new(target_client, pipeline),
+		FinalitySource::<_, _, E>::new(source_client, None),
+		SubstrateFinalityTarget::new(target_client, pipeline, mandatory_headers_tip_escalation),
 		FinalitySyncParams {
 			tick: std::cmp::max(SourceChain::AVERAGE_BLOCK_INTERVAL, TargetChain::AVERAGE_BLOCK_INTERVAL),
 			recent_finality_proofs_limit: RECENT_FINALITY_PROOFS_LIMIT,
 			stall_timeout: STALL_TIMEOUT,
+			submission_budget,
+			start_block,
+			only_mandatory_headers,
 		},
 		metrics_params,
 		futures::future::pending(),
@@ -154,3 +202,131 @@ where
 	.await
 	.map_err(|e| anyhow::format_err!("{}", e))
 }
+
+/// Run Substrate-to-Substrate finality sync from single source node to several target nodes.
+///
+/// All loops are sharing the same source client, so every source header and its finality proof are
+/// read from the source node once. All loops are reporting metrics to the same Prometheus endpoint.
+pub async fn run_to_many_targets<SourceChain, TargetChain, P, E>(
+	targets: Vec<(P, Client<TargetChain>)>,
+	source_client: Client<SourceChain>,
+	source_cache_size: usize,
+	only_mandatory_headers: bool,
+	mandatory_headers_tip_escalation: Option<TipEscalation<TargetChain::Balance>>,
+	metrics_params: MetricsParams,
+) -> anyhow::Result<()>
+where
+	P: SubstrateFinalitySyncPipeline<
+		Hash = HashOf<SourceChain>,
+		Number = BlockNumberOf<SourceChain>,
+		Header = SyncHeader<SourceChain::Header, E>,
+		FinalityProof = E::FinalityProof,
+		TargetChain = TargetChain,
+	>,
+	SourceChain: Clone + Chain,
+	BlockNumberOf<SourceChain>: BlockNumberBase,
+	TargetChain: Clone + Chain,
+	E: FinalityEngine<SourceChain::Header>,
+{
+	log::info!(
+		target: "bridge",
+		"Starting {} -> {} finality proof relay to {} target nodes",
+		SourceChain::NAME,
+		TargetChain::NAME,
+		targets.len(),
+	);
+
+	let source_client =
+		SharedSourceClient::<P, _>::new(FinalitySource::<_, P, E>::new(source_client, None), source_cache_size);
+	let sync_params = FinalitySyncParams {
+		tick: std::cmp::max(SourceChain::AVERAGE_BLOCK_INTERVAL, TargetChain::AVERAGE_BLOCK_INTERVAL),
+		recent_finality_proofs_limit: RECENT_FINALITY_PROOFS_LIMIT,
+		stall_timeout: STALL_TIMEOUT,
+		submission_budget: None,
+		start_block: None,
+		only_mandatory_headers,
+	};
+	let metrics_params = relay_utils::relay_metrics(None, metrics_params).into_params();
+
+	let loops = targets
+		.into_iter()
+		.enumerate()
+		.map(|(index, (pipeline, target_client))| {
+			let loop_metrics_prefix = format!("{}_{}", finality_relay::metrics_prefix::<P>(), index);
+			finality_relay::run(
+				source_client.clone(),
+				SubstrateFinalityTarget::new(target_client, pipeline, mandatory_headers_tip_escalation.clone()),
+				sync_params.clone(),
+				metrics_params.clone().disable().metrics_prefix(loop_metrics_prefix),
+				futures::future::pending(),
+			)
+		})
+		.collect::<Vec<_>>();
+
+	relay_utils::relay_metrics(None, metrics_params)
+		.expose()
+		.await
+		.map_err(|e| anyhow::format_err!("{}", e))?;
+
+	futures::future::try_join_all(loops)
+		.await
+		.map(drop)
+		.map_err(|e| anyhow::format_err!("{}", e))
+}
+
+/// Strip redundant votes and votes ancestries from the GRANDPA justification.
+///
+/// Only 2/3+1 of votes are required to justify header finalization, so the optimized
+/// justification is smaller and cheaper to verify. If optimization fails, the original
+/// justification is returned and the target chain will make the final decision.
+pub(crate) fn optimize_grandpa_justification<P: FinalitySyncPipeline, Header: HeaderT>(
+	header: &Header,
+	authority_set: AuthoritySet,
+	justification: GrandpaJustification<Header>,
+) -> GrandpaJustification<Header>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	let voter_set = match VoterSet::new(authority_set.authorities) {
+		Some(voter_set) => voter_set,
+		None => {
+			log::warn!(
+				target: "bridge",
+				"Failed to optimize {} justification: {} authorities set {} is invalid",
+				P::SOURCE_NAME,
+				P::TARGET_NAME,
+				authority_set.set_id,
+			);
+			return justification;
+		}
+	};
+
+	let header_id = (header.hash(), *header.number());
+	let original_precommits = justification.commit.precommits.len();
+	let original_votes_ancestries = justification.votes_ancestries.len();
+	match optimize_justification::<Header>(header_id, authority_set.set_id, &voter_set, justification.clone()) {
+		Ok(optimized_justification) => {
+			log::debug!(
+				target: "bridge",
+				"Optimized {} justification of header {:?}: {} -> {} precommits, {} -> {} votes ancestries",
+				P::SOURCE_NAME,
+				header_id,
+				original_precommits,
+				optimized_justification.commit.precommits.len(),
+				original_votes_ancestries,
+				optimized_justification.votes_ancestries.len(),
+			);
+			optimized_justification
+		}
+		Err(error) => {
+			log::warn!(
+				target: "bridge",
+				"Failed to optimize {} justification of header {:?}: {:?}. Submitting original justification",
+				P::SOURCE_NAME,
+				header_id,
+				error,
+			);
+			justification
+		}
+	}
+}
@@ -22,20 +22,53 @@ use crate::finality_pipeline::SubstrateFinalitySyncPipeline;
 
 use async_trait::async_trait;
 use codec::Decode;
-use finality_relay::TargetClient;
-use relay_substrate_client::{Chain, Client, Error as SubstrateError};
+use finality_relay::{SourceHeader, TargetClient};
+use num_traits::Zero;
+use relay_substrate_client::{Chain, Client, Error as SubstrateError, TipEscalation};
 use relay_utils::relay_loop::Client as RelayClient;
+use sp_runtime::traits::Header as HeaderT;
 
 /// Substrate client as Substrate finality target.
 pub struct SubstrateFinalityTarget<C: Chain, P> {
 	client: Client<C>,
 	pipeline: P,
+	mandatory_headers_tip_escalation: Option<TipEscalation<C::Balance>>,
 }
 
 impl<C: Chain, P> SubstrateFinalityTarget<C, P> {
 	/// Create new Substrate headers target.
-	pub fn new(client: Client<C>, pipeline: P) -> Self {
-		SubstrateFinalityTarget { client, pipeline }
+	///
+	/// If `mandatory_headers_tip_escalation` is `Some(_)`, transactions with mandatory headers are
+	/// paying tip that depends on the target chain congestion.
+	pub fn new(
+		client: Client<C>,
+		pipeline: P,
+		mandatory_headers_tip_escalation: Option<TipEscalation<C::Balance>>,
+	) -> Self {
+		SubstrateFinalityTarget {
+			client,
+			pipeline,
+			mandatory_headers_tip_escalation,
+		}
+	}
+
+	/// Returns tip that we are going to pay for submitting given header.
+	async fn submit_header_tip(&self, is_mandatory: bool) -> Result<C::Balance, SubstrateError> {
+		match self.mandatory_headers_tip_escalation {
+			Some(ref tip_escalation) if is_mandatory => {
+				let fee_multiplier = self.client.next_fee_multiplier().await?;
+				let tip = tip_escalation.tip(fee_multiplier);
+				log::debug!(
+					target: "bridge",
+					"Going to pay tip {:?} for mandatory header submission. {} fee multiplier: {:?}",
+					tip,
+					C::NAME,
+					fee_multiplier,
+				);
+				Ok(tip)
+			}
+			_ => Ok(Zero::zero()),
+		}
 	}
 }
 
@@ -44,6 +77,7 @@ impl<C: Chain, P: SubstrateFinalitySyncPipeline> Clone for SubstrateFinalityTarg
 		SubstrateFinalityTarget {
 			client: self.client.clone(),
 			pipeline: self.pipeline.clone(),
+			mandatory_headers_tip_escalation: self.mandatory_headers_tip_escalation,
 		}
 	}
 }
@@ -70,20 +104,31 @@ where
 		// it may have already received (some of) headers that we're going to relay
 		self.client.ensure_synced().await?;
 
-		Ok(crate::messages_source::read_client_state::<C, P::Hash, P::Number>(
-			&self.client,
-			P::BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET,
-		)
-		.await?
-		.best_finalized_peer_at_best_self
-		.0)
+		let best_header_hash = self.client.best_header().await?.hash();
+		let best_finalized_source_header_id =
+			crate::messages_source::read_best_finalized_peer_header_id::<C, P::Hash, P::Number>(
+				&self.client,
+				P::BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET,
+				P::TARGET_FINALITY_INSTANCE,
+				best_header_hash,
+			)
+			.await?;
+		Ok(best_finalized_source_header_id.0)
 	}
 
 	async fn submit_finality_proof(&self, header: P::Header, proof: P::FinalityProof) -> Result<(), SubstrateError> {
+		let tip = self.submit_header_tip(header.is_mandatory()).await?;
+		let proof = match P::target_authority_set_key() {
+			Some(authority_set_key) => match self.client.storage_value(authority_set_key).await? {
+				Some(authority_set) => self.pipeline.optimize_finality_proof(&header, authority_set, proof),
+				None => proof,
+			},
+			None => proof,
+		};
 		self.client
 			.submit_signed_extrinsic(self.pipeline.transactions_author(), move |transaction_nonce| {
 				self.pipeline
-					.make_submit_finality_proof_transaction(transaction_nonce, header, proof)
+					.make_submit_finality_proof_transaction(transaction_nonce, tip, header, proof)
 			})
 			.await
 			.map(drop)
@@ -21,11 +21,11 @@
 //! and authorities set from source to target chain. The headers sync starts
 //! with this header.
 
-use bp_header_chain::InitializationData;
 use bp_header_chain::{
 	find_grandpa_authorities_scheduled_change,
 	justification::{verify_justification, GrandpaJustification},
 };
+use bp_header_chain::{InitializationData, OperatingMode};
 use codec::Decode;
 use finality_grandpa::voter_set::VoterSet;
 use num_traits::{One, Zero};
@@ -210,7 +210,7 @@ async fn prepare_initialization_data<SourceChain: Chain>(
 		} else {
 			initial_authorities_set_id
 		},
-		is_halted: false,
+		operating_mode: OperatingMode::Normal,
 	})
 }
 
@@ -19,6 +19,7 @@ use crate::messages_target::SubstrateMessagesReceivingProof;
 use crate::on_demand_headers::OnDemandHeadersRelay;
 
 use bp_messages::{LaneId, MessageNonce};
+use bp_runtime::ChainId;
 use frame_support::weights::Weight;
 use messages_relay::message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf};
 use relay_substrate_client::{BlockNumberOf, Chain, Client, HashOf};
@@ -42,6 +43,15 @@ pub struct MessagesRelayParams<SC: Chain, SS, TC: Chain, TS> {
 	pub target_to_source_headers_relay: Option<OnDemandHeadersRelay<TC>>,
 	/// Identifier of lane that needs to be served.
 	pub lane_id: LaneId,
+	/// Instance of bridge pallets at the source chain. If `None`, the instance that is bridging
+	/// with the target chain is used.
+	pub source_instance: Option<ChainId>,
+	/// Instance of bridge pallets at the target chain. If `None`, the instance that is bridging
+	/// with the source chain is used.
+	pub target_instance: Option<ChainId>,
+	/// Skip outdated messages and only deliver the latest messages. May only be used at lanes
+	/// with `LaneOrdering::Unordered`.
+	pub skip_outdated_messages: bool,
 	/// Metrics parameters.
 	pub metrics_params: MetricsParams,
 }
@@ -67,6 +77,13 @@ pub trait SubstrateMessageLane: MessageLane {
 	/// Name of the runtime method that returns id of best finalized target header at source chain.
 	const BEST_FINALIZED_TARGET_HEADER_ID_AT_SOURCE: &'static str;
 
+	/// Name of the runtime method that returns state of the finality pallet at the source chain (the pallet
+	/// that is used to verify target chain headers).
+	const FINALITY_PALLET_STATE_AT_SOURCE: &'static str;
+	/// Name of the runtime method that returns state of the finality pallet at the target chain (the pallet
+	/// that is used to verify source chain headers).
+	const FINALITY_PALLET_STATE_AT_TARGET: &'static str;
+
 	/// Source chain.
 	type SourceChain: Chain;
 	/// Target chain.
@@ -22,21 +22,23 @@ use crate::messages_lane::SubstrateMessageLane;
 use crate::on_demand_headers::OnDemandHeadersRelay;
 
 use async_trait::async_trait;
-use bp_messages::{LaneId, MessageNonce};
-use bp_runtime::ChainId;
+use bp_header_chain::FinalityPalletState;
+use bp_messages::{LaneId, LaneState, MessageNonce, OperatingMode, OutboundLaneData};
+use bp_runtime::{ChainId, UnknownBridgeInstance};
 use bridge_runtime_common::messages::target::FromBridgedChainMessagesProof;
-use codec::{Decode, Encode};
+use codec::{Decode, DecodeAll, Encode};
 use frame_support::{traits::Instance, weights::Weight};
 use messages_relay::{
 	message_lane::{SourceHeaderIdOf, TargetHeaderIdOf},
 	message_lane_loop::{
 		ClientState, MessageProofParameters, MessageWeights, MessageWeightsMap, SourceClient, SourceClientState,
 	},
+	rejection_reason::RejectionReason,
 };
 use pallet_bridge_messages::Config as MessagesConfig;
-use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf, HeaderIdOf};
+use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf, HeaderIdOf, StorageChangesTracker};
 use relay_utils::{relay_loop::Client as RelayClient, BlockNumberBase, HeaderId};
-use sp_core::Bytes;
+use sp_core::{storage::StorageKey, Bytes};
 use sp_runtime::{traits::Header as HeaderT, DeserializeOwned};
 use std::{marker::PhantomData, ops::RangeInclusive};
 
@@ -52,10 +54,11 @@ pub struct SubstrateMessagesSource<C: Chain, P: SubstrateMessageLane, R, I> {
 	lane_id: LaneId,
 	instance: ChainId,
 	target_to_source_headers_relay: Option<OnDemandHeadersRelay<P::TargetChain>>,
+	outbound_lane_tracker: StorageChangesTracker<C>,
 	_phantom: PhantomData<(R, I)>,
 }
 
-impl<C: Chain, P: SubstrateMessageLane, R, I> SubstrateMessagesSource<C, P, R, I> {
+impl<C: Chain, P: SubstrateMessageLane, R, I: Instance> SubstrateMessagesSource<C, P, R, I> {
 	/// Create new Substrate headers source.
 	pub fn new(
 		client: Client<C>,
@@ -70,6 +73,9 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> SubstrateMessagesSource<C, P, R, I
 			lane_id,
 			instance,
 			target_to_source_headers_relay,
+			outbound_lane_tracker: StorageChangesTracker::new(vec![
+				pallet_bridge_messages::storage_keys::outbound_lane_data_key::<I>(&lane_id),
+			]),
 			_phantom: Default::default(),
 		}
 	}
@@ -83,6 +89,7 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> Clone for SubstrateMessagesSource<
 			lane_id: self.lane_id,
 			instance: self.instance,
 			target_to_source_headers_relay: self.target_to_source_headers_relay.clone(),
+			outbound_lane_tracker: self.outbound_lane_tracker.clone(),
 			_phantom: Default::default(),
 		}
 	}
@@ -127,27 +134,55 @@ where
 		// it may have already received confirmations that we're going to deliver
 		self.client.ensure_synced().await?;
 
-		read_client_state::<_, P::TargetHeaderHash, P::TargetHeaderNumber>(
+		let mut state = read_client_state::<_, P::TargetHeaderHash, P::TargetHeaderNumber>(
 			&self.client,
 			P::BEST_FINALIZED_TARGET_HEADER_ID_AT_SOURCE,
+			self.instance,
+			pallet_bridge_messages::storage_keys::operating_mode_key::<I>(),
+			P::FINALITY_PALLET_STATE_AT_SOURCE,
 		)
-		.await
+		.await?;
+
+		// closed lane won't accept any delivery confirmations
+		let lane_data: Option<OutboundLaneData> = self
+			.client
+			.storage_value(pallet_bridge_messages::storage_keys::outbound_lane_data_key::<I>(
+				&self.lane_id,
+			))
+			.await?;
+		state.is_halted |= is_lane_closed::<C>(self.lane_id, lane_data.map(|lane_data| lane_data.state));
+
+		// halted lane won't accept any delivery confirmations either
+		let lane_operating_mode: Option<OperatingMode> = self
+			.client
+			.storage_value(pallet_bridge_messages::storage_keys::lane_operating_mode_key::<I>(
+				&self.lane_id,
+			))
+			.await?;
+		state.is_halted |= is_lane_halted::<C>(self.lane_id, lane_operating_mode);
+
+		Ok(state)
 	}
 
 	async fn latest_generated_nonce(
 		&self,
 		id: SourceHeaderIdOf<P>,
 	) -> Result<(SourceHeaderIdOf<P>, MessageNonce), SubstrateError> {
-		let encoded_response = self
-			.client
-			.state_call(
-				P::OUTBOUND_LANE_LATEST_GENERATED_NONCE_METHOD.into(),
-				Bytes(self.lane_id.encode()),
-				Some(id.1),
+		let latest_generated_nonce = self
+			.outbound_lane_tracker
+			.read(
+				&self.client,
+				P::OUTBOUND_LANE_LATEST_GENERATED_NONCE_METHOD,
+				id.0,
+				read_lane_nonce(
+					&self.client,
+					P::OUTBOUND_LANE_LATEST_GENERATED_NONCE_METHOD,
+					self.instance,
+					self.lane_id,
+					id.1,
+				),
 			)
 			.await?;
-		let latest_generated_nonce: MessageNonce =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
 		Ok((id, latest_generated_nonce))
 	}
 
@@ -155,16 +190,21 @@ where
 		&self,
 		id: SourceHeaderIdOf<P>,
 	) -> Result<(SourceHeaderIdOf<P>, MessageNonce), SubstrateError> {
-		let encoded_response = self
-			.client
-			.state_call(
-				P::OUTBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD.into(),
-				Bytes(self.lane_id.encode()),
-				Some(id.1),
+		let latest_received_nonce = self
+			.outbound_lane_tracker
+			.read(
+				&self.client,
+				P::OUTBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD,
+				id.0,
+				read_lane_nonce(
+					&self.client,
+					P::OUTBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD,
+					self.instance,
+					self.lane_id,
+					id.1,
+				),
 			)
 			.await?;
-		let latest_received_nonce: MessageNonce =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
 		Ok((id, latest_received_nonce))
 	}
 
@@ -177,13 +217,13 @@ where
 			.client
 			.state_call(
 				P::OUTBOUND_LANE_MESSAGE_DETAILS_METHOD.into(),
-				Bytes((self.lane_id, nonces.start(), nonces.end()).encode()),
+				Bytes((self.instance, self.lane_id, nonces.start(), nonces.end()).encode()),
 				Some(id.1),
 			)
 			.await?;
 
 		make_message_details_map::<C>(
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?,
+			decode_bridge_instance_response(P::OUTBOUND_LANE_MESSAGE_DETAILS_METHOD, &encoded_response.0)?,
 			nonces,
 		)
 	}
@@ -209,7 +249,7 @@ where
 
 		let proof = self
 			.client
-			.prove_storage(storage_keys, id.1)
+			.prove_storage_with_cache(storage_keys, id.1)
 			.await?
 			.iter_nodes()
 			.collect();
@@ -233,7 +273,11 @@ where
 				self.lane
 					.make_messages_receiving_proof_transaction(transaction_nonce, generated_at_block, proof)
 			})
-			.await?;
+			.await
+			.map_err(|error| {
+				log_rejection_reason::<C>(self.lane_id, "Messages receiving proof", &error);
+				error
+			})?;
 		Ok(())
 	}
 
@@ -247,6 +291,9 @@ where
 pub async fn read_client_state<SelfChain, BridgedHeaderHash, BridgedHeaderNumber>(
 	self_client: &Client<SelfChain>,
 	best_finalized_header_id_method_name: &str,
+	instance: ChainId,
+	messages_pallet_operating_mode_key: StorageKey,
+	finality_pallet_state_method_name: &str,
 ) -> Result<ClientState<HeaderIdOf<SelfChain>, HeaderId<BridgedHeaderHash, BridgedHeaderNumber>>, SubstrateError>
 where
 	SelfChain: Chain,
@@ -266,24 +313,158 @@ where
 	let self_best_id = HeaderId(*self_best_header.number(), self_best_hash);
 
 	// now let's read id of best finalized peer header at our best finalized block
-	let encoded_best_finalized_peer_on_self = self_client
+	let peer_on_self_best_finalized_id = read_best_finalized_peer_header_id(
+		self_client,
+		best_finalized_header_id_method_name,
+		Some(instance),
+		self_best_hash,
+	)
+	.await?;
+
+	// finally, let's check if bridge pallets at this chain are able to accept our transactions.
+	// Messages pallet in `RejectingOutboundMessages` mode still accepts both messages and delivery
+	// proofs. Finality pallet in `RejectingNonMandatoryHeaders` mode still may be used to verify
+	// proofs. So we only care about `Halted` mode here
+	let messages_pallet_operating_mode: OperatingMode = self_client
+		.storage_value(messages_pallet_operating_mode_key)
+		.await?
+		.unwrap_or_default();
+	let encoded_finality_pallet_state = self_client
 		.state_call(
-			best_finalized_header_id_method_name.into(),
-			Bytes(Vec::new()),
+			finality_pallet_state_method_name.into(),
+			Bytes(instance.encode()),
 			Some(self_best_hash),
 		)
 		.await?;
-	let decoded_best_finalized_peer_on_self: (BridgedHeaderNumber, BridgedHeaderHash) =
-		Decode::decode(&mut &encoded_best_finalized_peer_on_self.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
-	let peer_on_self_best_finalized_id = HeaderId(
-		decoded_best_finalized_peer_on_self.0,
-		decoded_best_finalized_peer_on_self.1,
-	);
+	let finality_pallet_state: Option<FinalityPalletState<BridgedHeaderNumber, BridgedHeaderHash>> =
+		decode_bridge_instance_response(finality_pallet_state_method_name, &encoded_finality_pallet_state.0)?;
+	let is_halted = messages_pallet_operating_mode == OperatingMode::Halted
+		|| finality_pallet_state.map(|state| state.is_halted()).unwrap_or(false);
 
 	Ok(ClientState {
 		best_self: self_best_id,
 		best_finalized_self: self_best_finalized_id,
 		best_finalized_peer_at_best_self: peer_on_self_best_finalized_id,
+		is_halted,
+	})
+}
+
+/// Read id of the best finalized peer header, known to the bridge pallet at given block of **this** chain.
+///
+/// If `instance` is `None`, the runtime API method is expected to be the one that doesn't accept the
+/// bridge pallet instance.
+pub async fn read_best_finalized_peer_header_id<SelfChain, BridgedHeaderHash, BridgedHeaderNumber>(
+	self_client: &Client<SelfChain>,
+	best_finalized_header_id_method_name: &str,
+	instance: Option<ChainId>,
+	at_block: SelfChain::Hash,
+) -> Result<HeaderId<BridgedHeaderHash, BridgedHeaderNumber>, SubstrateError>
+where
+	SelfChain: Chain,
+	BridgedHeaderHash: Decode,
+	BridgedHeaderNumber: Decode,
+{
+	let encoded_best_finalized_peer_on_self = self_client
+		.state_call(
+			best_finalized_header_id_method_name.into(),
+			Bytes(instance.map(|instance| instance.encode()).unwrap_or_default()),
+			Some(at_block),
+		)
+		.await?;
+	let decoded_best_finalized_peer_on_self: (BridgedHeaderNumber, BridgedHeaderHash) = match instance {
+		Some(_) => decode_bridge_instance_response(
+			best_finalized_header_id_method_name,
+			&encoded_best_finalized_peer_on_self.0,
+		)?,
+		None => Decode::decode(&mut &encoded_best_finalized_peer_on_self.0[..])
+			.map_err(SubstrateError::ResponseParseFailed)?,
+	};
+	Ok(HeaderId(
+		decoded_best_finalized_peer_on_self.0,
+		decoded_best_finalized_peer_on_self.1,
+	))
+}
+
+/// Returns true if the lane is closed at the chain and relay shouldn't submit any lane transactions
+/// to this chain.
+pub fn is_lane_closed<SelfChain: Chain>(lane_id: LaneId, lane_state: Option<LaneState>) -> bool {
+	let is_closed = lane_state.map(|lane_state| lane_state.is_closed()).unwrap_or(false);
+	if is_closed {
+		log::debug!(
+			target: "bridge",
+			"Lane {:?} is closed at {}. No more lane transactions will be submitted to {}",
+			lane_id,
+			SelfChain::NAME,
+			SelfChain::NAME,
+		);
+	}
+
+	is_closed
+}
+
+/// Returns true if the lane is halted at the chain and relay shouldn't submit any lane transactions
+/// to this chain.
+///
+/// Lane in `RejectingOutboundMessages` mode still accepts both messages and delivery proofs, so we
+/// only care about `Halted` mode here.
+pub fn is_lane_halted<SelfChain: Chain>(lane_id: LaneId, lane_operating_mode: Option<OperatingMode>) -> bool {
+	let is_halted = lane_operating_mode == Some(OperatingMode::Halted);
+	if is_halted {
+		log::debug!(
+			target: "bridge",
+			"Lane {:?} is halted at {}. No lane transactions will be submitted to {} until it is resumed",
+			lane_id,
+			SelfChain::NAME,
+			SelfChain::NAME,
+		);
+	}
+
+	is_halted
+}
+
+/// Log the reason why the messages pallet has rejected the lane transaction, if it may be found in
+/// the transaction submission error.
+pub fn log_rejection_reason<SelfChain: Chain>(lane_id: LaneId, transaction: &str, error: &SubstrateError) {
+	if let Some(reason) = RejectionReason::from_error_description(&error.to_string()) {
+		log::error!(
+			target: "bridge",
+			"{} transaction at lane {:?} has been rejected by {}: {}. {}",
+			transaction,
+			lane_id,
+			SelfChain::NAME,
+			reason,
+			if reason.is_temporary() {
+				"It may succeed later"
+			} else {
+				"Resubmitting the same transaction won't help"
+			},
+		);
+	}
+}
+
+/// Read lane nonce using given runtime API method of given bridge pallet instance.
+pub async fn read_lane_nonce<C: Chain>(
+	client: &Client<C>,
+	method: &str,
+	instance: ChainId,
+	lane_id: LaneId,
+	at_block: C::Hash,
+) -> Result<MessageNonce, SubstrateError> {
+	let encoded_response = client
+		.state_call(method.into(), Bytes((instance, lane_id).encode()), Some(at_block))
+		.await?;
+	decode_bridge_instance_response(method, &encoded_response.0)
+}
+
+/// Decode response of the runtime API method that queries given bridge pallet instance.
+pub fn decode_bridge_instance_response<T: Decode>(method: &str, encoded_response: &[u8]) -> Result<T, SubstrateError> {
+	let response: Result<T, UnknownBridgeInstance> =
+		DecodeAll::decode_all(encoded_response).map_err(SubstrateError::ResponseParseFailed)?;
+	response.map_err(|UnknownBridgeInstance(instance)| {
+		SubstrateError::Custom(format!(
+			"Runtime API method {} has been called for unknown bridge pallet instance {:?}",
+			method, instance,
+		))
 	})
 }
 
@@ -367,6 +548,7 @@ mod tests {
 				dispatch_weight: 0,
 				size: 0,
 				delivery_and_dispatch_fee: 0,
+				dispatch_fee_payment: bp_runtime::DispatchFeePayment::AtSourceChain,
 			})
 			.collect()
 	}
@@ -423,4 +605,25 @@ mod tests {
 			Err(SubstrateError::Custom(_))
 		));
 	}
+
+	#[test]
+	fn decode_bridge_instance_response_works() {
+		let response: Result<MessageNonce, UnknownBridgeInstance> = Ok(42);
+		assert_eq!(
+			decode_bridge_instance_response::<MessageNonce>("method", &response.encode()).unwrap(),
+			42,
+		);
+
+		let response: Result<MessageNonce, UnknownBridgeInstance> = Err(UnknownBridgeInstance(*b"test"));
+		assert!(matches!(
+			decode_bridge_instance_response::<MessageNonce>("method", &response.encode()),
+			Err(SubstrateError::Custom(_))
+		));
+
+		let response: MessageNonce = 42;
+		assert!(matches!(
+			decode_bridge_instance_response::<MessageNonce>("method", &response.encode()),
+			Err(SubstrateError::ResponseParseFailed(_))
+		));
+	}
 }
@@ -19,11 +19,14 @@
 //! <BridgedName> chain.
 
 use crate::messages_lane::SubstrateMessageLane;
-use crate::messages_source::read_client_state;
+use crate::messages_source::{
+	decode_bridge_instance_response, is_lane_closed, is_lane_halted, log_rejection_reason, read_client_state,
+	read_lane_nonce,
+};
 use crate::on_demand_headers::OnDemandHeadersRelay;
 
 use async_trait::async_trait;
-use bp_messages::{LaneId, MessageNonce, UnrewardedRelayersState};
+use bp_messages::{InboundLaneData, LaneId, MessageNonce, OperatingMode, UnrewardedRelayersState};
 use bp_runtime::ChainId;
 use bridge_runtime_common::messages::source::FromBridgedChainMessagesDeliveryProof;
 use codec::{Decode, Encode};
@@ -33,7 +36,7 @@ use messages_relay::{
 	message_lane_loop::{TargetClient, TargetClientState},
 };
 use pallet_bridge_messages::Config as MessagesConfig;
-use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf};
+use relay_substrate_client::{Chain, Client, Error as SubstrateError, HashOf, StorageChangesTracker};
 use relay_utils::{relay_loop::Client as RelayClient, BlockNumberBase};
 use sp_core::Bytes;
 use sp_runtime::{traits::Header as HeaderT, DeserializeOwned};
@@ -52,10 +55,11 @@ pub struct SubstrateMessagesTarget<C: Chain, P: SubstrateMessageLane, R, I> {
 	lane_id: LaneId,
 	instance: ChainId,
 	source_to_target_headers_relay: Option<OnDemandHeadersRelay<P::SourceChain>>,
+	inbound_lane_tracker: StorageChangesTracker<C>,
 	_phantom: PhantomData<(R, I)>,
 }
 
-impl<C: Chain, P: SubstrateMessageLane, R, I> SubstrateMessagesTarget<C, P, R, I> {
+impl<C: Chain, P: SubstrateMessageLane, R: MessagesConfig<I>, I: Instance> SubstrateMessagesTarget<C, P, R, I> {
 	/// Create new Substrate headers target.
 	pub fn new(
 		client: Client<C>,
@@ -70,6 +74,9 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> SubstrateMessagesTarget<C, P, R, I
 			lane_id,
 			instance,
 			source_to_target_headers_relay,
+			inbound_lane_tracker: StorageChangesTracker::new(vec![
+				pallet_bridge_messages::storage_keys::inbound_lane_data_key::<R, I>(&lane_id),
+			]),
 			_phantom: Default::default(),
 		}
 	}
@@ -83,6 +90,7 @@ impl<C: Chain, P: SubstrateMessageLane, R, I> Clone for SubstrateMessagesTarget<
 			lane_id: self.lane_id,
 			instance: self.instance,
 			source_to_target_headers_relay: self.source_to_target_headers_relay.clone(),
+			inbound_lane_tracker: self.inbound_lane_tracker.clone(),
 			_phantom: Default::default(),
 		}
 	}
@@ -127,27 +135,55 @@ where
 		// it may have already received (some of) messages that we're going to deliver
 		self.client.ensure_synced().await?;
 
-		read_client_state::<_, P::SourceHeaderHash, P::SourceHeaderNumber>(
+		let mut state = read_client_state::<_, P::SourceHeaderHash, P::SourceHeaderNumber>(
 			&self.client,
 			P::BEST_FINALIZED_SOURCE_HEADER_ID_AT_TARGET,
+			self.instance,
+			pallet_bridge_messages::storage_keys::operating_mode_key::<I>(),
+			P::FINALITY_PALLET_STATE_AT_TARGET,
 		)
-		.await
+		.await?;
+
+		// closed lane won't accept any messages
+		let lane_data: Option<InboundLaneData<R::InboundRelayer>> = self
+			.client
+			.storage_value(pallet_bridge_messages::storage_keys::inbound_lane_data_key::<R, I>(
+				&self.lane_id,
+			))
+			.await?;
+		state.is_halted |= is_lane_closed::<C>(self.lane_id, lane_data.map(|lane_data| lane_data.state));
+
+		// halted lane won't accept any messages either
+		let lane_operating_mode: Option<OperatingMode> = self
+			.client
+			.storage_value(pallet_bridge_messages::storage_keys::lane_operating_mode_key::<I>(
+				&self.lane_id,
+			))
+			.await?;
+		state.is_halted |= is_lane_halted::<C>(self.lane_id, lane_operating_mode);
+
+		Ok(state)
 	}
 
 	async fn latest_received_nonce(
 		&self,
 		id: TargetHeaderIdOf<P>,
 	) -> Result<(TargetHeaderIdOf<P>, MessageNonce), SubstrateError> {
-		let encoded_response = self
-			.client
-			.state_call(
-				P::INBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD.into(),
-				Bytes(self.lane_id.encode()),
-				Some(id.1),
+		let latest_received_nonce = self
+			.inbound_lane_tracker
+			.read(
+				&self.client,
+				P::INBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD,
+				id.0,
+				read_lane_nonce(
+					&self.client,
+					P::INBOUND_LANE_LATEST_RECEIVED_NONCE_METHOD,
+					self.instance,
+					self.lane_id,
+					id.1,
+				),
 			)
 			.await?;
-		let latest_received_nonce: MessageNonce =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
 		Ok((id, latest_received_nonce))
 	}
 
@@ -155,17 +191,22 @@ where
 		&self,
 		id: TargetHeaderIdOf<P>,
 	) -> Result<(TargetHeaderIdOf<P>, MessageNonce), SubstrateError> {
-		let encoded_response = self
-			.client
-			.state_call(
-				P::INBOUND_LANE_LATEST_CONFIRMED_NONCE_METHOD.into(),
-				Bytes(self.lane_id.encode()),
-				Some(id.1),
+		let latest_confirmed_nonce = self
+			.inbound_lane_tracker
+			.read(
+				&self.client,
+				P::INBOUND_LANE_LATEST_CONFIRMED_NONCE_METHOD,
+				id.0,
+				read_lane_nonce(
+					&self.client,
+					P::INBOUND_LANE_LATEST_CONFIRMED_NONCE_METHOD,
+					self.instance,
+					self.lane_id,
+					id.1,
+				),
 			)
 			.await?;
-		let latest_received_nonce: MessageNonce =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
-		Ok((id, latest_received_nonce))
+		Ok((id, latest_confirmed_nonce))
 	}
 
 	async fn unrewarded_relayers_state(
@@ -176,12 +217,12 @@ where
 			.client
 			.state_call(
 				P::INBOUND_LANE_UNREWARDED_RELAYERS_STATE.into(),
-				Bytes(self.lane_id.encode()),
+				Bytes((self.instance, self.lane_id).encode()),
 				Some(id.1),
 			)
 			.await?;
 		let unrewarded_relayers_state: UnrewardedRelayersState =
-			Decode::decode(&mut &encoded_response.0[..]).map_err(SubstrateError::ResponseParseFailed)?;
+			decode_bridge_instance_response(P::INBOUND_LANE_UNREWARDED_RELAYERS_STATE, &encoded_response.0)?;
 		Ok((id, unrewarded_relayers_state))
 	}
 
@@ -220,7 +261,11 @@ where
 					proof,
 				)
 			})
-			.await?;
+			.await
+			.map_err(|error| {
+				log_rejection_reason::<C>(self.lane_id, "Messages delivery", &error);
+				error
+			})?;
 		Ok(nonces)
 	}
 
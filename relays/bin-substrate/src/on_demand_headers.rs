@@ -138,7 +138,7 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 		_,
 		SubstrateFinalityToSubstrate<SourceChain, TargetChain, TargetSign>,
 	>::new(source_client.clone(), Some(required_header_number.clone()));
-	let mut finality_target = SubstrateFinalityTarget::new(target_client.clone(), pipeline.clone());
+	let mut finality_target = SubstrateFinalityTarget::new(target_client.clone(), pipeline.clone(), None);
 
 	let mut restart_relay = true;
 	let finality_relay_task = futures::future::Fuse::terminated();
@@ -201,6 +201,9 @@ async fn background_task<SourceChain, TargetChain, TargetSign>(
 						tick: std::cmp::max(SourceChain::AVERAGE_BLOCK_INTERVAL, TargetChain::AVERAGE_BLOCK_INTERVAL),
 						recent_finality_proofs_limit: RECENT_FINALITY_PROOFS_LIMIT,
 						stall_timeout: STALL_TIMEOUT,
+						submission_budget: None,
+						start_block: None,
+						only_mandatory_headers: false,
 					},
 					MetricsParams::disabled(),
 					futures::future::pending(),
@@ -323,6 +326,7 @@ fn on_demand_headers_relay_name<SourceChain: Chain, TargetChain: Chain>() -> Str
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use relay_utils::HeaderId;
 
 	type TestChain = relay_millau_client::Millau;
 
@@ -342,4 +346,35 @@ mod tests {
 		.await;
 		assert_eq!(*required_header_number.lock().await, AT_SOURCE.unwrap());
 	}
+
+	#[async_std::test]
+	async fn does_not_update_required_header_when_few_headers_missing() {
+		let required_header_number = Arc::new(Mutex::new(0));
+		update_required_header_number_if_too_many_are_missing::<TestChain>(
+			AT_SOURCE,
+			AT_TARGET,
+			10,
+			&required_header_number,
+			"test",
+		)
+		.await;
+		assert_eq!(*required_header_number.lock().await, 0);
+	}
+
+	#[async_std::test]
+	async fn required_header_number_is_never_decreased() {
+		let relay = OnDemandHeadersRelay::<TestChain> {
+			relay_task_name: "test".into(),
+			required_header_number: Arc::new(Mutex::new(0)),
+		};
+
+		relay.require_finalized_header(HeaderId(10, Default::default())).await;
+		assert_eq!(*relay.required_header_number.lock().await, 10);
+
+		relay.require_finalized_header(HeaderId(5, Default::default())).await;
+		assert_eq!(*relay.required_header_number.lock().await, 10);
+
+		relay.require_finalized_header(HeaderId(15, Default::default())).await;
+		assert_eq!(*relay.required_header_number.lock().await, 15);
+	}
 }
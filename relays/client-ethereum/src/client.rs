@@ -16,18 +16,25 @@
 
 use crate::rpc::Ethereum;
 use crate::types::{
-	Address, Bytes, CallRequest, Header, HeaderWithTransactions, Receipt, SignedRawTx, SyncState, Transaction,
-	TransactionHash, H256, U256,
+	Address, BlockHeader, BlockNumber, Bytes, CallRequest, FeeHistory, Filter, Header, HeaderWithTransactions, Log,
+	Receipt, SignedRawTx, SyncState, Transaction, TransactionHash, H256, U256,
 };
 use crate::{ConnectionParams, Error, Result};
 
-use jsonrpsee_ws_client::{WsClient as RpcClient, WsClientBuilder as RpcClientBuilder};
+use jsonrpsee_ws_client::{traits::SubscriptionClient, v2::params::JsonRpcParams};
+use jsonrpsee_ws_client::{Subscription, WsClient as RpcClient, WsClientBuilder as RpcClientBuilder};
 use relay_utils::relay_loop::RECONNECT_DELAY;
 use std::sync::Arc;
 
 /// Number of headers missing from the Ethereum node for us to consider node not synced.
 const MAJOR_SYNC_BLOCKS: u64 = 5;
 
+/// New best headers subscription type.
+pub type NewHeadsSubscription = Subscription<BlockHeader>;
+
+/// Logs subscription type.
+pub type LogsSubscription = Subscription<Log>;
+
 /// The client used to interact with an Ethereum node through RPC.
 #[derive(Clone)]
 pub struct Client {
@@ -191,4 +198,40 @@ impl Client {
 	pub async fn eth_call(&self, call_transaction: CallRequest) -> Result<Bytes> {
 		Ok(Ethereum::call(&*self.client, call_transaction).await?)
 	}
+
+	/// Retrieve fee history of `block_count` latest blocks.
+	///
+	/// Every element of `reward_percentiles` is a percentile of priority fees that we want to
+	/// see in the history.
+	pub async fn fee_history(&self, block_count: u64, reward_percentiles: Vec<f64>) -> Result<FeeHistory> {
+		Ok(Ethereum::fee_history(&*self.client, block_count, BlockNumber::Latest, reward_percentiles).await?)
+	}
+
+	/// Return new stream of best headers.
+	///
+	/// The node only sends headers of new best blocks. When there's a reorg, headers of all
+	/// enacted blocks are sent.
+	pub async fn subscribe_new_heads(&self) -> Result<NewHeadsSubscription> {
+		Ok(self
+			.client
+			.subscribe(
+				"eth_subscribe",
+				JsonRpcParams::Array(vec!["newHeads".into()]),
+				"eth_unsubscribe",
+			)
+			.await?)
+	}
+
+	/// Return new stream of logs that are matching given filter.
+	pub async fn subscribe_logs(&self, filter: Filter) -> Result<LogsSubscription> {
+		let filter = serde_json::to_value(filter).expect("filter only contains serializable fields; qed");
+		Ok(self
+			.client
+			.subscribe(
+				"eth_subscribe",
+				JsonRpcParams::Array(vec!["logs".into(), filter]),
+				"eth_unsubscribe",
+			)
+			.await?)
+	}
 }
@@ -23,9 +23,9 @@ mod error;
 mod rpc;
 mod sign;
 
-pub use crate::client::Client;
+pub use crate::client::{Client, LogsSubscription, NewHeadsSubscription};
 pub use crate::error::{Error, Result};
-pub use crate::sign::{sign_and_submit_transaction, SigningParams};
+pub use crate::sign::{estimate_eip1559_fees, sign_and_submit_transaction, Eip1559Fees, SigningParams};
 
 pub mod types;
 
@@ -17,8 +17,8 @@
 //! Ethereum node RPC interface.
 
 use crate::types::{
-	Address, Bytes, CallRequest, Header, HeaderWithTransactions, Receipt, SyncState, Transaction, TransactionHash,
-	H256, U256, U64,
+	Address, BlockNumber, Bytes, CallRequest, FeeHistory, Header, HeaderWithTransactions, Receipt, SyncState,
+	Transaction, TransactionHash, H256, U256, U64,
 };
 
 jsonrpsee_proc_macros::rpc_client_api! {
@@ -47,5 +47,7 @@ jsonrpsee_proc_macros::rpc_client_api! {
 		fn submit_transaction(transaction: Bytes) -> TransactionHash;
 		#[rpc(method = "eth_call", positional_params)]
 		fn call(transaction_call: CallRequest) -> Bytes;
+		#[rpc(method = "eth_feeHistory", positional_params)]
+		fn fee_history(block_count: U256, newest_block: BlockNumber, reward_percentiles: Vec<f64>) -> FeeHistory;
 	}
 }
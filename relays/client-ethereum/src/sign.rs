@@ -14,12 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::types::{Address, CallRequest, U256};
+use crate::types::{Address, CallRequest, FeeHistory, U256};
 use crate::{Client, Result};
 use bp_eth_poa::signatures::{secret_to_address, SignTransaction};
 use hex_literal::hex;
+use relay_utils::MaybeConnectionError;
 use secp256k1::SecretKey;
 
+/// Number of recent blocks that are used to estimate EIP-1559 transaction fees.
+const FEE_HISTORY_BLOCKS: u64 = 10;
+/// Percentile of priority fees, paid in recent blocks, that we agree to pay.
+const PRIORITY_FEE_PERCENTILE: f64 = 50.0;
+/// Priority fee per gas that we pay if there were no transactions in recent blocks.
+const DEFAULT_PRIORITY_FEE_PER_GAS: u64 = 1_000_000_000; // 1 Gwei
+
 /// Ethereum signing params.
 #[derive(Clone, Debug)]
 pub struct SigningParams {
@@ -27,8 +35,16 @@ pub struct SigningParams {
 	pub chain_id: u64,
 	/// Ethereum transactions signer.
 	pub signer: SecretKey,
-	/// Gas price we agree to pay.
+	/// Gas price we agree to pay. Only used for legacy transactions.
 	pub gas_price: U256,
+	/// Maximal total fee per gas we agree to pay for EIP-1559 transactions. If `None`, it is
+	/// estimated using fee history of recent blocks.
+	pub max_fee_per_gas: Option<U256>,
+	/// Maximal priority fee per gas we agree to pay for EIP-1559 transactions. If `None`, it is
+	/// estimated using fee history of recent blocks.
+	pub max_priority_fee_per_gas: Option<U256>,
+	/// Submit legacy transactions even if the chain supports EIP-1559 transactions.
+	pub legacy_transactions: bool,
 }
 
 impl Default for SigningParams {
@@ -43,11 +59,93 @@ impl Default for SigningParams {
 			))
 			.expect("secret is hardcoded, thus valid; qed"),
 			gas_price: 8_000_000_000u64.into(), // 8 Gwei
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
+			legacy_transactions: false,
 		}
 	}
 }
 
+/// Fees of EIP-1559 transaction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Eip1559Fees {
+	/// Maximal total fee per gas.
+	pub max_fee_per_gas: U256,
+	/// Maximal priority fee per gas.
+	pub max_priority_fee_per_gas: U256,
+}
+
+impl Eip1559Fees {
+	/// Estimate transaction fees using fee history of recent blocks. Fees that are set in signing
+	/// params are not estimated.
+	///
+	/// Returns `None` if the chain doesn't support EIP-1559 transactions.
+	pub fn from_fee_history(fee_history: &FeeHistory, params: &SigningParams) -> Option<Self> {
+		// the last base fee in the history is the base fee of the next block
+		let next_base_fee_per_gas = *fee_history.base_fee_per_gas.last()?;
+		if next_base_fee_per_gas.is_zero() {
+			return None;
+		}
+
+		let max_priority_fee_per_gas = params.max_priority_fee_per_gas.unwrap_or_else(|| {
+			let mut priority_fees = fee_history
+				.reward
+				.iter()
+				.flatten()
+				.filter_map(|block_rewards| block_rewards.first().copied())
+				.filter(|priority_fee| !priority_fee.is_zero())
+				.collect::<Vec<_>>();
+			priority_fees.sort();
+			priority_fees
+				.get(priority_fees.len() / 2)
+				.copied()
+				.unwrap_or_else(|| DEFAULT_PRIORITY_FEE_PER_GAS.into())
+		});
+		// base fee may grow by 12.5% per block => doubled base fee stays valid for at least 6 blocks
+		let max_fee_per_gas = params.max_fee_per_gas.unwrap_or_else(|| {
+			next_base_fee_per_gas
+				.saturating_mul(2.into())
+				.saturating_add(max_priority_fee_per_gas)
+		});
+
+		Some(Eip1559Fees {
+			max_fee_per_gas,
+			max_priority_fee_per_gas: std::cmp::min(max_priority_fee_per_gas, max_fee_per_gas),
+		})
+	}
+}
+
+/// Estimate EIP-1559 transaction fees.
+///
+/// Returns `None` if legacy transactions must be used, either because they're forced by signing
+/// params, or because the chain doesn't support EIP-1559 transactions.
+pub async fn estimate_eip1559_fees(client: &Client, params: &SigningParams) -> Result<Option<Eip1559Fees>> {
+	if params.legacy_transactions {
+		return Ok(None);
+	}
+
+	let fee_history = match client
+		.fee_history(FEE_HISTORY_BLOCKS, vec![PRIORITY_FEE_PERCENTILE])
+		.await
+	{
+		Ok(fee_history) => fee_history,
+		Err(error) if !error.is_connection_error() => {
+			log::debug!(
+				target: "bridge",
+				"Failed to read fee history from Ethereum node: {:?}. Falling back to legacy transactions",
+				error,
+			);
+			return Ok(None);
+		}
+		Err(error) => return Err(error),
+	};
+
+	Ok(Eip1559Fees::from_fee_history(&fee_history, params))
+}
+
 /// Sign and submit tranaction using given Ethereum client.
+///
+/// EIP-1559 transaction is submitted if the chain supports it, unless legacy transactions are forced.
 pub async fn sign_and_submit_transaction(
 	client: &Client,
 	params: &SigningParams,
@@ -69,17 +167,96 @@ pub async fn sign_and_submit_transaction(
 		..Default::default()
 	};
 	let gas = client.estimate_gas(call_request).await?;
+	let gas = if double_gas { gas.saturating_mul(2.into()) } else { gas };
 
-	let raw_transaction = bp_eth_poa::UnsignedTransaction {
-		nonce,
-		to: contract_address,
-		value: U256::zero(),
-		gas: if double_gas { gas.saturating_mul(2.into()) } else { gas },
-		gas_price: params.gas_price,
-		payload: encoded_call,
-	}
-	.sign_by(&params.signer, Some(params.chain_id));
+	let raw_transaction = match estimate_eip1559_fees(client, params).await? {
+		Some(fees) => bp_eth_poa::UnsignedEip1559Transaction {
+			nonce,
+			max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+			max_fee_per_gas: fees.max_fee_per_gas,
+			gas,
+			to: contract_address,
+			value: U256::zero(),
+			payload: encoded_call,
+		}
+		.sign_by(&params.signer, Some(params.chain_id)),
+		None => bp_eth_poa::UnsignedTransaction {
+			nonce,
+			to: contract_address,
+			value: U256::zero(),
+			gas,
+			gas_price: params.gas_price,
+			payload: encoded_call,
+		}
+		.sign_by(&params.signer, Some(params.chain_id)),
+	};
 
 	let _ = client.submit_transaction(raw_transaction).await?;
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fee_history(base_fee_per_gas: Vec<u64>, rewards: Vec<u64>) -> FeeHistory {
+		FeeHistory {
+			base_fee_per_gas: base_fee_per_gas.into_iter().map(Into::into).collect(),
+			reward: Some(rewards.into_iter().map(|reward| vec![reward.into()]).collect()),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn eip1559_fees_are_estimated_from_fee_history() {
+		assert_eq!(
+			Eip1559Fees::from_fee_history(
+				&fee_history(vec![100, 110, 120], vec![5, 0, 3, 7]),
+				&SigningParams::default()
+			),
+			Some(Eip1559Fees {
+				max_fee_per_gas: 245.into(),
+				max_priority_fee_per_gas: 5.into(),
+			}),
+		);
+	}
+
+	#[test]
+	fn default_priority_fee_is_used_when_there_are_no_transactions() {
+		assert_eq!(
+			Eip1559Fees::from_fee_history(&fee_history(vec![100], vec![0, 0]), &SigningParams::default()),
+			Some(Eip1559Fees {
+				max_fee_per_gas: U256::from(200 + DEFAULT_PRIORITY_FEE_PER_GAS),
+				max_priority_fee_per_gas: DEFAULT_PRIORITY_FEE_PER_GAS.into(),
+			}),
+		);
+	}
+
+	#[test]
+	fn eip1559_fees_from_signing_params_are_used() {
+		let params = SigningParams {
+			max_fee_per_gas: Some(150.into()),
+			max_priority_fee_per_gas: Some(200.into()),
+			..Default::default()
+		};
+		assert_eq!(
+			Eip1559Fees::from_fee_history(&fee_history(vec![100], vec![5]), &params),
+			Some(Eip1559Fees {
+				max_fee_per_gas: 150.into(),
+				max_priority_fee_per_gas: 150.into(),
+			}),
+		);
+	}
+
+	#[test]
+	fn legacy_transactions_are_used_if_chain_has_no_base_fee() {
+		assert_eq!(
+			Eip1559Fees::from_fee_history(&fee_history(vec![], vec![]), &SigningParams::default()),
+			None,
+		);
+		assert_eq!(
+			Eip1559Fees::from_fee_history(&fee_history(vec![0, 0], vec![5]), &SigningParams::default()),
+			None,
+		);
+	}
+}
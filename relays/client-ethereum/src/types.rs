@@ -17,8 +17,11 @@
 //! Common types that are used in relay <-> Ethereum node communications.
 
 use headers_relay::sync_types::SourceHeader;
+use serde::Deserialize;
 
-pub use web3::types::{Address, Bytes, CallRequest, SyncState, H256, U128, U256, U64};
+pub use web3::types::{
+	Address, BlockHeader, BlockNumber, Bytes, CallRequest, Filter, Log, SyncState, H256, U128, U256, U64,
+};
 
 /// When header is just received from the Ethereum node, we check that it has
 /// both number and hash fields filled.
@@ -60,6 +63,23 @@ pub type HeaderId = relay_utils::HeaderId<H256, u64>;
 /// A raw Ethereum transaction that's been signed.
 pub type SignedRawTx = Vec<u8>;
 
+/// Fee history of recent blocks, returned by the `eth_feeHistory` call.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+	/// Number of the oldest block in the history.
+	pub oldest_block: U256,
+	/// Base fees per gas of blocks in the history. It also includes base fee of the block that
+	/// follows the newest block in the history.
+	#[serde(default)]
+	pub base_fee_per_gas: Vec<U256>,
+	/// Ratios of used gas to gas limit of blocks in the history.
+	#[serde(default)]
+	pub gas_used_ratio: Vec<f64>,
+	/// Requested percentiles of priority fees per gas, paid in blocks of the history.
+	pub reward: Option<Vec<Vec<U256>>>,
+}
+
 impl From<Header> for SyncHeader {
 	fn from(header: Header) -> Self {
 		Self(header)
@@ -17,8 +17,11 @@
 //! Types used to connect to the Millau-Substrate chain.
 
 use codec::Encode;
-use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, TransactionSignScheme};
-use sp_core::{storage::StorageKey, Pair};
+use relay_substrate_client::{
+	Chain, ChainBase, ChainWithBalances, ChainWithGrandpa, MultiSignerKeyPair, TransactionSignScheme,
+};
+use sp_core::{storage::StorageKey, Bytes};
+use sp_finality_grandpa::{EquivocationProof, OpaqueKeyOwnershipProof};
 use sp_runtime::{generic::SignedPayload, traits::IdentifyAccount};
 use std::time::Duration;
 
@@ -56,16 +59,33 @@ impl ChainWithBalances for Millau {
 	}
 }
 
+impl ChainWithGrandpa for Millau {
+	const GRANDPA_PALLET_NAME: &'static str = "Grandpa";
+
+	fn encode_report_equivocation_transaction(
+		equivocation_proof: EquivocationProof<Self::Hash, Self::BlockNumber>,
+		key_owner_proof: OpaqueKeyOwnershipProof,
+	) -> Option<Bytes> {
+		let call: millau_runtime::Call = millau_runtime::GrandpaCall::report_equivocation_unsigned(
+			Box::new(equivocation_proof),
+			key_owner_proof.decode()?,
+		)
+		.into();
+		Some(Bytes(millau_runtime::UncheckedExtrinsic::new_unsigned(call).encode()))
+	}
+}
+
 impl TransactionSignScheme for Millau {
 	type Chain = Millau;
 	type AccountKeyPair = sp_core::sr25519::Pair;
 	type SignedTransaction = millau_runtime::UncheckedExtrinsic;
 
-	fn sign_transaction(
+	fn sign_transaction_with_tip(
 		genesis_hash: <Self::Chain as ChainBase>::Hash,
 		signer: &Self::AccountKeyPair,
 		signer_nonce: <Self::Chain as Chain>::Index,
 		call: <Self::Chain as Chain>::Call,
+		tip: <Self::Chain as Chain>::Balance,
 	) -> Self::SignedTransaction {
 		let raw_payload = SignedPayload::from_raw(
 			call,
@@ -76,7 +96,9 @@ impl TransactionSignScheme for Millau {
 				frame_system::CheckEra::<millau_runtime::Runtime>::from(sp_runtime::generic::Era::Immortal),
 				frame_system::CheckNonce::<millau_runtime::Runtime>::from(signer_nonce),
 				frame_system::CheckWeight::<millau_runtime::Runtime>::new(),
-				pallet_transaction_payment::ChargeTransactionPayment::<millau_runtime::Runtime>::from(0),
+				pallet_transaction_payment::ChargeTransactionPayment::<millau_runtime::Runtime>::from(tip),
+				millau_runtime::RefundConfirmationTransactionFee::new(),
+				millau_runtime::RefundDeliveryTransactionFee::new(),
 			),
 			(
 				millau_runtime::VERSION.spec_version,
@@ -86,10 +108,12 @@ impl TransactionSignScheme for Millau {
 				(),
 				(),
 				(),
+				(),
+				(),
 			),
 		);
-		let signature = raw_payload.using_encoded(|payload| signer.sign(payload));
-		let signer: sp_runtime::MultiSigner = signer.public().into();
+		let signature = raw_payload.using_encoded(|payload| signer.multi_sign(payload));
+		let signer = signer.multi_signer();
 		let (call, extra, _) = raw_payload.deconstruct();
 
 		millau_runtime::UncheckedExtrinsic::new_signed(call, signer.into_account(), signature.into(), extra)
@@ -18,13 +18,15 @@ use bp_runtime::Chain as ChainBase;
 use frame_support::Parameter;
 use jsonrpsee_ws_client::{DeserializeOwned, Serialize};
 use num_traits::{CheckedSub, Zero};
-use sp_core::{storage::StorageKey, Pair};
+use sp_core::{storage::StorageKey, Bytes, Pair};
+use sp_finality_grandpa::{EquivocationProof, OpaqueKeyOwnershipProof};
 use sp_runtime::{
 	generic::SignedBlock,
 	traits::{
-		AtLeast32Bit, Block as BlockT, Dispatchable, MaybeDisplay, MaybeSerialize, MaybeSerializeDeserialize, Member,
+		AtLeast32Bit, Block as BlockT, Dispatchable, IdentifyAccount, MaybeDisplay, MaybeSerialize,
+		MaybeSerializeDeserialize, Member,
 	},
-	EncodedJustification,
+	AccountId32, ConsensusEngineId, EncodedJustification, FixedPointOperand, MultiSignature, MultiSigner,
 };
 use std::{fmt::Debug, time::Duration};
 
@@ -58,7 +60,15 @@ pub trait Chain: ChainBase + Clone {
 	///
 	/// The chain may suport multiple tokens, but this particular type is for token that is used
 	/// to pay for transaction dispatch, to reward different relayers (headers, messages), etc.
-	type Balance: Parameter + Member + DeserializeOwned + Clone + Copy + CheckedSub + PartialOrd + Zero;
+	type Balance: Parameter
+		+ Member
+		+ DeserializeOwned
+		+ Clone
+		+ Copy
+		+ CheckedSub
+		+ PartialOrd
+		+ Zero
+		+ FixedPointOperand;
 }
 
 /// Substrate-based chain with `frame_system::Config::AccountData` set to
@@ -68,12 +78,26 @@ pub trait ChainWithBalances: Chain {
 	fn account_info_storage_key(account_id: &Self::AccountId) -> StorageKey;
 }
 
+/// Substrate-based chain with GRANDPA pallet that accepts unsigned equivocation reports.
+pub trait ChainWithGrandpa: Chain {
+	/// Name of the GRANDPA pallet in the `construct_runtime!()` call.
+	const GRANDPA_PALLET_NAME: &'static str;
+
+	/// Encode unsigned `report_equivocation_unsigned` transaction of the GRANDPA pallet.
+	///
+	/// Returns `None` if the opaque key ownership proof can't be decoded into the runtime type.
+	fn encode_report_equivocation_transaction(
+		equivocation_proof: EquivocationProof<Self::Hash, Self::BlockNumber>,
+		key_owner_proof: OpaqueKeyOwnershipProof,
+	) -> Option<Bytes>;
+}
+
 /// Block with justification.
 pub trait BlockWithJustification<Header> {
 	/// Return block header.
 	fn header(&self) -> Header;
-	/// Return block justification, if known.
-	fn justification(&self) -> Option<&EncodedJustification>;
+	/// Return block justification of given finality engine, if known.
+	fn justification(&self, engine_id: ConsensusEngineId) -> Option<&EncodedJustification>;
 }
 
 /// Substrate-based chain transactions signing scheme.
@@ -91,17 +115,85 @@ pub trait TransactionSignScheme {
 		signer: &Self::AccountKeyPair,
 		signer_nonce: <Self::Chain as Chain>::Index,
 		call: <Self::Chain as Chain>::Call,
+	) -> Self::SignedTransaction {
+		Self::sign_transaction_with_tip(genesis_hash, signer, signer_nonce, call, Zero::zero())
+	}
+
+	/// Create transaction for given runtime call, signed by given account and paying given tip.
+	fn sign_transaction_with_tip(
+		genesis_hash: <Self::Chain as ChainBase>::Hash,
+		signer: &Self::AccountKeyPair,
+		signer_nonce: <Self::Chain as Chain>::Index,
+		call: <Self::Chain as Chain>::Call,
+		tip: <Self::Chain as Chain>::Balance,
 	) -> Self::SignedTransaction;
 }
 
+/// Key pair of any signature scheme that is supported by the `MultiSigner`/`MultiSignature`
+/// (sr25519, ed25519 or ECDSA).
+///
+/// Transactions of chains that are using `MultiSignature` may be signed by any of such key pairs.
+pub trait MultiSignerKeyPair: Pair {
+	/// Return signer that corresponds to this key pair.
+	fn multi_signer(&self) -> MultiSigner;
+	/// Sign given message.
+	fn multi_sign(&self, message: &[u8]) -> MultiSignature;
+
+	/// Return id of the account that is controlled by this key pair.
+	fn account_id(&self) -> AccountId32 {
+		self.multi_signer().into_account()
+	}
+}
+
+impl<P> MultiSignerKeyPair for P
+where
+	P: Pair,
+	P::Public: Into<MultiSigner>,
+	P::Signature: Into<MultiSignature>,
+{
+	fn multi_signer(&self) -> MultiSigner {
+		self.public().into()
+	}
+
+	fn multi_sign(&self, message: &[u8]) -> MultiSignature {
+		self.sign(message).into()
+	}
+}
+
 impl<Block: BlockT> BlockWithJustification<Block::Header> for SignedBlock<Block> {
 	fn header(&self) -> Block::Header {
 		self.block.header().clone()
 	}
 
-	fn justification(&self) -> Option<&EncodedJustification> {
-		self.justifications
-			.as_ref()
-			.and_then(|j| j.get(sp_finality_grandpa::GRANDPA_ENGINE_ID))
+	fn justification(&self, engine_id: ConsensusEngineId) -> Option<&EncodedJustification> {
+		self.justifications.as_ref().and_then(|j| j.get(engine_id))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::Verify;
+
+	fn ensure_signature_is_valid<P: MultiSignerKeyPair>(pair: P) {
+		let signature = pair.multi_sign(b"transaction");
+		assert!(signature.verify(&b"transaction"[..], &pair.account_id()));
+		assert!(!signature.verify(&b"other transaction"[..], &pair.account_id()));
+	}
+
+	#[test]
+	fn transactions_may_be_signed_with_any_supported_key_pair() {
+		ensure_signature_is_valid(sp_core::sr25519::Pair::from_string("//Alice", None).unwrap());
+		ensure_signature_is_valid(sp_core::ed25519::Pair::from_string("//Alice", None).unwrap());
+		ensure_signature_is_valid(sp_core::ecdsa::Pair::from_string("//Alice", None).unwrap());
+	}
+
+	#[test]
+	fn ecdsa_account_id_is_blake2_hash_of_public_key() {
+		let pair = sp_core::ecdsa::Pair::from_string("//Alice", None).unwrap();
+		assert_eq!(
+			pair.account_id(),
+			AccountId32::from(sp_core::blake2_256(pair.public().as_ref())),
+		);
 	}
 }
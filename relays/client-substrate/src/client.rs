@@ -17,27 +17,43 @@
 //! Substrate node client.
 
 use crate::chain::{Chain, ChainWithBalances};
+use crate::finality_engine::{FinalityEngine, Grandpa};
 use crate::rpc::Substrate;
+use crate::storage_proof_cache::StorageProofCache;
 use crate::{ConnectionParams, Error, Result};
 
 use async_std::sync::{Arc, Mutex};
-use codec::Decode;
+use codec::{Decode, Encode};
 use frame_system::AccountInfo;
 use jsonrpsee_ws_client::{traits::SubscriptionClient, v2::params::JsonRpcParams, DeserializeOwned};
 use jsonrpsee_ws_client::{Subscription, WsClient as RpcClient, WsClientBuilder as RpcClientBuilder};
 use num_traits::Zero;
 use pallet_balances::AccountData;
-use relay_utils::relay_loop::RECONNECT_DELAY;
-use sp_core::{storage::StorageKey, Bytes};
+use relay_utils::{relay_loop::RECONNECT_DELAY, MaybeConnectionError};
+use sp_core::{
+	storage::{StorageChangeSet, StorageData, StorageKey},
+	Bytes,
+};
+use sp_runtime::{
+	traits::Header as HeaderT,
+	transaction_validity::{TransactionSource, TransactionValidity},
+	ApplyExtrinsicResult, FixedPointNumber, FixedU128,
+};
 use sp_trie::StorageProof;
 use sp_version::RuntimeVersion;
 
 const SUB_API_GRANDPA_AUTHORITIES: &str = "GrandpaApi_grandpa_authorities";
+const SUB_API_VALIDATE_TRANSACTION: &str = "TaggedTransactionQueue_validate_transaction";
 const MAX_SUBSCRIPTION_CAPACITY: usize = 4096;
+/// Number of storage keys that are requested from the node by single `state_getKeysPaged` call.
+const STORAGE_KEYS_PAGE_SIZE: u32 = 1024;
 
 /// Opaque justifications subscription type.
 pub type JustificationsSubscription = Subscription<Bytes>;
 
+/// Storage changes subscription type.
+pub type StorageChangesSubscription<Hash> = Subscription<StorageChangeSet<Hash>>;
+
 /// Opaque GRANDPA authorities set.
 pub type OpaqueGrandpaAuthoritiesSet = Vec<u8>;
 
@@ -55,6 +71,8 @@ pub struct Client<C: Chain> {
 	/// method, they may get the same transaction nonce. So one of transactions will be rejected
 	/// from the pool. This lock is here to prevent situations like that.
 	submit_signed_extrinsic_lock: Arc<Mutex<()>>,
+	/// Trie nodes of recent storage proofs, generated by `prove_storage_with_cache`.
+	storage_proof_cache: Arc<Mutex<StorageProofCache<C::Hasher>>>,
 }
 
 impl<C: Chain> Clone for Client<C> {
@@ -64,6 +82,7 @@ impl<C: Chain> Clone for Client<C> {
 			client: self.client.clone(),
 			genesis_hash: self.genesis_hash,
 			submit_signed_extrinsic_lock: self.submit_signed_extrinsic_lock.clone(),
+			storage_proof_cache: self.storage_proof_cache.clone(),
 		}
 	}
 }
@@ -104,13 +123,16 @@ impl<C: Chain> Client<C> {
 		let client = Self::build_client(params.clone()).await?;
 
 		let number: C::BlockNumber = Zero::zero();
-		let genesis_hash = Substrate::<C>::chain_get_block_hash(&*client, number).await?;
+		let genesis_hash = Substrate::<C>::chain_get_block_hash(&*client, number)
+			.await?
+			.ok_or_else(|| Error::MissingBlock(format!("#{:?}", number)))?;
 
 		Ok(Self {
 			params,
 			client,
 			genesis_hash,
 			submit_signed_extrinsic_lock: Arc::new(Mutex::new(())),
+			storage_proof_cache: Arc::new(Mutex::new(StorageProofCache::default())),
 		})
 	}
 
@@ -169,7 +191,15 @@ impl<C: Chain> Client<C> {
 
 	/// Get a Substrate block from its hash.
 	pub async fn get_block(&self, block_hash: Option<C::Hash>) -> Result<C::SignedBlock> {
-		Ok(Substrate::<C>::chain_get_block(&*self.client, block_hash).await?)
+		Substrate::<C>::chain_get_block(&*self.client, block_hash)
+			.await?
+			.ok_or_else(|| {
+				Error::MissingBlock(
+					block_hash
+						.map(|hash| format!("{:?}", hash))
+						.unwrap_or_else(|| "<best>".into()),
+				)
+			})
 	}
 
 	/// Get a Substrate header by its hash.
@@ -182,7 +212,9 @@ impl<C: Chain> Client<C> {
 
 	/// Get a Substrate block hash by its number.
 	pub async fn block_hash_by_number(&self, number: C::BlockNumber) -> Result<C::Hash> {
-		Ok(Substrate::<C>::chain_get_block_hash(&*self.client, number).await?)
+		Substrate::<C>::chain_get_block_hash(&*self.client, number)
+			.await?
+			.ok_or_else(|| Error::MissingBlock(format!("#{:?}", number)))
 	}
 
 	/// Get a Substrate header by its number.
@@ -201,19 +233,70 @@ impl<C: Chain> Client<C> {
 
 	/// Read value from runtime storage.
 	pub async fn storage_value<T: Decode>(&self, storage_key: StorageKey) -> Result<Option<T>> {
-		Substrate::<C>::state_get_storage(&*self.client, storage_key)
+		Substrate::<C>::state_get_storage(&*self.client, storage_key, None)
+			.await?
+			.map(|encoded_value| T::decode(&mut &encoded_value.0[..]).map_err(Error::ResponseParseFailed))
+			.transpose()
+	}
+
+	/// Read value from runtime storage at given block.
+	pub async fn storage_value_at<T: Decode>(&self, storage_key: StorageKey, at_block: C::Hash) -> Result<Option<T>> {
+		Substrate::<C>::state_get_storage(&*self.client, storage_key, Some(at_block))
 			.await?
 			.map(|encoded_value| T::decode(&mut &encoded_value.0[..]).map_err(Error::ResponseParseFailed))
 			.transpose()
 	}
 
+	/// Read all key-value pairs with given key prefix from runtime storage at given block.
+	///
+	/// Keys are read page by page, so this may be used to read large storage maps.
+	pub async fn storage_pairs_at(
+		&self,
+		prefix: StorageKey,
+		at_block: C::Hash,
+	) -> Result<Vec<(StorageKey, StorageData)>> {
+		let mut pairs = Vec::new();
+		let mut start_key = None;
+		loop {
+			let keys = Substrate::<C>::state_get_keys_paged(
+				&*self.client,
+				prefix.clone(),
+				STORAGE_KEYS_PAGE_SIZE,
+				start_key,
+				Some(at_block),
+			)
+			.await?;
+			let is_last_page = keys.len() < STORAGE_KEYS_PAGE_SIZE as usize;
+			start_key = keys.last().cloned();
+
+			for key in keys {
+				let value = Substrate::<C>::state_get_storage(&*self.client, key.clone(), Some(at_block)).await?;
+				if let Some(value) = value {
+					pairs.push((key, value));
+				}
+			}
+
+			if is_last_page {
+				return Ok(pairs);
+			}
+		}
+	}
+
+	/// Return fee multiplier that will be used to compute fees of transactions in the next block.
+	pub async fn next_fee_multiplier(&self) -> Result<FixedU128> {
+		Ok(self
+			.storage_value(crate::tip_escalation::next_fee_multiplier_key())
+			.await?
+			.unwrap_or_else(FixedU128::one))
+	}
+
 	/// Return native tokens balance of the account.
 	pub async fn free_native_balance(&self, account: C::AccountId) -> Result<C::Balance>
 	where
 		C: ChainWithBalances,
 	{
 		let storage_key = C::account_info_storage_key(&account);
-		let encoded_account_data = Substrate::<C>::state_get_storage(&*self.client, storage_key)
+		let encoded_account_data = Substrate::<C>::state_get_storage(&*self.client, storage_key, None)
 			.await?
 			.ok_or(Error::AccountDoesNotExist)?;
 		let decoded_account_data =
@@ -244,6 +327,9 @@ impl<C: Chain> Client<C> {
 	/// `submit_signed_extrinsic()` call. This guarantees that no nonces collision may happen
 	/// if all client instances are clones of the same initial `Client`.
 	///
+	/// Before submission, the extrinsic is simulated (see `simulate_extrinsic`) and it isn't
+	/// submitted if the simulation fails.
+	///
 	/// Note: The given transaction needs to be SCALE encoded beforehand.
 	pub async fn submit_signed_extrinsic(
 		&self,
@@ -253,11 +339,79 @@ impl<C: Chain> Client<C> {
 		let _guard = self.submit_signed_extrinsic_lock.lock().await;
 		let transaction_nonce = self.next_account_index(extrinsic_signer).await?;
 		let extrinsic = prepare_extrinsic(transaction_nonce);
+		if let Err(error) = self.simulate_extrinsic(extrinsic.clone()).await {
+			log::error!(
+				target: "bridge",
+				"Transaction simulation at {} node has failed: {}. Transaction is not submitted",
+				C::NAME,
+				error,
+			);
+			return Err(error);
+		}
 		let tx_hash = Substrate::<C>::author_submit_extrinsic(&*self.client, extrinsic).await?;
 		log::trace!(target: "bridge", "Sent transaction to {} node: {:?}", C::NAME, tx_hash);
 		Ok(tx_hash)
 	}
 
+	/// Check that the extrinsic would be accepted by the node and its dispatch would succeed if it
+	/// is submitted right now.
+	///
+	/// The `system_dryRun` RPC is used if the node supports it. Since it is an unsafe RPC, it may be
+	/// disabled. Then the extrinsic is only validated using `TaggedTransactionQueue_validate_transaction`
+	/// runtime API, which doesn't detect dispatch errors. If both methods are unavailable, the
+	/// extrinsic is assumed to be valid.
+	///
+	/// Note: The given transaction needs to be SCALE encoded beforehand.
+	pub async fn simulate_extrinsic(&self, extrinsic: Bytes) -> Result<()> {
+		match Substrate::<C>::system_dry_run(&*self.client, extrinsic.clone(), None).await {
+			Ok(encoded_result) => {
+				let result: ApplyExtrinsicResult =
+					Decode::decode(&mut &encoded_result.0[..]).map_err(Error::ResponseParseFailed)?;
+				return match result {
+					Ok(Ok(())) => Ok(()),
+					Ok(Err(dispatch_error)) => Err(Error::TransactionDispatchFailed(dispatch_error)),
+					Err(validity_error) => Err(Error::TransactionInvalid(validity_error)),
+				};
+			}
+			Err(error) => {
+				let error = Error::from(error);
+				if error.is_connection_error() {
+					return Err(error);
+				}
+
+				log::debug!(
+					target: "bridge",
+					"Failed to dry-run transaction at {} node: {}. Falling back to transaction validation",
+					C::NAME,
+					error,
+				);
+			}
+		}
+
+		let mut data = TransactionSource::External.encode();
+		data.extend(extrinsic.0);
+		match self
+			.state_call(SUB_API_VALIDATE_TRANSACTION.into(), Bytes(data), None)
+			.await
+		{
+			Ok(encoded_result) => {
+				let result: TransactionValidity =
+					Decode::decode(&mut &encoded_result.0[..]).map_err(Error::ResponseParseFailed)?;
+				result.map(drop).map_err(Error::TransactionInvalid)
+			}
+			Err(error) if error.is_connection_error() => Err(error),
+			Err(error) => {
+				log::debug!(
+					target: "bridge",
+					"Failed to validate transaction at {} node: {}. Assuming it is valid",
+					C::NAME,
+					error,
+				);
+				Ok(())
+			}
+		}
+	}
+
 	/// Get the GRANDPA authority set at given block.
 	pub async fn grandpa_authorities_set(&self, block: C::Hash) -> Result<OpaqueGrandpaAuthoritiesSet> {
 		let call = SUB_API_GRANDPA_AUTHORITIES.to_string();
@@ -284,14 +438,60 @@ impl<C: Chain> Client<C> {
 			.map_err(Into::into)
 	}
 
-	/// Return new justifications stream.
+	/// Returns storage proof of given storage keys, reusing trie nodes of previous proofs, generated
+	/// at the same block.
+	///
+	/// Only keys that can't be proved using cached trie nodes are proved by the node.
+	pub async fn prove_storage_with_cache(&self, keys: Vec<StorageKey>, at_block: C::Hash) -> Result<StorageProof>
+	where
+		C::Header: DeserializeOwned,
+	{
+		let mut storage_proof_cache = self.storage_proof_cache.lock().await;
+		let state_root = match storage_proof_cache.state_root(&at_block) {
+			Some(state_root) => state_root,
+			None => *self.header_by_hash(at_block).await?.state_root(),
+		};
+
+		let missing_keys = storage_proof_cache.missing_keys(&state_root, &keys);
+		if !missing_keys.is_empty() {
+			let missing_keys_proof = self.prove_storage(missing_keys, at_block).await?;
+			storage_proof_cache.insert(at_block, state_root, missing_keys_proof);
+		}
+
+		storage_proof_cache.prove(&state_root, &keys).ok_or_else(|| {
+			Error::Custom(format!(
+				"Failed to generate {} storage proof at {:?} using cached trie nodes",
+				C::NAME,
+				at_block,
+			))
+		})
+	}
+
+	/// Return new GRANDPA justifications stream.
 	pub async fn subscribe_justifications(&self) -> Result<JustificationsSubscription> {
+		self.subscribe_finality_proofs::<Grandpa>().await
+	}
+
+	/// Return new stream of finality proofs, generated by given finality engine.
+	pub async fn subscribe_finality_proofs<E: FinalityEngine<C::Header>>(&self) -> Result<JustificationsSubscription> {
+		Ok(self
+			.client
+			.subscribe(E::SUBSCRIBE_METHOD, JsonRpcParams::NoParams, E::UNSUBSCRIBE_METHOD)
+			.await?)
+	}
+
+	/// Return new stream of changes of given storage keys.
+	///
+	/// The first item of the stream contains values of all keys at the current best block. Every
+	/// next item is generated when some of keys are changed by the new best block.
+	pub async fn subscribe_storage(&self, keys: Vec<StorageKey>) -> Result<StorageChangesSubscription<C::Hash>> {
+		let keys = serde_json::to_value(keys).map_err(|e| Error::Custom(format!("Failed to encode keys: {}", e)))?;
 		Ok(self
 			.client
 			.subscribe(
-				"grandpa_subscribeJustifications",
-				JsonRpcParams::NoParams,
-				"grandpa_unsubscribeJustifications",
+				"state_subscribeStorage",
+				JsonRpcParams::Array(vec![keys]),
+				"state_unsubscribeStorage",
 			)
 			.await?)
 	}
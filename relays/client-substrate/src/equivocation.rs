@@ -0,0 +1,434 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! GRANDPA equivocations detector.
+//!
+//! The finality relay is watching all justifications of the source chain. Apart from submitting
+//! them to the target chain, it may also cross-check them. If two justifications of the same
+//! GRANDPA round contain precommits of the same authority for different blocks, then this authority
+//! has equivocated. The detector reports such equivocations to the source chain, so that the
+//! offender may be punished there.
+
+use crate::chain::ChainWithGrandpa;
+use crate::client::Client;
+use crate::error::{Error, Result};
+
+use bp_header_chain::justification::GrandpaJustification;
+use codec::{Decode, Encode};
+use jsonrpsee_ws_client::DeserializeOwned;
+use relay_utils::relay_loop::RECONNECT_DELAY;
+use sp_core::{storage::StorageKey, Bytes};
+use sp_finality_grandpa::{
+	AuthorityId, AuthoritySignature, Equivocation, EquivocationProof, OpaqueKeyOwnershipProof, SetId,
+};
+use sp_runtime::traits::Header as HeaderT;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Name of the runtime method that generates key ownership proof of the GRANDPA authority.
+const SUB_API_GRANDPA_GENERATE_KEY_OWNERSHIP_PROOF: &str = "GrandpaApi_generate_key_ownership_proof";
+/// Maximal number of recent justifications that are used to cross-check new justifications.
+///
+/// Conflicting justifications of the same round are expected to be seen close to each other, so
+/// there's no need to keep a lot of them.
+const RECENT_JUSTIFICATIONS_LIMIT: usize = 128;
+
+/// Equivocation of GRANDPA authority that has signed two precommits in the same round.
+pub type GrandpaPrecommitEquivocation<Header> = finality_grandpa::Equivocation<
+	AuthorityId,
+	finality_grandpa::Precommit<<Header as HeaderT>::Hash, <Header as HeaderT>::Number>,
+	AuthoritySignature,
+>;
+
+/// Cross-checks GRANDPA justifications and finds equivocations.
+#[derive(Debug)]
+pub struct EquivocationsDetector<Header: HeaderT> {
+	/// Recent justifications along with ids of authorities sets that have signed them.
+	recent_justifications: VecDeque<(SetId, GrandpaJustification<Header>)>,
+}
+
+impl<Header: HeaderT> Default for EquivocationsDetector<Header> {
+	fn default() -> Self {
+		EquivocationsDetector {
+			recent_justifications: VecDeque::new(),
+		}
+	}
+}
+
+impl<Header: HeaderT> EquivocationsDetector<Header> {
+	/// Remember justification, signed by given authorities set, and return proofs of all
+	/// equivocations that are found in this justification or in this and any recent justification.
+	///
+	/// Every offender is reported at most once per call, but the same equivocation may be returned
+	/// again if it is proved by some other justification. The source chain rejects duplicate reports.
+	pub fn note_justification(
+		&mut self,
+		set_id: SetId,
+		justification: GrandpaJustification<Header>,
+	) -> Vec<EquivocationProof<Header::Hash, Header::Number>> {
+		// the justification itself may contain several precommits of the same authority
+		let mut equivocations = find_equivocations(&justification, &justification);
+		for (recent_set_id, recent_justification) in &self.recent_justifications {
+			if *recent_set_id == set_id {
+				equivocations.extend(find_equivocations(recent_justification, &justification));
+			}
+		}
+
+		self.recent_justifications.push_back((set_id, justification));
+		while self.recent_justifications.len() > RECENT_JUSTIFICATIONS_LIMIT {
+			self.recent_justifications.pop_front();
+		}
+
+		let mut offenders = BTreeSet::new();
+		equivocations
+			.into_iter()
+			.filter(|equivocation| offenders.insert(equivocation.identity.clone()))
+			.map(|equivocation| EquivocationProof::new(set_id, Equivocation::Precommit(equivocation)))
+			.collect()
+	}
+}
+
+/// Find authorities that have precommitted for different blocks in given justifications.
+///
+/// Justifications are expected to be signed by the same authorities set. Justifications of
+/// different rounds may not contain equivocations.
+pub fn find_equivocations<Header: HeaderT>(
+	first: &GrandpaJustification<Header>,
+	second: &GrandpaJustification<Header>,
+) -> Vec<GrandpaPrecommitEquivocation<Header>> {
+	if first.round != second.round {
+		return Vec::new();
+	}
+
+	let first_precommits = first
+		.commit
+		.precommits
+		.iter()
+		.map(|signed| (&signed.id, signed))
+		.collect::<BTreeMap<_, _>>();
+
+	let mut offenders = BTreeSet::new();
+	let mut equivocations = Vec::new();
+	for second_precommit in &second.commit.precommits {
+		let first_precommit = match first_precommits.get(&second_precommit.id) {
+			Some(first_precommit) => first_precommit,
+			None => continue,
+		};
+		if first_precommit.precommit.target_hash == second_precommit.precommit.target_hash {
+			continue;
+		}
+		if !offenders.insert(&second_precommit.id) {
+			continue;
+		}
+
+		equivocations.push(finality_grandpa::Equivocation {
+			round_number: first.round,
+			identity: second_precommit.id.clone(),
+			first: (first_precommit.precommit.clone(), first_precommit.signature.clone()),
+			second: (second_precommit.precommit.clone(), second_precommit.signature.clone()),
+		});
+	}
+
+	equivocations
+}
+
+/// Start background task that watches justifications of the chain and reports GRANDPA
+/// equivocations to the same chain.
+pub fn start_equivocations_detector<C: ChainWithGrandpa>(mut client: Client<C>)
+where
+	C::Header: DeserializeOwned,
+{
+	async_std::task::spawn(async move {
+		let mut detector = EquivocationsDetector::default();
+
+		loop {
+			let mut subscription = match client.subscribe_justifications().await {
+				Ok(subscription) => subscription,
+				Err(error) => {
+					log::warn!(
+						target: "bridge",
+						"Failed to subscribe to {} justifications: {:?}. Going to reconnect",
+						C::NAME,
+						error,
+					);
+
+					async_std::task::sleep(RECONNECT_DELAY).await;
+					if let Err(error) = client.reconnect().await {
+						log::warn!(target: "bridge", "Failed to reconnect to {} node: {:?}", C::NAME, error);
+					}
+					continue;
+				}
+			};
+
+			while let Some(encoded_justification) = subscription.next().await {
+				let justification = match GrandpaJustification::<C::Header>::decode(&mut &encoded_justification.0[..]) {
+					Ok(justification) => justification,
+					Err(error) => {
+						log::error!(
+							target: "bridge",
+							"Failed to decode justification from the {} justifications stream: {:?}",
+							C::NAME,
+							error,
+						);
+						continue;
+					}
+				};
+
+				if let Err(error) = report_equivocations(&client, &mut detector, justification).await {
+					log::warn!(
+						target: "bridge",
+						"Failed to check {} justification for equivocations: {:?}",
+						C::NAME,
+						error,
+					);
+				}
+			}
+
+			log::warn!(
+				target: "bridge",
+				"{} justifications stream has ended. Going to resubscribe",
+				C::NAME,
+			);
+		}
+	});
+}
+
+/// Check justification for equivocations and report all found equivocations to the chain.
+async fn report_equivocations<C: ChainWithGrandpa>(
+	client: &Client<C>,
+	detector: &mut EquivocationsDetector<C::Header>,
+	justification: GrandpaJustification<C::Header>,
+) -> Result<()>
+where
+	C::Header: DeserializeOwned,
+{
+	// the justification is signed by the set that has been active at the parent of the target block
+	let target_header = client.header_by_hash(justification.commit.target_hash).await?;
+	let set_id = client
+		.storage_value_at::<SetId>(current_set_id_storage_key::<C>(), *target_header.parent_hash())
+		.await?
+		.unwrap_or_default();
+
+	for equivocation_proof in detector.note_justification(set_id, justification) {
+		let offender = equivocation_proof.offender().clone();
+		log::warn!(
+			target: "bridge",
+			"{} GRANDPA authority {:?} has equivocated at round {} of set {}",
+			C::NAME,
+			offender,
+			equivocation_proof.round(),
+			set_id,
+		);
+
+		let encoded_key_owner_proof = client
+			.state_call(
+				SUB_API_GRANDPA_GENERATE_KEY_OWNERSHIP_PROOF.to_string(),
+				Bytes((set_id, offender.clone()).encode()),
+				None,
+			)
+			.await?;
+		let key_owner_proof = Option::<OpaqueKeyOwnershipProof>::decode(&mut &encoded_key_owner_proof.0[..])
+			.map_err(Error::ResponseParseFailed)?;
+		let key_owner_proof = match key_owner_proof {
+			Some(key_owner_proof) => key_owner_proof,
+			None => {
+				log::warn!(
+					target: "bridge",
+					"{} runtime is unable to generate key ownership proof for authority {:?}. Equivocation is not reported",
+					C::NAME,
+					offender,
+				);
+				continue;
+			}
+		};
+
+		let transaction = match C::encode_report_equivocation_transaction(equivocation_proof, key_owner_proof) {
+			Some(transaction) => transaction,
+			None => {
+				log::warn!(
+					target: "bridge",
+					"Failed to decode {} key ownership proof of authority {:?}. Equivocation is not reported",
+					C::NAME,
+					offender,
+				);
+				continue;
+			}
+		};
+
+		let transaction_hash = client.submit_unsigned_extrinsic(transaction).await?;
+		log::info!(
+			target: "bridge",
+			"Submitted {} equivocation report of authority {:?}: {:?}",
+			C::NAME,
+			offender,
+			transaction_hash,
+		);
+	}
+
+	Ok(())
+}
+
+/// Storage key of the GRANDPA authorities set id at the chain.
+fn current_set_id_storage_key<C: ChainWithGrandpa>() -> StorageKey {
+	let mut key = sp_core::twox_128(C::GRANDPA_PALLET_NAME.as_bytes()).to_vec();
+	key.extend_from_slice(&sp_core::twox_128(b"CurrentSetId"));
+	StorageKey(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use finality_grandpa::{Commit, Precommit, SignedPrecommit};
+	use sp_runtime::{generic::Header as GenericHeader, traits::BlakeTwo256};
+
+	type TestHeader = GenericHeader<u64, BlakeTwo256>;
+
+	fn authority(index: u8) -> AuthorityId {
+		sp_core::ed25519::Public([index; 32]).into()
+	}
+
+	fn signed_precommit(
+		authority_index: u8,
+		target_hash: sp_core::H256,
+	) -> SignedPrecommit<sp_core::H256, u64, AuthoritySignature, AuthorityId> {
+		SignedPrecommit {
+			precommit: Precommit {
+				target_hash,
+				target_number: 1,
+			},
+			signature: sp_core::ed25519::Signature::from_raw([authority_index; 64]).into(),
+			id: authority(authority_index),
+		}
+	}
+
+	fn justification(
+		round: u64,
+		precommits: Vec<SignedPrecommit<sp_core::H256, u64, AuthoritySignature, AuthorityId>>,
+	) -> GrandpaJustification<TestHeader> {
+		GrandpaJustification {
+			round,
+			commit: Commit {
+				target_hash: precommits[0].precommit.target_hash,
+				target_number: 1,
+				precommits,
+			},
+			votes_ancestries: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn no_equivocations_in_justifications_of_the_same_block() {
+		let hash = sp_core::H256::repeat_byte(1);
+		let first = justification(1, vec![signed_precommit(1, hash), signed_precommit(2, hash)]);
+		let second = justification(1, vec![signed_precommit(2, hash), signed_precommit(3, hash)]);
+
+		assert!(find_equivocations(&first, &second).is_empty());
+	}
+
+	#[test]
+	fn no_equivocations_in_justifications_of_different_rounds() {
+		let first = justification(1, vec![signed_precommit(1, sp_core::H256::repeat_byte(1))]);
+		let second = justification(2, vec![signed_precommit(1, sp_core::H256::repeat_byte(2))]);
+
+		assert!(find_equivocations(&first, &second).is_empty());
+	}
+
+	#[test]
+	fn finds_equivocations_in_conflicting_justifications() {
+		let first_hash = sp_core::H256::repeat_byte(1);
+		let second_hash = sp_core::H256::repeat_byte(2);
+		let first = justification(
+			1,
+			vec![signed_precommit(1, first_hash), signed_precommit(2, first_hash)],
+		);
+		let second = justification(
+			1,
+			vec![signed_precommit(2, second_hash), signed_precommit(3, second_hash)],
+		);
+
+		assert_eq!(
+			find_equivocations(&first, &second),
+			vec![finality_grandpa::Equivocation {
+				round_number: 1,
+				identity: authority(2),
+				first: (
+					signed_precommit(2, first_hash).precommit,
+					signed_precommit(2, first_hash).signature
+				),
+				second: (
+					signed_precommit(2, second_hash).precommit,
+					signed_precommit(2, second_hash).signature
+				),
+			}],
+		);
+	}
+
+	#[test]
+	fn finds_equivocations_in_single_justification() {
+		let first_hash = sp_core::H256::repeat_byte(1);
+		let second_hash = sp_core::H256::repeat_byte(2);
+		let justification = justification(
+			1,
+			vec![signed_precommit(1, first_hash), signed_precommit(1, second_hash)],
+		);
+
+		let equivocations = find_equivocations(&justification, &justification);
+		assert_eq!(equivocations.len(), 1);
+		assert_eq!(equivocations[0].identity, authority(1));
+	}
+
+	#[test]
+	fn detector_only_cross_checks_justifications_of_the_same_set() {
+		let mut detector = EquivocationsDetector::default();
+		let first_hash = sp_core::H256::repeat_byte(1);
+		let second_hash = sp_core::H256::repeat_byte(2);
+
+		assert!(detector
+			.note_justification(1, justification(1, vec![signed_precommit(1, first_hash)]))
+			.is_empty());
+		assert!(detector
+			.note_justification(2, justification(1, vec![signed_precommit(1, second_hash)]))
+			.is_empty());
+
+		let equivocation_proofs =
+			detector.note_justification(1, justification(1, vec![signed_precommit(1, second_hash)]));
+		assert_eq!(equivocation_proofs.len(), 1);
+		assert_eq!(equivocation_proofs[0].set_id(), 1);
+		assert_eq!(equivocation_proofs[0].round(), 1);
+		assert_eq!(equivocation_proofs[0].offender(), &authority(1));
+	}
+
+	#[test]
+	fn detector_forgets_old_justifications() {
+		let mut detector = EquivocationsDetector::default();
+		detector.note_justification(
+			1,
+			justification(1, vec![signed_precommit(1, sp_core::H256::repeat_byte(1))]),
+		);
+		for round in 2..RECENT_JUSTIFICATIONS_LIMIT as u64 + 2 {
+			detector.note_justification(
+				1,
+				justification(round, vec![signed_precommit(1, sp_core::H256::repeat_byte(1))]),
+			);
+		}
+
+		assert!(detector
+			.note_justification(
+				1,
+				justification(1, vec![signed_precommit(1, sp_core::H256::repeat_byte(2))])
+			)
+			.is_empty());
+	}
+}
@@ -19,6 +19,7 @@
 use jsonrpsee_ws_client::Error as RpcError;
 use relay_utils::MaybeConnectionError;
 use sc_rpc_api::system::Health;
+use sp_runtime::{transaction_validity::TransactionValidityError, DispatchError};
 
 /// Result type used by Substrate client.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -42,6 +43,12 @@ pub enum Error {
 	ClientNotSynced(Health),
 	/// An error has happened when we have tried to parse storage proof.
 	StorageProofError(bp_runtime::StorageProofError),
+	/// The block (or its hash) is missing from the node. Most probably it has been pruned.
+	MissingBlock(String),
+	/// Transaction is invalid and it would be rejected by the node.
+	TransactionInvalid(TransactionValidityError),
+	/// Transaction is valid, but its dispatch would fail.
+	TransactionDispatchFailed(DispatchError),
 	/// Custom logic error.
 	Custom(String),
 }
@@ -56,6 +63,9 @@ impl std::error::Error for Error {
 			Self::MissingMandatoryCodeEntry => None,
 			Self::ClientNotSynced(_) => None,
 			Self::StorageProofError(_) => None,
+			Self::MissingBlock(_) => None,
+			Self::TransactionInvalid(_) => None,
+			Self::TransactionDispatchFailed(_) => None,
 			Self::Custom(_) => None,
 		}
 	}
@@ -91,6 +101,13 @@ impl std::fmt::Display for Error {
 			Self::MissingMandatoryCodeEntry => "Mandatory :code: entry is missing from runtime storage".into(),
 			Self::StorageProofError(e) => format!("Error when parsing storage proof: {:?}", e),
 			Self::ClientNotSynced(health) => format!("Substrate client is not synced: {}", health),
+			Self::MissingBlock(block) => format!(
+				"Block {} is missing from the node. If the node is running in pruning mode, consider connecting \
+				to an archive node or initializing the bridge from a more recent checkpoint",
+				block,
+			),
+			Self::TransactionInvalid(e) => format!("Transaction is invalid: {:?}", e),
+			Self::TransactionDispatchFailed(e) => format!("Transaction dispatch would fail with: {:?}", e),
 			Self::Custom(e) => e.clone(),
 		};
 
@@ -0,0 +1,195 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Finality engines that may be used by the finality relay.
+//!
+//! The finality relay itself doesn't care about how source chain headers are finalized. Everything
+//! that is specific to the consensus gadget (format of finality proofs, the way they're verified
+//! and the way we detect headers that must be relayed) lives behind the `FinalityEngine` trait.
+//! GRANDPA is the only engine that is supported now.
+
+use bp_header_chain::{
+	find_grandpa_authorities_forced_change, find_grandpa_authorities_scheduled_change,
+	justification::{verify_justification, Error as GrandpaJustificationError, GrandpaJustification},
+	FinalityProof,
+};
+use codec::Decode;
+use finality_grandpa::voter_set::VoterSet;
+use sp_finality_grandpa::{AuthorityId, SetId, GRANDPA_ENGINE_ID};
+use sp_runtime::{traits::Header as HeaderT, ConsensusEngineId};
+use std::fmt::Debug;
+
+/// Finality engine (consensus gadget) of the source chain.
+pub trait FinalityEngine<Header: HeaderT>: 'static + Clone + Debug + PartialEq + Send + Sync {
+	/// Identifier of the engine. Used to select engine justification from the block justifications.
+	const ID: ConsensusEngineId;
+	/// Name of the RPC method that subscribes to the engine finality proofs.
+	const SUBSCRIBE_METHOD: &'static str;
+	/// Name of the RPC method that cancels finality proofs subscription.
+	const UNSUBSCRIBE_METHOD: &'static str;
+
+	/// Type of finality proofs, generated by the engine.
+	type FinalityProof: FinalityProof<Header::Number> + Decode;
+	/// Data that is required to verify finality proof (e.g. current authorities set).
+	type VerificationContext;
+	/// Finality proof verification error.
+	type VerificationError: Debug;
+
+	/// Returns true if header must be relayed to the target chain, even if there are better
+	/// finalized headers available. Usually this means that the header changes the set of
+	/// authorities that are finalizing headers.
+	fn is_mandatory_header(header: &Header) -> bool;
+
+	/// Returns true if header forcibly changes the set of authorities that are finalizing headers.
+	///
+	/// Such changes can't be relayed using regular finality proofs, so they require manual
+	/// intervention at the target chain.
+	fn is_forced_change_header(header: &Header) -> bool;
+
+	/// Verify that the finality proof finalizes given header.
+	fn verify_finality_proof(
+		target: (Header::Hash, Header::Number),
+		context: &Self::VerificationContext,
+		proof: &Self::FinalityProof,
+	) -> Result<(), Self::VerificationError>;
+}
+
+/// GRANDPA finality engine.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grandpa;
+
+/// Data that is required to verify GRANDPA justification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrandpaVerificationContext {
+	/// Id of authorities set that has signed the justification.
+	pub authorities_set_id: SetId,
+	/// Authorities set that has signed the justification.
+	pub authorities_set: VoterSet<AuthorityId>,
+}
+
+impl<Header: HeaderT> FinalityEngine<Header> for Grandpa
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	const ID: ConsensusEngineId = GRANDPA_ENGINE_ID;
+	const SUBSCRIBE_METHOD: &'static str = "grandpa_subscribeJustifications";
+	const UNSUBSCRIBE_METHOD: &'static str = "grandpa_unsubscribeJustifications";
+
+	type FinalityProof = GrandpaJustification<Header>;
+	type VerificationContext = GrandpaVerificationContext;
+	type VerificationError = GrandpaJustificationError;
+
+	fn is_mandatory_header(header: &Header) -> bool {
+		find_grandpa_authorities_scheduled_change(header).is_some()
+	}
+
+	fn is_forced_change_header(header: &Header) -> bool {
+		find_grandpa_authorities_forced_change(header).is_some()
+	}
+
+	fn verify_finality_proof(
+		target: (Header::Hash, Header::Number),
+		context: &GrandpaVerificationContext,
+		proof: &GrandpaJustification<Header>,
+	) -> Result<(), GrandpaJustificationError> {
+		verify_justification::<Header>(target, context.authorities_set_id, &context.authorities_set, proof)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::{generic::Header as GenericHeader, traits::BlakeTwo256, DigestItem};
+
+	type TestHeader = GenericHeader<u64, BlakeTwo256>;
+
+	fn test_header() -> TestHeader {
+		TestHeader::new(
+			1,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		)
+	}
+
+	#[test]
+	fn grandpa_header_is_mandatory_if_it_schedules_authorities_set_change() {
+		let mut header = test_header();
+		assert!(!<Grandpa as FinalityEngine<TestHeader>>::is_mandatory_header(&header));
+
+		header.digest_mut().push(DigestItem::Consensus(
+			GRANDPA_ENGINE_ID,
+			codec::Encode::encode(&sp_finality_grandpa::ConsensusLog::<u64>::ScheduledChange(
+				sp_finality_grandpa::ScheduledChange {
+					next_authorities: Vec::new(),
+					delay: 0,
+				},
+			)),
+		));
+		assert!(<Grandpa as FinalityEngine<TestHeader>>::is_mandatory_header(&header));
+	}
+
+	#[test]
+	fn grandpa_detects_forced_authorities_set_change() {
+		let mut header = test_header();
+		assert!(!<Grandpa as FinalityEngine<TestHeader>>::is_forced_change_header(
+			&header
+		));
+
+		header.digest_mut().push(DigestItem::Consensus(
+			GRANDPA_ENGINE_ID,
+			codec::Encode::encode(&sp_finality_grandpa::ConsensusLog::<u64>::ForcedChange(
+				0,
+				sp_finality_grandpa::ScheduledChange {
+					next_authorities: Vec::new(),
+					delay: 0,
+				},
+			)),
+		));
+		assert!(<Grandpa as FinalityEngine<TestHeader>>::is_forced_change_header(
+			&header
+		));
+		assert!(!<Grandpa as FinalityEngine<TestHeader>>::is_mandatory_header(&header));
+	}
+
+	#[test]
+	fn grandpa_rejects_justification_for_other_header() {
+		let header = test_header();
+		let justification = GrandpaJustification::<TestHeader> {
+			round: 0,
+			commit: finality_grandpa::Commit {
+				target_hash: Default::default(),
+				target_number: 2,
+				precommits: Vec::new(),
+			},
+			votes_ancestries: Vec::new(),
+		};
+		let context = GrandpaVerificationContext {
+			authorities_set_id: 0,
+			authorities_set: VoterSet::new(vec![(sp_core::ed25519::Public([1u8; 32]).into(), 1)]).unwrap(),
+		};
+
+		assert_eq!(
+			<Grandpa as FinalityEngine<TestHeader>>::verify_finality_proof(
+				(header.hash(), *header.number()),
+				&context,
+				&justification,
+			),
+			Err(GrandpaJustificationError::InvalidJustificationTarget),
+		);
+	}
+}
@@ -19,11 +19,11 @@
 use crate::chain::{BlockWithJustification, Chain};
 use crate::client::Client;
 use crate::error::Error;
+use crate::finality_engine::{FinalityEngine, Grandpa};
 use crate::sync_header::SyncHeader;
 
 use async_std::sync::{Arc, Mutex};
 use async_trait::async_trait;
-use bp_header_chain::justification::GrandpaJustification;
 use codec::Decode;
 use finality_relay::{FinalitySyncPipeline, SourceClient, SourceHeader};
 use futures::stream::{unfold, Stream, StreamExt};
@@ -35,13 +35,16 @@ use std::{marker::PhantomData, pin::Pin};
 pub type RequiredHeaderNumberRef<C> = Arc<Mutex<<C as bp_runtime::Chain>::BlockNumber>>;
 
 /// Substrate node as finality source.
-pub struct FinalitySource<C: Chain, P> {
+///
+/// The `E` is the finality engine of the source chain. Finality proofs are read from the block
+/// justifications and from the subscription that are specific to this engine.
+pub struct FinalitySource<C: Chain, P, E = Grandpa> {
 	client: Client<C>,
 	maximal_header_number: Option<RequiredHeaderNumberRef<C>>,
-	_phantom: PhantomData<P>,
+	_phantom: PhantomData<(P, E)>,
 }
 
-impl<C: Chain, P> FinalitySource<C, P> {
+impl<C: Chain, P, E> FinalitySource<C, P, E> {
 	/// Create new headers source using given client.
 	pub fn new(client: Client<C>, maximal_header_number: Option<RequiredHeaderNumberRef<C>>) -> Self {
 		FinalitySource {
@@ -61,7 +64,7 @@ impl<C: Chain, P> FinalitySource<C, P> {
 	}
 }
 
-impl<C: Chain, P> Clone for FinalitySource<C, P> {
+impl<C: Chain, P, E> Clone for FinalitySource<C, P, E> {
 	fn clone(&self) -> Self {
 		FinalitySource {
 			client: self.client.clone(),
@@ -72,7 +75,7 @@ impl<C: Chain, P> Clone for FinalitySource<C, P> {
 }
 
 #[async_trait]
-impl<C: Chain, P: FinalitySyncPipeline> RelayClient for FinalitySource<C, P> {
+impl<C: Chain, P: FinalitySyncPipeline, E: 'static + Send + Sync> RelayClient for FinalitySource<C, P, E> {
 	type Error = Error;
 
 	async fn reconnect(&mut self) -> Result<(), Error> {
@@ -81,19 +84,20 @@ impl<C: Chain, P: FinalitySyncPipeline> RelayClient for FinalitySource<C, P> {
 }
 
 #[async_trait]
-impl<C, P> SourceClient<P> for FinalitySource<C, P>
+impl<C, P, E> SourceClient<P> for FinalitySource<C, P, E>
 where
 	C: Chain,
 	C::BlockNumber: relay_utils::BlockNumberBase,
 	P: FinalitySyncPipeline<
 		Hash = C::Hash,
 		Number = C::BlockNumber,
-		Header = SyncHeader<C::Header>,
-		FinalityProof = GrandpaJustification<C::Header>,
+		Header = SyncHeader<C::Header, E>,
+		FinalityProof = E::FinalityProof,
 	>,
 	P::Header: SourceHeader<C::BlockNumber>,
+	E: FinalityEngine<C::Header>,
 {
-	type FinalityProofsStream = Pin<Box<dyn Stream<Item = GrandpaJustification<C::Header>> + Send>>;
+	type FinalityProofsStream = Pin<Box<dyn Stream<Item = E::FinalityProof> + Send>>;
 
 	async fn best_finalized_block_number(&self) -> Result<P::Number, Error> {
 		let mut finalized_header_number = self.on_chain_best_finalized_block_number().await?;
@@ -116,22 +120,33 @@ where
 		let signed_block = self.client.get_block(Some(header_hash)).await?;
 
 		let justification = signed_block
-			.justification()
-			.map(|raw_justification| GrandpaJustification::<C::Header>::decode(&mut raw_justification.as_slice()))
+			.justification(E::ID)
+			.map(|raw_justification| E::FinalityProof::decode(&mut raw_justification.as_slice()))
 			.transpose()
 			.map_err(Error::ResponseParseFailed)?;
 
-		Ok((signed_block.header().into(), justification))
+		let header = signed_block.header();
+		if E::is_forced_change_header(&header) {
+			log::error!(
+				target: "bridge",
+				"{} header {:?} forcibly changes the authorities set. Finality relay is unable to relay \
+				this change and manual intervention is required at the {} chain (e.g. `force_set_authorities` call)",
+				P::SOURCE_NAME,
+				number,
+				P::TARGET_NAME,
+			);
+		}
+
+		Ok((header.into(), justification))
 	}
 
 	async fn finality_proofs(&self) -> Result<Self::FinalityProofsStream, Error> {
 		Ok(unfold(
-			self.client.clone().subscribe_justifications().await?,
+			self.client.clone().subscribe_finality_proofs::<E>().await?,
 			move |mut subscription| async move {
 				loop {
 					let next_justification = subscription.next().await?;
-					let decoded_justification =
-						GrandpaJustification::<C::Header>::decode(&mut &next_justification.0[..]);
+					let decoded_justification = E::FinalityProof::decode(&mut &next_justification.0[..]);
 
 					let justification = match decoded_justification {
 						Ok(j) => j,
@@ -153,4 +168,8 @@ where
 		)
 		.boxed())
 	}
+
+	fn is_missing_header_error(error: &Error) -> bool {
+		matches!(*error, Error::MissingBlock(_))
+	}
 }
@@ -26,6 +26,7 @@ use headers_relay::{
 	sync_types::{HeaderIdOf, HeadersSyncPipeline, QueuedHeader, SourceHeader},
 };
 use relay_utils::relay_loop::Client as RelayClient;
+use sp_finality_grandpa::GRANDPA_ENGINE_ID;
 use sp_runtime::{traits::Header as HeaderT, EncodedJustification};
 use std::marker::PhantomData;
 
@@ -97,7 +98,7 @@ where
 	async fn header_completion(&self, id: HeaderIdOf<P>) -> Result<(HeaderIdOf<P>, Option<P::Completion>), Error> {
 		let hash = id.1;
 		let signed_block = self.client.get_block(Some(hash)).await?;
-		let grandpa_justification = signed_block.justification().cloned();
+		let grandpa_justification = signed_block.justification(GRANDPA_ENGINE_ID).cloned();
 
 		Ok((id, grandpa_justification))
 	}
@@ -22,17 +22,27 @@ mod chain;
 mod client;
 mod error;
 mod rpc;
+mod storage_changes_tracker;
+mod storage_proof_cache;
 mod sync_header;
+mod tip_escalation;
 
+pub mod equivocation;
+pub mod finality_engine;
 pub mod finality_source;
 pub mod guard;
 pub mod headers_source;
 pub mod metrics;
 
-pub use crate::chain::{BlockWithJustification, Chain, ChainWithBalances, TransactionSignScheme};
-pub use crate::client::{Client, JustificationsSubscription, OpaqueGrandpaAuthoritiesSet};
+pub use crate::chain::{
+	BlockWithJustification, Chain, ChainWithBalances, ChainWithGrandpa, MultiSignerKeyPair, TransactionSignScheme,
+};
+pub use crate::client::{Client, JustificationsSubscription, OpaqueGrandpaAuthoritiesSet, StorageChangesSubscription};
 pub use crate::error::{Error, Result};
+pub use crate::finality_engine::{FinalityEngine, Grandpa, GrandpaVerificationContext};
+pub use crate::storage_changes_tracker::StorageChangesTracker;
 pub use crate::sync_header::SyncHeader;
+pub use crate::tip_escalation::TipEscalation;
 pub use bp_runtime::{BlockNumberOf, Chain as ChainBase, HashOf, HeaderOf};
 
 /// Header id used by the chain.
@@ -17,7 +17,9 @@
 //! Contains several Substrate-specific metrics that may be exposed by relay.
 
 pub use float_storage_value::FloatStorageValueMetric;
+pub use relayer_rewards::{RelayerRewardsMetric, RewardsEvent, RewardsEventParser};
 pub use storage_proof_overhead::StorageProofOverheadMetric;
 
 mod float_storage_value;
+mod relayer_rewards;
 mod storage_proof_overhead;
@@ -0,0 +1,404 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metric that tracks rewards, earned by relayers for delivering messages over given lane.
+
+use crate::chain::Chain;
+use crate::client::Client;
+use crate::error::Error;
+
+use async_std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use bp_messages::LaneId;
+use frame_support::Parameter;
+use frame_system::{EventRecord, Phase};
+use num_traits::One;
+use relay_utils::metrics::{
+	counter_key, metric_name, register, GaugeVec, Opts, PersistentCounters, PrometheusError, Registry,
+	StandaloneMetrics, F64,
+};
+use sp_core::storage::StorageKey;
+use sp_runtime::traits::{Header as HeaderT, Member};
+use std::{
+	collections::BTreeMap,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Rewards update interval (in blocks).
+const UPDATE_INTERVAL_IN_BLOCKS: u32 = 5;
+/// Maximal number of blocks that are processed during single metric update.
+const MAX_BLOCKS_PER_UPDATE: u32 = 64;
+/// Number of seconds in one day.
+const SECONDS_IN_DAY: u64 = 24 * 60 * 60;
+
+/// Runtime event that is interesting to the relayer rewards metric.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RewardsEvent<AccountId, Balance> {
+	/// Delivery of messages over given lane has been confirmed (`MessagesDelivered` event of the
+	/// messages pallet).
+	MessagesDelivered(LaneId),
+	/// Funds have been transferred: `(from, to, amount)`.
+	Transfer(AccountId, AccountId, Balance),
+}
+
+/// Function that converts runtime event into `RewardsEvent`. Returns `None` if event is not
+/// related to relayer rewards.
+pub type RewardsEventParser<C, E> = fn(E) -> Option<RewardsEvent<<C as Chain>::AccountId, <C as Chain>::Balance>>;
+
+/// Metric that represents rewards, earned by given relayers at given lane.
+///
+/// The reward is paid by the messages pallet when message delivery is confirmed. So we're
+/// looking for `MessagesDelivered` events of the lane and transfers from the relayers fund
+/// account, made by the same transaction. Only rewards that are earned while the relay is running
+/// are tracked. Rewards are exposed in two flavors: `total` and `today` (where the day is
+/// the current UTC day of the relay host). The `total` rewards are kept in the persistent
+/// counters, so they survive relay restarts.
+pub struct RelayerRewardsMetric<C: Chain, E> {
+	client: Client<C>,
+	lane: LaneId,
+	relayers_fund_account: C::AccountId,
+	relayers: Vec<C::AccountId>,
+	parse_event: RewardsEventParser<C, E>,
+	state: Arc<Mutex<RewardsState<C::AccountId, C::BlockNumber>>>,
+	persistent_counters: PersistentCounters,
+	metric_name: String,
+	metric: GaugeVec<F64>,
+}
+
+/// Rewards that have been earned by relayers since relay start.
+#[derive(Debug)]
+struct RewardsState<AccountId, BlockNumber> {
+	/// Best block that has been processed by the metric.
+	best_processed_block: Option<BlockNumber>,
+	/// Current day (number of days since UNIX epoch).
+	day: u64,
+	/// Rewards that are earned since relay start (including rewards, restored from persistent counters).
+	total: BTreeMap<AccountId, u128>,
+	/// Rewards that are earned during current day.
+	today: BTreeMap<AccountId, u128>,
+}
+
+impl<C: Chain, E> Clone for RelayerRewardsMetric<C, E> {
+	fn clone(&self) -> Self {
+		RelayerRewardsMetric {
+			client: self.client.clone(),
+			lane: self.lane,
+			relayers_fund_account: self.relayers_fund_account.clone(),
+			relayers: self.relayers.clone(),
+			parse_event: self.parse_event,
+			state: self.state.clone(),
+			persistent_counters: self.persistent_counters.clone(),
+			metric_name: self.metric_name.clone(),
+			metric: self.metric.clone(),
+		}
+	}
+}
+
+impl<C: Chain, E> RelayerRewardsMetric<C, E>
+where
+	C::Balance: Into<u128>,
+	E: Parameter + Member,
+{
+	/// Create new metric instance with given name and help.
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(
+		registry: &Registry,
+		prefix: Option<&str>,
+		client: Client<C>,
+		lane: LaneId,
+		relayers_fund_account: C::AccountId,
+		relayers: Vec<C::AccountId>,
+		parse_event: RewardsEventParser<C, E>,
+		persistent_counters: PersistentCounters,
+		name: String,
+		help: String,
+	) -> Result<Self, PrometheusError> {
+		let metric_name = metric_name(prefix, &name);
+		let total = relayers
+			.iter()
+			.map(|relayer| {
+				let key = total_rewards_key(&metric_name, lane, relayer);
+				(relayer.clone(), persistent_counters.get(&key))
+			})
+			.collect();
+		Ok(RelayerRewardsMetric {
+			client,
+			lane,
+			relayers_fund_account,
+			relayers,
+			parse_event,
+			state: Arc::new(Mutex::new(RewardsState {
+				best_processed_block: None,
+				day: current_day(),
+				total,
+				today: BTreeMap::new(),
+			})),
+			persistent_counters,
+			metric: register(
+				GaugeVec::new(Opts::new(metric_name.clone(), help), &["lane", "relayer", "period"])?,
+				registry,
+			)?,
+			metric_name,
+		})
+	}
+
+	/// Read rewards from all new finalized blocks.
+	async fn update_rewards(&self) -> Result<(), Error> {
+		let best_finalized_hash = self.client.best_finalized_header_hash().await?;
+		let best_finalized_number = *self.client.header_by_hash(best_finalized_hash).await?.number();
+
+		let mut state = self.state.lock().await;
+		// we are not reading historical rewards => start from the current best finalized block
+		let mut next_block = state
+			.best_processed_block
+			.map(|best_processed_block| best_processed_block + One::one())
+			.unwrap_or(best_finalized_number);
+		let mut processed_blocks = 0;
+		while next_block <= best_finalized_number && processed_blocks < MAX_BLOCKS_PER_UPDATE {
+			let block_hash = self.client.block_hash_by_number(next_block).await?;
+			let events: Vec<EventRecord<E, C::Hash>> = self
+				.client
+				.storage_value_at(system_events_key(), block_hash)
+				.await?
+				.unwrap_or_default();
+			let rewards = select_rewards(
+				self.lane,
+				&self.relayers_fund_account,
+				&self.relayers,
+				self.parse_event,
+				events,
+			);
+			for (relayer, reward) in &rewards {
+				self.persistent_counters
+					.add(&total_rewards_key(&self.metric_name, self.lane, relayer), *reward);
+			}
+			state.add_rewards(current_day(), rewards);
+			state.best_processed_block = Some(next_block);
+
+			next_block = next_block + One::one();
+			processed_blocks += 1;
+		}
+
+		state.maybe_start_new_day(current_day());
+		let lane = hex::encode(self.lane);
+		for relayer in &self.relayers {
+			let relayer_label = format!("{:?}", relayer);
+			self.metric
+				.with_label_values(&[&lane, &relayer_label, "total"])
+				.set(state.total.get(relayer).cloned().unwrap_or(0) as f64);
+			self.metric
+				.with_label_values(&[&lane, &relayer_label, "today"])
+				.set(state.today.get(relayer).cloned().unwrap_or(0) as f64);
+		}
+
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl<C: Chain, E> StandaloneMetrics for RelayerRewardsMetric<C, E>
+where
+	C::Balance: Into<u128>,
+	E: Parameter + Member,
+{
+	fn update_interval(&self) -> Duration {
+		C::AVERAGE_BLOCK_INTERVAL * UPDATE_INTERVAL_IN_BLOCKS
+	}
+
+	async fn update(&self) {
+		if let Err(error) = self.update_rewards().await {
+			log::warn!(
+				target: "bridge-metrics",
+				"Failed to update {} relayer rewards at lane {}: {:?}",
+				C::NAME,
+				hex::encode(self.lane),
+				error,
+			);
+		}
+	}
+}
+
+impl<AccountId: Clone + Ord, BlockNumber> RewardsState<AccountId, BlockNumber> {
+	/// Reset daily rewards if new day has started.
+	fn maybe_start_new_day(&mut self, day: u64) {
+		if day != self.day {
+			self.day = day;
+			self.today.clear();
+		}
+	}
+
+	/// Remember rewards, earned by relayers.
+	fn add_rewards(&mut self, day: u64, rewards: BTreeMap<AccountId, u128>) {
+		self.maybe_start_new_day(day);
+		for (relayer, reward) in rewards {
+			let total = self.total.entry(relayer.clone()).or_insert(0);
+			*total = total.saturating_add(reward);
+			let today = self.today.entry(relayer).or_insert(0);
+			*today = today.saturating_add(reward);
+		}
+	}
+}
+
+/// Select rewards that have been paid to given relayers for delivering messages over given lane.
+fn select_rewards<AccountId, Balance, E, Hash>(
+	lane: LaneId,
+	relayers_fund_account: &AccountId,
+	relayers: &[AccountId],
+	parse_event: fn(E) -> Option<RewardsEvent<AccountId, Balance>>,
+	events: Vec<EventRecord<E, Hash>>,
+) -> BTreeMap<AccountId, u128>
+where
+	AccountId: Clone + Ord,
+	Balance: Into<u128>,
+	E: Parameter + Member,
+{
+	// rewards are paid by the same transaction that has emitted `MessagesDelivered` event
+	let mut transactions: BTreeMap<u32, (bool, Vec<(AccountId, u128)>)> = BTreeMap::new();
+	for event in events {
+		let transaction_index = match event.phase {
+			Phase::ApplyExtrinsic(transaction_index) => transaction_index,
+			_ => continue,
+		};
+
+		match (parse_event)(event.event) {
+			Some(RewardsEvent::MessagesDelivered(event_lane)) if event_lane == lane => {
+				transactions.entry(transaction_index).or_default().0 = true;
+			}
+			Some(RewardsEvent::Transfer(from, to, amount))
+				if from == *relayers_fund_account && relayers.contains(&to) =>
+			{
+				transactions
+					.entry(transaction_index)
+					.or_default()
+					.1
+					.push((to, amount.into()));
+			}
+			_ => (),
+		}
+	}
+
+	let mut rewards = BTreeMap::new();
+	for (relayer, reward) in transactions
+		.into_iter()
+		.filter(|(_, (is_lane_confirmation, _))| *is_lane_confirmation)
+		.flat_map(|(_, (_, transfers))| transfers)
+	{
+		let relayer_reward = rewards.entry(relayer).or_insert(0u128);
+		*relayer_reward = relayer_reward.saturating_add(reward);
+	}
+	rewards
+}
+
+/// Returns key of the persistent counter with total rewards of given relayer.
+fn total_rewards_key<AccountId: std::fmt::Debug>(metric_name: &str, lane: LaneId, relayer: &AccountId) -> String {
+	counter_key(metric_name, &[&hex::encode(lane), &format!("{:?}", relayer), "total"])
+}
+
+/// Returns storage key of the `frame_system::Events` storage value.
+fn system_events_key() -> StorageKey {
+	let mut key = sp_core::twox_128(b"System").to_vec();
+	key.extend_from_slice(&sp_core::twox_128(b"Events"));
+	StorageKey(key)
+}
+
+/// Returns current day number (number of days since UNIX epoch).
+fn current_day() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs() / SECONDS_IN_DAY)
+		.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::{Decode, Encode};
+
+	const LANE: LaneId = [0, 0, 0, 1];
+	const OTHER_LANE: LaneId = [0, 0, 0, 2];
+	const FUND: u64 = 0;
+	const RELAYER_1: u64 = 1;
+	const RELAYER_2: u64 = 2;
+	const OTHER_ACCOUNT: u64 = 3;
+
+	#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq)]
+	enum TestEvent {
+		MessagesDelivered(LaneId),
+		Transfer(u64, u64, u64),
+	}
+
+	fn parse_event(event: TestEvent) -> Option<RewardsEvent<u64, u64>> {
+		match event {
+			TestEvent::MessagesDelivered(lane) => Some(RewardsEvent::MessagesDelivered(lane)),
+			TestEvent::Transfer(from, to, amount) => Some(RewardsEvent::Transfer(from, to, amount)),
+		}
+	}
+
+	fn event(transaction_index: u32, event: TestEvent) -> EventRecord<TestEvent, u64> {
+		EventRecord {
+			phase: Phase::ApplyExtrinsic(transaction_index),
+			event,
+			topics: vec![],
+		}
+	}
+
+	#[test]
+	fn select_rewards_only_selects_lane_rewards_paid_to_given_relayers() {
+		let events = vec![
+			// rewards for delivering messages over our lane
+			event(0, TestEvent::Transfer(FUND, RELAYER_1, 10)),
+			event(0, TestEvent::Transfer(FUND, RELAYER_2, 20)),
+			event(0, TestEvent::Transfer(FUND, OTHER_ACCOUNT, 30)),
+			event(0, TestEvent::MessagesDelivered(LANE)),
+			// rewards for delivering messages over other lane
+			event(1, TestEvent::Transfer(FUND, RELAYER_1, 40)),
+			event(1, TestEvent::MessagesDelivered(OTHER_LANE)),
+			// regular transfer to the relayer
+			event(2, TestEvent::Transfer(OTHER_ACCOUNT, RELAYER_1, 50)),
+			// one more reward for delivering messages over our lane
+			event(3, TestEvent::MessagesDelivered(LANE)),
+			event(3, TestEvent::Transfer(FUND, RELAYER_1, 60)),
+		];
+
+		assert_eq!(
+			select_rewards(LANE, &FUND, &[RELAYER_1, RELAYER_2], parse_event, events),
+			vec![(RELAYER_1, 70), (RELAYER_2, 20)].into_iter().collect(),
+		);
+	}
+
+	#[test]
+	fn daily_rewards_are_reset_when_new_day_starts() {
+		let mut state = RewardsState::<u64, u64> {
+			best_processed_block: None,
+			day: 0,
+			total: BTreeMap::new(),
+			today: BTreeMap::new(),
+		};
+
+		state.add_rewards(0, vec![(RELAYER_1, 10)].into_iter().collect());
+		state.add_rewards(0, vec![(RELAYER_1, 20)].into_iter().collect());
+		assert_eq!(state.total, vec![(RELAYER_1, 30)].into_iter().collect());
+		assert_eq!(state.today, vec![(RELAYER_1, 30)].into_iter().collect());
+
+		state.add_rewards(1, vec![(RELAYER_1, 40)].into_iter().collect());
+		assert_eq!(state.total, vec![(RELAYER_1, 70)].into_iter().collect());
+		assert_eq!(state.today, vec![(RELAYER_1, 40)].into_iter().collect());
+
+		state.maybe_start_new_day(2);
+		assert_eq!(state.total, vec![(RELAYER_1, 70)].into_iter().collect());
+		assert!(state.today.is_empty());
+	}
+}
@@ -34,17 +34,26 @@ jsonrpsee_proc_macros::rpc_client_api! {
 		#[rpc(method = "chain_getFinalizedHead", positional_params)]
 		fn chain_get_finalized_head() -> C::Hash;
 		#[rpc(method = "chain_getBlock", positional_params)]
-		fn chain_get_block(block_hash: Option<C::Hash>) -> C::SignedBlock;
+		fn chain_get_block(block_hash: Option<C::Hash>) -> Option<C::SignedBlock>;
 		#[rpc(method = "chain_getBlockHash", positional_params)]
-		fn chain_get_block_hash(block_number: Option<C::BlockNumber>) -> C::Hash;
+		fn chain_get_block_hash(block_number: Option<C::BlockNumber>) -> Option<C::Hash>;
 		#[rpc(method = "system_accountNextIndex", positional_params)]
 		fn system_account_next_index(account_id: C::AccountId) -> C::Index;
 		#[rpc(method = "author_submitExtrinsic", positional_params)]
 		fn author_submit_extrinsic(extrinsic: Bytes) -> C::Hash;
+		#[rpc(method = "system_dryRun", positional_params)]
+		fn system_dry_run(extrinsic: Bytes, at_block: Option<C::Hash>) -> Bytes;
 		#[rpc(method = "state_call", positional_params)]
 		fn state_call(method: String, data: Bytes, at_block: Option<C::Hash>) -> Bytes;
 		#[rpc(method = "state_getStorage", positional_params)]
-		fn state_get_storage(key: StorageKey) -> Option<StorageData>;
+		fn state_get_storage(key: StorageKey, at_block: Option<C::Hash>) -> Option<StorageData>;
+		#[rpc(method = "state_getKeysPaged", positional_params)]
+		fn state_get_keys_paged(
+			prefix: StorageKey,
+			count: u32,
+			start_key: Option<StorageKey>,
+			at_block: Option<C::Hash>,
+		) -> Vec<StorageKey>;
 		#[rpc(method = "state_getReadProof", positional_params)]
 		fn state_prove_storage(keys: Vec<StorageKey>, hash: Option<C::Hash>) -> ReadProof<C::Hash>;
 		#[rpc(method = "state_getRuntimeVersion", positional_params)]
@@ -0,0 +1,299 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracker of runtime storage changes, that is used to avoid re-reading values that are computed
+//! from the storage, while the storage itself stays the same.
+
+use crate::chain::Chain;
+use crate::client::{Client, StorageChangesSubscription};
+use crate::error::Result;
+
+use async_std::sync::{Arc, Mutex};
+use codec::{Decode, Encode};
+use sp_core::storage::StorageKey;
+use sp_runtime::{traits::Header as HeaderT, DeserializeOwned};
+use std::{
+	collections::HashMap,
+	future::Future,
+	time::{Duration, Instant},
+};
+
+/// Maximal age of the cached value. Even if the subscription is alive, the value is re-read after
+/// this interval, because node may skip notifications (e.g. for blocks that are enacted by reorg).
+const MAX_CACHED_VALUE_AGE: Duration = Duration::from_secs(60);
+/// Minimal interval between attempts to (re)subscribe to storage changes.
+const RESUBSCRIBE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tracker of runtime storage changes.
+///
+/// The tracker subscribes to changes of given storage keys (using `state_subscribeStorage` method)
+/// and caches values that are computed from these keys. Cached value is reused when it is requested
+/// at the same or later block and there have been no changes since the block it has been read at.
+/// If node doesn't support subscriptions (or the subscription has been closed), the tracker falls
+/// back to polling - i.e. the value is read from the node every time it is requested.
+///
+/// Cloning `StorageChangesTracker` is a cheap operation.
+pub struct StorageChangesTracker<C: Chain> {
+	keys: Vec<StorageKey>,
+	state: Arc<Mutex<TrackerState<C::BlockNumber>>>,
+}
+
+/// Shared state of the tracker.
+#[derive(Debug)]
+struct TrackerState<BlockNumber> {
+	/// True if we have active storage subscription.
+	is_subscribed: bool,
+	/// Time of the last subscription attempt.
+	subscribed_at: Option<Instant>,
+	/// Best block number where tracked keys have been changed.
+	latest_change: Option<BlockNumber>,
+	/// Cached values.
+	cache: HashMap<&'static str, CachedValue<BlockNumber>>,
+}
+
+/// Cached value.
+#[derive(Debug)]
+struct CachedValue<BlockNumber> {
+	/// Number of block the value has been read at.
+	at_block: BlockNumber,
+	/// Time when the value has been read.
+	read_at: Instant,
+	/// Encoded value.
+	encoded_value: Vec<u8>,
+}
+
+impl<C: Chain> Clone for StorageChangesTracker<C> {
+	fn clone(&self) -> Self {
+		StorageChangesTracker {
+			keys: self.keys.clone(),
+			state: self.state.clone(),
+		}
+	}
+}
+
+impl<C: Chain> StorageChangesTracker<C> {
+	/// Create new tracker of given storage keys.
+	///
+	/// The subscription is started lazily, on the first `read` call.
+	pub fn new(keys: Vec<StorageKey>) -> Self {
+		StorageChangesTracker {
+			keys,
+			state: Arc::new(Mutex::new(TrackerState {
+				is_subscribed: false,
+				subscribed_at: None,
+				latest_change: None,
+				cache: HashMap::new(),
+			})),
+		}
+	}
+
+	/// Return value that is computed from tracked storage keys at given block.
+	///
+	/// If tracked keys haven't been changed since the block the cached value has been read at, the
+	/// cached value is returned. Otherwise the value is read using `read_value` future and cached.
+	pub async fn read<T, F>(
+		&self,
+		client: &Client<C>,
+		key: &'static str,
+		at_block: C::BlockNumber,
+		read_value: F,
+	) -> Result<T>
+	where
+		C::Header: DeserializeOwned,
+		T: Encode + Decode,
+		F: Future<Output = Result<T>>,
+	{
+		{
+			let mut state = self.state.lock().await;
+			if state.should_subscribe(Instant::now()) {
+				state.subscribed_at = Some(Instant::now());
+				match client.subscribe_storage(self.keys.clone()).await {
+					Ok(subscription) => {
+						state.is_subscribed = true;
+						state.latest_change = None;
+						async_std::task::spawn(track_changes(client.clone(), subscription, self.state.clone()));
+					}
+					Err(error) => log::debug!(
+						target: "bridge",
+						"Failed to subscribe to {} storage changes: {:?}. Falling back to polling",
+						C::NAME,
+						error,
+					),
+				}
+			}
+
+			let cached_value = state
+				.cached_value(key, at_block, Instant::now())
+				.and_then(|encoded_value| T::decode(&mut &encoded_value[..]).ok());
+			if let Some(cached_value) = cached_value {
+				return Ok(cached_value);
+			}
+		}
+
+		let value = read_value.await?;
+		self.state.lock().await.cache.insert(
+			key,
+			CachedValue {
+				at_block,
+				read_at: Instant::now(),
+				encoded_value: value.encode(),
+			},
+		);
+		Ok(value)
+	}
+}
+
+impl<BlockNumber: Copy + Ord> TrackerState<BlockNumber> {
+	/// Returns true if we need to (re)subscribe to storage changes.
+	fn should_subscribe(&self, now: Instant) -> bool {
+		!self.is_subscribed
+			&& self
+				.subscribed_at
+				.map(|subscribed_at| now.saturating_duration_since(subscribed_at) >= RESUBSCRIBE_INTERVAL)
+				.unwrap_or(true)
+	}
+
+	/// Note storage change at given block.
+	fn note_change(&mut self, at_block: BlockNumber) {
+		self.latest_change = Some(
+			self.latest_change
+				.map_or(at_block, |latest| std::cmp::max(latest, at_block)),
+		);
+	}
+
+	/// Returns encoded cached value, if it is still actual at given block.
+	fn cached_value(&self, key: &'static str, at_block: BlockNumber, now: Instant) -> Option<&[u8]> {
+		// we don't know anything about changes until subscription has yielded the first item
+		let latest_change = match (self.is_subscribed, self.latest_change) {
+			(true, Some(latest_change)) => latest_change,
+			_ => return None,
+		};
+
+		let cached = self.cache.get(key)?;
+		let is_actual = at_block >= cached.at_block
+			&& cached.at_block >= latest_change
+			&& now.saturating_duration_since(cached.read_at) < MAX_CACHED_VALUE_AGE;
+		if is_actual {
+			Some(&cached.encoded_value)
+		} else {
+			None
+		}
+	}
+}
+
+/// Read storage changes from the subscription and update tracker state.
+async fn track_changes<C: Chain>(
+	client: Client<C>,
+	mut subscription: StorageChangesSubscription<C::Hash>,
+	state: Arc<Mutex<TrackerState<C::BlockNumber>>>,
+) where
+	C::Header: DeserializeOwned,
+{
+	while let Some(change_set) = subscription.next().await {
+		match client.header_by_hash(change_set.block).await {
+			Ok(header) => state.lock().await.note_change(*header.number()),
+			Err(error) => {
+				// we don't know where the change has happened, so all cached values are outdated
+				log::debug!(
+					target: "bridge",
+					"Failed to read {} header {:?} with storage changes: {:?}",
+					C::NAME,
+					change_set.block,
+					error,
+				);
+				state.lock().await.cache.clear();
+			}
+		}
+	}
+
+	log::debug!(
+		target: "bridge",
+		"{} storage changes subscription has been closed. Falling back to polling",
+		C::NAME,
+	);
+
+	let mut state = state.lock().await;
+	state.is_subscribed = false;
+	state.cache.clear();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const KEY: &str = "key";
+
+	fn state_with_cached_value(at_block: u32, read_at: Instant) -> TrackerState<u32> {
+		let mut state = TrackerState {
+			is_subscribed: true,
+			subscribed_at: Some(read_at),
+			latest_change: Some(at_block),
+			cache: HashMap::new(),
+		};
+		state.cache.insert(
+			KEY,
+			CachedValue {
+				at_block,
+				read_at,
+				encoded_value: vec![42],
+			},
+		);
+		state
+	}
+
+	#[test]
+	fn cached_value_is_reused_until_storage_is_changed() {
+		let now = Instant::now();
+		let mut state = state_with_cached_value(10, now);
+
+		// value is reused at the same and later blocks
+		assert_eq!(state.cached_value(KEY, 10, now), Some(&[42][..]));
+		assert_eq!(state.cached_value(KEY, 15, now), Some(&[42][..]));
+		// but not at earlier blocks
+		assert_eq!(state.cached_value(KEY, 9, now), None);
+		// and not for other keys
+		assert_eq!(state.cached_value("other", 10, now), None);
+
+		// change at earlier block doesn't invalidate the value
+		state.note_change(5);
+		assert_eq!(state.cached_value(KEY, 15, now), Some(&[42][..]));
+
+		// change at later block does
+		state.note_change(12);
+		assert_eq!(state.cached_value(KEY, 15, now), None);
+	}
+
+	#[test]
+	fn cached_value_is_not_used_without_subscription() {
+		let now = Instant::now();
+		let mut state = state_with_cached_value(10, now);
+		state.latest_change = None;
+		assert_eq!(state.cached_value(KEY, 10, now), None);
+
+		let mut state = state_with_cached_value(10, now);
+		state.is_subscribed = false;
+		assert_eq!(state.cached_value(KEY, 10, now), None);
+		assert!(!state.should_subscribe(now));
+		assert!(state.should_subscribe(now + RESUBSCRIBE_INTERVAL));
+	}
+
+	#[test]
+	fn cached_value_expires() {
+		let now = Instant::now();
+		let state = state_with_cached_value(10, now);
+		assert_eq!(state.cached_value(KEY, 10, now + MAX_CACHED_VALUE_AGE), None);
+	}
+}
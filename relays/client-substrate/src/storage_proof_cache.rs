@@ -0,0 +1,185 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cache of storage trie nodes that are used to generate storage proofs.
+//!
+//! Relay often needs to prove overlapping sets of storage keys at the same block - e.g. when messages
+//! of the same lane are delivered in several consecutive batches. Such proofs share a lot of trie
+//! nodes (at least upper nodes and the lane data nodes). So instead of asking node to prove all keys
+//! again, we keep trie nodes of previous proofs (keyed by the state root) and only ask node to prove
+//! keys that can't be read using cached nodes. The proof of all requested keys is then generated
+//! locally.
+
+use sp_core::{storage::StorageKey, Hasher};
+use sp_trie::{read_trie_value_with, Layout, MemoryDB, Recorder, StorageProof};
+use std::collections::{BTreeSet, VecDeque};
+
+/// Maximal number of states that we keep trie nodes for.
+const MAX_CACHED_STATES: usize = 4;
+
+/// Trie nodes of the single state.
+struct CachedState<H: Hasher> {
+	/// Hash of the block that has the state.
+	block_hash: H::Out,
+	/// State root.
+	state_root: H::Out,
+	/// Cached trie nodes.
+	db: MemoryDB<H>,
+}
+
+/// Cached trie nodes of several recent states.
+pub struct StorageProofCache<H: Hasher> {
+	states: VecDeque<CachedState<H>>,
+}
+
+impl<H: Hasher> Default for StorageProofCache<H> {
+	fn default() -> Self {
+		StorageProofCache {
+			states: VecDeque::new(),
+		}
+	}
+}
+
+impl<H: Hasher> StorageProofCache<H> {
+	/// Returns state root of given block, if it is cached.
+	pub fn state_root(&self, block_hash: &H::Out) -> Option<H::Out> {
+		self.states
+			.iter()
+			.find(|state| state.block_hash == *block_hash)
+			.map(|state| state.state_root)
+	}
+
+	/// Returns keys that can't be read using cached trie nodes of given state.
+	pub fn missing_keys(&self, state_root: &H::Out, keys: &[StorageKey]) -> Vec<StorageKey> {
+		match self.state(state_root) {
+			Some(state) => keys
+				.iter()
+				.filter(|key| {
+					let mut recorder = Recorder::new();
+					read_trie_value_with::<Layout<H>, _, _>(&state.db, state_root, &key.0, &mut recorder).is_err()
+				})
+				.cloned()
+				.collect(),
+			None => keys.to_vec(),
+		}
+	}
+
+	/// Remember trie nodes of the storage proof, generated at given block.
+	pub fn insert(&mut self, block_hash: H::Out, state_root: H::Out, proof: StorageProof) {
+		let db = proof.into_memory_db::<H>();
+		match self.states.iter_mut().find(|state| state.state_root == state_root) {
+			Some(state) => state.db.consolidate(db),
+			None => {
+				if self.states.len() == MAX_CACHED_STATES {
+					self.states.pop_front();
+				}
+				self.states.push_back(CachedState {
+					block_hash,
+					state_root,
+					db,
+				});
+			}
+		}
+	}
+
+	/// Generate proof of given keys using cached trie nodes. Returns `None` if some nodes are missing.
+	///
+	/// The proof only contains nodes that are required to read given keys.
+	pub fn prove(&self, state_root: &H::Out, keys: &[StorageKey]) -> Option<StorageProof> {
+		let state = self.state(state_root)?;
+		let mut recorder = Recorder::new();
+		for key in keys {
+			read_trie_value_with::<Layout<H>, _, _>(&state.db, state_root, &key.0, &mut recorder).ok()?;
+		}
+
+		let nodes = recorder
+			.drain()
+			.into_iter()
+			.map(|record| record.data)
+			.collect::<BTreeSet<_>>();
+		Some(StorageProof::new(nodes.into_iter().collect()))
+	}
+
+	/// Returns cached trie nodes of given state.
+	fn state(&self, state_root: &H::Out) -> Option<&CachedState<H>> {
+		self.states.iter().find(|state| state.state_root == *state_root)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bp_runtime::StorageProofChecker;
+	use sp_runtime::traits::BlakeTwo256;
+	use sp_trie::{TrieDBMut, TrieMut};
+
+	type H = BlakeTwo256;
+
+	const BLOCK_HASH: [u8; 32] = [42; 32];
+
+	fn key(key: &[u8]) -> StorageKey {
+		StorageKey(key.to_vec())
+	}
+
+	/// Returns state root and proof that contains all trie nodes.
+	fn full_state() -> (<H as Hasher>::Out, StorageProof) {
+		let mut db = MemoryDB::<H>::default();
+		let mut root = Default::default();
+		{
+			let mut trie = TrieDBMut::<Layout<H>>::new(&mut db, &mut root);
+			trie.insert(b"key1", &[1; 64]).unwrap();
+			trie.insert(b"key2", &[2; 64]).unwrap();
+			trie.insert(b"key3", &[3; 64]).unwrap();
+		}
+
+		let nodes = db.drain().into_iter().map(|(_, (node, _))| node).collect();
+		(root, StorageProof::new(nodes))
+	}
+
+	#[test]
+	fn proof_is_generated_using_cached_nodes() {
+		let (root, full_proof) = full_state();
+		let mut cache = StorageProofCache::<H>::default();
+		assert_eq!(cache.state_root(&BLOCK_HASH.into()), None);
+		assert_eq!(cache.missing_keys(&root, &[key(b"key1")]), vec![key(b"key1")]);
+
+		// when all nodes are cached, proof of single key only contains required nodes
+		cache.insert(BLOCK_HASH.into(), root, full_proof);
+		assert_eq!(cache.state_root(&BLOCK_HASH.into()), Some(root));
+		assert!(cache.missing_keys(&root, &[key(b"key1"), key(b"key2")]).is_empty());
+		let proof = cache.prove(&root, &[key(b"key1")]).unwrap();
+
+		let checker = StorageProofChecker::<H>::new(root, proof).unwrap();
+		assert_eq!(checker.read_value(b"key1"), Ok(Some(vec![1; 64])));
+		assert_eq!(checker.ensure_no_unused_nodes(), Ok(()));
+	}
+
+	#[test]
+	fn missing_keys_are_detected() {
+		let (root, full_proof) = full_state();
+		let mut cache = StorageProofCache::<H>::default();
+		cache.insert(BLOCK_HASH.into(), root, full_proof);
+		let key1_proof = cache.prove(&root, &[key(b"key1")]).unwrap();
+
+		let mut cache = StorageProofCache::<H>::default();
+		cache.insert(BLOCK_HASH.into(), root, key1_proof);
+		assert_eq!(
+			cache.missing_keys(&root, &[key(b"key1"), key(b"key2")]),
+			vec![key(b"key2")]
+		);
+		assert!(cache.prove(&root, &[key(b"key2")]).is_none());
+	}
+}
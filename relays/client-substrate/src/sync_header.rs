@@ -14,26 +14,30 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use bp_header_chain::find_grandpa_authorities_scheduled_change;
+use crate::finality_engine::{FinalityEngine, Grandpa};
+
 use finality_relay::SourceHeader as FinalitySourceHeader;
 use headers_relay::sync_types::SourceHeader;
 use num_traits::{CheckedSub, One};
 use relay_utils::HeaderId;
 use sp_runtime::traits::Header as HeaderT;
+use std::marker::PhantomData;
 
 /// Generic wrapper for `sp_runtime::traits::Header` based headers, that
 /// implements `headers_relay::sync_types::SourceHeader` and may be used in headers sync directly.
+///
+/// The `E` is the finality engine of the chain. It is used to detect mandatory headers.
 #[derive(Clone, Debug, PartialEq)]
-pub struct SyncHeader<Header>(Header);
+pub struct SyncHeader<Header, E = Grandpa>(Header, PhantomData<E>);
 
-impl<Header> SyncHeader<Header> {
+impl<Header, E> SyncHeader<Header, E> {
 	/// Extracts wrapped header from self.
 	pub fn into_inner(self) -> Header {
 		self.0
 	}
 }
 
-impl<Header> std::ops::Deref for SyncHeader<Header> {
+impl<Header, E> std::ops::Deref for SyncHeader<Header, E> {
 	type Target = Header;
 
 	fn deref(&self) -> &Self::Target {
@@ -41,13 +45,16 @@ impl<Header> std::ops::Deref for SyncHeader<Header> {
 	}
 }
 
-impl<Header> From<Header> for SyncHeader<Header> {
+impl<Header, E> From<Header> for SyncHeader<Header, E> {
 	fn from(header: Header) -> Self {
-		Self(header)
+		Self(header, PhantomData)
 	}
 }
 
-impl<Header: HeaderT> SourceHeader<Header::Hash, Header::Number> for SyncHeader<Header> {
+impl<Header: HeaderT, E> SourceHeader<Header::Hash, Header::Number> for SyncHeader<Header, E>
+where
+	E: Clone + std::fmt::Debug + PartialEq + Send + Sync,
+{
 	fn id(&self) -> HeaderId<Header::Hash, Header::Number> {
 		relay_utils::HeaderId(*self.0.number(), self.hash())
 	}
@@ -62,12 +69,12 @@ impl<Header: HeaderT> SourceHeader<Header::Hash, Header::Number> for SyncHeader<
 	}
 }
 
-impl<Header: HeaderT> FinalitySourceHeader<Header::Number> for SyncHeader<Header> {
+impl<Header: HeaderT, E: FinalityEngine<Header>> FinalitySourceHeader<Header::Number> for SyncHeader<Header, E> {
 	fn number(&self) -> Header::Number {
 		*self.0.number()
 	}
 
 	fn is_mandatory(&self) -> bool {
-		find_grandpa_authorities_scheduled_change(&self.0).is_some()
+		E::is_mandatory_header(&self.0)
 	}
 }
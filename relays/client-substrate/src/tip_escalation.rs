@@ -0,0 +1,99 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Congestion-aware tip escalation for time-critical transactions.
+//!
+//! The congestion of Substrate chain is reflected by the `TransactionPayment::NextFeeMultiplier`
+//! value. When it is above one, blocks are (or recently were) more than targeted fullness, so
+//! we're raising tip of our time-critical transactions. When congestion subsides, the multiplier
+//! goes back to (or below) one and we stop paying tips.
+
+use sp_core::storage::StorageKey;
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	FixedPointNumber, FixedPointOperand, FixedU128,
+};
+
+/// Tip escalation parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TipEscalation<Balance> {
+	/// Tip that is paid when fee multiplier is two. For other multiplier values, this tip is
+	/// scaled proportionally to `multiplier - 1`.
+	pub base_tip: Balance,
+	/// Maximal tip that we're ready to pay.
+	pub max_tip: Balance,
+}
+
+impl<Balance: FixedPointOperand + Zero + PartialOrd> TipEscalation<Balance> {
+	/// Returns tip that needs to be paid for the time-critical transaction, given current fee multiplier.
+	pub fn tip(&self, fee_multiplier: FixedU128) -> Balance {
+		if fee_multiplier <= FixedU128::one() {
+			return Zero::zero();
+		}
+
+		let tip = fee_multiplier
+			.saturating_sub(FixedU128::one())
+			.saturating_mul_int(self.base_tip);
+		if tip > self.max_tip {
+			self.max_tip
+		} else {
+			tip
+		}
+	}
+}
+
+/// Returns storage key of the `TransactionPayment::NextFeeMultiplier` value.
+pub fn next_fee_multiplier_key() -> StorageKey {
+	let mut key = sp_core::twox_128(b"TransactionPayment").to_vec();
+	key.extend_from_slice(&sp_core::twox_128(b"NextFeeMultiplier"));
+	StorageKey(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ESCALATION: TipEscalation<u64> = TipEscalation {
+		base_tip: 1_000,
+		max_tip: 5_000,
+	};
+
+	#[test]
+	fn no_tip_is_paid_when_chain_is_not_congested() {
+		assert_eq!(ESCALATION.tip(FixedU128::saturating_from_rational(1, 2)), 0);
+		assert_eq!(ESCALATION.tip(FixedU128::one()), 0);
+	}
+
+	#[test]
+	fn tip_is_scaled_by_fee_multiplier() {
+		assert_eq!(ESCALATION.tip(FixedU128::saturating_from_rational(3, 2)), 500);
+		assert_eq!(ESCALATION.tip(FixedU128::saturating_from_integer(2)), 1_000);
+		assert_eq!(ESCALATION.tip(FixedU128::saturating_from_integer(4)), 3_000);
+	}
+
+	#[test]
+	fn tip_never_exceeds_maximal_tip() {
+		assert_eq!(ESCALATION.tip(FixedU128::saturating_from_integer(100)), 5_000);
+	}
+
+	#[test]
+	fn next_fee_multiplier_key_is_computed_correctly() {
+		assert_eq!(
+			hex::encode(next_fee_multiplier_key().0),
+			"3f1467a096bcd71a5b6a0c8155e208103f2edf3bdf381debe331ab7446addfdc",
+		);
+	}
+}
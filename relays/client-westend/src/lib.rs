@@ -17,8 +17,8 @@
 //! Types used to connect to the Westend chain.
 
 use codec::Encode;
-use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, TransactionSignScheme};
-use sp_core::{storage::StorageKey, Pair};
+use relay_substrate_client::{Chain, ChainBase, ChainWithBalances, MultiSignerKeyPair, TransactionSignScheme};
+use sp_core::storage::StorageKey;
 use sp_runtime::{generic::SignedPayload, traits::IdentifyAccount};
 use std::time::Duration;
 
@@ -61,11 +61,12 @@ impl TransactionSignScheme for Westend {
 	type AccountKeyPair = sp_core::sr25519::Pair;
 	type SignedTransaction = bp_westend::UncheckedExtrinsic;
 
-	fn sign_transaction(
+	fn sign_transaction_with_tip(
 		genesis_hash: <Self::Chain as ChainBase>::Hash,
 		signer: &Self::AccountKeyPair,
 		signer_nonce: <Self::Chain as Chain>::Index,
 		call: <Self::Chain as Chain>::Call,
+		tip: <Self::Chain as Chain>::Balance,
 	) -> Self::SignedTransaction {
 		let raw_payload = SignedPayload::new(
 			call,
@@ -74,13 +75,13 @@ impl TransactionSignScheme for Westend {
 				sp_runtime::generic::Era::Immortal,
 				genesis_hash,
 				signer_nonce,
-				0,
+				tip,
 			),
 		)
 		.expect("SignedExtension never fails.");
 
-		let signature = raw_payload.using_encoded(|payload| signer.sign(payload));
-		let signer: sp_runtime::MultiSigner = signer.public().into();
+		let signature = raw_payload.using_encoded(|payload| signer.multi_sign(payload));
+		let signer = signer.multi_signer();
 		let (call, extra, _) = raw_payload.deconstruct();
 
 		bp_westend::UncheckedExtrinsic::new_signed(
@@ -21,6 +21,7 @@ use relay_utils::{
 	relay_loop::Client as RelayClient, FailedClient, MaybeConnectionError, StringifiedMaybeConnectionError,
 };
 use std::{
+	convert::TryInto,
 	fmt::{Debug, Display},
 	string::ToString,
 };
@@ -39,6 +40,9 @@ pub trait TransactionProofPipeline: 'static {
 }
 
 /// Block that is participating in exchange.
+///
+/// Block "transactions" are all block items which inclusion may be proved to the target chain.
+/// Usually these are transactions, but source may also expose e.g. events (logs) of the block.
 pub trait SourceBlock: 'static + Send + Sync {
 	/// Block hash type.
 	type Hash: 'static + Clone + Send + Sync + Debug + Display;
@@ -50,7 +54,7 @@ pub trait SourceBlock: 'static + Send + Sync {
 		+ Copy
 		+ Send
 		+ Sync
-		+ Into<u64>
+		+ TryInto<u64>
 		+ std::cmp::Ord
 		+ std::ops::Add<Output = Self::Number>
 		+ num_traits::One;
@@ -63,7 +67,7 @@ pub trait SourceBlock: 'static + Send + Sync {
 	fn transactions(&self) -> Vec<Self::Transaction>;
 }
 
-/// Transaction that is participating in exchange.
+/// Transaction (or event) that is participating in exchange.
 pub trait SourceTransaction: 'static + Send {
 	/// Transaction hash type.
 	type Hash: Debug + Display;
@@ -102,6 +106,13 @@ pub trait SourceClient<P: TransactionProofPipeline>: RelayClient {
 		-> Result<Option<(HeaderId<P>, usize)>, Self::Error>;
 	/// Prepare transaction proof.
 	async fn transaction_proof(&self, block: &P::Block, tx_index: usize) -> Result<P::TransactionProof, Self::Error>;
+	/// Returns `Ok(true)` if transaction is matching the relay filter and its proof may need to be relayed.
+	///
+	/// This check is performed before building the proof, so it should be cheap. Transactions that
+	/// are not matching are ignored by the relay. By default all transactions are matching.
+	async fn is_matching_transaction(&self, _block: &P::Block, _tx_index: usize) -> Result<bool, Self::Error> {
+		Ok(true)
+	}
 }
 
 /// Target client API.
@@ -153,6 +164,22 @@ pub async fn relay_block_transactions<P: TransactionProofPipeline>(
 		.skip(relayed_transactions.processed);
 	for (source_tx_index, source_tx) in transactions_to_process {
 		let result = async {
+			let is_matching_transaction = source_client
+				.is_matching_transaction(source_block, source_tx_index)
+				.await
+				.map_err(|err| {
+					(
+						FailedClient::Source,
+						StringifiedMaybeConnectionError::new(
+							err.is_connection_error(),
+							format!("Transaction matching has failed with {:?}", err),
+						),
+					)
+				})?;
+			if !is_matching_transaction {
+				return Ok(false);
+			}
+
 			let source_tx_id = format!("{}/{}", source_block.id().1, source_tx_index);
 			let source_tx_proof =
 				prepare_transaction_proof(source_client, &source_tx_id, source_block, source_tx_index)
@@ -544,6 +571,7 @@ pub(crate) mod tests {
 		pub block: Result<TestBlock, TestError>,
 		pub transaction_block: Result<Option<(TestHeaderId, usize)>, TestError>,
 		pub proofs_to_fail: HashMap<TestTransactionHash, TestError>,
+		pub transactions_to_ignore: HashSet<TestTransactionHash>,
 	}
 
 	impl TestTransactionsSource {
@@ -554,6 +582,7 @@ pub(crate) mod tests {
 					block: Ok(test_block()),
 					transaction_block: Ok(Some((test_block_id(), 0))),
 					proofs_to_fail: HashMap::new(),
+					transactions_to_ignore: HashSet::new(),
 				})),
 			}
 		}
@@ -595,6 +624,10 @@ pub(crate) mod tests {
 
 			Ok(TestTransactionProof(tx_hash))
 		}
+
+		async fn is_matching_transaction(&self, block: &TestBlock, index: usize) -> Result<bool, TestError> {
+			Ok(!self.data.lock().transactions_to_ignore.contains(&block.1[index].hash()))
+		}
 	}
 
 	#[derive(Clone)]
@@ -864,6 +897,43 @@ pub(crate) mod tests {
 		assert_eq!(target.data.lock().submitted_proofs, vec![],);
 	}
 
+	#[test]
+	fn relay_block_transactions_ignores_non_matching_transactions() {
+		let source = TestTransactionsSource::new(Box::new(|_| unreachable!("no ticks allowed")));
+		let target = TestTransactionsTarget::new(Box::new(|_| unreachable!("no ticks allowed")));
+
+		// tx#0 is not matching, so its proof is not even built
+		source
+			.data
+			.lock()
+			.transactions_to_ignore
+			.insert(test_transaction_hash(0));
+		source
+			.data
+			.lock()
+			.proofs_to_fail
+			.insert(test_transaction_hash(0), TestError(true));
+		target
+			.data
+			.lock()
+			.transactions_to_accept
+			.insert(test_transaction_hash(2));
+
+		let relayed_transactions = test_relay_block_transactions(&source, &target, Default::default());
+		assert_eq!(
+			relayed_transactions,
+			Ok(RelayedBlockTransactions {
+				processed: 3,
+				relayed: 1,
+				failed: 0,
+			}),
+		);
+		assert_eq!(
+			target.data.lock().submitted_proofs,
+			vec![TestTransactionProof(test_transaction_hash(2))],
+		);
+	}
+
 	#[test]
 	fn relay_block_transactions_fails_on_connection_error() {
 		let source = TestTransactionsSource::new(Box::new(|_| unreachable!("no ticks allowed")));
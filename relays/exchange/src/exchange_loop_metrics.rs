@@ -17,8 +17,9 @@
 //! Metrics for currency-exchange relay loop.
 
 use crate::exchange::{BlockNumberOf, RelayedBlockTransactions, TransactionProofPipeline};
-use relay_utils::metrics::{
-	metric_name, register, Counter, CounterVec, GaugeVec, Opts, PrometheusError, Registry, U64,
+use relay_utils::{
+	block_number_to_u64,
+	metrics::{metric_name, register, Counter, CounterVec, GaugeVec, Opts, PrometheusError, Registry, U64},
 };
 
 /// Exchange transactions relay metrics.
@@ -77,10 +78,10 @@ impl ExchangeLoopMetrics {
 	) {
 		self.best_block_numbers
 			.with_label_values(&["processed"])
-			.set(best_processed_block_number.into());
+			.set(block_number_to_u64(best_processed_block_number));
 		self.best_block_numbers
 			.with_label_values(&["known"])
-			.set(best_known_block_number.into());
+			.set(block_number_to_u64(best_known_block_number));
 
 		self.processed_blocks.inc();
 
@@ -14,10 +14,18 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Relaying [`currency-exchange`](../pallet_bridge_currency_exchange/index.html) application
-//! specific data. Currency exchange application allows exchanging tokens between bridged chains.
+//! Relaying proofs of source chain transactions (or events) to the target chain.
+//!
+//! The relay watches source chain for matching transactions (or events), builds their inclusion
+//! proofs and submits them to the target chain. It was originally built for the
+//! [`currency-exchange`](../pallet_bridge_currency_exchange/index.html) application, which allows
+//! exchanging tokens between bridged chains, but it knows nothing about the application: source
+//! client decides which transactions are matching and how to prove them, and target client
+//! decides which pallet consumes the proof. So any other proof-consuming pallet (validators set
+//! updates, custom applications, ...) may reuse the same pipeline.
+//!
 //! This module provides entrypoints for crafting and submitting (single and multiple)
-//! proof-of-exchange-at-source-chain transaction(s) to target chain.
+//! proof(s) of source chain transaction(s) to target chain.
 
 #![warn(missing_docs)]
 
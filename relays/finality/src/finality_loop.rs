@@ -19,12 +19,12 @@
 //! is the mandatory headers, which we always submit to the target node. For such headers, we
 //! assume that the persistent proof either exists, or will eventually become available.
 
+use crate::finality_loop_metrics::FinalitySyncLoopMetrics;
 use crate::{FinalityProof, FinalitySyncPipeline, SourceHeader};
 
 use async_trait::async_trait;
 use backoff::backoff::Backoff;
 use futures::{select, Future, FutureExt, Stream, StreamExt};
-use headers_relay::sync_loop_metrics::SyncLoopMetrics;
 use num_traits::{One, Saturating};
 use relay_utils::{
 	metrics::{GlobalMetrics, MetricsParams},
@@ -32,7 +32,9 @@ use relay_utils::{
 	retry_backoff, FailedClient, MaybeConnectionError,
 };
 use std::{
+	collections::VecDeque,
 	pin::Pin,
+	sync::{Arc, Mutex},
 	time::{Duration, Instant},
 };
 
@@ -58,6 +60,35 @@ pub struct FinalitySyncParams {
 	pub recent_finality_proofs_limit: usize,
 	/// Timeout before we treat our transactions as lost and restart the whole sync process.
 	pub stall_timeout: Duration,
+	/// Finality proofs submission budget. If `None`, the number of submitted proofs is not limited.
+	pub submission_budget: Option<FinalitySubmissionBudget>,
+	/// Number of the first source header that we want to read. If `None`, we start reading headers
+	/// from the best source header known to the target node.
+	///
+	/// This may be used to skip (potentially large) range of headers that we know are useless,
+	/// e.g. when the bridge pallet has been initialized at some checkpoint. Keep in mind that if
+	/// skipped range contains mandatory headers, the target node won't accept any further finality proofs.
+	pub start_block: Option<u32>,
+	/// If `true`, only mandatory headers (headers that are changing authorities set) are submitted
+	/// to the target node.
+	///
+	/// This may be used by bridges that only occasionally exchange messages and don't want to pay for
+	/// importing every finality proof. Mandatory headers are still submitted, so the light client on
+	/// the target chain keeps tracking authorities set changes of the source chain.
+	pub only_mandatory_headers: bool,
+}
+
+/// Finality proofs submission budget.
+///
+/// The budget limits number of finality proofs (headers) that we submit to the target node within
+/// given period. It is applied to all headers, including mandatory. So even during aggressive catch-up
+/// the relay won't spend more than the operator has allowed.
+#[derive(Debug, Clone)]
+pub struct FinalitySubmissionBudget {
+	/// Maximal number of headers that may be submitted within the `period`.
+	pub max_headers: usize,
+	/// The budget period.
+	pub period: Duration,
 }
 
 /// Source client used in finality synchronization loop.
@@ -78,6 +109,12 @@ pub trait SourceClient<P: FinalitySyncPipeline>: RelayClient {
 
 	/// Subscribe to new finality proofs.
 	async fn finality_proofs(&self) -> Result<Self::FinalityProofsStream, Self::Error>;
+
+	/// Returns true if error means that the source node is unable to serve requested header or its
+	/// finality proof (e.g. because it has been pruned).
+	fn is_missing_header_error(_error: &Self::Error) -> bool {
+		false
+	}
 }
 
 /// Target client used in finality synchronization loop.
@@ -104,9 +141,11 @@ pub async fn run<P: FinalitySyncPipeline>(
 	exit_signal: impl Future<Output = ()> + 'static + Send,
 ) -> Result<(), String> {
 	let exit_signal = exit_signal.shared();
+	let fatal_error = Arc::new(Mutex::new(None));
+	let loop_fatal_error = fatal_error.clone();
 	relay_utils::relay_loop(source_client, target_client)
 		.with_metrics(Some(metrics_prefix::<P>()), metrics_params)
-		.loop_metric(|registry, prefix| SyncLoopMetrics::new(registry, prefix))?
+		.loop_metric(|registry, prefix| FinalitySyncLoopMetrics::new(registry, prefix))?
 		.standalone_metric(|registry, prefix| GlobalMetrics::new(registry, prefix))?
 		.expose()
 		.await?
@@ -117,9 +156,16 @@ pub async fn run<P: FinalitySyncPipeline>(
 				sync_params.clone(),
 				metrics,
 				exit_signal.clone(),
+				loop_fatal_error.clone(),
 			)
 		})
-		.await
+		.await?;
+
+	// the loop may only stop because of exit signal, or because of fatal error
+	match fatal_error.lock().ok().and_then(|mut fatal_error| fatal_error.take()) {
+		Some(fatal_error) => Err(fatal_error),
+		None => Ok(()),
+	}
 }
 
 /// Unjustified headers container. Ordered by header number.
@@ -144,6 +190,8 @@ pub(crate) enum Error<P: FinalitySyncPipeline, SourceError, TargetError> {
 	Target(TargetError),
 	/// Finality proof for mandatory header is missing from the source node.
 	MissingMandatoryFinalityProof(P::Number),
+	/// The source node is unable to serve header (or its finality proof) that we need to sync.
+	MissingSourceHeader(P::Number),
 	/// The synchronization has stalled.
 	Stalled,
 }
@@ -162,6 +210,18 @@ where
 			_ => Ok(()),
 		}
 	}
+
+	fn fatal_error(&self) -> Option<String> {
+		match *self {
+			Error::MissingSourceHeader(ref number) => Some(format!(
+				"{} node is unable to serve header #{:?}. If it is running in pruning mode, either connect \
+				to an archive node, or initialize the bridge using more recent header (checkpoint)",
+				P::SOURCE_NAME,
+				number,
+			)),
+			_ => None,
+		}
+	}
 }
 
 /// Information about transaction that we have submitted.
@@ -201,14 +261,17 @@ struct FinalityLoopState<'a, P: FinalitySyncPipeline, FinalityProofsStream> {
 	recent_finality_proofs: &'a mut FinalityProofs<P>,
 	/// Last transaction that we have submitted to the target node.
 	last_transaction: Option<Transaction<P::Number>>,
+	/// Submission times of headers that we have submitted within current budget period.
+	recent_submissions: &'a mut VecDeque<Instant>,
 }
 
 async fn run_until_connection_lost<P: FinalitySyncPipeline>(
 	source_client: impl SourceClient<P>,
 	target_client: impl TargetClient<P>,
 	sync_params: FinalitySyncParams,
-	metrics_sync: Option<SyncLoopMetrics>,
+	metrics_sync: Option<FinalitySyncLoopMetrics>,
 	exit_signal: impl Future<Output = ()>,
+	fatal_error: Arc<Mutex<Option<String>>>,
 ) -> Result<(), FailedClient> {
 	let restart_finality_proofs_stream = || async {
 		source_client.finality_proofs().await.map_err(|error| {
@@ -235,6 +298,7 @@ async fn run_until_connection_lost<P: FinalitySyncPipeline>(
 	let mut progress = (Instant::now(), None);
 	let mut retry_backoff = retry_backoff();
 	let mut last_transaction = None;
+	let mut recent_submissions = VecDeque::new();
 
 	loop {
 		// run loop iteration
@@ -246,6 +310,7 @@ async fn run_until_connection_lost<P: FinalitySyncPipeline>(
 				finality_proofs_stream: &mut finality_proofs_stream,
 				recent_finality_proofs: &mut recent_finality_proofs,
 				last_transaction: last_transaction.clone(),
+				recent_submissions: &mut recent_submissions,
 			},
 			&sync_params,
 			&metrics_sync,
@@ -261,6 +326,13 @@ async fn run_until_connection_lost<P: FinalitySyncPipeline>(
 			}
 			Err(error) => {
 				log::error!(target: "bridge", "Finality sync loop iteration has failed with error: {:?}", error);
+				if let Some(error) = error.fatal_error() {
+					log::error!(target: "bridge", "{}", error);
+					if let Ok(mut fatal_error) = fatal_error.lock() {
+						*fatal_error = Some(error);
+					}
+					return Ok(());
+				}
 				error.fail_if_connection_error()?;
 				retry_backoff
 					.next_backoff()
@@ -287,7 +359,7 @@ async fn run_loop_iteration<P, SC, TC>(
 	target_client: &TC,
 	state: FinalityLoopState<'_, P, SC::FinalityProofsStream>,
 	sync_params: &FinalitySyncParams,
-	metrics_sync: &Option<SyncLoopMetrics>,
+	metrics_sync: &Option<FinalitySyncLoopMetrics>,
 ) -> Result<Option<Transaction<P::Number>>, Error<P, SC::Error, TC::Error>>
 where
 	P: FinalitySyncPipeline,
@@ -304,8 +376,8 @@ where
 		.await
 		.map_err(Error::Target)?;
 	if let Some(ref metrics_sync) = *metrics_sync {
-		metrics_sync.update_best_block_at_source(best_number_at_source);
-		metrics_sync.update_best_block_at_target(best_number_at_target);
+		metrics_sync.sync().update_best_block_at_source(best_number_at_source);
+		metrics_sync.sync().update_best_block_at_target(best_number_at_target);
 	}
 	*state.progress = print_sync_progress::<P>(*state.progress, best_number_at_source, best_number_at_target);
 
@@ -328,6 +400,26 @@ where
 		}
 	}
 
+	// if we have spent the whole submission budget, we need to wait until next period
+	if let Some(ref submission_budget) = sync_params.submission_budget {
+		prune_recent_submissions(state.recent_submissions, submission_budget.period);
+		if let Some(ref metrics_sync) = *metrics_sync {
+			metrics_sync
+				.update_submission_budget_utilization(state.recent_submissions.len(), submission_budget.max_headers);
+		}
+		if state.recent_submissions.len() >= submission_budget.max_headers {
+			log::debug!(
+				target: "bridge",
+				"Finality proofs submission budget is exhausted: {} headers have been submitted to {} within last {}s",
+				state.recent_submissions.len(),
+				P::TARGET_NAME,
+				submission_budget.period.as_secs(),
+			);
+
+			return Ok(None);
+		}
+	}
+
 	// submit new header if we have something new
 	match select_header_to_submit(
 		source_client,
@@ -358,6 +450,7 @@ where
 				.submit_finality_proof(header, justification)
 				.await
 				.map_err(Error::Target)?;
+			state.recent_submissions.push_back(new_transaction.time);
 			Ok(Some(new_transaction))
 		}
 		None => Ok(None),
@@ -386,17 +479,34 @@ where
 		best_number_at_source,
 	);
 
+	// if we have been asked to start sync from given header, let's pretend that all previous headers
+	// are already known to the target node
+	let best_number_to_skip = match sync_params.start_block {
+		Some(start_block) => std::cmp::max(
+			best_number_at_target,
+			P::Number::from(start_block).saturating_sub(One::one()),
+		),
+		None => best_number_at_target,
+	};
+
 	// read missing headers. if we see that the header schedules GRANDPA change, we need to
 	// submit this header
-	let selected_finality_proof = read_missing_headers::<P, SC, TC>(
-		source_client,
-		target_client,
-		best_number_at_source,
-		best_number_at_target,
-	)
-	.await?;
+	let selected_finality_proof =
+		read_missing_headers::<P, SC, TC>(source_client, target_client, best_number_at_source, best_number_to_skip)
+			.await?;
 	let (mut unjustified_headers, mut selected_finality_proof) = match selected_finality_proof {
 		SelectedFinalityProof::Mandatory(header, finality_proof) => return Ok(Some((header, finality_proof))),
+		_ if sync_params.only_mandatory_headers => {
+			// we are not submitting non-mandatory headers, but we still need to drain the finality
+			// proofs stream to keep it alive
+			read_finality_proofs_from_stream::<P, _>(finality_proofs_stream, recent_finality_proofs);
+			prune_recent_finality_proofs::<P>(
+				best_number_at_target,
+				recent_finality_proofs,
+				sync_params.recent_finality_proofs_limit,
+			);
+			return Ok(None);
+		}
 		SelectedFinalityProof::Regular(unjustified_headers, header, finality_proof) => {
 			(unjustified_headers, Some((header, finality_proof)))
 		}
@@ -427,6 +537,17 @@ where
 	Ok(selected_finality_proof)
 }
 
+/// Forget about submissions that have happened before current budget period.
+pub(crate) fn prune_recent_submissions(recent_submissions: &mut VecDeque<Instant>, period: Duration) {
+	while let Some(submission_time) = recent_submissions.front() {
+		if submission_time.elapsed() < period {
+			break;
+		}
+
+		recent_submissions.pop_front();
+	}
+}
+
 /// Finality proof that has been selected by the `read_missing_headers` function.
 pub(crate) enum SelectedFinalityProof<Header, FinalityProof> {
 	/// Mandatory header and its proof has been selected. We shall submit proof for this header.
@@ -454,10 +575,25 @@ pub(crate) async fn read_missing_headers<P: FinalitySyncPipeline, SC: SourceClie
 	let mut selected_finality_proof = None;
 	let mut header_number = best_number_at_target + One::one();
 	while header_number <= best_number_at_source {
-		let (header, finality_proof) = source_client
-			.header_and_finality_proof(header_number)
-			.await
-			.map_err(Error::Source)?;
+		let (header, finality_proof) =
+			source_client
+				.header_and_finality_proof(header_number)
+				.await
+				.map_err(|error| {
+					if SC::is_missing_header_error(&error) {
+						log::error!(
+							target: "bridge",
+							"{} node is unable to serve header #{:?}. Missing headers range: [{:?}; {:?}]",
+							P::SOURCE_NAME,
+							header_number,
+							header_number,
+							best_number_at_source,
+						);
+						Error::MissingSourceHeader(header_number)
+					} else {
+						Error::Source(error)
+					}
+				})?;
 		let is_mandatory = header.is_mandatory();
 
 		match (is_mandatory, finality_proof) {
@@ -0,0 +1,60 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics for finality proofs synchronization loop.
+
+use headers_relay::sync_loop_metrics::SyncLoopMetrics;
+use relay_utils::metrics::{metric_name, register, Gauge, PrometheusError, Registry, F64};
+
+/// Finality proofs sync metrics.
+#[derive(Clone)]
+pub struct FinalitySyncLoopMetrics {
+	/// Generic headers sync metrics.
+	sync: SyncLoopMetrics,
+	/// Utilization of the finality proofs submission budget.
+	submission_budget_utilization: Gauge<F64>,
+}
+
+impl FinalitySyncLoopMetrics {
+	/// Create and register finality loop metrics.
+	pub fn new(registry: &Registry, prefix: Option<&str>) -> Result<Self, PrometheusError> {
+		Ok(FinalitySyncLoopMetrics {
+			sync: SyncLoopMetrics::new(registry, prefix)?,
+			submission_budget_utilization: register(
+				Gauge::new(
+					metric_name(prefix, "submission_budget_utilization"),
+					"Part of the finality proofs submission budget that has been spent during current period",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Returns generic headers sync metrics.
+	pub fn sync(&self) -> &SyncLoopMetrics {
+		&self.sync
+	}
+
+	/// Update finality proofs submission budget utilization.
+	pub fn update_submission_budget_utilization(&self, submitted_headers: usize, max_headers: usize) {
+		let utilization = if max_headers != 0 {
+			submitted_headers as f64 / max_headers as f64
+		} else {
+			1.0
+		};
+		self.submission_budget_utilization.set(utilization);
+	}
+}
@@ -19,23 +19,28 @@
 #![cfg(test)]
 
 use crate::finality_loop::{
-	prune_recent_finality_proofs, read_finality_proofs_from_stream, run, select_better_recent_finality_proof,
-	FinalityProofs, FinalitySyncParams, SourceClient, TargetClient,
+	prune_recent_finality_proofs, prune_recent_submissions, read_finality_proofs_from_stream, run,
+	select_better_recent_finality_proof, FinalityProofs, FinalitySyncParams, SourceClient, TargetClient,
 };
-use crate::{FinalityProof, FinalitySyncPipeline, SourceHeader};
+use crate::{FinalityProof, FinalitySyncPipeline, SharedSourceClient, SourceHeader};
 
 use async_trait::async_trait;
 use futures::{FutureExt, Stream, StreamExt};
 use parking_lot::Mutex;
 use relay_utils::{metrics::MetricsParams, relay_loop::Client as RelayClient, MaybeConnectionError};
-use std::{collections::HashMap, pin::Pin, sync::Arc, time::Duration};
+use std::{
+	collections::{HashMap, VecDeque},
+	pin::Pin,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 type IsMandatory = bool;
 type TestNumber = u64;
 
 #[derive(Debug, Clone)]
 enum TestError {
-	NonConnection,
+	MissingHeader,
 }
 
 impl MaybeConnectionError for TestError {
@@ -123,7 +128,7 @@ impl SourceClient<TestFinalitySyncPipeline> for TestSourceClient {
 		data.source_headers
 			.get(&number)
 			.cloned()
-			.ok_or(TestError::NonConnection)
+			.ok_or(TestError::MissingHeader)
 	}
 
 	async fn finality_proofs(&self) -> Result<Self::FinalityProofsStream, TestError> {
@@ -131,6 +136,10 @@ impl SourceClient<TestFinalitySyncPipeline> for TestSourceClient {
 		(self.on_method_call)(&mut *data);
 		Ok(futures::stream::iter(data.source_proofs.clone()).boxed())
 	}
+
+	fn is_missing_header_error(error: &TestError) -> bool {
+		matches!(*error, TestError::MissingHeader)
+	}
 }
 
 #[derive(Clone)]
@@ -165,7 +174,11 @@ impl TargetClient<TestFinalitySyncPipeline> for TestTargetClient {
 	}
 }
 
-fn run_sync_loop(state_function: impl Fn(&mut ClientsData) -> bool + Send + Sync + 'static) -> ClientsData {
+fn run_sync_loop(
+	start_block: Option<u32>,
+	only_mandatory_headers: bool,
+	state_function: impl Fn(&mut ClientsData) -> bool + Send + Sync + 'static,
+) -> (ClientsData, Result<(), String>) {
 	let (exit_sender, exit_receiver) = futures::channel::mpsc::unbounded();
 	let internal_state_function: Arc<dyn Fn(&mut ClientsData) + Send + Sync> = Arc::new(move |data| {
 		if state_function(data) {
@@ -200,9 +213,12 @@ fn run_sync_loop(state_function: impl Fn(&mut ClientsData) -> bool + Send + Sync
 		tick: Duration::from_secs(0),
 		recent_finality_proofs_limit: 1024,
 		stall_timeout: Duration::from_secs(1),
+		submission_budget: None,
+		start_block,
+		only_mandatory_headers,
 	};
 
-	let _ = async_std::task::block_on(run(
+	let result = async_std::task::block_on(run(
 		source_client,
 		target_client,
 		sync_params,
@@ -211,12 +227,12 @@ fn run_sync_loop(state_function: impl Fn(&mut ClientsData) -> bool + Send + Sync
 	));
 
 	let clients_data = clients_data.lock().clone();
-	clients_data
+	(clients_data, result)
 }
 
 #[test]
 fn finality_sync_loop_works() {
-	let client_data = run_sync_loop(|data| {
+	let (client_data, result) = run_sync_loop(None, false, |data| {
 		// header#7 has persistent finality proof, but it isn't mandatory => it isn't submitted, because
 		// header#8 has persistent finality proof && it is mandatory => it is submitted
 		// header#9 has persistent finality proof, but it isn't mandatory => it is submitted, because
@@ -257,6 +273,65 @@ fn finality_sync_loop_works() {
 			(TestSourceHeader(false, 16), TestFinalityProof(16)),
 		],
 	);
+	assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn finality_sync_loop_stops_when_source_node_is_unable_to_serve_header() {
+	let (client_data, result) = run_sync_loop(None, false, |data| {
+		// header#6 has been pruned from the source node
+		data.source_headers.remove(&6);
+		false
+	});
+
+	assert!(client_data.target_headers.is_empty());
+	assert!(result.unwrap_err().contains("unable to serve header #6"));
+}
+
+#[test]
+fn finality_sync_loop_skips_headers_before_start_block() {
+	let (client_data, result) = run_sync_loop(Some(9), false, |data| {
+		// headers 6..8 are not even read from the source node
+		data.source_headers.remove(&6);
+		data.target_best_block_number == 9
+	});
+
+	assert_eq!(
+		client_data.target_headers,
+		vec![(TestSourceHeader(false, 9), TestFinalityProof(9))]
+	);
+	assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn finality_sync_loop_only_submits_mandatory_headers() {
+	let (client_data, result) = run_sync_loop(None, true, |data| {
+		// header#7 and header#9 have persistent finality proofs, but they aren't mandatory =>
+		// only header#8 is submitted
+		//
+		// once this ^^^ is done, we generate more blocks, including mandatory header#13
+		if data.target_best_block_number == 8 {
+			data.source_best_block_number = 14;
+			data.source_headers.insert(11, (TestSourceHeader(false, 11), None));
+			data.source_headers
+				.insert(12, (TestSourceHeader(false, 12), Some(TestFinalityProof(12))));
+			data.source_headers
+				.insert(13, (TestSourceHeader(true, 13), Some(TestFinalityProof(13))));
+			data.source_headers
+				.insert(14, (TestSourceHeader(false, 14), Some(TestFinalityProof(14))));
+		}
+
+		data.target_best_block_number == 13
+	});
+
+	assert_eq!(
+		client_data.target_headers,
+		vec![
+			(TestSourceHeader(true, 8), TestFinalityProof(8)),
+			(TestSourceHeader(true, 13), TestFinalityProof(13)),
+		],
+	);
+	assert_eq!(result, Ok(()));
 }
 
 #[test]
@@ -402,3 +477,104 @@ fn prune_recent_finality_proofs_works() {
 	prune_recent_finality_proofs::<TestFinalitySyncPipeline>(20, &mut recent_finality_proofs, 2);
 	assert_eq!(&original_recent_finality_proofs[5..], recent_finality_proofs,);
 }
+
+#[test]
+fn prune_recent_submissions_works() {
+	let now = Instant::now();
+	let original_recent_submissions: VecDeque<_> = vec![
+		now - Duration::from_secs(30),
+		now - Duration::from_secs(20),
+		now - Duration::from_secs(10),
+		now,
+	]
+	.into_iter()
+	.collect();
+
+	// when nothing is pruned
+	let mut recent_submissions = original_recent_submissions.clone();
+	prune_recent_submissions(&mut recent_submissions, Duration::from_secs(60));
+	assert_eq!(original_recent_submissions, recent_submissions);
+
+	// when submissions before budget period are pruned
+	let mut recent_submissions = original_recent_submissions.clone();
+	prune_recent_submissions(&mut recent_submissions, Duration::from_secs(15));
+	assert_eq!(
+		original_recent_submissions
+			.iter()
+			.skip(2)
+			.cloned()
+			.collect::<VecDeque<_>>(),
+		recent_submissions,
+	);
+}
+
+#[test]
+fn shared_source_client_caches_headers_and_finality_proofs() {
+	let header_and_proof = |number| (TestSourceHeader(false, number), Some(TestFinalityProof(number)));
+	let data = Arc::new(Mutex::new(ClientsData {
+		source_headers: vec![(7, header_and_proof(7))].into_iter().collect(),
+		..Default::default()
+	}));
+	let source_client = SharedSourceClient::<TestFinalitySyncPipeline, _>::new(
+		TestSourceClient {
+			on_method_call: Arc::new(|_| {}),
+			data: data.clone(),
+		},
+		1,
+	);
+	let read_header = |number| {
+		async_std::task::block_on(SourceClient::<TestFinalitySyncPipeline>::header_and_finality_proof(
+			&source_client,
+			number,
+		))
+		.map_err(drop)
+	};
+
+	// header is read from the source node
+	assert_eq!(read_header(7), Ok(header_and_proof(7)));
+
+	// header is read from the cache
+	data.lock().source_headers.clear();
+	assert_eq!(read_header(7), Ok(header_and_proof(7)));
+
+	// oldest header is pruned from the cache when new header is inserted
+	data.lock().source_headers.insert(8, header_and_proof(8));
+	assert_eq!(read_header(8), Ok(header_and_proof(8)));
+	assert_eq!(read_header(7), Err(()));
+}
+
+#[test]
+fn shared_source_client_forwards_finality_proofs() {
+	let source_client = SharedSourceClient::<TestFinalitySyncPipeline, _>::new(
+		TestSourceClient {
+			on_method_call: Arc::new(|_| {}),
+			data: Arc::new(Mutex::new(ClientsData {
+				source_proofs: vec![TestFinalityProof(12), TestFinalityProof(14)],
+				..Default::default()
+			})),
+		},
+		1,
+	);
+	let read_finality_proofs = || {
+		async_std::task::block_on(async {
+			SourceClient::<TestFinalitySyncPipeline>::finality_proofs(&source_client)
+				.await
+				.map_err(drop)
+				.unwrap()
+				.collect::<Vec<_>>()
+				.await
+		})
+	};
+
+	// proofs from the source subscription are forwarded, and the stream is closed when the
+	// source subscription ends
+	assert_eq!(
+		read_finality_proofs(),
+		vec![TestFinalityProof(12), TestFinalityProof(14)]
+	);
+	// then the source subscription is restarted
+	assert_eq!(
+		read_finality_proofs(),
+		vec![TestFinalityProof(12), TestFinalityProof(14)]
+	);
+}
@@ -18,14 +18,24 @@
 //! proofs, as opposed to headers synchronization loop, which is built around headers. The headers
 //! are still submitted to the target node, but are treated as auxiliary data as we are not trying
 //! to submit all source headers to the target node.
+//!
+//! When the same source chain is bridged with multiple target chains, multiple loops may be started
+//! with clones of the same `SharedSourceClient`. Then the source finality proofs subscription and
+//! headers fetching work are shared by all loops.
 
-pub use crate::finality_loop::{metrics_prefix, run, FinalitySyncParams, SourceClient, TargetClient};
+pub use crate::finality_loop::{
+	metrics_prefix, run, FinalitySubmissionBudget, FinalitySyncParams, SourceClient, TargetClient,
+};
+pub use crate::finality_loop_metrics::FinalitySyncLoopMetrics;
+pub use crate::shared_source::SharedSourceClient;
 
 use bp_header_chain::FinalityProof;
 use std::fmt::Debug;
 
 mod finality_loop;
+mod finality_loop_metrics;
 mod finality_loop_tests;
+mod shared_source;
 
 /// Finality proofs synchronization pipeline.
 pub trait FinalitySyncPipeline: 'static + Clone + Debug + Send + Sync {
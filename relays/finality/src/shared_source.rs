@@ -0,0 +1,191 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Finality source client that may be shared by multiple finality synchronization loops.
+//!
+//! When the same source chain is bridged with several target chains, every loop would normally
+//! subscribe to source finality proofs and fetch the same headers and proofs from the source node
+//! on its own. The shared client makes all loops use single subscription and the same recently
+//! fetched headers and finality proofs.
+
+use crate::finality_loop::SourceClient;
+use crate::FinalitySyncPipeline;
+
+use async_std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use futures::{
+	channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+	Stream, StreamExt,
+};
+use relay_utils::relay_loop::Client as RelayClient;
+use std::collections::BTreeMap;
+
+/// Source client that may be shared by multiple finality synchronization loops.
+///
+/// The client may be used by loops of any pipelines, that have the same source chain. Finality
+/// proofs, received from the single source subscription, are forwarded to every loop. Headers and
+/// finality proofs, read by one loop, are cached and served to other loops without additional
+/// requests to the source node.
+///
+/// Cloning `SharedSourceClient` is a cheap operation.
+pub struct SharedSourceClient<P: FinalitySyncPipeline, SC> {
+	client: SC,
+	state: Arc<Mutex<SharedSourceState<P>>>,
+}
+
+/// Shared state of the source client.
+struct SharedSourceState<P: FinalitySyncPipeline> {
+	/// True if we have active finality proofs subscription.
+	is_subscribed: bool,
+	/// Senders of finality proofs streams that have been returned to synchronization loops.
+	subscribers: Vec<UnboundedSender<P::FinalityProof>>,
+	/// Maximal number of headers in the cache.
+	cache_size: usize,
+	/// Recently read headers and their finality proofs, ordered by header number.
+	cache: BTreeMap<P::Number, (P::Header, Option<P::FinalityProof>)>,
+}
+
+impl<P: FinalitySyncPipeline, SC: Clone> Clone for SharedSourceClient<P, SC> {
+	fn clone(&self) -> Self {
+		SharedSourceClient {
+			client: self.client.clone(),
+			state: self.state.clone(),
+		}
+	}
+}
+
+impl<P: FinalitySyncPipeline, SC> SharedSourceClient<P, SC> {
+	/// Create new shared source client, that caches up to `cache_size` headers.
+	///
+	/// The finality proofs subscription is started lazily, when the first loop asks for it.
+	pub fn new(client: SC, cache_size: usize) -> Self {
+		SharedSourceClient {
+			client,
+			state: Arc::new(Mutex::new(SharedSourceState {
+				is_subscribed: false,
+				subscribers: Vec::new(),
+				cache_size,
+				cache: BTreeMap::new(),
+			})),
+		}
+	}
+}
+
+#[async_trait]
+impl<P, SC> RelayClient for SharedSourceClient<P, SC>
+where
+	P: FinalitySyncPipeline,
+	SC: RelayClient,
+{
+	type Error = SC::Error;
+
+	async fn reconnect(&mut self) -> Result<(), Self::Error> {
+		self.client.reconnect().await
+	}
+}
+
+#[async_trait]
+impl<P, Q, SC> SourceClient<Q> for SharedSourceClient<P, SC>
+where
+	P: FinalitySyncPipeline,
+	Q: FinalitySyncPipeline<Hash = P::Hash, Number = P::Number, Header = P::Header, FinalityProof = P::FinalityProof>,
+	SC: SourceClient<P>,
+	SC::FinalityProofsStream: 'static,
+{
+	type FinalityProofsStream = UnboundedReceiver<P::FinalityProof>;
+
+	async fn best_finalized_block_number(&self) -> Result<P::Number, SC::Error> {
+		self.client.best_finalized_block_number().await
+	}
+
+	async fn header_and_finality_proof(
+		&self,
+		number: P::Number,
+	) -> Result<(P::Header, Option<P::FinalityProof>), SC::Error> {
+		if let Some(cached) = self.state.lock().await.cache.get(&number) {
+			return Ok(cached.clone());
+		}
+
+		let header_and_finality_proof = self.client.header_and_finality_proof(number).await?;
+		self.state
+			.lock()
+			.await
+			.insert(number, header_and_finality_proof.clone());
+		Ok(header_and_finality_proof)
+	}
+
+	async fn finality_proofs(&self) -> Result<Self::FinalityProofsStream, SC::Error> {
+		let mut state = self.state.lock().await;
+		let (sender, receiver) = unbounded();
+		if !state.is_subscribed {
+			let subscription = self.client.finality_proofs().await?;
+			state.is_subscribed = true;
+			async_std::task::spawn(forward_finality_proofs(subscription, self.state.clone()));
+		}
+		state.subscribers.push(sender);
+
+		Ok(receiver)
+	}
+
+	fn is_missing_header_error(error: &SC::Error) -> bool {
+		SC::is_missing_header_error(error)
+	}
+}
+
+impl<P: FinalitySyncPipeline> SharedSourceState<P> {
+	/// Insert header and its finality proof into the cache, pruning oldest cache entries.
+	fn insert(&mut self, number: P::Number, header_and_finality_proof: (P::Header, Option<P::FinalityProof>)) {
+		self.cache.insert(number, header_and_finality_proof);
+		while self.cache.len() > self.cache_size {
+			let oldest_number = match self.cache.keys().next() {
+				Some(oldest_number) => *oldest_number,
+				None => break,
+			};
+			self.cache.remove(&oldest_number);
+		}
+	}
+}
+
+/// Read finality proofs from the source subscription and forward them to all subscribers.
+///
+/// When the source subscription ends, all subscribers' streams are closed, so that the loops may
+/// restart them. When all subscribers have dropped their streams, the source subscription is
+/// dropped too.
+async fn forward_finality_proofs<P: FinalitySyncPipeline>(
+	subscription: impl Stream<Item = P::FinalityProof>,
+	state: Arc<Mutex<SharedSourceState<P>>>,
+) {
+	futures::pin_mut!(subscription);
+	loop {
+		let finality_proof = subscription.next().await;
+
+		let mut state = state.lock().await;
+		match finality_proof {
+			Some(finality_proof) => state
+				.subscribers
+				.retain(|subscriber| subscriber.unbounded_send(finality_proof.clone()).is_ok()),
+			None => {
+				log::debug!(target: "bridge", "{} finality proofs subscription has ended", P::SOURCE_NAME);
+				state.subscribers.clear();
+			}
+		}
+
+		if state.subscribers.is_empty() {
+			state.is_subscribed = false;
+			return;
+		}
+	}
+}
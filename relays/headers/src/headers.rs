@@ -23,7 +23,7 @@
 use crate::sync_types::{HeaderIdOf, HeaderStatus, HeadersSyncPipeline, QueuedHeader, SourceHeader};
 
 use linked_hash_map::LinkedHashMap;
-use num_traits::{One, Zero};
+use num_traits::{One, Saturating, Zero};
 use relay_utils::HeaderId;
 use std::{
 	collections::{btree_map::Entry as BTreeMapEntry, hash_map::Entry as HashMapEntry, BTreeMap, HashMap, HashSet},
@@ -34,6 +34,8 @@ type HeadersQueue<P> =
 	BTreeMap<<P as HeadersSyncPipeline>::Number, HashMap<<P as HeadersSyncPipeline>::Hash, QueuedHeader<P>>>;
 type SyncedChildren<P> =
 	BTreeMap<<P as HeadersSyncPipeline>::Number, HashMap<<P as HeadersSyncPipeline>::Hash, HashSet<HeaderIdOf<P>>>>;
+type QueuedChildren<P> =
+	BTreeMap<<P as HeadersSyncPipeline>::Number, HashMap<<P as HeadersSyncPipeline>::Hash, HashSet<HeaderIdOf<P>>>>;
 type KnownHeaders<P> =
 	BTreeMap<<P as HeadersSyncPipeline>::Number, HashMap<<P as HeadersSyncPipeline>::Hash, HeaderStatus>>;
 
@@ -68,6 +70,9 @@ pub struct QueuedHeaders<P: HeadersSyncPipeline> {
 	/// Synced headers childrens. We need it to support case when header is synced, but some of
 	/// its parents are incomplete.
 	synced_children: SyncedChildren<P>,
+	/// Ids of all queued headers, mapped by their parent id. We need it to find header
+	/// descendants without scanning queues.
+	queued_children: QueuedChildren<P>,
 	/// Pointers to all headers that we ever seen and we believe we can touch in the future.
 	known_headers: KnownHeaders<P>,
 	/// Headers that are waiting for completion data from source node. Mapped (and auto-sorted
@@ -102,6 +107,7 @@ impl<P: HeadersSyncPipeline> Default for QueuedHeaders<P> {
 			incomplete: HeadersQueue::new(),
 			submitted: HeadersQueue::new(),
 			synced_children: SyncedChildren::<P>::new(),
+			queued_children: QueuedChildren::<P>::new(),
 			known_headers: KnownHeaders::<P>::new(),
 			incomplete_headers: LinkedHashMap::new(),
 			completion_data: LinkedHashMap::new(),
@@ -252,6 +258,7 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 		let parent_status = self.status(&parent_id);
 		let header = QueuedHeader::new(header);
 
+		insert_queued_child::<P>(&mut self.queued_children, &parent_id, id);
 		let status = match parent_status {
 			HeaderStatus::Unknown | HeaderStatus::MaybeOrphan => {
 				insert_header(&mut self.maybe_orphan, id, header);
@@ -293,6 +300,7 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 			move_header_descendants::<P>(
 				&mut [&mut self.maybe_orphan],
 				&mut self.orphan,
+				&self.queued_children,
 				&mut self.known_headers,
 				HeaderStatus::Orphan,
 				id,
@@ -303,6 +311,7 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 		move_header_descendants::<P>(
 			&mut [&mut self.maybe_orphan, &mut self.orphan],
 			&mut self.maybe_extra,
+			&self.queued_children,
 			&mut self.known_headers,
 			HeaderStatus::MaybeExtra,
 			id,
@@ -435,6 +444,7 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 				move_header_descendants::<P>(
 					&mut [&mut self.ready, &mut self.submitted],
 					&mut self.incomplete,
+					&self.queued_children,
 					&mut self.known_headers,
 					HeaderStatus::Incomplete,
 					&move_origin,
@@ -483,6 +493,7 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 				move_header_descendants::<P>(
 					&mut [&mut self.incomplete],
 					&mut self.ready,
+					&self.queued_children,
 					&mut self.known_headers,
 					HeaderStatus::Ready,
 					&move_origin,
@@ -527,6 +538,43 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 		queued_incomplete_header(&mut self.completion_data, |_| true)
 	}
 
+	/// Forget header and all its queued descendants, because they are no longer part of
+	/// the canonical source chain.
+	///
+	/// Headers that are already synced are never forgotten, but their queued descendants are.
+	pub fn invalidate(&mut self, id: &HeaderIdOf<P>) {
+		let mut headers_to_invalidate = vec![*id];
+		while let Some(current) = headers_to_invalidate.pop() {
+			if let Some(children) = header_queued_children::<P>(&self.queued_children, &current) {
+				headers_to_invalidate.extend(children.iter().cloned());
+			}
+
+			let header = match self.status(&current) {
+				HeaderStatus::Unknown | HeaderStatus::Synced => continue,
+				HeaderStatus::MaybeOrphan => remove_header(&mut self.maybe_orphan, &current),
+				HeaderStatus::Orphan => remove_header(&mut self.orphan, &current),
+				HeaderStatus::MaybeExtra => remove_header(&mut self.maybe_extra, &current),
+				HeaderStatus::Extra => remove_header(&mut self.extra, &current),
+				HeaderStatus::Ready => remove_header(&mut self.ready, &current),
+				HeaderStatus::Incomplete => remove_header(&mut self.incomplete, &current),
+				HeaderStatus::Submitted => remove_header(&mut self.submitted, &current),
+			}
+			.expect("header has a given status; given queue has the header; qed");
+
+			log::debug!(
+				target: "bridge",
+				"Forgetting non-canonical {} header {:?}",
+				P::SOURCE_NAME,
+				current,
+			);
+
+			remove_queued_child::<P>(&mut self.queued_children, &header.parent_id(), &current);
+			remove_header_status::<P>(&mut self.known_headers, &current);
+			self.incomplete_headers.remove(&current);
+			self.completion_data.remove(&current);
+		}
+	}
+
 	/// Prune and never accept headers before this block.
 	pub fn prune(&mut self, prune_border: P::Number) {
 		if prune_border <= self.prune_border {
@@ -541,6 +589,8 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 		prune_queue(&mut self.submitted, prune_border);
 		prune_queue(&mut self.incomplete, prune_border);
 		self.synced_children = self.synced_children.split_off(&prune_border);
+		// children of the header right before the border are still in the queue
+		self.queued_children = self.queued_children.split_off(&prune_border.saturating_sub(One::one()));
 		prune_known_headers::<P>(&mut self.known_headers, prune_border);
 		self.prune_border = prune_border;
 	}
@@ -555,6 +605,7 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 		self.incomplete.clear();
 		self.submitted.clear();
 		self.synced_children.clear();
+		self.queued_children.clear();
 		self.known_headers.clear();
 		self.best_synced_number = Zero::zero();
 		self.prune_border = Zero::zero();
@@ -610,6 +661,7 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 				HeaderStatus::Synced => break,
 			}
 			.expect("header has a given status; given queue has the header; qed");
+			remove_queued_child::<P>(&mut self.queued_children, &header.parent_id(), &current);
 
 			// remember ids of all the children of the current header
 			let synced_children_entry = self
@@ -618,27 +670,8 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 				.or_default()
 				.entry(current.1)
 				.or_default();
-			let all_queues = [
-				&self.maybe_orphan,
-				&self.orphan,
-				&self.maybe_extra,
-				&self.extra,
-				&self.ready,
-				&self.incomplete,
-				&self.submitted,
-			];
-			for queue in &all_queues {
-				let children_from_queue = queue
-					.get(&(current.0 + One::one()))
-					.map(|potential_children| {
-						potential_children
-							.values()
-							.filter(|potential_child| potential_child.header().parent_id() == current)
-							.map(|child| child.id())
-							.collect::<Vec<_>>()
-					})
-					.unwrap_or_default();
-				synced_children_entry.extend(children_from_queue);
+			if let Some(queued_children) = header_queued_children::<P>(&self.queued_children, &current) {
+				synced_children_entry.extend(queued_children.iter().cloned());
 			}
 			if let Some(previous_current) = previous_current {
 				synced_children_entry.insert(previous_current);
@@ -662,6 +695,7 @@ impl<P: HeadersSyncPipeline> QueuedHeaders<P> {
 		move_header_descendants::<P>(
 			&mut [&mut self.maybe_orphan, &mut self.orphan],
 			&mut self.maybe_extra,
+			&self.queued_children,
 			&mut self.known_headers,
 			HeaderStatus::MaybeExtra,
 			id,
@@ -717,58 +751,83 @@ fn move_header<P: HeadersSyncPipeline>(
 }
 
 /// Move all descendant headers from the source to destination queue.
+///
+/// Descendants are found using queued children index, so only headers that are actually
+/// moved are touched.
 fn move_header_descendants<P: HeadersSyncPipeline>(
 	source_queues: &mut [&mut HeadersQueue<P>],
 	destination_queue: &mut HeadersQueue<P>,
+	queued_children: &QueuedChildren<P>,
 	known_headers: &mut KnownHeaders<P>,
 	destination_status: HeaderStatus,
 	id: &HeaderIdOf<P>,
 ) {
-	let mut current_number = id.0 + One::one();
-	let mut current_parents = HashSet::new();
-	current_parents.insert(id.1);
-
-	while !current_parents.is_empty() {
-		let mut next_parents = HashSet::new();
-		for source_queue in source_queues.iter_mut() {
-			let mut source_entry = match source_queue.entry(current_number) {
-				BTreeMapEntry::Occupied(source_entry) => source_entry,
-				BTreeMapEntry::Vacant(_) => continue,
-			};
+	let mut current_parents = vec![*id];
+	while let Some(current_parent) = current_parents.pop() {
+		let children = match header_queued_children::<P>(queued_children, &current_parent) {
+			Some(children) => children,
+			None => continue,
+		};
 
-			let mut headers_to_move = Vec::new();
-			let children_at_number = source_entry.get().keys().cloned().collect::<Vec<_>>();
-			for key in children_at_number {
-				let entry = match source_entry.get_mut().entry(key) {
-					HashMapEntry::Occupied(entry) => entry,
-					HashMapEntry::Vacant(_) => unreachable!("iterating existing keys; qed"),
-				};
-
-				if current_parents.contains(&entry.get().header().parent_id().1) {
-					let header_to_move = entry.remove();
-					let header_to_move_id = header_to_move.id();
-					headers_to_move.push((header_to_move_id, header_to_move));
-					set_header_status::<P>(known_headers, &header_to_move_id, destination_status);
-				}
+		for child in children {
+			let header_to_move = source_queues
+				.iter_mut()
+				.find_map(|source_queue| remove_header::<P>(source_queue, child));
+			if let Some(header_to_move) = header_to_move {
+				insert_header(destination_queue, *child, header_to_move);
+				set_header_status::<P>(known_headers, child, destination_status);
+				current_parents.push(*child);
 			}
+		}
+	}
+}
 
-			if source_entry.get().is_empty() {
-				source_entry.remove();
-			}
+/// Remember that the header with given id is queued and its parent is `parent_id`.
+fn insert_queued_child<P: HeadersSyncPipeline>(
+	queued_children: &mut QueuedChildren<P>,
+	parent_id: &HeaderIdOf<P>,
+	id: HeaderIdOf<P>,
+) {
+	queued_children
+		.entry(parent_id.0)
+		.or_default()
+		.entry(parent_id.1)
+		.or_default()
+		.insert(id);
+}
 
-			next_parents.extend(headers_to_move.iter().map(|(id, _)| id.1));
+/// Forget that the header with given id is queued.
+fn remove_queued_child<P: HeadersSyncPipeline>(
+	queued_children: &mut QueuedChildren<P>,
+	parent_id: &HeaderIdOf<P>,
+	id: &HeaderIdOf<P>,
+) {
+	let mut children_at = match queued_children.entry(parent_id.0) {
+		BTreeMapEntry::Occupied(children_at) => children_at,
+		BTreeMapEntry::Vacant(_) => return,
+	};
 
-			destination_queue
-				.entry(current_number)
-				.or_default()
-				.extend(headers_to_move.into_iter().map(|(id, h)| (id.1, h)))
+	if let HashMapEntry::Occupied(mut children) = children_at.get_mut().entry(parent_id.1) {
+		children.get_mut().remove(id);
+		if children.get().is_empty() {
+			children.remove();
 		}
-
-		current_number = current_number + One::one();
-		std::mem::swap(&mut current_parents, &mut next_parents);
+	}
+	if children_at.get().is_empty() {
+		children_at.remove();
 	}
 }
 
+/// Returns ids of all queued children of given header.
+fn header_queued_children<'a, P: HeadersSyncPipeline>(
+	queued_children: &'a QueuedChildren<P>,
+	parent_id: &HeaderIdOf<P>,
+) -> Option<&'a HashSet<HeaderIdOf<P>>> {
+	queued_children
+		.get(&parent_id.0)
+		.and_then(|by_hash| by_hash.get(&parent_id.1))
+}
+
 /// Selects (recursive) all synced children of given header.
 fn select_synced_children<P: HeadersSyncPipeline>(
 	synced_children: &SyncedChildren<P>,
@@ -852,6 +911,19 @@ fn set_header_status<P: HeadersSyncPipeline>(
 	*known_headers.entry(id.0).or_default().entry(id.1).or_insert(status) = status;
 }
 
+/// Forget header status.
+fn remove_header_status<P: HeadersSyncPipeline>(known_headers: &mut KnownHeaders<P>, id: &HeaderIdOf<P>) {
+	let mut known_at = match known_headers.entry(id.0) {
+		BTreeMapEntry::Occupied(known_at) => known_at,
+		BTreeMapEntry::Vacant(_) => return,
+	};
+
+	known_at.get_mut().remove(&id.1);
+	if known_at.get().is_empty() {
+		known_at.remove();
+	}
+}
+
 /// Returns queued incomplete header with maximal elapsed time since last update.
 fn queued_incomplete_header<Id: Clone + Eq + std::hash::Hash, T>(
 	map: &mut LinkedHashMap<Id, T>,
@@ -896,6 +968,27 @@ pub(crate) mod tests {
 		HeaderId(number, hash(number))
 	}
 
+	fn queue_header(
+		queue: &mut QueuedHeaders<TestHeadersSyncPipeline>,
+		status: HeaderStatus,
+		header: QueuedHeader<TestHeadersSyncPipeline>,
+	) {
+		let id = header.id();
+		let parent_id = header.header().parent_id();
+		let destination_queue = match status {
+			HeaderStatus::MaybeOrphan => &mut queue.maybe_orphan,
+			HeaderStatus::Orphan => &mut queue.orphan,
+			HeaderStatus::MaybeExtra => &mut queue.maybe_extra,
+			HeaderStatus::Extra => &mut queue.extra,
+			HeaderStatus::Ready => &mut queue.ready,
+			HeaderStatus::Incomplete => &mut queue.incomplete,
+			HeaderStatus::Submitted => &mut queue.submitted,
+			HeaderStatus::Unknown | HeaderStatus::Synced => unreachable!("there are no such queues"),
+		};
+		insert_header(destination_queue, id, header);
+		insert_queued_child::<TestHeadersSyncPipeline>(&mut queue.queued_children, &parent_id, id);
+	}
+
 	#[test]
 	fn total_headers_works() {
 		// total headers just sums up number of headers in every queue
@@ -1100,35 +1193,31 @@ pub(crate) mod tests {
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::MaybeOrphan);
-		queue
-			.maybe_orphan
-			.entry(100)
-			.or_default()
-			.insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::MaybeOrphan, header(100));
 		queue
 			.known_headers
 			.entry(99)
 			.or_default()
 			.insert(hash(99), HeaderStatus::Orphan);
-		queue.orphan.entry(99).or_default().insert(hash(99), header(99));
+		queue_header(&mut queue, HeaderStatus::Orphan, header(99));
 		queue
 			.known_headers
 			.entry(98)
 			.or_default()
 			.insert(hash(98), HeaderStatus::MaybeExtra);
-		queue.maybe_extra.entry(98).or_default().insert(hash(98), header(98));
+		queue_header(&mut queue, HeaderStatus::MaybeExtra, header(98));
 		queue
 			.known_headers
 			.entry(97)
 			.or_default()
 			.insert(hash(97), HeaderStatus::Extra);
-		queue.extra.entry(97).or_default().insert(hash(97), header(97));
+		queue_header(&mut queue, HeaderStatus::Extra, header(97));
 		queue
 			.known_headers
 			.entry(96)
 			.or_default()
 			.insert(hash(96), HeaderStatus::Ready);
-		queue.ready.entry(96).or_default().insert(hash(96), header(96));
+		queue_header(&mut queue, HeaderStatus::Ready, header(96));
 		queue.target_best_header_response(&id(100));
 
 		// then the #100 and all ancestors of #100 (#96..#99) are treated as synced
@@ -1188,23 +1277,19 @@ pub(crate) mod tests {
 			.entry(101)
 			.or_default()
 			.insert(hash(101), HeaderStatus::Orphan);
-		queue.orphan.entry(101).or_default().insert(hash(101), header(101));
+		queue_header(&mut queue, HeaderStatus::Orphan, header(101));
 		queue
 			.known_headers
 			.entry(102)
 			.or_default()
 			.insert(hash(102), HeaderStatus::MaybeOrphan);
-		queue
-			.maybe_orphan
-			.entry(102)
-			.or_default()
-			.insert(hash(102), header(102));
+		queue_header(&mut queue, HeaderStatus::MaybeOrphan, header(102));
 		queue
 			.known_headers
 			.entry(103)
 			.or_default()
 			.insert(hash(103), HeaderStatus::Orphan);
-		queue.orphan.entry(103).or_default().insert(hash(103), header(103));
+		queue_header(&mut queue, HeaderStatus::Orphan, header(103));
 		queue.target_best_header_response(&id(100));
 
 		// all descendants are moved to MaybeExtra
@@ -1230,27 +1315,19 @@ pub(crate) mod tests {
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::MaybeOrphan);
-		queue
-			.maybe_orphan
-			.entry(100)
-			.or_default()
-			.insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::MaybeOrphan, header(100));
 		queue
 			.known_headers
 			.entry(101)
 			.or_default()
 			.insert(hash(101), HeaderStatus::Orphan);
-		queue.orphan.entry(101).or_default().insert(hash(101), header(101));
+		queue_header(&mut queue, HeaderStatus::Orphan, header(101));
 		queue
 			.known_headers
 			.entry(102)
 			.or_default()
 			.insert(hash(102), HeaderStatus::MaybeOrphan);
-		queue
-			.maybe_orphan
-			.entry(102)
-			.or_default()
-			.insert(hash(102), header(102));
+		queue_header(&mut queue, HeaderStatus::MaybeOrphan, header(102));
 		queue.maybe_orphan_response(&id(99), true);
 
 		// then all headers (#100..#103) are moved to the MaybeExtra queue
@@ -1275,21 +1352,13 @@ pub(crate) mod tests {
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::MaybeOrphan);
-		queue
-			.maybe_orphan
-			.entry(100)
-			.or_default()
-			.insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::MaybeOrphan, header(100));
 		queue
 			.known_headers
 			.entry(101)
 			.or_default()
 			.insert(hash(101), HeaderStatus::MaybeOrphan);
-		queue
-			.maybe_orphan
-			.entry(101)
-			.or_default()
-			.insert(hash(101), header(101));
+		queue_header(&mut queue, HeaderStatus::MaybeOrphan, header(101));
 		queue.maybe_orphan_response(&id(99), false);
 
 		// then all headers (#100..#101) are moved to the Orphan queue
@@ -1307,7 +1376,7 @@ pub(crate) mod tests {
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::MaybeExtra);
-		queue.maybe_extra.entry(100).or_default().insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::MaybeExtra, header(100));
 		queue.maybe_extra_response(&id(100), true);
 		assert!(queue.maybe_extra.is_empty());
 		assert_eq!(queue.extra.len(), 1);
@@ -1323,7 +1392,7 @@ pub(crate) mod tests {
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::MaybeExtra);
-		queue.maybe_extra.entry(100).or_default().insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::MaybeExtra, header(100));
 		queue.maybe_extra_response(&id(100), false);
 		assert!(queue.maybe_extra.is_empty());
 		assert_eq!(queue.ready.len(), 1);
@@ -1336,7 +1405,7 @@ pub(crate) mod tests {
 			.entry(201)
 			.or_default()
 			.insert(hash(201), HeaderStatus::MaybeExtra);
-		queue.maybe_extra.entry(201).or_default().insert(hash(201), header(201));
+		queue_header(&mut queue, HeaderStatus::MaybeExtra, header(201));
 		queue.maybe_extra_response(&id(201), false);
 		assert!(queue.maybe_extra.is_empty());
 		assert_eq!(queue.incomplete.len(), 1);
@@ -1352,7 +1421,7 @@ pub(crate) mod tests {
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::Extra);
-		queue.extra.entry(100).or_default().insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::Extra, header(100));
 		queue.extra_response(&id(100), 100_100);
 		assert!(queue.extra.is_empty());
 		assert_eq!(queue.ready.len(), 1);
@@ -1365,7 +1434,7 @@ pub(crate) mod tests {
 			.entry(201)
 			.or_default()
 			.insert(hash(201), HeaderStatus::Extra);
-		queue.extra.entry(201).or_default().insert(hash(201), header(201));
+		queue_header(&mut queue, HeaderStatus::Extra, header(201));
 		queue.extra_response(&id(201), 201_201);
 		assert!(queue.extra.is_empty());
 		assert_eq!(queue.incomplete.len(), 1);
@@ -1380,7 +1449,7 @@ pub(crate) mod tests {
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::Ready);
-		queue.ready.entry(100).or_default().insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::Ready, header(100));
 		queue.headers_submitted(vec![id(100)]);
 		assert!(queue.ready.is_empty());
 		assert_eq!(queue.known_headers[&100][&hash(100)], HeaderStatus::Submitted);
@@ -1463,13 +1532,13 @@ pub(crate) mod tests {
 			.entry(101)
 			.or_default()
 			.insert(hash(101), HeaderStatus::Submitted);
-		queue.submitted.entry(101).or_default().insert(hash(101), header(101));
+		queue_header(&mut queue, HeaderStatus::Submitted, header(101));
 		queue
 			.known_headers
 			.entry(102)
 			.or_default()
 			.insert(hash(102), HeaderStatus::Ready);
-		queue.submitted.entry(102).or_default().insert(hash(102), header(102));
+		queue_header(&mut queue, HeaderStatus::Submitted, header(102));
 
 		// AND now we know that the #100 is incomplete
 		queue.incomplete_headers_response(vec![id(100)].into_iter().collect());
@@ -1513,7 +1582,7 @@ pub(crate) mod tests {
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::Incomplete);
-		queue.incomplete.entry(100).or_default().insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::Incomplete, header(100));
 		assert!(!queue.is_parent_incomplete(&id(100)));
 
 		// when parent is inside incomplete queue (i.e. some other ancestor is actually incomplete)
@@ -1522,7 +1591,7 @@ pub(crate) mod tests {
 			.entry(101)
 			.or_default()
 			.insert(hash(101), HeaderStatus::Submitted);
-		queue.submitted.entry(101).or_default().insert(hash(101), header(101));
+		queue_header(&mut queue, HeaderStatus::Submitted, header(101));
 		assert!(queue.is_parent_incomplete(&id(101)));
 
 		// when parent is the incomplete header and we do not have completion data
@@ -1532,7 +1601,7 @@ pub(crate) mod tests {
 			.entry(200)
 			.or_default()
 			.insert(hash(200), HeaderStatus::Submitted);
-		queue.submitted.entry(200).or_default().insert(hash(200), header(200));
+		queue_header(&mut queue, HeaderStatus::Submitted, header(200));
 		assert!(queue.is_parent_incomplete(&id(200)));
 
 		// when parent is the incomplete header and we have completion data
@@ -1542,7 +1611,7 @@ pub(crate) mod tests {
 			.entry(300)
 			.or_default()
 			.insert(hash(300), HeaderStatus::Submitted);
-		queue.submitted.entry(300).or_default().insert(hash(300), header(300));
+		queue_header(&mut queue, HeaderStatus::Submitted, header(300));
 		assert!(queue.is_parent_incomplete(&id(300)));
 	}
 
@@ -1554,41 +1623,37 @@ pub(crate) mod tests {
 			.entry(105)
 			.or_default()
 			.insert(hash(105), HeaderStatus::Incomplete);
-		queue.incomplete.entry(105).or_default().insert(hash(105), header(105));
+		queue_header(&mut queue, HeaderStatus::Incomplete, header(105));
 		queue
 			.known_headers
 			.entry(104)
 			.or_default()
 			.insert(hash(104), HeaderStatus::MaybeOrphan);
-		queue
-			.maybe_orphan
-			.entry(104)
-			.or_default()
-			.insert(hash(104), header(104));
+		queue_header(&mut queue, HeaderStatus::MaybeOrphan, header(104));
 		queue
 			.known_headers
 			.entry(103)
 			.or_default()
 			.insert(hash(103), HeaderStatus::Orphan);
-		queue.orphan.entry(103).or_default().insert(hash(103), header(103));
+		queue_header(&mut queue, HeaderStatus::Orphan, header(103));
 		queue
 			.known_headers
 			.entry(102)
 			.or_default()
 			.insert(hash(102), HeaderStatus::MaybeExtra);
-		queue.maybe_extra.entry(102).or_default().insert(hash(102), header(102));
+		queue_header(&mut queue, HeaderStatus::MaybeExtra, header(102));
 		queue
 			.known_headers
 			.entry(101)
 			.or_default()
 			.insert(hash(101), HeaderStatus::Extra);
-		queue.extra.entry(101).or_default().insert(hash(101), header(101));
+		queue_header(&mut queue, HeaderStatus::Extra, header(101));
 		queue
 			.known_headers
 			.entry(100)
 			.or_default()
 			.insert(hash(100), HeaderStatus::Ready);
-		queue.ready.entry(100).or_default().insert(hash(100), header(100));
+		queue_header(&mut queue, HeaderStatus::Ready, header(100));
 		queue
 			.synced_children
 			.entry(100)
@@ -1635,10 +1700,10 @@ pub(crate) mod tests {
 
 		// relay#1 knows that header#100 is incomplete && it has headers 101..104 in incomplete queue
 		queue.incomplete_headers.insert(id(100), None);
-		queue.incomplete.entry(101).or_default().insert(hash(101), header(101));
-		queue.incomplete.entry(102).or_default().insert(hash(102), header(102));
-		queue.incomplete.entry(103).or_default().insert(hash(103), header(103));
-		queue.incomplete.entry(104).or_default().insert(hash(104), header(104));
+		queue_header(&mut queue, HeaderStatus::Incomplete, header(101));
+		queue_header(&mut queue, HeaderStatus::Incomplete, header(102));
+		queue_header(&mut queue, HeaderStatus::Incomplete, header(103));
+		queue_header(&mut queue, HeaderStatus::Incomplete, header(104));
 		queue
 			.known_headers
 			.entry(100)
@@ -1681,9 +1746,9 @@ pub(crate) mod tests {
 		let mut queue = QueuedHeaders::<TestHeadersSyncPipeline>::default();
 
 		// we have submitted two headers - 100 and 101. 102 is ready
-		queue.submitted.entry(100).or_default().insert(hash(100), header(100));
-		queue.submitted.entry(101).or_default().insert(hash(101), header(101));
-		queue.ready.entry(102).or_default().insert(hash(102), header(102));
+		queue_header(&mut queue, HeaderStatus::Submitted, header(100));
+		queue_header(&mut queue, HeaderStatus::Submitted, header(101));
+		queue_header(&mut queue, HeaderStatus::Ready, header(102));
 		queue
 			.known_headers
 			.entry(100)
@@ -1718,4 +1783,72 @@ pub(crate) mod tests {
 		assert_eq!(queue.status(&id(102)), HeaderStatus::Ready);
 		assert!(queue.ready[&102].contains_key(&hash(102)));
 	}
+
+	#[test]
+	fn queued_children_index_is_maintained() {
+		let mut queue = QueuedHeaders::<TestHeadersSyncPipeline>::default();
+		queue.header_response(header(101).header().clone());
+		queue.header_response(header(102).header().clone());
+		assert_eq!(
+			header_queued_children::<TestHeadersSyncPipeline>(&queue.queued_children, &id(100)),
+			Some(&vec![id(101)].into_iter().collect()),
+		);
+
+		// when header is synced, it is removed from the index, but its children are not
+		queue.target_best_header_response(&id(101));
+		assert_eq!(queue.status(&id(102)), HeaderStatus::MaybeExtra);
+		assert_eq!(
+			header_queued_children::<TestHeadersSyncPipeline>(&queue.queued_children, &id(100)),
+			None,
+		);
+		assert_eq!(
+			header_queued_children::<TestHeadersSyncPipeline>(&queue.queued_children, &id(101)),
+			Some(&vec![id(102)].into_iter().collect()),
+		);
+
+		// and children of pruned headers are also removed from the index
+		queue.prune(104);
+		assert!(queue.queued_children.is_empty());
+	}
+
+	#[test]
+	fn invalidate_works() {
+		let side_header = |number, parent_hash| TestHeader {
+			number,
+			hash: 1000 + number,
+			parent_hash,
+		};
+
+		let mut queue = QueuedHeaders::<TestHeadersSyncPipeline>::default();
+		queue.target_best_header_response(&id(100));
+		queue.header_response(header(101).header().clone());
+		queue.header_response(header(102).header().clone());
+		queue.header_response(header(103).header().clone());
+		queue.header_response(side_header(102, hash(101)));
+		queue.header_response(side_header(101, hash(100)));
+		queue.incomplete_headers.insert(id(102), None);
+		assert_eq!(queue.total_headers(), 5);
+
+		// when header is invalidated, all its descendants are also invalidated
+		queue.invalidate(&id(101));
+		assert_eq!(queue.status(&id(101)), HeaderStatus::Unknown);
+		assert_eq!(queue.status(&id(102)), HeaderStatus::Unknown);
+		assert_eq!(queue.status(&id(103)), HeaderStatus::Unknown);
+		assert_eq!(queue.status(&HeaderId(102, 1102)), HeaderStatus::Unknown);
+		assert_eq!(queue.status(&HeaderId(101, 1101)), HeaderStatus::MaybeExtra);
+		assert_eq!(queue.total_headers(), 1);
+		assert!(queue.incomplete_headers.is_empty());
+		assert_eq!(
+			header_queued_children::<TestHeadersSyncPipeline>(&queue.queued_children, &id(100)),
+			Some(&vec![HeaderId(101, 1101)].into_iter().collect()),
+		);
+
+		// synced header itself is not invalidated, but its descendants are
+		queue.invalidate(&id(100));
+		assert_eq!(queue.status(&id(100)), HeaderStatus::Synced);
+		assert_eq!(queue.status(&HeaderId(101, 1101)), HeaderStatus::Unknown);
+		assert_eq!(queue.total_headers(), 0);
+		assert!(queue.queued_children.is_empty());
+		assert_eq!(queue.known_headers.len(), 1);
+	}
 }
@@ -20,8 +20,10 @@
 //! passed using `HeadersSyncParams` structure.
 
 use crate::headers::QueuedHeaders;
-use crate::sync_types::{HeaderIdOf, HeaderStatus, HeadersSyncPipeline, QueuedHeader};
+use crate::sync_types::{HeaderIdOf, HeaderStatus, HeadersSyncPipeline, QueuedHeader, SourceHeader};
 use num_traits::{One, Saturating, Zero};
+use relay_utils::HeaderId;
+use std::collections::BTreeMap;
 
 /// Common sync params.
 #[derive(Debug, Clone)]
@@ -37,6 +39,9 @@ pub struct HeadersSyncParams {
 	/// We only may store and accept (from Ethereum node) headers that have
 	/// number >= than best_substrate_header.number - prune_depth.
 	pub prune_depth: u32,
+	/// Maximal number of latest headers, downloaded by number, that we're checking against
+	/// the canonical source chain when looking for reorgs.
+	pub reorg_detection_window: usize,
 	/// Target transactions mode.
 	pub target_tx_mode: TargetTransactionMode,
 }
@@ -64,6 +69,12 @@ pub struct HeadersSync<P: HeadersSyncPipeline> {
 	target_best_header: Option<HeaderIdOf<P>>,
 	/// Headers queue.
 	headers: QueuedHeaders<P>,
+	/// Hashes of latest headers that have been downloaded by number, i.e. that have been
+	/// canonical at the moment of download.
+	canonical_headers: BTreeMap<P::Number, P::Hash>,
+	/// Number of the header that we need to re-read from the source node to check whether
+	/// there has been a reorg.
+	header_to_verify: Option<P::Number>,
 	/// Pause headers submission.
 	pause_submit: bool,
 }
@@ -76,6 +87,8 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 			params,
 			source_best_number: None,
 			target_best_header: None,
+			canonical_headers: BTreeMap::new(),
+			header_to_verify: None,
 			pause_submit: false,
 		}
 	}
@@ -137,7 +150,8 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 			return Some(source_best_number);
 		}
 
-		// we assume that there were no reorgs if we have already downloaded best header
+		// if we have already downloaded best header, there's nothing to download. Reorgs of already
+		// downloaded headers are detected separately (see `select_header_to_verify`)
 		let best_downloaded_number = std::cmp::max(
 			std::cmp::max(best_queued_number, self.headers.best_synced_number()),
 			target_best_header.0,
@@ -150,6 +164,12 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 		Some(best_downloaded_number + One::one())
 	}
 
+	/// Select canonical header that needs to be re-read from the source node to check
+	/// whether there has been a reorg.
+	pub fn select_header_to_verify(&self) -> Option<P::Number> {
+		self.header_to_verify
+	}
+
 	/// Selech orphan header to downoload.
 	pub fn select_orphan_header_to_download(&self) -> Option<&QueuedHeader<P>> {
 		let orphan_header = self.headers.header(HeaderStatus::Orphan)?;
@@ -214,6 +234,89 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 			best_header_number,
 		);
 		self.source_best_number = Some(best_header_number);
+
+		// if we're still downloading new headers, reorg would be detected when we'll see header
+		// with unexpected parent. Otherwise we need to verify our best canonical header
+		let best_canonical_number = self.canonical_headers.keys().next_back().cloned();
+		if self.header_to_verify.is_none() && best_canonical_number >= Some(best_header_number) {
+			self.header_to_verify = self
+				.canonical_headers
+				.range(..=best_header_number)
+				.next_back()
+				.map(|(number, _)| *number);
+		}
+	}
+
+	/// Receive new header that has been downloaded by number from the source node.
+	pub fn new_header_response(&mut self, header: P::Header) {
+		// if parent of the new header is not the header that we have downloaded before, then
+		// there has been a reorg and we need to find where the canonical chain has forked
+		let id = header.id();
+		let parent_id = header.parent_id();
+		if let Some(expected_parent_hash) = self.canonical_headers.get(&parent_id.0) {
+			if *expected_parent_hash != parent_id.1 {
+				log::debug!(
+					target: "bridge",
+					"Parent of new {} header {:?} is {:?}. Expected parent hash: {:?}. Looking for reorg",
+					P::SOURCE_NAME,
+					id,
+					parent_id,
+					expected_parent_hash,
+				);
+
+				self.header_to_verify = Some(parent_id.0);
+				return;
+			}
+		}
+
+		self.canonical_headers.insert(id.0, id.1);
+		while self.canonical_headers.len() > self.params.reorg_detection_window {
+			let oldest_canonical_number = match self.canonical_headers.keys().next() {
+				Some(oldest_canonical_number) => *oldest_canonical_number,
+				None => break,
+			};
+			self.canonical_headers.remove(&oldest_canonical_number);
+		}
+
+		self.headers.header_response(header);
+	}
+
+	/// Receive canonical header that has been re-read from the source node.
+	pub fn verified_header_response(&mut self, header: P::Header) {
+		let id = header.id();
+		if self.header_to_verify != Some(id.0) {
+			return;
+		}
+		self.header_to_verify = None;
+
+		// if hash of the canonical header is the same, there were no reorgs (or we have found
+		// the fork point)
+		let stale_hash = match self.canonical_headers.get(&id.0) {
+			Some(stale_hash) if *stale_hash != id.1 => *stale_hash,
+			_ => return,
+		};
+
+		// otherwise the header that we have downloaded before (and all its descendants) are
+		// no longer canonical => forget them and continue looking for the fork point
+		log::info!(
+			target: "bridge",
+			"{} chain has been reorganized. Header {:?} has been replaced with {:?}",
+			P::SOURCE_NAME,
+			HeaderId(id.0, stale_hash),
+			id,
+		);
+
+		self.headers.invalidate(&HeaderId(id.0, stale_hash));
+		let _ = self.canonical_headers.split_off(&id.0);
+		self.header_to_verify = self.canonical_headers.keys().next_back().cloned();
+		if self.header_to_verify.is_none() {
+			log::warn!(
+				target: "bridge",
+				"Failed to find fork point of {} chain reorg within {} latest headers",
+				P::SOURCE_NAME,
+				self.params.reorg_detection_window,
+			);
+		}
 	}
 
 	/// Receive new best header from the target node.
@@ -274,6 +377,8 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 		self.source_best_number = None;
 		self.target_best_header = None;
 		self.headers.clear();
+		self.canonical_headers.clear();
+		self.header_to_verify = None;
 		self.pause_submit = false;
 	}
 }
@@ -282,7 +387,7 @@ impl<P: HeadersSyncPipeline> HeadersSync<P> {
 pub mod tests {
 	use super::*;
 	use crate::headers::tests::{header, id};
-	use crate::sync_loop_tests::{TestHash, TestHeadersSyncPipeline, TestNumber};
+	use crate::sync_loop_tests::{TestHash, TestHeader, TestHeadersSyncPipeline, TestNumber};
 	use crate::sync_types::HeaderStatus;
 	use relay_utils::HeaderId;
 
@@ -290,6 +395,14 @@ pub mod tests {
 		1000 + number
 	}
 
+	fn side_header(number: TestNumber, parent_hash: TestHash) -> TestHeader {
+		TestHeader {
+			number,
+			hash: side_hash(number),
+			parent_hash,
+		}
+	}
+
 	pub fn default_sync_params() -> HeadersSyncParams {
 		HeadersSyncParams {
 			max_future_headers_to_download: 128,
@@ -297,6 +410,7 @@ pub mod tests {
 			max_headers_in_single_submit: 32,
 			max_headers_size_in_single_submit: 131_072,
 			prune_depth: 4096,
+			reorg_detection_window: 64,
 			target_tx_mode: TargetTransactionMode::Signed,
 		}
 	}
@@ -520,4 +634,112 @@ pub mod tests {
 		eth_sync.target_best_header_response(id(101));
 		assert_eq!(eth_sync.select_headers_to_submit(false), Some(vec![&header(102)]));
 	}
+
+	#[test]
+	fn reorg_is_detected_when_new_header_has_unexpected_parent() {
+		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
+		eth_sync.source_best_header_number_response(103);
+		eth_sync.target_best_header_response(id(100));
+
+		// headers #101 and #102 are downloaded
+		eth_sync.new_header_response(header(101).header().clone());
+		eth_sync.new_header_response(header(102).header().clone());
+		assert_eq!(eth_sync.select_header_to_verify(), None);
+		assert_eq!(eth_sync.select_new_header_to_download(), Some(103));
+
+		// but parent of header #103 is not the header #102 we have downloaded before
+		eth_sync.new_header_response(side_header(103, side_hash(102)));
+		assert_eq!(
+			eth_sync.headers.status(&HeaderId(103, side_hash(103))),
+			HeaderStatus::Unknown
+		);
+		assert_eq!(eth_sync.select_header_to_verify(), Some(102));
+
+		// canonical header #102 is different => it is forgotten and we're checking #101
+		eth_sync.verified_header_response(side_header(102, hash(101)));
+		assert_eq!(eth_sync.headers.status(&id(102)), HeaderStatus::Unknown);
+		assert_eq!(eth_sync.select_header_to_verify(), Some(101));
+
+		// canonical header #101 is the same => it is the fork point
+		eth_sync.verified_header_response(header(101).header().clone());
+		assert_eq!(eth_sync.headers.status(&id(101)), HeaderStatus::MaybeExtra);
+		assert_eq!(eth_sync.select_header_to_verify(), None);
+
+		// and we're downloading the new canonical branch
+		assert_eq!(eth_sync.select_new_header_to_download(), Some(102));
+		eth_sync.new_header_response(side_header(102, hash(101)));
+		assert_eq!(eth_sync.select_new_header_to_download(), Some(103));
+		eth_sync.new_header_response(side_header(103, side_hash(102)));
+		assert_eq!(
+			eth_sync.headers.status(&HeaderId(103, side_hash(103))),
+			HeaderStatus::MaybeExtra
+		);
+		assert_eq!(eth_sync.select_header_to_verify(), None);
+	}
+
+	#[test]
+	fn reorg_is_detected_when_best_canonical_header_is_replaced() {
+		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
+		eth_sync.source_best_header_number_response(102);
+		eth_sync.target_best_header_response(id(100));
+		eth_sync.new_header_response(header(101).header().clone());
+		eth_sync.new_header_response(header(102).header().clone());
+		assert_eq!(eth_sync.select_new_header_to_download(), None);
+
+		// when we have downloaded best header, we're checking if it is still canonical
+		eth_sync.source_best_header_number_response(102);
+		assert_eq!(eth_sync.select_header_to_verify(), Some(102));
+		eth_sync.verified_header_response(header(102).header().clone());
+		assert_eq!(eth_sync.headers.status(&id(102)), HeaderStatus::MaybeExtra);
+		assert_eq!(eth_sync.select_header_to_verify(), None);
+
+		// when it has been replaced, we're looking for the fork point
+		eth_sync.source_best_header_number_response(102);
+		eth_sync.verified_header_response(side_header(102, hash(101)));
+		assert_eq!(eth_sync.headers.status(&id(102)), HeaderStatus::Unknown);
+		assert_eq!(eth_sync.select_header_to_verify(), Some(101));
+		eth_sync.verified_header_response(header(101).header().clone());
+		assert_eq!(eth_sync.select_header_to_verify(), None);
+
+		// and re-download the new canonical header
+		assert_eq!(eth_sync.select_new_header_to_download(), Some(102));
+	}
+
+	#[test]
+	fn reorg_detection_window_is_limited() {
+		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
+		eth_sync.params.reorg_detection_window = 2;
+		eth_sync.source_best_header_number_response(104);
+		eth_sync.target_best_header_response(id(100));
+		for number in 101..=104 {
+			eth_sync.new_header_response(header(number).header().clone());
+		}
+		assert_eq!(
+			eth_sync.canonical_headers.keys().cloned().collect::<Vec<_>>(),
+			vec![103, 104]
+		);
+
+		// when fork point is outside of the window, we're only forgetting headers within window
+		eth_sync.source_best_header_number_response(104);
+		eth_sync.verified_header_response(side_header(104, side_hash(103)));
+		eth_sync.verified_header_response(side_header(103, side_hash(102)));
+		assert_eq!(eth_sync.select_header_to_verify(), None);
+		assert_eq!(eth_sync.headers.status(&id(102)), HeaderStatus::MaybeExtra);
+		assert_eq!(eth_sync.headers.status(&id(103)), HeaderStatus::Unknown);
+		assert_eq!(eth_sync.headers.status(&id(104)), HeaderStatus::Unknown);
+	}
+
+	#[test]
+	fn restart_forgets_canonical_headers() {
+		let mut eth_sync = HeadersSync::<TestHeadersSyncPipeline>::new(default_sync_params());
+		eth_sync.source_best_header_number_response(101);
+		eth_sync.target_best_header_response(id(100));
+		eth_sync.new_header_response(header(101).header().clone());
+		eth_sync.source_best_header_number_response(101);
+		assert_eq!(eth_sync.select_header_to_verify(), Some(101));
+
+		eth_sync.restart();
+		assert!(eth_sync.canonical_headers.is_empty());
+		assert_eq!(eth_sync.select_header_to_verify(), None);
+	}
 }
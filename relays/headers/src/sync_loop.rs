@@ -21,7 +21,10 @@ use crate::sync_loop_metrics::SyncLoopMetrics;
 use crate::sync_types::{HeaderIdOf, HeaderStatus, HeadersSyncPipeline, QueuedHeader, SubmittedHeaders};
 
 use async_trait::async_trait;
-use futures::{future::FutureExt, stream::StreamExt};
+use futures::{
+	future::FutureExt,
+	stream::{BoxStream, StreamExt},
+};
 use num_traits::{Saturating, Zero};
 use relay_utils::{
 	format_ids, interval,
@@ -52,6 +55,11 @@ const STALL_SYNC_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 const BACKUP_STALL_SYNC_TIMEOUT: Duration = Duration::from_secs(10 * 60);
 /// Interval between calling sync maintain procedure.
 const MAINTAIN_INTERVAL: Duration = Duration::from_secs(30);
+/// Minimal interval between attempts to (re)subscribe to best source block numbers.
+const RESUBSCRIBE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Stream of best block numbers, pushed by the source node.
+pub type BestBlockNumbersStream<P> = BoxStream<'static, <P as HeadersSyncPipeline>::Number>;
 
 /// Source client trait.
 #[async_trait]
@@ -59,6 +67,14 @@ pub trait SourceClient<P: HeadersSyncPipeline>: RelayClient {
 	/// Get best block number.
 	async fn best_block_number(&self) -> Result<P::Number, Self::Error>;
 
+	/// Subscribe to best block numbers.
+	///
+	/// Returns `None` if source node doesn't support subscriptions. While there's no active
+	/// subscription, best block number is polled using `best_block_number` method.
+	async fn subscribe_best_block_numbers(&self) -> Result<Option<BestBlockNumbersStream<P>>, Self::Error> {
+		Ok(None)
+	}
+
 	/// Get header by hash.
 	async fn header_by_hash(&self, hash: P::Hash) -> Result<P::Header, Self::Error>;
 
@@ -170,8 +186,13 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 	let mut source_retry_backoff = retry_backoff();
 	let mut source_client_is_online = false;
 	let mut source_best_block_number_required = false;
+	let mut source_is_subscribed = false;
+	let mut source_subscribed_at: Option<Instant> = None;
 	let source_best_block_number_future = source_client.best_block_number().fuse();
+	let source_subscribe_future = futures::future::Fuse::terminated();
+	let source_best_block_numbers_stream = futures::stream::pending().boxed().fuse();
 	let source_new_header_future = futures::future::Fuse::terminated();
+	let source_verified_header_future = futures::future::Fuse::terminated();
 	let source_orphan_header_future = futures::future::Fuse::terminated();
 	let source_extra_future = futures::future::Fuse::terminated();
 	let source_completion_future = futures::future::Fuse::terminated();
@@ -190,6 +211,7 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 	let target_complete_header_future = futures::future::Fuse::terminated();
 	let target_go_offline_future = futures::future::Fuse::terminated();
 	let target_tick_stream = interval(target_tick).fuse();
+	let mut prefer_headers_submission = false;
 
 	let mut maintain_required = false;
 	let maintain_stream = interval(MAINTAIN_INTERVAL).fuse();
@@ -198,7 +220,10 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 
 	futures::pin_mut!(
 		source_best_block_number_future,
+		source_subscribe_future,
+		source_best_block_numbers_stream,
 		source_new_header_future,
+		source_verified_header_future,
 		source_orphan_header_future,
 		source_extra_future,
 		source_completion_future,
@@ -230,16 +255,60 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 					|| format!("Error retrieving best header number from {}", P::SOURCE_NAME),
 				).fail_if_connection_error(FailedClient::Source)?;
 			},
+			source_best_block_numbers = source_subscribe_future => {
+				source_client_is_online = process_future_result(
+					source_best_block_numbers,
+					&mut source_retry_backoff,
+					|source_best_block_numbers| match source_best_block_numbers {
+						Some(source_best_block_numbers) => {
+							source_is_subscribed = true;
+							source_best_block_numbers_stream.set(source_best_block_numbers.fuse());
+						},
+						None => log::debug!(
+							target: "bridge",
+							"{} node doesn't support best block subscriptions. Falling back to polling",
+							P::SOURCE_NAME,
+						),
+					},
+					&mut source_go_offline_future,
+					async_std::task::sleep,
+					|| format!("Error subscribing to best block numbers of {} node", P::SOURCE_NAME),
+				).fail_if_connection_error(FailedClient::Source)?;
+			},
+			source_best_block_number = source_best_block_numbers_stream.next() => {
+				match source_best_block_number {
+					Some(source_best_block_number) => sync.source_best_header_number_response(source_best_block_number),
+					None => {
+						log::debug!(
+							target: "bridge",
+							"{} best block numbers subscription has been closed. Falling back to polling",
+							P::SOURCE_NAME,
+						);
+						source_is_subscribed = false;
+						source_best_block_number_required = true;
+					},
+				}
+			},
 			source_new_header = source_new_header_future => {
 				source_client_is_online = process_future_result(
 					source_new_header,
 					&mut source_retry_backoff,
-					|source_new_header| sync.headers_mut().header_response(source_new_header),
+					|source_new_header| sync.new_header_response(source_new_header),
 					&mut source_go_offline_future,
 					async_std::task::sleep,
 					|| format!("Error retrieving header from {} node", P::SOURCE_NAME),
 				).fail_if_connection_error(FailedClient::Source)?;
 			},
+			source_verified_header = source_verified_header_future => {
+				source_client_is_online = process_future_result(
+					source_verified_header,
+					&mut source_retry_backoff,
+					|source_verified_header| sync.verified_header_response(source_verified_header),
+					&mut source_go_offline_future,
+					async_std::task::sleep,
+					|| format!("Error retrieving canonical header from {} node", P::SOURCE_NAME),
+				).fail_if_connection_error(FailedClient::Source)?;
+			},
 			source_orphan_header = source_orphan_header_future => {
 				source_client_is_online = process_future_result(
 					source_orphan_header,
@@ -274,7 +343,8 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 				source_client_is_online = true;
 			},
 			_ = source_tick_stream.next() => {
-				if sync.is_almost_synced() {
+				// while we're subscribed, best block numbers are pushed by the source node
+				if sync.is_almost_synced() && !source_is_subscribed {
 					source_best_block_number_required = true;
 				}
 			},
@@ -466,7 +536,15 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 			//     - Stops us from submitting new blocks
 			//
 			// 6. Submit header
-
+			//
+			// While catching up, new headers are constantly added to the MaybeExtra and MaybeOrphan
+			// queues, so (4) and (5) would postpone submission until the queues are drained. To keep
+			// the submission pipeline full, we're alternating between these checks and (6) - extra
+			// data for later headers is then fetched by the source client while earlier headers
+			// are submitted.
+
+			let is_stalled = last_update_time.elapsed() > BACKUP_STALL_SYNC_TIMEOUT;
+			let submit_before_checks = prefer_headers_submission && sync.select_headers_to_submit(is_stalled).is_some();
 			if target_best_block_required {
 				log::debug!(target: "bridge", "Asking {} about best block", P::TARGET_NAME);
 				target_best_block_future.set(target_client.best_header_id().fuse());
@@ -481,7 +559,11 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 				);
 
 				target_complete_header_future.set(target_client.complete_header(id, completion.clone()).fuse());
-			} else if let Some(header) = sync.headers().header(HeaderStatus::MaybeExtra) {
+			} else if let Some(header) = sync
+				.headers()
+				.header(HeaderStatus::MaybeExtra)
+				.filter(|_| !submit_before_checks)
+			{
 				log::debug!(
 					target: "bridge",
 					"Checking if header submission requires extra: {:?}",
@@ -489,7 +571,12 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 				);
 
 				target_extra_check_future.set(target_client.requires_extra(header.clone()).fuse());
-			} else if let Some(header) = sync.headers().header(HeaderStatus::MaybeOrphan) {
+				prefer_headers_submission = true;
+			} else if let Some(header) = sync
+				.headers()
+				.header(HeaderStatus::MaybeOrphan)
+				.filter(|_| !submit_before_checks)
+			{
 				// for MaybeOrphan we actually ask for parent' header existence
 				let parent_id = header.parent_id();
 
@@ -501,9 +588,8 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 				);
 
 				target_existence_status_future.set(target_client.is_known_header(parent_id).fuse());
-			} else if let Some(headers) =
-				sync.select_headers_to_submit(last_update_time.elapsed() > BACKUP_STALL_SYNC_TIMEOUT)
-			{
+				prefer_headers_submission = true;
+			} else if let Some(headers) = sync.select_headers_to_submit(is_stalled) {
 				log::debug!(
 					target: "bridge",
 					"Submitting {} header(s) to {} node: {:?}",
@@ -514,6 +600,7 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 
 				let headers = headers.into_iter().cloned().collect();
 				target_submit_header_future.set(target_client.submit_headers(headers).fuse());
+				prefer_headers_submission = false;
 
 				// remember that we have submitted some headers
 				if stall_countdown.is_none() {
@@ -539,20 +626,34 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 			//     - Stops us from downloading or submitting new blocks
 			//     - Only called rarely
 			//
-			// 2. Download completion data
+			// 2. Subscribe to best block numbers
+			//     - Only called rarely
+			//
+			// 3. Download completion data
 			//     - Stops us from submitting new blocks
 			//
-			// 3. Download extra data
+			// 4. Download extra data
 			//     - Stops us from submitting new blocks
 			//
-			// 4. Download missing headers
+			// 5. Download missing headers
 			//     - Stops us from downloading or submitting new blocks
 			//
-			// 5. Downloading new headers
+			// 6. Verify that previously downloaded headers are still canonical
+			//     - Stops us from downloading new blocks
+			//
+			// 7. Downloading new headers
 
+			let source_subscription_required = !source_is_subscribed
+				&& source_subscribed_at
+					.map(|subscribed_at| subscribed_at.elapsed() >= RESUBSCRIBE_INTERVAL)
+					.unwrap_or(true);
 			if source_best_block_number_required {
 				log::debug!(target: "bridge", "Asking {} node about best block number", P::SOURCE_NAME);
 				source_best_block_number_future.set(source_client.best_block_number().fuse());
+			} else if source_subscription_required {
+				log::debug!(target: "bridge", "Subscribing to {} node best block numbers", P::SOURCE_NAME);
+				source_subscribed_at = Some(Instant::now());
+				source_subscribe_future.set(source_client.subscribe_best_block_numbers().fuse());
 			} else if let Some(id) = sync.headers_mut().incomplete_header() {
 				log::debug!(
 					target: "bridge",
@@ -591,6 +692,15 @@ async fn run_until_connection_lost<P: HeadersSyncPipeline, TC: TargetClient<P>>(
 				);
 
 				source_orphan_header_future.set(source_client.header_by_hash(parent_id.1).fuse());
+			} else if let Some(number) = sync.select_header_to_verify() {
+				log::debug!(
+					target: "bridge",
+					"Going to verify that {} header is still canonical: {:?}",
+					P::SOURCE_NAME,
+					number,
+				);
+
+				source_verified_header_future.set(source_client.header_by_number(number).fuse());
 			} else if let Some(id) = sync.select_new_header_to_download() {
 				log::debug!(
 					target: "bridge",
@@ -20,7 +20,10 @@ use crate::sync::HeadersSync;
 use crate::sync_types::{HeaderStatus, HeadersSyncPipeline};
 
 use num_traits::Zero;
-use relay_utils::metrics::{metric_name, register, GaugeVec, Opts, PrometheusError, Registry, U64};
+use relay_utils::{
+	block_number_to_u64,
+	metrics::{metric_name, register, GaugeVec, Opts, PrometheusError, Registry, U64},
+};
 
 /// Headers sync metrics.
 #[derive(Clone)]
@@ -61,17 +64,17 @@ impl SyncLoopMetrics {
 
 impl SyncLoopMetrics {
 	/// Update best block number at source.
-	pub fn update_best_block_at_source<Number: Into<u64>>(&self, source_best_number: Number) {
+	pub fn update_best_block_at_source<Number: relay_utils::BlockNumberBase>(&self, source_best_number: Number) {
 		self.best_block_numbers
 			.with_label_values(&["source"])
-			.set(source_best_number.into());
+			.set(block_number_to_u64(source_best_number));
 	}
 
 	/// Update best block number at target.
-	pub fn update_best_block_at_target<Number: Into<u64>>(&self, target_best_number: Number) {
+	pub fn update_best_block_at_target<Number: relay_utils::BlockNumberBase>(&self, target_best_number: Number) {
 		self.best_block_numbers
 			.with_label_values(&["target"])
-			.set(target_best_number.into());
+			.set(block_number_to_u64(target_best_number));
 	}
 
 	/// Update metrics.
@@ -16,7 +16,7 @@
 
 #![cfg(test)]
 
-use crate::sync_loop::{run, SourceClient, TargetClient};
+use crate::sync_loop::{run, BestBlockNumbersStream, SourceClient, TargetClient};
 use crate::sync_types::{HeadersSyncPipeline, QueuedHeader, SourceHeader, SubmittedHeaders};
 
 use async_trait::async_trait;
@@ -86,6 +86,7 @@ impl HeadersSyncPipeline for TestHeadersSyncPipeline {
 
 enum SourceMethod {
 	BestBlockNumber,
+	SubscribeBestBlockNumbers,
 	HeaderByHash(TestHash),
 	HeaderByNumber(TestNumber),
 	HeaderCompletion(TestHeaderId),
@@ -100,6 +101,7 @@ struct Source {
 
 struct SourceData {
 	best_block_number: Result<TestNumber, TestError>,
+	best_block_numbers_subscription: Option<Vec<TestNumber>>,
 	header_by_hash: HashMap<TestHash, TestHeader>,
 	header_by_number: HashMap<TestNumber, TestHeader>,
 	provides_completion: bool,
@@ -115,6 +117,7 @@ impl Source {
 		Source {
 			data: Arc::new(Mutex::new(SourceData {
 				best_block_number: Ok(best_block_id.0),
+				best_block_numbers_subscription: None,
 				header_by_hash: headers
 					.iter()
 					.map(|(_, header)| (header.hash, header.clone()))
@@ -154,6 +157,19 @@ impl SourceClient<TestHeadersSyncPipeline> for Source {
 		data.best_block_number.clone()
 	}
 
+	async fn subscribe_best_block_numbers(
+		&self,
+	) -> Result<Option<BestBlockNumbersStream<TestHeadersSyncPipeline>>, TestError> {
+		let mut data = self.data.lock();
+		(self.on_method_call)(SourceMethod::SubscribeBestBlockNumbers, &mut *data);
+		Ok(data.best_block_numbers_subscription.take().map(|best_block_numbers| {
+			// keep subscription alive after all numbers are yielded
+			futures::stream::iter(best_block_numbers)
+				.chain(futures::stream::pending())
+				.boxed()
+		}))
+	}
+
 	async fn header_by_hash(&self, hash: TestHash) -> Result<TestHeader, TestError> {
 		let mut data = self.data.lock();
 		(self.on_method_call)(SourceMethod::HeaderByHash(hash), &mut *data);
@@ -459,6 +475,7 @@ struct SyncLoopTestParams {
 	headers_on_target: Vec<TestHeader>,
 	target_requires_extra: bool,
 	target_requires_completion: bool,
+	source_best_block_numbers_subscription: Option<Vec<TestNumber>>,
 	stop_at: TestHeaderId,
 }
 
@@ -493,6 +510,7 @@ fn run_sync_loop_test(params: SyncLoopTestParams) {
 	);
 	target.data.lock().requires_extra = target_requires_extra;
 	target.data.lock().requires_completion = target_requires_completion;
+	source.data.lock().best_block_numbers_subscription = params.source_best_block_numbers_subscription;
 
 	let _ = async_std::task::block_on(run(
 		source,
@@ -515,6 +533,7 @@ fn sync_loop_is_able_to_synchronize_single_header() {
 		headers_on_target: vec![test_header(0)],
 		target_requires_extra: false,
 		target_requires_completion: false,
+		source_best_block_numbers_subscription: None,
 		stop_at: test_id(1),
 	});
 }
@@ -528,6 +547,7 @@ fn sync_loop_is_able_to_synchronize_single_header_with_extra() {
 		headers_on_target: vec![test_header(0)],
 		target_requires_extra: true,
 		target_requires_completion: false,
+		source_best_block_numbers_subscription: None,
 		stop_at: test_id(1),
 	});
 }
@@ -541,6 +561,7 @@ fn sync_loop_is_able_to_synchronize_single_header_with_completion() {
 		headers_on_target: vec![test_header(0)],
 		target_requires_extra: false,
 		target_requires_completion: true,
+		source_best_block_numbers_subscription: None,
 		stop_at: test_id(1),
 	});
 }
@@ -560,6 +581,7 @@ fn sync_loop_is_able_to_reorganize_from_shorter_fork() {
 		headers_on_target: vec![test_header(0), test_forked_header(1, 0), test_forked_header(2, 0)],
 		target_requires_extra: false,
 		target_requires_completion: false,
+		source_best_block_numbers_subscription: None,
 		stop_at: test_id(3),
 	});
 }
@@ -589,6 +611,23 @@ fn sync_loop_is_able_to_reorganize_from_longer_fork() {
 		],
 		target_requires_extra: false,
 		target_requires_completion: false,
+		source_best_block_numbers_subscription: None,
 		stop_at: test_id(3),
 	});
 }
+
+#[test]
+fn sync_loop_is_able_to_synchronize_headers_pushed_by_source() {
+	// polling would always return header#0 as the best header, so header#2 may only be synced
+	// if its number is pushed by the subscription
+	run_sync_loop_test(SyncLoopTestParams {
+		best_source_header: test_header(0),
+		headers_on_source: vec![(true, test_header(1)), (true, test_header(2))],
+		best_target_header: test_header(0),
+		headers_on_target: vec![test_header(0)],
+		target_requires_extra: false,
+		target_requires_completion: false,
+		source_best_block_numbers_subscription: Some(vec![1, 2]),
+		stop_at: test_id(2),
+	});
+}
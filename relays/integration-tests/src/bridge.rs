@@ -0,0 +1,181 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mock bridge that runs complete headers+messages relay over two mock chains.
+
+use crate::chain::{ChainScript, MockChain};
+use crate::client::{MockClient, Side};
+
+use finality_relay::FinalitySyncParams;
+use futures::{channel::oneshot, FutureExt};
+use messages_relay::message_lane_loop::{MessageDeliveryParams, Params as MessageLaneParams};
+use parking_lot::Mutex;
+use relay_utils::metrics::MetricsParams;
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// State of both mock chains.
+#[derive(Debug, Clone)]
+pub struct MockBridgeState {
+	/// Source chain of the message lane.
+	pub source: MockChain,
+	/// Target chain of the message lane.
+	pub target: MockChain,
+}
+
+/// Mock bridge run parameters.
+#[derive(Debug, Clone)]
+pub struct BridgeParams {
+	/// Interval between blocks at both chains.
+	pub block_time: Duration,
+	/// Maximal duration of the run. If the stop condition isn't met until then, the run fails.
+	pub timeout: Duration,
+	/// Parameters of finality relays (used in both directions).
+	pub finality: FinalitySyncParams,
+	/// Parameters of messages relay.
+	pub messages: MessageLaneParams,
+}
+
+impl Default for BridgeParams {
+	fn default() -> Self {
+		BridgeParams {
+			block_time: Duration::from_millis(20),
+			timeout: Duration::from_secs(60),
+			finality: FinalitySyncParams {
+				tick: Duration::from_millis(10),
+				recent_finality_proofs_limit: 1024,
+				stall_timeout: Duration::from_secs(60),
+				submission_budget: None,
+				start_block: None,
+				only_mandatory_headers: false,
+			},
+			messages: MessageLaneParams {
+				lane: [0, 0, 0, 0],
+				source_tick: Duration::from_millis(10),
+				target_tick: Duration::from_millis(10),
+				reconnect_delay: Duration::from_millis(0),
+				stall_timeout: Duration::from_secs(1),
+				race_stall_timeout: Duration::from_secs(1),
+				delivery_params: MessageDeliveryParams {
+					max_unrewarded_relayer_entries_at_target: 4,
+					max_unconfirmed_nonces_at_target: 16,
+					max_messages_in_single_batch: 4,
+					max_messages_weight_in_single_batch: 4,
+					max_messages_size_in_single_batch: 4,
+					skip_outdated_messages: false,
+				},
+			},
+		}
+	}
+}
+
+/// Mock bridge between two mock chains.
+pub struct MockBridge {
+	state: Arc<Mutex<MockBridgeState>>,
+}
+
+impl MockBridge {
+	/// Create new mock bridge with given scripts of source and target chains.
+	pub fn new(source_script: ChainScript, target_script: ChainScript) -> Self {
+		MockBridge {
+			state: Arc::new(Mutex::new(MockBridgeState {
+				source: MockChain::new(source_script),
+				target: MockChain::new(target_script),
+			})),
+		}
+	}
+
+	/// Returns client of the chain at given side of the bridge.
+	pub fn client(&self, side: Side) -> MockClient {
+		MockClient::new(self.state.clone(), side)
+	}
+
+	/// Start finality relays in both directions and messages relay from source to target chain.
+	/// Blocks are produced at both chains until `stop_condition` returns true. Returns final
+	/// state of both chains.
+	pub fn run(
+		self,
+		params: BridgeParams,
+		stop_condition: impl Fn(&MockBridgeState) -> bool,
+	) -> Result<MockBridgeState, String> {
+		async_std::task::block_on(async move {
+			let (exit_sender, exit_receiver) = oneshot::channel::<()>();
+			let exit_signal = exit_receiver.map(|_| ()).shared();
+
+			let source_to_target_finality = finality_relay::run(
+				self.client(Side::Source),
+				self.client(Side::Target),
+				params.finality.clone(),
+				MetricsParams::disabled(),
+				exit_signal.clone(),
+			);
+			let target_to_source_finality = finality_relay::run(
+				self.client(Side::Target),
+				self.client(Side::Source),
+				params.finality.clone(),
+				MetricsParams::disabled(),
+				exit_signal.clone(),
+			);
+			let messages = messages_relay::message_lane_loop::run(
+				params.messages.clone(),
+				self.client(Side::Source),
+				self.client(Side::Target),
+				MetricsParams::disabled(),
+				exit_signal,
+			);
+
+			let state = self.state.clone();
+			let block_production = async move {
+				let started_at = Instant::now();
+				let result = loop {
+					async_std::task::sleep(params.block_time).await;
+
+					let mut state = state.lock();
+					state.source.produce_block();
+					state.target.produce_block();
+					if stop_condition(&*state) {
+						break Ok(());
+					}
+					if started_at.elapsed() > params.timeout {
+						break Err(format!(
+							"Stop condition is not met in {:?}. State: {:#?}",
+							params.timeout, *state
+						));
+					}
+				};
+
+				let _ = exit_sender.send(());
+				result
+			};
+
+			let (result, source_to_target_finality, target_to_source_finality, messages) = futures::join!(
+				block_production,
+				source_to_target_finality,
+				target_to_source_finality,
+				messages,
+			);
+			result?;
+			source_to_target_finality?;
+			target_to_source_finality?;
+			messages?;
+
+			let state = self.state.lock().clone();
+			Ok(state)
+		})
+	}
+}
@@ -0,0 +1,356 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Programmable mock chain.
+
+use bp_header_chain::FinalityProof;
+use bp_messages::{MessageNonce, UnrewardedRelayersState};
+use finality_relay::SourceHeader;
+use futures::channel::mpsc::UnboundedSender;
+use relay_utils::HeaderId;
+use std::{
+	collections::{BTreeMap, BTreeSet, VecDeque},
+	ops::RangeInclusive,
+};
+
+/// Number of mock chain header.
+pub type MockNumber = u64;
+/// Hash of mock chain header. Every mock chain has single fork, so hash is equal to header number.
+pub type MockHash = u64;
+/// Id of mock chain header.
+pub type MockHeaderId = HeaderId<MockHash, MockNumber>;
+/// Messages proof: range of delivered nonces and (optionally) latest confirmed nonce at the source chain.
+pub type MockMessagesProof = (RangeInclusive<MessageNonce>, Option<MessageNonce>);
+/// Messages receiving proof: latest nonce that has been received by the target chain.
+pub type MockMessagesReceivingProof = MessageNonce;
+
+/// Header of the mock chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockHeader {
+	/// Header number.
+	pub number: MockNumber,
+	/// True if header changes finality authorities set.
+	pub is_mandatory: bool,
+}
+
+impl SourceHeader<MockNumber> for MockHeader {
+	fn number(&self) -> MockNumber {
+		self.number
+	}
+
+	fn is_mandatory(&self) -> bool {
+		self.is_mandatory
+	}
+}
+
+/// Finality proof of the mock chain header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockFinalityProof(pub MockNumber);
+
+impl FinalityProof<MockNumber> for MockFinalityProof {
+	fn target_header_number(&self) -> MockNumber {
+		self.0
+	}
+}
+
+/// Scripted behavior of the mock chain.
+#[derive(Debug, Clone)]
+pub struct ChainScript {
+	/// Number of blocks between header import and its finalization.
+	pub finality_delay: MockNumber,
+	/// Finality proof is generated for every header which number is divisible by this value.
+	/// Finality proofs for mandatory headers are always generated.
+	pub justification_period: MockNumber,
+	/// Numbers of mandatory headers.
+	pub mandatory_headers: BTreeSet<MockNumber>,
+	/// Number of outbound messages that are generated at every block.
+	pub messages_per_block: MessageNonce,
+	/// Maximal number of outbound messages that the chain generates.
+	pub max_messages: MessageNonce,
+	/// Number of blocks between transaction submission and its inclusion. Transactions are included
+	/// into the next block if this is zero.
+	pub inclusion_delay: MockNumber,
+	/// Number of next messages-related transactions that are accepted, but never included
+	/// into the chain.
+	///
+	/// Only messages transactions are affected, because the finality relay restarts with a
+	/// fixed (large) delay when its transaction is lost.
+	pub lost_message_transactions: usize,
+	/// Number of next messages-related requests that will fail with connection error.
+	///
+	/// Only messages requests are affected, because the finality relay reconnects with a
+	/// fixed (large) delay.
+	pub failing_message_requests: usize,
+}
+
+impl Default for ChainScript {
+	fn default() -> Self {
+		ChainScript {
+			finality_delay: 0,
+			justification_period: 1,
+			mandatory_headers: BTreeSet::new(),
+			messages_per_block: 0,
+			max_messages: 0,
+			inclusion_delay: 0,
+			lost_message_transactions: 0,
+			failing_message_requests: 0,
+		}
+	}
+}
+
+/// Transaction that may be submitted to the mock chain.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockTransaction {
+	/// Import finalized peer chain header.
+	FinalityProof(MockHeader, MockFinalityProof),
+	/// Deliver messages, proved at given peer chain header.
+	MessagesProof(MockHeaderId, MockMessagesProof),
+	/// Confirm messages delivery, proved at given peer chain header.
+	MessagesReceivingProof(MockHeaderId, MockMessagesReceivingProof),
+}
+
+impl MockTransaction {
+	/// Returns true if this is messages-related transaction.
+	pub fn is_message_transaction(&self) -> bool {
+		!matches!(*self, MockTransaction::FinalityProof(..))
+	}
+}
+
+/// State of the single (bidirectional) lane at the mock chain.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaneState {
+	/// Nonce of the latest generated outbound message.
+	pub latest_generated_nonce: MessageNonce,
+	/// Nonce of the latest outbound message, which delivery has been confirmed.
+	pub latest_confirmed_nonce: MessageNonce,
+	/// Nonce of the latest received inbound message.
+	pub latest_received_nonce: MessageNonce,
+	/// Nonce of the latest received inbound message, which receiving has been confirmed
+	/// by the peer chain.
+	pub latest_confirmed_received_nonce: MessageNonce,
+	/// Inbound messages ranges that have been delivered by single transaction and are not
+	/// yet confirmed by the peer chain.
+	pub unrewarded_relayer_entries: VecDeque<RangeInclusive<MessageNonce>>,
+}
+
+impl LaneState {
+	/// Returns state of unrewarded relayers set at the inbound lane.
+	pub fn unrewarded_relayers_state(&self) -> UnrewardedRelayersState {
+		UnrewardedRelayersState {
+			unrewarded_relayer_entries: self.unrewarded_relayer_entries.len() as MessageNonce,
+			messages_in_oldest_entry: self
+				.unrewarded_relayer_entries
+				.front()
+				.map(|entry| entry.end() - entry.start() + 1)
+				.unwrap_or(0),
+			total_messages: self.latest_received_nonce - self.latest_confirmed_received_nonce,
+		}
+	}
+}
+
+/// Mock chain.
+#[derive(Debug, Clone, Default)]
+pub struct MockChain {
+	/// Scripted behavior of the chain.
+	pub script: ChainScript,
+	/// Number of the best header.
+	pub best_number: MockNumber,
+	/// Number of the best finalized header.
+	pub best_finalized_number: MockNumber,
+	/// Finality proofs that have been generated by the chain.
+	pub finality_proofs: BTreeMap<MockNumber, MockFinalityProof>,
+	/// Number of the best finalized header of the peer chain, known to this chain.
+	pub best_finalized_peer_number: MockNumber,
+	/// Numbers of peer chain headers that have been imported by this chain.
+	pub imported_peer_headers: Vec<MockNumber>,
+	/// Current state of the lane.
+	pub lane: LaneState,
+	/// State of the lane at every header.
+	pub lane_history: BTreeMap<MockNumber, LaneState>,
+	/// Submitted transactions along with number of the block where they will be included.
+	pub pending_transactions: Vec<(MockNumber, MockTransaction)>,
+	/// Transactions that have been included into the chain.
+	pub included_transactions: Vec<MockTransaction>,
+	/// Transactions that have been rejected by the chain.
+	pub rejected_transactions: Vec<MockTransaction>,
+	/// Transactions that have been lost.
+	pub lost_transactions: Vec<MockTransaction>,
+	/// Peer chain headers that have been required by the messages relay.
+	pub required_peer_headers: Vec<MockHeaderId>,
+	/// Number of times clients have reconnected to this chain.
+	pub reconnects: usize,
+	/// Subscribers to finality proofs of this chain.
+	finality_proofs_subscribers: Vec<UnboundedSender<MockFinalityProof>>,
+}
+
+impl MockChain {
+	/// Create new mock chain with given script.
+	pub fn new(script: ChainScript) -> Self {
+		let mut chain = MockChain {
+			script,
+			..Default::default()
+		};
+		chain.lane_history.insert(0, LaneState::default());
+		chain
+	}
+
+	/// Returns id of header with given number.
+	pub fn header_id(number: MockNumber) -> MockHeaderId {
+		HeaderId(number, number)
+	}
+
+	/// Returns header with given number.
+	pub fn header(&self, number: MockNumber) -> MockHeader {
+		MockHeader {
+			number,
+			is_mandatory: self.script.mandatory_headers.contains(&number),
+		}
+	}
+
+	/// Returns state of the lane at given header.
+	pub fn lane_at(&self, id: MockHeaderId) -> LaneState {
+		self.lane_history
+			.range(..=id.0)
+			.next_back()
+			.map(|(_, lane)| lane.clone())
+			.unwrap_or_default()
+	}
+
+	/// Subscribe to finality proofs of this chain.
+	pub fn subscribe_to_finality_proofs(&mut self, subscriber: UnboundedSender<MockFinalityProof>) {
+		self.finality_proofs_subscribers.push(subscriber);
+	}
+
+	/// Called by messages clients before serving any request.
+	pub fn on_message_request(&mut self) -> bool {
+		if self.script.failing_message_requests == 0 {
+			return true;
+		}
+
+		self.script.failing_message_requests -= 1;
+		false
+	}
+
+	/// Submit transaction to the chain.
+	pub fn submit_transaction(&mut self, transaction: MockTransaction) {
+		if transaction.is_message_transaction() && self.script.lost_message_transactions != 0 {
+			self.script.lost_message_transactions -= 1;
+			self.lost_transactions.push(transaction);
+			return;
+		}
+
+		let inclusion_block = self.best_number + std::cmp::max(self.script.inclusion_delay, 1);
+		self.pending_transactions.push((inclusion_block, transaction));
+	}
+
+	/// Produce next block.
+	pub fn produce_block(&mut self) {
+		self.best_number += 1;
+
+		// include transactions
+		let best_number = self.best_number;
+		let (ready_transactions, pending_transactions) = std::mem::take(&mut self.pending_transactions)
+			.into_iter()
+			.partition::<Vec<_>, _>(|(inclusion_block, _)| *inclusion_block <= best_number);
+		self.pending_transactions = pending_transactions;
+		for (_, transaction) in ready_transactions {
+			if self.apply_transaction(&transaction) {
+				self.included_transactions.push(transaction);
+			} else {
+				self.rejected_transactions.push(transaction);
+			}
+		}
+
+		// generate messages
+		let messages_to_generate = std::cmp::min(
+			self.script.messages_per_block,
+			self.script
+				.max_messages
+				.saturating_sub(self.lane.latest_generated_nonce),
+		);
+		self.lane.latest_generated_nonce += messages_to_generate;
+		self.lane_history.insert(best_number, self.lane.clone());
+
+		// finalize headers
+		let new_best_finalized_number = best_number.saturating_sub(self.script.finality_delay);
+		for number in self.best_finalized_number + 1..=new_best_finalized_number {
+			let is_justified = self.script.mandatory_headers.contains(&number)
+				|| number % std::cmp::max(self.script.justification_period, 1) == 0;
+			if is_justified {
+				let proof = MockFinalityProof(number);
+				self.finality_proofs.insert(number, proof.clone());
+				self.finality_proofs_subscribers
+					.retain(|subscriber| subscriber.unbounded_send(proof.clone()).is_ok());
+			}
+		}
+		self.best_finalized_number = std::cmp::max(self.best_finalized_number, new_best_finalized_number);
+	}
+
+	/// Apply transaction to the chain state. Returns false if transaction is rejected.
+	fn apply_transaction(&mut self, transaction: &MockTransaction) -> bool {
+		match *transaction {
+			MockTransaction::FinalityProof(ref header, _) => {
+				if header.number <= self.best_finalized_peer_number {
+					return false;
+				}
+
+				self.best_finalized_peer_number = header.number;
+				self.imported_peer_headers.push(header.number);
+			}
+			MockTransaction::MessagesProof(ref at_peer_header, (ref nonces, latest_confirmed_nonce)) => {
+				if at_peer_header.0 > self.best_finalized_peer_number
+					|| *nonces.start() != self.lane.latest_received_nonce + 1
+					|| nonces.end() < nonces.start()
+				{
+					return false;
+				}
+
+				self.lane.latest_received_nonce = *nonces.end();
+				self.lane.unrewarded_relayer_entries.push_back(nonces.clone());
+				if let Some(latest_confirmed_nonce) = latest_confirmed_nonce {
+					self.confirm_received_nonce(latest_confirmed_nonce);
+				}
+			}
+			MockTransaction::MessagesReceivingProof(ref at_peer_header, latest_received_nonce) => {
+				if at_peer_header.0 > self.best_finalized_peer_number
+					|| latest_received_nonce <= self.lane.latest_confirmed_nonce
+					|| latest_received_nonce > self.lane.latest_generated_nonce
+				{
+					return false;
+				}
+
+				self.lane.latest_confirmed_nonce = latest_received_nonce;
+			}
+		}
+
+		true
+	}
+
+	/// Prune unrewarded relayer entries that are confirmed by the peer chain.
+	fn confirm_received_nonce(&mut self, latest_confirmed_nonce: MessageNonce) {
+		if latest_confirmed_nonce <= self.lane.latest_confirmed_received_nonce {
+			return;
+		}
+
+		self.lane.latest_confirmed_received_nonce = latest_confirmed_nonce;
+		while let Some(entry) = self.lane.unrewarded_relayer_entries.front() {
+			if *entry.end() > latest_confirmed_nonce {
+				break;
+			}
+			self.lane.unrewarded_relayer_entries.pop_front();
+		}
+	}
+}
@@ -0,0 +1,301 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mock chain client, implementing all relay client traits.
+
+use crate::bridge::MockBridgeState;
+use crate::chain::{
+	MockChain, MockFinalityProof, MockHash, MockHeader, MockHeaderId, MockMessagesProof, MockMessagesReceivingProof,
+	MockNumber, MockTransaction,
+};
+
+use async_trait::async_trait;
+use bp_messages::{MessageNonce, UnrewardedRelayersState};
+use finality_relay::{
+	FinalitySyncPipeline, SourceClient as FinalitySourceClient, TargetClient as FinalityTargetClient,
+};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use messages_relay::{
+	message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf},
+	message_lane_loop::{
+		ClientState, MessageProofParameters, MessageWeights, MessageWeightsMap,
+		SourceClient as MessageLaneSourceClient, SourceClientState, TargetClient as MessageLaneTargetClient,
+		TargetClientState,
+	},
+};
+use parking_lot::Mutex;
+use relay_utils::{relay_loop::Client as RelayClient, MaybeConnectionError};
+use std::{ops::RangeInclusive, sync::Arc};
+
+/// Finality pipeline of the mock bridge. The same pipeline is used in both directions.
+#[derive(Debug, Clone)]
+pub struct MockFinalityPipeline;
+
+impl FinalitySyncPipeline for MockFinalityPipeline {
+	const SOURCE_NAME: &'static str = "MockSource";
+	const TARGET_NAME: &'static str = "MockTarget";
+
+	type Hash = MockHash;
+	type Number = MockNumber;
+	type Header = MockHeader;
+	type FinalityProof = MockFinalityProof;
+}
+
+/// Message lane of the mock bridge (source -> target).
+#[derive(Debug, Clone)]
+pub struct MockMessageLane;
+
+impl MessageLane for MockMessageLane {
+	const SOURCE_NAME: &'static str = "MockSource";
+	const TARGET_NAME: &'static str = "MockTarget";
+
+	type MessagesProof = MockMessagesProof;
+	type MessagesReceivingProof = MockMessagesReceivingProof;
+
+	type SourceHeaderNumber = MockNumber;
+	type SourceHeaderHash = MockHash;
+
+	type TargetHeaderNumber = MockNumber;
+	type TargetHeaderHash = MockHash;
+}
+
+/// Error returned by mock client.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockError {
+	/// Scripted connection error.
+	Connection,
+	/// Requested header is not yet known to the chain.
+	MissingHeader(MockNumber),
+}
+
+impl MaybeConnectionError for MockError {
+	fn is_connection_error(&self) -> bool {
+		matches!(*self, MockError::Connection)
+	}
+}
+
+/// Side of the mock bridge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+	/// Source chain of the message lane.
+	Source,
+	/// Target chain of the message lane.
+	Target,
+}
+
+/// Client of the single mock chain.
+#[derive(Clone)]
+pub struct MockClient {
+	state: Arc<Mutex<MockBridgeState>>,
+	side: Side,
+}
+
+impl MockClient {
+	/// Create client of the chain at given side of the bridge.
+	pub(crate) fn new(state: Arc<Mutex<MockBridgeState>>, side: Side) -> Self {
+		MockClient { state, side }
+	}
+
+	/// Execute given function with the chain of this client.
+	fn with_chain<R>(&self, f: impl FnOnce(&mut MockChain) -> R) -> R {
+		let mut state = self.state.lock();
+		match self.side {
+			Side::Source => f(&mut state.source),
+			Side::Target => f(&mut state.target),
+		}
+	}
+
+	/// Execute given messages-related request with the chain of this client.
+	fn message_request<R>(&self, f: impl FnOnce(&mut MockChain) -> R) -> Result<R, MockError> {
+		self.with_chain(|chain| {
+			if !chain.on_message_request() {
+				return Err(MockError::Connection);
+			}
+
+			Ok(f(chain))
+		})
+	}
+
+	/// Returns state of the chain, as seen by messages relay.
+	fn client_state(&self) -> Result<ClientState<MockHeaderId, MockHeaderId>, MockError> {
+		self.message_request(|chain| ClientState {
+			best_self: MockChain::header_id(chain.best_number),
+			best_finalized_self: MockChain::header_id(chain.best_finalized_number),
+			best_finalized_peer_at_best_self: MockChain::header_id(chain.best_finalized_peer_number),
+			is_halted: false,
+		})
+	}
+}
+
+#[async_trait]
+impl RelayClient for MockClient {
+	type Error = MockError;
+
+	async fn reconnect(&mut self) -> Result<(), MockError> {
+		self.with_chain(|chain| chain.reconnects += 1);
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl FinalitySourceClient<MockFinalityPipeline> for MockClient {
+	type FinalityProofsStream = UnboundedReceiver<MockFinalityProof>;
+
+	async fn best_finalized_block_number(&self) -> Result<MockNumber, MockError> {
+		Ok(self.with_chain(|chain| chain.best_finalized_number))
+	}
+
+	async fn header_and_finality_proof(
+		&self,
+		number: MockNumber,
+	) -> Result<(MockHeader, Option<MockFinalityProof>), MockError> {
+		self.with_chain(|chain| {
+			if number > chain.best_number {
+				return Err(MockError::MissingHeader(number));
+			}
+
+			Ok((chain.header(number), chain.finality_proofs.get(&number).cloned()))
+		})
+	}
+
+	async fn finality_proofs(&self) -> Result<Self::FinalityProofsStream, MockError> {
+		let (sender, receiver) = unbounded();
+		self.with_chain(|chain| chain.subscribe_to_finality_proofs(sender));
+		Ok(receiver)
+	}
+}
+
+#[async_trait]
+impl FinalityTargetClient<MockFinalityPipeline> for MockClient {
+	async fn best_finalized_source_block_number(&self) -> Result<MockNumber, MockError> {
+		Ok(self.with_chain(|chain| chain.best_finalized_peer_number))
+	}
+
+	async fn submit_finality_proof(&self, header: MockHeader, proof: MockFinalityProof) -> Result<(), MockError> {
+		self.with_chain(|chain| chain.submit_transaction(MockTransaction::FinalityProof(header, proof)));
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl MessageLaneSourceClient<MockMessageLane> for MockClient {
+	async fn state(&self) -> Result<SourceClientState<MockMessageLane>, MockError> {
+		self.client_state()
+	}
+
+	async fn latest_generated_nonce(&self, id: MockHeaderId) -> Result<(MockHeaderId, MessageNonce), MockError> {
+		self.message_request(|chain| (id, chain.lane_at(id).latest_generated_nonce))
+	}
+
+	async fn latest_confirmed_received_nonce(
+		&self,
+		id: MockHeaderId,
+	) -> Result<(MockHeaderId, MessageNonce), MockError> {
+		self.message_request(|chain| (id, chain.lane_at(id).latest_confirmed_nonce))
+	}
+
+	async fn generated_messages_weights(
+		&self,
+		id: MockHeaderId,
+		nonces: RangeInclusive<MessageNonce>,
+	) -> Result<MessageWeightsMap, MockError> {
+		self.message_request(|chain| {
+			let latest_generated_nonce = chain.lane_at(id).latest_generated_nonce;
+			nonces
+				.filter(|nonce| *nonce <= latest_generated_nonce)
+				.map(|nonce| (nonce, MessageWeights { weight: 1, size: 1 }))
+				.collect()
+		})
+	}
+
+	async fn prove_messages(
+		&self,
+		id: MockHeaderId,
+		nonces: RangeInclusive<MessageNonce>,
+		proof_parameters: MessageProofParameters,
+	) -> Result<(MockHeaderId, RangeInclusive<MessageNonce>, MockMessagesProof), MockError> {
+		self.message_request(|chain| {
+			let latest_confirmed_nonce = if proof_parameters.outbound_state_proof_required {
+				Some(chain.lane_at(id).latest_confirmed_nonce)
+			} else {
+				None
+			};
+			(id, nonces.clone(), (nonces, latest_confirmed_nonce))
+		})
+	}
+
+	async fn submit_messages_receiving_proof(
+		&self,
+		generated_at_block: TargetHeaderIdOf<MockMessageLane>,
+		proof: MockMessagesReceivingProof,
+	) -> Result<(), MockError> {
+		self.message_request(|chain| {
+			chain.submit_transaction(MockTransaction::MessagesReceivingProof(generated_at_block, proof))
+		})
+	}
+
+	async fn require_target_header_on_source(&self, id: TargetHeaderIdOf<MockMessageLane>) {
+		self.with_chain(|chain| chain.required_peer_headers.push(id));
+	}
+}
+
+#[async_trait]
+impl MessageLaneTargetClient<MockMessageLane> for MockClient {
+	async fn state(&self) -> Result<TargetClientState<MockMessageLane>, MockError> {
+		self.client_state()
+	}
+
+	async fn latest_received_nonce(&self, id: MockHeaderId) -> Result<(MockHeaderId, MessageNonce), MockError> {
+		self.message_request(|chain| (id, chain.lane_at(id).latest_received_nonce))
+	}
+
+	async fn latest_confirmed_received_nonce(
+		&self,
+		id: MockHeaderId,
+	) -> Result<(MockHeaderId, MessageNonce), MockError> {
+		self.message_request(|chain| (id, chain.lane_at(id).latest_confirmed_received_nonce))
+	}
+
+	async fn unrewarded_relayers_state(
+		&self,
+		id: MockHeaderId,
+	) -> Result<(MockHeaderId, UnrewardedRelayersState), MockError> {
+		self.message_request(|chain| (id, chain.lane_at(id).unrewarded_relayers_state()))
+	}
+
+	async fn prove_messages_receiving(
+		&self,
+		id: MockHeaderId,
+	) -> Result<(MockHeaderId, MockMessagesReceivingProof), MockError> {
+		self.message_request(|chain| (id, chain.lane_at(id).latest_received_nonce))
+	}
+
+	async fn submit_messages_proof(
+		&self,
+		generated_at_header: SourceHeaderIdOf<MockMessageLane>,
+		nonces: RangeInclusive<MessageNonce>,
+		proof: MockMessagesProof,
+	) -> Result<RangeInclusive<MessageNonce>, MockError> {
+		self.message_request(|chain| {
+			chain.submit_transaction(MockTransaction::MessagesProof(generated_at_header, proof));
+			nonces
+		})
+	}
+
+	async fn require_source_header_on_target(&self, id: SourceHeaderIdOf<MockMessageLane>) {
+		self.with_chain(|chain| chain.required_peer_headers.push(id));
+	}
+}
@@ -0,0 +1,39 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Framework for testing complete headers+messages relay as a unit.
+//!
+//! The crate provides mock source and target chains, which implement all relay client traits of
+//! both finality and messages relays. Behavior of every chain is programmed using [`ChainScript`]:
+//! it defines how headers are finalized, how many messages are generated, how long it takes to
+//! include submitted transactions and what failures must happen. The [`MockBridge`] starts both
+//! finality relays and the messages relay over mock chains and produces blocks until the given
+//! condition is met. This allows testing failure-recovery paths that are impossible to hit reliably
+//! when running relay against real nodes.
+
+#![warn(missing_docs)]
+
+pub use crate::bridge::{BridgeParams, MockBridge, MockBridgeState};
+pub use crate::chain::{
+	ChainScript, LaneState, MockChain, MockFinalityProof, MockHash, MockHeader, MockHeaderId, MockMessagesProof,
+	MockMessagesReceivingProof, MockNumber, MockTransaction,
+};
+pub use crate::client::{MockClient, MockError, MockFinalityPipeline, MockMessageLane, Side};
+
+mod bridge;
+mod chain;
+mod client;
+mod tests;
@@ -0,0 +1,145 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests of complete headers+messages relay over mock chains.
+
+#![cfg(test)]
+
+use crate::{BridgeParams, ChainScript, MockBridge, MockTransaction};
+
+use std::time::Duration;
+
+fn source_with_messages(messages: u64) -> ChainScript {
+	ChainScript {
+		messages_per_block: 2,
+		max_messages: messages,
+		..Default::default()
+	}
+}
+
+#[test]
+fn headers_and_messages_are_relayed() {
+	let state = MockBridge::new(source_with_messages(10), ChainScript::default())
+		.run(BridgeParams::default(), |state| {
+			state.source.lane.latest_confirmed_nonce == 10
+		})
+		.unwrap();
+
+	assert_eq!(state.target.lane.latest_received_nonce, 10);
+	assert!(state.target.best_finalized_peer_number > 0);
+	assert!(state.source.best_finalized_peer_number > 0);
+	// all delivered messages have been proved at headers that were known to the target chain
+	assert!(!state
+		.target
+		.rejected_transactions
+		.iter()
+		.any(MockTransaction::is_message_transaction));
+}
+
+#[test]
+fn messages_are_relayed_when_finality_and_inclusion_are_slow() {
+	let slow_chain = |script: ChainScript| ChainScript {
+		finality_delay: 3,
+		justification_period: 4,
+		inclusion_delay: 3,
+		..script
+	};
+	let state = MockBridge::new(slow_chain(source_with_messages(10)), slow_chain(ChainScript::default()))
+		.run(BridgeParams::default(), |state| {
+			state.source.lane.latest_confirmed_nonce == 10
+		})
+		.unwrap();
+
+	assert_eq!(state.target.lane.latest_received_nonce, 10);
+	// only justified headers may be imported
+	assert!(state
+		.target
+		.imported_peer_headers
+		.iter()
+		.chain(state.source.imported_peer_headers.iter())
+		.all(|number| number % 4 == 0));
+}
+
+#[test]
+fn mandatory_headers_are_relayed_when_finality_proofs_are_sparse() {
+	let source = ChainScript {
+		justification_period: 1_000,
+		mandatory_headers: vec![5, 10, 15].into_iter().collect(),
+		..Default::default()
+	};
+	let state = MockBridge::new(source, ChainScript::default())
+		.run(BridgeParams::default(), |state| {
+			state.target.best_finalized_peer_number >= 15
+		})
+		.unwrap();
+
+	assert_eq!(state.target.imported_peer_headers, vec![5, 10, 15]);
+}
+
+#[test]
+fn relay_recovers_from_lost_message_transactions() {
+	let state = MockBridge::new(
+		ChainScript {
+			lost_message_transactions: 1,
+			..source_with_messages(10)
+		},
+		ChainScript {
+			lost_message_transactions: 2,
+			..Default::default()
+		},
+	)
+	.run(BridgeParams::default(), |state| {
+		state.source.lane.latest_confirmed_nonce == 10
+	})
+	.unwrap();
+
+	assert_eq!(state.source.lost_transactions.len(), 1);
+	assert_eq!(state.target.lost_transactions.len(), 2);
+	assert_eq!(state.target.lane.latest_received_nonce, 10);
+}
+
+#[test]
+fn relay_recovers_from_connection_errors() {
+	let state = MockBridge::new(
+		ChainScript {
+			failing_message_requests: 3,
+			..source_with_messages(10)
+		},
+		ChainScript {
+			failing_message_requests: 3,
+			..Default::default()
+		},
+	)
+	.run(BridgeParams::default(), |state| {
+		state.source.lane.latest_confirmed_nonce == 10
+	})
+	.unwrap();
+
+	assert_eq!(state.target.lane.latest_received_nonce, 10);
+	assert!(state.source.reconnects > 0);
+	assert!(state.target.reconnects > 0);
+}
+
+#[test]
+fn run_fails_when_stop_condition_is_not_met_in_time() {
+	let params = BridgeParams {
+		timeout: Duration::from_millis(100),
+		..Default::default()
+	};
+	assert!(MockBridge::new(ChainScript::default(), ChainScript::default())
+		.run(params, |state| state.source.lane.latest_confirmed_nonce == 10)
+		.is_err());
+}
@@ -26,12 +26,17 @@
 
 use crate::message_lane::{MessageLane, SourceHeaderIdOf, TargetHeaderIdOf};
 use crate::message_race_delivery::run as run_message_delivery_race;
+use crate::message_race_loop::RaceError;
 use crate::message_race_receiving::run as run_message_receiving_race;
 use crate::metrics::MessageLaneLoopMetrics;
 
 use async_trait::async_trait;
 use bp_messages::{LaneId, MessageNonce, UnrewardedRelayersState, Weight};
-use futures::{channel::mpsc::unbounded, future::FutureExt, stream::StreamExt};
+use futures::{
+	channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+	future::FutureExt,
+	stream::StreamExt,
+};
 use relay_utils::{
 	interval,
 	metrics::{GlobalMetrics, MetricsParams},
@@ -41,6 +46,10 @@ use relay_utils::{
 };
 use std::{collections::BTreeMap, fmt::Debug, future::Future, ops::RangeInclusive, time::Duration};
 
+/// If the race has stalled this number of times in a row (without delivering anything between
+/// restarts), the whole lane loop is restarted.
+const MAX_CONSECUTIVE_RACE_STALLS: u32 = 3;
+
 /// Message lane loop configuration params.
 #[derive(Debug, Clone)]
 pub struct Params {
@@ -54,6 +63,11 @@ pub struct Params {
 	pub reconnect_delay: Duration,
 	/// The loop will auto-restart if there has been no updates during this period.
 	pub stall_timeout: Duration,
+	/// If single race has made no progress during this period while its clients look healthy, only
+	/// this race is restarted (with fresh state). If the race keeps stalling after
+	/// `MAX_CONSECUTIVE_RACE_STALLS` restarts, the whole loop is restarted. The race that is waiting
+	/// for required headers isn't considered stalled.
+	pub race_stall_timeout: Duration,
 	/// Message delivery race parameters.
 	pub delivery_params: MessageDeliveryParams,
 }
@@ -75,6 +89,11 @@ pub struct MessageDeliveryParams {
 	pub max_messages_weight_in_single_batch: Weight,
 	/// Maximal cumulative size of relayed messages in single delivery transaction.
 	pub max_messages_size_in_single_batch: usize,
+	/// If `true`, only the latest messages that fit into single delivery transaction are delivered
+	/// and older undelivered messages are skipped. This may only be used at lanes with
+	/// `LaneOrdering::Unordered`, because target node rejects messages with nonce gaps at ordered
+	/// lanes.
+	pub skip_outdated_messages: bool,
 }
 
 /// Message weights.
@@ -194,6 +213,9 @@ pub struct ClientState<SelfHeaderId, PeerHeaderId> {
 	pub best_finalized_self: SelfHeaderId,
 	/// Best finalized header id of the peer chain read at the best block of this chain (at `best_finalized_self`).
 	pub best_finalized_peer_at_best_self: PeerHeaderId,
+	/// True if bridge pallets at this chain are halted (or the lane is closed) and won't accept any new
+	/// proofs. While this is true, the relay doesn't submit any transactions to this chain.
+	pub is_halted: bool,
 }
 
 /// State of source client in one-way message lane.
@@ -230,10 +252,11 @@ pub async fn run<P: MessageLane>(
 	exit_signal: impl Future<Output = ()> + Send + 'static,
 ) -> Result<(), String> {
 	let exit_signal = exit_signal.shared();
+	let persistent_counters = metrics_params.persistent_counters.clone();
 	relay_utils::relay_loop(source_client, target_client)
 		.reconnect_delay(params.reconnect_delay)
 		.with_metrics(Some(metrics_prefix::<P>(&params.lane)), metrics_params)
-		.loop_metric(|registry, prefix| MessageLaneLoopMetrics::new(registry, prefix))?
+		.loop_metric(|registry, prefix| MessageLaneLoopMetrics::new(registry, prefix, persistent_counters))?
 		.standalone_metric(|registry, prefix| GlobalMetrics::new(registry, prefix))?
 		.expose()
 		.await?
@@ -274,34 +297,44 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 	let target_go_offline_future = futures::future::Fuse::terminated();
 	let target_tick_stream = interval(params.target_tick).fuse();
 
+	// last known client states are sent to the races that are restarted
+	let mut last_source_state: Option<SourceClientState<P>> = None;
+	let mut last_target_state: Option<TargetClientState<P>> = None;
+
 	let (
-		(delivery_source_state_sender, delivery_source_state_receiver),
-		(delivery_target_state_sender, delivery_target_state_receiver),
-	) = (unbounded(), unbounded());
+		(mut delivery_source_state_sender, delivery_source_state_receiver),
+		(mut delivery_target_state_sender, delivery_target_state_receiver),
+	) = race_state_channels(&last_source_state, &last_target_state);
 	let delivery_race_loop = run_message_delivery_race(
 		source_client.clone(),
 		delivery_source_state_receiver,
 		target_client.clone(),
 		delivery_target_state_receiver,
 		params.stall_timeout,
+		params.race_stall_timeout,
 		metrics_msg.clone(),
-		params.delivery_params,
+		params.delivery_params.clone(),
 	)
 	.fuse();
+	let mut restart_delivery_race = false;
+	let mut delivery_race_stalls = 0;
 
 	let (
-		(receiving_source_state_sender, receiving_source_state_receiver),
-		(receiving_target_state_sender, receiving_target_state_receiver),
-	) = (unbounded(), unbounded());
+		(mut receiving_source_state_sender, receiving_source_state_receiver),
+		(mut receiving_target_state_sender, receiving_target_state_receiver),
+	) = race_state_channels(&last_source_state, &last_target_state);
 	let receiving_race_loop = run_message_receiving_race(
 		source_client.clone(),
 		receiving_source_state_receiver,
 		target_client.clone(),
 		receiving_target_state_receiver,
 		params.stall_timeout,
+		params.race_stall_timeout,
 		metrics_msg.clone(),
 	)
 	.fuse();
+	let mut restart_receiving_race = false;
+	let mut receiving_race_stalls = 0;
 
 	let exit_signal = exit_signal.fuse();
 
@@ -334,6 +367,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 						);
 						let _ = delivery_source_state_sender.unbounded_send(new_source_state.clone());
 						let _ = receiving_source_state_sender.unbounded_send(new_source_state.clone());
+						last_source_state = Some(new_source_state.clone());
 
 						if let Some(metrics_msg) = metrics_msg.as_ref() {
 							metrics_msg.update_source_state::<P>(new_source_state);
@@ -365,6 +399,7 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 						);
 						let _ = delivery_target_state_sender.unbounded_send(new_target_state.clone());
 						let _ = receiving_target_state_sender.unbounded_send(new_target_state.clone());
+						last_target_state = Some(new_target_state.clone());
 
 						if let Some(metrics_msg) = metrics_msg.as_ref() {
 							metrics_msg.update_target_state::<P>(new_target_state);
@@ -385,13 +420,21 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 			delivery_error = delivery_race_loop => {
 				match delivery_error {
 					Ok(_) => unreachable!("only ends with error; qed"),
-					Err(err) => return Err(err),
+					Err(RaceError::Stalled { has_progressed }) => {
+						restart_delivery_race = true;
+						on_race_stalled::<P>("delivery", &mut delivery_race_stalls, has_progressed)?;
+					},
+					Err(RaceError::Client(err)) => return Err(err),
 				}
 			},
 			receiving_error = receiving_race_loop => {
 				match receiving_error {
 					Ok(_) => unreachable!("only ends with error; qed"),
-					Err(err) => return Err(err),
+					Err(RaceError::Stalled { has_progressed }) => {
+						restart_receiving_race = true;
+						on_race_stalled::<P>("receiving", &mut receiving_race_stalls, has_progressed)?;
+					},
+					Err(RaceError::Client(err)) => return Err(err),
 				}
 			},
 
@@ -400,6 +443,51 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 			}
 		}
 
+		if restart_delivery_race {
+			log::info!(target: "bridge", "Restarting {} -> {} delivery race", P::SOURCE_NAME, P::TARGET_NAME);
+
+			let ((source_state_sender, source_state_receiver), (target_state_sender, target_state_receiver)) =
+				race_state_channels(&last_source_state, &last_target_state);
+			delivery_source_state_sender = source_state_sender;
+			delivery_target_state_sender = target_state_sender;
+			delivery_race_loop.set(
+				run_message_delivery_race(
+					source_client.clone(),
+					source_state_receiver,
+					target_client.clone(),
+					target_state_receiver,
+					params.stall_timeout,
+					params.race_stall_timeout,
+					metrics_msg.clone(),
+					params.delivery_params.clone(),
+				)
+				.fuse(),
+			);
+			restart_delivery_race = false;
+		}
+
+		if restart_receiving_race {
+			log::info!(target: "bridge", "Restarting {} -> {} receiving race", P::TARGET_NAME, P::SOURCE_NAME);
+
+			let ((source_state_sender, source_state_receiver), (target_state_sender, target_state_receiver)) =
+				race_state_channels(&last_source_state, &last_target_state);
+			receiving_source_state_sender = source_state_sender;
+			receiving_target_state_sender = target_state_sender;
+			receiving_race_loop.set(
+				run_message_receiving_race(
+					source_client.clone(),
+					source_state_receiver,
+					target_client.clone(),
+					target_state_receiver,
+					params.stall_timeout,
+					params.race_stall_timeout,
+					metrics_msg.clone(),
+				)
+				.fuse(),
+			);
+			restart_receiving_race = false;
+		}
+
 		if source_client_is_online && source_state_required {
 			log::debug!(target: "bridge", "Asking {} node about its state", P::SOURCE_NAME);
 			source_state.set(source_client.state().fuse());
@@ -414,6 +502,53 @@ async fn run_until_connection_lost<P: MessageLane, SC: SourceClient<P>, TC: Targ
 	}
 }
 
+/// Update number of consecutive stalls of the race. Returns error if the race has stalled too many
+/// times in a row and the whole loop needs to be restarted.
+fn on_race_stalled<P: MessageLane>(
+	race_name: &str,
+	consecutive_stalls: &mut u32,
+	has_progressed: bool,
+) -> Result<(), FailedClient> {
+	*consecutive_stalls = if has_progressed { 1 } else { *consecutive_stalls + 1 };
+	if *consecutive_stalls >= MAX_CONSECUTIVE_RACE_STALLS {
+		log::warn!(
+			target: "bridge",
+			"{} -> {} {} race has stalled {} times in a row. Restarting the loop",
+			P::SOURCE_NAME,
+			P::TARGET_NAME,
+			race_name,
+			consecutive_stalls,
+		);
+
+		return Err(FailedClient::Both);
+	}
+
+	Ok(())
+}
+
+/// Sender and receiver of the client state updates.
+type StateChannel<S> = (UnboundedSender<S>, UnboundedReceiver<S>);
+
+/// Create channels that are used to pass client states to the race. Last known states (if any) are
+/// sent to the channels immediately.
+fn race_state_channels<P: MessageLane>(
+	last_source_state: &Option<SourceClientState<P>>,
+	last_target_state: &Option<TargetClientState<P>>,
+) -> (StateChannel<SourceClientState<P>>, StateChannel<TargetClientState<P>>) {
+	let (source_state_sender, source_state_receiver) = unbounded();
+	let (target_state_sender, target_state_receiver) = unbounded();
+	if let Some(last_source_state) = last_source_state {
+		let _ = source_state_sender.unbounded_send(last_source_state.clone());
+	}
+	if let Some(last_target_state) = last_target_state {
+		let _ = target_state_sender.unbounded_send(last_target_state.clone());
+	}
+	(
+		(source_state_sender, source_state_receiver),
+		(target_state_sender, target_state_receiver),
+	)
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
@@ -709,6 +844,22 @@ pub(crate) mod tests {
 		source_tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
 		target_tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
 		exit_signal: impl Future<Output = ()> + 'static + Send,
+	) -> TestClientData {
+		run_loop_test_with_race_stall_timeout(
+			data,
+			source_tick,
+			target_tick,
+			exit_signal,
+			Duration::from_millis(60 * 1000),
+		)
+	}
+
+	fn run_loop_test_with_race_stall_timeout(
+		data: TestClientData,
+		source_tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
+		target_tick: Arc<dyn Fn(&mut TestClientData) + Send + Sync>,
+		exit_signal: impl Future<Output = ()> + 'static + Send,
+		race_stall_timeout: Duration,
 	) -> TestClientData {
 		async_std::task::block_on(async {
 			let data = Arc::new(Mutex::new(data));
@@ -728,12 +879,14 @@ pub(crate) mod tests {
 					target_tick: Duration::from_millis(100),
 					reconnect_delay: Duration::from_millis(0),
 					stall_timeout: Duration::from_millis(60 * 1000),
+					race_stall_timeout,
 					delivery_params: MessageDeliveryParams {
 						max_unrewarded_relayer_entries_at_target: 4,
 						max_unconfirmed_nonces_at_target: 4,
 						max_messages_in_single_batch: 4,
 						max_messages_weight_in_single_batch: 4,
 						max_messages_size_in_single_batch: 4,
+						skip_outdated_messages: false,
 					},
 				},
 				source_client,
@@ -760,12 +913,14 @@ pub(crate) mod tests {
 					best_self: HeaderId(0, 0),
 					best_finalized_self: HeaderId(0, 0),
 					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
 				},
 				source_latest_generated_nonce: 1,
 				target_state: ClientState {
 					best_self: HeaderId(0, 0),
 					best_finalized_self: HeaderId(0, 0),
 					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
 				},
 				target_latest_received_nonce: 0,
 				..Default::default()
@@ -796,6 +951,169 @@ pub(crate) mod tests {
 		assert_eq!(result.submitted_messages_proofs, vec![(1..=1, None)],);
 	}
 
+	#[test]
+	fn race_stalls_are_counted_until_race_progresses() {
+		let mut stalls = 0;
+		assert_eq!(
+			on_race_stalled::<TestMessageLane>("delivery", &mut stalls, false),
+			Ok(())
+		);
+		assert_eq!(
+			on_race_stalled::<TestMessageLane>("delivery", &mut stalls, false),
+			Ok(())
+		);
+		assert_eq!(stalls, 2);
+
+		// the race has delivered something before last stall => it is the first stall in a row
+		assert_eq!(
+			on_race_stalled::<TestMessageLane>("delivery", &mut stalls, true),
+			Ok(())
+		);
+		assert_eq!(stalls, 1);
+
+		assert_eq!(
+			on_race_stalled::<TestMessageLane>("delivery", &mut stalls, false),
+			Ok(())
+		);
+		assert_eq!(
+			on_race_stalled::<TestMessageLane>("delivery", &mut stalls, false),
+			Err(FailedClient::Both),
+		);
+	}
+
+	#[test]
+	fn message_lane_loop_is_restarted_if_race_keeps_stalling() {
+		// the delivery transaction is submitted, but it never makes it to the target chain, so
+		// the delivery race keeps stalling while both clients are healthy
+		let (exit_sender, exit_receiver) = unbounded();
+		let result = run_loop_test_with_race_stall_timeout(
+			TestClientData {
+				source_state: ClientState {
+					best_self: HeaderId(0, 0),
+					best_finalized_self: HeaderId(0, 0),
+					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
+				},
+				source_latest_generated_nonce: 1,
+				target_state: ClientState {
+					best_self: HeaderId(0, 0),
+					best_finalized_self: HeaderId(0, 0),
+					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
+				},
+				target_latest_received_nonce: 0,
+				..Default::default()
+			},
+			Arc::new(|_: &mut TestClientData| {}),
+			Arc::new(move |data: &mut TestClientData| {
+				// submitted messages are never received by the target
+				data.target_latest_received_nonce = 0;
+				// clients are reconnected only when the whole loop is restarted
+				if data.is_target_reconnected {
+					let _ = exit_sender.unbounded_send(());
+				}
+			}),
+			exit_receiver.into_future().map(|(_, _)| ()),
+			Duration::from_millis(200),
+		);
+
+		assert!(result.is_source_reconnected);
+		assert!(!result.submitted_messages_proofs.is_empty());
+	}
+
+	#[test]
+	fn message_lane_loop_is_not_restarted_while_race_is_waiting_for_headers() {
+		// the message is generated at source header #10, but target never learns about this header,
+		// so the delivery race is waiting for headers all the time
+		let (exit_sender, exit_receiver) = unbounded();
+		let target_ticks = Arc::new(Mutex::new(0));
+		let result = run_loop_test_with_race_stall_timeout(
+			TestClientData {
+				source_state: ClientState {
+					best_self: HeaderId(10, 10),
+					best_finalized_self: HeaderId(10, 10),
+					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
+				},
+				source_latest_generated_nonce: 1,
+				target_state: ClientState {
+					best_self: HeaderId(0, 0),
+					best_finalized_self: HeaderId(0, 0),
+					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
+				},
+				target_latest_received_nonce: 0,
+				..Default::default()
+			},
+			Arc::new(|_: &mut TestClientData| {}),
+			Arc::new(move |_: &mut TestClientData| {
+				// that's much longer than `MAX_CONSECUTIVE_RACE_STALLS` race stall timeouts
+				let mut target_ticks = target_ticks.lock();
+				*target_ticks += 1;
+				if *target_ticks == 50 {
+					let _ = exit_sender.unbounded_send(());
+				}
+			}),
+			exit_receiver.into_future().map(|(_, _)| ()),
+			Duration::from_millis(200),
+		);
+
+		assert!(!result.is_source_reconnected);
+		assert!(!result.is_target_reconnected);
+		assert!(!result.source_to_target_header_requirements.is_empty());
+		assert!(result.submitted_messages_proofs.is_empty());
+	}
+
+	#[test]
+	fn message_lane_loop_does_not_submit_proofs_while_target_is_halted() {
+		let (exit_sender, exit_receiver) = unbounded();
+		let target_ticks = Arc::new(Mutex::new(0));
+		let result = run_loop_test(
+			TestClientData {
+				source_state: ClientState {
+					best_self: HeaderId(0, 0),
+					best_finalized_self: HeaderId(0, 0),
+					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
+				},
+				source_latest_generated_nonce: 1,
+				target_state: ClientState {
+					best_self: HeaderId(0, 0),
+					best_finalized_self: HeaderId(0, 0),
+					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: true,
+				},
+				target_latest_received_nonce: 0,
+				..Default::default()
+			},
+			Arc::new(|_: &mut TestClientData| {}),
+			Arc::new(move |data: &mut TestClientData| {
+				// nothing may be submitted while target pallets are halted
+				if data.target_state.is_halted {
+					assert!(data.submitted_messages_proofs.is_empty());
+				}
+
+				let mut target_ticks = target_ticks.lock();
+				*target_ticks += 1;
+				if *target_ticks == 10 {
+					data.target_state.is_halted = false;
+				}
+				if data.target_state.best_finalized_peer_at_best_self.0 < 10 {
+					data.target_state.best_finalized_peer_at_best_self = HeaderId(
+						data.target_state.best_finalized_peer_at_best_self.0 + 1,
+						data.target_state.best_finalized_peer_at_best_self.0 + 1,
+					);
+				}
+				if !data.submitted_messages_proofs.is_empty() {
+					exit_sender.unbounded_send(()).unwrap();
+				}
+			}),
+			exit_receiver.into_future().map(|(_, _)| ()),
+		);
+
+		assert_eq!(result.submitted_messages_proofs, vec![(1..=1, None)],);
+	}
+
 	#[test]
 	fn message_lane_loop_works() {
 		let (exit_sender, exit_receiver) = unbounded();
@@ -805,12 +1123,14 @@ pub(crate) mod tests {
 					best_self: HeaderId(10, 10),
 					best_finalized_self: HeaderId(10, 10),
 					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
 				},
 				source_latest_generated_nonce: 10,
 				target_state: ClientState {
 					best_self: HeaderId(0, 0),
 					best_finalized_self: HeaderId(0, 0),
 					best_finalized_peer_at_best_self: HeaderId(0, 0),
+					is_halted: false,
 				},
 				target_latest_received_nonce: 0,
 				..Default::default()
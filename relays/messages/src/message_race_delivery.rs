@@ -19,7 +19,7 @@ use crate::message_lane_loop::{
 	SourceClientState, TargetClient as MessageLaneTargetClient, TargetClientState,
 };
 use crate::message_race_loop::{
-	MessageRace, NoncesRange, RaceState, RaceStrategy, SourceClient, SourceClientNonces, TargetClient,
+	MessageRace, NoncesRange, RaceError, RaceState, RaceStrategy, SourceClient, SourceClientNonces, TargetClient,
 	TargetClientNonces,
 };
 use crate::message_race_strategy::BasicStrategy;
@@ -28,7 +28,6 @@ use crate::metrics::MessageLaneLoopMetrics;
 use async_trait::async_trait;
 use bp_messages::{MessageNonce, UnrewardedRelayersState, Weight};
 use futures::stream::FusedStream;
-use relay_utils::FailedClient;
 use std::{
 	collections::{BTreeMap, VecDeque},
 	marker::PhantomData,
@@ -43,9 +42,10 @@ pub async fn run<P: MessageLane>(
 	target_client: impl MessageLaneTargetClient<P>,
 	target_state_updates: impl FusedStream<Item = TargetClientState<P>>,
 	stall_timeout: Duration,
+	race_stall_timeout: Duration,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
 	params: MessageDeliveryParams,
-) -> Result<(), FailedClient> {
+) -> Result<(), RaceError> {
 	crate::message_race_loop::run(
 		MessageDeliveryRaceSource {
 			client: source_client,
@@ -60,12 +60,14 @@ pub async fn run<P: MessageLane>(
 		},
 		target_state_updates,
 		stall_timeout,
+		race_stall_timeout,
 		MessageDeliveryStrategy::<P> {
 			max_unrewarded_relayer_entries_at_target: params.max_unrewarded_relayer_entries_at_target,
 			max_unconfirmed_nonces_at_target: params.max_unconfirmed_nonces_at_target,
 			max_messages_in_single_batch: params.max_messages_in_single_batch,
 			max_messages_weight_in_single_batch: params.max_messages_weight_in_single_batch,
 			max_messages_size_in_single_batch: params.max_messages_size_in_single_batch,
+			skip_outdated_messages: params.skip_outdated_messages,
 			latest_confirmed_nonces_at_source: VecDeque::new(),
 			target_nonces: None,
 			strategy: BasicStrategy::new(),
@@ -204,9 +206,14 @@ where
 		nonces: RangeInclusive<MessageNonce>,
 		proof: P::MessagesProof,
 	) -> Result<RangeInclusive<MessageNonce>, Self::Error> {
-		self.client
+		let delivered_nonces = self
+			.client
 			.submit_messages_proof(generated_at_block, nonces, proof)
-			.await
+			.await?;
+		if let Some(metrics_msg) = self.metrics_msg.as_ref() {
+			metrics_msg.note_messages_delivered(&delivered_nonces);
+		}
+		Ok(delivered_nonces)
 	}
 }
 
@@ -233,6 +240,8 @@ struct MessageDeliveryStrategy<P: MessageLane> {
 	max_messages_weight_in_single_batch: Weight,
 	/// Maximal messages size in the single delivery transaction.
 	max_messages_size_in_single_batch: usize,
+	/// Skip outdated messages and only deliver the latest messages.
+	skip_outdated_messages: bool,
 	/// Latest confirmed nonces at the source client + the header id where we have first met this nonce.
 	latest_confirmed_nonces_at_source: VecDeque<(SourceHeaderIdOf<P>, MessageNonce)>,
 	/// Target nonces from the source client.
@@ -270,6 +279,7 @@ impl<P: MessageLane> std::fmt::Debug for MessageDeliveryStrategy<P> {
 				"max_messages_size_in_single_batch",
 				&self.max_messages_size_in_single_batch,
 			)
+			.field("skip_outdated_messages", &self.skip_outdated_messages)
 			.field(
 				"latest_confirmed_nonces_at_source",
 				&self.latest_confirmed_nonces_at_source,
@@ -293,8 +303,11 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 
 	fn required_source_header_at_target(&self, current_best: &SourceHeaderIdOf<P>) -> Option<SourceHeaderIdOf<P>> {
 		let header_required_for_messages_delivery = self.strategy.required_source_header_at_target(current_best);
-		let header_required_for_reward_confirmations_delivery =
-			self.latest_confirmed_nonces_at_source.back().map(|(id, _)| id.clone());
+		let header_required_for_reward_confirmations_delivery = self
+			.latest_confirmed_nonces_at_source
+			.back()
+			.filter(|(id, _)| id.0 > current_best.0)
+			.map(|(id, _)| id.clone());
 		match (
 			header_required_for_messages_delivery,
 			header_required_for_reward_confirmations_delivery,
@@ -470,7 +483,16 @@ impl<P: MessageLane> RaceStrategy<SourceHeaderIdOf<P>, TargetHeaderIdOf<P>, P::M
 			.checked_sub(future_confirmed_nonce_at_target)
 			.and_then(|diff| self.max_unconfirmed_nonces_at_target.checked_sub(diff))
 			.unwrap_or_default();
-		let max_nonces = std::cmp::min(max_nonces, self.max_messages_in_single_batch);
+		let max_nonces = if self.skip_outdated_messages {
+			// at unordered lanes we may skip outdated messages and deliver the latest messages
+			// instead. The target node accepts message if there are no more than
+			// `max_unconfirmed_nonces_at_target` unconfirmed messages before it (including itself)
+			let max_nonce = future_confirmed_nonce_at_target.saturating_add(self.max_unconfirmed_nonces_at_target);
+			self.strategy
+				.skip_outdated_nonces(race_state, max_nonce, self.max_messages_in_single_batch)
+		} else {
+			std::cmp::min(max_nonces, self.max_messages_in_single_batch)
+		};
 		let max_messages_weight_in_single_batch = self.max_messages_weight_in_single_batch;
 		let max_messages_size_in_single_batch = self.max_messages_size_in_single_batch;
 		let mut selected_weight: Weight = 0;
@@ -599,6 +621,7 @@ mod tests {
 			max_messages_in_single_batch: 4,
 			max_messages_weight_in_single_batch: 4,
 			max_messages_size_in_single_batch: 4,
+			skip_outdated_messages: false,
 			latest_confirmed_nonces_at_source: vec![(header_id(1), 19)].into_iter().collect(),
 			target_nonces: Some(TargetClientNonces {
 				latest_nonce: 19,
@@ -829,6 +852,19 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn message_delivery_strategy_skips_outdated_messages_if_configured() {
+		let (state, mut strategy) = prepare_strategy();
+
+		// only the latest messages are delivered if outdated messages may be skipped
+		strategy.skip_outdated_messages = true;
+		strategy.max_messages_in_single_batch = 3;
+		assert_eq!(
+			strategy.select_nonces_to_deliver(&state),
+			Some(((21..=23), proof_parameters(false, 3)))
+		);
+	}
+
 	#[test]
 	fn message_delivery_strategy_limits_batch_by_messages_count_when_there_are_unconfirmed_nonces() {
 		let (state, mut strategy) = prepare_strategy();
@@ -25,7 +25,7 @@ use crate::message_lane_loop::ClientState;
 use async_trait::async_trait;
 use bp_messages::MessageNonce;
 use futures::{
-	future::FutureExt,
+	future::{FusedFuture, FutureExt},
 	stream::{FusedStream, StreamExt},
 };
 use relay_utils::{process_future_result, retry_backoff, FailedClient, MaybeConnectionError};
@@ -35,6 +35,27 @@ use std::{
 	time::{Duration, Instant},
 };
 
+/// Error that terminates the race loop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RaceError {
+	/// Connection with given client(s) needs to be restarted. The whole lane loop is restarted.
+	Client(FailedClient),
+	/// The race has made no progress while both its clients have been healthy. Only this race
+	/// needs to be restarted (with fresh state).
+	Stalled {
+		/// True if the race has delivered some nonces or has been waiting for required source headers
+		/// at target before it has stalled. Waiting for headers means that the race itself hasn't been
+		/// stuck all the time.
+		has_progressed: bool,
+	},
+}
+
+impl From<FailedClient> for RaceError {
+	fn from(failed_client: FailedClient) -> Self {
+		RaceError::Client(failed_client)
+	}
+}
+
 /// One of races within lane.
 pub trait MessageRace {
 	/// Header id of the race source.
@@ -154,6 +175,10 @@ pub trait RaceStrategy<SourceHeaderId, TargetHeaderId, Proof>: Debug {
 	/// Should return true if nothing has to be synced.
 	fn is_empty(&self) -> bool;
 	/// Return id of source header that is required to be on target to continue synchronization.
+	///
+	/// Should return `None` if the next nonces may already be proved using the `current_best`
+	/// source header, known to the target. The proof is then generated at this (older) header
+	/// and we don't need to wait for a newer source header to be finalized at the target.
 	fn required_source_header_at_target(&self, current_best: &SourceHeaderId) -> Option<SourceHeaderId>;
 	/// Return best nonce at source node.
 	///
@@ -213,6 +238,7 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 	race_target: TC,
 	race_target_updated: impl FusedStream<Item = TargetClientState<P>>,
 	stall_timeout: Duration,
+	race_stall_timeout: Duration,
 	mut strategy: impl RaceStrategy<
 		P::SourceHeaderId,
 		P::TargetHeaderId,
@@ -221,10 +247,12 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 		ProofParameters = SC::ProofParameters,
 		TargetNoncesData = TC::TargetNoncesData,
 	>,
-) -> Result<(), FailedClient> {
+) -> Result<(), RaceError> {
 	let mut progress_context = Instant::now();
 	let mut race_state = RaceState::default();
 	let mut stall_countdown = Instant::now();
+	let mut has_progressed = false;
+	let mut is_waiting_for_headers = false;
 
 	let mut source_retry_backoff = retry_backoff();
 	let mut source_client_is_online = true;
@@ -235,6 +263,7 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 
 	let mut target_retry_backoff = retry_backoff();
 	let mut target_client_is_online = true;
+	let mut target_is_halted = false;
 	let mut target_best_nonces_required = false;
 	let mut target_finalized_nonces_required = false;
 	let target_best_nonces = futures::future::Fuse::terminated();
@@ -269,6 +298,27 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 			},
 			target_state = race_target_updated.next() => {
 				if let Some(target_state) = target_state {
+					if target_state.is_halted != target_is_halted {
+						target_is_halted = target_state.is_halted;
+						if target_is_halted {
+							log::warn!(
+								target: "bridge",
+								"Bridge pallets at {} are halted. Pausing {} -> {} race",
+								P::target_name(),
+								P::source_name(),
+								P::target_name(),
+							);
+						} else {
+							log::info!(
+								target: "bridge",
+								"Bridge pallets at {} are operational again. Resuming {} -> {} race",
+								P::target_name(),
+								P::source_name(),
+								P::target_name(),
+							);
+						}
+					}
+
 					let is_target_best_state_updated = race_state.best_target_header_id.as_ref()
 						!= Some(&target_state.best_self);
 
@@ -315,6 +365,8 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 					.best_finalized_source_header_id_at_best_target
 					.as_ref()
 					.and_then(|best|strategy.required_source_header_at_target(best));
+				is_waiting_for_headers = required_source_header_id.is_some();
+				has_progressed = has_progressed || is_waiting_for_headers;
 				if let Some(required_source_header_id) = required_source_header_id {
 					race_target.require_source_header(required_source_header_id).await;
 				}
@@ -337,12 +389,25 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 						strategy.best_target_nonces_updated(nonces, &mut race_state);
 						if strategy.best_at_target() != prev_best_at_target {
 							stall_countdown = Instant::now();
+							has_progressed = has_progressed || prev_best_at_target.is_some();
 						}
 					},
 					&mut target_go_offline_future,
 					async_std::task::sleep,
 					|| format!("Error retrieving best nonces from {}", P::target_name()),
 				).fail_if_connection_error(FailedClient::Target)?;
+
+				// nonces, that were provable using older source header, may have been delivered => ask for
+				// more headers if required headers are missing
+				let required_source_header_id = race_state
+					.best_finalized_source_header_id_at_best_target
+					.as_ref()
+					.and_then(|best|strategy.required_source_header_at_target(best));
+				is_waiting_for_headers = required_source_header_id.is_some();
+				has_progressed = has_progressed || is_waiting_for_headers;
+				if let Some(required_source_header_id) = required_source_header_id {
+					race_target.require_source_header(required_source_header_id).await;
+				}
 			},
 			nonces = target_finalized_nonces => {
 				target_finalized_nonces_required = false;
@@ -419,7 +484,22 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 
 		progress_context = print_race_progress::<P, _>(progress_context, &strategy);
 
-		if stall_countdown.elapsed() > stall_timeout {
+		// if there are no pending retries, we assume that both clients are healthy and the race itself
+		// is stuck => it is enough to restart the race
+		let are_clients_healthy = source_go_offline_future.is_terminated() && target_go_offline_future.is_terminated();
+		if are_clients_healthy && !is_waiting_for_headers && stall_countdown.elapsed() > race_stall_timeout {
+			log::warn!(
+				target: "bridge",
+				"{} -> {} race has made no progress while both clients are healthy. Restarting the race. \
+				State: {:?}. Strategy: {:?}",
+				P::source_name(),
+				P::target_name(),
+				race_state,
+				strategy,
+			);
+
+			return Err(RaceError::Stalled { has_progressed });
+		} else if stall_countdown.elapsed() > stall_timeout {
 			log::warn!(
 				target: "bridge",
 				"{} -> {} race has stalled. State: {:?}. Strategy: {:?}",
@@ -429,16 +509,25 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 				strategy,
 			);
 
-			return Err(FailedClient::Both);
-		} else if race_state.nonces_to_submit.is_none() && race_state.nonces_submitted.is_none() && strategy.is_empty()
+			return Err(RaceError::Client(FailedClient::Both));
+		} else if target_is_halted
+			|| is_waiting_for_headers
+			|| (race_state.nonces_to_submit.is_none() && race_state.nonces_submitted.is_none() && strategy.is_empty())
 		{
+			// we are not expecting any progress while target pallets are halted or while required source
+			// headers are not yet relayed to the target
 			stall_countdown = Instant::now();
 		}
 
 		if source_client_is_online {
 			source_client_is_online = false;
 
-			let nonces_to_deliver = select_nonces_to_deliver(&race_state, &mut strategy);
+			// there's no point in generating proofs if the target won't accept them
+			let nonces_to_deliver = if target_is_halted {
+				None
+			} else {
+				select_nonces_to_deliver(&race_state, &mut strategy)
+			};
 			let best_at_source = strategy.best_at_source();
 
 			if let Some((at_block, nonces_range, proof_parameters)) = nonces_to_deliver {
@@ -477,7 +566,12 @@ pub async fn run<P: MessageRace, SC: SourceClient<P>, TC: TargetClient<P>>(
 		if target_client_is_online {
 			target_client_is_online = false;
 
-			if let Some((at_block, nonces_range, proof)) = race_state.nonces_to_submit.as_ref() {
+			let nonces_to_submit = if target_is_halted {
+				None
+			} else {
+				race_state.nonces_to_submit.as_ref()
+			};
+			if let Some((at_block, nonces_range, proof)) = nonces_to_submit {
 				log::debug!(
 					target: "bridge",
 					"Going to submit proof of messages in range {:?} to {} node",
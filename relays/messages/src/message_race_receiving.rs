@@ -19,7 +19,7 @@ use crate::message_lane_loop::{
 	TargetClientState,
 };
 use crate::message_race_loop::{
-	MessageRace, NoncesRange, SourceClient, SourceClientNonces, TargetClient, TargetClientNonces,
+	MessageRace, NoncesRange, RaceError, SourceClient, SourceClientNonces, TargetClient, TargetClientNonces,
 };
 use crate::message_race_strategy::BasicStrategy;
 use crate::metrics::MessageLaneLoopMetrics;
@@ -27,7 +27,6 @@ use crate::metrics::MessageLaneLoopMetrics;
 use async_trait::async_trait;
 use bp_messages::MessageNonce;
 use futures::stream::FusedStream;
-use relay_utils::FailedClient;
 use std::{marker::PhantomData, ops::RangeInclusive, time::Duration};
 
 /// Message receiving confirmations delivery strategy.
@@ -47,8 +46,9 @@ pub async fn run<P: MessageLane>(
 	target_client: impl MessageLaneTargetClient<P>,
 	target_state_updates: impl FusedStream<Item = TargetClientState<P>>,
 	stall_timeout: Duration,
+	race_stall_timeout: Duration,
 	metrics_msg: Option<MessageLaneLoopMetrics>,
-) -> Result<(), FailedClient> {
+) -> Result<(), RaceError> {
 	crate::message_race_loop::run(
 		ReceivingConfirmationsRaceSource {
 			client: target_client,
@@ -63,6 +63,7 @@ pub async fn run<P: MessageLane>(
 		},
 		source_state_updates,
 		stall_timeout,
+		race_stall_timeout,
 		ReceivingConfirmationsBasicStrategy::<P>::new(),
 	)
 	.await
@@ -192,6 +193,9 @@ where
 		self.client
 			.submit_messages_receiving_proof(generated_at_block, proof)
 			.await?;
+		if let Some(metrics_msg) = self.metrics_msg.as_ref() {
+			metrics_msg.note_messages_confirmed(&nonces);
+		}
 		Ok(nonces)
 	}
 }
@@ -100,6 +100,12 @@ where
 		// by target client
 		// 3) selector is used for more complicated logic
 		let best_header_at_target = &race_state.best_finalized_source_header_id_at_best_target.as_ref()?;
+		// outdated nonces may have been skipped (see `skip_outdated_nonces`), so we start from the
+		// oldest queued nonce
+		let nonces_begin = std::cmp::max(
+			target_nonce + 1,
+			self.source_queue.front().map(|(_, range)| range.begin()).unwrap_or(0),
+		);
 		let mut nonces_end = None;
 		while let Some((queued_at, queued_range)) = self.source_queue.pop_front() {
 			// select (sub) range to deliver
@@ -139,7 +145,72 @@ where
 			}
 		}
 
-		nonces_end.map(|nonces_end| RangeInclusive::new(target_nonce + 1, nonces_end))
+		nonces_end.map(|nonces_end| RangeInclusive::new(nonces_begin, nonces_end))
+	}
+
+	/// Forget about queued nonces that are older than the latest `max_nonces` nonces that may be
+	/// proved to the target node. Nonces that are larger than `max_nonce` are never selected.
+	///
+	/// Returns maximal number of nonces that may be selected for delivery after skipping. This may
+	/// only be used at lanes with `LaneOrdering::Unordered`, because target node rejects messages
+	/// with nonce gaps at ordered lanes.
+	pub fn skip_outdated_nonces(
+		&mut self,
+		race_state: &RaceState<
+			HeaderId<SourceHeaderHash, SourceHeaderNumber>,
+			HeaderId<TargetHeaderHash, TargetHeaderNumber>,
+			Proof,
+		>,
+		max_nonce: MessageNonce,
+		max_nonces: MessageNonce,
+	) -> MessageNonce {
+		// never skip nonces while we're delivering some other nonces
+		if race_state.nonces_to_submit.is_some() || race_state.nonces_submitted.is_some() {
+			return 0;
+		}
+
+		let best_header_at_target = match race_state.best_finalized_source_header_id_at_best_target.as_ref() {
+			Some(best_header_at_target) => best_header_at_target,
+			None => return 0,
+		};
+		let best_provable_nonce = self
+			.source_queue
+			.iter()
+			.take_while(|(queued_at, _)| queued_at.0 <= best_header_at_target.0)
+			.last()
+			.map(|(_, range)| range.end());
+		let oldest_queued_nonce = self.source_queue.front().map(|(_, range)| range.begin());
+		let (best_provable_nonce, oldest_queued_nonce) = match (best_provable_nonce, oldest_queued_nonce) {
+			(Some(best_provable_nonce), Some(oldest_queued_nonce)) => (best_provable_nonce, oldest_queued_nonce),
+			_ => return 0,
+		};
+
+		let nonces_end = std::cmp::min(best_provable_nonce, max_nonce);
+		if nonces_end < oldest_queued_nonce || max_nonces == 0 {
+			return 0;
+		}
+
+		let nonces_begin = std::cmp::max(nonces_end.saturating_sub(max_nonces - 1), oldest_queued_nonce);
+		if nonces_begin > oldest_queued_nonce {
+			log::debug!(
+				target: "bridge",
+				"Skipping outdated nonces {:?}",
+				oldest_queued_nonce..=nonces_begin - 1,
+			);
+			self.remove_le_nonces_from_source_queue(nonces_begin - 1);
+		}
+
+		nonces_end - nonces_begin + 1
+	}
+
+	/// Remove all nonces that are less than or equal to given nonce from the source queue.
+	fn remove_le_nonces_from_source_queue(&mut self, nonce: MessageNonce) {
+		while let Some((queued_at, queued_range)) = self.source_queue.pop_front() {
+			if let Some(range_to_requeue) = queued_range.greater_than(nonce) {
+				self.source_queue.push_front((queued_at, range_to_requeue));
+				break;
+			}
+		}
 	}
 }
 
@@ -166,6 +237,14 @@ where
 		&self,
 		current_best: &HeaderId<SourceHeaderHash, SourceHeaderNumber>,
 	) -> Option<HeaderId<SourceHeaderHash, SourceHeaderNumber>> {
+		// if the oldest queued nonces may already be proved at the best header, known to the target
+		// node, we don't need newer header to make progress - the proof will be generated at this
+		// older header
+		let (oldest_queued_at, _) = self.source_queue.front()?;
+		if oldest_queued_at.0 <= current_best.0 {
+			return None;
+		}
+
 		self.source_queue
 			.back()
 			.and_then(|(h, _)| if h.0 > current_best.0 { Some(h.clone()) } else { None })
@@ -221,16 +300,7 @@ where
 			}
 		}
 
-		while let Some(true) = self.source_queue.front().map(|(_, range)| range.begin() <= nonce) {
-			let maybe_subrange = self
-				.source_queue
-				.pop_front()
-				.and_then(|(at_block, range)| range.greater_than(nonce).map(|subrange| (at_block, subrange)));
-			if let Some((at_block, subrange)) = maybe_subrange {
-				self.source_queue.push_front((at_block, subrange));
-				break;
-			}
-		}
+		self.remove_le_nonces_from_source_queue(nonce);
 
 		let need_to_select_new_nonces = race_state
 			.nonces_to_submit
@@ -323,6 +393,28 @@ mod tests {
 		assert!(!strategy.is_empty());
 	}
 
+	#[test]
+	fn source_header_is_not_required_when_queued_nonces_are_provable_at_best_target_header() {
+		let mut state = RaceState::<_, _, TestMessagesProof>::default();
+		let mut strategy = BasicStrategy::<TestMessageLane>::new();
+		strategy.best_target_nonces_updated(target_nonces(0), &mut state);
+		strategy.source_nonces_updated(header_id(1), source_nonces(1..=5));
+		strategy.source_nonces_updated(header_id(3), source_nonces(6..=10));
+
+		// nonces 1..=5 are provable at header#1 => we don't need newer header to deliver them
+		assert_eq!(strategy.required_source_header_at_target(&header_id(1)), None);
+		state.best_finalized_source_header_id_at_best_target = Some(header_id(1));
+		assert_eq!(strategy.select_nonces_to_deliver(&state), Some((1..=5, ())));
+
+		// once they're delivered, we need header#3 to deliver the rest
+		strategy.best_target_nonces_updated(target_nonces(5), &mut state);
+		assert_eq!(
+			strategy.required_source_header_at_target(&header_id(1)),
+			Some(header_id(3))
+		);
+		assert_eq!(strategy.required_source_header_at_target(&header_id(3)), None);
+	}
+
 	#[test]
 	fn best_at_source_is_never_lower_than_target_nonce() {
 		let mut strategy = BasicStrategy::<TestMessageLane>::new();
@@ -454,6 +546,32 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn skip_outdated_nonces_works() {
+		let mut state = RaceState::<_, _, TestMessagesProof>::default();
+		let mut strategy = BasicStrategy::<TestMessageLane>::new();
+		strategy.best_target_nonces_updated(target_nonces(0), &mut state);
+		strategy.source_nonces_updated(header_id(1), source_nonces(1..=5));
+		strategy.source_nonces_updated(header_id(2), source_nonces(6..=10));
+		strategy.source_nonces_updated(header_id(3), source_nonces(11..=12));
+		state.best_finalized_source_header_id_at_best_target = Some(header_id(2));
+
+		// nothing is skipped while we're delivering other nonces
+		state.nonces_submitted = Some(1..=1);
+		assert_eq!(strategy.skip_outdated_nonces(&state, 100, 3), 0);
+		assert_eq!(strategy.source_queue[0], (header_id(1), 1..=5));
+		state.nonces_submitted = None;
+
+		// nonces that can't be delivered to the target node are never selected
+		assert_eq!(strategy.skip_outdated_nonces(&state, 9, 3), 3);
+		assert_eq!(strategy.source_queue[0], (header_id(2), 7..=10));
+
+		// only the latest provable nonces are selected
+		assert_eq!(strategy.skip_outdated_nonces(&state, 100, 3), 3);
+		assert_eq!(strategy.source_queue[0], (header_id(2), 8..=10));
+		assert_eq!(strategy.select_nonces_to_deliver(&state), Some((8..=10, ())));
+	}
+
 	fn run_panic_test_for_incorrect_selector(
 		invalid_selector: impl Fn(SourceNoncesRange) -> Option<SourceNoncesRange>,
 	) {
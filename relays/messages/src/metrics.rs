@@ -20,7 +20,13 @@ use crate::message_lane::MessageLane;
 use crate::message_lane_loop::{SourceClientState, TargetClientState};
 
 use bp_messages::MessageNonce;
-use relay_utils::metrics::{metric_name, register, GaugeVec, Opts, PrometheusError, Registry, U64};
+use relay_utils::{
+	block_number_to_u64,
+	metrics::{
+		metric_name, register, GaugeVec, Opts, PersistentCounterVec, PersistentCounters, PrometheusError, Registry, U64,
+	},
+};
+use std::ops::RangeInclusive;
 
 /// Message lane relay metrics.
 ///
@@ -32,12 +38,30 @@ pub struct MessageLaneLoopMetrics {
 	/// Lane state nonces: "source_latest_generated", "source_latest_confirmed",
 	/// "target_latest_received", "target_latest_confirmed".
 	lane_state_nonces: GaugeVec<U64>,
+	/// Bridge pallets halt flags: "source", "target". Set to `1` if pallets at given chain are halted.
+	halted_pallets: GaugeVec<U64>,
+	/// Cumulative counters that survive relay restarts: "delivered_messages", "delivery_transactions",
+	/// "confirmed_messages", "confirmation_transactions". Only transactions that have been successfully
+	/// submitted by this relay are counted.
+	cumulative_counters: PersistentCounterVec,
 }
 
+/// Types of cumulative counters of the message lane relay loop.
+const CUMULATIVE_COUNTERS: [&str; 4] = [
+	"delivered_messages",
+	"delivery_transactions",
+	"confirmed_messages",
+	"confirmation_transactions",
+];
+
 impl MessageLaneLoopMetrics {
 	/// Create and register messages loop metrics.
-	pub fn new(registry: &Registry, prefix: Option<&str>) -> Result<Self, PrometheusError> {
-		Ok(MessageLaneLoopMetrics {
+	pub fn new(
+		registry: &Registry,
+		prefix: Option<&str>,
+		persistent_counters: PersistentCounters,
+	) -> Result<Self, PrometheusError> {
+		let metrics = MessageLaneLoopMetrics {
 			best_block_numbers: register(
 				GaugeVec::new(
 					Opts::new(
@@ -55,7 +79,25 @@ impl MessageLaneLoopMetrics {
 				)?,
 				registry,
 			)?,
-		})
+			halted_pallets: register(
+				GaugeVec::new(
+					Opts::new(metric_name(prefix, "halted_pallets"), "Bridge pallets halt flags"),
+					&["type"],
+				)?,
+				registry,
+			)?,
+			cumulative_counters: PersistentCounterVec::new(
+				registry,
+				persistent_counters,
+				metric_name(prefix, "cumulative_counters"),
+				"Cumulative message lane counters".into(),
+				&["type"],
+			)?,
+		};
+		for counter in CUMULATIVE_COUNTERS.iter() {
+			metrics.cumulative_counters.restore(&[counter]);
+		}
+		Ok(metrics)
 	}
 }
 
@@ -64,20 +106,30 @@ impl MessageLaneLoopMetrics {
 	pub fn update_source_state<P: MessageLane>(&self, source_client_state: SourceClientState<P>) {
 		self.best_block_numbers
 			.with_label_values(&["source"])
-			.set(source_client_state.best_self.0.into());
+			.set(block_number_to_u64(source_client_state.best_self.0));
 		self.best_block_numbers
 			.with_label_values(&["target_at_source"])
-			.set(source_client_state.best_finalized_peer_at_best_self.0.into());
+			.set(block_number_to_u64(
+				source_client_state.best_finalized_peer_at_best_self.0,
+			));
+		self.halted_pallets
+			.with_label_values(&["source"])
+			.set(source_client_state.is_halted as u64);
 	}
 
 	/// Update target client state metrics.
 	pub fn update_target_state<P: MessageLane>(&self, target_client_state: TargetClientState<P>) {
 		self.best_block_numbers
 			.with_label_values(&["target"])
-			.set(target_client_state.best_self.0.into());
+			.set(block_number_to_u64(target_client_state.best_self.0));
 		self.best_block_numbers
 			.with_label_values(&["source_at_target"])
-			.set(target_client_state.best_finalized_peer_at_best_self.0.into());
+			.set(block_number_to_u64(
+				target_client_state.best_finalized_peer_at_best_self.0,
+			));
+		self.halted_pallets
+			.with_label_values(&["target"])
+			.set(target_client_state.is_halted as u64);
 	}
 
 	/// Update latest generated nonce at source.
@@ -107,4 +159,26 @@ impl MessageLaneLoopMetrics {
 			.with_label_values(&["target_latest_confirmed"])
 			.set(target_latest_confirmed_nonce);
 	}
+
+	/// Note that the messages delivery transaction has been submitted.
+	pub fn note_messages_delivered(&self, nonces: &RangeInclusive<MessageNonce>) {
+		self.note_transaction("delivery_transactions", "delivered_messages", nonces);
+	}
+
+	/// Note that the messages delivery confirmation transaction has been submitted.
+	pub fn note_messages_confirmed(&self, nonces: &RangeInclusive<MessageNonce>) {
+		self.note_transaction("confirmation_transactions", "confirmed_messages", nonces);
+	}
+
+	/// Update cumulative counters after transaction has been submitted.
+	fn note_transaction(
+		&self,
+		transactions_counter: &str,
+		messages_counter: &str,
+		nonces: &RangeInclusive<MessageNonce>,
+	) {
+		let messages = nonces.end().saturating_sub(*nonces.start()).saturating_add(1);
+		self.cumulative_counters.inc_by(&[transactions_counter], 1);
+		self.cumulative_counters.inc_by(&[messages_counter], messages as u128);
+	}
 }
@@ -0,0 +1,169 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reasons why the messages pallet may reject messages proof or messages delivery proof.
+//!
+//! The messages pallet rejects invalid `receive_messages_proof` and `receive_messages_delivery_proof`
+//! calls with one of its (documented) errors. The error name may be found in the `ExtrinsicFailed`
+//! event, so this module allows relayer to explain what has gone wrong with its transaction.
+
+use std::fmt;
+
+/// Reason why the messages pallet has rejected messages proof or messages delivery proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+	/// Transaction declares too many messages (the messages proof is oversized).
+	TooManyMessagesInTheProof,
+	/// Declared dispatch weight is less than the actual dispatch weight of proved messages.
+	InvalidMessagesDispatchWeight,
+	/// Declared unrewarded relayers state doesn't match the proved state.
+	InvalidUnrewardedRelayersState,
+	/// The bridged header, used to build the proof, is unknown to the target chain.
+	UnknownBridgedHeader,
+	/// The storage proof doesn't match the storage root of the bridged header.
+	StorageRootMismatch,
+	/// The messages proof contains neither messages, nor outbound lane state.
+	EmptyMessagesProof,
+	/// The number of messages in the proof doesn't match the declared value.
+	MessagesCountMismatch,
+	/// The message is missing from the messages proof.
+	MissingMessageInTheProof,
+	/// The message from the messages proof can't be decoded.
+	FailedToDecodeMessage,
+	/// The lane state is missing from the proof.
+	MissingLaneStateInTheProof,
+	/// The lane state from the proof can't be decoded.
+	FailedToDecodeLaneState,
+	/// There's a gap between the latest delivered nonce and nonces of proved messages.
+	MessagesNonceGap,
+	/// There are too many unrewarded relayer entries at the inbound lane.
+	TooManyUnrewardedRelayerEntries,
+	/// There are too many unconfirmed messages at the inbound lane.
+	TooManyUnconfirmedMessages,
+	/// The storage proof has duplicate or unused nodes.
+	ExtraNodesInTheProof,
+	/// The proof is invalid and there's no more specific reason for that.
+	InvalidProof,
+}
+
+impl RejectionReason {
+	/// Parse rejection reason from the name of the messages pallet error.
+	pub fn from_pallet_error(error_name: &str) -> Option<Self> {
+		Some(match error_name {
+			"TooManyMessagesInTheProof" => RejectionReason::TooManyMessagesInTheProof,
+			"InvalidMessagesDispatchWeight" => RejectionReason::InvalidMessagesDispatchWeight,
+			"InvalidUnrewardedRelayersState" => RejectionReason::InvalidUnrewardedRelayersState,
+			"UnknownBridgedHeader" => RejectionReason::UnknownBridgedHeader,
+			"StorageRootMismatch" => RejectionReason::StorageRootMismatch,
+			"EmptyMessagesProof" => RejectionReason::EmptyMessagesProof,
+			"MessagesCountMismatch" => RejectionReason::MessagesCountMismatch,
+			"MissingMessageInTheProof" => RejectionReason::MissingMessageInTheProof,
+			"FailedToDecodeMessage" => RejectionReason::FailedToDecodeMessage,
+			"MissingLaneStateInTheProof" => RejectionReason::MissingLaneStateInTheProof,
+			"FailedToDecodeLaneState" => RejectionReason::FailedToDecodeLaneState,
+			"MessagesNonceGap" => RejectionReason::MessagesNonceGap,
+			"TooManyUnrewardedRelayerEntries" => RejectionReason::TooManyUnrewardedRelayerEntries,
+			"TooManyUnconfirmedMessages" => RejectionReason::TooManyUnconfirmedMessages,
+			"ExtraNodesInTheProof" => RejectionReason::ExtraNodesInTheProof,
+			"InvalidMessagesProof" | "InvalidMessagesDeliveryProof" => RejectionReason::InvalidProof,
+			_ => return None,
+		})
+	}
+
+	/// Find rejection reason in the description of the transaction submission error.
+	///
+	/// The description may contain name of the messages pallet error, e.g. if the node has rejected
+	/// the transaction because it has failed to dispatch at the pool validation or dry run.
+	pub fn from_error_description(description: &str) -> Option<Self> {
+		description
+			.split(|c: char| !c.is_ascii_alphanumeric())
+			.find_map(Self::from_pallet_error)
+	}
+
+	/// Returns true if the same transaction may succeed later, without any changes at relayer side.
+	///
+	/// I.e. the bridged header may be imported later, or the inbound lane may be cleared by delivery
+	/// confirmations.
+	pub fn is_temporary(&self) -> bool {
+		matches!(
+			*self,
+			RejectionReason::UnknownBridgedHeader
+				| RejectionReason::TooManyUnrewardedRelayerEntries
+				| RejectionReason::TooManyUnconfirmedMessages
+		)
+	}
+}
+
+impl fmt::Display for RejectionReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match *self {
+			RejectionReason::TooManyMessagesInTheProof => "transaction declares too many messages",
+			RejectionReason::InvalidMessagesDispatchWeight => {
+				"declared dispatch weight is less than actual dispatch weight of messages"
+			}
+			RejectionReason::InvalidUnrewardedRelayersState => {
+				"declared unrewarded relayers state doesn't match proved state"
+			}
+			RejectionReason::UnknownBridgedHeader => "proof is built at header that is unknown to the target chain",
+			RejectionReason::StorageRootMismatch => "storage proof doesn't match storage root of the header",
+			RejectionReason::EmptyMessagesProof => "messages proof contains neither messages, nor lane state",
+			RejectionReason::MessagesCountMismatch => "declared messages count doesn't match the proof",
+			RejectionReason::MissingMessageInTheProof => "message is missing from the proof",
+			RejectionReason::FailedToDecodeMessage => "failed to decode message from the proof",
+			RejectionReason::MissingLaneStateInTheProof => "lane state is missing from the proof",
+			RejectionReason::FailedToDecodeLaneState => "failed to decode lane state from the proof",
+			RejectionReason::MessagesNonceGap => "there's a gap between delivered and proved messages nonces",
+			RejectionReason::TooManyUnrewardedRelayerEntries => "inbound lane has too many unrewarded relayer entries",
+			RejectionReason::TooManyUnconfirmedMessages => "inbound lane has too many unconfirmed messages",
+			RejectionReason::ExtraNodesInTheProof => "storage proof has duplicate or unused nodes",
+			RejectionReason::InvalidProof => "proof is invalid",
+		};
+
+		write!(f, "{}", s)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn rejection_reason_is_parsed_from_pallet_error_name() {
+		assert_eq!(
+			RejectionReason::from_pallet_error("MessagesNonceGap"),
+			Some(RejectionReason::MessagesNonceGap),
+		);
+		assert_eq!(
+			RejectionReason::from_pallet_error("InvalidMessagesDeliveryProof"),
+			Some(RejectionReason::InvalidProof),
+		);
+		assert_eq!(RejectionReason::from_pallet_error("Halted"), None);
+	}
+
+	#[test]
+	fn rejection_reason_is_found_in_error_description() {
+		assert_eq!(
+			RejectionReason::from_error_description(
+				"Transaction failed: Module { index: 11, error: 14, message: Some(\"UnknownBridgedHeader\") }",
+			),
+			Some(RejectionReason::UnknownBridgedHeader),
+		);
+		assert_eq!(
+			RejectionReason::from_error_description("Transaction is outdated: InvalidProofOfSomething"),
+			None,
+		);
+	}
+}
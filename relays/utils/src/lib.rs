@@ -20,7 +20,7 @@ pub use relay_loop::{relay_loop, relay_metrics};
 
 use backoff::{backoff::Backoff, ExponentialBackoff};
 use futures::future::FutureExt;
-use std::time::Duration;
+use std::{convert::TryInto, time::Duration};
 
 /// Max delay after connection-unrelated error happened before we'll try the
 /// same request again.
@@ -34,10 +34,13 @@ pub mod metrics;
 pub mod relay_loop;
 
 /// Block number traits shared by all chains that relay is able to serve.
+///
+/// Block numbers are not required to fit into `u64` - use `block_number_to_u64` when `u64`
+/// value is required (e.g. by metrics).
 pub trait BlockNumberBase:
 	'static
 	+ From<u32>
-	+ Into<u64>
+	+ TryInto<u64>
 	+ Ord
 	+ Clone
 	+ Copy
@@ -59,7 +62,7 @@ pub trait BlockNumberBase:
 impl<T> BlockNumberBase for T where
 	T: 'static
 		+ From<u32>
-		+ Into<u64>
+		+ TryInto<u64>
 		+ Ord
 		+ Clone
 		+ Copy
@@ -100,6 +103,14 @@ macro_rules! bail_on_arg_error {
 	};
 }
 
+/// Convert block number into `u64` value.
+///
+/// Prometheus gauges are only able to hold `u64` values, so numbers that do not fit are saturated
+/// to `u64::MAX`.
+pub fn block_number_to_u64<Number: TryInto<u64>>(number: Number) -> u64 {
+	number.try_into().unwrap_or(u64::MAX)
+}
+
 /// Ethereum header Id.
 #[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq)]
 pub struct HeaderId<Hash, Number>(pub Number, pub Hash);
@@ -275,3 +286,24 @@ where
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_block_number_base<Number: BlockNumberBase>() {}
+
+	#[test]
+	fn primitive_block_numbers_are_supported() {
+		assert_block_number_base::<u32>();
+		assert_block_number_base::<u64>();
+		assert_block_number_base::<u128>();
+	}
+
+	#[test]
+	fn block_number_to_u64_saturates() {
+		assert_eq!(block_number_to_u64(42u32), 42);
+		assert_eq!(block_number_to_u64(u64::MAX), u64::MAX);
+		assert_eq!(block_number_to_u64(u64::MAX as u128 + 1), u64::MAX);
+	}
+}
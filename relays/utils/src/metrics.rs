@@ -16,6 +16,11 @@
 
 pub use float_json_value::FloatJsonValueMetric;
 pub use global::GlobalMetrics;
+pub use persistent_counters::{counter_key, PersistentCounterVec, PersistentCounters};
+pub(crate) use push_gateway::spawn_push_task;
+pub use push_gateway::{PushGatewayAuth, PushGatewayParams};
+pub(crate) use server::serve;
+pub use server::{MetricsAuth, MetricsTls};
 pub use substrate_prometheus_endpoint::{
 	prometheus::core::{Atomic, Collector},
 	register, Counter, CounterVec, Gauge, GaugeVec, Opts, PrometheusError, Registry, F64, U64,
@@ -26,6 +31,9 @@ use std::{fmt::Debug, time::Duration};
 
 mod float_json_value;
 mod global;
+mod persistent_counters;
+mod push_gateway;
+mod server;
 
 /// Unparsed address that needs to be used to expose Prometheus metrics.
 #[derive(Debug, Clone)]
@@ -34,6 +42,10 @@ pub struct MetricsAddress {
 	pub host: String,
 	/// Serve HTTP requests at given port.
 	pub port: u16,
+	/// Serve HTTPS requests instead of HTTP, using given certificate.
+	pub tls: Option<MetricsTls>,
+	/// Only serve requests with given credentials.
+	pub auth: Option<MetricsAuth>,
 }
 
 /// Prometheus endpoint MetricsParams.
@@ -45,6 +57,10 @@ pub struct MetricsParams {
 	pub registry: Option<Registry>,
 	/// Prefix that must be used in metric names.
 	pub metrics_prefix: Option<String>,
+	/// Prometheus push gateway parameters. If `Some(_)`, metrics are periodically pushed to the gateway.
+	pub push_gateway: Option<PushGatewayParams>,
+	/// Cumulative counters that survive relay restarts.
+	pub persistent_counters: PersistentCounters,
 }
 
 /// Metrics API.
@@ -81,6 +97,8 @@ impl Default for MetricsAddress {
 		MetricsAddress {
 			host: "127.0.0.1".into(),
 			port: 9616,
+			tls: None,
+			auth: None,
 		}
 	}
 }
@@ -92,12 +110,27 @@ impl MetricsParams {
 			address: None,
 			registry: None,
 			metrics_prefix: None,
+			push_gateway: None,
+			persistent_counters: PersistentCounters::in_memory(),
 		}
 	}
 
 	/// Do not expose metrics.
 	pub fn disable(mut self) -> Self {
 		self.address = None;
+		self.push_gateway = None;
+		self
+	}
+
+	/// Push metrics to the Prometheus push gateway.
+	pub fn push_gateway(mut self, push_gateway: PushGatewayParams) -> Self {
+		self.push_gateway = Some(push_gateway);
+		self
+	}
+
+	/// Store cumulative counters in given storage, so that they survive relay restarts.
+	pub fn persistent_counters(mut self, persistent_counters: PersistentCounters) -> Self {
+		self.persistent_counters = persistent_counters;
 		self
 	}
 
@@ -114,6 +147,8 @@ impl From<Option<MetricsAddress>> for MetricsParams {
 			address,
 			registry: None,
 			metrics_prefix: None,
+			push_gateway: None,
+			persistent_counters: PersistentCounters::in_memory(),
 		}
 	}
 }
@@ -0,0 +1,219 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Counters that survive relay restarts.
+//!
+//! Prometheus counters are expected to be monotonically increasing. But every relay restart resets
+//! in-memory counters, so cumulative values (like number of delivered messages) are lost. Counters
+//! of this module are stored in the relay-local state file after every update and are restored
+//! when relay starts again.
+
+use crate::metrics::{register, CounterVec, Opts, PrometheusError, Registry, F64};
+
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex},
+};
+
+/// Cumulative counters values, optionally backed by the relay-local state file.
+///
+/// Cloning only clones references.
+#[derive(Clone, Debug, Default)]
+pub struct PersistentCounters {
+	/// Path to the state file. If `None`, counters are only kept in memory.
+	path: Option<PathBuf>,
+	/// Counters values, mapped by their keys.
+	values: Arc<Mutex<BTreeMap<String, u128>>>,
+}
+
+impl PersistentCounters {
+	/// Create counters that are only kept in memory.
+	pub fn in_memory() -> Self {
+		PersistentCounters::default()
+	}
+
+	/// Open counters that are stored in given file.
+	///
+	/// If file doesn't exist yet, it'll be created on the first counter update.
+	pub fn open(path: PathBuf) -> Result<Self, String> {
+		let values = match std::fs::read(&path) {
+			Ok(contents) => serde_json::from_slice(&contents)
+				.map_err(|e| format!("Failed to parse counters file {:?}: {}", path, e))?,
+			Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+			Err(e) => return Err(format!("Failed to read counters file {:?}: {}", path, e)),
+		};
+
+		Ok(PersistentCounters {
+			path: Some(path),
+			values: Arc::new(Mutex::new(values)),
+		})
+	}
+
+	/// Returns current value of the counter with given key.
+	pub fn get(&self, key: &str) -> u128 {
+		self.values
+			.lock()
+			.expect("counters lock is never poisoned; qed")
+			.get(key)
+			.cloned()
+			.unwrap_or(0)
+	}
+
+	/// Increase value of the counter with given key and save all counters to the state file.
+	///
+	/// Returns updated counter value.
+	pub fn add(&self, key: &str, value: u128) -> u128 {
+		let mut values = self.values.lock().expect("counters lock is never poisoned; qed");
+		let counter = values.entry(key.into()).or_insert(0);
+		*counter = counter.saturating_add(value);
+		let updated_value = *counter;
+
+		if let Some(ref path) = self.path {
+			if let Err(error) = save(path, &values) {
+				log::error!(
+					target: "bridge-metrics",
+					"Failed to save counters to {:?}: {}",
+					path,
+					error,
+				);
+			}
+		}
+
+		updated_value
+	}
+}
+
+/// Prometheus counters vector, which values are stored in the `PersistentCounters`.
+///
+/// Cloning only clones references.
+#[derive(Clone)]
+pub struct PersistentCounterVec {
+	name: String,
+	counters: PersistentCounters,
+	metric: CounterVec<F64>,
+}
+
+impl PersistentCounterVec {
+	/// Create and register counters vector.
+	pub fn new(
+		registry: &Registry,
+		counters: PersistentCounters,
+		name: String,
+		help: String,
+		labels: &[&str],
+	) -> Result<Self, PrometheusError> {
+		Ok(PersistentCounterVec {
+			metric: register(CounterVec::new(Opts::new(name.clone(), help), labels)?, registry)?,
+			name,
+			counters,
+		})
+	}
+
+	/// Expose value of the counter with given labels, stored by previous relay runs.
+	pub fn restore(&self, label_values: &[&str]) {
+		let value = self.counters.get(&counter_key(&self.name, label_values));
+		self.expose(label_values, value);
+	}
+
+	/// Increase value of the counter with given labels.
+	pub fn inc_by(&self, label_values: &[&str], value: u128) {
+		let value = self.counters.add(&counter_key(&self.name, label_values), value);
+		self.expose(label_values, value);
+	}
+
+	/// Make sure that the Prometheus counter has given value.
+	fn expose(&self, label_values: &[&str], value: u128) {
+		let counter = self.metric.with_label_values(label_values);
+		let value = value as f64;
+		let current_value = counter.get();
+		if value > current_value {
+			counter.inc_by(value - current_value);
+		}
+	}
+}
+
+/// Returns key of the counter with given metric name and label values.
+pub fn counter_key(name: &str, label_values: &[&str]) -> String {
+	format!("{}{{{}}}", name, label_values.join(","))
+}
+
+/// Atomically (write to temporary file + rename) save counters to the file.
+fn save(path: &Path, values: &BTreeMap<String, u128>) -> Result<(), String> {
+	let contents = serde_json::to_vec_pretty(values).map_err(|e| e.to_string())?;
+	let mut temp_path = path.as_os_str().to_owned();
+	temp_path.push(".tmp");
+	std::fs::write(&temp_path, contents).map_err(|e| e.to_string())?;
+	std::fs::rename(&temp_path, path).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temp_counters_path(name: &str) -> PathBuf {
+		let mut path = std::env::temp_dir();
+		path.push(format!(
+			"relay-persistent-counters-{}-{}.json",
+			name,
+			std::process::id()
+		));
+		let _ = std::fs::remove_file(&path);
+		path
+	}
+
+	#[test]
+	fn counters_survive_reopen() {
+		let path = temp_counters_path("reopen");
+
+		let counters = PersistentCounters::open(path.clone()).unwrap();
+		assert_eq!(counters.add("delivered{0000}", 10), 10);
+		assert_eq!(counters.add("delivered{0000}", 5), 15);
+		assert_eq!(counters.add("confirmed{0000}", 7), 7);
+
+		let counters = PersistentCounters::open(path.clone()).unwrap();
+		assert_eq!(counters.get("delivered{0000}"), 15);
+		assert_eq!(counters.get("confirmed{0000}"), 7);
+		assert_eq!(counters.get("unknown{0000}"), 0);
+
+		std::fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn corrupted_counters_file_is_rejected() {
+		let path = temp_counters_path("corrupted");
+		std::fs::write(&path, b"not a json").unwrap();
+
+		assert!(PersistentCounters::open(path.clone()).is_err());
+
+		std::fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn restored_counter_is_exposed() {
+		let counters = PersistentCounters::in_memory();
+		counters.add(&counter_key("delivered", &["0000"]), 42);
+
+		let registry = Registry::new();
+		let metric =
+			PersistentCounterVec::new(&registry, counters, "delivered".into(), "help".into(), &["lane"]).unwrap();
+		metric.restore(&["0000"]);
+		assert_eq!(metric.metric.with_label_values(&["0000"]).get(), 42.0);
+
+		metric.inc_by(&["0000"], 8);
+		assert_eq!(metric.metric.with_label_values(&["0000"]).get(), 50.0);
+	}
+}
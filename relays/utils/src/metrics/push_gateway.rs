@@ -0,0 +1,145 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Publishing metrics to the Prometheus push gateway.
+//!
+//! This is useful when relay is running behind NAT and Prometheus server is unable to
+//! scrape metrics from the relay endpoint.
+
+use crate::metrics::Registry;
+
+use std::time::Duration;
+use substrate_prometheus_endpoint::prometheus::{Encoder, TextEncoder};
+
+/// Credentials that are used to authenticate at the push gateway (HTTP basic authentication).
+#[derive(Debug, Clone)]
+pub struct PushGatewayAuth {
+	/// User name.
+	pub username: String,
+	/// User password.
+	pub password: String,
+}
+
+/// Prometheus push gateway parameters.
+#[derive(Debug, Clone)]
+pub struct PushGatewayParams {
+	/// Push gateway URL (e.g. `http://127.0.0.1:9091`).
+	pub url: String,
+	/// Interval at which metrics are pushed to the gateway.
+	pub interval: Duration,
+	/// Value of the `job` label of all pushed metrics.
+	pub job: String,
+	/// Value of the `instance` label of all pushed metrics.
+	pub instance: Option<String>,
+	/// Push gateway credentials.
+	pub auth: Option<PushGatewayAuth>,
+}
+
+/// Spawn task that is periodically pushing all metrics from the registry to the push gateway.
+pub fn spawn_push_task(params: PushGatewayParams, registry: Registry) {
+	async_std::task::spawn(async move {
+		loop {
+			if let Err(error) = push_metrics(&params, &registry).await {
+				log::warn!(
+					target: "bridge-metrics",
+					"Failed to push metrics to the Prometheus push gateway at {}: {}",
+					params.url,
+					error,
+				);
+			}
+
+			async_std::task::sleep(params.interval).await;
+		}
+	});
+}
+
+/// Push all metrics from the registry to the push gateway.
+async fn push_metrics(params: &PushGatewayParams, registry: &Registry) -> Result<(), String> {
+	use isahc::{
+		auth::{Authentication, Credentials},
+		config::Configurable,
+		AsyncReadResponseExt, HttpClient, Request,
+	};
+
+	fn map_isahc_err(err: impl std::fmt::Display) -> String {
+		format!("Failed to push metrics: {}", err)
+	}
+
+	let encoder = TextEncoder::new();
+	let mut encoded_metrics = Vec::new();
+	encoder
+		.encode(&registry.gather(), &mut encoded_metrics)
+		.map_err(|err| format!("Failed to encode metrics: {}", err))?;
+
+	let mut request = Request::put(push_url(params)).header("Content-Type", encoder.format_type());
+	if let Some(ref auth) = params.auth {
+		request = request
+			.authentication(Authentication::basic())
+			.credentials(Credentials::new(auth.username.clone(), auth.password.clone()));
+	}
+	let mut response = HttpClient::new()
+		.map_err(map_isahc_err)?
+		.send_async(request.body(encoded_metrics).map_err(map_isahc_err)?)
+		.await
+		.map_err(map_isahc_err)?;
+
+	if !response.status().is_success() {
+		return Err(format!(
+			"Push gateway has responded with {}: {}",
+			response.status(),
+			response.text().await.unwrap_or_default(),
+		));
+	}
+
+	Ok(())
+}
+
+/// Returns URL that is used to push metrics with configured grouping labels.
+fn push_url(params: &PushGatewayParams) -> String {
+	let mut url = format!("{}/metrics/job/{}", params.url.trim_end_matches('/'), params.job);
+	if let Some(ref instance) = params.instance {
+		url.push_str("/instance/");
+		url.push_str(instance);
+	}
+	url
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn params(url: &str, instance: Option<&str>) -> PushGatewayParams {
+		PushGatewayParams {
+			url: url.into(),
+			interval: Duration::from_secs(15),
+			job: "substrate-relay".into(),
+			instance: instance.map(Into::into),
+			auth: None,
+		}
+	}
+
+	#[test]
+	fn push_url_works() {
+		assert_eq!(
+			push_url(&params("http://127.0.0.1:9091", None)),
+			"http://127.0.0.1:9091/metrics/job/substrate-relay",
+		);
+		assert_eq!(
+			push_url(&params("http://127.0.0.1:9091/", Some("relay-1"))),
+			"http://127.0.0.1:9091/metrics/job/substrate-relay/instance/relay-1",
+		);
+	}
+}
@@ -0,0 +1,279 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics HTTP server that supports TLS and authentication.
+//!
+//! The plain (unauthenticated) endpoint is served by the `substrate-prometheus-endpoint`. This
+//! server is only used when TLS or authentication is required.
+
+use crate::metrics::Registry;
+
+use async_std::net::TcpListener;
+use async_tls::TlsAcceptor;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, StreamExt};
+use rustls::{
+	internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys},
+	NoClientAuth, ServerConfig,
+};
+use std::{io::BufReader, net::SocketAddr, path::PathBuf, sync::Arc};
+use substrate_prometheus_endpoint::prometheus::{Encoder, TextEncoder};
+
+/// Maximal size of HTTP request head that we are ready to accept.
+const MAX_REQUEST_HEAD_SIZE: usize = 8 * 1024;
+
+/// TLS parameters of the metrics endpoint.
+#[derive(Debug, Clone)]
+pub struct MetricsTls {
+	/// Path to the PEM-encoded certificate chain.
+	pub certificate_path: PathBuf,
+	/// Path to the PEM-encoded (PKCS8 or RSA) private key.
+	pub private_key_path: PathBuf,
+}
+
+/// Authentication that is required to access the metrics endpoint.
+#[derive(Debug, Clone)]
+pub enum MetricsAuth {
+	/// HTTP basic authentication.
+	Basic {
+		/// User name.
+		username: String,
+		/// User password.
+		password: String,
+	},
+	/// Bearer token authentication.
+	Token(String),
+}
+
+/// Outcome of processing metrics endpoint request.
+#[derive(Debug, PartialEq)]
+enum RequestOutcome {
+	/// Request is valid and metrics need to be sent back.
+	Metrics,
+	/// Request is missing valid credentials.
+	Unauthorized,
+	/// Requested path is unknown.
+	NotFound,
+	/// Request is malformed.
+	BadRequest,
+}
+
+/// Serve metrics from given registry at given address until error happens.
+pub(crate) async fn serve(
+	address: SocketAddr,
+	registry: Registry,
+	tls: Option<MetricsTls>,
+	auth: Option<MetricsAuth>,
+) -> Result<(), String> {
+	let acceptor = tls.map(|tls| tls_acceptor(&tls)).transpose()?;
+	let expected_authorization = auth.map(|auth| expected_authorization(&auth));
+	let listener = TcpListener::bind(address)
+		.await
+		.map_err(|err| format!("Failed to bind metrics endpoint to {}: {}", address, err))?;
+
+	let mut incoming = listener.incoming();
+	while let Some(stream) = incoming.next().await {
+		let stream = match stream {
+			Ok(stream) => stream,
+			Err(error) => {
+				log::trace!(target: "bridge-metrics", "Failed to accept metrics connection: {}", error);
+				continue;
+			}
+		};
+
+		let registry = registry.clone();
+		let acceptor = acceptor.clone();
+		let expected_authorization = expected_authorization.clone();
+		async_std::task::spawn(async move {
+			let result = match acceptor {
+				Some(acceptor) => match acceptor.accept(stream).await {
+					Ok(stream) => handle_connection(stream, &registry, expected_authorization.as_deref()).await,
+					Err(error) => Err(format!("TLS handshake has failed: {}", error)),
+				},
+				None => handle_connection(stream, &registry, expected_authorization.as_deref()).await,
+			};
+
+			if let Err(error) = result {
+				log::trace!(target: "bridge-metrics", "Failed to serve metrics request: {}", error);
+			}
+		});
+	}
+
+	Ok(())
+}
+
+/// Create TLS acceptor using given certificate and private key.
+fn tls_acceptor(tls: &MetricsTls) -> Result<TlsAcceptor, String> {
+	let read_file = |path: &PathBuf| {
+		std::fs::File::open(path)
+			.map(BufReader::new)
+			.map_err(|err| format!("Failed to open {:?}: {}", path, err))
+	};
+
+	let certificates = certs(&mut read_file(&tls.certificate_path)?)
+		.map_err(|_| format!("Failed to read certificates from {:?}", tls.certificate_path))?;
+	let mut private_keys = pkcs8_private_keys(&mut read_file(&tls.private_key_path)?)
+		.map_err(|_| format!("Failed to read private key from {:?}", tls.private_key_path))?;
+	if private_keys.is_empty() {
+		private_keys = rsa_private_keys(&mut read_file(&tls.private_key_path)?)
+			.map_err(|_| format!("Failed to read private key from {:?}", tls.private_key_path))?;
+	}
+	let private_key = private_keys
+		.into_iter()
+		.next()
+		.ok_or_else(|| format!("No private keys found in {:?}", tls.private_key_path))?;
+
+	let mut config = ServerConfig::new(NoClientAuth::new());
+	config
+		.set_single_cert(certificates, private_key)
+		.map_err(|err| format!("Invalid TLS certificate or private key: {}", err))?;
+
+	Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Returns value of the `Authorization` header that is expected in every request.
+fn expected_authorization(auth: &MetricsAuth) -> String {
+	match *auth {
+		MetricsAuth::Basic {
+			ref username,
+			ref password,
+		} => format!("Basic {}", base64::encode(format!("{}:{}", username, password))),
+		MetricsAuth::Token(ref token) => format!("Bearer {}", token),
+	}
+}
+
+/// Read single request from the connection and send response back.
+async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin>(
+	mut stream: S,
+	registry: &Registry,
+	expected_authorization: Option<&str>,
+) -> Result<(), String> {
+	let mut request_head = Vec::new();
+	let mut buffer = [0u8; 1024];
+	while !request_head.windows(4).any(|window| window == b"\r\n\r\n") {
+		if request_head.len() > MAX_REQUEST_HEAD_SIZE {
+			break;
+		}
+
+		let read_bytes = stream.read(&mut buffer).await.map_err(|err| err.to_string())?;
+		if read_bytes == 0 {
+			break;
+		}
+		request_head.extend_from_slice(&buffer[..read_bytes]);
+	}
+
+	let (status, content_type, body) =
+		match process_request(&String::from_utf8_lossy(&request_head), expected_authorization) {
+			RequestOutcome::Metrics => {
+				let encoder = TextEncoder::new();
+				let mut body = Vec::new();
+				encoder
+					.encode(&registry.gather(), &mut body)
+					.map_err(|err| format!("Failed to encode metrics: {}", err))?;
+				("200 OK", encoder.format_type().to_owned(), body)
+			}
+			RequestOutcome::Unauthorized => ("401 Unauthorized", "text/plain".into(), b"Unauthorized".to_vec()),
+			RequestOutcome::NotFound => ("404 Not Found", "text/plain".into(), b"Not found".to_vec()),
+			RequestOutcome::BadRequest => ("400 Bad Request", "text/plain".into(), b"Bad request".to_vec()),
+		};
+
+	let mut response = format!(
+		"HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+		status,
+		content_type,
+		body.len(),
+	);
+	if status.starts_with("401") {
+		response.push_str("WWW-Authenticate: Basic realm=\"metrics\"\r\n");
+	}
+	response.push_str("\r\n");
+
+	stream
+		.write_all(response.as_bytes())
+		.await
+		.map_err(|err| err.to_string())?;
+	stream.write_all(&body).await.map_err(|err| err.to_string())?;
+	stream.flush().await.map_err(|err| err.to_string())?;
+	stream.close().await.map_err(|err| err.to_string())
+}
+
+/// Decide what to do with the request, given its head (request line and headers).
+fn process_request(request_head: &str, expected_authorization: Option<&str>) -> RequestOutcome {
+	let mut lines = request_head.lines();
+	let mut request_line = match lines.next() {
+		Some(request_line) => request_line.split_whitespace(),
+		None => return RequestOutcome::BadRequest,
+	};
+	let (method, path) = match (request_line.next(), request_line.next()) {
+		(Some(method), Some(path)) => (method, path),
+		_ => return RequestOutcome::BadRequest,
+	};
+
+	if let Some(expected_authorization) = expected_authorization {
+		let authorization = lines
+			.filter_map(|line| line.split_once(':'))
+			.find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+			.map(|(_, value)| value.trim());
+		if authorization != Some(expected_authorization) {
+			return RequestOutcome::Unauthorized;
+		}
+	}
+
+	if method != "GET" || path != "/metrics" {
+		return RequestOutcome::NotFound;
+	}
+
+	RequestOutcome::Metrics
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn expected_authorization_works() {
+		assert_eq!(
+			expected_authorization(&MetricsAuth::Basic {
+				username: "relay".into(),
+				password: "secret".into(),
+			}),
+			"Basic cmVsYXk6c2VjcmV0",
+		);
+		assert_eq!(
+			expected_authorization(&MetricsAuth::Token("token".into())),
+			"Bearer token"
+		);
+	}
+
+	#[test]
+	fn process_request_works() {
+		let request = "GET /metrics HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer token\r\n\r\n";
+		assert_eq!(process_request(request, None), RequestOutcome::Metrics);
+		assert_eq!(process_request(request, Some("Bearer token")), RequestOutcome::Metrics);
+		assert_eq!(
+			process_request(request, Some("Bearer other")),
+			RequestOutcome::Unauthorized
+		);
+		assert_eq!(
+			process_request("GET /metrics HTTP/1.1\r\n\r\n", Some("Bearer token")),
+			RequestOutcome::Unauthorized,
+		);
+		assert_eq!(
+			process_request("GET /other HTTP/1.1\r\n\r\n", None),
+			RequestOutcome::NotFound
+		);
+		assert_eq!(process_request("", None), RequestOutcome::BadRequest);
+	}
+}
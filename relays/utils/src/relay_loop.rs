@@ -14,7 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::metrics::{Metrics, MetricsAddress, MetricsParams, PrometheusError, StandaloneMetrics};
+use crate::metrics::{
+	spawn_push_task, Metrics, MetricsAddress, MetricsParams, PersistentCounters, PrometheusError, PushGatewayParams,
+	StandaloneMetrics,
+};
 use crate::{FailedClient, MaybeConnectionError};
 
 use async_trait::async_trait;
@@ -56,6 +59,8 @@ pub fn relay_metrics(prefix: Option<String>, params: MetricsParams) -> LoopMetri
 		address: params.address,
 		registry: params.registry.unwrap_or_else(|| create_metrics_registry(prefix)),
 		metrics_prefix: params.metrics_prefix,
+		push_gateway: params.push_gateway,
+		persistent_counters: params.persistent_counters,
 		loop_metric: None,
 	}
 }
@@ -74,6 +79,8 @@ pub struct LoopMetrics<SC, TC, LM> {
 	address: Option<MetricsAddress>,
 	registry: Registry,
 	metrics_prefix: Option<String>,
+	push_gateway: Option<PushGatewayParams>,
+	persistent_counters: PersistentCounters,
 	loop_metric: Option<LM>,
 }
 
@@ -96,6 +103,8 @@ impl<SC, TC, LM> Loop<SC, TC, LM> {
 			address: params.address,
 			registry: params.registry.unwrap_or_else(|| create_metrics_registry(prefix)),
 			metrics_prefix: params.metrics_prefix,
+			push_gateway: params.push_gateway,
+			persistent_counters: params.persistent_counters,
 			loop_metric: None,
 		}
 	}
@@ -159,6 +168,8 @@ impl<SC, TC, LM> LoopMetrics<SC, TC, LM> {
 			address: self.address,
 			registry: self.registry,
 			metrics_prefix: self.metrics_prefix,
+			push_gateway: self.push_gateway,
+			persistent_counters: self.persistent_counters,
 			loop_metric: Some(loop_metric),
 		})
 	}
@@ -185,13 +196,20 @@ impl<SC, TC, LM> LoopMetrics<SC, TC, LM> {
 			address: self.address,
 			registry: Some(self.registry),
 			metrics_prefix: self.metrics_prefix,
+			push_gateway: self.push_gateway,
+			persistent_counters: self.persistent_counters,
 		}
 	}
 
 	/// Expose metrics using address passed at creation.
 	///
-	/// If passed `address` is `None`, metrics are not exposed.
+	/// If passed `address` is `None`, metrics are not exposed. If push gateway parameters were passed
+	/// at creation, metrics are also periodically pushed to the gateway.
 	pub async fn expose(self) -> Result<Loop<SC, TC, LM>, String> {
+		if let Some(push_gateway) = self.push_gateway {
+			spawn_push_task(push_gateway, self.registry.clone());
+		}
+
 		if let Some(address) = self.address {
 			let socket_addr = SocketAddr::new(
 				address.host.parse().map_err(|err| {
@@ -205,7 +223,11 @@ impl<SC, TC, LM> LoopMetrics<SC, TC, LM> {
 
 			let registry = self.registry;
 			async_std::task::spawn(async move {
-				let result = init_prometheus(socket_addr, registry).await;
+				let result = if address.tls.is_some() || address.auth.is_some() {
+					crate::metrics::serve(socket_addr, registry, address.tls, address.auth).await
+				} else {
+					init_prometheus(socket_addr, registry).await.map_err(|e| e.to_string())
+				};
 				log::trace!(
 					target: "bridge-metrics",
 					"Prometheus endpoint has exited with result: {:?}",
@@ -0,0 +1,247 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Developer tool that reports sizes and estimated verification weights of bridge proofs.
+//!
+//! For given chain pair, it generates representative messages proof, messages delivery proof
+//! and finality proof and prints their encoded sizes and verification weights, computed using
+//! weight functions of the target chain runtime. Generated storage proofs are minimal - i.e. they
+//! only contain nodes that are required to prove inserted values. Proofs that are generated by the
+//! real chain are larger, because the real storage trie has more nodes.
+
+use bp_messages::{
+	InboundLaneData, LaneId, MessageData, MessageKey, MessageNonce, OutboundLaneData, UnrewardedRelayersState,
+};
+use bp_test_utils::{accounts, make_justification_for_header, test_header, JustificationGeneratorParams};
+use bridge_runtime_common::messages::{
+	source::FromBridgedChainMessagesDeliveryProof, target::FromBridgedChainMessagesProof,
+};
+use codec::Encode;
+use frame_support::weights::Weight;
+use pallet_bridge_grandpa::weights::WeightInfo as GrandpaWeightInfo;
+use pallet_bridge_messages::WeightInfoExt as MessagesWeightInfoExt;
+use sp_core::H256;
+use sp_runtime::traits::BlakeTwo256;
+use sp_trie::{record_all_keys, trie_types::TrieDBMut, Layout, MemoryDB, Recorder, TrieMut};
+use structopt::{clap::arg_enum, StructOpt};
+
+/// Lane that is used in all generated proofs.
+const LANE: LaneId = [0, 0, 0, 0];
+
+/// Report sizes and weights of bridge proofs.
+#[derive(StructOpt)]
+struct ProofSizeReport {
+	/// A bridge instance to generate proofs for.
+	#[structopt(possible_values = &Bridge::variants(), case_insensitive = true)]
+	bridge: Bridge,
+	/// Number of messages in the messages proof.
+	#[structopt(long, default_value = "1")]
+	messages: MessageNonce,
+	/// Size of every message payload (in bytes).
+	#[structopt(long, default_value = "128")]
+	message_size: usize,
+	/// Dispatch weight of every message.
+	#[structopt(long, default_value = "0")]
+	message_dispatch_weight: Weight,
+	/// Number of entries in the unrewarded relayers set that is proved by the messages delivery proof.
+	#[structopt(long, default_value = "1")]
+	unrewarded_relayers: MessageNonce,
+	/// Number of GRANDPA authorities that have signed the finality proof.
+	#[structopt(long, default_value = "8")]
+	authorities: u16,
+	/// Number of votes ancestries in the finality proof.
+	#[structopt(long, default_value = "2")]
+	votes_ancestries: u32,
+}
+
+arg_enum! {
+	#[derive(Debug)]
+	/// Bridge to generate proofs for.
+	enum Bridge {
+		MillauToRialto,
+		RialtoToMillau,
+	}
+}
+
+macro_rules! select_bridge {
+	($bridge: expr, $generic: tt) => {
+		match $bridge {
+			Bridge::MillauToRialto => {
+				type SourceRuntime = millau_runtime::Runtime;
+				type SourceMessagesInstance = millau_runtime::WithRialtoMessagesInstance;
+				type SourceHeader = bp_millau::Header;
+				type SourceBalance = bp_millau::Balance;
+				type SourceAccountId = bp_millau::AccountId;
+				type TargetRuntime = rialto_runtime::Runtime;
+				type TargetMessagesInstance = rialto_runtime::WithMillauMessagesInstance;
+				type TargetGrandpaInstance = rialto_runtime::MillauGrandpaInstance;
+
+				$generic
+			}
+			Bridge::RialtoToMillau => {
+				type SourceRuntime = rialto_runtime::Runtime;
+				type SourceMessagesInstance = rialto_runtime::WithMillauMessagesInstance;
+				type SourceHeader = bp_rialto::Header;
+				type SourceBalance = bp_rialto::Balance;
+				type SourceAccountId = bp_rialto::AccountId;
+				type TargetRuntime = millau_runtime::Runtime;
+				type TargetMessagesInstance = millau_runtime::WithRialtoMessagesInstance;
+				type TargetGrandpaInstance = millau_runtime::RialtoGrandpaInstance;
+
+				$generic
+			}
+		}
+	};
+}
+
+fn main() {
+	let params = ProofSizeReport::from_args();
+	select_bridge!(params.bridge, {
+		type TargetMessagesWeight =
+			<TargetRuntime as pallet_bridge_messages::Config<TargetMessagesInstance>>::WeightInfo;
+		type SourceMessagesWeight =
+			<SourceRuntime as pallet_bridge_messages::Config<SourceMessagesInstance>>::WeightInfo;
+		type TargetGrandpaWeight = <TargetRuntime as pallet_bridge_grandpa::Config<TargetGrandpaInstance>>::WeightInfo;
+
+		// messages proof is generated at the source chain and verified at the target chain
+		let mut storage = (1..=params.messages)
+			.map(|nonce| {
+				let message_key = MessageKey { lane_id: LANE, nonce };
+				let message_data = MessageData {
+					payload: vec![42u8; params.message_size],
+					fee: SourceBalance::from(0u32),
+				};
+				(
+					pallet_bridge_messages::storage_keys::message_key::<SourceRuntime, SourceMessagesInstance>(
+						&message_key.lane_id,
+						message_key.nonce,
+					)
+					.0,
+					message_data.encode(),
+				)
+			})
+			.collect::<Vec<_>>();
+		storage.push((
+			pallet_bridge_messages::storage_keys::outbound_lane_data_key::<SourceMessagesInstance>(&LANE).0,
+			OutboundLaneData {
+				oldest_unpruned_nonce: 1,
+				latest_received_nonce: 0,
+				latest_generated_nonce: params.messages,
+				..Default::default()
+			}
+			.encode(),
+		));
+		let storage_proof = prove_storage(storage);
+		let messages_proof = FromBridgedChainMessagesProof {
+			bridged_header_hash: H256::zero(),
+			storage_proof,
+			lane: LANE,
+			nonces_start: 1,
+			nonces_end: params.messages,
+		};
+		report(
+			&format!("Proof of {} messages of {} bytes", params.messages, params.message_size),
+			messages_proof.encode().len(),
+			TargetMessagesWeight::receive_messages_proof_weight(
+				&messages_proof,
+				params.messages as _,
+				params.message_dispatch_weight.saturating_mul(params.messages),
+			),
+		);
+
+		// messages delivery proof is generated at the target chain and verified at the source chain
+		let inbound_lane_data = InboundLaneData {
+			relayers: (1..=params.unrewarded_relayers)
+				.map(|nonce| (nonce, nonce, SourceAccountId::default()))
+				.collect(),
+			last_confirmed_nonce: 0,
+			..Default::default()
+		};
+		let storage_proof = prove_storage(vec![(
+			pallet_bridge_messages::storage_keys::inbound_lane_data_key::<TargetRuntime, TargetMessagesInstance>(&LANE)
+				.0,
+			inbound_lane_data.encode(),
+		)]);
+		let messages_delivery_proof = FromBridgedChainMessagesDeliveryProof {
+			bridged_header_hash: H256::zero(),
+			storage_proof,
+			lane: LANE,
+		};
+		report(
+			&format!(
+				"Proof of messages delivery with {} unrewarded relayers",
+				params.unrewarded_relayers
+			),
+			messages_delivery_proof.encode().len(),
+			SourceMessagesWeight::receive_messages_delivery_proof_weight(
+				&messages_delivery_proof,
+				&UnrewardedRelayersState {
+					unrewarded_relayer_entries: params.unrewarded_relayers,
+					messages_in_oldest_entry: 1,
+					total_messages: params.unrewarded_relayers,
+				},
+			),
+		);
+
+		// finality proof is generated at the source chain and verified at the target chain
+		let header = test_header::<SourceHeader>(1);
+		let justification = make_justification_for_header(JustificationGeneratorParams {
+			header: header.clone(),
+			authorities: accounts(params.authorities)
+				.into_iter()
+				.map(|account| (account, 1))
+				.collect(),
+			ancestors: params.votes_ancestries,
+			..Default::default()
+		});
+		report(
+			&format!(
+				"Finality proof with {} precommits and {} votes ancestries",
+				justification.commit.precommits.len(),
+				justification.votes_ancestries.len(),
+			),
+			(header, &justification).encode().len(),
+			TargetGrandpaWeight::submit_finality_proof(
+				justification.commit.precommits.len() as _,
+				justification.votes_ancestries.len() as _,
+			),
+		);
+	});
+}
+
+/// Build storage trie with given key-value pairs and return proof of all its values.
+fn prove_storage(storage: Vec<(Vec<u8>, Vec<u8>)>) -> Vec<Vec<u8>> {
+	let mut root = H256::default();
+	let mut mdb = MemoryDB::default();
+	{
+		let mut trie = TrieDBMut::<BlakeTwo256>::new(&mut mdb, &mut root);
+		for (key, value) in storage {
+			trie.insert(&key, &value).expect("TrieMut::insert should not fail");
+		}
+	}
+
+	let mut proof_recorder = Recorder::<H256>::new();
+	record_all_keys::<Layout<BlakeTwo256>, _>(&mdb, &root, &mut proof_recorder)
+		.expect("record_all_keys should not fail");
+	proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect()
+}
+
+/// Print proof size and weight.
+fn report(proof_name: &str, encoded_size: usize, weight: Weight) {
+	println!("{}:", proof_name);
+	println!("\tencoded size: {} bytes", encoded_size);
+	println!("\tverification weight: {}", weight);
+}